@@ -0,0 +1,179 @@
+//! Counts the allocations of rendering a 1k-row table, with the text of its
+//! cells owned by the nodes and in a `FrameLoop`, and times both. Every 10th
+//! row changes between renders. Building the trees is measured on its own as
+//! well, as diffing them allocates the same either way.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
+use vdom::{
+    driver::DriverCtx,
+    html::HtmlDriver,
+    vdom::{
+        arena::{AttrRef, Frame, FrameLoop, FrameView, TextRef},
+        attr::{AttrDyn, AttrListEntry},
+        node::{TagStatic, TextDyn},
+        patch,
+    },
+};
+
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+const ROWS: usize = 1_000;
+const RENDERS: usize = 100;
+
+struct Rows {
+    labels: Vec<String>,
+}
+
+impl Rows {
+    fn new() -> Rows {
+        Rows {
+            labels: (0..ROWS).map(|i| format!("row {}", i)).collect(),
+        }
+    }
+
+    fn update(&mut self) {
+        for label in self.labels.iter_mut().step_by(10) {
+            if label.ends_with(" !") {
+                label.truncate(label.len() - 2);
+            } else {
+                label.push_str(" !");
+            }
+        }
+    }
+}
+
+type OwnedRow = TagStatic<
+    HtmlDriver,
+    (
+        TagStatic<HtmlDriver, TextDyn<HtmlDriver>, ()>,
+        TagStatic<HtmlDriver, TextDyn<HtmlDriver>, ()>,
+    ),
+    AttrListEntry<AttrDyn<HtmlDriver>>,
+>;
+
+fn owned_table(rows: &Rows) -> TagStatic<HtmlDriver, Vec<OwnedRow>, ()> {
+    let rows = rows
+        .labels
+        .iter()
+        .enumerate()
+        .map(|(id, label)| {
+            TagStatic::new(
+                "tr",
+                AttrListEntry(AttrDyn::new("data-id", id.to_string())),
+                (
+                    TagStatic::new("td", (), TextDyn::new(id.to_string())),
+                    TagStatic::new("td", (), TextDyn::new(label.clone())),
+                ),
+            )
+        })
+        .collect();
+    TagStatic::new("table", (), rows)
+}
+
+type FrameRow<'a> = TagStatic<
+    HtmlDriver,
+    (
+        TagStatic<HtmlDriver, TextRef<'a, HtmlDriver>, ()>,
+        TagStatic<HtmlDriver, TextRef<'a, HtmlDriver>, ()>,
+    ),
+    AttrListEntry<AttrRef<'a, HtmlDriver>>,
+>;
+
+impl<'a> FrameView<'a, HtmlDriver> for Rows {
+    type Node = TagStatic<HtmlDriver, Vec<FrameRow<'a>>, ()>;
+
+    fn view(&mut self, frame: &'a Frame) -> Self::Node {
+        let rows = self
+            .labels
+            .iter()
+            .enumerate()
+            .map(|(id, label)| {
+                TagStatic::new(
+                    "tr",
+                    AttrListEntry(frame.attr("data-id", &id)),
+                    (
+                        TagStatic::new("td", (), frame.text(&id)),
+                        TagStatic::new("td", (), frame.text(label)),
+                    ),
+                )
+            })
+            .collect();
+        TagStatic::new("table", (), rows)
+    }
+}
+
+fn measure<F>(name: &str, mut render: F)
+where
+    F: FnMut(),
+{
+    // The first renders size the frames and the allocator's caches.
+    render();
+    render();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    for _ in 0..RENDERS {
+        render();
+    }
+    let elapsed = start.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+    println!(
+        "{}: {} allocations, {:?} per render",
+        name,
+        allocations / RENDERS,
+        elapsed / RENDERS as u32
+    );
+}
+
+fn main() {
+    let driver_ctx = DriverCtx::new(HtmlDriver);
+
+    let mut rows = Rows::new();
+    measure("owned build", || {
+        owned_table(&rows);
+    });
+    let mut frame = Frame::new();
+    measure("frame build", || {
+        frame.reset();
+        rows.view(&frame);
+    });
+
+    let mut ancestor = owned_table(&rows);
+    measure("owned", || {
+        rows.update();
+        let mut curr = owned_table(&rows);
+        patch::diff(&driver_ctx, &mut curr, &mut ancestor);
+        ancestor = curr;
+    });
+
+    let mut frames = FrameLoop::new(Rows::new());
+    frames.render(&driver_ctx);
+    measure("frame", || {
+        frames.view().update();
+        frames.render(&driver_ctx);
+    });
+}
@@ -0,0 +1,78 @@
+//! Times rendering a mostly static page, a 1k-row table under a heading
+//! with a counter that changes between renders, with `render_to_string` and
+//! with an `IncrementalRenderer`.
+
+use std::time::Instant;
+use vdom::{
+    html::{render_to_string, HtmlDriver, IncrementalRenderer},
+    vdom::node::{TagStatic, TextDyn, TextStatic},
+};
+
+const ROWS: usize = 1_000;
+const RENDERS: u32 = 100;
+
+type Row = TagStatic<
+    HtmlDriver,
+    (
+        TagStatic<HtmlDriver, TextStatic<HtmlDriver>, ()>,
+        TagStatic<HtmlDriver, TextDyn<HtmlDriver>, ()>,
+    ),
+    (),
+>;
+
+type Page = TagStatic<
+    HtmlDriver,
+    (
+        TagStatic<HtmlDriver, TextDyn<HtmlDriver>, ()>,
+        TagStatic<HtmlDriver, Vec<Row>, ()>,
+    ),
+    (),
+>;
+
+fn page(visits: usize) -> Page {
+    let rows = (0..ROWS)
+        .map(|row| {
+            TagStatic::new(
+                "tr",
+                (),
+                (
+                    TagStatic::new("td", (), TextStatic::new("row")),
+                    TagStatic::new("td", (), TextDyn::new(row.to_string())),
+                ),
+            )
+        })
+        .collect();
+    TagStatic::new(
+        "body",
+        (),
+        (
+            TagStatic::new("h1", (), TextDyn::new(format!("{} visits", visits))),
+            TagStatic::new("table", (), rows),
+        ),
+    )
+}
+
+fn measure<F>(name: &str, mut render: F)
+where
+    F: FnMut(usize),
+{
+    render(0);
+    let start = Instant::now();
+    for visits in 1..=RENDERS as usize {
+        render(visits);
+    }
+    println!("{}: {:?} per render", name, start.elapsed() / RENDERS);
+}
+
+fn main() {
+    let mut renderer = IncrementalRenderer::new();
+    assert_eq!(renderer.render(page(0)), render_to_string(&mut page(0)));
+    assert_eq!(renderer.render(page(1)), render_to_string(&mut page(1)));
+
+    measure("from scratch", |visits| {
+        render_to_string(&mut page(visits));
+    });
+    measure("incremental", |visits| {
+        renderer.render(page(visits));
+    });
+}
@@ -0,0 +1,74 @@
+//! Times rendering a wide page, 64 sections of 500 rows each, with
+//! `render_to_string` and with `render_to_string_parallel`.
+
+use std::time::Instant;
+use vdom::{
+    html::{render_to_string, render_to_string_parallel, HtmlDriver},
+    vdom::node::{TagStatic, TextDyn},
+};
+
+const SECTIONS: usize = 64;
+const ROWS: usize = 500;
+const RENDERS: u32 = 20;
+
+type Row = TagStatic<
+    HtmlDriver,
+    (
+        TagStatic<HtmlDriver, TextDyn<HtmlDriver>, ()>,
+        TagStatic<HtmlDriver, TextDyn<HtmlDriver>, ()>,
+    ),
+    (),
+>;
+
+type Page = TagStatic<HtmlDriver, Vec<TagStatic<HtmlDriver, Vec<Row>, ()>>, ()>;
+
+fn page() -> Page {
+    let sections = (0..SECTIONS)
+        .map(|section| {
+            let rows = (0..ROWS)
+                .map(|row| {
+                    TagStatic::new(
+                        "tr",
+                        (),
+                        (
+                            TagStatic::new("td", (), TextDyn::new(row.to_string())),
+                            TagStatic::new(
+                                "td",
+                                (),
+                                TextDyn::new(format!("section {} & row {}", section, row)),
+                            ),
+                        ),
+                    )
+                })
+                .collect();
+            TagStatic::new("table", (), rows)
+        })
+        .collect();
+    TagStatic::new("body", (), sections)
+}
+
+fn measure<F>(name: &str, mut render: F)
+where
+    F: FnMut() -> String,
+{
+    render();
+    let start = Instant::now();
+    for _ in 0..RENDERS {
+        render();
+    }
+    println!("{}: {:?} per render", name, start.elapsed() / RENDERS);
+}
+
+fn main() {
+    let mut page = page();
+    assert_eq!(
+        render_to_string_parallel(&mut page, 1_000),
+        render_to_string(&mut page)
+    );
+    measure("sequential", || render_to_string(&mut page));
+    for &min_subtree_size in &[100, 1_000, 10_000] {
+        measure(&format!("parallel, {}", min_subtree_size), || {
+            render_to_string_parallel(&mut page, min_subtree_size)
+        });
+    }
+}
@@ -0,0 +1,107 @@
+//! Counts the allocations of rendering a 1k-row table whose rows are dynamic
+//! lists of two or three cells, with the cells in a `Vec` and in a
+//! `SmallVec`, and times both. Half the rows change their length between
+//! renders.
+
+use smallvec::SmallVec;
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    iter::FromIterator,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
+use vdom::{
+    driver::DriverCtx,
+    html::HtmlDriver,
+    vdom::{
+        node::{Node, TagStatic, TextStatic},
+        patch,
+    },
+};
+
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+const ROWS: usize = 1_000;
+const RENDERS: usize = 100;
+
+type Cell = TagStatic<HtmlDriver, TextStatic<HtmlDriver>, ()>;
+
+type Table<L> = TagStatic<HtmlDriver, Vec<TagStatic<HtmlDriver, L, ()>>, ()>;
+
+fn table<L>(render: usize) -> Table<L>
+where
+    L: FromIterator<Cell> + Node<HtmlDriver>,
+{
+    let rows = (0..ROWS)
+        .map(|row| {
+            let cells = ["a", "b", "c"][..2 + (row + render) % 2]
+                .iter()
+                .map(|&text| TagStatic::new("td", (), TextStatic::new(text)))
+                .collect();
+            TagStatic::new("tr", (), cells)
+        })
+        .collect();
+    TagStatic::new("table", (), rows)
+}
+
+fn measure<F>(name: &str, mut render: F)
+where
+    F: FnMut(usize),
+{
+    render(0);
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    for i in 1..=RENDERS {
+        render(i);
+    }
+    let elapsed = start.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+    println!(
+        "{}: {} allocations, {:?} per render",
+        name,
+        allocations / RENDERS,
+        elapsed / RENDERS as u32
+    );
+}
+
+fn measure_lists<L>(name: &str)
+where
+    L: FromIterator<Cell> + Node<HtmlDriver>,
+{
+    let driver_ctx = DriverCtx::new(HtmlDriver);
+    measure(&format!("{} build", name), |render| {
+        table::<L>(render);
+    });
+    let mut ancestor = table::<L>(0);
+    measure(name, |render| {
+        let mut curr = table::<L>(render);
+        patch::diff(&driver_ctx, &mut curr, &mut ancestor);
+        ancestor = curr;
+    });
+}
+
+fn main() {
+    measure_lists::<Vec<Cell>>("Vec");
+    measure_lists::<SmallVec<[Cell; 4]>>("SmallVec");
+}
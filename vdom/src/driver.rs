@@ -6,6 +6,7 @@ pub trait Driver /*: LocalSpawn */ {
     type TagStore;
     type TextStore;
     type CompStore;
+    type Event;
 
     fn new_attr_store() -> Self::AttrStore;
     fn new_tag_store() -> Self::TagStore;
@@ -0,0 +1,4 @@
+//! Events that happen outside of the driver, e.g. in a browser showing HTML
+//! rendered on a server.
+
+pub mod wire;
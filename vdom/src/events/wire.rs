@@ -0,0 +1,584 @@
+//! A protocol for server-driven UIs: the client reports an event on the node
+//! at a `Path` of the tree it was sent, and the server calls the listener the
+//! tree has at that path.
+//!
+//! Each tree sent is a frame, numbered by a `FrameId` that events name, so an
+//! event referring to an older tree than the current one can be told apart
+//! and handled according to a `StaleEventPolicy`.
+
+#[cfg(feature = "serde_json")]
+use crate::vdom::{
+    json::{json_patch, JsonError},
+    patch::Patch,
+    path::PathIndex,
+};
+use crate::{
+    driver::{Driver, DriverCtx},
+    vdom::{
+        attr::{Attr, AttrVisitor},
+        node::{Comp, CompNode, Node, NodeVisitor, Tag, Text},
+        path::Path,
+    },
+};
+#[cfg(feature = "serde_json")]
+use serde_json::{Map, Value};
+#[cfg(feature = "serde_json")]
+use std::convert::TryFrom;
+use std::{
+    cell::RefCell,
+    collections::{HashMap, VecDeque},
+    convert::Infallible,
+    mem,
+    rc::Rc,
+};
+
+/// The number of a rendered tree, which increases with each render.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct FrameId(pub u64);
+
+impl FrameId {
+    pub fn next(self) -> FrameId {
+        FrameId(self.0 + 1)
+    }
+}
+
+/// An event of type `kind`, e.g. `click`, that fired on the element at
+/// `path` in the tree of `frame`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RemoteEvent {
+    pub frame: FrameId,
+    pub path: Path,
+    pub kind: String,
+    pub payload: EventPayload,
+}
+
+/// The data of an event the client sends along, where it applies.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct EventPayload {
+    /// The value of the form control the event fired on.
+    pub value: Option<String>,
+    /// Whether the checkbox or radio button the event fired on is checked.
+    pub checked: Option<bool>,
+    /// The key pressed, as in `KeyboardEvent.key`.
+    pub key: Option<String>,
+    /// The pointer's coordinates within the viewport.
+    pub coords: Option<(i32, i32)>,
+}
+
+/// For drivers whose events carry no data, such as `HtmlDriver`.
+impl From<EventPayload> for () {
+    fn from(_payload: EventPayload) {}
+}
+
+#[cfg(feature = "serde_json")]
+impl RemoteEvent {
+    /// The event as `{"frame": .., "path": [..], "kind": .., "payload": {..}}`,
+    /// where the payload leaves out what isn't set and has `coords` as
+    /// `[x, y]`.
+    pub fn to_json(&self) -> Value {
+        let path = self
+            .path
+            .indices()
+            .iter()
+            .map(|index| Value::Number((*index as u64).into()))
+            .collect();
+        let mut payload = Map::new();
+        if let Some(value) = &self.payload.value {
+            payload.insert("value".to_string(), Value::String(value.clone()));
+        }
+        if let Some(checked) = self.payload.checked {
+            payload.insert("checked".to_string(), Value::Bool(checked));
+        }
+        if let Some(key) = &self.payload.key {
+            payload.insert("key".to_string(), Value::String(key.clone()));
+        }
+        if let Some((x, y)) = self.payload.coords {
+            let coords = vec![Value::Number(x.into()), Value::Number(y.into())];
+            payload.insert("coords".to_string(), Value::Array(coords));
+        }
+
+        let mut event = Map::new();
+        event.insert("frame".to_string(), Value::Number(self.frame.0.into()));
+        event.insert("path".to_string(), Value::Array(path));
+        event.insert("kind".to_string(), Value::String(self.kind.clone()));
+        event.insert("payload".to_string(), Value::Object(payload));
+        Value::Object(event)
+    }
+
+    /// Reads an event from JSON as produced by `to_json`. The payload may be
+    /// left out if empty, and may have a `token`, see `token_from_json`.
+    pub fn from_json(value: &Value) -> Result<RemoteEvent, JsonError> {
+        let event = as_object(value, "")?;
+        check_members(event, "", &["frame", "path", "kind", "payload"])?;
+
+        let frame = event
+            .get("frame")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| invalid_type("/frame", "unsigned integer"))?;
+        let path = match event.get("path") {
+            Some(Value::Array(indices)) => indices,
+            _ => return Err(invalid_type("/path", "array")),
+        };
+        let path = path
+            .iter()
+            .enumerate()
+            .map(|(i, index)| {
+                index
+                    .as_u64()
+                    .filter(|index| *index <= u64::from(PathIndex::MAX))
+                    .map(|index| index as usize)
+                    .ok_or_else(|| invalid_type(&format!("/path/{}", i), "32-bit unsigned integer"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let kind = match event.get("kind") {
+            Some(Value::String(kind)) => kind.clone(),
+            _ => return Err(invalid_type("/kind", "string")),
+        };
+
+        let mut payload = EventPayload::default();
+        if let Some(value) = event.get("payload") {
+            let object = as_object(value, "/payload")?;
+            check_members(
+                object,
+                "/payload",
+                &["value", "checked", "key", "coords", "token"],
+            )?;
+            for (name, value) in object {
+                let pointer = format!("/payload/{}", name);
+                match (name.as_str(), value) {
+                    ("value", Value::String(value)) => payload.value = Some(value.clone()),
+                    ("checked", Value::Bool(checked)) => payload.checked = Some(*checked),
+                    ("key", Value::String(key)) => payload.key = Some(key.clone()),
+                    ("token", token) if token.as_u64().is_some() => {}
+                    ("coords", Value::Array(coords)) => {
+                        let coord = |i: usize| {
+                            coords
+                                .get(i)
+                                .and_then(Value::as_i64)
+                                .and_then(|coord| i32::try_from(coord).ok())
+                        };
+                        match (coord(0), coord(1), coords.len()) {
+                            (Some(x), Some(y), 2) => payload.coords = Some((x, y)),
+                            _ => {
+                                return Err(invalid_type(&pointer, "array of 2 32-bit integers"));
+                            }
+                        }
+                    }
+                    ("value", _) | ("key", _) => return Err(invalid_type(&pointer, "string")),
+                    ("checked", _) => return Err(invalid_type(&pointer, "boolean")),
+                    ("token", _) => return Err(invalid_type(&pointer, "unsigned integer")),
+                    _ => return Err(invalid_type(&pointer, "array of 2 32-bit integers")),
+                }
+            }
+        }
+
+        Ok(RemoteEvent {
+            frame: FrameId(frame),
+            path: path.into(),
+            kind,
+            payload,
+        })
+    }
+
+    /// The `token` in the payload of an event read with `from_json`, a
+    /// number the client increases with each change to the value of a form
+    /// control, so events carrying a value can be ordered, see
+    /// `HandlerRegistry::dispatch_with_token`.
+    pub fn token_from_json(value: &Value) -> Option<u64> {
+        match value {
+            Value::Object(event) => {
+                match event.get("payload")? {
+                    Value::Object(payload) => payload.get("token")?.as_u64(),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+/// `patches` that turn the tree of the previous frame into that of `frame`,
+/// as `{"frame": .., "patch": [..]}` with a JSON Patch, see `json_patch`.
+/// Clients send the frame along with their events.
+#[cfg(feature = "serde_json")]
+pub fn frame_to_json<D>(frame: FrameId, patches: &[Patch<D>]) -> Value
+where
+    D: Driver,
+{
+    let mut object = Map::new();
+    object.insert("frame".to_string(), Value::Number(frame.0.into()));
+    object.insert("patch".to_string(), json_patch(patches));
+    Value::Object(object)
+}
+
+#[cfg(feature = "serde_json")]
+fn as_object<'a>(value: &'a Value, pointer: &str) -> Result<&'a Map<String, Value>, JsonError> {
+    match value {
+        Value::Object(object) => Ok(object),
+        _ => Err(invalid_type(pointer, "object")),
+    }
+}
+
+#[cfg(feature = "serde_json")]
+fn check_members(
+    object: &Map<String, Value>,
+    pointer: &str,
+    members: &[&str],
+) -> Result<(), JsonError> {
+    match object.keys().find(|name| !members.contains(&name.as_str())) {
+        Some(name) => {
+            Err(JsonError::UnknownMember {
+                pointer: pointer.to_string(),
+                name: name.clone(),
+            })
+        }
+        None => Ok(()),
+    }
+}
+
+#[cfg(feature = "serde_json")]
+fn invalid_type(pointer: &str, expected: &'static str) -> JsonError {
+    JsonError::InvalidType {
+        pointer: pointer.to_string(),
+        expected,
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DispatchError {
+    /// The tree has no element at the path, e.g. because it changed since
+    /// the client was sent the tree the event happened in.
+    NoElement(Path),
+    /// The element at `path` has no listener for `kind`, which may also be
+    /// because the tree changed.
+    NoListener { path: Path, kind: String },
+    /// The event is from the earlier frame `frame`, and the policy rejected
+    /// it.
+    Stale { frame: FrameId, current: FrameId },
+    /// The event is from a frame too old to be remembered, or from one that
+    /// hasn't been rendered yet.
+    UnknownFrame(FrameId),
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StaleAction {
+    /// Ignores the event.
+    Drop,
+    /// Fails with `DispatchError::Stale`.
+    Reject,
+    /// Dispatches the event to the listener at its path in the current tree.
+    Dispatch,
+}
+
+/// Decides what to do with events from an earlier frame than the current
+/// one, whose paths may address other elements by now.
+pub trait StaleEventPolicy {
+    /// The action for `event`, whose path addressed an element with the tag
+    /// `then` in its frame, and addresses one with the tag `now` in the
+    /// current frame. Elements that aren't in either tree, but have a
+    /// listener registered with `HandlerRegistry::register`, have no tag.
+    fn on_stale(&self, event: &RemoteEvent, then: Option<&str>, now: Option<&str>) -> StaleAction;
+}
+
+/// Ignores stale events.
+pub struct DropStale;
+
+impl StaleEventPolicy for DropStale {
+    fn on_stale(
+        &self,
+        _event: &RemoteEvent,
+        _then: Option<&str>,
+        _now: Option<&str>,
+    ) -> StaleAction {
+        StaleAction::Drop
+    }
+}
+
+/// Fails on stale events.
+pub struct RejectStale;
+
+impl StaleEventPolicy for RejectStale {
+    fn on_stale(
+        &self,
+        _event: &RemoteEvent,
+        _then: Option<&str>,
+        _now: Option<&str>,
+    ) -> StaleAction {
+        StaleAction::Reject
+    }
+}
+
+/// Dispatches stale events if their path still addresses an element with the
+/// same tag, on the assumption that it's the same element, and ignores them
+/// otherwise.
+pub struct RemapStale;
+
+impl StaleEventPolicy for RemapStale {
+    fn on_stale(&self, _event: &RemoteEvent, then: Option<&str>, now: Option<&str>) -> StaleAction {
+        match (then, now) {
+            (Some(then), Some(now)) if then == now => StaleAction::Dispatch,
+            _ => StaleAction::Drop,
+        }
+    }
+}
+
+/// The number of frames before the current one whose elements are kept to
+/// handle stale events.
+const FRAME_HISTORY: usize = 8;
+
+/// The listeners of a rendered tree by the path of their element and their
+/// event type, to dispatch `RemoteEvent`s to. Collect them again after each
+/// render, which starts a new frame.
+pub struct HandlerRegistry<D>
+where
+    D: Driver,
+{
+    frame: FrameId,
+    /// The tags of the elements of the current frame by path, or `None` for
+    /// those that only have listeners registered with `register`.
+    elements: HashMap<Path, Option<String>>,
+    /// The elements of the frames before, most recent first.
+    history: VecDeque<(FrameId, HashMap<Path, Option<String>>)>,
+    handlers: HashMap<(Path, String), Handler<D>>,
+    /// The latest token dispatched by path, see `dispatch_with_token`.
+    tokens: RefCell<HashMap<Path, u64>>,
+}
+
+type Handler<D> = Rc<dyn Fn(<D as Driver>::Event)>;
+
+impl<D> HandlerRegistry<D>
+where
+    D: Driver,
+{
+    pub fn new() -> HandlerRegistry<D> {
+        HandlerRegistry {
+            frame: FrameId::default(),
+            elements: HashMap::new(),
+            history: VecDeque::new(),
+            handlers: HashMap::new(),
+            tokens: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// The frame of the tree last collected.
+    pub fn frame(&self) -> FrameId {
+        self.frame
+    }
+
+    /// Replaces the listeners with those of the tree rendered from `root`
+    /// and returns its frame, to send to the client along with the tree.
+    /// Components are rendered using `driver_ctx`.
+    pub fn collect<N>(&mut self, driver_ctx: &DriverCtx<D>, root: &mut N) -> FrameId
+    where
+        N: Node<D> + ?Sized,
+    {
+        let elements = mem::take(&mut self.elements);
+        self.history.push_front((self.frame, elements));
+        self.history.truncate(FRAME_HISTORY);
+        self.frame = self.frame.next();
+        self.handlers.clear();
+        let mut collector = ListenerCollector {
+            driver_ctx,
+            registry: self,
+            path: Path::root(),
+        };
+        match root.visit(&mut 0, &mut collector) {
+            Ok(()) => {}
+            Err(never) => match never {},
+        }
+        // The tokens of the elements that are gone would only grow the map,
+        // and be compared to those of the elements taking their place.
+        let elements = &self.elements;
+        self.tokens
+            .get_mut()
+            .retain(|path, _| elements.contains_key(path));
+        self.frame
+    }
+
+    /// Registers `handler` for events of type `kind` on the element at
+    /// `path`, in addition to the listeners of the tree. An element that
+    /// isn't in the tree has no tag, so stale events are never remapped to
+    /// it.
+    pub fn register<K>(&mut self, path: Path, kind: K, handler: Rc<dyn Fn(D::Event)>)
+    where
+        K: Into<String>,
+    {
+        self.elements.entry(path.clone()).or_insert(None);
+        self.handlers.insert((path, kind.into()), handler);
+    }
+
+    /// The latest token of an event dispatched to the element at `path`
+    /// with `dispatch_with_token`.
+    pub fn value_token(&self, path: &Path) -> Option<u64> {
+        self.tokens.borrow().get(path).copied()
+    }
+
+    /// Calls the listener for `event`, with the event converted from its
+    /// payload, and returns whether it did. Events from an earlier frame are
+    /// handled according to `policy`.
+    pub fn dispatch<P>(&self, event: RemoteEvent, policy: &P) -> Result<bool, DispatchError>
+    where
+        D::Event: From<EventPayload>,
+        P: StaleEventPolicy,
+    {
+        self.dispatch_token(event, None, policy)
+    }
+
+    /// Like `dispatch`, for an event carrying the value of a form control
+    /// with the `token` the client sent along, see `token_from_json`.
+    ///
+    /// An event whose token is lower than that of an event dispatched to the
+    /// same path before carries an older value, e.g. as it was delayed on the
+    /// way, and is dropped, so the last value the client wrote wins and the
+    /// tree isn't rendered with an outdated one. Tokens are forgotten with
+    /// their elements when the tree is collected again.
+    pub fn dispatch_with_token<P>(
+        &self,
+        event: RemoteEvent,
+        token: u64,
+        policy: &P,
+    ) -> Result<bool, DispatchError>
+    where
+        D::Event: From<EventPayload>,
+        P: StaleEventPolicy,
+    {
+        self.dispatch_token(event, Some(token), policy)
+    }
+
+    fn dispatch_token<P>(
+        &self,
+        event: RemoteEvent,
+        token: Option<u64>,
+        policy: &P,
+    ) -> Result<bool, DispatchError>
+    where
+        D::Event: From<EventPayload>,
+        P: StaleEventPolicy,
+    {
+        if event.frame != self.frame {
+            let elements = self
+                .history
+                .iter()
+                .find(|(frame, _)| *frame == event.frame)
+                .map(|(_, elements)| elements)
+                .ok_or(DispatchError::UnknownFrame(event.frame))?;
+            let then = match elements.get(&event.path) {
+                Some(then) => then.as_deref(),
+                None => return Err(DispatchError::NoElement(event.path)),
+            };
+            let now = self.elements.get(&event.path).and_then(Option::as_deref);
+            match policy.on_stale(&event, then, now) {
+                StaleAction::Drop => return Ok(false),
+                StaleAction::Reject => {
+                    return Err(DispatchError::Stale {
+                        frame: event.frame,
+                        current: self.frame,
+                    });
+                }
+                StaleAction::Dispatch => {}
+            }
+        }
+
+        if !self.elements.contains_key(&event.path) {
+            return Err(DispatchError::NoElement(event.path));
+        }
+        if let (Some(token), Some(latest)) = (token, self.value_token(&event.path)) {
+            if token < latest {
+                return Ok(false);
+            }
+        }
+        let key = (event.path, event.kind);
+        match self.handlers.get(&key) {
+            Some(handler) => {
+                if let Some(token) = token {
+                    self.tokens.borrow_mut().insert(key.0.clone(), token);
+                }
+                handler(event.payload.into());
+                Ok(true)
+            }
+            None => {
+                let (path, kind) = key;
+                Err(DispatchError::NoListener { path, kind })
+            }
+        }
+    }
+}
+
+impl<D> Default for HandlerRegistry<D>
+where
+    D: Driver,
+{
+    fn default() -> HandlerRegistry<D> {
+        HandlerRegistry::new()
+    }
+}
+
+struct ListenerCollector<'a, D>
+where
+    D: Driver,
+{
+    driver_ctx: &'a DriverCtx<D>,
+    registry: &'a mut HandlerRegistry<D>,
+    path: Path,
+}
+
+impl<'a, D> NodeVisitor<D> for ListenerCollector<'a, D>
+where
+    D: Driver,
+{
+    type Err = Infallible;
+
+    fn on_tag<T>(&mut self, index: usize, tag: &mut T) -> Result<(), Infallible>
+    where
+        T: Tag<D>,
+    {
+        self.path.push(index);
+        let tag_name = tag.tag().to_string();
+        self.registry
+            .elements
+            .insert(self.path.clone(), Some(tag_name));
+        tag.visit_attrs(self)?;
+        tag.visit_children(self)?;
+        self.path.pop();
+        Ok(())
+    }
+
+    fn on_text<T>(&mut self, _index: usize, _text: &mut T) -> Result<(), Infallible>
+    where
+        T: Text<D>,
+    {
+        Ok(())
+    }
+
+    fn on_comp<C>(&mut self, index: &mut usize, comp: &mut CompNode<D, C>) -> Result<(), Infallible>
+    where
+        C: Comp<D>,
+    {
+        if comp.comp_ctx().is_none() {
+            comp.init_comp_ctx(self.driver_ctx.clone());
+        }
+        comp.visit_rendered(index, self)
+    }
+
+    fn driver_ctx(&self) -> Option<DriverCtx<D>> {
+        Some(self.driver_ctx.clone())
+    }
+}
+
+impl<'a, D> AttrVisitor<D> for ListenerCollector<'a, D>
+where
+    D: Driver,
+{
+    type Err = Infallible;
+
+    fn on_attr<A>(&mut self, attr: &mut A) -> Result<(), Infallible>
+    where
+        A: Attr<D>,
+    {
+        if let Some(listener) = attr.listener() {
+            let key = (self.path.clone(), attr.name().to_string());
+            self.registry.handlers.insert(key, listener.clone());
+        }
+        Ok(())
+    }
+}
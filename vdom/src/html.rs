@@ -0,0 +1,1677 @@
+use crate::{
+    driver::{Driver, DriverCtx},
+    vdom::{
+        attr::{Attr, AttrDiffer, AttrRefValue, AttrVisitor},
+        element_info::{is_valid_attr_name, is_void, raw_text_content},
+        key::Key,
+        node::{Comp, CompNode, ContextProvider, Node, NodeDiffer, NodeVisitor, Stream, Tag, Text},
+        patch::{diff, Patch},
+        path::{resolve, Path, ResolveError},
+        style::StyleRegistry,
+    },
+};
+use futures::{
+    io::{AsyncWrite, AsyncWriteExt},
+    Future,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    convert::Infallible,
+    fmt::{self, Write},
+    io, mem,
+    ops::Range,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+#[cfg(feature = "rayon")]
+use crate::vdom::{
+    collect::fold,
+    dom::DomNode,
+    node::{is_providing, TagStatic, TextDyn, TextStatic},
+};
+
+/// Driver for rendering nodes to HTML text, e.g. on the server.
+pub struct HtmlDriver;
+
+impl Driver for HtmlDriver {
+    type AttrStore = ();
+    type TagStore = ();
+    type TextStore = ();
+    type CompStore = ();
+    type Event = ();
+
+    fn new_attr_store() {}
+
+    fn new_tag_store() {}
+
+    fn new_text_store() {}
+
+    fn new_comp_store() {}
+
+    fn spawn<F>(&mut self, _fut: F)
+    where
+        F: Future<Output = ()> + 'static,
+    {
+    }
+}
+
+/// Renders `node` to HTML. Lists of nodes without a single root, such as
+/// tuples, `Vec`s and slices, render as the concatenation of their entries,
+/// and an empty list as an empty string.
+pub fn render_to_string<N>(node: &mut N) -> String
+where
+    N: Node<HtmlDriver> + ?Sized,
+{
+    let mut out = String::new();
+    node.visit(&mut 0, &mut HtmlRenderer::new(&mut out))
+        .expect("writing to a String failed");
+    out
+}
+
+/// Renders `node` like `render_to_string`, with a `StyleRegistry` provided to
+/// the `ScopedStyle`s in it, and puts the CSS they registered in front of it
+/// in a single `<style>` element.
+pub fn render_to_string_with_styles<N>(node: &mut N) -> String
+where
+    N: Node<HtmlDriver>,
+{
+    let styles = StyleRegistry::new();
+    let html = render_to_string(&mut ContextProvider::new(styles.clone(), node));
+    styles.style_tag() + &html
+}
+
+/// Renders `node` like `render_to_string`, but in parallel, e.g. a large
+/// page with many sections. The tree is split at lists and tuples into
+/// subtrees of at least `min_subtree_size` nodes, counting descendants, which
+/// are rendered on the rayon thread pool into buffers of their own, then
+/// concatenated in order. The output is the same as that of
+/// `render_to_string`.
+///
+/// Components and listeners aren't `Send`, so trees that hold them can only
+/// be rendered with `render_to_string`, or wrapped in `Sequential`. Neither
+/// are context values, so if this is called while a `ContextProvider` or a
+/// streamed render is visited on the current thread, e.g. from a component,
+/// the tree is rendered sequentially, as the worker threads couldn't see
+/// them.
+#[cfg(feature = "rayon")]
+pub fn render_to_string_parallel<N>(node: &mut N, min_subtree_size: usize) -> String
+where
+    N: ParallelNode,
+{
+    if is_providing() || Stream::is_active() {
+        return render_to_string(node);
+    }
+    let mut out = String::new();
+    node.render_parallel(false, min_subtree_size, &mut out);
+    out
+}
+
+/// Nodes that can be split up by `render_to_string_parallel`.
+#[cfg(feature = "rayon")]
+pub trait ParallelNode: Node<HtmlDriver> + Send {
+    /// Appends the HTML of `self` to `out`, rendering the subtrees of at
+    /// least `min_subtree_size` nodes in it concurrently. `raw_text` is
+    /// whether the parent element has raw text content.
+    fn render_parallel(&mut self, raw_text: bool, min_subtree_size: usize, out: &mut String);
+}
+
+/// Renders `N` sequentially within a tree rendered by
+/// `render_to_string_parallel`, e.g. a subtree that is `Send` but has nodes
+/// which don't implement `ParallelNode`.
+#[cfg(feature = "rayon")]
+pub struct Sequential<N>(pub N);
+
+#[cfg(feature = "rayon")]
+impl<N> Node<HtmlDriver> for Sequential<N>
+where
+    N: Node<HtmlDriver>,
+{
+    fn visit<NV>(&mut self, index: &mut usize, visitor: &mut NV) -> Result<(), NV::Err>
+    where
+        NV: NodeVisitor<HtmlDriver>,
+    {
+        self.0.visit(index, visitor)
+    }
+
+    fn diff<ND>(
+        &mut self,
+        curr_index: &mut usize,
+        ancestor_index: &mut usize,
+        ancestor: &mut Self,
+        differ: &mut ND,
+    ) -> Result<(), ND::Err>
+    where
+        ND: NodeDiffer<HtmlDriver>,
+    {
+        self.0
+            .diff(curr_index, ancestor_index, &mut ancestor.0, differ)
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<N> ParallelNode for Sequential<N>
+where
+    N: Node<HtmlDriver> + Send,
+{
+    fn render_parallel(&mut self, raw_text: bool, _min_subtree_size: usize, out: &mut String) {
+        render_sequential(self, raw_text, out)
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<C, A> ParallelNode for TagStatic<HtmlDriver, C, A>
+where
+    C: ParallelNode,
+    // Named in full, as importing `AttrList` brings its `push` into scope
+    // for the `Vec`s of this module.
+    A: crate::vdom::attr::AttrList<HtmlDriver> + Send,
+{
+    fn render_parallel(&mut self, _raw_text: bool, min_subtree_size: usize, out: &mut String) {
+        write_start_tag(out, self, Quote::Double, None).expect("writing to a String failed");
+        if is_void(self.tag()) {
+            return;
+        }
+        let raw_text = raw_text_content(self.tag());
+        self.children_mut()
+            .render_parallel(raw_text, min_subtree_size, out);
+        out.push_str("</");
+        out.push_str(self.tag());
+        out.push('>');
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl ParallelNode for DomNode<HtmlDriver> {
+    fn render_parallel(&mut self, raw_text: bool, min_subtree_size: usize, out: &mut String) {
+        let tag = match self {
+            DomNode::Tag(tag) => tag,
+            DomNode::Text(_) | DomNode::Comment(_) => {
+                return render_sequential(self, raw_text, out)
+            }
+        };
+        write_start_tag(out, tag, Quote::Double, None).expect("writing to a String failed");
+        if is_void(&tag.tag) {
+            return;
+        }
+        let raw_text = raw_text_content(&tag.tag);
+        tag.children
+            .render_parallel(raw_text, min_subtree_size, out);
+        out.push_str("</");
+        out.push_str(&tag.tag);
+        out.push('>');
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl ParallelNode for TextStatic<HtmlDriver> {
+    fn render_parallel(&mut self, raw_text: bool, _min_subtree_size: usize, out: &mut String) {
+        render_sequential(self, raw_text, out)
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl ParallelNode for TextDyn<HtmlDriver> {
+    fn render_parallel(&mut self, raw_text: bool, _min_subtree_size: usize, out: &mut String) {
+        render_sequential(self, raw_text, out)
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl ParallelNode for () {
+    fn render_parallel(&mut self, _raw_text: bool, _min_subtree_size: usize, _out: &mut String) {}
+}
+
+#[cfg(feature = "rayon")]
+impl<N> ParallelNode for Option<N>
+where
+    N: ParallelNode,
+{
+    fn render_parallel(&mut self, raw_text: bool, min_subtree_size: usize, out: &mut String) {
+        if let Some(node) = self {
+            node.render_parallel(raw_text, min_subtree_size, out);
+        }
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<N> ParallelNode for Vec<N>
+where
+    N: ParallelNode,
+{
+    fn render_parallel(&mut self, raw_text: bool, min_subtree_size: usize, out: &mut String) {
+        use rayon::prelude::*;
+
+        // Counting the nodes of an entry takes about as long as rendering
+        // it, so that is done concurrently as well.
+        let sizes = self
+            .par_iter_mut()
+            .map(|node| subtree_size(node))
+            .collect::<Vec<_>>();
+        // Consecutive entries are grouped until a group holds at least
+        // `min_subtree_size` nodes, so small entries aren't sent to the
+        // thread pool one by one.
+        let mut groups = Vec::new();
+        let (mut rest, mut sizes) = (&mut self[..], &sizes[..]);
+        while !rest.is_empty() {
+            let (mut len, mut size) = (0, 0);
+            while len < rest.len() && (len == 0 || size < min_subtree_size) {
+                size += sizes[len];
+                len += 1;
+            }
+            let (group, tail) = mem::take(&mut rest).split_at_mut(len);
+            groups.push((group, &sizes[..len]));
+            rest = tail;
+            sizes = &sizes[len..];
+        }
+        let render_group = |(group, sizes): (&mut [N], &[usize])| {
+            let mut out = String::new();
+            for (node, &size) in group.iter_mut().zip(sizes) {
+                render_subtree(node, size, raw_text, min_subtree_size, &mut out);
+            }
+            out
+        };
+        if groups.len() == 1 {
+            out.push_str(&render_group(groups.pop().expect("one group")));
+            return;
+        }
+        let rendered = groups.into_par_iter().map(render_group).collect::<Vec<_>>();
+        for html in rendered {
+            out.push_str(&html);
+        }
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<L1, L2> ParallelNode for (L1, L2)
+where
+    L1: ParallelNode,
+    L2: ParallelNode,
+{
+    fn render_parallel(&mut self, raw_text: bool, min_subtree_size: usize, out: &mut String) {
+        let (first, second) = self;
+        let sizes = (subtree_size(first), subtree_size(second));
+        if sizes.0 < min_subtree_size || sizes.1 < min_subtree_size {
+            render_subtree(first, sizes.0, raw_text, min_subtree_size, out);
+            render_subtree(second, sizes.1, raw_text, min_subtree_size, out);
+            return;
+        }
+        let (first, second) = rayon::join(
+            || {
+                let mut out = String::new();
+                render_subtree(first, sizes.0, raw_text, min_subtree_size, &mut out);
+                out
+            },
+            || {
+                let mut out = String::new();
+                render_subtree(second, sizes.1, raw_text, min_subtree_size, &mut out);
+                out
+            },
+        );
+        out.push_str(&first);
+        out.push_str(&second);
+    }
+}
+
+/// Renders `node` of `size` nodes in parallel if it's large enough to be
+/// split up, otherwise sequentially.
+#[cfg(feature = "rayon")]
+fn render_subtree<N>(
+    node: &mut N,
+    size: usize,
+    raw_text: bool,
+    min_subtree_size: usize,
+    out: &mut String,
+) where
+    N: ParallelNode,
+{
+    if size < min_subtree_size {
+        render_sequential(node, raw_text, out);
+    } else {
+        node.render_parallel(raw_text, min_subtree_size, out);
+    }
+}
+
+#[cfg(feature = "rayon")]
+fn render_sequential<N>(node: &mut N, raw_text: bool, out: &mut String)
+where
+    N: Node<HtmlDriver>,
+{
+    let mut renderer = HtmlRenderer::new(out);
+    renderer.raw_text = raw_text;
+    node.visit(&mut 0, &mut renderer)
+        .expect("writing to a String failed");
+}
+
+/// The number of tags and texts in `node`, including descendants.
+#[cfg(feature = "rayon")]
+fn subtree_size<N>(node: &mut N) -> usize
+where
+    N: Node<HtmlDriver> + ?Sized,
+{
+    fold(&DriverCtx::new(HtmlDriver), node, 0, |size, _| size + 1)
+}
+
+/// A complete page, made of a doctype such as `html` and the root element.
+///
+/// Documents can be rendered, but aren't nodes, so they can't be mounted into
+/// an element.
+pub struct Document<N> {
+    pub doctype: &'static str,
+    pub root: N,
+}
+
+impl<N> Document<N>
+where
+    N: Node<HtmlDriver>,
+{
+    pub fn new(doctype: &'static str, root: N) -> Document<N> {
+        Document { doctype, root }
+    }
+
+    pub fn render<W>(&mut self, renderer: &mut HtmlRenderer<'_, W>) -> fmt::Result
+    where
+        W: Write,
+    {
+        write!(renderer.out, "<!DOCTYPE {}>", self.doctype)?;
+        self.root.visit(&mut 0, renderer)
+    }
+
+    pub fn render_to_string(&mut self) -> String {
+        let mut out = String::new();
+        self.render(&mut HtmlRenderer::new(&mut out))
+            .expect("writing to a String failed");
+        out
+    }
+}
+
+/// Renders the parts of `curr` that changed since `ancestor` as fragments for
+/// out-of-band swaps, each paired with the `id` of the element it replaces.
+///
+/// A change is attributed to the innermost element around it with an `id` in
+/// `ancestor`, which is then rendered as a whole. Changes outside of any such
+/// element can't be targeted and are not reported.
+pub fn render_oob<N>(curr: &mut N, ancestor: &mut N) -> Vec<(String, String)>
+where
+    N: Node<HtmlDriver>,
+{
+    let mut differ = OobDiffer {
+        changed: false,
+        fragments: Vec::new(),
+    };
+    curr.diff(&mut 0, &mut 0, ancestor, &mut differ)
+        .expect("writing to a String failed");
+    differ.fragments
+}
+
+struct OobDiffer {
+    changed: bool,
+    fragments: Vec<(String, String)>,
+}
+
+impl NodeDiffer<HtmlDriver> for OobDiffer {
+    type Err = fmt::Error;
+
+    fn on_node_added<N>(&mut self, index: &mut usize, curr: &mut N) -> Result<(), fmt::Error>
+    where
+        N: Node<HtmlDriver>,
+    {
+        self.changed = true;
+        curr.visit(index, &mut HtmlRenderer::new(&mut Discard))
+    }
+
+    fn on_node_removed<N>(
+        &mut self,
+        _curr_index: usize,
+        ancestor_index: &mut usize,
+        ancestor: &mut N,
+    ) -> Result<(), fmt::Error>
+    where
+        N: Node<HtmlDriver>,
+    {
+        self.changed = true;
+        ancestor.visit(ancestor_index, &mut HtmlRenderer::new(&mut Discard))
+    }
+
+    fn on_node_moved(
+        &mut self,
+        _from_index: usize,
+        _to_index: usize,
+        _key: &Key,
+    ) -> Result<(), fmt::Error> {
+        self.changed = true;
+        Ok(())
+    }
+
+    fn on_invariant_violation(
+        &mut self,
+        _curr_index: usize,
+        _expected: &str,
+        _found: &str,
+    ) -> Result<(), fmt::Error> {
+        Ok(())
+    }
+
+    fn on_tag<T>(
+        &mut self,
+        _curr_index: usize,
+        _ancestor_index: usize,
+        curr: &mut T,
+        ancestor: &mut T,
+    ) -> Result<(), fmt::Error>
+    where
+        T: Tag<HtmlDriver>,
+    {
+        let mut id = IdFinder { id: None };
+        match ancestor.visit_attrs(&mut id) {
+            Ok(()) => {}
+            Err(never) => match never {},
+        }
+
+        let outer_changed = mem::replace(&mut self.changed, false);
+        let start = self.fragments.len();
+        let mut attr_differ = OobAttrDiffer { changed: false };
+        match curr.diff_attrs(ancestor, &mut attr_differ) {
+            Ok(()) => self.changed |= attr_differ.changed,
+            Err(never) => match never {},
+        }
+        curr.diff_children(ancestor, self)?;
+
+        match id.id {
+            Some(id) => {
+                if self.changed {
+                    // The fragments of nested elements are part of this one.
+                    self.fragments.truncate(start);
+                    let mut html = String::new();
+                    HtmlRenderer::new(&mut html).on_tag(0, curr)?;
+                    self.fragments.push((id, html));
+                }
+                self.changed = outer_changed;
+            }
+            None => self.changed |= outer_changed,
+        }
+        Ok(())
+    }
+
+    fn on_text<T>(
+        &mut self,
+        _curr_index: usize,
+        _ancestor_index: usize,
+        curr: &mut T,
+        ancestor: &mut T,
+    ) -> Result<(), fmt::Error>
+    where
+        T: Text<HtmlDriver>,
+    {
+        self.changed |= curr.get() != ancestor.get();
+        Ok(())
+    }
+
+    fn on_comp<C>(
+        &mut self,
+        curr_index: &mut usize,
+        ancestor_index: &mut usize,
+        curr: &mut CompNode<HtmlDriver, C>,
+        ancestor: &mut CompNode<HtmlDriver, C>,
+    ) -> Result<(), fmt::Error>
+    where
+        C: Comp<HtmlDriver>,
+    {
+        if curr.comp_ctx().is_none() {
+            let ctx = ancestor.comp_ctx().expect("ancestor.comp_ctx is None");
+            curr.set_comp_ctx(ctx.clone());
+        }
+        curr.diff_rendered(curr_index, ancestor_index, ancestor, self)
+    }
+}
+
+struct OobAttrDiffer {
+    changed: bool,
+}
+
+impl AttrDiffer<HtmlDriver> for OobAttrDiffer {
+    type Err = Infallible;
+
+    fn on_diff<A>(&mut self, curr: &mut A, ancestor: &mut A) -> Result<(), Infallible>
+    where
+        A: Attr<HtmlDriver>,
+    {
+        self.changed |= curr.value() != ancestor.value();
+        Ok(())
+    }
+
+    fn on_added<A>(&mut self, _curr: &mut A) -> Result<(), Infallible>
+    where
+        A: Attr<HtmlDriver>,
+    {
+        self.changed = true;
+        Ok(())
+    }
+
+    fn on_removed<A>(&mut self, _ancestor: &mut A) -> Result<(), Infallible>
+    where
+        A: Attr<HtmlDriver>,
+    {
+        self.changed = true;
+        Ok(())
+    }
+}
+
+struct IdFinder {
+    id: Option<String>,
+}
+
+impl AttrVisitor<HtmlDriver> for IdFinder {
+    type Err = Infallible;
+
+    fn on_attr<A>(&mut self, attr: &mut A) -> Result<(), Infallible>
+    where
+        A: Attr<HtmlDriver>,
+    {
+        if let ("id", AttrRefValue::Str(id)) = (attr.name(), attr.value()) {
+            self.id = Some(id.to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Output for renderers that are only run to walk nodes.
+struct Discard;
+
+impl Write for Discard {
+    fn write_str(&mut self, _s: &str) -> fmt::Result {
+        Ok(())
+    }
+}
+
+/// The character attribute values are quoted with.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Quote {
+    Double,
+    Single,
+}
+
+impl Quote {
+    fn as_char(self) -> char {
+        match self {
+            Quote::Double => '"',
+            Quote::Single => '\'',
+        }
+    }
+}
+
+/// The attribute that `HtmlRenderer::hydration_keys` writes the path of each
+/// element to.
+pub const HYDRATION_KEY: &str = "data-hk";
+
+/// Renders nodes to HTML. The output is minified: nothing is written between
+/// or within tags but what the nodes hold, so there is no whitespace between
+/// adjacent elements and no newlines, while the text of text nodes is written
+/// exactly as is, only escaped. The other renderers write the same HTML. For
+/// a readable form, e.g. in tests, see `testing::Snapshot::pretty`.
+///
+/// Comments are only written for `Comment` nodes, e.g. ones read from
+/// existing markup, which `dom::sanitize` removes.
+pub struct HtmlRenderer<'a, W>
+where
+    W: Write,
+{
+    out: &'a mut W,
+    driver_ctx: DriverCtx<HtmlDriver>,
+    quote: Quote,
+    /// The path of the element whose children are being rendered, if
+    /// hydration keys are written.
+    parent: Option<Path>,
+    /// Whether the element whose children are being rendered has raw text
+    /// content.
+    raw_text: bool,
+}
+
+impl<'a, W> HtmlRenderer<'a, W>
+where
+    W: Write,
+{
+    pub fn new(out: &'a mut W) -> HtmlRenderer<'a, W> {
+        HtmlRenderer {
+            out,
+            driver_ctx: DriverCtx::new(HtmlDriver),
+            quote: Quote::Double,
+            parent: None,
+            raw_text: false,
+        }
+    }
+
+    pub fn quote(mut self, quote: Quote) -> HtmlRenderer<'a, W> {
+        self.quote = quote;
+        self
+    }
+
+    /// Writes the path of each element to its `HYDRATION_KEY` attribute, e.g.
+    /// `data-hk="/0/2"`, so that the client can match the elements by their
+    /// path when hydrating, regardless of the text between them.
+    pub fn hydration_keys(mut self) -> HtmlRenderer<'a, W> {
+        self.parent = Some(Path::root());
+        self
+    }
+}
+
+impl<'a, W> NodeVisitor<HtmlDriver> for HtmlRenderer<'a, W>
+where
+    W: Write,
+{
+    type Err = fmt::Error;
+
+    fn on_tag<T>(&mut self, index: usize, tag: &mut T) -> Result<(), fmt::Error>
+    where
+        T: Tag<HtmlDriver>,
+    {
+        let path = self.parent.as_ref().map(|parent| parent.child(index));
+        write_start_tag(self.out, tag, self.quote, path.as_ref())?;
+        if is_void(tag.tag()) {
+            return Ok(());
+        }
+        let parent = mem::replace(&mut self.parent, path);
+        let raw_text = mem::replace(&mut self.raw_text, raw_text_content(tag.tag()));
+        tag.visit_children(self)?;
+        self.parent = parent;
+        self.raw_text = raw_text;
+        write!(self.out, "</{}>", tag.tag())
+    }
+
+    fn on_text<T>(&mut self, _index: usize, text: &mut T) -> Result<(), fmt::Error>
+    where
+        T: Text<HtmlDriver>,
+    {
+        write_text_node(self.out, text, self.raw_text)
+    }
+
+    fn on_comp<C>(
+        &mut self,
+        index: &mut usize,
+        comp: &mut CompNode<HtmlDriver, C>,
+    ) -> Result<(), fmt::Error>
+    where
+        C: Comp<HtmlDriver>,
+    {
+        if comp.comp_ctx().is_none() {
+            comp.init_comp_ctx(self.driver_ctx.clone());
+        }
+        comp.visit_rendered(index, self)
+    }
+
+    fn driver_ctx(&self) -> Option<DriverCtx<HtmlDriver>> {
+        Some(self.driver_ctx.clone())
+    }
+}
+
+/// Streams HTML with the content of `Suspense` nodes sent as it becomes
+/// available, e.g. to send a page's shell before slow data was loaded.
+///
+/// `shell` renders the tree with the placeholders of pending boundaries
+/// wrapped in a `<vdom-boundary>` element. Each `flush` builds the tree again
+/// and renders the content of the boundaries that settled since, each in a
+/// `<template>` followed by a `<script>` that replaces the placeholder with
+/// it, so boundaries can be sent in any order. Boundaries in the streamed
+/// content are streamed later the same way.
+///
+/// The placeholders and their content are matched up by ids numbering the
+/// boundaries by their position in the tree, see `render_stream` to write
+/// the chunks to an `AsyncWrite` as the resources settle.
+pub struct StreamingRenderer<F> {
+    build: F,
+    quote: Quote,
+    stream: Stream,
+}
+
+impl<F, N> StreamingRenderer<F>
+where
+    F: FnMut() -> N,
+    N: Node<HtmlDriver>,
+{
+    /// `build` builds the tree from the current state of the resources in
+    /// it. It's expected to build the same tree but for the state of the
+    /// `Suspense` nodes, which are matched up by position.
+    pub fn new(build: F) -> StreamingRenderer<F> {
+        StreamingRenderer {
+            build,
+            quote: Quote::Double,
+            stream: Stream::default(),
+        }
+    }
+
+    pub fn quote(mut self, quote: Quote) -> StreamingRenderer<F> {
+        self.quote = quote;
+        self
+    }
+
+    /// Renders the whole tree, with placeholders for pending boundaries.
+    pub fn shell(&mut self) -> String {
+        let mut node = (self.build)();
+        let mut out = String::new();
+        let quote = self.quote;
+        let (stream, res) = mem::take(&mut self.stream).run(true, || {
+            node.visit(&mut 0, &mut HtmlRenderer::new(&mut out).quote(quote))
+        });
+        self.stream = stream;
+        res.expect("writing to a String failed");
+        out
+    }
+
+    /// Renders the content of the boundaries whose placeholder was sent and
+    /// that settled since, in document order. Empty if there are none.
+    pub fn flush(&mut self) -> String {
+        if self.is_done() {
+            return String::new();
+        }
+        let mut node = (self.build)();
+        let mut flusher = StreamFlusher {
+            quote: self.quote,
+            raw_text: false,
+            captured: Vec::new(),
+        };
+        let (stream, res) =
+            mem::take(&mut self.stream).run(false, || node.visit(&mut 0, &mut flusher));
+        self.stream = stream;
+        res.expect("writing to a String failed");
+
+        let mut out = String::new();
+        for (id, html) in flusher.captured {
+            write!(
+                out,
+                "<template id=\"vs-c{id}\">{html}</template><script>(function(){{\
+                 var t=document.getElementById(\"vs-c{id}\");\
+                 document.getElementById(\"vs-b{id}\").replaceWith(t.content);\
+                 t.remove()}})()</script>",
+                id = id,
+                html = html,
+            )
+            .expect("writing to a String failed");
+        }
+        out
+    }
+
+    /// Whether the content of every boundary has been sent.
+    pub fn is_done(&self) -> bool {
+        self.stream.pending.is_empty()
+    }
+
+    /// Writes the shell to `out`, then runs `tasks`, e.g. those of
+    /// `Resource::from_future`, and writes what `flush` returns after each
+    /// of them settled, until every boundary was sent or the tasks are done.
+    /// At most `concurrency` tasks run at once, but at least one, and the
+    /// others start in order as those running finish.
+    ///
+    /// Each chunk is written with a single `write_all` and followed by a
+    /// flush, so it's sent as soon as it's available.
+    pub async fn render_stream<W, I>(
+        &mut self,
+        out: &mut W,
+        tasks: I,
+        concurrency: usize,
+    ) -> io::Result<()>
+    where
+        W: AsyncWrite + Unpin,
+        I: IntoIterator,
+        I::Item: Future<Output = ()>,
+    {
+        out.write_all(self.shell().as_bytes()).await?;
+        out.flush().await?;
+        let mut queued = tasks.into_iter();
+        let mut running = Vec::new();
+        loop {
+            while running.len() < concurrency.max(1) {
+                match queued.next() {
+                    Some(task) => running.push(Box::pin(task)),
+                    None => break,
+                }
+            }
+            if self.is_done() || running.is_empty() {
+                return Ok(());
+            }
+            AnySettled {
+                tasks: &mut running,
+            }
+            .await;
+            let chunk = self.flush();
+            if !chunk.is_empty() {
+                out.write_all(chunk.as_bytes()).await?;
+                out.flush().await?;
+            }
+        }
+    }
+}
+
+/// Runs `tasks` until at least one of them finished, removing those that
+/// did.
+struct AnySettled<'a, T> {
+    tasks: &'a mut Vec<Pin<Box<T>>>,
+}
+
+impl<'a, T> Future for AnySettled<'a, T>
+where
+    T: Future<Output = ()>,
+{
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let mut settled = false;
+        let mut i = 0;
+        while i < self.tasks.len() {
+            if self.tasks[i].as_mut().poll(cx).is_ready() {
+                self.tasks.remove(i);
+                settled = true;
+            } else {
+                i += 1;
+            }
+        }
+        if settled {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/// Renders only the content of the boundaries being captured, each to its
+/// own string.
+struct StreamFlusher {
+    quote: Quote,
+    /// Whether the text visited is the content of an element with raw text
+    /// content, as in `HtmlRenderer`.
+    raw_text: bool,
+    captured: Vec<(String, String)>,
+}
+
+impl StreamFlusher {
+    /// The output for the boundary being captured, if any.
+    fn out(&mut self) -> Option<&mut String> {
+        let id = Stream::capture()?;
+        match self.captured.last() {
+            Some((last, _)) if *last == id => {}
+            _ => self.captured.push((id, String::new())),
+        }
+        self.captured.last_mut().map(|(_, html)| html)
+    }
+}
+
+impl NodeVisitor<HtmlDriver> for StreamFlusher {
+    type Err = fmt::Error;
+
+    fn on_tag<T>(&mut self, index: usize, tag: &mut T) -> Result<(), fmt::Error>
+    where
+        T: Tag<HtmlDriver>,
+    {
+        let quote = self.quote;
+        match self.out() {
+            Some(out) => HtmlRenderer::new(out).quote(quote).on_tag(index, tag),
+            None => {
+                let raw_text = mem::replace(&mut self.raw_text, raw_text_content(tag.tag()));
+                tag.visit_children(self)?;
+                self.raw_text = raw_text;
+                Ok(())
+            }
+        }
+    }
+
+    fn on_text<T>(&mut self, _index: usize, text: &mut T) -> Result<(), fmt::Error>
+    where
+        T: Text<HtmlDriver>,
+    {
+        let raw_text = self.raw_text;
+        match self.out() {
+            Some(out) => write_text_node(out, text, raw_text),
+            None => Ok(()),
+        }
+    }
+
+    fn on_comp<C>(
+        &mut self,
+        index: &mut usize,
+        comp: &mut CompNode<HtmlDriver, C>,
+    ) -> Result<(), fmt::Error>
+    where
+        C: Comp<HtmlDriver>,
+    {
+        if comp.comp_ctx().is_none() {
+            comp.init_comp_ctx(DriverCtx::new(HtmlDriver));
+        }
+        comp.visit_rendered(index, self)
+    }
+
+    fn driver_ctx(&self) -> Option<DriverCtx<HtmlDriver>> {
+        Some(DriverCtx::new(HtmlDriver))
+    }
+}
+
+/// Renders nodes to HTML like [`HtmlRenderer`], but stops once `budget`
+/// characters of text have been written. The text that crosses the budget is
+/// cut off with an ellipsis and everything after it is skipped, while elements
+/// that are already open are still closed, so the output stays well-formed.
+///
+/// The budget counts `char`s, not grapheme clusters.
+pub struct TruncatingRenderer<'a, W>
+where
+    W: Write,
+{
+    out: &'a mut W,
+    driver_ctx: DriverCtx<HtmlDriver>,
+    quote: Quote,
+    remaining: usize,
+    truncated: bool,
+    raw_text: bool,
+}
+
+impl<'a, W> TruncatingRenderer<'a, W>
+where
+    W: Write,
+{
+    pub fn new(out: &'a mut W, budget: usize) -> TruncatingRenderer<'a, W> {
+        TruncatingRenderer {
+            out,
+            driver_ctx: DriverCtx::new(HtmlDriver),
+            quote: Quote::Double,
+            remaining: budget,
+            truncated: false,
+            raw_text: false,
+        }
+    }
+
+    pub fn quote(mut self, quote: Quote) -> TruncatingRenderer<'a, W> {
+        self.quote = quote;
+        self
+    }
+
+    /// Whether any text was cut off.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+}
+
+impl<'a, W> NodeVisitor<HtmlDriver> for TruncatingRenderer<'a, W>
+where
+    W: Write,
+{
+    type Err = fmt::Error;
+
+    fn on_tag<T>(&mut self, _index: usize, tag: &mut T) -> Result<(), fmt::Error>
+    where
+        T: Tag<HtmlDriver>,
+    {
+        if self.truncated {
+            return Ok(());
+        }
+        write_start_tag(self.out, tag, self.quote, None)?;
+        if is_void(tag.tag()) {
+            return Ok(());
+        }
+        let raw_text = mem::replace(&mut self.raw_text, raw_text_content(tag.tag()));
+        tag.visit_children(self)?;
+        self.raw_text = raw_text;
+        write!(self.out, "</{}>", tag.tag())
+    }
+
+    fn on_text<T>(&mut self, _index: usize, text: &mut T) -> Result<(), fmt::Error>
+    where
+        T: Text<HtmlDriver>,
+    {
+        if self.truncated {
+            return Ok(());
+        }
+        // Comments aren't shown, so they don't count towards the budget.
+        if text.is_comment() {
+            return write_comment(self.out, text.get());
+        }
+        let text = text.get();
+        match text.char_indices().nth(self.remaining) {
+            None => {
+                self.remaining -= text.chars().count();
+                write_text(self.out, text, self.raw_text)
+            }
+            Some((end, _)) => {
+                self.remaining = 0;
+                self.truncated = true;
+                write_text(self.out, &text[..end], self.raw_text)?;
+                self.out.write_char('\u{2026}')
+            }
+        }
+    }
+
+    fn on_comp<C>(
+        &mut self,
+        index: &mut usize,
+        comp: &mut CompNode<HtmlDriver, C>,
+    ) -> Result<(), fmt::Error>
+    where
+        C: Comp<HtmlDriver>,
+    {
+        if self.truncated {
+            return Ok(());
+        }
+        if comp.comp_ctx().is_none() {
+            comp.init_comp_ctx(self.driver_ctx.clone());
+        }
+        comp.visit_rendered(index, self)
+    }
+
+    fn driver_ctx(&self) -> Option<DriverCtx<HtmlDriver>> {
+        Some(self.driver_ctx.clone())
+    }
+}
+
+/// Renders nodes to HTML like [`HtmlRenderer`], but renders every distinct
+/// element subtree only once per call and copies it where it repeats, e.g.
+/// for pages made of many identical widgets.
+///
+/// Subtrees are told apart by their start tags and text, which takes a first
+/// pass over the nodes.
+pub struct CachingRenderer {
+    driver_ctx: DriverCtx<HtmlDriver>,
+    quote: Quote,
+    hits: usize,
+}
+
+impl CachingRenderer {
+    pub fn new() -> CachingRenderer {
+        CachingRenderer {
+            driver_ctx: DriverCtx::new(HtmlDriver),
+            quote: Quote::Double,
+            hits: 0,
+        }
+    }
+
+    pub fn quote(mut self, quote: Quote) -> CachingRenderer {
+        self.quote = quote;
+        self
+    }
+
+    pub fn render_to_string<N>(&mut self, node: &mut N) -> String
+    where
+        N: Node<HtmlDriver> + ?Sized,
+    {
+        let mut interner = SubtreeInterner {
+            driver_ctx: &self.driver_ctx,
+            quote: self.quote,
+            ids: HashMap::new(),
+            subtrees: Vec::new(),
+            open: Vec::new(),
+        };
+        match node.visit(&mut 0, &mut interner) {
+            Ok(()) => {}
+            Err(never) => match never {},
+        }
+
+        let mut out = String::new();
+        let mut renderer = CachedRenderer {
+            out: &mut out,
+            quote: self.quote,
+            subtrees: &interner.subtrees,
+            next: 0,
+            cache: HashMap::new(),
+            raw_text: false,
+            hits: 0,
+        };
+        node.visit(&mut 0, &mut renderer)
+            .expect("writing to a String failed");
+        self.hits += renderer.hits;
+        out
+    }
+
+    /// How many subtrees were copied from the cache so far.
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+}
+
+impl Default for CachingRenderer {
+    fn default() -> CachingRenderer {
+        CachingRenderer::new()
+    }
+}
+
+/// The id of an element's subtree and the number of elements in it,
+/// including itself.
+struct Subtree {
+    id: usize,
+    len: usize,
+}
+
+/// What makes up the HTML of an element's subtree: its start tag, which
+/// includes the attributes, and its children.
+#[derive(PartialEq, Eq, Hash)]
+struct SubtreeKey {
+    start_tag: String,
+    children: Vec<SubtreeChild>,
+}
+
+#[derive(PartialEq, Eq, Hash)]
+enum SubtreeChild {
+    Tag(usize),
+    Text(String),
+    Comment(String),
+}
+
+/// Gives the subtrees of all elements an id, in the order they are visited,
+/// which is the same for subtrees that render the same.
+struct SubtreeInterner<'a> {
+    driver_ctx: &'a DriverCtx<HtmlDriver>,
+    quote: Quote,
+    ids: HashMap<SubtreeKey, usize>,
+    subtrees: Vec<Subtree>,
+    /// The keys of the elements being visited, innermost last.
+    open: Vec<SubtreeKey>,
+}
+
+impl<'a> NodeVisitor<HtmlDriver> for SubtreeInterner<'a> {
+    type Err = Infallible;
+
+    fn on_tag<T>(&mut self, _index: usize, tag: &mut T) -> Result<(), Infallible>
+    where
+        T: Tag<HtmlDriver>,
+    {
+        let slot = self.subtrees.len();
+        self.subtrees.push(Subtree { id: 0, len: 0 });
+
+        let mut start_tag = String::new();
+        write_start_tag(&mut start_tag, tag, self.quote, None).expect("writing to a String failed");
+        self.open.push(SubtreeKey {
+            start_tag,
+            children: Vec::new(),
+        });
+        tag.visit_children(self)?;
+        let key = self.open.pop().expect("key of the tag");
+        let next_id = self.ids.len();
+        let id = *self.ids.entry(key).or_insert(next_id);
+
+        self.subtrees[slot] = Subtree {
+            id,
+            len: self.subtrees.len() - slot,
+        };
+        if let Some(parent) = self.open.last_mut() {
+            parent.children.push(SubtreeChild::Tag(id));
+        }
+        Ok(())
+    }
+
+    fn on_text<T>(&mut self, _index: usize, text: &mut T) -> Result<(), Infallible>
+    where
+        T: Text<HtmlDriver>,
+    {
+        if let Some(parent) = self.open.last_mut() {
+            let copy = text.get().to_string();
+            parent.children.push(if text.is_comment() {
+                SubtreeChild::Comment(copy)
+            } else {
+                SubtreeChild::Text(copy)
+            });
+        }
+        Ok(())
+    }
+
+    fn on_comp<C>(
+        &mut self,
+        index: &mut usize,
+        comp: &mut CompNode<HtmlDriver, C>,
+    ) -> Result<(), Infallible>
+    where
+        C: Comp<HtmlDriver>,
+    {
+        if comp.comp_ctx().is_none() {
+            comp.init_comp_ctx(self.driver_ctx.clone());
+        }
+        comp.visit_rendered(index, self)
+    }
+
+    fn driver_ctx(&self) -> Option<DriverCtx<HtmlDriver>> {
+        Some(self.driver_ctx.clone())
+    }
+}
+
+/// Renders like `HtmlRenderer`, copying the HTML of subtrees that were
+/// rendered before.
+struct CachedRenderer<'a> {
+    out: &'a mut String,
+    quote: Quote,
+    subtrees: &'a [Subtree],
+    /// The position in `subtrees` of the next element.
+    next: usize,
+    /// The HTML of the subtrees rendered so far, by id.
+    cache: HashMap<usize, String>,
+    hits: usize,
+    raw_text: bool,
+}
+
+impl<'a> NodeVisitor<HtmlDriver> for CachedRenderer<'a> {
+    type Err = fmt::Error;
+
+    fn on_tag<T>(&mut self, _index: usize, tag: &mut T) -> Result<(), fmt::Error>
+    where
+        T: Tag<HtmlDriver>,
+    {
+        // Only a node that visits other nodes than before, which it
+        // shouldn't, runs past the subtrees.
+        let subtree = self.subtrees.get(self.next);
+        if let Some(subtree) = subtree {
+            if let Some(html) = self.cache.get(&subtree.id) {
+                self.out.push_str(html);
+                self.next += subtree.len;
+                self.hits += 1;
+                return Ok(());
+            }
+        }
+        self.next += 1;
+
+        let start = self.out.len();
+        write_start_tag(self.out, tag, self.quote, None)?;
+        if !is_void(tag.tag()) {
+            let raw_text = mem::replace(&mut self.raw_text, raw_text_content(tag.tag()));
+            tag.visit_children(self)?;
+            self.raw_text = raw_text;
+            write!(self.out, "</{}>", tag.tag())?;
+        }
+        if let Some(subtree) = subtree {
+            self.cache.insert(subtree.id, self.out[start..].to_string());
+        }
+        Ok(())
+    }
+
+    fn on_text<T>(&mut self, _index: usize, text: &mut T) -> Result<(), fmt::Error>
+    where
+        T: Text<HtmlDriver>,
+    {
+        write_text_node(self.out, text, self.raw_text)
+    }
+
+    fn on_comp<C>(
+        &mut self,
+        index: &mut usize,
+        comp: &mut CompNode<HtmlDriver, C>,
+    ) -> Result<(), fmt::Error>
+    where
+        C: Comp<HtmlDriver>,
+    {
+        // Components were rendered by the `SubtreeInterner` already.
+        comp.visit_rendered(index, self)
+    }
+}
+
+/// Renders a tree to HTML again and again, e.g. on a server re-rendering
+/// similar pages, reusing the output of the previous render.
+///
+/// The previous tree is kept to diff the next one against, and only the
+/// HTML of the elements and text that changed is rendered and spliced into
+/// the output. Everything is rendered anew on the first render, when nodes
+/// are added to or removed from the top-level list, or when changes are
+/// spread over too much of the tree.
+pub struct IncrementalRenderer<N> {
+    driver_ctx: DriverCtx<HtmlDriver>,
+    quote: Quote,
+    prev: Option<N>,
+    out: String,
+    /// Where in `out` each node was rendered to.
+    ranges: HashMap<Path, Range<usize>>,
+    /// The paths of the elements with raw text content, for rendering their
+    /// text again.
+    raw_text_parents: HashSet<Path>,
+}
+
+impl<N> IncrementalRenderer<N>
+where
+    N: Node<HtmlDriver>,
+{
+    pub fn new() -> IncrementalRenderer<N> {
+        IncrementalRenderer {
+            driver_ctx: DriverCtx::new(HtmlDriver),
+            quote: Quote::Double,
+            prev: None,
+            out: String::new(),
+            ranges: HashMap::new(),
+            raw_text_parents: HashSet::new(),
+        }
+    }
+
+    pub fn quote(mut self, quote: Quote) -> IncrementalRenderer<N> {
+        self.quote = quote;
+        self
+    }
+
+    /// Renders `node`, which is kept until the next render.
+    pub fn render(&mut self, mut node: N) -> &str {
+        let regions = match &mut self.prev {
+            Some(prev) => changed_regions(&diff(&self.driver_ctx, &mut node, prev)),
+            None => None,
+        };
+        let spliced = match regions {
+            // Regions are spliced back to front, so the ranges of the ones
+            // before stay valid.
+            Some(regions) if regions.len() * 2 <= self.ranges.len() => {
+                regions
+                    .iter()
+                    .rev()
+                    .all(|path| self.splice(&mut node, path))
+            }
+            _ => false,
+        };
+        if !spliced {
+            self.out.clear();
+            self.ranges.clear();
+            self.raw_text_parents.clear();
+            let mut renderer = RangeRenderer {
+                out: &mut self.out,
+                driver_ctx: &self.driver_ctx,
+                quote: self.quote,
+                parent: Path::root(),
+                ranges: &mut self.ranges,
+                raw_text_parents: &mut self.raw_text_parents,
+            };
+            node.visit(&mut 0, &mut renderer)
+                .expect("writing to a String failed");
+        }
+        self.prev = Some(node);
+        &self.out
+    }
+
+    /// Renders the node at `path` in `node` over its previous HTML. Returns
+    /// false if it wasn't rendered before.
+    fn splice(&mut self, node: &mut N, path: &Path) -> bool {
+        let old = match self.ranges.get(path) {
+            Some(old) => old.clone(),
+            None => return false,
+        };
+        let mut html = String::new();
+        let mut ranges = HashMap::new();
+        // Keeps the parent, whose text is rendered the same as before.
+        self.raw_text_parents.retain(|p| !p.starts_with(path));
+        let mut renderer = RangeRenderer {
+            out: &mut html,
+            driver_ctx: &self.driver_ctx,
+            quote: self.quote,
+            parent: path.parent().expect("path of a node"),
+            ranges: &mut ranges,
+            raw_text_parents: &mut self.raw_text_parents,
+        };
+        let found = match resolve(node, path, &mut renderer) {
+            Ok(found) => found,
+            // Rendering all of `node` instead mounts what isn't.
+            Err(ResolveError::Unmounted(_)) => false,
+            Err(ResolveError::Visitor(fmt::Error)) => panic!("writing to a String failed"),
+        };
+        if !found {
+            return false;
+        }
+
+        // Nodes after the old HTML move, and those around it grow or shrink.
+        let (added, removed) = (html.len(), old.len());
+        self.ranges.retain(|p, _| !p.starts_with(path));
+        for range in self.ranges.values_mut() {
+            if range.start >= old.end {
+                range.start = range.start + added - removed;
+            }
+            if range.end >= old.end {
+                range.end = range.end + added - removed;
+            }
+        }
+        self.ranges.extend(
+            ranges
+                .into_iter()
+                .map(|(p, range)| (p, range.start + old.start..range.end + old.start)),
+        );
+        self.out.replace_range(old, &html);
+        true
+    }
+}
+
+impl<N> Default for IncrementalRenderer<N>
+where
+    N: Node<HtmlDriver>,
+{
+    fn default() -> IncrementalRenderer<N> {
+        IncrementalRenderer::new()
+    }
+}
+
+/// The outermost nodes to render again to apply `patches`, in document order,
+/// or `None` if the top-level list changed. These are the nodes that changed
+/// and the parents of nodes added or removed, which also means they are at
+/// the same paths in the previous and the current tree.
+fn changed_regions(patches: &[Patch<HtmlDriver>]) -> Option<Vec<Path>> {
+    let mut paths = patches
+        .iter()
+        .map(|patch| {
+            match patch {
+                Patch::InsertNode { path, .. }
+                | Patch::RemoveNode { path }
+                | Patch::MoveNode { path, .. } => path.parent(),
+                Patch::SetText { path, .. }
+                | Patch::SpliceText { path, .. }
+                | Patch::SetAttr { path, .. }
+                | Patch::RemoveAttr { path, .. } => Some(path.clone()),
+            }
+        })
+        .collect::<Option<Vec<_>>>()?;
+    if paths.iter().any(Path::is_root) {
+        return None;
+    }
+    // Descendants sort right after their ancestors.
+    paths.sort();
+    let mut regions: Vec<Path> = Vec::new();
+    for path in paths {
+        match regions.last() {
+            Some(region) if path.starts_with(region) => {}
+            _ => regions.push(path),
+        }
+    }
+    Some(regions)
+}
+
+/// Renders like `HtmlRenderer`, recording where each node was rendered to.
+struct RangeRenderer<'a> {
+    out: &'a mut String,
+    driver_ctx: &'a DriverCtx<HtmlDriver>,
+    quote: Quote,
+    parent: Path,
+    ranges: &'a mut HashMap<Path, Range<usize>>,
+    raw_text_parents: &'a mut HashSet<Path>,
+}
+
+impl<'a> NodeVisitor<HtmlDriver> for RangeRenderer<'a> {
+    type Err = fmt::Error;
+
+    fn on_tag<T>(&mut self, index: usize, tag: &mut T) -> Result<(), fmt::Error>
+    where
+        T: Tag<HtmlDriver>,
+    {
+        let path = self.parent.child(index);
+        let start = self.out.len();
+        write_start_tag(self.out, tag, self.quote, None)?;
+        if !is_void(tag.tag()) {
+            if raw_text_content(tag.tag()) {
+                self.raw_text_parents.insert(path.clone());
+            }
+            tag.visit_children(&mut RangeRenderer {
+                out: &mut *self.out,
+                driver_ctx: self.driver_ctx,
+                quote: self.quote,
+                parent: path.clone(),
+                ranges: &mut *self.ranges,
+                raw_text_parents: &mut *self.raw_text_parents,
+            })?;
+            write!(self.out, "</{}>", tag.tag())?;
+        }
+        self.ranges.insert(path, start..self.out.len());
+        Ok(())
+    }
+
+    fn on_text<T>(&mut self, index: usize, text: &mut T) -> Result<(), fmt::Error>
+    where
+        T: Text<HtmlDriver>,
+    {
+        let start = self.out.len();
+        let raw_text = self.raw_text_parents.contains(&self.parent);
+        write_text_node(self.out, text, raw_text)?;
+        self.ranges
+            .insert(self.parent.child(index), start..self.out.len());
+        Ok(())
+    }
+
+    fn on_comp<C>(
+        &mut self,
+        index: &mut usize,
+        comp: &mut CompNode<HtmlDriver, C>,
+    ) -> Result<(), fmt::Error>
+    where
+        C: Comp<HtmlDriver>,
+    {
+        if comp.comp_ctx().is_none() {
+            comp.init_comp_ctx(self.driver_ctx.clone());
+        }
+        comp.visit_rendered(index, self)
+    }
+
+    fn driver_ctx(&self) -> Option<DriverCtx<HtmlDriver>> {
+        Some(self.driver_ctx.clone())
+    }
+}
+
+/// Writes the start tag of `tag`, with `key` as its hydration key if any.
+fn write_start_tag<W, T>(out: &mut W, tag: &mut T, quote: Quote, key: Option<&Path>) -> fmt::Result
+where
+    W: Write,
+    T: Tag<HtmlDriver>,
+{
+    write!(out, "<{}", tag.tag())?;
+    tag.visit_attrs(&mut HtmlAttrRenderer {
+        out: &mut *out,
+        quote,
+    })?;
+    if let Some(key) = key {
+        let quote = quote.as_char();
+        write!(out, " {}={}{}{}", HYDRATION_KEY, quote, key, quote)?;
+    }
+    out.write_char('>')
+}
+
+struct HtmlAttrRenderer<'a, W>
+where
+    W: Write,
+{
+    out: &'a mut W,
+    quote: Quote,
+}
+
+impl<'a, W> AttrVisitor<HtmlDriver> for HtmlAttrRenderer<'a, W>
+where
+    W: Write,
+{
+    type Err = fmt::Error;
+
+    fn on_attr<A>(&mut self, attr: &mut A) -> Result<(), fmt::Error>
+    where
+        A: Attr<HtmlDriver>,
+    {
+        if let AttrRefValue::Null = attr.value() {
+            return Ok(());
+        }
+        // A name that would be read as other markup, e.g. one given to
+        // `DomAttr::new` by a user, is left out as a `null` attribute is.
+        if !is_valid_attr_name(attr.name()) {
+            return Ok(());
+        }
+        match attr.namespace() {
+            Some(ns) => write!(self.out, " {}:{}", ns.prefix, attr.name())?,
+            None => write!(self.out, " {}", attr.name())?,
+        }
+        match attr.value() {
+            AttrRefValue::True | AttrRefValue::Null => Ok(()),
+            AttrRefValue::Str(value) => {
+                let quote = self.quote.as_char();
+                write!(self.out, "={}", quote)?;
+                write_escaped(self.out, value, Some(self.quote))?;
+                self.out.write_char(quote)
+            }
+        }
+    }
+}
+
+/// Writes the text of a text node, escaped unless it's the content of an
+/// element with raw text content. Raw text can't be escaped, so only the end
+/// tags of raw text elements are broken up, e.g. `</script>` is written as
+/// `<\/script>`, which scripts and style sheets read the same in strings.
+/// Writes `text` as a comment if it's one, and as text content otherwise.
+fn write_text_node<W, T>(out: &mut W, text: &T, raw_text: bool) -> fmt::Result
+where
+    W: Write,
+    T: Text<HtmlDriver>,
+{
+    if text.is_comment() {
+        write_comment(out, text.get())
+    } else {
+        write_text(out, text.get(), raw_text)
+    }
+}
+
+/// Writes `<!--text-->`. Adjacent dashes are written with a space between
+/// them, as is a leading `>` or `->`, so the text can't end the comment
+/// early.
+fn write_comment<W>(out: &mut W, text: &str) -> fmt::Result
+where
+    W: Write,
+{
+    out.write_str("<!--")?;
+    if text.starts_with('>') || text.starts_with("->") {
+        out.write_char(' ')?;
+    }
+    let mut prev = None;
+    for c in text.chars() {
+        if c == '-' && prev == Some('-') {
+            out.write_char(' ')?;
+        }
+        out.write_char(c)?;
+        prev = Some(c);
+    }
+    out.write_str("-->")
+}
+
+fn write_text<W>(out: &mut W, text: &str, raw_text: bool) -> fmt::Result
+where
+    W: Write,
+{
+    if !raw_text {
+        return write_escaped(out, text, None);
+    }
+    let mut last = 0;
+    for (i, _) in text.match_indices("</") {
+        let rest = &text.as_bytes()[i + 2..];
+        let is_end_tag = ["script", "style"].iter().any(|tag| {
+            rest.len() >= tag.len() && rest[..tag.len()].eq_ignore_ascii_case(tag.as_bytes())
+        });
+        if is_end_tag {
+            out.write_str(&text[last..i + 1])?;
+            out.write_char('\\')?;
+            last = i + 1;
+        }
+    }
+    out.write_str(&text[last..])
+}
+
+/// Escapes `s` for text content, or for an attribute value quoted with
+/// `quote`, in which case only that quote character needs escaping.
+fn write_escaped<W>(out: &mut W, s: &str, quote: Option<Quote>) -> fmt::Result
+where
+    W: Write,
+{
+    let mut last = 0;
+    for (i, c) in s.char_indices() {
+        let escaped = match (c, quote) {
+            ('&', _) => "&amp;",
+            ('<', _) => "&lt;",
+            ('>', _) => "&gt;",
+            ('"', Some(Quote::Double)) => "&quot;",
+            ('\'', Some(Quote::Single)) => "&#39;",
+            _ => continue,
+        };
+        out.write_str(&s[last..i])?;
+        out.write_str(escaped)?;
+        last = i + c.len_utf8();
+    }
+    out.write_str(&s[last..])
+}
@@ -1,4 +1,8 @@
 #![deny(bare_trait_objects, anonymous_parameters, elided_lifetimes_in_paths)]
 
 pub mod driver;
+pub mod events;
+pub mod html;
+#[cfg(feature = "testing")]
+pub mod testing;
 pub mod vdom;
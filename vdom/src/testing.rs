@@ -0,0 +1,309 @@
+//! Helpers for testing apps built on vdom, enabled with the `testing`
+//! feature.
+
+use crate::{
+    html::{render_to_string, HtmlDriver},
+    vdom::{element_info::preserves_whitespace, node::Node},
+};
+use std::{
+    env,
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Asserts that `node` renders to the HTML stored in the snapshot file at
+/// `path`, relative to the crate's manifest directory, see `Snapshot`. With
+/// `pretty` as a third argument, both sides are compared with a tag per line.
+#[macro_export]
+macro_rules! assert_html_snapshot {
+    ($node:expr, $path:expr) => {
+        $crate::testing::Snapshot::new(
+            ::std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join($path),
+        )
+        .assert(&mut $node)
+    };
+    ($node:expr, $path:expr, pretty) => {
+        $crate::testing::Snapshot::new(
+            ::std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join($path),
+        )
+        .pretty()
+        .assert(&mut $node)
+    };
+}
+
+/// Asserts that two trees are the same, see `nodes_eq`, e.g. a tree built
+/// with `html!` and one parsed from a fixture. The trees can be of different
+/// types and are compared with the default `EqOptions` unless they are given
+/// as a third argument.
+#[macro_export]
+macro_rules! assert_nodes_eq {
+    ($left:expr, $right:expr) => {
+        $crate::assert_nodes_eq!($left, $right, $crate::vdom::dom::EqOptions::new())
+    };
+    ($left:expr, $right:expr, $options:expr) => {{
+        let driver_ctx = $crate::driver::DriverCtx::new($crate::html::HtmlDriver);
+        let left = $crate::vdom::dom::to_dom(&driver_ctx, &mut $left);
+        let right = $crate::vdom::dom::to_dom(&driver_ctx, &mut $right);
+        if !$crate::vdom::dom::nodes_eq(&left, &right, &$options) {
+            panic!(
+                "assertion failed: nodes are not equal\n  left: {:?}\n right: {:?}",
+                left, right,
+            );
+        }
+    }};
+}
+
+/// A file holding the expected HTML of a rendered tree.
+///
+/// On a mismatch, or if the file doesn't exist yet, the actual HTML is
+/// written next to it with a `.new` extension appended, to be inspected or
+/// moved into place. Running with `UPDATE_SNAPSHOTS=1` instead overwrites the
+/// snapshot and passes.
+pub struct Snapshot {
+    path: PathBuf,
+    pretty: bool,
+}
+
+impl Snapshot {
+    pub fn new<P>(path: P) -> Snapshot
+    where
+        P: Into<PathBuf>,
+    {
+        Snapshot {
+            path: path.into(),
+            pretty: false,
+        }
+    }
+
+    /// Puts every tag on a line of its own and drops whitespace between tags
+    /// before comparing, so snapshots are readable and whitespace-only
+    /// changes of the renderer don't change them.
+    pub fn pretty(mut self) -> Snapshot {
+        self.pretty = true;
+        self
+    }
+
+    /// Panics with a diff if `node` doesn't match the snapshot.
+    pub fn assert<N>(&self, node: &mut N)
+    where
+        N: Node<HtmlDriver> + ?Sized,
+    {
+        if let Err(msg) = self.check(&render_to_string(node)) {
+            panic!("{}", msg);
+        }
+    }
+
+    /// Compares `html` to the snapshot, returning a message with a diff on a
+    /// mismatch.
+    pub fn check(&self, html: &str) -> Result<(), String> {
+        let actual = if self.pretty {
+            prettify(html)
+        } else {
+            html.to_string()
+        };
+        let new_path = self.new_path();
+
+        if env::var("UPDATE_SNAPSHOTS").map_or(false, |v| v == "1") {
+            write_file(&self.path, &actual)?;
+            // A stale `.new` file would be mistaken for a pending change.
+            let _ = fs::remove_file(&new_path);
+            return Ok(());
+        }
+
+        let expected = match fs::read_to_string(&self.path) {
+            Ok(expected) => expected,
+            Err(_) => {
+                write_file(&new_path, &actual)?;
+                return Err(format!(
+                    "snapshot {} doesn't exist, the actual HTML was written to {}, \
+                     run with UPDATE_SNAPSHOTS=1 to accept it",
+                    self.path.display(),
+                    new_path.display(),
+                ));
+            }
+        };
+        let expected = if self.pretty {
+            prettify(&expected)
+        } else {
+            expected
+        };
+        if expected == actual {
+            let _ = fs::remove_file(&new_path);
+            return Ok(());
+        }
+
+        write_file(&new_path, &actual)?;
+        Err(format!(
+            "snapshot {} doesn't match, run with UPDATE_SNAPSHOTS=1 to accept the changes\n{}",
+            self.path.display(),
+            unified_diff(&expected, &actual, &self.path, &new_path),
+        ))
+    }
+
+    fn new_path(&self) -> PathBuf {
+        let mut new_path = self.path.clone().into_os_string();
+        new_path.push(".new");
+        new_path.into()
+    }
+}
+
+fn write_file(path: &Path, contents: &str) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|err| format!("{}: {}", dir.display(), err))?;
+    }
+    fs::write(path, contents).map_err(|err| format!("{}: {}", path.display(), err))
+}
+
+/// Puts every tag on a line of its own, dropping whitespace-only text
+/// between tags and trimming the remaining text. An element that preserves
+/// whitespace, e.g. `<pre>`, is kept as is on a single line, as changing its
+/// content would change what's rendered.
+fn prettify(html: &str) -> String {
+    let mut lines = Vec::new();
+    let mut rest = html;
+    while !rest.is_empty() {
+        let end = if rest.starts_with('<') {
+            let start_end = rest.find('>').map_or(rest.len(), |i| i + 1);
+            let name = start_tag_name(&rest[..start_end]).to_ascii_lowercase();
+            if preserves_whitespace(&name) {
+                let end_tag = format!("</{}", name);
+                rest.to_ascii_lowercase()[start_end..]
+                    .find(&end_tag)
+                    .and_then(|i| {
+                        rest[start_end + i..]
+                            .find('>')
+                            .map(|j| start_end + i + j + 1)
+                    })
+                    .unwrap_or_else(|| rest.len())
+            } else {
+                start_end
+            }
+        } else {
+            rest.find('<').unwrap_or_else(|| rest.len())
+        };
+        let line = rest[..end].trim();
+        if !line.is_empty() {
+            lines.push(line);
+        }
+        rest = &rest[end..];
+    }
+    let mut pretty = lines.join("\n");
+    pretty.push('\n');
+    pretty
+}
+
+/// The name of the start tag `tag`, which is empty for any other tag.
+fn start_tag_name(tag: &str) -> &str {
+    let name = &tag[1..];
+    let end = name
+        .find(|c: char| c.is_whitespace() || c == '/' || c == '>')
+        .unwrap_or_else(|| name.len());
+    &name[..end]
+}
+
+enum Line<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// The lines of `a` and `b` in order, marked by whether they are in either
+/// or both, using their longest common subsequence.
+fn diff_lines<'a>(a: &[&'a str], b: &[&'a str]) -> Vec<Line<'a>> {
+    // `lcs[i][j]` is the length of the LCS of `a[i..]` and `b[j..]`.
+    let mut lcs = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            lines.push(Line::Same(a[i]));
+            i += 1;
+            j += 1;
+        } else if j == b.len() || (i < a.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            lines.push(Line::Removed(a[i]));
+            i += 1;
+        } else {
+            lines.push(Line::Added(b[j]));
+            j += 1;
+        }
+    }
+    lines
+}
+
+/// A unified diff of `expected` and `actual`, with 3 lines of context around
+/// each change.
+fn unified_diff(expected: &str, actual: &str, expected_path: &Path, actual_path: &Path) -> String {
+    const CONTEXT: usize = 3;
+
+    let a = expected.lines().collect::<Vec<_>>();
+    let b = actual.lines().collect::<Vec<_>>();
+    let lines = diff_lines(&a, &b);
+
+    let mut out = String::new();
+    let _ = writeln!(out, "--- {}", expected_path.display());
+    let _ = writeln!(out, "+++ {}", actual_path.display());
+
+    let mut start = 0;
+    while let Some(first) = lines[start..].iter().position(is_change) {
+        let first = start + first;
+        // Extend the hunk while the next change is close enough that their
+        // contexts touch.
+        let mut last = first;
+        while let Some(next) = lines[last + 1..].iter().position(is_change) {
+            if next > 2 * CONTEXT {
+                break;
+            }
+            last += 1 + next;
+        }
+        let hunk_start = first.saturating_sub(CONTEXT);
+        let hunk_end = (last + 1 + CONTEXT).min(lines.len());
+
+        // Line numbers are 1-based and count the lines before the hunk on
+        // each side.
+        let before = &lines[..hunk_start];
+        let a_start = before.iter().filter(|l| !is_added(l)).count() + 1;
+        let b_start = before.iter().filter(|l| !is_removed(l)).count() + 1;
+        let hunk = &lines[hunk_start..hunk_end];
+        let a_len = hunk.iter().filter(|l| !is_added(l)).count();
+        let b_len = hunk.iter().filter(|l| !is_removed(l)).count();
+        let _ = writeln!(out, "@@ -{},{} +{},{} @@", a_start, a_len, b_start, b_len);
+        for line in hunk {
+            let _ = match line {
+                Line::Same(line) => writeln!(out, " {}", line),
+                Line::Removed(line) => writeln!(out, "-{}", line),
+                Line::Added(line) => writeln!(out, "+{}", line),
+            };
+        }
+        start = hunk_end;
+    }
+    out
+}
+
+fn is_change(line: &Line<'_>) -> bool {
+    is_added(line) || is_removed(line)
+}
+
+fn is_added(line: &Line<'_>) -> bool {
+    match line {
+        Line::Added(_) => true,
+        Line::Same(_) | Line::Removed(_) => false,
+    }
+}
+
+fn is_removed(line: &Line<'_>) -> bool {
+    match line {
+        Line::Removed(_) => true,
+        Line::Same(_) | Line::Added(_) => false,
+    }
+}
@@ -0,0 +1,375 @@
+//! Per-frame allocation of dynamic text, see `Frame` and `FrameLoop`.
+//!
+//! `TextDyn` and `AttrDyn` own their strings, so every render allocates the
+//! text of every dynamic node anew, only to drop it after the diff. Nodes
+//! built in a `Frame` borrow their text from it instead, and a `FrameLoop`
+//! reuses the memory of two frames, one for the current tree and one for its
+//! ancestor, so rendering stops allocating once the frames are large enough.
+
+use super::{
+    attr::{Attr, AttrRefValue},
+    node::{Node, NodeDiffer, NodeVisitor, Text},
+    patch::{self, Patch},
+};
+use crate::driver::{Driver, DriverCtx};
+use std::{
+    cell::RefCell,
+    fmt::{self, Write},
+    mem::ManuallyDrop,
+    ptr::{self, NonNull},
+    slice, str,
+};
+
+/// The capacity of the first chunk of a `Frame`.
+const FIRST_CHUNK: usize = 1024;
+
+/// A bump allocator for strings. Strings are copied into chunks that are
+/// never reallocated, which keeps the strings handed out in place as the
+/// frame grows, until `reset` frees them all at once.
+#[derive(Default)]
+pub struct Frame {
+    chunks: RefCell<Vec<String>>,
+    /// Where `fmt` formats to, as the length of the output isn't known
+    /// before.
+    scratch: RefCell<String>,
+}
+
+impl Frame {
+    pub fn new() -> Frame {
+        Default::default()
+    }
+
+    pub fn alloc_str(&self, s: &str) -> &str {
+        if s.is_empty() {
+            return "";
+        }
+        let mut chunks = self.chunks.borrow_mut();
+        let fits = match chunks.last() {
+            Some(chunk) => chunk.capacity() - chunk.len() >= s.len(),
+            None => false,
+        };
+        if !fits {
+            let last = chunks.last().map_or(0, String::capacity);
+            let capacity = s.len().max(last * 2).max(FIRST_CHUNK);
+            chunks.push(String::with_capacity(capacity));
+        }
+        let chunk = chunks.last_mut().expect("a chunk was pushed");
+        let start = chunk.len();
+        // Doesn't reallocate, as the chunk has room for `s`.
+        chunk.push_str(s);
+        // Safety: The bytes are a copy of `s`, so valid UTF-8. They stay in
+        // place until `reset`, which takes `&mut self` and so can't be called
+        // while they're borrowed, as chunks only grow up to their capacity
+        // and moving a `String` doesn't move its bytes.
+        unsafe {
+            let bytes = slice::from_raw_parts(chunk.as_ptr().add(start), s.len());
+            str::from_utf8_unchecked(bytes)
+        }
+    }
+
+    /// Formats `args` into the frame, e.g. `frame.fmt(format_args!("{}", n))`.
+    pub fn fmt(&self, args: fmt::Arguments<'_>) -> &str {
+        // Taken out of the cell, in case a `Display` impl formats into the
+        // frame as well.
+        let mut scratch = self.scratch.replace(String::new());
+        scratch.clear();
+        scratch.write_fmt(args).expect("writing to a String failed");
+        let s = self.alloc_str(&scratch);
+        self.scratch.replace(scratch);
+        s
+    }
+
+    pub fn display<T>(&self, value: &T) -> &str
+    where
+        T: fmt::Display + ?Sized,
+    {
+        self.fmt(format_args!("{}", value))
+    }
+
+    /// A text node of `value` as formatted by `Display`.
+    pub fn text<D, T>(&self, value: &T) -> TextRef<'_, D>
+    where
+        D: Driver,
+        T: fmt::Display + ?Sized,
+    {
+        TextRef::new(self.display(value))
+    }
+
+    /// An attribute with the value of `value` as formatted by `Display`.
+    pub fn attr<D, T>(&self, name: &'static str, value: &T) -> AttrRef<'_, D>
+    where
+        D: Driver,
+        T: fmt::Display + ?Sized,
+    {
+        AttrRef::new(name, Some(self.display(value)))
+    }
+
+    /// The number of bytes the frame holds without allocating.
+    pub fn capacity(&self) -> usize {
+        self.chunks.borrow().iter().map(String::capacity).sum()
+    }
+
+    /// Frees the strings of the frame while keeping its memory. A frame that
+    /// needed several chunks is merged into one, so the next frame of the
+    /// same size fits into it.
+    pub fn reset(&mut self) {
+        let chunks = self.chunks.get_mut();
+        if chunks.len() > 1 {
+            let capacity = chunks.iter().map(String::capacity).sum();
+            *chunks = vec![String::with_capacity(capacity)];
+        } else if let Some(chunk) = chunks.last_mut() {
+            chunk.clear();
+        }
+    }
+}
+
+/// A text node borrowing its text, e.g. from a `Frame`.
+pub struct TextRef<'a, D>
+where
+    D: Driver,
+{
+    text: &'a str,
+    driver_store: D::TextStore,
+}
+
+impl<'a, D> TextRef<'a, D>
+where
+    D: Driver,
+{
+    pub fn new(text: &'a str) -> TextRef<'a, D> {
+        TextRef {
+            text,
+            driver_store: D::new_text_store(),
+        }
+    }
+}
+
+impl<'a, D> Text<D> for TextRef<'a, D>
+where
+    D: Driver,
+{
+    fn is_static(&self) -> bool {
+        false
+    }
+
+    fn get(&self) -> &str {
+        self.text
+    }
+
+    fn driver_store(&mut self) -> &mut D::TextStore {
+        &mut self.driver_store
+    }
+}
+
+impl<'a, D> fmt::Debug for TextRef<'a, D>
+where
+    D: Driver,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.text, f)
+    }
+}
+
+impl<'a, D> Node<D> for TextRef<'a, D>
+where
+    D: Driver,
+{
+    fn visit<NV>(&mut self, index: &mut usize, visitor: &mut NV) -> Result<(), NV::Err>
+    where
+        NV: NodeVisitor<D>,
+    {
+        visitor.on_text(*index, self)?;
+        *index += 1;
+        Ok(())
+    }
+
+    fn diff<ND>(
+        &mut self,
+        curr_index: &mut usize,
+        ancestor_index: &mut usize,
+        ancestor: &mut Self,
+        differ: &mut ND,
+    ) -> Result<(), ND::Err>
+    where
+        ND: NodeDiffer<D>,
+    {
+        differ.on_text(*curr_index, *ancestor_index, self, ancestor)?;
+        *curr_index += 1;
+        *ancestor_index += 1;
+        Ok(())
+    }
+}
+
+/// An attribute borrowing its value, e.g. from a `Frame`. A `None` value is
+/// null, like `AttrValue::Null`.
+pub struct AttrRef<'a, D>
+where
+    D: Driver,
+{
+    key: &'static str,
+    value: Option<&'a str>,
+    driver_store: D::AttrStore,
+}
+
+impl<'a, D> AttrRef<'a, D>
+where
+    D: Driver,
+{
+    pub fn new(key: &'static str, value: Option<&'a str>) -> AttrRef<'a, D> {
+        AttrRef {
+            key,
+            value,
+            driver_store: D::new_attr_store(),
+        }
+    }
+}
+
+impl<'a, D> Attr<D> for AttrRef<'a, D>
+where
+    D: Driver,
+{
+    fn is_value_static(&self) -> bool {
+        false
+    }
+
+    fn name(&self) -> &str {
+        self.key
+    }
+
+    fn value(&self) -> AttrRefValue<'_> {
+        match self.value {
+            Some(value) => AttrRefValue::Str(value),
+            None => AttrRefValue::Null,
+        }
+    }
+
+    fn driver_store(&mut self) -> &mut D::AttrStore {
+        &mut self.driver_store
+    }
+}
+
+/// Builds the tree of a `FrameLoop` in a frame. The node type may borrow
+/// from the frame, hence the lifetime, which a `FrameLoop` requires for any
+/// `'a`, e.g.:
+///
+/// ```ignore
+/// impl<'a> FrameView<'a, HtmlDriver> for Counter {
+///     type Node = TagStatic<HtmlDriver, TextRef<'a, HtmlDriver>>;
+///
+///     fn view(&mut self, frame: &'a Frame) -> Self::Node {
+///         TagStatic::new("p", (), frame.text(&self.count))
+///     }
+/// }
+/// ```
+pub trait FrameView<'a, D>
+where
+    D: Driver,
+{
+    type Node: Node<D>;
+
+    fn view(&mut self, frame: &'a Frame) -> Self::Node;
+}
+
+/// Renders `view` into two frames in turn: each tree is built in the frame
+/// its ancestor isn't in, diffed against the ancestor, after which the
+/// ancestor is dropped and its frame reset for the next render.
+pub struct FrameLoop<D, V>
+where
+    D: Driver,
+    V: for<'a> FrameView<'a, D>,
+{
+    /// Borrows from `frames[current]`. The lifetime is erased, as the frames
+    /// are owned by the loop, and the tree never leaves it.
+    ancestor: Option<<V as FrameView<'static, D>>::Node>,
+    /// Boxed separately, so nodes may hold a reference to their frame
+    /// across moves of the loop, and resetting one frame doesn't touch the
+    /// other.
+    frames: [NonNull<Frame>; 2],
+    current: usize,
+    view: V,
+}
+
+impl<D, V> FrameLoop<D, V>
+where
+    D: Driver,
+    V: for<'a> FrameView<'a, D>,
+{
+    pub fn new(view: V) -> FrameLoop<D, V> {
+        let frame = || NonNull::from(Box::leak(Box::new(Frame::new())));
+        FrameLoop {
+            ancestor: None,
+            frames: [frame(), frame()],
+            current: 0,
+            view,
+        }
+    }
+
+    pub fn view(&mut self) -> &mut V {
+        &mut self.view
+    }
+
+    /// Renders a new tree and returns the patches from the previous one, or
+    /// the patches inserting the tree on the first render.
+    pub fn render(&mut self, driver_ctx: &DriverCtx<D>) -> Vec<Patch<D>> {
+        let next = 1 - self.current;
+        // Safety: `frames[next]` is only borrowed by the tree built here, as
+        // the tree last built in it was dropped when it was reset.
+        let (patches, curr) = unsafe {
+            render_in(
+                &mut self.view,
+                self.frames[next].as_ref(),
+                &mut self.ancestor,
+                driver_ctx,
+            )
+        };
+        self.ancestor = None;
+        // Safety: Nothing borrows from the frame, as `ancestor` was dropped.
+        unsafe { self.frames[self.current].as_mut().reset() };
+        self.ancestor = curr;
+        self.current = next;
+        patches
+    }
+}
+
+/// Builds a tree in `frame` and diffs it against `ancestor`, returning the
+/// tree with its lifetime erased.
+///
+/// Safety: The lifetime of the tree is erased by casting between the types
+/// of `FrameView<'a, D>::Node` and `FrameView<'static, D>::Node`, which
+/// differ in lifetimes only, as the same impl of `FrameView` is used for
+/// both. The returned tree must be dropped before `frame` is reset or freed.
+unsafe fn render_in<'a, D, V>(
+    view: &mut V,
+    frame: &'a Frame,
+    ancestor: &mut Option<<V as FrameView<'static, D>>::Node>,
+    driver_ctx: &DriverCtx<D>,
+) -> (Vec<Patch<D>>, Option<<V as FrameView<'static, D>>::Node>)
+where
+    D: Driver,
+    V: for<'b> FrameView<'b, D>,
+{
+    let ancestor = ancestor as *mut Option<<V as FrameView<'static, D>>::Node>;
+    let ancestor = &mut *ancestor.cast::<Option<<V as FrameView<'a, D>>::Node>>();
+    let mut curr = Some(view.view(frame));
+    let patches = patch::diff(driver_ctx, &mut curr, ancestor);
+    let curr = ManuallyDrop::new(curr);
+    let curr = &*curr as *const Option<<V as FrameView<'a, D>>::Node>;
+    let curr = ptr::read(curr.cast::<Option<<V as FrameView<'static, D>>::Node>>());
+    (patches, curr)
+}
+
+impl<D, V> Drop for FrameLoop<D, V>
+where
+    D: Driver,
+    V: for<'a> FrameView<'a, D>,
+{
+    fn drop(&mut self) {
+        // The tree borrows from a frame, so it's dropped first.
+        self.ancestor = None;
+        for frame in &self.frames {
+            // Safety: The frames were leaked by `new`, and nothing borrows
+            // from them anymore.
+            unsafe { drop(Box::from_raw(frame.as_ptr())) };
+        }
+    }
+}
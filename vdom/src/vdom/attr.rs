@@ -1,6 +1,10 @@
-use crate::driver::Driver;
+use crate::{
+    driver::Driver,
+    vdom::intern::{Interner, Name},
+};
+use std::{borrow::Cow, fmt, iter::FromIterator, rc::Rc};
 
-#[derive(Clone, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub enum AttrValue {
     True,
     Null,
@@ -56,7 +60,37 @@ impl From<Option<String>> for AttrValue {
     }
 }
 
-#[derive(Clone, Eq, PartialEq)]
+/// The value of the `contenteditable` attribute. Unlike for boolean
+/// attributes, `false` is rendered rather than omitted, since an omitted
+/// `contenteditable` inherits the parent's state instead. Use an `Option` to
+/// omit it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ContentEditable {
+    True,
+    False,
+    PlaintextOnly,
+}
+
+impl From<ContentEditable> for AttrValue {
+    fn from(v: ContentEditable) -> AttrValue {
+        AttrValue::Str(match v {
+            ContentEditable::True => "true",
+            ContentEditable::False => "false",
+            ContentEditable::PlaintextOnly => "plaintext-only",
+        })
+    }
+}
+
+impl From<Option<ContentEditable>> for AttrValue {
+    fn from(v: Option<ContentEditable>) -> AttrValue {
+        match v {
+            Some(v) => v.into(),
+            None => AttrValue::Null,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub enum AttrRefValue<'a> {
     True,
     Null,
@@ -74,16 +108,110 @@ impl<'a> From<&'a AttrValue> for AttrRefValue<'a> {
     }
 }
 
+/// The namespace of an attribute such as SVG's `xlink:href`, given as the
+/// prefix to render and the namespace URI to set it under.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct AttrNs {
+    pub prefix: &'static str,
+    pub uri: &'static str,
+}
+
+impl AttrNs {
+    pub const XLINK: AttrNs = AttrNs {
+        prefix: "xlink",
+        uri: "http://www.w3.org/1999/xlink",
+    };
+    pub const XML: AttrNs = AttrNs {
+        prefix: "xml",
+        uri: "http://www.w3.org/XML/1998/namespace",
+    };
+}
+
 pub trait Attr<D>
 where
     D: Driver,
 {
+    /// Whether the name and value are known from the type alone, see
+    /// `AttrList::IS_STATIC`.
+    const IS_STATIC: bool = false;
+
     fn is_value_static(&self) -> bool;
     fn name(&self) -> &str;
     fn value(&self) -> AttrRefValue<'_>;
     fn driver_store(&mut self) -> &mut D::AttrStore;
+
+    /// Whether the driver should set the DOM property of this name instead of
+    /// the HTML attribute, e.g. the current `value` of an `<input>`.
+    fn is_prop(&self) -> bool {
+        false
+    }
+
+    /// The event handler to attach under the event type `name()`, if any.
+    fn listener(&self) -> Option<&Rc<dyn Fn(D::Event)>> {
+        None
+    }
+
+    /// The namespace of this attribute, in which case `name()` is the local
+    /// name without the prefix.
+    fn namespace(&self) -> Option<AttrNs> {
+        None
+    }
+
+    /// The tokens of a space-separated list such as `class`, in which case
+    /// `value()` is the tokens joined by spaces. Drivers may then update only
+    /// the tokens that changed, see `TokenDiff`.
+    fn tokens(&self) -> Option<&[Cow<'static, str>]> {
+        None
+    }
 }
 
+/// Writes `attr` as in an HTML start tag, e.g. `class="x"`, for `Debug`
+/// output. Values are not escaped.
+pub(crate) fn fmt_attr<D, A>(attr: &A, f: &mut fmt::Formatter<'_>) -> fmt::Result
+where
+    D: Driver,
+    A: Attr<D> + ?Sized,
+{
+    if let Some(ns) = attr.namespace() {
+        write!(f, "{}:", ns.prefix)?;
+    }
+    f.write_str(attr.name())?;
+    if attr.listener().is_some() {
+        return f.write_str("=<listener>");
+    }
+    match attr.value() {
+        AttrRefValue::True => Ok(()),
+        AttrRefValue::Null => f.write_str("=null"),
+        AttrRefValue::Str(value) => write!(f, "=\"{}\"", value),
+    }
+}
+
+macro_rules! attr_debug {
+    ($($name:ident),*) => {
+        $(
+            impl<D> fmt::Debug for $name<D>
+            where
+                D: Driver,
+            {
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    fmt_attr(self, f)
+                }
+            }
+        )*
+    };
+}
+
+attr_debug!(
+    AttrTrue,
+    AttrStr,
+    AttrDyn,
+    AttrProp,
+    AttrListener,
+    TokenList,
+    ClassList,
+    DynAttr
+);
+
 pub struct AttrTrue<D>
 where
     D: Driver,
@@ -108,6 +236,8 @@ impl<D> Attr<D> for AttrTrue<D>
 where
     D: Driver,
 {
+    const IS_STATIC: bool = true;
+
     fn is_value_static(&self) -> bool {
         true
     }
@@ -131,6 +261,7 @@ where
 {
     key: &'static str,
     value: &'static str,
+    ns: Option<AttrNs>,
     driver_store: D::AttrStore,
 }
 
@@ -142,15 +273,23 @@ where
         AttrStr {
             key,
             value,
+            ns: None,
             driver_store: D::new_attr_store(),
         }
     }
+
+    pub fn ns(mut self, ns: AttrNs) -> AttrStr<D> {
+        self.ns = Some(ns);
+        self
+    }
 }
 
 impl<D> Attr<D> for AttrStr<D>
 where
     D: Driver,
 {
+    const IS_STATIC: bool = true;
+
     fn is_value_static(&self) -> bool {
         true
     }
@@ -166,14 +305,19 @@ where
     fn driver_store(&mut self) -> &mut D::AttrStore {
         &mut self.driver_store
     }
+
+    fn namespace(&self) -> Option<AttrNs> {
+        self.ns
+    }
 }
 
 pub struct AttrDyn<D>
 where
     D: Driver,
 {
-    key: &'static str,
+    key: Name,
     value: AttrValue,
+    ns: Option<AttrNs>,
     driver_store: D::AttrStore,
 }
 
@@ -181,16 +325,31 @@ impl<D> AttrDyn<D>
 where
     D: Driver,
 {
-    pub fn new<V>(key: &'static str, value: V) -> AttrDyn<D>
+    pub fn new<K, V>(key: K, value: V) -> AttrDyn<D>
     where
+        K: Into<Name>,
         V: Into<AttrValue>,
     {
         AttrDyn {
-            key,
+            key: key.into(),
             value: value.into(),
+            ns: None,
             driver_store: D::new_attr_store(),
         }
     }
+
+    pub fn ns(mut self, ns: AttrNs) -> AttrDyn<D> {
+        self.ns = Some(ns);
+        self
+    }
+
+    /// Like `new`, for names only known at runtime, e.g. keys of a map.
+    pub fn interned<V>(interner: &mut Interner, key: &str, value: V) -> AttrDyn<D>
+    where
+        V: Into<AttrValue>,
+    {
+        AttrDyn::new(interner.intern(key), value)
+    }
 }
 
 impl<D> Attr<D> for AttrDyn<D>
@@ -201,6 +360,56 @@ where
         false
     }
 
+    fn name(&self) -> &str {
+        &self.key
+    }
+
+    fn value(&self) -> AttrRefValue<'_> {
+        (&self.value).into()
+    }
+
+    fn driver_store(&mut self) -> &mut D::AttrStore {
+        &mut self.driver_store
+    }
+
+    fn namespace(&self) -> Option<AttrNs> {
+        self.ns
+    }
+}
+
+pub struct AttrProp<D>
+where
+    D: Driver,
+{
+    key: &'static str,
+    value: AttrValue,
+    driver_store: D::AttrStore,
+}
+
+impl<D> AttrProp<D>
+where
+    D: Driver,
+{
+    pub fn new<V>(key: &'static str, value: V) -> AttrProp<D>
+    where
+        V: Into<AttrValue>,
+    {
+        AttrProp {
+            key,
+            value: value.into(),
+            driver_store: D::new_attr_store(),
+        }
+    }
+}
+
+impl<D> Attr<D> for AttrProp<D>
+where
+    D: Driver,
+{
+    fn is_value_static(&self) -> bool {
+        false
+    }
+
     fn name(&self) -> &str {
         self.key
     }
@@ -212,6 +421,301 @@ where
     fn driver_store(&mut self) -> &mut D::AttrStore {
         &mut self.driver_store
     }
+
+    fn is_prop(&self) -> bool {
+        true
+    }
+}
+
+pub struct AttrListener<D>
+where
+    D: Driver,
+{
+    event: &'static str,
+    handler: Rc<dyn Fn(D::Event)>,
+    driver_store: D::AttrStore,
+}
+
+impl<D> AttrListener<D>
+where
+    D: Driver,
+{
+    pub fn new<F>(event: &'static str, handler: F) -> AttrListener<D>
+    where
+        F: Fn(D::Event) + 'static,
+    {
+        AttrListener {
+            event,
+            handler: Rc::new(handler),
+            driver_store: D::new_attr_store(),
+        }
+    }
+
+    /// Like `new`, handing `handler` the event converted to the type of its
+    /// parameter, e.g. a wrapper of a driver that extracts the payload.
+    /// Events that don't convert are ignored.
+    pub fn typed<E, F>(event: &'static str, handler: F) -> AttrListener<D>
+    where
+        E: FromEvent<D>,
+        F: Fn(E) + 'static,
+    {
+        AttrListener::new(event, move |event| {
+            if let Some(event) = E::from_event(event) {
+                handler(event)
+            }
+        })
+    }
+}
+
+/// An event that listeners can take instead of the `Driver::Event` it's
+/// converted from, see `AttrListener::typed`.
+pub trait FromEvent<D>: Sized
+where
+    D: Driver,
+{
+    fn from_event(event: D::Event) -> Option<Self>;
+}
+
+impl<D> Attr<D> for AttrListener<D>
+where
+    D: Driver,
+{
+    fn is_value_static(&self) -> bool {
+        false
+    }
+
+    fn name(&self) -> &str {
+        self.event
+    }
+
+    fn value(&self) -> AttrRefValue<'_> {
+        AttrRefValue::Null
+    }
+
+    fn driver_store(&mut self) -> &mut D::AttrStore {
+        &mut self.driver_store
+    }
+
+    fn listener(&self) -> Option<&Rc<dyn Fn(D::Event)>> {
+        Some(&self.handler)
+    }
+}
+
+/// An attribute whose value is a list of space-separated tokens, such as
+/// `class`, `rel` or `sandbox`. The attribute is omitted while the list is
+/// empty.
+pub struct TokenList<D>
+where
+    D: Driver,
+{
+    key: &'static str,
+    tokens: Vec<Cow<'static, str>>,
+    /// The tokens joined by spaces.
+    value: String,
+    driver_store: D::AttrStore,
+}
+
+impl<D> TokenList<D>
+where
+    D: Driver,
+{
+    pub fn new(key: &'static str) -> TokenList<D> {
+        TokenList {
+            key,
+            tokens: Vec::new(),
+            value: String::new(),
+            driver_store: D::new_attr_store(),
+        }
+    }
+
+    /// Appends `token` unless the list already contains it. A token can't
+    /// be empty or contain whitespace, so `token` is split at whitespace
+    /// into the tokens it's made of, e.g. `"a b"` into `a` and `b`.
+    pub fn with<T>(mut self, token: T) -> TokenList<D>
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        let token = token.into();
+        if !token.is_empty() && !token.bytes().any(|b| b.is_ascii_whitespace()) {
+            self.push(token);
+            return self;
+        }
+        match token {
+            Cow::Borrowed(tokens) => {
+                for token in tokens.split_ascii_whitespace() {
+                    self.push(Cow::Borrowed(token));
+                }
+            }
+            Cow::Owned(tokens) => {
+                for token in tokens.split_ascii_whitespace() {
+                    self.push(Cow::Owned(token.to_string()));
+                }
+            }
+        }
+        self
+    }
+
+    /// Appends `token` if `cond` holds, e.g. `with_if(selected, "selected")`.
+    pub fn with_if<T>(self, cond: bool, token: T) -> TokenList<D>
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        if cond {
+            self.with(token)
+        } else {
+            self
+        }
+    }
+
+    pub fn contains(&self, token: &str) -> bool {
+        self.tokens.iter().any(|t| t == token)
+    }
+
+    pub fn tokens(&self) -> &[Cow<'static, str>] {
+        &self.tokens
+    }
+
+    fn push(&mut self, token: Cow<'static, str>) {
+        if !self.contains(&token) {
+            if !self.value.is_empty() {
+                self.value.push(' ');
+            }
+            self.value.push_str(&token);
+            // Plain `push` would resolve to `AttrList::push`.
+            Vec::push(&mut self.tokens, token);
+        }
+    }
+}
+
+impl<D> Attr<D> for TokenList<D>
+where
+    D: Driver,
+{
+    fn is_value_static(&self) -> bool {
+        false
+    }
+
+    fn name(&self) -> &str {
+        self.key
+    }
+
+    fn value(&self) -> AttrRefValue<'_> {
+        if self.tokens.is_empty() {
+            AttrRefValue::Null
+        } else {
+            AttrRefValue::Str(&self.value)
+        }
+    }
+
+    fn driver_store(&mut self) -> &mut D::AttrStore {
+        &mut self.driver_store
+    }
+
+    fn tokens(&self) -> Option<&[Cow<'static, str>]> {
+        Some(&self.tokens)
+    }
+}
+
+/// The `class` attribute as a `TokenList`, e.g.
+/// `ClassList::new().with("card").with_if(selected, "selected")`.
+pub struct ClassList<D>(TokenList<D>)
+where
+    D: Driver;
+
+impl<D> ClassList<D>
+where
+    D: Driver,
+{
+    pub fn new() -> ClassList<D> {
+        ClassList(TokenList::new("class"))
+    }
+
+    /// See `TokenList::with`.
+    pub fn with<T>(self, token: T) -> ClassList<D>
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        ClassList(self.0.with(token))
+    }
+
+    /// See `TokenList::with_if`.
+    pub fn with_if<T>(self, cond: bool, token: T) -> ClassList<D>
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        ClassList(self.0.with_if(cond, token))
+    }
+
+    pub fn contains(&self, token: &str) -> bool {
+        self.0.contains(token)
+    }
+
+    pub fn tokens(&self) -> &[Cow<'static, str>] {
+        self.0.tokens()
+    }
+}
+
+impl<D> Default for ClassList<D>
+where
+    D: Driver,
+{
+    fn default() -> ClassList<D> {
+        ClassList::new()
+    }
+}
+
+impl<D> Attr<D> for ClassList<D>
+where
+    D: Driver,
+{
+    fn is_value_static(&self) -> bool {
+        false
+    }
+
+    fn name(&self) -> &str {
+        self.0.name()
+    }
+
+    fn value(&self) -> AttrRefValue<'_> {
+        self.0.value()
+    }
+
+    fn driver_store(&mut self) -> &mut D::AttrStore {
+        self.0.driver_store()
+    }
+
+    fn tokens(&self) -> Option<&[Cow<'static, str>]> {
+        Some(self.0.tokens())
+    }
+}
+
+/// The tokens to add and remove to turn the list `ancestor` into `curr`, in
+/// the order of the lists. Only the order changing needs no update.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TokenDiff<'a> {
+    pub added: Vec<&'a str>,
+    pub removed: Vec<&'a str>,
+}
+
+impl<'a> TokenDiff<'a> {
+    pub fn new(curr: &'a [Cow<'static, str>], ancestor: &'a [Cow<'static, str>]) -> TokenDiff<'a> {
+        TokenDiff {
+            added: curr
+                .iter()
+                .filter(|token| !ancestor.contains(token))
+                .map(|token| token.as_ref())
+                .collect(),
+            removed: ancestor
+                .iter()
+                .filter(|token| !curr.contains(token))
+                .map(|token| token.as_ref())
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
 }
 
 pub trait AttrVisitor<D>
@@ -234,6 +738,14 @@ where
     fn on_diff<A>(&mut self, curr: &mut A, ancestor: &mut A) -> Result<(), Self::Err>
     where
         A: Attr<D>;
+
+    fn on_added<A>(&mut self, curr: &mut A) -> Result<(), Self::Err>
+    where
+        A: Attr<D>;
+
+    fn on_removed<A>(&mut self, ancestor: &mut A) -> Result<(), Self::Err>
+    where
+        A: Attr<D>;
 }
 
 pub trait AttrList<D>
@@ -241,6 +753,10 @@ where
     Self: Sized,
     D: Driver,
 {
+    /// Whether every attribute of this list is known from its type alone,
+    /// see `Node::IS_STATIC`.
+    const IS_STATIC: bool = false;
+
     fn visit<AV>(&mut self, visitor: &mut AV) -> Result<(), AV::Err>
     where
         AV: AttrVisitor<D>;
@@ -249,6 +765,13 @@ where
     where
         AD: AttrDiffer<D>;
 
+    /// Writes the attributes as in an HTML start tag, each preceded by a
+    /// space, for the `Debug` output of tags. Lists that don't override it
+    /// are written as ` ..`.
+    fn fmt_attrs(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(" ..")
+    }
+
     fn push<A>(self, attr: A) -> (Self, AttrListEntry<A>)
     where
         A: Attr<D>,
@@ -263,6 +786,8 @@ where
     L1: AttrList<D>,
     L2: AttrList<D>,
 {
+    const IS_STATIC: bool = L1::IS_STATIC && L2::IS_STATIC;
+
     fn visit<AV>(&mut self, visitor: &mut AV) -> Result<(), AV::Err>
     where
         AV: AttrVisitor<D>,
@@ -278,12 +803,19 @@ where
         self.0.diff(&mut ancestor.0, differ)?;
         self.1.diff(&mut ancestor.1, differ)
     }
+
+    fn fmt_attrs(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt_attrs(f)?;
+        self.1.fmt_attrs(f)
+    }
 }
 
 impl<D> AttrList<D> for ()
 where
     D: Driver,
 {
+    const IS_STATIC: bool = true;
+
     fn visit<AV>(&mut self, _visitor: &mut AV) -> Result<(), AV::Err>
     where
         AV: AttrVisitor<D>,
@@ -297,15 +829,259 @@ where
     {
         Ok(())
     }
+
+    fn fmt_attrs(&self, _f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Ok(())
+    }
+}
+
+/// Attributes whose names are only known at runtime. Unlike the positional
+/// tuple lists, entries are matched up by name when diffing.
+impl<D, A> AttrList<D> for Vec<A>
+where
+    D: Driver,
+    A: Attr<D>,
+{
+    fn visit<AV>(&mut self, visitor: &mut AV) -> Result<(), AV::Err>
+    where
+        AV: AttrVisitor<D>,
+    {
+        for attr in self {
+            visitor.on_attr(attr)?;
+        }
+        Ok(())
+    }
+
+    fn diff<AD>(&mut self, ancestor: &mut Self, differ: &mut AD) -> Result<(), AD::Err>
+    where
+        AD: AttrDiffer<D>,
+    {
+        // Entries are matched by name, since a runtime list may declare the
+        // same attributes in a different order between renders. The entry at
+        // the same position is checked first, which covers the common case of
+        // an unchanged order without searching.
+        let mut matched = vec![false; ancestor.len()];
+        for (i, curr) in self.iter_mut().enumerate() {
+            let pos = match ancestor.get(i) {
+                Some(same) if !matched[i] && same.name() == curr.name() => Some(i),
+                _ => {
+                    ancestor
+                        .iter()
+                        .enumerate()
+                        .position(|(j, ancestor)| !matched[j] && ancestor.name() == curr.name())
+                }
+            };
+            match pos {
+                Some(pos) => {
+                    matched[pos] = true;
+                    differ.on_diff(curr, &mut ancestor[pos])?;
+                }
+                None => differ.on_added(curr)?,
+            }
+        }
+        for (ancestor, matched) in ancestor.iter_mut().zip(matched) {
+            if !matched {
+                differ.on_removed(ancestor)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn fmt_attrs(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for attr in self {
+            f.write_str(" ")?;
+            fmt_attr(attr, f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Attributes given as pairs of names and values at runtime, e.g. from a map
+/// loaded from a CMS. Attributes are matched up by name when diffing, as for
+/// `Vec`. A name given more than once takes the last value, and is listed
+/// by `duplicates`, e.g. to warn about data with repeated names.
+pub struct DynAttrs<D>
+where
+    D: Driver,
+{
+    attrs: Vec<DynAttr<D>>,
+    duplicates: Vec<Cow<'static, str>>,
+}
+
+impl<D> DynAttrs<D>
+where
+    D: Driver,
+{
+    pub fn new() -> DynAttrs<D> {
+        DynAttrs {
+            attrs: Vec::new(),
+            duplicates: Vec::new(),
+        }
+    }
+
+    /// Sets `name` to `value`, replacing an earlier value of it.
+    pub fn set<K, V>(mut self, name: K, value: V) -> DynAttrs<D>
+    where
+        K: Into<Cow<'static, str>>,
+        V: Into<Cow<'static, str>>,
+    {
+        self.insert(name.into(), value.into());
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.attrs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.attrs.is_empty()
+    }
+
+    /// The names that were given more than once, in the order they were
+    /// first repeated.
+    pub fn duplicates(&self) -> &[Cow<'static, str>] {
+        &self.duplicates
+    }
+
+    fn insert(&mut self, name: Cow<'static, str>, value: Cow<'static, str>) {
+        match self.attrs.iter_mut().find(|attr| attr.name == name) {
+            Some(attr) => {
+                if !self.duplicates.contains(&name) {
+                    self.duplicates.push(name);
+                }
+                attr.value = value;
+            }
+            None => {
+                // Plain `push` would resolve to `AttrList::push`.
+                Vec::push(
+                    &mut self.attrs,
+                    DynAttr {
+                        name,
+                        value,
+                        driver_store: D::new_attr_store(),
+                    },
+                );
+            }
+        }
+    }
+}
+
+impl<D> Default for DynAttrs<D>
+where
+    D: Driver,
+{
+    fn default() -> DynAttrs<D> {
+        DynAttrs::new()
+    }
+}
+
+impl<D, K, V> FromIterator<(K, V)> for DynAttrs<D>
+where
+    D: Driver,
+    K: Into<Cow<'static, str>>,
+    V: Into<Cow<'static, str>>,
+{
+    fn from_iter<I>(iter: I) -> DynAttrs<D>
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let mut attrs = DynAttrs::new();
+        for (name, value) in iter {
+            attrs.insert(name.into(), value.into());
+        }
+        attrs
+    }
+}
+
+impl<D> From<Vec<(Cow<'static, str>, Cow<'static, str>)>> for DynAttrs<D>
+where
+    D: Driver,
+{
+    fn from(pairs: Vec<(Cow<'static, str>, Cow<'static, str>)>) -> DynAttrs<D> {
+        pairs.into_iter().collect()
+    }
+}
+
+impl<D> fmt::Debug for DynAttrs<D>
+where
+    D: Driver,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(&self.attrs).finish()
+    }
+}
+
+impl<D> AttrList<D> for DynAttrs<D>
+where
+    D: Driver,
+{
+    fn visit<AV>(&mut self, visitor: &mut AV) -> Result<(), AV::Err>
+    where
+        AV: AttrVisitor<D>,
+    {
+        self.attrs.visit(visitor)
+    }
+
+    fn diff<AD>(&mut self, ancestor: &mut Self, differ: &mut AD) -> Result<(), AD::Err>
+    where
+        AD: AttrDiffer<D>,
+    {
+        self.attrs.diff(&mut ancestor.attrs, differ)
+    }
+
+    fn fmt_attrs(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.attrs.fmt_attrs(f)
+    }
+}
+
+struct DynAttr<D>
+where
+    D: Driver,
+{
+    name: Cow<'static, str>,
+    value: Cow<'static, str>,
+    driver_store: D::AttrStore,
+}
+
+impl<D> Attr<D> for DynAttr<D>
+where
+    D: Driver,
+{
+    fn is_value_static(&self) -> bool {
+        false
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn value(&self) -> AttrRefValue<'_> {
+        AttrRefValue::Str(&self.value)
+    }
+
+    fn driver_store(&mut self) -> &mut D::AttrStore {
+        &mut self.driver_store
+    }
 }
 
 pub struct AttrListEntry<A>(pub A);
 
+impl<A> fmt::Debug for AttrListEntry<A>
+where
+    A: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
 impl<A, D> AttrList<D> for AttrListEntry<A>
 where
     A: Attr<D>,
     D: Driver,
 {
+    const IS_STATIC: bool = A::IS_STATIC;
+
     fn visit<AV>(&mut self, visitor: &mut AV) -> Result<(), AV::Err>
     where
         AV: AttrVisitor<D>,
@@ -329,4 +1105,9 @@ where
 
         differ.on_diff(&mut self.0, &mut ancestor.0)
     }
+
+    fn fmt_attrs(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(" ")?;
+        fmt_attr(&self.0, f)
+    }
 }
@@ -0,0 +1,603 @@
+use super::{
+    attr::{Attr, AttrRefValue, AttrVisitor},
+    dom::qualified_name,
+    element_info::{is_inline, preserves_whitespace, raw_text_content},
+    node::{Comp, CompNode, Node, NodeVisitor, Tag, Text},
+    path::Path,
+};
+use crate::driver::{Driver, DriverCtx};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    convert::Infallible,
+    mem,
+};
+
+/// Collects the names of the attributes used across all elements of a tree,
+/// and optionally the class names they use, e.g. to audit markup or to find
+/// the CSS a page needs. Components are rendered using `driver_ctx`.
+pub struct AttrNameCollector<'a, D>
+where
+    D: Driver,
+{
+    driver_ctx: &'a DriverCtx<D>,
+    names: BTreeSet<String>,
+    class_names: Option<BTreeSet<String>>,
+}
+
+impl<'a, D> AttrNameCollector<'a, D>
+where
+    D: Driver,
+{
+    pub fn new(driver_ctx: &'a DriverCtx<D>) -> AttrNameCollector<'a, D> {
+        AttrNameCollector {
+            driver_ctx,
+            names: BTreeSet::new(),
+            class_names: None,
+        }
+    }
+
+    /// Also collects the class names in `class` attributes.
+    pub fn with_class_names(mut self) -> AttrNameCollector<'a, D> {
+        self.class_names = Some(BTreeSet::new());
+        self
+    }
+
+    /// The attribute names seen so far, sorted and without duplicates.
+    pub fn names(&self) -> Vec<String> {
+        self.names.iter().cloned().collect()
+    }
+
+    /// The class names seen so far, sorted and without duplicates. Empty
+    /// unless enabled with `with_class_names`.
+    pub fn class_names(&self) -> Vec<String> {
+        self.class_names.iter().flatten().cloned().collect()
+    }
+}
+
+impl<'a, D> NodeVisitor<D> for AttrNameCollector<'a, D>
+where
+    D: Driver,
+{
+    type Err = Infallible;
+
+    fn on_tag<T>(&mut self, _index: usize, tag: &mut T) -> Result<(), Infallible>
+    where
+        T: Tag<D>,
+    {
+        tag.visit_attrs(self)?;
+        tag.visit_children(self)
+    }
+
+    fn on_text<T>(&mut self, _index: usize, _text: &mut T) -> Result<(), Infallible>
+    where
+        T: Text<D>,
+    {
+        Ok(())
+    }
+
+    fn on_comp<C>(&mut self, index: &mut usize, comp: &mut CompNode<D, C>) -> Result<(), Infallible>
+    where
+        C: Comp<D>,
+    {
+        if comp.comp_ctx().is_none() {
+            comp.init_comp_ctx(self.driver_ctx.clone());
+        }
+        comp.visit_rendered(index, self)
+    }
+
+    fn driver_ctx(&self) -> Option<DriverCtx<D>> {
+        Some(self.driver_ctx.clone())
+    }
+}
+
+impl<'a, D> AttrVisitor<D> for AttrNameCollector<'a, D>
+where
+    D: Driver,
+{
+    type Err = Infallible;
+
+    fn on_attr<A>(&mut self, attr: &mut A) -> Result<(), Infallible>
+    where
+        A: Attr<D>,
+    {
+        // Listeners and null values don't end up as attributes in the DOM.
+        if attr.listener().is_some() {
+            return Ok(());
+        }
+        let value = attr.value();
+        if value == AttrRefValue::Null {
+            return Ok(());
+        }
+        if let (Some(class_names), "class", AttrRefValue::Str(classes)) =
+            (&mut self.class_names, attr.name(), value)
+        {
+            class_names.extend(classes.split_whitespace().map(str::to_string));
+        }
+        self.names.insert(qualified_name(attr));
+        Ok(())
+    }
+}
+
+/// How urgently a live region's updates are announced.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Politeness {
+    Polite,
+    Assertive,
+}
+
+/// An element with `aria-live`, and its text as it would be announced.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LiveRegion {
+    pub politeness: Politeness,
+    pub text: String,
+}
+
+/// Collects the text of the `aria-live` regions of a tree in document order,
+/// e.g. to assert what a screen reader would announce after an update.
+/// Regions with `aria-live="off"` are skipped, and whitespace in the text is
+/// collapsed. Components are rendered using `driver_ctx`.
+pub struct LiveRegionCollector<'a, D>
+where
+    D: Driver,
+{
+    driver_ctx: &'a DriverCtx<D>,
+    regions: Vec<LiveRegion>,
+    /// Indices into `regions` of the regions being visited, which may nest.
+    open: Vec<usize>,
+}
+
+impl<'a, D> LiveRegionCollector<'a, D>
+where
+    D: Driver,
+{
+    pub fn new(driver_ctx: &'a DriverCtx<D>) -> LiveRegionCollector<'a, D> {
+        LiveRegionCollector {
+            driver_ctx,
+            regions: Vec::new(),
+            open: Vec::new(),
+        }
+    }
+
+    pub fn regions(&self) -> &[LiveRegion] {
+        &self.regions
+    }
+}
+
+impl<'a, D> NodeVisitor<D> for LiveRegionCollector<'a, D>
+where
+    D: Driver,
+{
+    type Err = Infallible;
+
+    fn on_tag<T>(&mut self, _index: usize, tag: &mut T) -> Result<(), Infallible>
+    where
+        T: Tag<D>,
+    {
+        let mut finder = PolitenessFinder { politeness: None };
+        tag.visit_attrs(&mut finder)?;
+        let politeness = match finder.politeness {
+            Some(politeness) => politeness,
+            None => return tag.visit_children(self),
+        };
+
+        let region = self.regions.len();
+        self.regions.push(LiveRegion {
+            politeness,
+            text: String::new(),
+        });
+        self.open.push(region);
+        tag.visit_children(self)?;
+        self.open.pop();
+
+        let text = &mut self.regions[region].text;
+        *text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        Ok(())
+    }
+
+    fn on_text<T>(&mut self, _index: usize, text: &mut T) -> Result<(), Infallible>
+    where
+        T: Text<D>,
+    {
+        if text.is_comment() {
+            return Ok(());
+        }
+        for &region in &self.open {
+            self.regions[region].text.push_str(text.get());
+        }
+        Ok(())
+    }
+
+    fn on_comp<C>(&mut self, index: &mut usize, comp: &mut CompNode<D, C>) -> Result<(), Infallible>
+    where
+        C: Comp<D>,
+    {
+        if comp.comp_ctx().is_none() {
+            comp.init_comp_ctx(self.driver_ctx.clone());
+        }
+        comp.visit_rendered(index, self)
+    }
+
+    fn driver_ctx(&self) -> Option<DriverCtx<D>> {
+        Some(self.driver_ctx.clone())
+    }
+}
+
+struct PolitenessFinder {
+    politeness: Option<Politeness>,
+}
+
+impl<D> AttrVisitor<D> for PolitenessFinder
+where
+    D: Driver,
+{
+    type Err = Infallible;
+
+    fn on_attr<A>(&mut self, attr: &mut A) -> Result<(), Infallible>
+    where
+        A: Attr<D>,
+    {
+        if attr.name() != "aria-live" {
+            return Ok(());
+        }
+        self.politeness = match attr.value() {
+            AttrRefValue::Str("polite") => Some(Politeness::Polite),
+            AttrRefValue::Str("assertive") => Some(Politeness::Assertive),
+            _ => None,
+        };
+        Ok(())
+    }
+}
+
+/// An `id` used by more than one element, and the paths of those elements in
+/// document order.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DuplicateId {
+    pub id: String,
+    pub paths: Vec<Path>,
+}
+
+/// Finds `id` attributes that aren't unique within a tree, e.g. to assert in
+/// tests that a page is a valid document. Components are rendered using
+/// `driver_ctx`.
+pub struct IdUniquenessValidator<'a, D>
+where
+    D: Driver,
+{
+    driver_ctx: &'a DriverCtx<D>,
+    ids: BTreeMap<String, Vec<Path>>,
+    path: Path,
+}
+
+impl<'a, D> IdUniquenessValidator<'a, D>
+where
+    D: Driver,
+{
+    pub fn new(driver_ctx: &'a DriverCtx<D>) -> IdUniquenessValidator<'a, D> {
+        IdUniquenessValidator {
+            driver_ctx,
+            ids: BTreeMap::new(),
+            path: Path::root(),
+        }
+    }
+
+    /// The ids seen more than once so far, sorted by id.
+    pub fn duplicates(&self) -> Vec<DuplicateId> {
+        self.ids
+            .iter()
+            .filter(|(_, paths)| paths.len() > 1)
+            .map(|(id, paths)| {
+                DuplicateId {
+                    id: id.clone(),
+                    paths: paths.clone(),
+                }
+            })
+            .collect()
+    }
+}
+
+impl<'a, D> NodeVisitor<D> for IdUniquenessValidator<'a, D>
+where
+    D: Driver,
+{
+    type Err = Infallible;
+
+    fn on_tag<T>(&mut self, index: usize, tag: &mut T) -> Result<(), Infallible>
+    where
+        T: Tag<D>,
+    {
+        self.path.push(index);
+        let mut finder = IdFinder { id: None };
+        tag.visit_attrs(&mut finder)?;
+        if let Some(id) = finder.id {
+            self.ids.entry(id).or_default().push(self.path.clone());
+        }
+        tag.visit_children(self)?;
+        self.path.pop();
+        Ok(())
+    }
+
+    fn on_text<T>(&mut self, _index: usize, _text: &mut T) -> Result<(), Infallible>
+    where
+        T: Text<D>,
+    {
+        Ok(())
+    }
+
+    fn on_comp<C>(&mut self, index: &mut usize, comp: &mut CompNode<D, C>) -> Result<(), Infallible>
+    where
+        C: Comp<D>,
+    {
+        if comp.comp_ctx().is_none() {
+            comp.init_comp_ctx(self.driver_ctx.clone());
+        }
+        comp.visit_rendered(index, self)
+    }
+
+    fn driver_ctx(&self) -> Option<DriverCtx<D>> {
+        Some(self.driver_ctx.clone())
+    }
+}
+
+struct IdFinder {
+    id: Option<String>,
+}
+
+impl<D> AttrVisitor<D> for IdFinder
+where
+    D: Driver,
+{
+    type Err = Infallible;
+
+    fn on_attr<A>(&mut self, attr: &mut A) -> Result<(), Infallible>
+    where
+        A: Attr<D>,
+    {
+        if let ("id", AttrRefValue::Str(id)) = (attr.name(), attr.value()) {
+            self.id = Some(id.to_string());
+        }
+        Ok(())
+    }
+}
+
+/// A node as handed to the function of `fold`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FoldNode<'a> {
+    Tag { path: &'a Path, tag: &'a str },
+    Text { path: &'a Path, text: &'a str },
+    Comment { path: &'a Path, text: &'a str },
+}
+
+impl<'a> FoldNode<'a> {
+    pub fn path(&self) -> &'a Path {
+        match self {
+            FoldNode::Tag { path, .. }
+            | FoldNode::Text { path, .. }
+            | FoldNode::Comment { path, .. } => path,
+        }
+    }
+}
+
+/// Combines the nodes of `root` in document order into one value, starting
+/// from `init`, like `Iterator::fold`, e.g. to count the elements with some
+/// tag or sum up the length of all text. Components are rendered using
+/// `driver_ctx`, and only their rendered nodes are handed to `f`.
+pub fn fold<D, N, B, F>(driver_ctx: &DriverCtx<D>, root: &mut N, init: B, f: F) -> B
+where
+    D: Driver,
+    N: Node<D> + ?Sized,
+    F: FnMut(B, FoldNode<'_>) -> B,
+{
+    let mut folder = Folder {
+        driver_ctx,
+        acc: Some(init),
+        f,
+        path: Path::root(),
+    };
+    match root.visit(&mut 0, &mut folder) {
+        Ok(()) => folder.acc.expect("fold accumulator is always put back"),
+        Err(never) => match never {},
+    }
+}
+
+struct Folder<'a, D, B, F>
+where
+    D: Driver,
+{
+    driver_ctx: &'a DriverCtx<D>,
+    /// Only `None` while `f` runs.
+    acc: Option<B>,
+    f: F,
+    path: Path,
+}
+
+impl<'a, D, B, F> Folder<'a, D, B, F>
+where
+    D: Driver,
+    F: FnMut(B, FoldNode<'_>) -> B,
+{
+    /// Takes the fields rather than `self`, so `node` can borrow `path`.
+    fn apply(acc: &mut Option<B>, f: &mut F, node: FoldNode<'_>) {
+        let prev = acc.take().expect("fold accumulator is always put back");
+        *acc = Some(f(prev, node));
+    }
+}
+
+impl<'a, D, B, F> NodeVisitor<D> for Folder<'a, D, B, F>
+where
+    D: Driver,
+    F: FnMut(B, FoldNode<'_>) -> B,
+{
+    type Err = Infallible;
+
+    fn on_tag<T>(&mut self, index: usize, tag: &mut T) -> Result<(), Infallible>
+    where
+        T: Tag<D>,
+    {
+        // One path is grown and shrunk for the whole tree, rather than
+        // allocating one for each node.
+        self.path.push(index);
+        Self::apply(
+            &mut self.acc,
+            &mut self.f,
+            FoldNode::Tag {
+                path: &self.path,
+                tag: tag.tag(),
+            },
+        );
+        tag.visit_children(self)?;
+        self.path.pop();
+        Ok(())
+    }
+
+    fn on_text<T>(&mut self, index: usize, text: &mut T) -> Result<(), Infallible>
+    where
+        T: Text<D>,
+    {
+        self.path.push(index);
+        let node = if text.is_comment() {
+            FoldNode::Comment {
+                path: &self.path,
+                text: text.get(),
+            }
+        } else {
+            FoldNode::Text {
+                path: &self.path,
+                text: text.get(),
+            }
+        };
+        Self::apply(&mut self.acc, &mut self.f, node);
+        self.path.pop();
+        Ok(())
+    }
+
+    fn on_comp<C>(&mut self, index: &mut usize, comp: &mut CompNode<D, C>) -> Result<(), Infallible>
+    where
+        C: Comp<D>,
+    {
+        if comp.comp_ctx().is_none() {
+            comp.init_comp_ctx(self.driver_ctx.clone());
+        }
+        comp.visit_rendered(index, self)
+    }
+
+    fn driver_ctx(&self) -> Option<DriverCtx<D>> {
+        Some(self.driver_ctx.clone())
+    }
+}
+
+/// The text of `root` as it would read on a page, e.g. for search indexes or
+/// plain-text emails. Runs of whitespace are collapsed to a space, except
+/// within elements that preserve whitespace such as `<pre>`, and elements
+/// that aren't inline start on a new line. The content of `<script>` and
+/// `<style>` is left out. Components are rendered using `driver_ctx`.
+pub fn plain_text<D, N>(driver_ctx: &DriverCtx<D>, root: &mut N) -> String
+where
+    D: Driver,
+    N: Node<D> + ?Sized,
+{
+    let mut extractor = TextExtractor {
+        driver_ctx,
+        text: String::new(),
+        preserve: false,
+        skip: false,
+    };
+    match root.visit(&mut 0, &mut extractor) {
+        Ok(()) => extractor.text.trim_end().to_string(),
+        Err(never) => match never {},
+    }
+}
+
+struct TextExtractor<'a, D>
+where
+    D: Driver,
+{
+    driver_ctx: &'a DriverCtx<D>,
+    text: String,
+    /// Whether the text being visited preserves whitespace.
+    preserve: bool,
+    /// Whether the text being visited is raw text, which isn't displayed.
+    skip: bool,
+}
+
+impl<'a, D> TextExtractor<'a, D>
+where
+    D: Driver,
+{
+    fn break_line(&mut self) {
+        let text = self.text.trim_end_matches(' ');
+        self.text.truncate(text.len());
+        if !self.text.is_empty() && !self.text.ends_with('\n') {
+            self.text.push('\n');
+        }
+    }
+}
+
+impl<'a, D> NodeVisitor<D> for TextExtractor<'a, D>
+where
+    D: Driver,
+{
+    type Err = Infallible;
+
+    fn on_tag<T>(&mut self, _index: usize, tag: &mut T) -> Result<(), Infallible>
+    where
+        T: Tag<D>,
+    {
+        let name = tag.tag();
+        if name == "br" {
+            self.text.push('\n');
+            return Ok(());
+        }
+        let block = !is_inline(name);
+        let preserve = self.preserve || preserves_whitespace(name);
+        let skip = self.skip || raw_text_content(name);
+        if block {
+            self.break_line();
+        }
+        let outer = (
+            mem::replace(&mut self.preserve, preserve),
+            mem::replace(&mut self.skip, skip),
+        );
+        tag.visit_children(self)?;
+        self.preserve = outer.0;
+        self.skip = outer.1;
+        if block {
+            self.break_line();
+        }
+        Ok(())
+    }
+
+    fn on_text<T>(&mut self, _index: usize, text: &mut T) -> Result<(), Infallible>
+    where
+        T: Text<D>,
+    {
+        if self.skip || text.is_comment() {
+            return Ok(());
+        }
+        if self.preserve {
+            self.text.push_str(text.get());
+            return Ok(());
+        }
+        for c in text.get().chars() {
+            if !c.is_whitespace() {
+                self.text.push(c);
+            } else if !self.text.is_empty() && !self.text.ends_with(char::is_whitespace) {
+                self.text.push(' ');
+            }
+        }
+        Ok(())
+    }
+
+    fn on_comp<C>(&mut self, index: &mut usize, comp: &mut CompNode<D, C>) -> Result<(), Infallible>
+    where
+        C: Comp<D>,
+    {
+        if comp.comp_ctx().is_none() {
+            comp.init_comp_ctx(self.driver_ctx.clone());
+        }
+        comp.visit_rendered(index, self)
+    }
+
+    fn driver_ctx(&self) -> Option<DriverCtx<D>> {
+        Some(self.driver_ctx.clone())
+    }
+}
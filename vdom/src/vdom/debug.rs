@@ -0,0 +1,168 @@
+//! Keeps what a driver rendered recently, for inspecting a running app, e.g.
+//! from a devtools panel.
+
+use super::{
+    dom::{to_dom, DomNode},
+    node::Node,
+    patch::{diff, Patch},
+};
+use crate::driver::{Driver, DriverCtx};
+use std::{collections::VecDeque, fmt};
+
+/// The latest tree rendered by an app and the frames that led to it, of which
+/// only the most recent `capacity` are retained.
+pub struct DebugHistory<D>
+where
+    D: Driver,
+{
+    capacity: usize,
+    tree: Vec<DomNode<D>>,
+    frames: VecDeque<Frame<D>>,
+}
+
+/// A render, with the patches turning the previous tree into the rendered
+/// one and how long rendering took.
+pub struct Frame<D>
+where
+    D: Driver,
+{
+    pub patches: Vec<Patch<D>>,
+    pub duration_ms: f64,
+}
+
+impl<D> DebugHistory<D>
+where
+    D: Driver,
+{
+    /// Starts with `node` as the latest tree and no frames. A `capacity` of
+    /// zero still keeps the latest tree.
+    pub fn new<N>(driver_ctx: &DriverCtx<D>, node: &mut N, capacity: usize) -> DebugHistory<D>
+    where
+        N: Node<D> + ?Sized,
+    {
+        DebugHistory {
+            capacity,
+            tree: to_dom(driver_ctx, node),
+            frames: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Records that `node` was rendered in `duration_ms`, evicting the oldest
+    /// frame if `capacity` frames are retained already. The patches are
+    /// found by diffing copies of the trees, so components have to be
+    /// mounted, and those of `node` aren't touched.
+    pub fn record<N>(&mut self, driver_ctx: &DriverCtx<D>, node: &mut N, duration_ms: f64)
+    where
+        N: Node<D> + ?Sized,
+    {
+        let mut tree = to_dom(driver_ctx, node);
+        let patches = diff(driver_ctx, &mut tree, &mut self.tree);
+        self.tree = tree;
+        if self.capacity == 0 {
+            return;
+        }
+        if self.frames.len() == self.capacity {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(Frame {
+            patches,
+            duration_ms,
+        });
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn tree(&self) -> &[DomNode<D>] {
+        &self.tree
+    }
+
+    /// The retained frames, oldest first.
+    pub fn frames(&self) -> impl Iterator<Item = &Frame<D>> {
+        self.frames.iter()
+    }
+
+    pub fn snapshot(&self) -> DebugSnapshot<'_, D> {
+        DebugSnapshot {
+            tree: &self.tree,
+            patches: self.frames.back().map_or(&[], |frame| &frame.patches),
+            frame_times_ms: self.frames.iter().map(|frame| frame.duration_ms).collect(),
+            registries: Vec::new(),
+        }
+    }
+}
+
+/// The state of an app at one point, as returned by `DebugHistory::snapshot`.
+/// With the `serde_json` feature it can be converted to JSON with `to_json`.
+pub struct DebugSnapshot<'a, D>
+where
+    D: Driver,
+{
+    /// The latest tree, with components rendered.
+    pub tree: &'a [DomNode<D>],
+    /// The patches of the latest frame, which are empty before the first.
+    pub patches: &'a [Patch<D>],
+    /// How long the retained frames took to render, oldest first.
+    pub frame_times_ms: Vec<f64>,
+    /// The number of entries of registries the app uses, by name.
+    pub registries: Vec<(&'static str, usize)>,
+}
+
+impl<'a, D> DebugSnapshot<'a, D>
+where
+    D: Driver,
+{
+    /// Adds the size of a registry, e.g. `StyleRegistry::len`.
+    pub fn with_registry(mut self, name: &'static str, len: usize) -> DebugSnapshot<'a, D> {
+        self.registries.push((name, len));
+        self
+    }
+
+    /// Converts the snapshot to
+    /// `{"tree": .., "patches": .., "frameTimesMs": [..], "registries": {name: len}}`,
+    /// where the tree is as of `json::to_json` and the patches are as of
+    /// `json::json_patch`.
+    #[cfg(feature = "serde_json")]
+    pub fn to_json(&self) -> serde_json::Value {
+        use super::json::{json_patch, to_json};
+        use serde_json::{Map, Number, Value};
+
+        let mut snapshot = Map::new();
+        snapshot.insert("tree".to_string(), to_json(self.tree));
+        snapshot.insert("patches".to_string(), json_patch(self.patches));
+        snapshot.insert(
+            "frameTimesMs".to_string(),
+            Value::Array(
+                self.frame_times_ms
+                    .iter()
+                    .map(|&ms| Number::from_f64(ms).map_or(Value::Null, Value::Number))
+                    .collect(),
+            ),
+        );
+        snapshot.insert(
+            "registries".to_string(),
+            Value::Object(
+                self.registries
+                    .iter()
+                    .map(|&(name, len)| (name.to_string(), Value::Number((len as u64).into())))
+                    .collect(),
+            ),
+        );
+        Value::Object(snapshot)
+    }
+}
+
+impl<'a, D> fmt::Debug for DebugSnapshot<'a, D>
+where
+    D: Driver,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DebugSnapshot")
+            .field("tree", &self.tree)
+            .field("patches", &self.patches)
+            .field("frame_times_ms", &self.frame_times_ms)
+            .field("registries", &self.registries)
+            .finish()
+    }
+}
@@ -0,0 +1,148 @@
+use super::*;
+use crate::vdom::element_info::preserves_whitespace;
+
+/// What `nodes_eq` disregards when comparing trees, e.g. the formatting of a
+/// hand-written HTML fixture. By default only the order of attributes is
+/// disregarded, which never matters.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct EqOptions {
+    ignore_whitespace_text: bool,
+    collapse_whitespace: bool,
+    ignore_case: bool,
+}
+
+impl EqOptions {
+    pub fn new() -> EqOptions {
+        Default::default()
+    }
+
+    /// Skips text nodes that only contain whitespace, except within elements
+    /// that preserve whitespace such as `<pre>`.
+    pub fn ignore_whitespace_text(mut self) -> EqOptions {
+        self.ignore_whitespace_text = true;
+        self
+    }
+
+    /// Treats every run of whitespace in text as a single space, except
+    /// within elements that preserve whitespace.
+    pub fn collapse_whitespace(mut self) -> EqOptions {
+        self.collapse_whitespace = true;
+        self
+    }
+
+    /// Compares tag and attribute names case-insensitively, as HTML does.
+    pub fn ignore_case(mut self) -> EqOptions {
+        self.ignore_case = true;
+        self
+    }
+}
+
+/// Whether `a` and `b` are the same trees, up to `options`.
+pub fn nodes_eq<D>(a: &[DomNode<D>], b: &[DomNode<D>], options: &EqOptions) -> bool
+where
+    D: Driver,
+{
+    children_eq(a, b, options, false)
+}
+
+/// Whether `a` and `b` are the same lists, where `preserve` is whether their
+/// parent preserves whitespace.
+fn children_eq<D>(a: &[DomNode<D>], b: &[DomNode<D>], options: &EqOptions, preserve: bool) -> bool
+where
+    D: Driver,
+{
+    let is_kept = |node: &&DomNode<D>| {
+        match node {
+            DomNode::Text(text) if options.ignore_whitespace_text && !preserve => {
+                !text.get().chars().all(char::is_whitespace)
+            }
+            _ => true,
+        }
+    };
+    let mut a = a.iter().filter(is_kept);
+    let mut b = b.iter().filter(is_kept);
+    loop {
+        match (a.next(), b.next()) {
+            (None, None) => return true,
+            (Some(a), Some(b)) if node_eq(a, b, options, preserve) => {}
+            _ => return false,
+        }
+    }
+}
+
+fn node_eq<D>(a: &DomNode<D>, b: &DomNode<D>, options: &EqOptions, preserve: bool) -> bool
+where
+    D: Driver,
+{
+    match (a, b) {
+        (DomNode::Tag(a), DomNode::Tag(b)) => {
+            let preserve = preserve || preserves_whitespace(&a.tag.to_ascii_lowercase());
+            name_eq(&a.tag, &b.tag, options)
+                && attrs_eq(&a.attrs, &b.attrs, options)
+                && children_eq(&a.children, &b.children, options, preserve)
+        }
+        (DomNode::Text(a), DomNode::Text(b)) => {
+            if options.collapse_whitespace && !preserve {
+                collapse(a.get()) == collapse(b.get())
+            } else {
+                a.get() == b.get()
+            }
+        }
+        (DomNode::Comment(a), DomNode::Comment(b)) => a.get() == b.get(),
+        _ => false,
+    }
+}
+
+fn attrs_eq<D>(a: &[DomAttr<D>], b: &[DomAttr<D>], options: &EqOptions) -> bool
+where
+    D: Driver,
+{
+    sorted_attrs(a, options) == sorted_attrs(b, options)
+}
+
+fn sorted_attrs<'a, D>(
+    attrs: &'a [DomAttr<D>],
+    options: &EqOptions,
+) -> Vec<(String, AttrRefValue<'a>)>
+where
+    D: Driver,
+{
+    let mut attrs = attrs
+        .iter()
+        .map(|attr| {
+            let name = if options.ignore_case {
+                attr.name.to_lowercase()
+            } else {
+                attr.name.to_string()
+            };
+            (name, AttrRefValue::from(&attr.value))
+        })
+        .collect::<Vec<_>>();
+    attrs.sort_by(|a, b| a.0.cmp(&b.0));
+    attrs
+}
+
+fn name_eq(a: &str, b: &str, options: &EqOptions) -> bool {
+    if options.ignore_case {
+        a.eq_ignore_ascii_case(b)
+    } else {
+        a == b
+    }
+}
+
+fn collapse(text: &str) -> String {
+    let mut collapsed = String::with_capacity(text.len());
+    let mut in_whitespace = false;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !in_whitespace {
+                collapsed.push(' ');
+            }
+            in_whitespace = true;
+        } else {
+            collapsed.push(c);
+            in_whitespace = false;
+        }
+    }
+    collapsed
+}
@@ -0,0 +1,238 @@
+use super::*;
+
+/// Bounds on the size of a tree built from untrusted input, e.g. with
+/// `from_json_limited`. Each bound is `None` by default, meaning no limit.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Limits {
+    /// The most nodes on a path from the root list down to a node, so a
+    /// root node has a depth of 1.
+    pub max_depth: Option<usize>,
+    /// The most children of a single element.
+    pub max_children: Option<usize>,
+    /// The most bytes of a single text or attribute value.
+    pub max_text_len: Option<usize>,
+    /// The most nodes in the whole tree.
+    pub max_total_nodes: Option<usize>,
+}
+
+/// One of the bounds of `Limits`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Limit {
+    Depth,
+    Children,
+    TextLen,
+    TotalNodes,
+}
+
+/// The node at `path` exceeds `limit`. For `Limit::Children` it's the element
+/// with too many children, and for `Limit::TextLen` the text node or the
+/// element with the attribute.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LimitError {
+    pub limit: Limit,
+    pub path: Path,
+}
+
+/// Checks a tree node by node against `Limits` while it's being built, so
+/// input is rejected before all of it is read.
+pub(crate) struct LimitChecker {
+    limits: Limits,
+    total_nodes: usize,
+}
+
+impl LimitChecker {
+    pub(crate) fn new(limits: Limits) -> LimitChecker {
+        LimitChecker {
+            limits,
+            total_nodes: 0,
+        }
+    }
+
+    /// Counts the node at `path`, before its children are read.
+    pub(crate) fn node(&mut self, path: &Path) -> Result<(), LimitError> {
+        self.total_nodes += 1;
+        check(
+            self.limits.max_depth,
+            path.indices().len(),
+            Limit::Depth,
+            path,
+        )?;
+        check(
+            self.limits.max_total_nodes,
+            self.total_nodes,
+            Limit::TotalNodes,
+            path,
+        )
+    }
+
+    pub(crate) fn children(&self, path: &Path, len: usize) -> Result<(), LimitError> {
+        check(self.limits.max_children, len, Limit::Children, path)
+    }
+
+    pub(crate) fn text(&self, path: &Path, text: &str) -> Result<(), LimitError> {
+        check(self.limits.max_text_len, text.len(), Limit::TextLen, path)
+    }
+}
+
+fn check(max: Option<usize>, value: usize, limit: Limit, path: &Path) -> Result<(), LimitError> {
+    match max {
+        Some(max) if value > max => {
+            Err(LimitError {
+                limit,
+                path: path.clone(),
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Checks `nodes` against `limits`, e.g. a tree produced by another parser.
+/// The tree is walked without recursion, so deep trees can't overflow the
+/// stack.
+pub fn check_limits<D>(nodes: &[DomNode<D>], limits: Limits) -> Result<(), LimitError>
+where
+    D: Driver,
+{
+    let mut checker = LimitChecker::new(limits);
+    // The siblings still to be checked at each level, and the path of the
+    // node checked last, which is one level deeper while an element's
+    // children are checked.
+    let mut stack = vec![nodes.iter().enumerate()];
+    let mut path = Path::root();
+    while let Some(siblings) = stack.last_mut() {
+        let (index, node) = match siblings.next() {
+            Some(next) => next,
+            None => {
+                stack.pop();
+                path.pop();
+                continue;
+            }
+        };
+        path.push(index);
+        checker.node(&path)?;
+        match node {
+            DomNode::Tag(tag) => {
+                for attr in &tag.attrs {
+                    if let AttrValue::String(value) = &attr.value {
+                        checker.text(&path, value)?;
+                    }
+                }
+                checker.children(&path, tag.children.len())?;
+                stack.push(tag.children.iter().enumerate());
+            }
+            DomNode::Text(text) => {
+                checker.text(&path, text.get())?;
+                path.pop();
+            }
+            DomNode::Comment(comment) => {
+                checker.text(&path, comment.get())?;
+                path.pop();
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks that no node of `node` is deeper than `max_depth`, counted like
+/// `Limits::max_depth`, e.g. before rendering or diffing a tree built from
+/// untrusted input. Visiting and diffing recurse once per level, so a tree
+/// that's too deep would overflow the stack, while this stops descending at
+/// `max_depth`. Components are rendered with `driver_ctx` to be checked.
+pub fn check_depth<D, N>(
+    driver_ctx: &DriverCtx<D>,
+    node: &mut N,
+    max_depth: usize,
+) -> Result<(), LimitError>
+where
+    D: Driver,
+    N: Node<D> + ?Sized,
+{
+    check_depth_below(driver_ctx, node, &Path::root(), 0, max_depth)
+}
+
+/// Like `check_depth`, for `node` as the child of `parent` at `index`.
+pub(crate) fn check_depth_below<D, N>(
+    driver_ctx: &DriverCtx<D>,
+    node: &mut N,
+    parent: &Path,
+    mut index: usize,
+    max_depth: usize,
+) -> Result<(), LimitError>
+where
+    D: Driver,
+    N: Node<D> + ?Sized,
+{
+    node.visit(
+        &mut index,
+        &mut DepthChecker {
+            driver_ctx,
+            parent: parent.clone(),
+            max_depth,
+        },
+    )
+}
+
+struct DepthChecker<'a, D>
+where
+    D: Driver,
+{
+    driver_ctx: &'a DriverCtx<D>,
+    parent: Path,
+    max_depth: usize,
+}
+
+impl<'a, D> DepthChecker<'a, D>
+where
+    D: Driver,
+{
+    fn check(&self, index: usize) -> Result<Path, LimitError> {
+        let path = self.parent.child(index);
+        check(
+            Some(self.max_depth),
+            path.indices().len(),
+            Limit::Depth,
+            &path,
+        )?;
+        Ok(path)
+    }
+}
+
+impl<'a, D> NodeVisitor<D> for DepthChecker<'a, D>
+where
+    D: Driver,
+{
+    type Err = LimitError;
+
+    fn on_tag<T>(&mut self, index: usize, tag: &mut T) -> Result<(), LimitError>
+    where
+        T: Tag<D>,
+    {
+        let path = self.check(index)?;
+        tag.visit_children(&mut DepthChecker {
+            driver_ctx: self.driver_ctx,
+            parent: path,
+            max_depth: self.max_depth,
+        })
+    }
+
+    fn on_text<T>(&mut self, index: usize, _text: &mut T) -> Result<(), LimitError>
+    where
+        T: Text<D>,
+    {
+        self.check(index).map(|_| ())
+    }
+
+    fn on_comp<C>(&mut self, index: &mut usize, comp: &mut CompNode<D, C>) -> Result<(), LimitError>
+    where
+        C: Comp<D>,
+    {
+        if comp.comp_ctx().is_none() {
+            comp.init_comp_ctx(self.driver_ctx.clone());
+        }
+        comp.visit_rendered(index, self)
+    }
+
+    fn driver_ctx(&self) -> Option<DriverCtx<D>> {
+        Some(self.driver_ctx.clone())
+    }
+}
@@ -0,0 +1,830 @@
+use super::{sanitize, Allowlist, DomAttr, DomNode, LimitChecker, LimitError, Limits};
+use crate::{driver::Driver, vdom::path::Path};
+use std::mem;
+
+/// Builds nodes from `markdown`, e.g. for docs or CMS content, sanitized with
+/// `Allowlist::basic` so links and images to e.g. `javascript:` URLs lose
+/// their URL.
+///
+/// The common subset of CommonMark is supported: ATX headings, paragraphs,
+/// hard breaks, block quotes, bullet and ordered lists, fenced and indented
+/// code blocks, thematic breaks, emphasis, code spans, inline links and
+/// images, autolinks and backslash escapes. Setext headings, reference links
+/// and tables are not, and raw HTML is kept as text rather than parsed.
+///
+/// Nested quotes, lists and emphasis are parsed recursively, so markdown
+/// from untrusted users should be parsed with `from_markdown_limited`.
+pub fn from_markdown<D>(markdown: &str) -> Vec<DomNode<D>>
+where
+    D: Driver,
+{
+    from_markdown_limited(markdown, Limits::default()).expect("no limits to exceed")
+}
+
+/// Like `from_markdown`, but fails as soon as the nodes exceed `limits`.
+/// Nodes are checked before their content is parsed, so markdown nested too
+/// deeply is rejected at the depth limit rather than overflowing the stack.
+/// Items of tight lists are checked with the paragraphs they're unwrapped
+/// from, so their content counts one level deeper than it ends up.
+pub fn from_markdown_limited<D>(
+    markdown: &str,
+    limits: Limits,
+) -> Result<Vec<DomNode<D>>, LimitError>
+where
+    D: Driver,
+{
+    let mut nodes = parse(markdown, limits)?;
+    let mut allowlist = Allowlist::basic();
+    allowlist.allow_attr("start");
+    sanitize(&mut nodes, &allowlist);
+    Ok(nodes)
+}
+
+/// Builds nodes from `markdown` like `from_markdown`, but keeps URLs as
+/// written, for trusted content.
+pub fn from_markdown_unsanitized<D>(markdown: &str) -> Vec<DomNode<D>>
+where
+    D: Driver,
+{
+    parse(markdown, Limits::default()).expect("no limits to exceed")
+}
+
+fn parse<D>(markdown: &str, limits: Limits) -> Result<Vec<DomNode<D>>, LimitError>
+where
+    D: Driver,
+{
+    let lines = markdown
+        .lines()
+        .map(|line| line.replace('\t', "    "))
+        .collect::<Vec<_>>();
+    let lines = lines.iter().map(String::as_str).collect::<Vec<_>>();
+    blocks(&lines, &Path::root(), &mut LimitChecker::new(limits))
+}
+
+/// Counts the child of `parent` at `index` before its content is parsed,
+/// returning its path.
+fn enter(checker: &mut LimitChecker, parent: &Path, index: usize) -> Result<Path, LimitError> {
+    if !parent.is_root() {
+        checker.children(parent, index + 1)?;
+    }
+    let path = parent.child(index);
+    checker.node(&path)?;
+    Ok(path)
+}
+
+/// A text node that's been entered at `path`.
+fn text<D>(text: String, path: &Path, checker: &LimitChecker) -> Result<DomNode<D>, LimitError>
+where
+    D: Driver,
+{
+    checker.text(path, &text)?;
+    Ok(DomNode::text(text))
+}
+
+/// An attribute of the element at `path`.
+fn attr<D>(
+    name: &'static str,
+    value: String,
+    path: &Path,
+    checker: &LimitChecker,
+) -> Result<DomAttr<D>, LimitError>
+where
+    D: Driver,
+{
+    checker.text(path, &value)?;
+    Ok(DomAttr::new(name, value))
+}
+
+fn blocks<D>(
+    lines: &[&str],
+    parent: &Path,
+    checker: &mut LimitChecker,
+) -> Result<Vec<DomNode<D>>, LimitError>
+where
+    D: Driver,
+{
+    let mut nodes = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        if line.trim().is_empty() {
+            i += 1;
+            continue;
+        }
+        let path = enter(checker, parent, nodes.len())?;
+        let trimmed = line.trim_start();
+        let (node, len) = if indent(line) >= 4 {
+            indented_code(&lines[i..], &path, checker)?
+        } else if let Some(level) = heading_level(trimmed) {
+            (heading(trimmed, level, &path, checker)?, 1)
+        } else if is_rule(trimmed) {
+            (DomNode::tag("hr", Vec::new(), Vec::new()), 1)
+        } else if let Some(fence) = fence(trimmed) {
+            fenced_code(&lines[i..], fence, &path, checker)?
+        } else if trimmed.starts_with('>') {
+            blockquote(&lines[i..], &path, checker)?
+        } else if let Some(marker) = list_marker(trimmed) {
+            list(&lines[i..], marker, &path, checker)?
+        } else {
+            paragraph(&lines[i..], &path, checker)?
+        };
+        nodes.push(node);
+        i += len;
+    }
+    Ok(nodes)
+}
+
+fn indent(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+/// Whether `line` starts a block that ends a paragraph before it.
+fn is_block_start(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    !trimmed.is_empty()
+        && indent(line) < 4
+        && (heading_level(trimmed).is_some()
+            || is_rule(trimmed)
+            || fence(trimmed).is_some()
+            || trimmed.starts_with('>')
+            // Only lists starting at 1 do, so a line wrapped before e.g.
+            // "2019." doesn't become a list.
+            || list_marker(trimmed).map_or(false, |marker| marker.start == 1))
+}
+
+fn paragraph<D>(
+    lines: &[&str],
+    path: &Path,
+    checker: &mut LimitChecker,
+) -> Result<(DomNode<D>, usize), LimitError>
+where
+    D: Driver,
+{
+    let len = 1 + lines[1..]
+        .iter()
+        .take_while(|line| !line.trim().is_empty() && !is_block_start(line))
+        .count();
+    let text = lines[..len]
+        .iter()
+        .map(|line| line.trim_start())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let children = inlines(text.trim_end(), path, checker)?;
+    Ok((DomNode::tag("p", Vec::new(), children), len))
+}
+
+fn heading_level(line: &str) -> Option<usize> {
+    let level = delimiter_run(line, '#');
+    let rest = &line[level..];
+    if (1..=6).contains(&level) && (rest.is_empty() || rest.starts_with(' ')) {
+        Some(level)
+    } else {
+        None
+    }
+}
+
+fn heading<D>(
+    line: &str,
+    level: usize,
+    path: &Path,
+    checker: &mut LimitChecker,
+) -> Result<DomNode<D>, LimitError>
+where
+    D: Driver,
+{
+    let mut text = line[level..].trim();
+    // An optional closing sequence of `#`s isn't part of the heading.
+    let without_closing = text.trim_end_matches('#');
+    if without_closing.is_empty() || without_closing.ends_with(' ') {
+        text = without_closing.trim_end();
+    }
+    let children = inlines(text, path, checker)?;
+    Ok(DomNode::tag(format!("h{}", level), Vec::new(), children))
+}
+
+fn is_rule(line: &str) -> bool {
+    let mut chars = line.chars().filter(|c| *c != ' ');
+    match chars.next() {
+        Some(c) if c == '-' || c == '*' || c == '_' => {
+            chars.clone().all(|d| d == c) && 1 + chars.count() >= 3
+        }
+        _ => false,
+    }
+}
+
+/// The character and length of the fence `line` opens a code block with.
+fn fence(line: &str) -> Option<(char, usize)> {
+    let ch = line.chars().next()?;
+    let len = delimiter_run(line, ch);
+    if (ch != '`' && ch != '~') || len < 3 || (ch == '`' && line[len..].contains('`')) {
+        return None;
+    }
+    Some((ch, len))
+}
+
+fn fenced_code<D>(
+    lines: &[&str],
+    (ch, len): (char, usize),
+    path: &Path,
+    checker: &mut LimitChecker,
+) -> Result<(DomNode<D>, usize), LimitError>
+where
+    D: Driver,
+{
+    let fence_indent = indent(lines[0]);
+    let mut code = String::new();
+    let mut consumed = lines.len();
+    for (i, line) in lines.iter().enumerate().skip(1) {
+        let closing = line.trim();
+        if indent(line) < 4 && closing.len() >= len && closing.chars().all(|c| c == ch) {
+            consumed = i + 1;
+            break;
+        }
+        // The content is indented relative to the fence.
+        code.push_str(&line[fence_indent.min(indent(line))..]);
+        code.push('\n');
+    }
+    Ok((code_block(code, path, checker)?, consumed))
+}
+
+fn indented_code<D>(
+    lines: &[&str],
+    path: &Path,
+    checker: &mut LimitChecker,
+) -> Result<(DomNode<D>, usize), LimitError>
+where
+    D: Driver,
+{
+    let mut len = lines
+        .iter()
+        .take_while(|line| line.trim().is_empty() || indent(line) >= 4)
+        .count();
+    while lines[len - 1].trim().is_empty() {
+        len -= 1;
+    }
+    let mut code = String::new();
+    for line in &lines[..len] {
+        code.push_str(line.get(4..).unwrap_or(""));
+        code.push('\n');
+    }
+    Ok((code_block(code, path, checker)?, len))
+}
+
+fn code_block<D>(
+    code: String,
+    path: &Path,
+    checker: &mut LimitChecker,
+) -> Result<DomNode<D>, LimitError>
+where
+    D: Driver,
+{
+    let code_path = enter(checker, path, 0)?;
+    let text_path = enter(checker, &code_path, 0)?;
+    let code = DomNode::tag("code", Vec::new(), vec![text(code, &text_path, checker)?]);
+    Ok(DomNode::tag("pre", Vec::new(), vec![code]))
+}
+
+fn blockquote<D>(
+    lines: &[&str],
+    path: &Path,
+    checker: &mut LimitChecker,
+) -> Result<(DomNode<D>, usize), LimitError>
+where
+    D: Driver,
+{
+    let mut content: Vec<&str> = Vec::new();
+    for line in lines {
+        let trimmed = line.trim_start();
+        if indent(line) < 4 && trimmed.starts_with('>') {
+            let rest = &trimmed[1..];
+            content.push(if rest.starts_with(' ') {
+                &rest[1..]
+            } else {
+                rest
+            });
+        } else if !trimmed.is_empty()
+            && content.last().map_or(false, |prev| !prev.trim().is_empty())
+            && !is_block_start(line)
+        {
+            // A lazy continuation of a paragraph within the quote.
+            content.push(trimmed);
+        } else {
+            break;
+        }
+    }
+    let children = blocks(&content, path, checker)?;
+    Ok((
+        DomNode::tag("blockquote", Vec::new(), children),
+        content.len(),
+    ))
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ListKind {
+    Bullet(char),
+    Ordered(char),
+}
+
+struct ListMarker {
+    kind: ListKind,
+    start: u32,
+    /// The width of the marker and the spaces after it, by which the content
+    /// of the item is indented.
+    width: usize,
+}
+
+fn list_marker(line: &str) -> Option<ListMarker> {
+    let (kind, start, len) = match line.chars().next()? {
+        c @ '-' | c @ '*' | c @ '+' => (ListKind::Bullet(c), 1, 1),
+        _ => {
+            let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+            let delimiter = line[digits..].chars().next()?;
+            if digits == 0 || digits > 9 || (delimiter != '.' && delimiter != ')') {
+                return None;
+            }
+            (
+                ListKind::Ordered(delimiter),
+                line[..digits].parse().ok()?,
+                digits + 1,
+            )
+        }
+    };
+    let rest = &line[len..];
+    let spaces = indent(rest);
+    let width = if rest.trim().is_empty() || spaces > 4 {
+        // With more than 4 spaces the content is an indented code block,
+        // which starts after the first.
+        len + 1
+    } else if spaces == 0 {
+        return None;
+    } else {
+        len + spaces
+    };
+    Some(ListMarker { kind, start, width })
+}
+
+fn list<D>(
+    lines: &[&str],
+    marker: ListMarker,
+    path: &Path,
+    checker: &mut LimitChecker,
+) -> Result<(DomNode<D>, usize), LimitError>
+where
+    D: Driver,
+{
+    let mut items: Vec<Vec<&str>> = Vec::new();
+    let mut loose = false;
+    let mut content_indent = 0;
+    let mut len = 0;
+    for line in lines {
+        let trimmed = line.trim_start();
+        let prev_blank = len > 0 && lines[len - 1].trim().is_empty();
+        let item_marker = if indent(line) < 4 && !is_rule(trimmed) {
+            list_marker(trimmed).filter(|item_marker| item_marker.kind == marker.kind)
+        } else {
+            None
+        };
+        match items.last_mut() {
+            Some(item) if trimmed.is_empty() => item.push(""),
+            Some(item) if indent(line) >= content_indent => {
+                loose |= prev_blank;
+                item.push(&line[content_indent..]);
+            }
+            _ if item_marker.is_some() => {
+                loose |= prev_blank;
+                content_indent = indent(line) + item_marker.map_or(0, |m| m.width);
+                items.push(vec![line.get(content_indent..).unwrap_or("")]);
+            }
+            Some(item) if !prev_blank && !is_block_start(line) => {
+                // A lazy continuation of a paragraph within the item.
+                item.push(trimmed);
+            }
+            _ => break,
+        }
+        len += 1;
+    }
+    // Trailing blank lines separate the list from what follows.
+    while lines[len - 1].trim().is_empty() {
+        len -= 1;
+    }
+
+    let (tag, attrs) = match marker.kind {
+        ListKind::Bullet(_) => ("ul", Vec::new()),
+        ListKind::Ordered(_) if marker.start != 1 => {
+            ("ol", vec![DomAttr::new("start", marker.start.to_string())])
+        }
+        ListKind::Ordered(_) => ("ol", Vec::new()),
+    };
+    let items = items
+        .iter()
+        .enumerate()
+        .map(|(i, lines)| {
+            let item_path = enter(checker, path, i)?;
+            let mut children = blocks(lines, &item_path, checker)?;
+            // The paragraphs of tight lists are rendered without `p`s.
+            if !loose {
+                children = children.into_iter().flat_map(unwrap_paragraph).collect();
+            }
+            Ok(DomNode::tag("li", Vec::new(), children))
+        })
+        .collect::<Result<_, LimitError>>()?;
+    Ok((DomNode::tag(tag, attrs, items), len))
+}
+
+fn unwrap_paragraph<D>(node: DomNode<D>) -> Vec<DomNode<D>>
+where
+    D: Driver,
+{
+    match node {
+        DomNode::Tag(tag) if tag.tag == "p" => tag.children,
+        node => vec![node],
+    }
+}
+
+fn inlines<D>(
+    text: &str,
+    parent: &Path,
+    checker: &mut LimitChecker,
+) -> Result<Vec<DomNode<D>>, LimitError>
+where
+    D: Driver,
+{
+    let mut nodes = Vec::new();
+    let mut buf = String::new();
+    let mut i = 0;
+    while let Some(c) = text[i..].chars().next() {
+        let rest = &text[i..];
+        // The index of an inline found here, after the text before it.
+        let index = nodes.len() + !buf.is_empty() as usize;
+        let inline = match c {
+            '\\' => {
+                match rest[1..].chars().next() {
+                    Some('\n') => {
+                        enter(checker, parent, index)?;
+                        Some((DomNode::tag("br", Vec::new(), Vec::new()), 2))
+                    }
+                    Some(next) if next.is_ascii_punctuation() => {
+                        buf.push(next);
+                        i += 2;
+                        continue;
+                    }
+                    _ => None,
+                }
+            }
+            '\n' => {
+                // Two or more spaces at the end of a line make a hard break.
+                let is_hard = buf.ends_with("  ");
+                buf.truncate(buf.trim_end_matches(' ').len());
+                if is_hard {
+                    enter(checker, parent, nodes.len() + !buf.is_empty() as usize)?;
+                    Some((DomNode::tag("br", Vec::new(), Vec::new()), 1))
+                } else {
+                    None
+                }
+            }
+            '`' => {
+                match code_span(rest, parent, index, checker)? {
+                    Some(inline) => Some(inline),
+                    None => {
+                        // The whole run is literal, rather than a shorter
+                        // run within it opening a code span.
+                        let run = delimiter_run(rest, '`');
+                        buf.push_str(&rest[..run]);
+                        i += run;
+                        continue;
+                    }
+                }
+            }
+            '*' | '_' => {
+                let prev = text[..i].chars().next_back();
+                emphasis(rest, prev, parent, index, checker)?
+            }
+            '!' if rest[1..].starts_with('[') => {
+                match link(&rest[1..]) {
+                    Some(link) => {
+                        let path = enter(checker, parent, index)?;
+                        let mut attrs = vec![
+                            attr("src", unescape(link.url), &path, checker)?,
+                            attr("alt", unescape(link.label), &path, checker)?,
+                        ];
+                        if let Some(title) = link.title {
+                            attrs.push(attr("title", unescape(title), &path, checker)?);
+                        }
+                        Some((DomNode::tag("img", attrs, Vec::new()), 1 + link.len))
+                    }
+                    None => None,
+                }
+            }
+            '[' => {
+                match link(rest) {
+                    Some(link) => {
+                        let path = enter(checker, parent, index)?;
+                        let mut attrs = vec![attr("href", unescape(link.url), &path, checker)?];
+                        if let Some(title) = link.title {
+                            attrs.push(attr("title", unescape(title), &path, checker)?);
+                        }
+                        let children = inlines(link.label, &path, checker)?;
+                        Some((DomNode::tag("a", attrs, children), link.len))
+                    }
+                    None => None,
+                }
+            }
+            '<' => autolink(rest, parent, index, checker)?,
+            _ => None,
+        };
+        match inline {
+            Some((node, len)) => {
+                if !buf.is_empty() {
+                    let path = enter(checker, parent, nodes.len())?;
+                    nodes.push(self::text(mem::take(&mut buf), &path, checker)?);
+                }
+                nodes.push(node);
+                i += len;
+            }
+            None => {
+                buf.push(c);
+                i += c.len_utf8();
+            }
+        }
+    }
+    if !buf.is_empty() {
+        let path = enter(checker, parent, nodes.len())?;
+        nodes.push(self::text(buf, &path, checker)?);
+    }
+    Ok(nodes)
+}
+
+fn delimiter_run(text: &str, ch: char) -> usize {
+    text.len() - text.trim_start_matches(ch).len()
+}
+
+/// A code span at the start of `text`, as the child of `parent` at `index`.
+fn code_span<D>(
+    text: &str,
+    parent: &Path,
+    index: usize,
+    checker: &mut LimitChecker,
+) -> Result<Option<(DomNode<D>, usize)>, LimitError>
+where
+    D: Driver,
+{
+    let run = delimiter_run(text, '`');
+    let mut i = run;
+    loop {
+        let start = match text[i..].find('`') {
+            Some(start) => i + start,
+            None => return Ok(None),
+        };
+        let len = delimiter_run(&text[start..], '`');
+        if len == run {
+            let code = text[run..start].replace('\n', " ");
+            // One space on both sides is stripped, so code can start or end
+            // with a backtick.
+            let code = if code.len() >= 2
+                && code.starts_with(' ')
+                && code.ends_with(' ')
+                && !code.trim().is_empty()
+            {
+                code[1..code.len() - 1].to_string()
+            } else {
+                code
+            };
+            let path = enter(checker, parent, index)?;
+            let text_path = enter(checker, &path, 0)?;
+            let children = vec![self::text(code, &text_path, checker)?];
+            return Ok(Some((
+                DomNode::tag("code", Vec::new(), children),
+                start + len,
+            )));
+        }
+        i = start + len;
+    }
+}
+
+/// Emphasis opened by the run of `*` or `_` `text` starts with, where `prev`
+/// is the character before it. A run of two or more opens strong emphasis.
+fn emphasis<D>(
+    text: &str,
+    prev: Option<char>,
+    parent: &Path,
+    index: usize,
+    checker: &mut LimitChecker,
+) -> Result<Option<(DomNode<D>, usize)>, LimitError>
+where
+    D: Driver,
+{
+    let ch = match text.chars().next() {
+        Some(ch) => ch,
+        None => return Ok(None),
+    };
+    let run = delimiter_run(text, ch);
+    let next = match text[run..].chars().next() {
+        Some(next) => next,
+        None => return Ok(None),
+    };
+    // `_` doesn't open emphasis within words, e.g. in `snake_case`.
+    if next.is_whitespace() || (ch == '_' && prev.map_or(false, char::is_alphanumeric)) {
+        return Ok(None);
+    }
+    if run >= 2 {
+        if let Some(end) = closing_delimiter(&text[2..], ch, 2) {
+            let path = enter(checker, parent, index)?;
+            let children = inlines(&text[2..2 + end], &path, checker)?;
+            return Ok(Some((
+                DomNode::tag("strong", Vec::new(), children),
+                end + 4,
+            )));
+        }
+    }
+    let end = match closing_delimiter(&text[1..], ch, 1) {
+        Some(end) => end,
+        None => return Ok(None),
+    };
+    let path = enter(checker, parent, index)?;
+    let children = inlines(&text[1..1 + end], &path, checker)?;
+    Ok(Some((DomNode::tag("em", Vec::new(), children), end + 2)))
+}
+
+/// The offset of the `len` `ch`s closing emphasis in `text`. They end a run
+/// that follows non-whitespace, and a run of two is skipped when looking for
+/// one as it closes strong emphasis nested within.
+fn closing_delimiter(text: &str, ch: char, len: usize) -> Option<usize> {
+    let mut prev = None;
+    let mut i = 0;
+    while let Some(c) = text[i..].chars().next() {
+        if c == '\\' {
+            i += 1 + text[i + 1..].chars().next().map_or(0, char::len_utf8);
+            prev = Some(c);
+            continue;
+        }
+        if c != ch {
+            prev = Some(c);
+            i += c.len_utf8();
+            continue;
+        }
+        let run = delimiter_run(&text[i..], ch);
+        let next = text[i + run..].chars().next();
+        let can_close = prev.map_or(false, |prev: char| !prev.is_whitespace())
+            && (ch != '_' || !next.map_or(false, char::is_alphanumeric))
+            && run >= len
+            && !(len == 1 && run == 2);
+        if can_close {
+            return Some(i + run - len);
+        }
+        prev = Some(ch);
+        i += run;
+    }
+    None
+}
+
+struct Link<'a> {
+    label: &'a str,
+    url: &'a str,
+    title: Option<&'a str>,
+    /// The length of the whole link in the text.
+    len: usize,
+}
+
+/// An inline link `[label](url "title")` at the start of `text`.
+fn link(text: &str) -> Option<Link<'_>> {
+    let mut depth = 0;
+    let mut label_end = None;
+    let mut chars = text.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    label_end = Some(i);
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+    let label_end = label_end?;
+    let rest = &text[label_end + 1..];
+    if !rest.starts_with('(') {
+        return None;
+    }
+
+    let rest = rest[1..].trim_start();
+    let (url, rest) = if rest.starts_with('<') {
+        let end = rest.find('>')?;
+        (&rest[1..end], &rest[end + 1..])
+    } else {
+        // Parentheses within the URL have to be balanced.
+        let mut depth = 0;
+        let end = rest
+            .char_indices()
+            .find(|&(_, c)| {
+                match c {
+                    '(' => {
+                        depth += 1;
+                        false
+                    }
+                    ')' if depth == 0 => true,
+                    ')' => {
+                        depth -= 1;
+                        false
+                    }
+                    c => c.is_whitespace(),
+                }
+            })
+            .map(|(i, _)| i)?;
+        (&rest[..end], &rest[end..])
+    };
+    let rest = rest.trim_start();
+    let (title, rest) = match rest.chars().next()? {
+        quote @ '"' | quote @ '\'' => {
+            let end = 1 + rest[1..].find(quote)?;
+            (Some(&rest[1..end]), rest[end + 1..].trim_start())
+        }
+        _ => (None, rest),
+    };
+    if !rest.starts_with(')') {
+        return None;
+    }
+    Some(Link {
+        label: &text[1..label_end],
+        url,
+        title,
+        len: text.len() - rest.len() + 1,
+    })
+}
+
+/// `<url>` or `<address>` at the start of `text`, linking to the URL or
+/// mailing the address, as the child of `parent` at `index`.
+fn autolink<D>(
+    text: &str,
+    parent: &Path,
+    index: usize,
+    checker: &mut LimitChecker,
+) -> Result<Option<(DomNode<D>, usize)>, LimitError>
+where
+    D: Driver,
+{
+    let end = match text.find('>') {
+        Some(end) => end,
+        None => return Ok(None),
+    };
+    let target = &text[1..end];
+    if target.is_empty() || target.contains(|c: char| c.is_whitespace() || c == '<') {
+        return Ok(None);
+    }
+    let href = if is_absolute_url(target) {
+        target.to_string()
+    } else if is_email(target) {
+        format!("mailto:{}", target)
+    } else {
+        return Ok(None);
+    };
+    let path = enter(checker, parent, index)?;
+    let attrs = vec![attr("href", href, &path, checker)?];
+    let text_path = enter(checker, &path, 0)?;
+    let children = vec![self::text(target.to_string(), &text_path, checker)?];
+    Ok(Some((DomNode::tag("a", attrs, children), end + 1)))
+}
+
+fn is_absolute_url(text: &str) -> bool {
+    let scheme = match text.find(':') {
+        Some(i) => &text[..i],
+        None => return false,
+    };
+    (2..=32).contains(&scheme.len())
+        && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '.' || c == '-')
+}
+
+fn is_email(text: &str) -> bool {
+    let mut parts = text.splitn(2, '@');
+    match (parts.next(), parts.next()) {
+        (Some(local), Some(domain)) => {
+            !local.is_empty()
+                && domain.contains('.')
+                && !domain.contains('@')
+                && !domain.starts_with('.')
+                && !domain.ends_with('.')
+        }
+        _ => false,
+    }
+}
+
+/// Removes the backslashes escaping punctuation in `text`.
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\\' && chars.peek().map_or(false, char::is_ascii_punctuation) {
+            continue;
+        }
+        unescaped.push(c);
+    }
+    unescaped
+}
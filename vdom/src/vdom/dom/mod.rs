@@ -0,0 +1,436 @@
+mod eq;
+mod limits;
+#[cfg(feature = "markdown")]
+mod markdown;
+mod sanitize;
+mod walk;
+
+use super::{
+    attr::{fmt_attr, Attr, AttrDiffer, AttrList, AttrRefValue, AttrValue, AttrVisitor},
+    node::{Comment, Comp, CompNode, Node, NodeDiffer, NodeVisitor, Tag, Text, TextDyn},
+    path::{Path, PathError},
+};
+use crate::driver::{Driver, DriverCtx};
+use std::{borrow::Cow, convert::Infallible, fmt, mem};
+
+pub use self::eq::*;
+pub use self::limits::*;
+#[cfg(feature = "markdown")]
+pub use self::markdown::*;
+pub use self::sanitize::*;
+pub use self::walk::*;
+
+/// An owned tree whose shape is only known at runtime, e.g. built from
+/// untrusted markup or data, which can be inspected and rewritten in place.
+pub enum DomNode<D>
+where
+    D: Driver,
+{
+    Tag(DomTag<D>),
+    Text(TextDyn<D>),
+    Comment(Comment<D>),
+}
+
+impl<D> fmt::Debug for DomNode<D>
+where
+    D: Driver,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DomNode::Tag(tag) => tag.fmt(f),
+            DomNode::Text(text) => text.fmt(f),
+            DomNode::Comment(comment) => comment.fmt(f),
+        }
+    }
+}
+
+impl<D> DomNode<D>
+where
+    D: Driver,
+{
+    pub fn tag<T>(tag: T, attrs: Vec<DomAttr<D>>, children: Vec<DomNode<D>>) -> DomNode<D>
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        DomNode::Tag(DomTag::new(tag, attrs, children))
+    }
+
+    pub fn text<T>(text: T) -> DomNode<D>
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        DomNode::Text(TextDyn::new(text))
+    }
+
+    pub fn comment<T>(text: T) -> DomNode<D>
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        DomNode::Comment(Comment::new(text))
+    }
+
+    /// The node at `path` relative to this one, which the root path
+    /// addresses.
+    pub fn get_mut(&mut self, path: &Path) -> Result<&mut DomNode<D>, PathError> {
+        let mut node = self;
+        for (depth, &index) in path.indices().iter().enumerate() {
+            let parent = || Path::from(path.indices()[..depth].to_vec());
+            node = match node {
+                DomNode::Tag(tag) => {
+                    tag.children.get_mut(index).ok_or_else(|| {
+                        PathError::OutOfBounds {
+                            parent: parent(),
+                            index,
+                        }
+                    })?
+                }
+                DomNode::Text(_) | DomNode::Comment(_) => {
+                    return Err(PathError::NotAnElement(parent()))
+                }
+            };
+        }
+        Ok(node)
+    }
+}
+
+/// Copies the nodes of `node` into owned `DomNode`s, rendering components
+/// with `driver_ctx`. Listeners are not copied, and namespaced attributes are
+/// named `prefix:name`.
+pub fn to_dom<D, N>(driver_ctx: &DriverCtx<D>, node: &mut N) -> Vec<DomNode<D>>
+where
+    D: Driver,
+    N: Node<D> + ?Sized,
+{
+    let mut builder = DomBuilder {
+        driver_ctx,
+        nodes: Vec::new(),
+    };
+    match node.visit(&mut 0, &mut builder) {
+        Ok(()) => builder.nodes,
+        Err(never) => match never {},
+    }
+}
+
+struct DomBuilder<'a, D>
+where
+    D: Driver,
+{
+    driver_ctx: &'a DriverCtx<D>,
+    nodes: Vec<DomNode<D>>,
+}
+
+impl<'a, D> NodeVisitor<D> for DomBuilder<'a, D>
+where
+    D: Driver,
+{
+    type Err = Infallible;
+
+    fn on_tag<T>(&mut self, _index: usize, tag: &mut T) -> Result<(), Infallible>
+    where
+        T: Tag<D>,
+    {
+        let mut attrs = DomAttrBuilder { attrs: Vec::new() };
+        tag.visit_attrs(&mut attrs)?;
+        let mut children = DomBuilder {
+            driver_ctx: self.driver_ctx,
+            nodes: Vec::new(),
+        };
+        tag.visit_children(&mut children)?;
+        self.nodes.push(DomNode::tag(
+            tag.tag().to_string(),
+            attrs.attrs,
+            children.nodes,
+        ));
+        Ok(())
+    }
+
+    fn on_text<T>(&mut self, _index: usize, text: &mut T) -> Result<(), Infallible>
+    where
+        T: Text<D>,
+    {
+        let is_comment = text.is_comment();
+        let text = text.get().to_string();
+        self.nodes.push(if is_comment {
+            DomNode::comment(text)
+        } else {
+            DomNode::text(text)
+        });
+        Ok(())
+    }
+
+    fn on_comp<C>(&mut self, index: &mut usize, comp: &mut CompNode<D, C>) -> Result<(), Infallible>
+    where
+        C: Comp<D>,
+    {
+        if comp.comp_ctx().is_none() {
+            comp.init_comp_ctx(self.driver_ctx.clone());
+        }
+        comp.visit_rendered(index, self)
+    }
+
+    fn driver_ctx(&self) -> Option<DriverCtx<D>> {
+        Some(self.driver_ctx.clone())
+    }
+}
+
+struct DomAttrBuilder<D>
+where
+    D: Driver,
+{
+    attrs: Vec<DomAttr<D>>,
+}
+
+impl<D> AttrVisitor<D> for DomAttrBuilder<D>
+where
+    D: Driver,
+{
+    type Err = Infallible;
+
+    fn on_attr<A>(&mut self, attr: &mut A) -> Result<(), Infallible>
+    where
+        A: Attr<D>,
+    {
+        if attr.listener().is_some() {
+            return Ok(());
+        }
+        let value = match attr.value() {
+            AttrRefValue::True => AttrValue::True,
+            AttrRefValue::Null => return Ok(()),
+            AttrRefValue::Str(s) => AttrValue::String(s.to_string()),
+        };
+        // `AttrList::push` would shadow `Vec::push` here.
+        Vec::push(&mut self.attrs, DomAttr::new(qualified_name(attr), value));
+        Ok(())
+    }
+}
+
+/// The name of `attr` including its namespace prefix, if any.
+pub(crate) fn qualified_name<D, A>(attr: &A) -> String
+where
+    D: Driver,
+    A: Attr<D>,
+{
+    match attr.namespace() {
+        Some(ns) => format!("{}:{}", ns.prefix, attr.name()),
+        None => attr.name().to_string(),
+    }
+}
+
+/// Replaces the subtree at `path` relative to `tree` with `new`, returning
+/// the replaced subtree. The root path replaces `tree` as a whole.
+pub fn replace_at<D>(
+    tree: &mut DomNode<D>,
+    path: &Path,
+    new: DomNode<D>,
+) -> Result<DomNode<D>, PathError>
+where
+    D: Driver,
+{
+    Ok(mem::replace(tree.get_mut(path)?, new))
+}
+
+impl<D> Node<D> for DomNode<D>
+where
+    D: Driver,
+{
+    fn visit<NV>(&mut self, index: &mut usize, visitor: &mut NV) -> Result<(), NV::Err>
+    where
+        NV: NodeVisitor<D>,
+    {
+        match self {
+            DomNode::Tag(tag) => visitor.on_tag(*index, tag)?,
+            DomNode::Text(text) => visitor.on_text(*index, text)?,
+            DomNode::Comment(comment) => visitor.on_text(*index, comment)?,
+        }
+        *index += 1;
+        Ok(())
+    }
+
+    fn diff<ND>(
+        &mut self,
+        curr_index: &mut usize,
+        ancestor_index: &mut usize,
+        ancestor: &mut Self,
+        differ: &mut ND,
+    ) -> Result<(), ND::Err>
+    where
+        ND: NodeDiffer<D>,
+    {
+        match (self, ancestor) {
+            (DomNode::Tag(curr), DomNode::Tag(ancestor)) if curr.tag == ancestor.tag => {
+                differ.on_tag(*curr_index, *ancestor_index, curr, ancestor)?
+            }
+            (DomNode::Text(curr), DomNode::Text(ancestor)) => {
+                differ.on_text(*curr_index, *ancestor_index, curr, ancestor)?
+            }
+            (DomNode::Comment(curr), DomNode::Comment(ancestor)) => {
+                differ.on_text(*curr_index, *ancestor_index, curr, ancestor)?
+            }
+            (curr, ancestor) => {
+                differ.on_node_removed(*curr_index, ancestor_index, ancestor)?;
+                return differ.on_node_added(curr_index, curr);
+            }
+        }
+        *curr_index += 1;
+        *ancestor_index += 1;
+        Ok(())
+    }
+}
+
+pub struct DomTag<D>
+where
+    D: Driver,
+{
+    pub tag: Cow<'static, str>,
+    pub attrs: Vec<DomAttr<D>>,
+    pub children: Vec<DomNode<D>>,
+    driver_store: D::TagStore,
+}
+
+impl<D> DomTag<D>
+where
+    D: Driver,
+{
+    pub fn new<T>(tag: T, attrs: Vec<DomAttr<D>>, children: Vec<DomNode<D>>) -> DomTag<D>
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        DomTag {
+            tag: tag.into(),
+            attrs,
+            children,
+            driver_store: D::new_tag_store(),
+        }
+    }
+
+    pub fn attr(&self, name: &str) -> Option<AttrRefValue<'_>> {
+        self.attrs
+            .iter()
+            .find(|attr| attr.name == name)
+            .map(|attr| (&attr.value).into())
+    }
+}
+
+/// Shaped like an HTML start tag followed by the number of children, e.g.
+/// `<div class="x"> (2 children)`. The children are written out in full
+/// with `{:#?}`.
+impl<D> fmt::Debug for DomTag<D>
+where
+    D: Driver,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<{}", self.tag)?;
+        for attr in &self.attrs {
+            write!(f, " {:?}", attr)?;
+        }
+        if f.alternate() {
+            return write!(f, "> {:#?}", self.children);
+        }
+        match self.children.len() {
+            1 => f.write_str("> (1 child)"),
+            len => write!(f, "> ({} children)", len),
+        }
+    }
+}
+
+impl<D> Tag<D> for DomTag<D>
+where
+    D: Driver,
+{
+    fn is_tag_static(&self) -> bool {
+        false
+    }
+
+    fn tag(&self) -> &str {
+        self.tag.as_ref()
+    }
+
+    fn visit_children<NV>(&mut self, visitor: &mut NV) -> Result<(), NV::Err>
+    where
+        NV: NodeVisitor<D>,
+    {
+        self.children.visit(&mut 0, visitor)
+    }
+
+    fn diff_children<ND>(&mut self, ancestor: &mut Self, differ: &mut ND) -> Result<(), ND::Err>
+    where
+        ND: NodeDiffer<D>,
+    {
+        self.children
+            .diff(&mut 0, &mut 0, &mut ancestor.children, differ)
+    }
+
+    fn visit_attrs<AV>(&mut self, visitor: &mut AV) -> Result<(), AV::Err>
+    where
+        AV: AttrVisitor<D>,
+    {
+        self.attrs.visit(visitor)
+    }
+
+    fn diff_attrs<AD>(&mut self, ancestor: &mut Self, differ: &mut AD) -> Result<(), AD::Err>
+    where
+        AD: AttrDiffer<D>,
+    {
+        self.attrs.diff(&mut ancestor.attrs, differ)
+    }
+
+    fn driver_store(&mut self) -> &mut D::TagStore {
+        &mut self.driver_store
+    }
+}
+
+pub struct DomAttr<D>
+where
+    D: Driver,
+{
+    pub name: Cow<'static, str>,
+    pub value: AttrValue,
+    driver_store: D::AttrStore,
+}
+
+impl<D> DomAttr<D>
+where
+    D: Driver,
+{
+    pub fn new<N, V>(name: N, value: V) -> DomAttr<D>
+    where
+        N: Into<Cow<'static, str>>,
+        V: Into<AttrValue>,
+    {
+        DomAttr {
+            name: name.into(),
+            value: value.into(),
+            driver_store: D::new_attr_store(),
+        }
+    }
+}
+
+impl<D> fmt::Debug for DomAttr<D>
+where
+    D: Driver,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_attr(self, f)
+    }
+}
+
+impl<D> Attr<D> for DomAttr<D>
+where
+    D: Driver,
+{
+    fn is_value_static(&self) -> bool {
+        false
+    }
+
+    fn name(&self) -> &str {
+        self.name.as_ref()
+    }
+
+    fn value(&self) -> AttrRefValue<'_> {
+        (&self.value).into()
+    }
+
+    fn driver_store(&mut self) -> &mut D::AttrStore {
+        &mut self.driver_store
+    }
+}
@@ -0,0 +1,142 @@
+use super::*;
+use std::collections::HashSet;
+
+/// Tags and attributes `sanitize` keeps. Everything else is removed. Names
+/// are matched ignoring ASCII case, as HTML does.
+#[derive(Default)]
+pub struct Allowlist {
+    tags: HashSet<Cow<'static, str>>,
+    attrs: HashSet<Cow<'static, str>>,
+}
+
+impl Allowlist {
+    pub fn new() -> Allowlist {
+        Default::default()
+    }
+
+    /// Text formatting, links, lists and images, as commonly allowed in
+    /// user generated content.
+    pub fn basic() -> Allowlist {
+        let mut allowlist = Allowlist::new();
+        for tag in &[
+            "a",
+            "b",
+            "blockquote",
+            "br",
+            "code",
+            "em",
+            "h1",
+            "h2",
+            "h3",
+            "h4",
+            "h5",
+            "h6",
+            "hr",
+            "i",
+            "img",
+            "li",
+            "ol",
+            "p",
+            "pre",
+            "span",
+            "strong",
+            "ul",
+        ] {
+            allowlist.allow_tag(*tag);
+        }
+        for attr in &["alt", "href", "src", "title"] {
+            allowlist.allow_attr(*attr);
+        }
+        allowlist
+    }
+
+    pub fn allow_tag<T>(&mut self, tag: T) -> &mut Allowlist
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        self.tags.insert(lowercase(tag.into()));
+        self
+    }
+
+    pub fn allow_attr<T>(&mut self, attr: T) -> &mut Allowlist
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        self.attrs.insert(lowercase(attr.into()));
+        self
+    }
+}
+
+fn lowercase(name: Cow<'static, str>) -> Cow<'static, str> {
+    if name.bytes().any(|b| b.is_ascii_uppercase()) {
+        Cow::Owned(name.to_ascii_lowercase())
+    } else {
+        name
+    }
+}
+
+/// Strips `nodes` down to `allowlist`:
+///
+/// - elements with a disallowed tag are removed together with their content,
+/// - comments are removed, as old browsers run conditional comments,
+/// - disallowed attributes and any `on*` event handler attributes are removed,
+///   in any case, e.g. `ONCLICK`,
+/// - URL attributes with a `javascript:`, `vbscript:` or `data:` scheme are
+///   removed.
+pub fn sanitize<D>(nodes: &mut Vec<DomNode<D>>, allowlist: &Allowlist)
+where
+    D: Driver,
+{
+    nodes.retain(|node| {
+        match node {
+            DomNode::Tag(tag) => {
+                allowlist
+                    .tags
+                    .contains(tag.tag.to_ascii_lowercase().as_str())
+            }
+            DomNode::Text(_) => true,
+            DomNode::Comment(_) => false,
+        }
+    });
+    for node in nodes {
+        if let DomNode::Tag(tag) = node {
+            tag.attrs.retain(|attr| is_attr_allowed(attr, allowlist));
+            sanitize(&mut tag.children, allowlist);
+        }
+    }
+}
+
+fn is_attr_allowed<D>(attr: &DomAttr<D>, allowlist: &Allowlist) -> bool
+where
+    D: Driver,
+{
+    let name = attr.name.to_ascii_lowercase();
+    if name.starts_with("on") || !allowlist.attrs.contains(name.as_str()) {
+        return false;
+    }
+    match (is_url_attr(&name), attr.value()) {
+        (true, AttrRefValue::Str(url)) => !is_dangerous_url(url),
+        _ => true,
+    }
+}
+
+fn is_url_attr(name: &str) -> bool {
+    match name {
+        "action" | "background" | "cite" | "formaction" | "href" | "poster" | "src"
+        | "xlink:href" => true,
+        _ => false,
+    }
+}
+
+fn is_dangerous_url(url: &str) -> bool {
+    // Browsers ignore whitespace and control characters within the scheme,
+    // e.g. `java\tscript:`.
+    let scheme = url
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace() && !c.is_ascii_control())
+        .take_while(|c| *c != ':')
+        .collect::<String>()
+        .to_ascii_lowercase();
+    let has_scheme = url.contains(':') && !scheme.contains('/');
+    has_scheme && (scheme == "javascript" || scheme == "vbscript" || scheme == "data")
+}
@@ -0,0 +1,111 @@
+use super::*;
+use std::{iter::Enumerate, slice, vec};
+
+/// Iterates over the nodes of `root` and all their descendants in document
+/// order, i.e. pre-order: an element comes before its children, which come
+/// before its next sibling. Each node is yielded with its path from `root`,
+/// and components with the nodes they render.
+///
+/// The tree is copied with `to_dom` first, rendering its components with
+/// `driver_ctx`, and the copies are yielded, elements without their children
+/// as those follow them. `iter_dom` borrows the nodes of a `DomNode` tree
+/// instead.
+pub fn iter<D, N>(driver_ctx: &DriverCtx<D>, root: &mut N) -> IntoIter<D>
+where
+    D: Driver,
+    N: Node<D> + ?Sized,
+{
+    IntoIter {
+        stack: vec![to_dom(driver_ctx, root).into_iter().enumerate()],
+        path: Path::root(),
+    }
+}
+
+/// Like `iter`, borrowing `nodes` and the children of their elements.
+pub fn iter_dom<D>(nodes: &[DomNode<D>]) -> Iter<'_, D>
+where
+    D: Driver,
+{
+    Iter {
+        stack: vec![nodes.iter().enumerate()],
+        path: Path::root(),
+    }
+}
+
+/// The iterator returned by `iter_dom`. Like `check_limits`, it keeps a
+/// stack of siblings rather than recursing, so deep trees can't overflow the
+/// stack.
+pub struct Iter<'a, D>
+where
+    D: Driver,
+{
+    stack: Vec<Enumerate<slice::Iter<'a, DomNode<D>>>>,
+    // The path of the node yielded last, while its children are pending.
+    path: Path,
+}
+
+impl<'a, D> Iterator for Iter<'a, D>
+where
+    D: Driver,
+{
+    type Item = (Path, &'a DomNode<D>);
+
+    fn next(&mut self) -> Option<(Path, &'a DomNode<D>)> {
+        while let Some(siblings) = self.stack.last_mut() {
+            let (index, node) = match siblings.next() {
+                Some(next) => next,
+                None => {
+                    self.stack.pop();
+                    self.path.pop();
+                    continue;
+                }
+            };
+            let path = self.path.child(index);
+            if let DomNode::Tag(tag) = node {
+                self.path = path.clone();
+                self.stack.push(tag.children.iter().enumerate());
+            }
+            return Some((path, node));
+        }
+        None
+    }
+}
+
+/// The iterator returned by `iter`, which takes the children out of each
+/// element it yields, so no node is copied twice.
+pub struct IntoIter<D>
+where
+    D: Driver,
+{
+    stack: Vec<Enumerate<vec::IntoIter<DomNode<D>>>>,
+    // The path of the node yielded last, while its children are pending.
+    path: Path,
+}
+
+impl<D> Iterator for IntoIter<D>
+where
+    D: Driver,
+{
+    type Item = (Path, DomNode<D>);
+
+    fn next(&mut self) -> Option<(Path, DomNode<D>)> {
+        while let Some(siblings) = self.stack.last_mut() {
+            let (index, mut node) = match siblings.next() {
+                Some(next) => next,
+                None => {
+                    self.stack.pop();
+                    self.path.pop();
+                    continue;
+                }
+            };
+            let path = self.path.child(index);
+            if let DomNode::Tag(tag) = &mut node {
+                self.path = path.clone();
+                let children = mem::take(&mut tag.children);
+                self.stack.push(children.into_iter().enumerate());
+            }
+            return Some((path, node));
+        }
+        None
+    }
+}
@@ -0,0 +1,46 @@
+//! Constructors for common HTML elements, e.g. `div((), text)` for
+//! `TagStatic::new("div", (), text)`. Void elements take no children.
+
+use super::{
+    attr::AttrList,
+    node::{Node, TagStatic},
+};
+use crate::driver::Driver;
+
+macro_rules! elements {
+    ($($name:ident),*) => {
+        $(
+            #[doc = concat!("A `<", stringify!($name), ">` element.")]
+            pub fn $name<D, A, C>(attrs: A, children: C) -> TagStatic<D, C, A>
+            where
+                D: Driver,
+                A: AttrList<D>,
+                C: Node<D>,
+            {
+                TagStatic::new(stringify!($name), attrs, children)
+            }
+        )*
+    };
+}
+
+macro_rules! void_elements {
+    ($($name:ident),*) => {
+        $(
+            #[doc = concat!("A `<", stringify!($name), ">` element, which has no children.")]
+            pub fn $name<D, A>(attrs: A) -> TagStatic<D, (), A>
+            where
+                D: Driver,
+                A: AttrList<D>,
+            {
+                TagStatic::new(stringify!($name), attrs, ())
+            }
+        )*
+    };
+}
+
+elements!(
+    a, button, div, footer, form, h1, h2, h3, header, label, li, main, nav, ol, option, p, section,
+    select, span, table, td, textarea, th, tr, ul
+);
+
+void_elements!(br, hr, img, input);
@@ -0,0 +1,160 @@
+//! Subresource integrity for `<script>` and `<link>`, see
+//! `subresource_integrity`.
+
+use super::attr::{AttrDyn, AttrListEntry, AttrValue};
+use crate::driver::Driver;
+use std::{error::Error, fmt};
+
+/// The `integrity` and `crossorigin` attributes of a `<script>` or `<link>`
+/// loading a resource from another origin, which the browser refuses to use
+/// unless its hash matches. The resource must be served with CORS headers.
+pub fn subresource_integrity<D>(
+    integrity: Integrity,
+    cross_origin: CrossOrigin,
+) -> (AttrListEntry<AttrDyn<D>>, AttrListEntry<AttrDyn<D>>)
+where
+    D: Driver,
+{
+    (
+        AttrListEntry(AttrDyn::new("integrity", integrity)),
+        AttrListEntry(AttrDyn::new("crossorigin", cross_origin)),
+    )
+}
+
+/// The value of an `integrity` attribute: hashes such as `sha384-<base64>`,
+/// separated by spaces, each optionally followed by `?options`. Values are
+/// checked when constructed, since the browser would block a resource with a
+/// malformed value rather than report it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Integrity {
+    value: String,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum IntegrityError {
+    /// There is no hash.
+    Empty,
+    /// The hash at position `hash` isn't prefixed with `sha256-`, `sha384-`
+    /// or `sha512-`.
+    UnknownAlgorithm { hash: usize },
+    /// The digest of the hash at position `hash` isn't the base64 of as many
+    /// bytes as its algorithm produces.
+    InvalidDigest { hash: usize },
+}
+
+impl fmt::Display for IntegrityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IntegrityError::Empty => f.write_str("integrity has no hash"),
+            IntegrityError::UnknownAlgorithm { hash } => {
+                write!(
+                    f,
+                    "hash {} of integrity isn't sha256, sha384 or sha512",
+                    hash
+                )
+            }
+            IntegrityError::InvalidDigest { hash } => {
+                write!(f, "hash {} of integrity has an invalid digest", hash)
+            }
+        }
+    }
+}
+
+impl Error for IntegrityError {}
+
+impl Integrity {
+    pub fn new<V>(value: V) -> Result<Integrity, IntegrityError>
+    where
+        V: Into<String>,
+    {
+        let value = value.into();
+        let mut hashes = 0;
+        for (i, hash) in value.split_ascii_whitespace().enumerate() {
+            check_hash(i, hash)?;
+            hashes += 1;
+        }
+        if hashes == 0 {
+            return Err(IntegrityError::Empty);
+        }
+        Ok(Integrity { value })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.value
+    }
+}
+
+fn check_hash(index: usize, hash: &str) -> Result<(), IntegrityError> {
+    let hash = hash.split('?').next().unwrap_or("");
+    let (algorithm, digest) = match hash.find('-') {
+        Some(i) => (&hash[..i], &hash[i + 1..]),
+        None => (hash, ""),
+    };
+    // The length of the digests in base64, without padding.
+    let len = match algorithm {
+        "sha256" => 43,
+        "sha384" => 64,
+        "sha512" => 86,
+        _ => return Err(IntegrityError::UnknownAlgorithm { hash: index }),
+    };
+    if is_base64(digest, len) {
+        Ok(())
+    } else {
+        Err(IntegrityError::InvalidDigest { hash: index })
+    }
+}
+
+/// Whether `digest` is `len` characters of base64, either in the standard or
+/// in the URL-safe alphabet but not a mix of both, with or without padding.
+fn is_base64(digest: &str, len: usize) -> bool {
+    let data = digest.trim_end_matches('=');
+    let padding = digest.len() - data.len();
+    let in_alphabet = |extra: &[u8]| {
+        data.bytes()
+            .all(|b| b.is_ascii_alphanumeric() || extra.contains(&b))
+    };
+    data.len() == len
+        && (padding == 0 || padding == (4 - len % 4) % 4)
+        && (in_alphabet(b"+/") || in_alphabet(b"-_"))
+}
+
+impl fmt::Display for Integrity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.value)
+    }
+}
+
+impl From<Integrity> for AttrValue {
+    fn from(integrity: Integrity) -> AttrValue {
+        AttrValue::String(integrity.value)
+    }
+}
+
+/// The value of a `crossorigin` attribute, i.e. whether credentials are
+/// sent along with the request.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CrossOrigin {
+    Anonymous,
+    UseCredentials,
+}
+
+impl CrossOrigin {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CrossOrigin::Anonymous => "anonymous",
+            CrossOrigin::UseCredentials => "use-credentials",
+        }
+    }
+}
+
+impl fmt::Display for CrossOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<CrossOrigin> for AttrValue {
+    fn from(cross_origin: CrossOrigin) -> AttrValue {
+        AttrValue::Str(cross_origin.as_str())
+    }
+}
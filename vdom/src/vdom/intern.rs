@@ -0,0 +1,98 @@
+use std::{borrow::Cow, collections::HashSet, fmt, ops::Deref, sync::Arc};
+
+/// Deduplicates tag and attribute names that are only known at runtime, e.g.
+/// keys of attribute maps repeated across a large table.
+///
+/// Every distinct name is allocated once and shared by reference counting,
+/// so it's freed once the interner and the nodes using it are dropped. The
+/// count is atomic, so nodes with interned names can still be rendered in
+/// parallel.
+#[derive(Default)]
+pub struct Interner {
+    names: HashSet<Arc<str>>,
+}
+
+impl Interner {
+    pub fn new() -> Interner {
+        Default::default()
+    }
+
+    pub fn intern(&mut self, name: &str) -> Name {
+        if let Some(name) = self.names.get(name) {
+            return Name::Interned(name.clone());
+        }
+        let name: Arc<str> = name.into();
+        self.names.insert(name.clone());
+        Name::Interned(name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+/// The name of a tag or attribute, either written in the code, built at
+/// runtime, or shared with the other names interned by an `Interner`.
+#[derive(Clone)]
+pub enum Name {
+    Static(&'static str),
+    Owned(String),
+    Interned(Arc<str>),
+}
+
+impl Deref for Name {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        match self {
+            Name::Static(name) => name,
+            Name::Owned(name) => name,
+            Name::Interned(name) => name,
+        }
+    }
+}
+
+impl PartialEq for Name {
+    fn eq(&self, other: &Name) -> bool {
+        **self == **other
+    }
+}
+
+impl Eq for Name {}
+
+impl fmt::Debug for Name {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl fmt::Display for Name {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self)
+    }
+}
+
+impl From<&'static str> for Name {
+    fn from(name: &'static str) -> Name {
+        Name::Static(name)
+    }
+}
+
+impl From<String> for Name {
+    fn from(name: String) -> Name {
+        Name::Owned(name)
+    }
+}
+
+impl From<Cow<'static, str>> for Name {
+    fn from(name: Cow<'static, str>) -> Name {
+        match name {
+            Cow::Borrowed(name) => Name::Static(name),
+            Cow::Owned(name) => Name::Owned(name),
+        }
+    }
+}
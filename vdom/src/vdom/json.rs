@@ -0,0 +1,352 @@
+use super::{
+    attr::AttrValue,
+    dom::{DomAttr, DomNode, LimitChecker, LimitError, Limits},
+    element_info::{is_valid_attr_name, is_valid_tag_name},
+    node::{Node, Text},
+    patch::{diff, Patch},
+    path::Path,
+};
+use crate::driver::{Driver, DriverCtx};
+use serde_json::{Map, Value};
+
+/// Converts `nodes` to the JSON that `json_patch` operations apply to, for
+/// clients that aren't written in Rust. The nodes are an array, an element is
+/// `{"tag": .., "attrs": {name: value}, "children": [..]}`, a text node is
+/// `{"text": ..}` and a comment is `{"text": .., "comment": true}`, so text
+/// operations apply to both. Attributes without a value are `true`.
+pub fn to_json<D>(nodes: &[DomNode<D>]) -> Value
+where
+    D: Driver,
+{
+    Value::Array(nodes.iter().map(node_to_json).collect())
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum JsonError {
+    /// The value at the JSON Pointer `pointer` is not a JSON `expected`.
+    InvalidType {
+        pointer: String,
+        expected: &'static str,
+    },
+    /// The object at the pointer is neither an element nor a text node.
+    UnknownShape(String),
+    /// The object at `pointer` has a member `name` that isn't in the schema.
+    UnknownMember { pointer: String, name: String },
+    /// The tag or attribute name at `pointer` can't be written as HTML, as
+    /// it would be read as other markup, see `element_info`.
+    InvalidName { pointer: String, name: String },
+    /// The nodes exceed the limits given to `from_json_limited`.
+    LimitExceeded(LimitError),
+}
+
+impl From<LimitError> for JsonError {
+    fn from(err: LimitError) -> JsonError {
+        JsonError::LimitExceeded(err)
+    }
+}
+
+impl<D> DomNode<D>
+where
+    D: Driver,
+{
+    /// Builds a node from JSON as produced by `to_json`, e.g. to render UIs
+    /// defined by a server. Attributes that are `false` or `null` are
+    /// omitted, and `attrs` and `children` may be left out if empty. Names
+    /// that would be read as other markup when rendered are rejected.
+    pub fn from_json(value: &Value) -> Result<DomNode<D>, JsonError> {
+        let mut checker = LimitChecker::new(Limits::default());
+        node_from_json(value, "", &Path::root().child(0), &mut checker)
+    }
+}
+
+/// Builds nodes from a JSON array as produced by `to_json`.
+pub fn from_json<D>(value: &Value) -> Result<Vec<DomNode<D>>, JsonError>
+where
+    D: Driver,
+{
+    from_json_limited(value, Limits::default())
+}
+
+/// Like `from_json`, but fails with `JsonError::LimitExceeded` as soon as the
+/// nodes exceed `limits`, e.g. for JSON from an untrusted client. Nodes are
+/// checked before their children are read, so a document nested too deeply
+/// is rejected at the depth limit.
+pub fn from_json_limited<D>(value: &Value, limits: Limits) -> Result<Vec<DomNode<D>>, JsonError>
+where
+    D: Driver,
+{
+    let mut checker = LimitChecker::new(limits);
+    nodes_from_json(value, "", &Path::root(), &mut checker)
+}
+
+fn nodes_from_json<D>(
+    value: &Value,
+    pointer: &str,
+    parent: &Path,
+    checker: &mut LimitChecker,
+) -> Result<Vec<DomNode<D>>, JsonError>
+where
+    D: Driver,
+{
+    let values = match value {
+        Value::Array(values) => values,
+        _ => return Err(invalid_type(pointer, "array")),
+    };
+    if !parent.is_root() {
+        checker.children(parent, values.len())?;
+    }
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            node_from_json(
+                value,
+                &format!("{}/{}", pointer, i),
+                &parent.child(i),
+                checker,
+            )
+        })
+        .collect()
+}
+
+fn node_from_json<D>(
+    value: &Value,
+    pointer: &str,
+    path: &Path,
+    checker: &mut LimitChecker,
+) -> Result<DomNode<D>, JsonError>
+where
+    D: Driver,
+{
+    checker.node(path)?;
+    let object = match value {
+        Value::Object(object) => object,
+        _ => return Err(invalid_type(pointer, "object")),
+    };
+    let member_pointer = |name: &str| format!("{}/{}", pointer, name);
+
+    let (members, node) = match (object.get("tag"), object.get("text")) {
+        (Some(tag), None) => {
+            let tag = match tag {
+                Value::String(tag) if is_valid_tag_name(tag) => tag.clone(),
+                Value::String(tag) => {
+                    return Err(JsonError::InvalidName {
+                        pointer: member_pointer("tag"),
+                        name: tag.clone(),
+                    })
+                }
+                _ => return Err(invalid_type(&member_pointer("tag"), "string")),
+            };
+            let attrs = match object.get("attrs") {
+                Some(attrs) => attrs_from_json(attrs, &member_pointer("attrs"), path, checker)?,
+                None => Vec::new(),
+            };
+            let children = match object.get("children") {
+                Some(children) => {
+                    nodes_from_json(children, &member_pointer("children"), path, checker)?
+                }
+                None => Vec::new(),
+            };
+            (
+                &["tag", "attrs", "children"][..],
+                DomNode::tag(tag, attrs, children),
+            )
+        }
+        (None, Some(text)) => {
+            let text = match text {
+                Value::String(text) => text.clone(),
+                _ => return Err(invalid_type(&member_pointer("text"), "string")),
+            };
+            checker.text(path, &text)?;
+            let node = match object.get("comment") {
+                Some(Value::Bool(true)) => DomNode::comment(text),
+                Some(Value::Bool(false)) | None => DomNode::text(text),
+                Some(_) => return Err(invalid_type(&member_pointer("comment"), "boolean")),
+            };
+            (&["text", "comment"][..], node)
+        }
+        _ => return Err(JsonError::UnknownShape(pointer.to_string())),
+    };
+
+    match object.keys().find(|name| !members.contains(&name.as_str())) {
+        Some(name) => {
+            Err(JsonError::UnknownMember {
+                pointer: pointer.to_string(),
+                name: name.clone(),
+            })
+        }
+        None => Ok(node),
+    }
+}
+
+fn attrs_from_json<D>(
+    value: &Value,
+    pointer: &str,
+    path: &Path,
+    checker: &LimitChecker,
+) -> Result<Vec<DomAttr<D>>, JsonError>
+where
+    D: Driver,
+{
+    let object = match value {
+        Value::Object(object) => object,
+        _ => return Err(invalid_type(pointer, "object")),
+    };
+    let mut attrs = Vec::new();
+    for (name, value) in object {
+        if !is_valid_attr_name(name) {
+            return Err(JsonError::InvalidName {
+                pointer: format!("{}/{}", pointer, escape(name)),
+                name: name.clone(),
+            });
+        }
+        let value = match value {
+            Value::Bool(true) => AttrValue::True,
+            Value::Bool(false) | Value::Null => continue,
+            Value::String(value) => {
+                checker.text(path, value)?;
+                AttrValue::String(value.clone())
+            }
+            _ => {
+                let pointer = format!("{}/{}", pointer, escape(name));
+                return Err(invalid_type(&pointer, "string, boolean or null"));
+            }
+        };
+        attrs.push(DomAttr::new(name.clone(), value));
+    }
+    Ok(attrs)
+}
+
+fn invalid_type(pointer: &str, expected: &'static str) -> JsonError {
+    JsonError::InvalidType {
+        pointer: pointer.to_string(),
+        expected,
+    }
+}
+
+/// Diffs `curr` against `ancestor` and returns the changes as a JSON Patch
+/// (RFC 6902) for the JSON of `to_json`.
+pub fn diff_to_json_patch<D, N>(driver_ctx: &DriverCtx<D>, curr: &mut N, ancestor: &mut N) -> Value
+where
+    D: Driver,
+    N: Node<D>,
+{
+    json_patch(&diff(driver_ctx, curr, ancestor))
+}
+
+/// Converts `patches` to a JSON Patch (RFC 6902) for the JSON of `to_json`,
+/// with the operations in the same order. A `SpliceText` has no equivalent in
+/// JSON Patch and is written as a `splice` operation with the members `start`
+/// and `delete` besides `value`. A `move` operation has the `Key` of the moved
+/// item as its `key` member, formatted with `Display`.
+pub fn json_patch<D>(patches: &[Patch<D>]) -> Value
+where
+    D: Driver,
+{
+    Value::Array(patches.iter().map(patch_to_json).collect())
+}
+
+fn patch_to_json<D>(patch: &Patch<D>) -> Value
+where
+    D: Driver,
+{
+    let mut op = Map::new();
+    let (name, pointer, value) = match patch {
+        Patch::InsertNode { path, node } => ("add", pointer(path), Some(node_to_json(node))),
+        Patch::RemoveNode { path } => ("remove", pointer(path), None),
+        Patch::MoveNode { path, to, key } => {
+            op.insert("from".to_string(), Value::String(pointer(path)));
+            op.insert("key".to_string(), Value::String(key.to_string()));
+            ("move", pointer(&path.sibling(*to)), None)
+        }
+        Patch::SetText { path, text } => {
+            let pointer = pointer(path) + "/text";
+            ("replace", pointer, Some(Value::String(text.clone())))
+        }
+        Patch::SpliceText {
+            path,
+            start,
+            delete_len,
+            insert,
+        } => {
+            op.insert("start".to_string(), Value::Number((*start as u64).into()));
+            op.insert(
+                "delete".to_string(),
+                Value::Number((*delete_len as u64).into()),
+            );
+            let pointer = pointer(path) + "/text";
+            ("splice", pointer, Some(Value::String(insert.clone())))
+        }
+        // Adding to an object replaces an existing member, so this works
+        // whether or not the attribute was set before.
+        Patch::SetAttr { path, name, value } => {
+            let pointer = attr_pointer(path, name);
+            ("add", pointer, Some(attr_value_to_json(value)))
+        }
+        Patch::RemoveAttr { path, name } => ("remove", attr_pointer(path, name), None),
+    };
+    op.insert("op".to_string(), Value::String(name.to_string()));
+    op.insert("path".to_string(), Value::String(pointer));
+    if let Some(value) = value {
+        op.insert("value".to_string(), value);
+    }
+    Value::Object(op)
+}
+
+fn node_to_json<D>(node: &DomNode<D>) -> Value
+where
+    D: Driver,
+{
+    let mut object = Map::new();
+    match node {
+        DomNode::Tag(tag) => {
+            let attrs = tag
+                .attrs
+                .iter()
+                .map(|attr| (attr.name.to_string(), attr_value_to_json(&attr.value)))
+                .collect();
+            object.insert("tag".to_string(), Value::String(tag.tag.to_string()));
+            object.insert("attrs".to_string(), Value::Object(attrs));
+            object.insert("children".to_string(), to_json(&tag.children));
+        }
+        DomNode::Text(text) => {
+            object.insert("text".to_string(), Value::String(text.get().to_string()));
+        }
+        DomNode::Comment(comment) => {
+            object.insert("text".to_string(), Value::String(comment.get().to_string()));
+            object.insert("comment".to_string(), Value::Bool(true));
+        }
+    }
+    Value::Object(object)
+}
+
+fn attr_value_to_json(value: &AttrValue) -> Value {
+    match value {
+        AttrValue::True => Value::Bool(true),
+        AttrValue::Null => Value::Null,
+        AttrValue::Str(value) => Value::String(value.to_string()),
+        AttrValue::String(value) => Value::String(value.clone()),
+    }
+}
+
+/// The JSON Pointer (RFC 6901) of the node at `path`.
+fn pointer(path: &Path) -> String {
+    let mut pointer = String::new();
+    for (depth, index) in path.indices().iter().enumerate() {
+        if depth > 0 {
+            pointer.push_str("/children");
+        }
+        pointer.push('/');
+        pointer.push_str(&index.to_string());
+    }
+    pointer
+}
+
+fn attr_pointer(path: &Path, name: &str) -> String {
+    format!("{}/attrs/{}", pointer(path), escape(name))
+}
+
+/// Escapes `~` and `/`, which have a meaning within a JSON Pointer.
+fn escape(name: &str) -> String {
+    name.replace('~', "~0").replace('/', "~1")
+}
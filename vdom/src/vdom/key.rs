@@ -0,0 +1,101 @@
+use std::{borrow::Cow, fmt, sync::Arc};
+
+/// Identifies an item of a keyed list across renders, without forcing every
+/// key into a `String`. Cloning is cheap for all variants, and keys can be
+/// sent to other threads along with their nodes.
+///
+/// Keys of different variants are never equal, even if they look alike, e.g.
+/// `Key::U64(1) != Key::I64(1)`, so a list should use a single kind of key.
+/// Across variants they order by variant first. Likewise, keys display with
+/// their variant as a prefix, e.g. `u64:1` and `i64:1`, with bytes in hex.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Key {
+    U64(u64),
+    I64(i64),
+    Str(Cow<'static, str>),
+    /// Raw bytes, e.g. of a UUID.
+    Bytes(Arc<[u8]>),
+}
+
+impl From<u64> for Key {
+    fn from(v: u64) -> Key {
+        Key::U64(v)
+    }
+}
+
+impl From<u32> for Key {
+    fn from(v: u32) -> Key {
+        Key::U64(v.into())
+    }
+}
+
+impl From<usize> for Key {
+    fn from(v: usize) -> Key {
+        Key::U64(v as u64)
+    }
+}
+
+impl From<i64> for Key {
+    fn from(v: i64) -> Key {
+        Key::I64(v)
+    }
+}
+
+impl From<i32> for Key {
+    fn from(v: i32) -> Key {
+        Key::I64(v.into())
+    }
+}
+
+impl From<&'static str> for Key {
+    fn from(v: &'static str) -> Key {
+        Key::Str(Cow::Borrowed(v))
+    }
+}
+
+impl From<String> for Key {
+    fn from(v: String) -> Key {
+        Key::Str(Cow::Owned(v))
+    }
+}
+
+impl From<Cow<'static, str>> for Key {
+    fn from(v: Cow<'static, str>) -> Key {
+        Key::Str(v)
+    }
+}
+
+impl From<[u8; 16]> for Key {
+    fn from(v: [u8; 16]) -> Key {
+        Key::Bytes(Arc::from(&v[..]))
+    }
+}
+
+impl From<&[u8]> for Key {
+    fn from(v: &[u8]) -> Key {
+        Key::Bytes(Arc::from(v))
+    }
+}
+
+impl From<Vec<u8>> for Key {
+    fn from(v: Vec<u8>) -> Key {
+        Key::Bytes(Arc::from(v))
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Key::U64(v) => write!(f, "u64:{}", v),
+            Key::I64(v) => write!(f, "i64:{}", v),
+            Key::Str(v) => write!(f, "str:{}", v),
+            Key::Bytes(v) => {
+                f.write_str("bytes:")?;
+                for byte in v.iter() {
+                    write!(f, "{:02x}", byte)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
@@ -1,2 +1,18 @@
+pub mod arena;
 pub mod attr;
+pub mod collect;
+pub mod debug;
+pub mod dom;
+pub use vdom_element_info as element_info;
+pub mod elements;
+pub mod integrity;
+pub mod intern;
+#[cfg(feature = "serde_json")]
+pub mod json;
+pub mod key;
 pub mod node;
+pub mod patch;
+pub mod path;
+pub mod profile;
+pub mod style;
+pub mod tee;
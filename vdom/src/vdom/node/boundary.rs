@@ -0,0 +1,379 @@
+use super::*;
+use crate::driver::DriverCtx;
+use std::{
+    any::Any,
+    cell::RefCell,
+    panic::{self, AssertUnwindSafe},
+    rc::Rc,
+};
+
+/// A panic caught by an `ErrorBoundary`.
+#[derive(Clone, Debug)]
+pub struct Failure {
+    /// The panic message, if the panic had a string payload.
+    pub message: Option<String>,
+}
+
+impl Failure {
+    fn from_payload(payload: Box<dyn Any + Send>) -> Failure {
+        let message = match payload.downcast::<String>() {
+            Ok(message) => Some(*message),
+            Err(payload) => {
+                payload
+                    .downcast_ref::<&str>()
+                    .map(|message| message.to_string())
+            }
+        };
+        Failure { message }
+    }
+}
+
+#[derive(Default)]
+struct BoundaryState {
+    latch: bool,
+    latched: Option<Failure>,
+    failures: Vec<Failure>,
+}
+
+/// The state of an `ErrorBoundary` across renders. Keep it alive for as long
+/// as the boundary's region is mounted.
+#[derive(Clone, Default)]
+pub struct Boundary {
+    state: Rc<RefCell<BoundaryState>>,
+}
+
+impl Boundary {
+    /// A boundary that retries rendering its subtree on every render.
+    pub fn new() -> Boundary {
+        Boundary::default()
+    }
+
+    /// A boundary that keeps rendering the fallback once its subtree failed,
+    /// until `reset`.
+    pub fn latching() -> Boundary {
+        let boundary = Boundary::default();
+        boundary.state.borrow_mut().latch = true;
+        boundary
+    }
+
+    /// Retries rendering the subtree after a latched failure.
+    pub fn reset(&self) {
+        self.state.borrow_mut().latched = None;
+    }
+
+    /// Takes the failures caught since the last call, e.g. for logging.
+    pub fn take_failures(&self) -> Vec<Failure> {
+        self.state.borrow_mut().failures.drain(..).collect()
+    }
+
+    /// Records the panic with `payload`, returning the fallback for it.
+    fn fail<X>(&self, payload: Box<dyn Any + Send>, fallback: &dyn Fn(&Failure) -> X) -> X {
+        let failure = Failure::from_payload(payload);
+        let node = fallback(&failure);
+        let mut state = self.state.borrow_mut();
+        if state.latch {
+            state.latched = Some(failure.clone());
+        }
+        state.failures.push(failure);
+        node
+    }
+}
+
+/// Renders a subtree, or a fallback if rendering it panicked, so bad data in
+/// one region doesn't take down the whole app. Switching between the two
+/// replaces the node.
+///
+/// Panics are caught while the subtree is built, and while the components
+/// within it render as it's visited or diffed, e.g. by the HTML renderer or
+/// a DOM driver. The boundary renders them before passing the subtree on, so
+/// a panic leaves nothing of it behind. New components are rendered with the
+/// `driver_ctx` of the visitor or differ, and aren't caught without one.
+/// Panics of the visitor or differ itself aren't caught either, and none
+/// can be if the app is built with `panic = "abort"`, which is the default
+/// for `wasm32-unknown-unknown`.
+pub struct ErrorBoundary<N, X> {
+    boundary: Boundary,
+    fallback: Box<dyn Fn(&Failure) -> X>,
+    region: Region<N, X>,
+}
+
+enum Region<N, X> {
+    Ok(N),
+    Failed(X),
+}
+
+impl<N, X> ErrorBoundary<N, X> {
+    /// Calls `render`, and `fallback` if it or a component panicked, or
+    /// `boundary` latched. Any state `render` mutates may be left
+    /// half-updated by a panic.
+    pub fn new<FN, FX>(boundary: &Boundary, render: FN, fallback: FX) -> ErrorBoundary<N, X>
+    where
+        FN: FnOnce() -> N,
+        FX: Fn(&Failure) -> X + 'static,
+    {
+        let latched = boundary.state.borrow().latched.clone();
+        let region = match latched {
+            Some(failure) => Region::Failed(fallback(&failure)),
+            None => {
+                match panic::catch_unwind(AssertUnwindSafe(render)) {
+                    Ok(node) => Region::Ok(node),
+                    Err(payload) => Region::Failed(boundary.fail(payload, &fallback)),
+                }
+            }
+        };
+        ErrorBoundary {
+            boundary: boundary.clone(),
+            fallback: Box::new(fallback),
+            region,
+        }
+    }
+
+    /// Whether the fallback is rendered.
+    pub fn is_failed(&self) -> bool {
+        match self.region {
+            Region::Ok(_) => false,
+            Region::Failed(_) => true,
+        }
+    }
+
+    fn catch<F>(&mut self, f: F)
+    where
+        F: FnOnce(&mut N),
+    {
+        let node = match &mut self.region {
+            Region::Ok(node) => node,
+            Region::Failed(_) => return,
+        };
+        if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| f(node))) {
+            self.region = Region::Failed(self.boundary.fail(payload, &*self.fallback));
+        }
+    }
+}
+
+impl<D, N, X> Node<D> for ErrorBoundary<N, X>
+where
+    D: Driver,
+    N: Node<D>,
+    X: Node<D>,
+{
+    fn visit<NV>(&mut self, index: &mut usize, visitor: &mut NV) -> Result<(), NV::Err>
+    where
+        NV: NodeVisitor<D>,
+    {
+        let mut renderer = CompRenderer {
+            driver_ctx: visitor.driver_ctx(),
+        };
+        self.catch(|node| {
+            match node.visit(&mut { *index }, &mut renderer) {
+                Ok(()) => {}
+                Err(never) => match never {},
+            }
+        });
+        match &mut self.region {
+            Region::Ok(node) => node.visit(index, visitor),
+            Region::Failed(node) => node.visit(index, visitor),
+        }
+    }
+
+    fn diff<ND>(
+        &mut self,
+        curr_index: &mut usize,
+        ancestor_index: &mut usize,
+        ancestor: &mut Self,
+        differ: &mut ND,
+    ) -> Result<(), ND::Err>
+    where
+        ND: NodeDiffer<D>,
+    {
+        let driver_ctx = differ.driver_ctx();
+        match &mut ancestor.region {
+            Region::Ok(ancestor) => {
+                self.catch(|node| {
+                    let mut renderer = CompDiffRenderer { driver_ctx };
+                    let result = node.diff(
+                        &mut { *curr_index },
+                        &mut { *ancestor_index },
+                        ancestor,
+                        &mut renderer,
+                    );
+                    match result {
+                        Ok(()) => {}
+                        Err(never) => match never {},
+                    }
+                })
+            }
+            Region::Failed(_) => {
+                self.catch(|node| {
+                    let mut renderer = CompRenderer { driver_ctx };
+                    match node.visit(&mut { *curr_index }, &mut renderer) {
+                        Ok(()) => {}
+                        Err(never) => match never {},
+                    }
+                })
+            }
+        }
+        match (&mut self.region, &mut ancestor.region) {
+            (Region::Ok(curr), Region::Ok(ancestor)) => {
+                curr.diff(curr_index, ancestor_index, ancestor, differ)
+            }
+            (Region::Failed(curr), Region::Failed(ancestor)) => {
+                curr.diff(curr_index, ancestor_index, ancestor, differ)
+            }
+            (Region::Ok(curr), Region::Failed(ancestor)) => {
+                differ.on_node_removed(*curr_index, ancestor_index, ancestor)?;
+                differ.on_node_added(curr_index, curr)
+            }
+            (Region::Failed(curr), Region::Ok(ancestor)) => {
+                differ.on_node_removed(*curr_index, ancestor_index, ancestor)?;
+                differ.on_node_added(curr_index, curr)
+            }
+        }
+    }
+}
+
+/// Renders the components of a subtree ahead of visiting it.
+struct CompRenderer<D>
+where
+    D: Driver,
+{
+    driver_ctx: Option<DriverCtx<D>>,
+}
+
+impl<D> NodeVisitor<D> for CompRenderer<D>
+where
+    D: Driver,
+{
+    type Err = Infallible;
+
+    fn on_tag<T>(&mut self, _index: usize, tag: &mut T) -> Result<(), Infallible>
+    where
+        T: Tag<D>,
+    {
+        tag.visit_children(self)
+    }
+
+    fn on_text<T>(&mut self, _index: usize, _text: &mut T) -> Result<(), Infallible>
+    where
+        T: Text<D>,
+    {
+        Ok(())
+    }
+
+    fn on_comp<C>(&mut self, index: &mut usize, comp: &mut CompNode<D, C>) -> Result<(), Infallible>
+    where
+        C: Comp<D>,
+    {
+        if comp.comp_ctx().is_none() {
+            match &self.driver_ctx {
+                Some(driver_ctx) => comp.init_comp_ctx(driver_ctx.clone()),
+                None => return Ok(()),
+            }
+        }
+        comp.visit_rendered(index, self)
+    }
+}
+
+/// Renders the components of a subtree ahead of diffing it, as the diff
+/// would. Unlike a diff, it leaves the ancestor's rendered nodes in place,
+/// so the ancestor can still be replaced if a component panics.
+struct CompDiffRenderer<D>
+where
+    D: Driver,
+{
+    driver_ctx: Option<DriverCtx<D>>,
+}
+
+impl<D> NodeDiffer<D> for CompDiffRenderer<D>
+where
+    D: Driver,
+{
+    type Err = Infallible;
+
+    fn on_node_added<N>(&mut self, index: &mut usize, curr: &mut N) -> Result<(), Infallible>
+    where
+        N: Node<D>,
+    {
+        curr.visit(
+            index,
+            &mut CompRenderer {
+                driver_ctx: self.driver_ctx.clone(),
+            },
+        )
+    }
+
+    fn on_node_removed<N>(
+        &mut self,
+        _curr_index: usize,
+        ancestor_index: &mut usize,
+        ancestor: &mut N,
+    ) -> Result<(), Infallible>
+    where
+        N: Node<D>,
+    {
+        ancestor.visit(ancestor_index, &mut NodeCounter)
+    }
+
+    fn on_node_moved(
+        &mut self,
+        _from_index: usize,
+        _to_index: usize,
+        _key: &Key,
+    ) -> Result<(), Infallible> {
+        Ok(())
+    }
+
+    fn on_invariant_violation(
+        &mut self,
+        _curr_index: usize,
+        _expected: &str,
+        _found: &str,
+    ) -> Result<(), Infallible> {
+        Ok(())
+    }
+
+    fn on_tag<T>(
+        &mut self,
+        _curr_index: usize,
+        _ancestor_index: usize,
+        curr: &mut T,
+        ancestor: &mut T,
+    ) -> Result<(), Infallible>
+    where
+        T: Tag<D>,
+    {
+        curr.diff_children(ancestor, self)
+    }
+
+    fn on_text<T>(
+        &mut self,
+        _curr_index: usize,
+        _ancestor_index: usize,
+        _curr: &mut T,
+        _ancestor: &mut T,
+    ) -> Result<(), Infallible>
+    where
+        T: Text<D>,
+    {
+        Ok(())
+    }
+
+    fn on_comp<C>(
+        &mut self,
+        curr_index: &mut usize,
+        ancestor_index: &mut usize,
+        curr: &mut CompNode<D, C>,
+        ancestor: &mut CompNode<D, C>,
+    ) -> Result<(), Infallible>
+    where
+        C: Comp<D>,
+    {
+        if curr.comp_ctx().is_none() {
+            let ctx = ancestor.comp_ctx().expect("ancestor.comp_ctx is None");
+            curr.set_comp_ctx(ctx.clone());
+        }
+        if curr.needs_render(ancestor) {
+            curr.diff_rendered(curr_index, ancestor_index, ancestor, self)?;
+        }
+        Ok(())
+    }
+}
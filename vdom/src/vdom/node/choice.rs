@@ -0,0 +1,64 @@
+use super::*;
+
+// The driver is `Drv` rather than `D`, so the variants can be named `A` to
+// `L` without a gap.
+macro_rules! choice {
+    ($name:ident, $($variant:ident),*) => {
+        /// One of several nodes of different types, e.g. the branches of a
+        /// `match`. When the active variant differs from the ancestor's, the
+        /// node is replaced rather than diffed.
+        pub enum $name<$($variant),*> {
+            $($variant($variant)),*
+        }
+
+        impl<Drv, $($variant),*> Node<Drv> for $name<$($variant),*>
+        where
+            Drv: Driver,
+            $($variant: Node<Drv>),*
+        {
+            fn visit<NV>(&mut self, index: &mut usize, visitor: &mut NV) -> Result<(), NV::Err>
+            where
+                NV: NodeVisitor<Drv>,
+            {
+                match self {
+                    $($name::$variant(node) => node.visit(index, visitor)),*
+                }
+            }
+
+            fn diff<ND>(
+                &mut self,
+                curr_index: &mut usize,
+                ancestor_index: &mut usize,
+                ancestor: &mut Self,
+                differ: &mut ND,
+            ) -> Result<(), ND::Err>
+            where
+                ND: NodeDiffer<Drv>,
+            {
+                match (self, ancestor) {
+                    $(
+                        ($name::$variant(curr), $name::$variant(ancestor)) => {
+                            curr.diff(curr_index, ancestor_index, ancestor, differ)
+                        }
+                    )*
+                    (curr, ancestor) => {
+                        differ.on_node_removed(*curr_index, ancestor_index, ancestor)?;
+                        differ.on_node_added(curr_index, curr)
+                    }
+                }
+            }
+        }
+    };
+}
+
+choice!(Choice2, A, B);
+choice!(Choice3, A, B, C);
+choice!(Choice4, A, B, C, D);
+choice!(Choice5, A, B, C, D, E);
+choice!(Choice6, A, B, C, D, E, F);
+choice!(Choice7, A, B, C, D, E, F, G);
+choice!(Choice8, A, B, C, D, E, F, G, H);
+choice!(Choice9, A, B, C, D, E, F, G, H, I);
+choice!(Choice10, A, B, C, D, E, F, G, H, I, J);
+choice!(Choice11, A, B, C, D, E, F, G, H, I, J, K);
+choice!(Choice12, A, B, C, D, E, F, G, H, I, J, K, L);
@@ -5,6 +5,7 @@ use crate::{
 use futures::{channel::mpsc, Sink, Stream, StreamExt as _};
 use std::{
     cell::{Ref, RefCell, RefMut},
+    fmt,
     future::Future,
     hash::{Hash, Hasher},
     marker::PhantomData,
@@ -82,11 +83,12 @@ where
 
         let rendered = match &mut self.comp_rendered {
             NotRendered => {
-                let instance = self
+                let mut instance = self
                     .comp_ctx
                     .as_ref()
                     .expect("CompNode.comp_ctx is None")
                     .instance_mut();
+                instance.invalidated = false;
                 let rendered = instance.comp.render(&instance.input);
                 self.comp_rendered =
                     Rendered(instance.comp.clone(), instance.input.clone(), rendered);
@@ -128,15 +130,19 @@ where
         };
         let rendered = match &mut self.comp_rendered {
             NotRendered => {
-                let instance = self
+                let mut instance = self
                     .comp_ctx
                     .as_ref()
                     .expect("CompNode.comp_ctx is None")
                     .instance_mut();
-                if ancestor_comp == &instance.comp && ancestor_input == &instance.input {
+                if !instance.invalidated
+                    && ancestor_comp == &instance.comp
+                    && ancestor_input == &instance.input
+                {
                     self.comp_rendered = mem::replace(&mut ancestor.comp_rendered, Taken);
                     return Ok(());
                 } else {
+                    instance.invalidated = false;
                     let rendered = instance.comp.render(&instance.input);
                     self.comp_rendered =
                         Rendered(instance.comp.clone(), instance.input.clone(), rendered);
@@ -152,11 +158,88 @@ where
         rendered.diff(curr_index, ancestor_index, ancestor_rendered, differ)
     }
 
+    /// Whether `diff_rendered` would render this node rather than take what
+    /// `ancestor` rendered, as its component or input changed or it was
+    /// invalidated.
+    pub(crate) fn needs_render(&self, ancestor: &Self) -> bool {
+        use self::CompNodeCompRendered::*;
+
+        match (&self.comp_rendered, &ancestor.comp_rendered) {
+            (NotRendered, Rendered(ancestor_comp, ancestor_input, _)) => {
+                let instance = self
+                    .comp_ctx
+                    .as_ref()
+                    .expect("CompNode.comp_ctx is None")
+                    .instance();
+                instance.invalidated
+                    || ancestor_comp != &instance.comp
+                    || ancestor_input != &instance.input
+            }
+            _ => false,
+        }
+    }
+
+    /// Renders the component again if it was invalidated, see
+    /// `CompCtx::invalidate`, or its component or input changed since it was
+    /// rendered, and diffs that against what it rendered before, so a driver
+    /// can update it without its owner being rendered. Returns whether it
+    /// did, in which case `index` is advanced past the rendered nodes.
+    pub fn update_rendered<ND>(
+        &mut self,
+        index: &mut usize,
+        differ: &mut ND,
+    ) -> Result<bool, ND::Err>
+    where
+        ND: NodeDiffer<D>,
+    {
+        use self::CompNodeCompRendered::*;
+
+        let (comp, input, mut rendered) = {
+            let mut instance = match &self.comp_ctx {
+                Some(ctx) => ctx.instance_mut(),
+                None => return Ok(false),
+            };
+            match &self.comp_rendered {
+                Rendered(comp, input, _) => {
+                    if !instance.invalidated && comp == &instance.comp && input == &instance.input {
+                        return Ok(false);
+                    }
+                }
+                NotRendered | Taken => return Ok(false),
+            }
+            instance.invalidated = false;
+            let rendered = instance.comp.render(&instance.input);
+            (instance.comp.clone(), instance.input.clone(), rendered)
+        };
+        let ancestor = match &mut self.comp_rendered {
+            Rendered(_, _, ancestor) => ancestor,
+            NotRendered | Taken => unreachable!(),
+        };
+        let mut ancestor_index = *index;
+        rendered.diff(index, &mut ancestor_index, ancestor, differ)?;
+        self.comp_rendered = Rendered(comp, input, rendered);
+        Ok(true)
+    }
+
     pub fn driver_store(&mut self) -> &mut D::CompStore {
         &mut self.driver_store
     }
 }
 
+/// The type of the component, e.g. `CompNode(app::Counter)`, as the input
+/// and rendered nodes needn't implement `Debug`.
+impl<D, C> fmt::Debug for CompNode<D, C>
+where
+    D: Driver,
+    C: Comp<D>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("CompNode")
+            .field(&format_args!("{}", std::any::type_name::<C>()))
+            .finish()
+    }
+}
+
 impl<D, C> Node<D> for CompNode<D, C>
 where
     D: Driver,
@@ -191,6 +274,9 @@ where
     pub comp: C,
     pub input: C::Input,
     driver_ctx: DriverCtx<D>,
+    /// Whether the component is to be rendered even if neither `comp` nor
+    /// `input` changed.
+    invalidated: bool,
     phantom: PhantomData<D>,
 }
 
@@ -216,6 +302,7 @@ where
             comp,
             input,
             driver_ctx,
+            invalidated: false,
             phantom: PhantomData,
         });
         ctx
@@ -310,6 +397,13 @@ where
         // Sender { sender, id }
     }
 
+    /// Has the component rendered again the next time it's diffed or
+    /// updated, see `CompNode::update_rendered`, even if neither its state nor
+    /// its input changed, e.g. once a `Resource` it renders settled.
+    pub fn invalidate(&self) {
+        self.with_instance_mut(|instance| instance.invalidated = true);
+    }
+
     pub fn with_instance<F, R>(&self, f: F) -> Option<R>
     where
         F: FnOnce(&CompInstance<D, C>) -> R,
@@ -0,0 +1,116 @@
+use super::*;
+use std::{
+    any::{Any, TypeId},
+    cell::RefCell,
+    collections::HashMap,
+    rc::Rc,
+};
+
+thread_local! {
+    static CONTEXT: RefCell<HashMap<TypeId, Vec<Rc<dyn Any>>>> = RefCell::new(HashMap::new());
+}
+
+/// Returns the value of the innermost `ContextProvider<T, _>` currently being
+/// visited or diffed, e.g. from within `Comp::render`.
+///
+/// Components whose comp and input are unchanged are not re-rendered on diff,
+/// so they don't observe a changed context value until they are.
+pub fn use_context<T>() -> Option<Rc<T>>
+where
+    T: 'static,
+{
+    CONTEXT
+        .with(|context| {
+            context
+                .borrow()
+                .get(&TypeId::of::<T>())
+                .and_then(|values| values.last().cloned())
+        })
+        .map(|value| value.downcast::<T>().ok().unwrap())
+}
+
+/// Whether a `ContextProvider` is being visited or diffed on this thread.
+pub(crate) fn is_providing() -> bool {
+    CONTEXT.with(|context| !context.borrow().is_empty())
+}
+
+struct ContextGuard(TypeId);
+
+impl ContextGuard {
+    fn push<T>(value: &Rc<T>) -> ContextGuard
+    where
+        T: 'static,
+    {
+        let type_id = TypeId::of::<T>();
+        CONTEXT.with(|context| {
+            context
+                .borrow_mut()
+                .entry(type_id)
+                .or_insert_with(Vec::new)
+                .push(value.clone());
+        });
+        ContextGuard(type_id)
+    }
+}
+
+impl Drop for ContextGuard {
+    fn drop(&mut self) {
+        CONTEXT.with(|context| {
+            let mut context = context.borrow_mut();
+            let values = context.get_mut(&self.0).unwrap();
+            values.pop();
+            if values.is_empty() {
+                context.remove(&self.0);
+            }
+        });
+    }
+}
+
+/// Makes `value` available through `use_context::<T>()` while its subtree is
+/// visited or diffed. Nested providers of the same type shadow outer ones.
+pub struct ContextProvider<T, N> {
+    value: Rc<T>,
+    node: N,
+}
+
+impl<T, N> ContextProvider<T, N>
+where
+    T: 'static,
+{
+    pub fn new(value: T, node: N) -> ContextProvider<T, N> {
+        ContextProvider {
+            value: Rc::new(value),
+            node,
+        }
+    }
+}
+
+impl<D, T, N> Node<D> for ContextProvider<T, N>
+where
+    D: Driver,
+    T: 'static,
+    N: Node<D>,
+{
+    fn visit<NV>(&mut self, index: &mut usize, visitor: &mut NV) -> Result<(), NV::Err>
+    where
+        NV: NodeVisitor<D>,
+    {
+        let _guard = ContextGuard::push(&self.value);
+        self.node.visit(index, visitor)
+    }
+
+    fn diff<ND>(
+        &mut self,
+        curr_index: &mut usize,
+        ancestor_index: &mut usize,
+        ancestor: &mut Self,
+        differ: &mut ND,
+    ) -> Result<(), ND::Err>
+    where
+        ND: NodeDiffer<D>,
+    {
+        let _guard = ContextGuard::push(&self.value);
+        self.node
+            .diff(curr_index, ancestor_index, &mut ancestor.node, differ)
+    }
+}
@@ -0,0 +1,355 @@
+use super::*;
+use crate::vdom::{
+    attr::{Attr, AttrNs, AttrRefValue, AttrValue},
+    path::Path,
+};
+use std::{any::TypeId, rc::Rc};
+
+/// A node of any type, e.g. to store in a struct field or to keep trees of
+/// different shapes in one `Vec`, which would otherwise need the full type
+/// of each tree. The node is copied into an owned tree when it's created.
+/// Attributes are copied with their values, namespaces and listeners.
+///
+/// A component couldn't be updated once copied, as its state lives in its
+/// `CompNode`, so nodes with components are rejected by `new`. A component
+/// can hold a `DynNode` in what it renders instead.
+///
+/// A `DynNode` is diffed against an ancestor made from a node of the same
+/// type as the trees they hold, and replaces it otherwise.
+pub struct DynNode<D>
+where
+    D: Driver,
+{
+    type_id: TypeId,
+    nodes: Vec<ErasedNode<D>>,
+}
+
+impl<D> DynNode<D>
+where
+    D: Driver,
+{
+    pub fn new<N>(mut node: N) -> Result<DynNode<D>, CompError>
+    where
+        N: Node<D> + 'static,
+    {
+        let mut builder = ErasedBuilder {
+            parent: Path::root(),
+            nodes: Vec::new(),
+        };
+        node.visit(&mut 0, &mut builder)?;
+        Ok(DynNode {
+            type_id: TypeId::of::<N>(),
+            nodes: builder.nodes,
+        })
+    }
+
+    /// Whether this node was made from a node of type `N`.
+    pub fn is<N>(&self) -> bool
+    where
+        N: Node<D> + 'static,
+    {
+        self.type_id == TypeId::of::<N>()
+    }
+}
+
+impl<D> Node<D> for DynNode<D>
+where
+    D: Driver,
+{
+    fn visit<NV>(&mut self, index: &mut usize, visitor: &mut NV) -> Result<(), NV::Err>
+    where
+        NV: NodeVisitor<D>,
+    {
+        self.nodes.visit(index, visitor)
+    }
+
+    fn diff<ND>(
+        &mut self,
+        curr_index: &mut usize,
+        ancestor_index: &mut usize,
+        ancestor: &mut Self,
+        differ: &mut ND,
+    ) -> Result<(), ND::Err>
+    where
+        ND: NodeDiffer<D>,
+    {
+        if self.type_id == ancestor.type_id {
+            return self
+                .nodes
+                .diff(curr_index, ancestor_index, &mut ancestor.nodes, differ);
+        }
+        differ.on_node_removed(*curr_index, ancestor_index, &mut ancestor.nodes)?;
+        differ.on_node_added(curr_index, &mut self.nodes)
+    }
+}
+
+enum ErasedNode<D>
+where
+    D: Driver,
+{
+    Tag(ErasedTag<D>),
+    Text(TextDyn<D>),
+    Comment(Comment<D>),
+}
+
+impl<D> Node<D> for ErasedNode<D>
+where
+    D: Driver,
+{
+    fn visit<NV>(&mut self, index: &mut usize, visitor: &mut NV) -> Result<(), NV::Err>
+    where
+        NV: NodeVisitor<D>,
+    {
+        match self {
+            ErasedNode::Tag(tag) => visitor.on_tag(*index, tag)?,
+            ErasedNode::Text(text) => visitor.on_text(*index, text)?,
+            ErasedNode::Comment(comment) => visitor.on_text(*index, comment)?,
+        }
+        *index += 1;
+        Ok(())
+    }
+
+    fn diff<ND>(
+        &mut self,
+        curr_index: &mut usize,
+        ancestor_index: &mut usize,
+        ancestor: &mut Self,
+        differ: &mut ND,
+    ) -> Result<(), ND::Err>
+    where
+        ND: NodeDiffer<D>,
+    {
+        match (self, ancestor) {
+            (ErasedNode::Tag(curr), ErasedNode::Tag(ancestor)) if curr.tag == ancestor.tag => {
+                differ.on_tag(*curr_index, *ancestor_index, curr, ancestor)?
+            }
+            (ErasedNode::Text(curr), ErasedNode::Text(ancestor)) => {
+                differ.on_text(*curr_index, *ancestor_index, curr, ancestor)?
+            }
+            (ErasedNode::Comment(curr), ErasedNode::Comment(ancestor)) => {
+                differ.on_text(*curr_index, *ancestor_index, curr, ancestor)?
+            }
+            (curr, ancestor) => {
+                differ.on_node_removed(*curr_index, ancestor_index, ancestor)?;
+                return differ.on_node_added(curr_index, curr);
+            }
+        }
+        *curr_index += 1;
+        *ancestor_index += 1;
+        Ok(())
+    }
+}
+
+struct ErasedTag<D>
+where
+    D: Driver,
+{
+    tag: String,
+    is_tag_static: bool,
+    attrs: Vec<ErasedAttr<D>>,
+    children: Vec<ErasedNode<D>>,
+    driver_store: D::TagStore,
+}
+
+impl<D> Tag<D> for ErasedTag<D>
+where
+    D: Driver,
+{
+    fn is_tag_static(&self) -> bool {
+        self.is_tag_static
+    }
+
+    fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    fn visit_children<NV>(&mut self, visitor: &mut NV) -> Result<(), NV::Err>
+    where
+        NV: NodeVisitor<D>,
+    {
+        self.children.visit(&mut 0, visitor)
+    }
+
+    fn diff_children<ND>(&mut self, ancestor: &mut Self, differ: &mut ND) -> Result<(), ND::Err>
+    where
+        ND: NodeDiffer<D>,
+    {
+        self.children
+            .diff(&mut 0, &mut 0, &mut ancestor.children, differ)
+    }
+
+    fn visit_attrs<AV>(&mut self, visitor: &mut AV) -> Result<(), AV::Err>
+    where
+        AV: AttrVisitor<D>,
+    {
+        self.attrs.visit(visitor)
+    }
+
+    fn diff_attrs<AD>(&mut self, ancestor: &mut Self, differ: &mut AD) -> Result<(), AD::Err>
+    where
+        AD: AttrDiffer<D>,
+    {
+        self.attrs.diff(&mut ancestor.attrs, differ)
+    }
+
+    fn driver_store(&mut self) -> &mut D::TagStore {
+        &mut self.driver_store
+    }
+}
+
+struct ErasedAttr<D>
+where
+    D: Driver,
+{
+    name: String,
+    value: AttrValue,
+    is_prop: bool,
+    listener: Option<Rc<dyn Fn(D::Event)>>,
+    namespace: Option<AttrNs>,
+    tokens: Option<Vec<Cow<'static, str>>>,
+    driver_store: D::AttrStore,
+}
+
+impl<D> Attr<D> for ErasedAttr<D>
+where
+    D: Driver,
+{
+    fn is_value_static(&self) -> bool {
+        false
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn value(&self) -> AttrRefValue<'_> {
+        (&self.value).into()
+    }
+
+    fn driver_store(&mut self) -> &mut D::AttrStore {
+        &mut self.driver_store
+    }
+
+    fn is_prop(&self) -> bool {
+        self.is_prop
+    }
+
+    fn listener(&self) -> Option<&Rc<dyn Fn(D::Event)>> {
+        self.listener.as_ref()
+    }
+
+    fn namespace(&self) -> Option<AttrNs> {
+        self.namespace
+    }
+
+    fn tokens(&self) -> Option<&[Cow<'static, str>]> {
+        self.tokens.as_deref()
+    }
+}
+
+/// The error for a node passed to `DynNode::new` that has a component at
+/// `path`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CompError {
+    pub path: Path,
+}
+
+struct ErasedBuilder<D>
+where
+    D: Driver,
+{
+    parent: Path,
+    nodes: Vec<ErasedNode<D>>,
+}
+
+impl<D> NodeVisitor<D> for ErasedBuilder<D>
+where
+    D: Driver,
+{
+    type Err = CompError;
+
+    fn on_tag<T>(&mut self, index: usize, tag: &mut T) -> Result<(), CompError>
+    where
+        T: Tag<D>,
+    {
+        let mut attrs = ErasedAttrBuilder { attrs: Vec::new() };
+        match tag.visit_attrs(&mut attrs) {
+            Ok(()) => {}
+            Err(never) => match never {},
+        }
+        let mut children = ErasedBuilder {
+            parent: self.parent.child(index),
+            nodes: Vec::new(),
+        };
+        tag.visit_children(&mut children)?;
+        self.nodes.push(ErasedNode::Tag(ErasedTag {
+            tag: tag.tag().to_string(),
+            is_tag_static: tag.is_tag_static(),
+            attrs: attrs.attrs,
+            children: children.nodes,
+            driver_store: D::new_tag_store(),
+        }));
+        Ok(())
+    }
+
+    fn on_text<T>(&mut self, _index: usize, text: &mut T) -> Result<(), CompError>
+    where
+        T: Text<D>,
+    {
+        let copy = text.get().to_string();
+        self.nodes.push(if text.is_comment() {
+            ErasedNode::Comment(Comment::new(copy))
+        } else {
+            ErasedNode::Text(TextDyn::new(copy))
+        });
+        Ok(())
+    }
+
+    fn on_comp<C>(&mut self, index: &mut usize, _comp: &mut CompNode<D, C>) -> Result<(), CompError>
+    where
+        C: Comp<D>,
+    {
+        Err(CompError {
+            path: self.parent.child(*index),
+        })
+    }
+}
+
+struct ErasedAttrBuilder<D>
+where
+    D: Driver,
+{
+    attrs: Vec<ErasedAttr<D>>,
+}
+
+impl<D> AttrVisitor<D> for ErasedAttrBuilder<D>
+where
+    D: Driver,
+{
+    type Err = Infallible;
+
+    fn on_attr<A>(&mut self, attr: &mut A) -> Result<(), Infallible>
+    where
+        A: Attr<D>,
+    {
+        let value = match attr.value() {
+            AttrRefValue::True => AttrValue::True,
+            AttrRefValue::Null => AttrValue::Null,
+            AttrRefValue::Str(s) => AttrValue::String(s.to_string()),
+        };
+        // `AttrList::push` would shadow `Vec::push` here.
+        Vec::push(
+            &mut self.attrs,
+            ErasedAttr {
+                name: attr.name().to_string(),
+                value,
+                is_prop: attr.is_prop(),
+                listener: attr.listener().cloned(),
+                namespace: attr.namespace(),
+                tokens: attr.tokens().map(<[_]>::to_vec),
+                driver_store: D::new_attr_store(),
+            },
+        );
+        Ok(())
+    }
+}
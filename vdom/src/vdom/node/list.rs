@@ -0,0 +1,377 @@
+use super::*;
+use crate::vdom::key::Key;
+use std::collections::HashMap;
+
+/// Interleaves a separator node between consecutive items, with none before
+/// the first or after the last, e.g. for breadcrumbs.
+pub struct Separated<N, S> {
+    entries: Vec<(Option<S>, N)>,
+}
+
+impl<N, S> Separated<N, S> {
+    pub fn new<I, F>(items: I, mut separator: F) -> Separated<N, S>
+    where
+        I: IntoIterator<Item = N>,
+        F: FnMut() -> S,
+    {
+        let entries = items
+            .into_iter()
+            .enumerate()
+            .map(|(i, item)| (if i > 0 { Some(separator()) } else { None }, item))
+            .collect();
+        Separated { entries }
+    }
+}
+
+impl<D, N, S> Node<D> for Separated<N, S>
+where
+    D: Driver,
+    N: Node<D>,
+    S: Node<D>,
+{
+    fn visit<NV>(&mut self, index: &mut usize, visitor: &mut NV) -> Result<(), NV::Err>
+    where
+        NV: NodeVisitor<D>,
+    {
+        self.entries.visit(index, visitor)
+    }
+
+    fn diff<ND>(
+        &mut self,
+        curr_index: &mut usize,
+        ancestor_index: &mut usize,
+        ancestor: &mut Self,
+        differ: &mut ND,
+    ) -> Result<(), ND::Err>
+    where
+        ND: NodeDiffer<D>,
+    {
+        // Every entry but the first carries its leading separator, so a
+        // positional diff adds and removes separators along with their items.
+        self.entries
+            .diff(curr_index, ancestor_index, &mut ancestor.entries, differ)
+    }
+}
+
+/// Renders only the items for which `predicate` returns true, like `filter`
+/// on an iterator. Filtered out items take up no index, so the paths of the
+/// rendered items are as if the others weren't there. Diffing pairs each item
+/// with the ancestor's item at the same position in the unfiltered list, so
+/// an item that's shown on one side only is added or removed, while the items
+/// shown on both are diffed against each other.
+pub struct FilterList<N, P> {
+    items: Vec<N>,
+    predicate: P,
+}
+
+impl<N, P> FilterList<N, P>
+where
+    P: Fn(&N) -> bool,
+{
+    pub fn new<I>(items: I, predicate: P) -> FilterList<N, P>
+    where
+        I: IntoIterator<Item = N>,
+    {
+        FilterList {
+            items: items.into_iter().collect(),
+            predicate,
+        }
+    }
+
+    fn rendered(&mut self) -> Vec<&mut N> {
+        let predicate = &self.predicate;
+        self.items
+            .iter_mut()
+            .filter(|item| predicate(item))
+            .collect()
+    }
+}
+
+impl<D, N, P> Node<D> for FilterList<N, P>
+where
+    D: Driver,
+    N: Node<D>,
+    P: Fn(&N) -> bool,
+{
+    fn visit<NV>(&mut self, index: &mut usize, visitor: &mut NV) -> Result<(), NV::Err>
+    where
+        NV: NodeVisitor<D>,
+    {
+        self.rendered().visit(index, visitor)
+    }
+
+    fn diff<ND>(
+        &mut self,
+        curr_index: &mut usize,
+        ancestor_index: &mut usize,
+        ancestor: &mut Self,
+        differ: &mut ND,
+    ) -> Result<(), ND::Err>
+    where
+        ND: NodeDiffer<D>,
+    {
+        let (predicate, ancestor_predicate) = (&self.predicate, &ancestor.predicate);
+        let len = self.items.len().max(ancestor.items.len());
+        for i in 0..len {
+            let mut curr = self.items.get_mut(i).filter(|item| predicate(item));
+            let mut ancestor = ancestor
+                .items
+                .get_mut(i)
+                .filter(|item| ancestor_predicate(item));
+            curr.diff(curr_index, ancestor_index, &mut ancestor, differ)?;
+        }
+        Ok(())
+    }
+}
+
+/// Items identified by a `Key`, so that diffing matches items by key rather
+/// than by position. Items that were reordered are moved with
+/// `NodeDiffer::on_node_moved` instead of being removed and added again,
+/// which keeps the state of their elements, e.g. focus. As few items as
+/// possible are moved. If keys repeat, later items with a key are treated as
+/// new.
+pub struct Keyed<N> {
+    items: Vec<(Key, N)>,
+}
+
+impl<N> Keyed<N> {
+    pub fn new<I, K>(items: I) -> Keyed<N>
+    where
+        I: IntoIterator<Item = (K, N)>,
+        K: Into<Key>,
+    {
+        Keyed {
+            items: items
+                .into_iter()
+                .map(|(key, item)| (key.into(), item))
+                .collect(),
+        }
+    }
+}
+
+impl<D, N> Node<D> for Keyed<N>
+where
+    D: Driver,
+    N: Node<D>,
+{
+    fn visit<NV>(&mut self, index: &mut usize, visitor: &mut NV) -> Result<(), NV::Err>
+    where
+        NV: NodeVisitor<D>,
+    {
+        for (_, item) in &mut self.items {
+            item.visit(index, visitor)?;
+        }
+        Ok(())
+    }
+
+    fn diff<ND>(
+        &mut self,
+        curr_index: &mut usize,
+        ancestor_index: &mut usize,
+        ancestor: &mut Self,
+        differ: &mut ND,
+    ) -> Result<(), ND::Err>
+    where
+        ND: NodeDiffer<D>,
+    {
+        let start = *curr_index;
+
+        // Where the nodes of every previous item start and how many there are.
+        let mut ancestor_starts: Vec<(usize, usize)> = Vec::with_capacity(ancestor.items.len());
+        for (_, item) in &mut ancestor.items {
+            let item_start = *ancestor_index;
+            match item.visit(ancestor_index, &mut NodeCounter) {
+                Ok(()) => {}
+                Err(never) => match never {},
+            }
+            ancestor_starts.push((item_start, *ancestor_index - item_start));
+        }
+
+        let mut by_key = HashMap::with_capacity(ancestor.items.len());
+        for (i, (key, _)) in ancestor.items.iter().enumerate() {
+            by_key.entry(key).or_insert(i);
+        }
+        let matches = self
+            .items
+            .iter()
+            .map(|(key, _)| by_key.remove(key))
+            .collect::<Vec<_>>();
+        let mut matched = vec![false; ancestor.items.len()];
+        for &i in matches.iter().flatten() {
+            matched[i] = true;
+        }
+
+        // The number of nodes of the previous items not diffed yet. Previous
+        // items that are gone are removed first.
+        let mut pending = Fenwick::new(ancestor.items.len());
+        let mut offset = 0;
+        for (i, (_, item)) in ancestor.items.iter_mut().enumerate() {
+            let (mut item_start, len) = ancestor_starts[i];
+            if matched[i] {
+                pending.add(i, len);
+                offset += len;
+            } else {
+                differ.on_node_removed(start + offset, &mut item_start, item)?;
+            }
+        }
+
+        // Items that keep their order relative to each other stay in place,
+        // and all others are moved next to the item before them.
+        let stays = longest_increasing(&matches.iter().flatten().cloned().collect::<Vec<_>>());
+        let mut stays = matches
+            .iter()
+            .flatten()
+            .zip(stays)
+            .filter(|(_, stays)| *stays)
+            .map(|(&i, _)| i)
+            .collect::<Vec<_>>();
+        stays.sort_unstable();
+
+        // The DOM is made of the items diffed so far and the previous items
+        // not diffed yet. Each item goes right after the one diffed before
+        // it, unless it stays, so the previous items after the last one that
+        // stayed come after all diffed items, while a previous item before it
+        // comes after the items diffed until the first item after it stayed.
+        // That way, the offset of each item is found without searching the
+        // DOM.
+        // The number of nodes of the items diffed before each one.
+        let mut diffed: Vec<usize> = Vec::with_capacity(self.items.len() + 1);
+        diffed.push(0);
+        // The step at which each item that stays was diffed.
+        let mut stayed = vec![None; stays.len()];
+        // The item that stayed last.
+        let mut last_stay = None;
+
+        for (step, ((key, item), matched)) in self.items.iter_mut().zip(matches).enumerate() {
+            let diffed_len = diffed[step];
+            // Where the item diffed last ends, i.e. this one goes.
+            let cursor = match last_stay {
+                Some(last_stay) => start + diffed_len + pending.sum(last_stay),
+                None => start + diffed_len,
+            };
+            let i = match matched {
+                Some(i) => i,
+                None => {
+                    let mut index = cursor;
+                    differ.on_node_added(&mut index, item)?;
+                    diffed.push(diffed_len + index - cursor);
+                    continue;
+                }
+            };
+            let len = ancestor_starts[i].1;
+            let mut index = match stays.binary_search(&i) {
+                Ok(k) => {
+                    let index = start + diffed_len + pending.sum(i);
+                    pending.remove(i, len);
+                    stayed[k] = Some(step);
+                    last_stay = Some(i);
+                    index
+                }
+                Err(k) => {
+                    let before = match stayed.get(k) {
+                        Some(&Some(step)) => diffed[step],
+                        _ => diffed_len,
+                    };
+                    let from = start + before + pending.sum(i);
+                    pending.remove(i, len);
+                    // Moving it forward leaves a gap where it was.
+                    let to = match last_stay {
+                        Some(last_stay) if last_stay > i => cursor - len,
+                        _ => cursor,
+                    };
+                    if from != to {
+                        for k in 0..len {
+                            if to < from {
+                                differ.on_node_moved(from + k, to + k, key)?;
+                            } else {
+                                differ.on_node_moved(from, to + len - 1, key)?;
+                            }
+                        }
+                    }
+                    to
+                }
+            };
+            let item_start = index;
+            let (mut ancestor_start, _) = ancestor_starts[i];
+            item.diff(
+                &mut index,
+                &mut ancestor_start,
+                &mut ancestor.items[i].1,
+                differ,
+            )?;
+            diffed.push(diffed_len + index - item_start);
+        }
+
+        *curr_index = start + diffed[self.items.len()];
+        Ok(())
+    }
+}
+
+/// A Fenwick tree of the number of nodes of each previous item not diffed
+/// yet, to sum up those before an item in logarithmic time.
+struct Fenwick {
+    tree: Vec<usize>,
+}
+
+impl Fenwick {
+    fn new(len: usize) -> Fenwick {
+        Fenwick {
+            tree: vec![0; len + 1],
+        }
+    }
+
+    fn add(&mut self, i: usize, len: usize) {
+        let mut i = i + 1;
+        while i < self.tree.len() {
+            self.tree[i] += len;
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    fn remove(&mut self, i: usize, len: usize) {
+        let mut i = i + 1;
+        while i < self.tree.len() {
+            self.tree[i] -= len;
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    /// The number of nodes of the items before `end`.
+    fn sum(&self, end: usize) -> usize {
+        let (mut i, mut sum) = (end, 0);
+        while i > 0 {
+            sum += self.tree[i];
+            i -= i & i.wrapping_neg();
+        }
+        sum
+    }
+}
+
+/// Which items of `seq` form a longest strictly increasing subsequence.
+fn longest_increasing(seq: &[usize]) -> Vec<bool> {
+    // `tails[k]` is the index of the smallest item ending an increasing
+    // subsequence of length `k + 1`, and `prev` links each item to the one
+    // before it in the subsequence it ends.
+    let mut tails: Vec<usize> = Vec::new();
+    let mut prev = vec![None; seq.len()];
+    for (i, &item) in seq.iter().enumerate() {
+        let k = tails
+            .binary_search_by(|&tail| seq[tail].cmp(&item))
+            .unwrap_or_else(|k| k);
+        if k > 0 {
+            prev[i] = Some(tails[k - 1]);
+        }
+        if k == tails.len() {
+            tails.push(i);
+        } else {
+            tails[k] = i;
+        }
+    }
+    let mut in_seq = vec![false; seq.len()];
+    let mut i = tails.last().cloned();
+    while let Some(j) = i {
+        in_seq[j] = true;
+        i = prev[j];
+    }
+    in_seq
+}
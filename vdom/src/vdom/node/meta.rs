@@ -0,0 +1,178 @@
+use super::*;
+use crate::vdom::path::Path;
+use std::{any::Any, cell::RefCell, rc::Rc};
+
+thread_local! {
+    /// The metadata of the `WithMeta` nodes currently being visited,
+    /// innermost last.
+    static META: RefCell<Vec<Rc<dyn Any>>> = RefCell::new(Vec::new());
+}
+
+struct MetaGuard;
+
+impl MetaGuard {
+    fn push(meta: Rc<dyn Any>) -> MetaGuard {
+        META.with(|stack| stack.borrow_mut().push(meta));
+        MetaGuard
+    }
+}
+
+impl Drop for MetaGuard {
+    fn drop(&mut self) {
+        META.with(|stack| stack.borrow_mut().pop());
+    }
+}
+
+/// Attaches `meta` to the nodes of `node`, e.g. for a runtime to associate
+/// its own state with them. The metadata isn't rendered and doesn't affect
+/// diffing, and can be looked up by path with `meta_at`.
+pub struct WithMeta<M, N> {
+    meta: Rc<M>,
+    node: N,
+}
+
+impl<M, N> WithMeta<M, N>
+where
+    M: 'static,
+{
+    pub fn new(meta: M, node: N) -> WithMeta<M, N> {
+        WithMeta {
+            meta: Rc::new(meta),
+            node,
+        }
+    }
+
+    pub fn meta(&self) -> &M {
+        &self.meta
+    }
+}
+
+impl<D, M, N> Node<D> for WithMeta<M, N>
+where
+    D: Driver,
+    M: 'static,
+    N: Node<D>,
+{
+    const IS_STATIC: bool = N::IS_STATIC;
+
+    fn visit<NV>(&mut self, index: &mut usize, visitor: &mut NV) -> Result<(), NV::Err>
+    where
+        NV: NodeVisitor<D>,
+    {
+        let _guard = MetaGuard::push(self.meta.clone());
+        self.node.visit(index, visitor)
+    }
+
+    fn diff<ND>(
+        &mut self,
+        curr_index: &mut usize,
+        ancestor_index: &mut usize,
+        ancestor: &mut Self,
+        differ: &mut ND,
+    ) -> Result<(), ND::Err>
+    where
+        ND: NodeDiffer<D>,
+    {
+        self.node
+            .diff(curr_index, ancestor_index, &mut ancestor.node, differ)
+    }
+}
+
+/// The metadata of type `M` attached to the node at `path` within `root` by
+/// the innermost `WithMeta` around it, if any. Metadata attached to an
+/// ancestor of the node doesn't count. Components have to be mounted, as
+/// they're rendered to be searched.
+pub fn meta_at<D, N, M>(root: &mut N, path: &Path) -> Result<Option<Rc<M>>, Unmounted>
+where
+    D: Driver,
+    N: Node<D> + ?Sized,
+    M: 'static,
+{
+    let mut finder = MetaFinder {
+        path: path.indices(),
+        base: 0,
+        meta: None,
+    };
+    if !path.is_root() {
+        root.visit(&mut 0, &mut finder)?;
+    }
+    Ok(finder.meta)
+}
+
+struct MetaFinder<'a, M> {
+    path: &'a [usize],
+    /// The length of `META` when the siblings of the nodes visited were
+    /// entered, so older entries belong to ancestors.
+    base: usize,
+    meta: Option<Rc<M>>,
+}
+
+impl<'a, M> MetaFinder<'a, M>
+where
+    M: 'static,
+{
+    fn innermost(&self) -> Option<Rc<M>> {
+        META.with(|stack| {
+            stack.borrow()[self.base..]
+                .iter()
+                .rev()
+                .find_map(|meta| meta.clone().downcast::<M>().ok())
+        })
+    }
+}
+
+impl<'a, D, M> NodeVisitor<D> for MetaFinder<'a, M>
+where
+    D: Driver,
+    M: 'static,
+{
+    type Err = Unmounted;
+
+    fn on_tag<T>(&mut self, index: usize, tag: &mut T) -> Result<(), Unmounted>
+    where
+        T: Tag<D>,
+    {
+        match self.path.split_first() {
+            Some((i, rest)) if *i == index => {
+                if rest.is_empty() {
+                    self.meta = self.innermost();
+                    return Ok(());
+                }
+                let mut finder = MetaFinder {
+                    path: rest,
+                    base: META.with(|stack| stack.borrow().len()),
+                    meta: None,
+                };
+                tag.visit_children(&mut finder)?;
+                self.meta = finder.meta;
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn on_text<T>(&mut self, index: usize, _text: &mut T) -> Result<(), Unmounted>
+    where
+        T: Text<D>,
+    {
+        if self.path == [index] {
+            self.meta = self.innermost();
+        }
+        Ok(())
+    }
+
+    fn on_comp<C>(&mut self, index: &mut usize, comp: &mut CompNode<D, C>) -> Result<(), Unmounted>
+    where
+        C: Comp<D>,
+    {
+        // Components after the node can't lead to it.
+        match self.path.first() {
+            Some(i) if *i >= *index => {}
+            _ => return Ok(()),
+        }
+        if comp.comp_ctx().is_none() {
+            return Err(Unmounted);
+        }
+        comp.visit_rendered(index, self)
+    }
+}
@@ -1,14 +1,35 @@
+mod boundary;
+mod choice;
 mod comp;
+mod context;
+mod erased;
+mod list;
+mod meta;
+mod scoped;
+mod shared;
+mod suspense;
 mod tag;
 mod text;
 
-use std::borrow::Cow;
+use std::{borrow::Cow, convert::Infallible, marker::PhantomData};
 
+pub use self::boundary::*;
+pub use self::choice::*;
 pub use self::comp::*;
+pub use self::context::*;
+pub use self::erased::*;
+pub use self::list::*;
+pub use self::meta::*;
+pub use self::scoped::*;
+pub use self::shared::*;
+pub use self::suspense::*;
 pub use self::tag::*;
 pub use self::text::*;
-use super::attr::{AttrDiffer, AttrList, AttrVisitor};
-use crate::driver::Driver;
+use super::{
+    attr::{AttrDiffer, AttrList, AttrVisitor},
+    key::Key,
+};
+use crate::driver::{Driver, DriverCtx};
 
 pub trait NodeVisitor<D>
 where
@@ -27,8 +48,22 @@ where
     fn on_comp<C>(&mut self, index: &mut usize, comp: &mut CompNode<D, C>) -> Result<(), Self::Err>
     where
         C: Comp<D>;
+
+    /// The context this visitor creates components with, if it does, so
+    /// nodes such as `ErrorBoundary` can render them ahead of the visit.
+    fn driver_ctx(&self) -> Option<DriverCtx<D>> {
+        None
+    }
 }
 
+/// Receives the differences between a current and an ancestor tree.
+///
+/// Callbacks are made in document order. A `curr_index` is the position of a
+/// node among its siblings in the current tree, which is also its position in
+/// a DOM that all earlier callbacks have already been applied to. Appliers
+/// that address nodes by index rather than by reference must therefore apply
+/// changes in callback order, and remove nodes at `curr_index`, not at their
+/// `ancestor_index` from before earlier siblings were added or removed.
 pub trait NodeDiffer<D>
 where
     D: Driver,
@@ -41,12 +76,43 @@ where
 
     fn on_node_removed<N>(
         &mut self,
+        curr_index: usize,
         ancestor_index: &mut usize,
         ancestor: &mut N,
     ) -> Result<(), Self::Err>
     where
         N: Node<D>;
 
+    /// Moves the node at the first index so that it ends up at the second,
+    /// as if it was removed and inserted again, e.g. for items of a `Keyed`
+    /// list that were reordered. The node keeps its state, and is diffed at
+    /// its new index afterwards. Differs that don't track positions can
+    /// ignore it.
+    fn on_node_moved(
+        &mut self,
+        _from_index: usize,
+        _to_index: usize,
+        _key: &Key,
+    ) -> Result<(), Self::Err> {
+        Ok(())
+    }
+
+    /// Called with the `strict` feature with the index of a node whose
+    /// static tag or text isn't the expected one of its ancestor, which its
+    /// type promises, and the one found, e.g. as a `TagStatic` was built with
+    /// another name. Returning an error aborts the diff. Otherwise, and without the
+    /// feature, the node is diffed as if it was of a dynamic type, so a tag
+    /// is replaced and a text is diffed with `on_text`, which is all that
+    /// happens by default.
+    fn on_invariant_violation(
+        &mut self,
+        _curr_index: usize,
+        _expected: &str,
+        _found: &str,
+    ) -> Result<(), Self::Err> {
+        Ok(())
+    }
+
     fn on_tag<T>(
         &mut self,
         curr_index: usize,
@@ -76,12 +142,63 @@ where
     ) -> Result<(), Self::Err>
     where
         C: Comp<D>;
+
+    /// The context this differ creates added components with, as for
+    /// `NodeVisitor::driver_ctx`.
+    fn driver_ctx(&self) -> Option<DriverCtx<D>> {
+        None
+    }
+}
+
+/// The error for walking a component that isn't mounted, which can't be
+/// rendered before it's created with the context of a driver.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Unmounted;
+
+/// Walks nodes without looking at them, to advance an index past them.
+pub(crate) struct NodeCounter;
+
+impl<D> NodeVisitor<D> for NodeCounter
+where
+    D: Driver,
+{
+    type Err = Infallible;
+
+    fn on_tag<T>(&mut self, _index: usize, _tag: &mut T) -> Result<(), Infallible>
+    where
+        T: Tag<D>,
+    {
+        Ok(())
+    }
+
+    fn on_text<T>(&mut self, _index: usize, _text: &mut T) -> Result<(), Infallible>
+    where
+        T: Text<D>,
+    {
+        Ok(())
+    }
+
+    fn on_comp<C>(&mut self, index: &mut usize, comp: &mut CompNode<D, C>) -> Result<(), Infallible>
+    where
+        C: Comp<D>,
+    {
+        comp.visit_rendered(index, self)
+    }
 }
 
 pub trait Node<D>
 where
     D: Driver,
 {
+    /// Whether everything in this list is known from its type alone, i.e. it
+    /// only consists of static tags with static attributes and static text.
+    /// False unless an implementation can prove otherwise, see
+    /// `assert_static`.
+    const IS_STATIC: bool = false;
+
+    /// Hands every node of this list to `visitor` along with its `index`
+    /// among the parent's children, advancing `index` past them. Nested lists
+    /// share the counter, so indices are flat, see `Path`.
     fn visit<NV>(&mut self, index: &mut usize, visitor: &mut NV) -> Result<(), NV::Err>
     where
         NV: NodeVisitor<D>;
@@ -95,6 +212,59 @@ where
     ) -> Result<(), ND::Err>
     where
         ND: NodeDiffer<D>;
+
+    /// Diffs this list against an empty ancestor, reporting all of it to
+    /// `differ` as added. This lets a backend mount the first render through
+    /// the same `NodeDiffer` it uses for updates.
+    fn diff_new<ND>(&mut self, curr_index: &mut usize, differ: &mut ND) -> Result<(), ND::Err>
+    where
+        Self: Sized,
+        ND: NodeDiffer<D>,
+    {
+        differ.on_node_added(curr_index, self)
+    }
+
+    /// The number of nodes in this list, counted the way `visit` indexes
+    /// them. Components have to be mounted, as they're rendered to be
+    /// counted.
+    fn node_count(&mut self) -> Result<usize, Unmounted> {
+        let mut counter = ChildCounter { len: 0 };
+        self.visit(&mut 0, &mut counter)?;
+        Ok(counter.len)
+    }
+}
+
+/// Fails to compile, when used as a constant, unless `N` is static, e.g.
+/// `const _: () = assert_static::<HtmlDriver, Header>();` for a template
+/// that is expected to never change. See `Node::IS_STATIC`.
+pub const fn assert_static<D, N>()
+where
+    D: Driver,
+    N: Node<D> + ?Sized,
+{
+    assert!(N::IS_STATIC, "node is not static");
+}
+
+/// Returns `node`, failing to compile unless it is static. Unlike
+/// `assert_static`, this works for templates whose type can't be named, such
+/// as the output of `html!`.
+pub fn static_node<D, N>(node: N) -> N
+where
+    D: Driver,
+    N: Node<D>,
+{
+    let () = StaticCheck::<D, N>::OK;
+    node
+}
+
+struct StaticCheck<D, N>(PhantomData<(D, N)>);
+
+impl<D, N> StaticCheck<D, N>
+where
+    D: Driver,
+    N: Node<D>,
+{
+    const OK: () = assert_static::<D, N>();
 }
 
 impl<D, L1, L2> Node<D> for (L1, L2)
@@ -103,6 +273,8 @@ where
     L1: Node<D>,
     L2: Node<D>,
 {
+    const IS_STATIC: bool = L1::IS_STATIC && L2::IS_STATIC;
+
     fn visit<NV>(&mut self, index: &mut usize, visitor: &mut NV) -> Result<(), NV::Err>
     where
         NV: NodeVisitor<D>,
@@ -132,6 +304,8 @@ impl<D> Node<D> for ()
 where
     D: Driver,
 {
+    const IS_STATIC: bool = true;
+
     fn visit<NV>(&mut self, _index: &mut usize, _visitor: &mut NV) -> Result<(), NV::Err>
     where
         NV: NodeVisitor<D>,
@@ -182,12 +356,169 @@ where
         match (self, ancestor) {
             (Some(curr), Some(ancestor)) => curr.diff(curr_index, ancestor_index, ancestor, differ),
             (Some(curr), None) => differ.on_node_added(curr_index, curr),
-            (None, Some(ancestor)) => differ.on_node_removed(ancestor_index, ancestor),
+            (None, Some(ancestor)) => differ.on_node_removed(*curr_index, ancestor_index, ancestor),
             (None, None) => Ok(()),
         }
     }
 }
 
+impl<D, N> Node<D> for [N]
+where
+    D: Driver,
+    N: Node<D>,
+{
+    fn visit<NV>(&mut self, index: &mut usize, visitor: &mut NV) -> Result<(), NV::Err>
+    where
+        NV: NodeVisitor<D>,
+    {
+        for node in self {
+            node.visit(index, visitor)?;
+        }
+        Ok(())
+    }
+
+    fn diff<ND>(
+        &mut self,
+        curr_index: &mut usize,
+        ancestor_index: &mut usize,
+        ancestor: &mut Self,
+        differ: &mut ND,
+    ) -> Result<(), ND::Err>
+    where
+        ND: NodeDiffer<D>,
+    {
+        let mut curr = self.iter_mut();
+        let mut ancestor = ancestor.iter_mut();
+        loop {
+            match (curr.next(), ancestor.next()) {
+                (Some(curr), Some(ancestor)) => {
+                    curr.diff(curr_index, ancestor_index, ancestor, differ)?
+                }
+                (Some(curr), None) => differ.on_node_added(curr_index, curr)?,
+                (None, Some(ancestor)) => {
+                    differ.on_node_removed(*curr_index, ancestor_index, ancestor)?
+                }
+                (None, None) => return Ok(()),
+            }
+        }
+    }
+}
+
+impl<D, N> Node<D> for Vec<N>
+where
+    D: Driver,
+    N: Node<D>,
+{
+    fn visit<NV>(&mut self, index: &mut usize, visitor: &mut NV) -> Result<(), NV::Err>
+    where
+        NV: NodeVisitor<D>,
+    {
+        self.as_mut_slice().visit(index, visitor)
+    }
+
+    fn diff<ND>(
+        &mut self,
+        curr_index: &mut usize,
+        ancestor_index: &mut usize,
+        ancestor: &mut Self,
+        differ: &mut ND,
+    ) -> Result<(), ND::Err>
+    where
+        ND: NodeDiffer<D>,
+    {
+        self.as_mut_slice()
+            .diff(curr_index, ancestor_index, ancestor.as_mut_slice(), differ)
+    }
+}
+
+/// Inline storage for short dynamic child lists, with the same diff
+/// semantics as `Vec`, also once a list spilled to the heap. `html!` has no
+/// loop syntax, so such lists are built by collecting into a `SmallVec`.
+#[cfg(feature = "smallvec")]
+impl<D, A> Node<D> for smallvec::SmallVec<A>
+where
+    D: Driver,
+    A: smallvec::Array,
+    A::Item: Node<D>,
+{
+    fn visit<NV>(&mut self, index: &mut usize, visitor: &mut NV) -> Result<(), NV::Err>
+    where
+        NV: NodeVisitor<D>,
+    {
+        self.as_mut_slice().visit(index, visitor)
+    }
+
+    fn diff<ND>(
+        &mut self,
+        curr_index: &mut usize,
+        ancestor_index: &mut usize,
+        ancestor: &mut Self,
+        differ: &mut ND,
+    ) -> Result<(), ND::Err>
+    where
+        ND: NodeDiffer<D>,
+    {
+        self.as_mut_slice()
+            .diff(curr_index, ancestor_index, ancestor.as_mut_slice(), differ)
+    }
+}
+
+impl<D, N> Node<D> for Box<N>
+where
+    D: Driver,
+    N: Node<D> + ?Sized,
+{
+    const IS_STATIC: bool = N::IS_STATIC;
+
+    fn visit<NV>(&mut self, index: &mut usize, visitor: &mut NV) -> Result<(), NV::Err>
+    where
+        NV: NodeVisitor<D>,
+    {
+        (**self).visit(index, visitor)
+    }
+
+    fn diff<ND>(
+        &mut self,
+        curr_index: &mut usize,
+        ancestor_index: &mut usize,
+        ancestor: &mut Self,
+        differ: &mut ND,
+    ) -> Result<(), ND::Err>
+    where
+        ND: NodeDiffer<D>,
+    {
+        (**self).diff(curr_index, ancestor_index, &mut **ancestor, differ)
+    }
+}
+
+impl<'a, D, N> Node<D> for &'a mut N
+where
+    D: Driver,
+    N: Node<D> + ?Sized,
+{
+    const IS_STATIC: bool = N::IS_STATIC;
+
+    fn visit<NV>(&mut self, index: &mut usize, visitor: &mut NV) -> Result<(), NV::Err>
+    where
+        NV: NodeVisitor<D>,
+    {
+        (**self).visit(index, visitor)
+    }
+
+    fn diff<ND>(
+        &mut self,
+        curr_index: &mut usize,
+        ancestor_index: &mut usize,
+        ancestor: &mut Self,
+        differ: &mut ND,
+    ) -> Result<(), ND::Err>
+    where
+        ND: NodeDiffer<D>,
+    {
+        (**self).diff(curr_index, ancestor_index, &mut **ancestor, differ)
+    }
+}
+
 pub trait IntoNode<D>
 where
     D: Driver,
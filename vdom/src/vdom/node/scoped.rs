@@ -0,0 +1,318 @@
+use super::*;
+use crate::vdom::{
+    attr::{Attr, AttrDyn, AttrRefValue},
+    style::{scope_class, StyleRegistry},
+};
+
+/// An element styled by a CSS snippet that is scoped to it through a class
+/// derived from the snippet, so identical snippets share one class. The class
+/// is added to those of the element.
+///
+/// Whenever the element is visited or diffed, the snippet is registered with
+/// the `StyleRegistry` provided by a `ContextProvider`, if any. A snippet is
+/// either declarations for the element, e.g. `color: red;`, or rules if it
+/// contains a `{`, in which `&` stands for the element, e.g.
+/// `& a:hover { color: red; }`.
+pub struct ScopedStyle<D, T>
+where
+    D: Driver,
+{
+    css: Cow<'static, str>,
+    class: String,
+    tag: T,
+    /// The class attribute for elements without one of their own.
+    class_attr: AttrDyn<D>,
+}
+
+impl<D, T> ScopedStyle<D, T>
+where
+    D: Driver,
+    T: Tag<D>,
+{
+    pub fn new<C>(css: C, tag: T) -> ScopedStyle<D, T>
+    where
+        C: Into<Cow<'static, str>>,
+    {
+        let css = css.into();
+        let class = scope_class(&css);
+        ScopedStyle {
+            class_attr: AttrDyn::new("class", class.clone()),
+            css,
+            class,
+            tag,
+        }
+    }
+
+    pub fn class(&self) -> &str {
+        &self.class
+    }
+
+    fn register(&self) {
+        if let Some(styles) = use_context::<StyleRegistry>() {
+            styles.register(&self.css);
+        }
+    }
+
+    fn scoped_tag(&mut self) -> ScopedTag<'_, D, T> {
+        ScopedTag {
+            tag: &mut self.tag,
+            class: &self.class,
+            class_attr: &mut self.class_attr,
+        }
+    }
+}
+
+impl<D, T> Node<D> for ScopedStyle<D, T>
+where
+    D: Driver,
+    T: Tag<D>,
+{
+    fn visit<NV>(&mut self, index: &mut usize, visitor: &mut NV) -> Result<(), NV::Err>
+    where
+        NV: NodeVisitor<D>,
+    {
+        self.register();
+        visitor.on_tag(*index, &mut self.scoped_tag())?;
+        *index += 1;
+        Ok(())
+    }
+
+    fn diff<ND>(
+        &mut self,
+        curr_index: &mut usize,
+        ancestor_index: &mut usize,
+        ancestor: &mut Self,
+        differ: &mut ND,
+    ) -> Result<(), ND::Err>
+    where
+        ND: NodeDiffer<D>,
+    {
+        self.register();
+        differ.on_tag(
+            *curr_index,
+            *ancestor_index,
+            &mut self.scoped_tag(),
+            &mut ancestor.scoped_tag(),
+        )?;
+        *curr_index += 1;
+        *ancestor_index += 1;
+        Ok(())
+    }
+}
+
+/// The element of a `ScopedStyle` as visitors see it, with the scope class
+/// merged into its `class` attribute.
+struct ScopedTag<'a, D, T>
+where
+    D: Driver,
+{
+    tag: &'a mut T,
+    class: &'a str,
+    class_attr: &'a mut AttrDyn<D>,
+}
+
+impl<'a, D, T> Tag<D> for ScopedTag<'a, D, T>
+where
+    D: Driver,
+    T: Tag<D>,
+{
+    fn is_tag_static(&self) -> bool {
+        self.tag.is_tag_static()
+    }
+
+    fn tag(&self) -> &str {
+        self.tag.tag()
+    }
+
+    fn visit_children<NV>(&mut self, visitor: &mut NV) -> Result<(), NV::Err>
+    where
+        NV: NodeVisitor<D>,
+    {
+        self.tag.visit_children(visitor)
+    }
+
+    fn diff_children<ND>(&mut self, ancestor: &mut Self, differ: &mut ND) -> Result<(), ND::Err>
+    where
+        ND: NodeDiffer<D>,
+    {
+        self.tag.diff_children(ancestor.tag, differ)
+    }
+
+    fn visit_attrs<AV>(&mut self, visitor: &mut AV) -> Result<(), AV::Err>
+    where
+        AV: AttrVisitor<D>,
+    {
+        let mut merger = ClassMerger {
+            visitor: &mut *visitor,
+            class: self.class,
+            merged: false,
+        };
+        self.tag.visit_attrs(&mut merger)?;
+        if !merger.merged {
+            visitor.on_attr(self.class_attr)?;
+        }
+        Ok(())
+    }
+
+    fn diff_attrs<AD>(&mut self, ancestor: &mut Self, differ: &mut AD) -> Result<(), AD::Err>
+    where
+        AD: AttrDiffer<D>,
+    {
+        let mut merger = ClassDiffMerger {
+            differ: &mut *differ,
+            curr_class: self.class,
+            ancestor_class: ancestor.class,
+            curr_merged: false,
+            ancestor_merged: false,
+        };
+        self.tag.diff_attrs(ancestor.tag, &mut merger)?;
+        // Lists whose attributes are only known at runtime may gain or lose
+        // their `class` between renders, in which case the merged attribute
+        // and `class_attr` take each other's place.
+        match (merger.curr_merged, merger.ancestor_merged) {
+            (false, false) => differ.on_diff(self.class_attr, ancestor.class_attr),
+            (false, true) => differ.on_added(self.class_attr),
+            (true, _) => Ok(()),
+        }
+    }
+
+    fn driver_store(&mut self) -> &mut D::TagStore {
+        self.tag.driver_store()
+    }
+}
+
+fn is_class<D, A>(attr: &A) -> bool
+where
+    D: Driver,
+    A: Attr<D>,
+{
+    attr.name() == "class"
+        && attr.namespace().is_none()
+        && attr.listener().is_none()
+        && !attr.is_prop()
+}
+
+struct ClassMerger<'a, AV> {
+    visitor: &'a mut AV,
+    class: &'a str,
+    merged: bool,
+}
+
+impl<'a, D, AV> AttrVisitor<D> for ClassMerger<'a, AV>
+where
+    D: Driver,
+    AV: AttrVisitor<D>,
+{
+    type Err = AV::Err;
+
+    fn on_attr<A>(&mut self, attr: &mut A) -> Result<(), AV::Err>
+    where
+        A: Attr<D>,
+    {
+        if !is_class(attr) {
+            return self.visitor.on_attr(attr);
+        }
+        self.merged = true;
+        self.visitor
+            .on_attr(&mut MergedClass::new(attr, self.class))
+    }
+}
+
+struct ClassDiffMerger<'a, AD> {
+    differ: &'a mut AD,
+    curr_class: &'a str,
+    ancestor_class: &'a str,
+    curr_merged: bool,
+    ancestor_merged: bool,
+}
+
+impl<'a, D, AD> AttrDiffer<D> for ClassDiffMerger<'a, AD>
+where
+    D: Driver,
+    AD: AttrDiffer<D>,
+{
+    type Err = AD::Err;
+
+    fn on_diff<A>(&mut self, curr: &mut A, ancestor: &mut A) -> Result<(), AD::Err>
+    where
+        A: Attr<D>,
+    {
+        if !is_class(curr) {
+            return self.differ.on_diff(curr, ancestor);
+        }
+        self.curr_merged = true;
+        self.ancestor_merged = true;
+        self.differ.on_diff(
+            &mut MergedClass::new(curr, self.curr_class),
+            &mut MergedClass::new(ancestor, self.ancestor_class),
+        )
+    }
+
+    fn on_added<A>(&mut self, curr: &mut A) -> Result<(), AD::Err>
+    where
+        A: Attr<D>,
+    {
+        if !is_class(curr) {
+            return self.differ.on_added(curr);
+        }
+        self.curr_merged = true;
+        self.differ
+            .on_added(&mut MergedClass::new(curr, self.curr_class))
+    }
+
+    fn on_removed<A>(&mut self, ancestor: &mut A) -> Result<(), AD::Err>
+    where
+        A: Attr<D>,
+    {
+        if !is_class(ancestor) {
+            return self.differ.on_removed(ancestor);
+        }
+        self.ancestor_merged = true;
+        self.differ
+            .on_removed(&mut MergedClass::new(ancestor, self.ancestor_class))
+    }
+}
+
+/// A `class` attribute with the scope class appended to its value.
+struct MergedClass<'a, A> {
+    attr: &'a mut A,
+    value: String,
+}
+
+impl<'a, A> MergedClass<'a, A> {
+    fn new<D>(attr: &'a mut A, class: &str) -> MergedClass<'a, A>
+    where
+        D: Driver,
+        A: Attr<D>,
+    {
+        let value = match attr.value() {
+            AttrRefValue::Str(classes) if !classes.trim().is_empty() => {
+                format!("{} {}", classes, class)
+            }
+            _ => class.to_string(),
+        };
+        MergedClass { attr, value }
+    }
+}
+
+impl<'a, D, A> Attr<D> for MergedClass<'a, A>
+where
+    D: Driver,
+    A: Attr<D>,
+{
+    fn is_value_static(&self) -> bool {
+        false
+    }
+
+    fn name(&self) -> &str {
+        self.attr.name()
+    }
+
+    fn value(&self) -> AttrRefValue<'_> {
+        AttrRefValue::Str(&self.value)
+    }
+
+    fn driver_store(&mut self) -> &mut D::AttrStore {
+        self.attr.driver_store()
+    }
+}
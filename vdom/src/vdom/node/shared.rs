@@ -0,0 +1,64 @@
+use super::*;
+use std::{cell::RefCell, rc::Rc};
+
+/// A subtree that is built once and referenced from several places. Nodes
+/// keep driver state, so the subtree is kept in a `RefCell` and borrowed
+/// while it is visited or diffed.
+///
+/// Diffing against an ancestor that shares the same subtree does nothing, as
+/// it can't have changed: the subtree is only reachable through `Shared`,
+/// which doesn't hand it out. Drivers that keep a live DOM node per node, such as
+/// the web driver, can only mount each shared subtree once at a time.
+pub struct Shared<N>(Rc<RefCell<N>>);
+
+impl<N> Shared<N> {
+    pub fn new(node: N) -> Shared<N> {
+        Shared(Rc::new(RefCell::new(node)))
+    }
+}
+
+impl<N> Clone for Shared<N> {
+    fn clone(&self) -> Shared<N> {
+        Shared(self.0.clone())
+    }
+}
+
+impl<D, N> Node<D> for Shared<N>
+where
+    D: Driver,
+    N: Node<D>,
+{
+    fn visit<NV>(&mut self, index: &mut usize, visitor: &mut NV) -> Result<(), NV::Err>
+    where
+        NV: NodeVisitor<D>,
+    {
+        self.0.borrow_mut().visit(index, visitor)
+    }
+
+    fn diff<ND>(
+        &mut self,
+        curr_index: &mut usize,
+        ancestor_index: &mut usize,
+        ancestor: &mut Self,
+        differ: &mut ND,
+    ) -> Result<(), ND::Err>
+    where
+        ND: NodeDiffer<D>,
+    {
+        if Rc::ptr_eq(&self.0, &ancestor.0) {
+            let start = *curr_index;
+            match self.0.borrow_mut().visit(curr_index, &mut NodeCounter) {
+                Ok(()) => {}
+                Err(never) => match never {},
+            }
+            *ancestor_index += *curr_index - start;
+            return Ok(());
+        }
+        self.0.borrow_mut().diff(
+            curr_index,
+            ancestor_index,
+            &mut ancestor.0.borrow_mut(),
+            differ,
+        )
+    }
+}
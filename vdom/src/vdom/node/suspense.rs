@@ -0,0 +1,370 @@
+use super::*;
+use crate::{
+    driver::DriverCtx,
+    vdom::attr::{AttrDyn, AttrListEntry},
+};
+use std::{
+    cell::RefCell,
+    collections::BTreeSet,
+    future::Future,
+    pin::Pin,
+    rc::{Rc, Weak},
+    task::{Context, Poll},
+};
+
+pub enum ResourceState<T, E> {
+    Pending,
+    Ready(T),
+    Failed(E),
+}
+
+/// Async data a `Suspense` node renders from. Keep it alive across renders
+/// for as long as its region is mounted; dropping it cancels the future.
+pub struct Resource<T, E> {
+    state: Rc<RefCell<ResourceState<T, E>>>,
+}
+
+impl<T, E> Resource<T, E>
+where
+    T: 'static,
+    E: 'static,
+{
+    /// Spawns `fut` on the driver and calls `on_settled` once it resolved.
+    /// To update only the boundary rather than render the owner again, render
+    /// the `Suspense` in a component and have `on_settled` invalidate it with
+    /// `CompCtx::invalidate`, for the driver to render it with
+    /// `CompNode::update_rendered`.
+    pub fn spawn<D, F, S>(driver_ctx: &DriverCtx<D>, fut: F, on_settled: S) -> Resource<T, E>
+    where
+        D: Driver,
+        F: Future<Output = Result<T, E>> + 'static,
+        S: FnOnce() + 'static,
+    {
+        let resource = Resource {
+            state: Rc::new(RefCell::new(ResourceState::Pending)),
+        };
+        driver_ctx.with_mut(|drv| {
+            drv.spawn(Settle {
+                fut: Box::pin(fut),
+                state: Rc::downgrade(&resource.state),
+                on_settled: Some(Box::new(on_settled)),
+            })
+        });
+        resource
+    }
+
+    /// A resource settled by the returned future rather than by a driver,
+    /// for the caller to run, e.g. with `StreamingRenderer::render_stream`
+    /// on a server, whose driver doesn't run futures.
+    pub fn from_future<F>(fut: F) -> (Resource<T, E>, impl Future<Output = ()>)
+    where
+        F: Future<Output = Result<T, E>> + 'static,
+    {
+        let resource = Resource {
+            state: Rc::new(RefCell::new(ResourceState::Pending)),
+        };
+        let settle = Settle {
+            fut: Box::pin(fut),
+            state: Rc::downgrade(&resource.state),
+            on_settled: None,
+        };
+        (resource, settle)
+    }
+
+    /// A resource whose data is already available, e.g. when rendering on a
+    /// server, so `Suspense` renders it right away.
+    pub fn ready(value: T) -> Resource<T, E> {
+        Resource {
+            state: Rc::new(RefCell::new(ResourceState::Ready(value))),
+        }
+    }
+
+    pub fn failed(err: E) -> Resource<T, E> {
+        Resource {
+            state: Rc::new(RefCell::new(ResourceState::Failed(err))),
+        }
+    }
+
+    /// Whether the future hasn't settled yet. A resource only ever moves from
+    /// pending to ready or failed, once.
+    pub fn is_pending(&self) -> bool {
+        self.with_state(|state| {
+            match state {
+                ResourceState::Pending => true,
+                ResourceState::Ready(_) | ResourceState::Failed(_) => false,
+            }
+        })
+    }
+
+    pub fn with_state<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&ResourceState<T, E>) -> R,
+    {
+        f(&self.state.borrow())
+    }
+}
+
+struct Settle<T, E> {
+    fut: Pin<Box<dyn Future<Output = Result<T, E>>>>,
+    state: Weak<RefCell<ResourceState<T, E>>>,
+    on_settled: Option<Box<dyn FnOnce()>>,
+}
+
+impl<T, E> Future for Settle<T, E> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.state.upgrade().is_none() {
+            return Poll::Ready(());
+        }
+        let res = match self.fut.as_mut().poll(cx) {
+            Poll::Ready(res) => res,
+            Poll::Pending => return Poll::Pending,
+        };
+        if let Some(state) = self.state.upgrade() {
+            *state.borrow_mut() = match res {
+                Ok(value) => ResourceState::Ready(value),
+                Err(err) => ResourceState::Failed(err),
+            };
+            if let Some(on_settled) = self.on_settled.take() {
+                on_settled();
+            }
+        }
+        Poll::Ready(())
+    }
+}
+
+/// Renders a placeholder while a `Resource` is pending and the rendered data
+/// or error once it settled. A change of state replaces the whole subtree,
+/// which is all that's rendered again if the `Suspense` is in a component
+/// invalidated when the resource settled, see `Resource::spawn`.
+pub enum Suspense<P, N, X> {
+    Pending(P),
+    Ready(N),
+    Failed(X),
+}
+
+impl<P, N, X> Suspense<P, N, X> {
+    pub fn new<T, E, FP, FN, FX>(
+        resource: &Resource<T, E>,
+        placeholder: FP,
+        render: FN,
+        render_err: FX,
+    ) -> Suspense<P, N, X>
+    where
+        T: 'static,
+        E: 'static,
+        FP: FnOnce() -> P,
+        FN: FnOnce(&T) -> N,
+        FX: FnOnce(&E) -> X,
+    {
+        resource.with_state(|state| {
+            match state {
+                ResourceState::Pending => Suspense::Pending(placeholder()),
+                ResourceState::Ready(value) => Suspense::Ready(render(value)),
+                ResourceState::Failed(err) => Suspense::Failed(render_err(err)),
+            }
+        })
+    }
+}
+
+impl<D, P, N, X> Node<D> for Suspense<P, N, X>
+where
+    D: Driver,
+    P: Node<D>,
+    N: Node<D>,
+    X: Node<D>,
+{
+    fn visit<NV>(&mut self, index: &mut usize, visitor: &mut NV) -> Result<(), NV::Err>
+    where
+        NV: NodeVisitor<D>,
+    {
+        let boundary = match StreamBoundary::enter() {
+            Some(boundary) => boundary,
+            None => {
+                return match self {
+                    Suspense::Pending(node) => node.visit(index, visitor),
+                    Suspense::Ready(node) => node.visit(index, visitor),
+                    Suspense::Failed(node) => node.visit(index, visitor),
+                };
+            }
+        };
+        match self {
+            Suspense::Pending(node) => {
+                if !boundary.start_pending() {
+                    return Ok(());
+                }
+                let id = AttrDyn::new("id", format!("vs-b{}", boundary.id));
+                TagStatic::new(BOUNDARY_TAG, AttrListEntry(id), node).visit(index, visitor)
+            }
+            Suspense::Ready(node) => {
+                let _capture = boundary.settle();
+                node.visit(index, visitor)
+            }
+            Suspense::Failed(node) => {
+                let _capture = boundary.settle();
+                node.visit(index, visitor)
+            }
+        }
+    }
+
+    fn diff<ND>(
+        &mut self,
+        curr_index: &mut usize,
+        ancestor_index: &mut usize,
+        ancestor: &mut Self,
+        differ: &mut ND,
+    ) -> Result<(), ND::Err>
+    where
+        ND: NodeDiffer<D>,
+    {
+        use self::Suspense::*;
+
+        match (self, ancestor) {
+            (Pending(curr), Pending(ancestor)) => {
+                curr.diff(curr_index, ancestor_index, ancestor, differ)
+            }
+            (Ready(curr), Ready(ancestor)) => {
+                curr.diff(curr_index, ancestor_index, ancestor, differ)
+            }
+            (Failed(curr), Failed(ancestor)) => {
+                curr.diff(curr_index, ancestor_index, ancestor, differ)
+            }
+            (curr, ancestor) => {
+                differ.on_node_removed(*curr_index, ancestor_index, ancestor)?;
+                differ.on_node_added(curr_index, curr)
+            }
+        }
+    }
+}
+
+/// The element the placeholder of a pending boundary is wrapped in when
+/// streaming, which the streamed content replaces.
+pub(crate) const BOUNDARY_TAG: &str = "vdom-boundary";
+
+thread_local! {
+    static STREAM: RefCell<Option<Stream>> = RefCell::new(None);
+}
+
+/// The state of a streamed render, see `html::StreamingRenderer`.
+///
+/// Boundaries are numbered by their position among the `Suspense` nodes
+/// within the same boundary, e.g. `1-0` for the first one in the second
+/// top-level one, so they keep their id when the tree is built again.
+#[derive(Default)]
+pub(crate) struct Stream {
+    /// The boundaries whose placeholder was sent, but not their content.
+    pub(crate) pending: BTreeSet<String>,
+    /// Whether output is written outside of captures, i.e. the shell is
+    /// being rendered.
+    shell: bool,
+    /// The id and the number of boundaries seen so far within each
+    /// boundary being visited, outermost first.
+    scopes: Vec<(String, usize)>,
+    /// The boundary whose content is being rendered to be streamed.
+    capture: Option<String>,
+}
+
+impl Stream {
+    /// Runs `f` with `self` as the state of `Suspense` nodes visited, for the
+    /// shell if `shell` is set, and returns the state afterwards.
+    pub(crate) fn run<F, R>(mut self, shell: bool, f: F) -> (Stream, R)
+    where
+        F: FnOnce() -> R,
+    {
+        self.shell = shell;
+        self.scopes = vec![(String::new(), 0)];
+        self.capture = None;
+        STREAM.with(|stream| *stream.borrow_mut() = Some(self));
+        let guard = StreamGuard;
+        let res = f();
+        let stream = STREAM.with(|stream| stream.borrow_mut().take());
+        drop(guard);
+        (stream.unwrap_or_default(), res)
+    }
+
+    /// Whether a streamed render is running on this thread.
+    pub(crate) fn is_active() -> bool {
+        STREAM.with(|stream| stream.borrow().is_some())
+    }
+
+    /// The boundary whose content is currently rendered to be streamed.
+    pub(crate) fn capture() -> Option<String> {
+        STREAM.with(|stream| stream.borrow().as_ref()?.capture.clone())
+    }
+
+    fn with<F, R>(f: F) -> Option<R>
+    where
+        F: FnOnce(&mut Stream) -> R,
+    {
+        STREAM.with(|stream| stream.borrow_mut().as_mut().map(f))
+    }
+}
+
+/// Clears the state of `Stream::run` when dropped, so a panic while
+/// rendering doesn't leave it to the next render on the thread.
+struct StreamGuard;
+
+impl Drop for StreamGuard {
+    fn drop(&mut self) {
+        STREAM.with(|stream| stream.borrow_mut().take());
+    }
+}
+
+/// A `Suspense` node being visited while streaming. Leaves its scope when
+/// dropped.
+struct StreamBoundary {
+    id: String,
+}
+
+impl StreamBoundary {
+    fn enter() -> Option<StreamBoundary> {
+        Stream::with(|stream| {
+            let (parent, seen) = stream.scopes.last_mut().expect("stream has no scope");
+            let id = if parent.is_empty() {
+                seen.to_string()
+            } else {
+                format!("{}-{}", parent, seen)
+            };
+            *seen += 1;
+            stream.scopes.push((id.clone(), 0));
+            StreamBoundary { id }
+        })
+    }
+
+    /// Registers the boundary as pending, returning whether its placeholder
+    /// is to be written, which is once, and only where output is written.
+    fn start_pending(&self) -> bool {
+        Stream::with(|stream| {
+            (stream.shell || stream.capture.is_some()) && stream.pending.insert(self.id.clone())
+        })
+        .unwrap_or(false)
+    }
+
+    /// Captures the content of the boundary until the returned guard is
+    /// dropped, if its placeholder was sent.
+    fn settle(&self) -> Option<StreamCapture> {
+        Stream::with(|stream| {
+            if stream.shell || stream.capture.is_some() || !stream.pending.remove(&self.id) {
+                return None;
+            }
+            stream.capture = Some(self.id.clone());
+            Some(StreamCapture)
+        })
+        .flatten()
+    }
+}
+
+impl Drop for StreamBoundary {
+    fn drop(&mut self) {
+        Stream::with(|stream| stream.scopes.pop());
+    }
+}
+
+struct StreamCapture;
+
+impl Drop for StreamCapture {
+    fn drop(&mut self) {
+        Stream::with(|stream| stream.capture = None);
+    }
+}
@@ -1,4 +1,10 @@
 use super::*;
+use crate::vdom::{
+    attr::{Attr, AttrRefValue, AttrValue},
+    element_info,
+    intern::{Interner, Name},
+};
+use std::fmt;
 
 pub trait Tag<D>
 where
@@ -25,6 +31,106 @@ where
         AD: AttrDiffer<D>;
 
     fn driver_store(&mut self) -> &mut D::TagStore;
+
+    /// The number of children, which are indexed from 0 to this, see
+    /// `Node::node_count`.
+    fn children_len(&mut self) -> Result<usize, Unmounted> {
+        let mut counter = ChildCounter { len: 0 };
+        self.visit_children(&mut counter)?;
+        Ok(counter.len)
+    }
+
+    /// Whether the children of the element are left to code outside of
+    /// vdom, e.g. a map widget or an editor, as marked by a non-null
+    /// `SKIP_DIFF_ATTR` attribute, or to the browser, as for `<noscript>`,
+    /// see `element_info::is_noscript_content`. Drivers still mount the
+    /// element and keep its attributes up to date, but neither add, diff nor
+    /// remove its children, whatever the tag's children are.
+    fn skips_diff(&mut self) -> bool {
+        if element_info::is_noscript_content(self.tag()) {
+            return true;
+        }
+        self.attr_value(SKIP_DIFF_ATTR)
+            .map_or(false, |value| value != AttrValue::Null)
+    }
+
+    /// The value of the last attribute named `name`, or `None` if there's
+    /// none.
+    fn attr_value(&mut self, name: &str) -> Option<AttrValue> {
+        let mut finder = AttrFinder { name, value: None };
+        match self.visit_attrs(&mut finder) {
+            Ok(()) => finder.value,
+            Err(never) => match never {},
+        }
+    }
+}
+
+/// The attribute marking an element whose children vdom leaves alone, see
+/// `Tag::skips_diff`, e.g. `div data-vdom-skip-diff {}` in `html!`.
+pub const SKIP_DIFF_ATTR: &str = "data-vdom-skip-diff";
+
+struct AttrFinder<'n> {
+    name: &'n str,
+    value: Option<AttrValue>,
+}
+
+impl<'n, D> AttrVisitor<D> for AttrFinder<'n>
+where
+    D: Driver,
+{
+    type Err = Infallible;
+
+    fn on_attr<A>(&mut self, attr: &mut A) -> Result<(), Infallible>
+    where
+        A: Attr<D>,
+    {
+        if attr.name() == self.name {
+            self.value = Some(match attr.value() {
+                AttrRefValue::True => AttrValue::True,
+                AttrRefValue::Null => AttrValue::Null,
+                AttrRefValue::Str(value) => AttrValue::String(value.to_string()),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Counts the nodes of a list by the index after the last one.
+pub(crate) struct ChildCounter {
+    pub(crate) len: usize,
+}
+
+impl<D> NodeVisitor<D> for ChildCounter
+where
+    D: Driver,
+{
+    type Err = Unmounted;
+
+    fn on_tag<T>(&mut self, index: usize, _tag: &mut T) -> Result<(), Unmounted>
+    where
+        T: Tag<D>,
+    {
+        self.len = index + 1;
+        Ok(())
+    }
+
+    fn on_text<T>(&mut self, index: usize, _text: &mut T) -> Result<(), Unmounted>
+    where
+        T: Text<D>,
+    {
+        self.len = index + 1;
+        Ok(())
+    }
+
+    fn on_comp<C>(&mut self, index: &mut usize, comp: &mut CompNode<D, C>) -> Result<(), Unmounted>
+    where
+        C: Comp<D>,
+    {
+        if comp.comp_ctx().is_none() {
+            return Err(Unmounted);
+        }
+        comp.visit_rendered(index, self)
+    }
 }
 
 pub struct TagStatic<D, C, A>
@@ -51,6 +157,11 @@ where
             driver_store: D::new_tag_store(),
         }
     }
+
+    #[cfg(feature = "rayon")]
+    pub(crate) fn children_mut(&mut self) -> &mut C {
+        &mut self.children
+    }
 }
 
 impl<D, C, A> Tag<D> for TagStatic<D, C, A>
@@ -101,12 +212,27 @@ where
     }
 }
 
+/// Shaped like an HTML start tag, e.g. `<div class="x"> (..)`, see
+/// `fmt_tag`.
+impl<D, C, A> fmt::Debug for TagStatic<D, C, A>
+where
+    D: Driver,
+    C: fmt::Debug,
+    A: AttrList<D>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_tag(f, self.tag, &self.attrs, &self.children)
+    }
+}
+
 impl<D, C, A> Node<D> for TagStatic<D, C, A>
 where
     D: Driver,
     C: Node<D>,
     A: AttrList<D>,
 {
+    const IS_STATIC: bool = C::IS_STATIC && A::IS_STATIC;
+
     fn visit<NV>(&mut self, index: &mut usize, visitor: &mut NV) -> Result<(), NV::Err>
     where
         NV: NodeVisitor<D>,
@@ -126,7 +252,12 @@ where
     where
         ND: NodeDiffer<D>,
     {
-        debug_assert_eq!(self.tag, ancestor.tag);
+        if self.tag != ancestor.tag {
+            #[cfg(feature = "strict")]
+            differ.on_invariant_violation(*curr_index, ancestor.tag, self.tag)?;
+            differ.on_node_removed(*curr_index, ancestor_index, ancestor)?;
+            return differ.on_node_added(curr_index, self);
+        }
 
         differ.on_tag(*curr_index, *ancestor_index, self, ancestor)?;
         *curr_index += 1;
@@ -139,7 +270,7 @@ pub struct TagDyn<D, C, A>
 where
     D: Driver,
 {
-    tag: Cow<'static, str>,
+    tag: Name,
     children: C,
     attrs: A,
     driver_store: D::TagStore,
@@ -153,7 +284,7 @@ where
 {
     pub fn new<T>(tag: T, attrs: A, children: C) -> TagDyn<D, C, A>
     where
-        T: Into<Cow<'static, str>>,
+        T: Into<Name>,
     {
         TagDyn {
             tag: tag.into(),
@@ -162,6 +293,12 @@ where
             driver_store: D::new_tag_store(),
         }
     }
+
+    /// Like `new`, but shares the storage of `tag` with every other tag
+    /// interned into `interner` under the same name.
+    pub fn interned(interner: &mut Interner, tag: &str, attrs: A, children: C) -> TagDyn<D, C, A> {
+        TagDyn::new(interner.intern(tag), attrs, children)
+    }
 }
 
 impl<D, C, A> Tag<D> for TagDyn<D, C, A>
@@ -175,7 +312,7 @@ where
     }
 
     fn tag(&self) -> &str {
-        self.tag.as_ref()
+        &self.tag
     }
 
     fn visit_children<NV>(&mut self, visitor: &mut NV) -> Result<(), NV::Err>
@@ -212,6 +349,36 @@ where
     }
 }
 
+/// Shaped like an HTML start tag, e.g. `<div class="x"> (..)`, see
+/// `fmt_tag`.
+impl<D, C, A> fmt::Debug for TagDyn<D, C, A>
+where
+    D: Driver,
+    C: fmt::Debug,
+    A: AttrList<D>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_tag(f, &self.tag, &self.attrs, &self.children)
+    }
+}
+
+/// Writes a tag as its start tag followed by `(..)` in place of its
+/// children, which are written out in full with `{:#?}`.
+fn fmt_tag<D, C, A>(f: &mut fmt::Formatter<'_>, tag: &str, attrs: &A, children: &C) -> fmt::Result
+where
+    D: Driver,
+    C: fmt::Debug,
+    A: AttrList<D>,
+{
+    write!(f, "<{}", tag)?;
+    attrs.fmt_attrs(f)?;
+    if f.alternate() {
+        write!(f, "> {:#?}", children)
+    } else {
+        f.write_str("> (..)")
+    }
+}
+
 impl<D, C, A> Node<D> for TagDyn<D, C, A>
 where
     D: Driver,
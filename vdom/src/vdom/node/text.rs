@@ -1,4 +1,5 @@
 use super::*;
+use std::fmt;
 
 pub trait Text<D>
 where
@@ -7,6 +8,12 @@ where
     fn is_static(&self) -> bool;
     fn get(&self) -> &str;
     fn driver_store(&mut self) -> &mut D::TextStore;
+
+    /// Whether this is a comment rather than text, see `Comment`. Drivers
+    /// render comments as such, while they're visited and diffed like text.
+    fn is_comment(&self) -> bool {
+        false
+    }
 }
 
 pub struct TextStatic<D>
@@ -46,10 +53,21 @@ where
     }
 }
 
+impl<D> fmt::Debug for TextStatic<D>
+where
+    D: Driver,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.get(), f)
+    }
+}
+
 impl<D> Node<D> for TextStatic<D>
 where
     D: Driver,
 {
+    const IS_STATIC: bool = true;
+
     fn visit<NV>(&mut self, index: &mut usize, visitor: &mut NV) -> Result<(), NV::Err>
     where
         NV: NodeVisitor<D>,
@@ -69,7 +87,12 @@ where
     where
         ND: NodeDiffer<D>,
     {
-        debug_assert_eq!(self.text, ancestor.text);
+        #[cfg(feature = "strict")]
+        {
+            if self.text != ancestor.text {
+                differ.on_invariant_violation(*curr_index, ancestor.text, self.text)?;
+            }
+        }
         differ.on_text(*curr_index, *ancestor_index, self, ancestor)?;
         *curr_index += 1;
         *ancestor_index += 1;
@@ -98,6 +121,13 @@ where
             driver_store: D::new_text_store(),
         }
     }
+
+    pub fn set<T>(&mut self, text: T)
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        self.text = text.into();
+    }
 }
 
 impl<D> Text<D> for TextDyn<D>
@@ -117,6 +147,15 @@ where
     }
 }
 
+impl<D> fmt::Debug for TextDyn<D>
+where
+    D: Driver,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.get(), f)
+    }
+}
+
 impl<D> Node<D> for TextDyn<D>
 where
     D: Driver,
@@ -146,3 +185,96 @@ where
         Ok(())
     }
 }
+
+/// A comment, e.g. one read from existing markup, which is character data
+/// like text. It's visited and diffed as a `Text` whose `is_comment` is true.
+pub struct Comment<D>
+where
+    D: Driver,
+{
+    text: Cow<'static, str>,
+    driver_store: D::TextStore,
+}
+
+impl<D> Comment<D>
+where
+    D: Driver,
+{
+    pub fn new<T>(text: T) -> Comment<D>
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        Comment {
+            text: text.into(),
+            driver_store: D::new_text_store(),
+        }
+    }
+
+    pub fn set<T>(&mut self, text: T)
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        self.text = text.into();
+    }
+}
+
+impl<D> Text<D> for Comment<D>
+where
+    D: Driver,
+{
+    fn is_static(&self) -> bool {
+        false
+    }
+
+    fn get(&self) -> &str {
+        self.text.as_ref()
+    }
+
+    fn driver_store(&mut self) -> &mut D::TextStore {
+        &mut self.driver_store
+    }
+
+    fn is_comment(&self) -> bool {
+        true
+    }
+}
+
+/// Shaped like the comment in HTML, e.g. `<!--"note"-->`.
+impl<D> fmt::Debug for Comment<D>
+where
+    D: Driver,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<!--{:?}-->", self.get())
+    }
+}
+
+impl<D> Node<D> for Comment<D>
+where
+    D: Driver,
+{
+    fn visit<NV>(&mut self, index: &mut usize, visitor: &mut NV) -> Result<(), NV::Err>
+    where
+        NV: NodeVisitor<D>,
+    {
+        visitor.on_text(*index, self)?;
+        *index += 1;
+        Ok(())
+    }
+
+    fn diff<ND>(
+        &mut self,
+        curr_index: &mut usize,
+        ancestor_index: &mut usize,
+        ancestor: &mut Self,
+        differ: &mut ND,
+    ) -> Result<(), ND::Err>
+    where
+        ND: NodeDiffer<D>,
+    {
+        differ.on_text(*curr_index, *ancestor_index, self, ancestor)?;
+        *curr_index += 1;
+        *ancestor_index += 1;
+        Ok(())
+    }
+}
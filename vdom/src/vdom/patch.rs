@@ -0,0 +1,1196 @@
+use super::{
+    attr::{Attr, AttrDiffer, AttrRefValue, AttrValue},
+    dom::{check_depth_below, qualified_name, to_dom, DomAttr, DomNode, Limit, LimitError},
+    element_info,
+    key::Key,
+    node::{Comp, CompNode, Node, NodeCounter, NodeDiffer, NodeVisitor, Tag, Text},
+    path::{resolve, Path, PathError, ResolveError},
+};
+use crate::driver::{Driver, DriverCtx};
+use std::{collections::HashSet, convert::Infallible, fmt};
+
+/// A single change to a DOM, addressed by the `Path` of the affected node.
+///
+/// Patches are meant to be applied in the order they were recorded, and each
+/// path is valid in the DOM that all earlier patches have been applied to.
+pub enum Patch<D>
+where
+    D: Driver,
+{
+    /// Inserts `node` so that it ends up at `path`, shifting later siblings.
+    InsertNode {
+        path: Path,
+        node: DomNode<D>,
+    },
+    /// Removes the node at `path` with its subtree, shifting later siblings.
+    RemoveNode {
+        path: Path,
+    },
+    /// Moves the node at `path` with its subtree so that it ends up at index
+    /// `to` among its siblings, as if it was removed and inserted again.
+    /// `key` is the key of the `Keyed` item the node belongs to.
+    MoveNode {
+        path: Path,
+        to: usize,
+        key: Key,
+    },
+    SetText {
+        path: Path,
+        text: String,
+    },
+    /// Replaces `delete_len` characters of the text at `path`, starting at
+    /// character `start`, with `insert`. Offsets count `char`s.
+    SpliceText {
+        path: Path,
+        start: usize,
+        delete_len: usize,
+        insert: String,
+    },
+    SetAttr {
+        path: Path,
+        name: String,
+        value: AttrValue,
+    },
+    RemoveAttr {
+        path: Path,
+        name: String,
+    },
+}
+
+impl<D> Patch<D>
+where
+    D: Driver,
+{
+    pub fn path(&self) -> &Path {
+        match self {
+            Patch::InsertNode { path, .. }
+            | Patch::RemoveNode { path }
+            | Patch::MoveNode { path, .. }
+            | Patch::SetText { path, .. }
+            | Patch::SpliceText { path, .. }
+            | Patch::SetAttr { path, .. }
+            | Patch::RemoveAttr { path, .. } => path,
+        }
+    }
+}
+
+impl<D> fmt::Debug for Patch<D>
+where
+    D: Driver,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Patch::InsertNode { path, node } => {
+                f.debug_struct("InsertNode")
+                    .field("path", path)
+                    .field("node", node)
+                    .finish()
+            }
+            Patch::RemoveNode { path } => f.debug_struct("RemoveNode").field("path", path).finish(),
+            Patch::MoveNode { path, to, key } => {
+                f.debug_struct("MoveNode")
+                    .field("path", path)
+                    .field("to", to)
+                    .field("key", key)
+                    .finish()
+            }
+            Patch::SetText { path, text } => {
+                f.debug_struct("SetText")
+                    .field("path", path)
+                    .field("text", text)
+                    .finish()
+            }
+            Patch::SpliceText {
+                path,
+                start,
+                delete_len,
+                insert,
+            } => {
+                f.debug_struct("SpliceText")
+                    .field("path", path)
+                    .field("start", start)
+                    .field("delete_len", delete_len)
+                    .field("insert", insert)
+                    .finish()
+            }
+            Patch::SetAttr { path, name, value } => {
+                f.debug_struct("SetAttr")
+                    .field("path", path)
+                    .field("name", name)
+                    .field("value", value)
+                    .finish()
+            }
+            Patch::RemoveAttr { path, name } => {
+                f.debug_struct("RemoveAttr")
+                    .field("path", path)
+                    .field("name", name)
+                    .finish()
+            }
+        }
+    }
+}
+
+/// Records the differences between `curr` and `ancestor` as patches. Inserted
+/// subtrees are embedded as owned `DomNode`s, with components rendered using
+/// `driver_ctx`. Listeners are not recorded. As with drivers, an element
+/// that embeds another document is replaced, see
+/// `element_info::document_attrs`, and the children of a `<script>` aren't
+/// patched, see `element_info::runs_once`.
+pub fn diff<D, N>(driver_ctx: &DriverCtx<D>, curr: &mut N, ancestor: &mut N) -> Vec<Patch<D>>
+where
+    D: Driver,
+    N: Node<D>,
+{
+    unlimited(record(
+        driver_ctx,
+        curr,
+        ancestor,
+        &Path::root(),
+        false,
+        None,
+    ))
+}
+
+/// Like `diff`, for trees that may be too deep to diff, e.g. built from
+/// untrusted input. The first node of `curr` deeper than `max_depth`,
+/// counted like `Limits::max_depth`, is returned as an error instead, before
+/// diffing descends into it. Added subtrees are checked with `check_depth`
+/// before they're copied.
+pub fn diff_limited<D, N>(
+    driver_ctx: &DriverCtx<D>,
+    curr: &mut N,
+    ancestor: &mut N,
+    max_depth: usize,
+) -> Result<Vec<Patch<D>>, LimitError>
+where
+    D: Driver,
+    N: Node<D>,
+{
+    record(
+        driver_ctx,
+        curr,
+        ancestor,
+        &Path::root(),
+        false,
+        Some(max_depth),
+    )
+    .map_err(|err| {
+        match err {
+            RecordError::Limit(err) => err,
+            RecordError::Scope(_) => unreachable!("diffing the whole tree has no scope"),
+        }
+    })
+}
+
+/// Like `diff`, but a changed text that shares a prefix or suffix with its
+/// previous value is recorded as a `SpliceText` of the part in between, which
+/// keeps patches small for long texts that are edited, e.g. when streamed to
+/// a client.
+pub fn diff_spliced<D, N>(
+    driver_ctx: &DriverCtx<D>,
+    curr: &mut N,
+    ancestor: &mut N,
+) -> Vec<Patch<D>>
+where
+    D: Driver,
+    N: Node<D>,
+{
+    unlimited(record(
+        driver_ctx,
+        curr,
+        ancestor,
+        &Path::root(),
+        true,
+        None,
+    ))
+}
+
+/// Like `diff`, but only diffs the subtrees at `path`, for when it's known
+/// that nothing else changed. The node at `path` is found in `ancestor` with
+/// `path::resolve`, and the elements leading to it are descended into
+/// without being diffed themselves. Nodes added, removed or moved after the
+/// ones leading to `path` are outside of it and not recorded, while those
+/// before would shift `path` to another node, so they fail with
+/// `ScopeError::Changed`. The patches still carry paths from the root.
+pub fn diff_at<D, N>(
+    driver_ctx: &DriverCtx<D>,
+    curr: &mut N,
+    ancestor: &mut N,
+    path: &Path,
+) -> Result<Vec<Patch<D>>, ScopeError>
+where
+    D: Driver,
+    N: Node<D>,
+{
+    if !path.is_root() {
+        match resolve(ancestor, path, &mut NodeCounter) {
+            Ok(true) => {}
+            Ok(false) => return Err(ScopeError::NotFound(path.clone())),
+            Err(ResolveError::Unmounted(path)) => return Err(ScopeError::Unmounted(path)),
+            Err(ResolveError::Visitor(never)) => match never {},
+        }
+    }
+    record(driver_ctx, curr, ancestor, path, false, None).map_err(|err| {
+        match err {
+            RecordError::Limit(_) => unreachable!("diffing without a depth limit failed"),
+            RecordError::Scope(err) => err,
+        }
+    })
+}
+
+/// Why `diff_at` couldn't diff at a path.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ScopeError {
+    /// There's no node at the path in the ancestor tree.
+    NotFound(Path),
+    /// The component at the path leading to the node in the ancestor tree
+    /// isn't mounted, see `ResolveError::Unmounted`.
+    Unmounted(Path),
+    /// The node at the path was added, removed or moved in the current tree,
+    /// as was a node leading to it or a sibling before one of those, so the
+    /// path doesn't address the same node in both trees.
+    Changed(Path),
+}
+
+enum RecordError {
+    Limit(LimitError),
+    Scope(ScopeError),
+}
+
+impl From<LimitError> for RecordError {
+    fn from(err: LimitError) -> RecordError {
+        RecordError::Limit(err)
+    }
+}
+
+fn unlimited<D>(result: Result<Vec<Patch<D>>, RecordError>) -> Vec<Patch<D>>
+where
+    D: Driver,
+{
+    match result {
+        Ok(patches) => patches,
+        Err(_) => panic!("diffing the whole tree without a depth limit failed"),
+    }
+}
+
+fn record<D, N>(
+    driver_ctx: &DriverCtx<D>,
+    curr: &mut N,
+    ancestor: &mut N,
+    path: &Path,
+    splice_text: bool,
+    max_depth: Option<usize>,
+) -> Result<Vec<Patch<D>>, RecordError>
+where
+    D: Driver,
+    N: Node<D>,
+{
+    let mut patches = Vec::new();
+    let result = curr.diff(
+        &mut 0,
+        &mut 0,
+        ancestor,
+        &mut PatchRecorder {
+            driver_ctx,
+            parent: Path::root(),
+            scope: if path.is_root() {
+                None
+            } else {
+                Some(path.indices())
+            },
+            splice_text,
+            max_depth,
+            patches: &mut patches,
+        },
+    );
+    result.map(|()| patches)
+}
+
+/// Whether `curr` embeds another document than `ancestor`, see
+/// `element_info::document_attrs`, so the element is replaced rather than
+/// patched.
+fn loads_other_document<D, T>(curr: &mut T, ancestor: &mut T) -> bool
+where
+    D: Driver,
+    T: Tag<D>,
+{
+    element_info::document_attrs(curr.tag())
+        .iter()
+        .any(|name| curr.attr_value(name) != ancestor.attr_value(name))
+}
+
+/// A tag as a node of its own, e.g. to record it as added.
+struct TagNode<'t, T>(&'t mut T);
+
+impl<'t, D, T> Node<D> for TagNode<'t, T>
+where
+    D: Driver,
+    T: Tag<D>,
+{
+    fn visit<NV>(&mut self, index: &mut usize, visitor: &mut NV) -> Result<(), NV::Err>
+    where
+        NV: NodeVisitor<D>,
+    {
+        visitor.on_tag(*index, self.0)?;
+        *index += 1;
+        Ok(())
+    }
+
+    fn diff<ND>(
+        &mut self,
+        curr_index: &mut usize,
+        ancestor_index: &mut usize,
+        ancestor: &mut Self,
+        differ: &mut ND,
+    ) -> Result<(), ND::Err>
+    where
+        ND: NodeDiffer<D>,
+    {
+        differ.on_tag(*curr_index, *ancestor_index, self.0, ancestor.0)?;
+        *curr_index += 1;
+        *ancestor_index += 1;
+        Ok(())
+    }
+}
+
+struct PatchRecorder<'a, D>
+where
+    D: Driver,
+{
+    driver_ctx: &'a DriverCtx<D>,
+    parent: Path,
+    /// The rest of the path to diff at, relative to `parent`, or `None` if
+    /// everything below `parent` is diffed.
+    scope: Option<&'a [usize]>,
+    /// Whether changed texts are recorded as `SpliceText` where possible.
+    splice_text: bool,
+    /// The most levels of nodes to descend to, or `None` for no limit.
+    max_depth: Option<usize>,
+    patches: &'a mut Vec<Patch<D>>,
+}
+
+enum Scope<'a> {
+    /// The node is outside of the scope.
+    Skip,
+    /// The node leads to the scope, which is `rest` below it.
+    Along(&'a [usize]),
+    /// The node is within the scope.
+    Full,
+}
+
+impl<'a, D> PatchRecorder<'a, D>
+where
+    D: Driver,
+{
+    fn scope_of(&self, index: usize) -> Scope<'a> {
+        match self.scope.map(|scope| scope.split_first()) {
+            None => Scope::Full,
+            Some(Some((&i, rest))) if i == index => {
+                if rest.is_empty() {
+                    Scope::Full
+                } else {
+                    Scope::Along(rest)
+                }
+            }
+            Some(_) => Scope::Skip,
+        }
+    }
+
+    /// Whether the nodes from `index` on are outside of the scope, as they're
+    /// after the node leading to it. Fails if they're before it, or are the
+    /// node leading to it, unless that node is the scope itself.
+    fn outside_scope(&self, index: usize) -> Result<bool, RecordError> {
+        let (first, rest) = match self.scope.and_then(|scope| scope.split_first()) {
+            Some(scope) => scope,
+            None => return Ok(false),
+        };
+        if index > *first {
+            Ok(true)
+        } else if index == *first && rest.is_empty() {
+            Ok(false)
+        } else {
+            Err(RecordError::Scope(ScopeError::Changed(
+                self.parent.child(index),
+            )))
+        }
+    }
+
+    fn insert<N>(&mut self, index: &mut usize, curr: &mut N) -> Result<(), RecordError>
+    where
+        N: Node<D>,
+    {
+        if let Some(max_depth) = self.max_depth {
+            check_depth_below(self.driver_ctx, curr, &self.parent, *index, max_depth)?;
+        }
+        for node in to_dom(self.driver_ctx, curr) {
+            self.patches.push(Patch::InsertNode {
+                path: self.parent.child(*index),
+                node,
+            });
+            *index += 1;
+        }
+        Ok(())
+    }
+
+    /// The path of the child at `index`, unless it's deeper than
+    /// `max_depth`.
+    fn child_path(&self, index: usize) -> Result<Path, LimitError> {
+        let path = self.parent.child(index);
+        match self.max_depth {
+            Some(max_depth) if path.indices().len() > max_depth => {
+                Err(LimitError {
+                    limit: Limit::Depth,
+                    path,
+                })
+            }
+            _ => Ok(path),
+        }
+    }
+}
+
+impl<'a, D> NodeDiffer<D> for PatchRecorder<'a, D>
+where
+    D: Driver,
+{
+    type Err = RecordError;
+
+    fn on_node_added<N>(&mut self, index: &mut usize, curr: &mut N) -> Result<(), RecordError>
+    where
+        N: Node<D>,
+    {
+        if self.outside_scope(*index)? {
+            *index += to_dom(self.driver_ctx, curr).len();
+            return Ok(());
+        }
+        self.insert(index, curr)
+    }
+
+    fn on_node_removed<N>(
+        &mut self,
+        curr_index: usize,
+        ancestor_index: &mut usize,
+        ancestor: &mut N,
+    ) -> Result<(), RecordError>
+    where
+        N: Node<D>,
+    {
+        let start = *ancestor_index;
+        match ancestor.visit(ancestor_index, &mut NodeCounter) {
+            Ok(()) => {}
+            Err(never) => match never {},
+        }
+        if self.outside_scope(curr_index)? {
+            return Ok(());
+        }
+        for _ in start..*ancestor_index {
+            self.patches.push(Patch::RemoveNode {
+                path: self.parent.child(curr_index),
+            });
+        }
+        Ok(())
+    }
+
+    fn on_node_moved(
+        &mut self,
+        from_index: usize,
+        to_index: usize,
+        key: &Key,
+    ) -> Result<(), RecordError> {
+        if self.outside_scope(from_index.min(to_index))? {
+            return Ok(());
+        }
+        self.patches.push(Patch::MoveNode {
+            path: self.parent.child(from_index),
+            to: to_index,
+            key: key.clone(),
+        });
+        Ok(())
+    }
+
+    fn on_invariant_violation(
+        &mut self,
+        _curr_index: usize,
+        _expected: &str,
+        _found: &str,
+    ) -> Result<(), RecordError> {
+        Ok(())
+    }
+
+    fn on_tag<T>(
+        &mut self,
+        curr_index: usize,
+        _ancestor_index: usize,
+        curr: &mut T,
+        ancestor: &mut T,
+    ) -> Result<(), RecordError>
+    where
+        T: Tag<D>,
+    {
+        let path = self.child_path(curr_index)?;
+        let scope = match self.scope_of(curr_index) {
+            Scope::Skip => return Ok(()),
+            Scope::Along(rest) => Some(rest),
+            Scope::Full if loads_other_document(curr, ancestor) => {
+                self.patches.push(Patch::RemoveNode { path });
+                return self.insert(&mut curr_index.clone(), &mut TagNode(curr));
+            }
+            Scope::Full => {
+                let result = curr.diff_attrs(
+                    ancestor,
+                    &mut AttrRecorder {
+                        path: &path,
+                        patches: self.patches,
+                    },
+                );
+                match result {
+                    Ok(()) => {}
+                    Err(never) => match never {},
+                }
+                None
+            }
+        };
+        // Whatever is in an element that skips diffing isn't known, so there
+        // are no patches to it, even if it stopped skipping. Scripts ran
+        // when they were inserted, so patching them would change nothing.
+        if curr.skips_diff() || ancestor.skips_diff() || element_info::runs_once(curr.tag()) {
+            return Ok(());
+        }
+        curr.diff_children(
+            ancestor,
+            &mut PatchRecorder {
+                driver_ctx: self.driver_ctx,
+                parent: path,
+                scope,
+                splice_text: self.splice_text,
+                max_depth: self.max_depth,
+                patches: self.patches,
+            },
+        )
+    }
+
+    fn on_text<T>(
+        &mut self,
+        curr_index: usize,
+        _ancestor_index: usize,
+        curr: &mut T,
+        ancestor: &mut T,
+    ) -> Result<(), RecordError>
+    where
+        T: Text<D>,
+    {
+        let path = self.child_path(curr_index)?;
+        let in_scope = match self.scope_of(curr_index) {
+            Scope::Full => true,
+            Scope::Skip | Scope::Along(_) => false,
+        };
+        if !in_scope || curr.get() == ancestor.get() {
+            return Ok(());
+        }
+        let patch = match text_splice(ancestor.get(), curr.get()) {
+            Some((start, delete_len, insert)) if self.splice_text => {
+                Patch::SpliceText {
+                    path,
+                    start,
+                    delete_len,
+                    insert: insert.to_string(),
+                }
+            }
+            _ => {
+                Patch::SetText {
+                    path,
+                    text: curr.get().to_string(),
+                }
+            }
+        };
+        self.patches.push(patch);
+        Ok(())
+    }
+
+    fn on_comp<C>(
+        &mut self,
+        curr_index: &mut usize,
+        ancestor_index: &mut usize,
+        curr: &mut CompNode<D, C>,
+        ancestor: &mut CompNode<D, C>,
+    ) -> Result<(), RecordError>
+    where
+        C: Comp<D>,
+    {
+        if curr.comp_ctx().is_none() {
+            let ctx = ancestor.comp_ctx().expect("ancestor.comp_ctx is None");
+            curr.set_comp_ctx(ctx.clone());
+        }
+        curr.diff_rendered(curr_index, ancestor_index, ancestor, self)
+    }
+
+    fn driver_ctx(&self) -> Option<DriverCtx<D>> {
+        Some(self.driver_ctx.clone())
+    }
+}
+
+/// The splice turning `old` into `new` as `(start, delete_len, insert)`,
+/// counted in chars as for `SpliceText`, which keeps their common prefix and
+/// suffix, or `None` if they have neither.
+pub fn text_splice<'a>(old: &str, new: &'a str) -> Option<(usize, usize, &'a str)> {
+    let start = old
+        .chars()
+        .zip(new.chars())
+        .take_while(|(old, new)| old == new)
+        .count();
+    // The prefix is the same in both, and so is its length in bytes.
+    let prefix_len = new.char_indices().nth(start).map_or(new.len(), |(i, _)| i);
+    let (old, new) = (&old[prefix_len..], &new[prefix_len..]);
+    let suffix = old
+        .chars()
+        .rev()
+        .zip(new.chars().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+    if start == 0 && suffix == 0 {
+        return None;
+    }
+    let suffix_len: usize = new.chars().rev().take(suffix).map(char::len_utf8).sum();
+    let insert = &new[..new.len() - suffix_len];
+    Some((start, old.chars().count() - suffix, insert))
+}
+
+/// Applies a `SpliceText` to `text`, or returns `None` if the spliced range is
+/// out of bounds.
+pub fn splice_text(text: &str, start: usize, delete_len: usize, insert: &str) -> Option<String> {
+    let byte_index = |index| {
+        text.char_indices()
+            .map(|(i, _)| i)
+            .chain(Some(text.len()))
+            .nth(index)
+    };
+    let end = byte_index(start.checked_add(delete_len)?)?;
+    let start = byte_index(start)?;
+    Some([&text[..start], insert, &text[end..]].concat())
+}
+
+struct AttrRecorder<'a, D>
+where
+    D: Driver,
+{
+    path: &'a Path,
+    patches: &'a mut Vec<Patch<D>>,
+}
+
+impl<'a, D> AttrRecorder<'a, D>
+where
+    D: Driver,
+{
+    fn set<A>(&mut self, attr: &A)
+    where
+        A: Attr<D>,
+    {
+        let value = match attr.value() {
+            AttrRefValue::True => AttrValue::True,
+            AttrRefValue::Null => return self.remove(attr),
+            AttrRefValue::Str(s) => AttrValue::String(s.to_string()),
+        };
+        self.patches.push(Patch::SetAttr {
+            path: self.path.clone(),
+            name: qualified_name(attr),
+            value,
+        });
+    }
+
+    fn remove<A>(&mut self, attr: &A)
+    where
+        A: Attr<D>,
+    {
+        self.patches.push(Patch::RemoveAttr {
+            path: self.path.clone(),
+            name: qualified_name(attr),
+        });
+    }
+}
+
+impl<'a, D> AttrDiffer<D> for AttrRecorder<'a, D>
+where
+    D: Driver,
+{
+    type Err = Infallible;
+
+    fn on_diff<A>(&mut self, curr: &mut A, ancestor: &mut A) -> Result<(), Infallible>
+    where
+        A: Attr<D>,
+    {
+        if curr.listener().is_some() || curr.value() == ancestor.value() {
+            return Ok(());
+        }
+        self.set(curr);
+        Ok(())
+    }
+
+    fn on_added<A>(&mut self, curr: &mut A) -> Result<(), Infallible>
+    where
+        A: Attr<D>,
+    {
+        if curr.listener().is_none() && curr.value() != AttrRefValue::Null {
+            self.set(curr);
+        }
+        Ok(())
+    }
+
+    fn on_removed<A>(&mut self, ancestor: &mut A) -> Result<(), Infallible>
+    where
+        A: Attr<D>,
+    {
+        if ancestor.listener().is_none() && ancestor.value() != AttrRefValue::Null {
+            self.remove(ancestor);
+        }
+        Ok(())
+    }
+}
+
+/// Drops patches that don't change the outcome of applying `patches`: writes
+/// of an attribute or text that are overwritten by a later write to the same
+/// node, and nodes that are inserted and then immediately removed again.
+pub fn coalesce<D>(patches: Vec<Patch<D>>) -> Vec<Patch<D>>
+where
+    D: Driver,
+{
+    // Walking backwards, `written` holds the writes made later on, keyed by
+    // path and attribute name, or `None` for text. Inserting or removing a
+    // node shifts the paths after it, so later writes to those paths may be
+    // for other nodes and are forgotten.
+    let mut written = HashSet::new();
+    let mut kept = Vec::with_capacity(patches.len());
+    for patch in patches.into_iter().rev() {
+        let key = match &patch {
+            Patch::InsertNode { path, .. } | Patch::RemoveNode { path } => {
+                written.retain(|(written, _): &(Path, Option<String>)| !is_shifted(path, written));
+                None
+            }
+            Patch::MoveNode { path, to, .. } => {
+                let to = path.sibling(*to);
+                written.retain(|(written, _): &(Path, Option<String>)| {
+                    !is_shifted(path, written) && !is_shifted(&to, written)
+                });
+                None
+            }
+            Patch::SetText { path, .. } => Some((path.clone(), None)),
+            // A splice edits the text written before it, so it doesn't
+            // overwrite earlier writes, but is dropped if a later one does.
+            Patch::SpliceText { path, .. } => {
+                if written.contains(&(path.clone(), None)) {
+                    continue;
+                }
+                None
+            }
+            Patch::SetAttr { path, name, .. } | Patch::RemoveAttr { path, name } => {
+                Some((path.clone(), Some(name.clone())))
+            }
+        };
+        let overwritten = match key {
+            Some(key) => !written.insert(key),
+            None => false,
+        };
+        if !overwritten {
+            kept.push(patch);
+        }
+    }
+
+    let mut coalesced: Vec<Patch<D>> = Vec::with_capacity(kept.len());
+    for patch in kept.into_iter().rev() {
+        if let Patch::RemoveNode { path } = &patch {
+            if let Some(Patch::InsertNode { path: inserted, .. }) = coalesced.last() {
+                if inserted == path {
+                    coalesced.pop();
+                    continue;
+                }
+            }
+        }
+        coalesced.push(patch);
+    }
+    coalesced
+}
+
+/// Whether inserting or removing a node at `changed` moves the node at
+/// `path`, which is the case for the node itself, its subtree and later
+/// siblings along with their subtrees.
+fn is_shifted(changed: &Path, path: &Path) -> bool {
+    match changed.indices().split_last() {
+        Some((index, parent)) => {
+            path.starts_with(&Path::from(parent.to_vec()))
+                && path
+                    .indices()
+                    .get(parent.len())
+                    .map_or(false, |i| i >= index)
+        }
+        None => true,
+    }
+}
+
+/// A patch that doesn't apply, as the `index` of it among the patches and the
+/// reason.
+#[derive(Debug)]
+pub struct PatchError {
+    pub index: usize,
+    pub err: PathError,
+}
+
+/// The kind of a node of a `PatchTarget`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum NodeKind {
+    Element,
+    Text,
+    /// A comment, whose text is patched like that of a text node.
+    Comment,
+    /// Nodes that patches can't address, e.g. processing instructions.
+    Other,
+}
+
+/// A node of a tree that `validate` checks patches against, e.g. of a live
+/// DOM. Children and text lengths are only looked up where patches lead.
+pub trait PatchTarget: Sized {
+    fn kind(&self) -> NodeKind;
+
+    /// The children of an element.
+    fn children(&self) -> Vec<Self>;
+
+    /// The length of a text in chars.
+    fn text_len(&self) -> usize;
+}
+
+impl<'a, D> PatchTarget for &'a DomNode<D>
+where
+    D: Driver,
+{
+    fn kind(&self) -> NodeKind {
+        match self {
+            DomNode::Tag(_) => NodeKind::Element,
+            DomNode::Text(_) => NodeKind::Text,
+            DomNode::Comment(_) => NodeKind::Comment,
+        }
+    }
+
+    fn children(&self) -> Vec<&'a DomNode<D>> {
+        match self {
+            DomNode::Tag(tag) => tag.children.iter().collect(),
+            DomNode::Text(_) | DomNode::Comment(_) => Vec::new(),
+        }
+    }
+
+    fn text_len(&self) -> usize {
+        match self {
+            DomNode::Tag(_) => 0,
+            DomNode::Text(text) => text.get().chars().count(),
+            DomNode::Comment(comment) => comment.get().chars().count(),
+        }
+    }
+}
+
+/// Checks that `patches` apply in order to the tree whose root list is
+/// `roots`, without changing it: that every path leads to a node, and that
+/// the node is of the kind the patch expects. Appliers can check patches
+/// first, so that a bad patch doesn't leave a tree half patched.
+pub fn validate<D, T>(roots: Vec<T>, patches: &[Patch<D>]) -> Result<(), PatchError>
+where
+    D: Driver,
+    T: PatchTarget,
+{
+    let mut roots = roots.into_iter().map(Shadow::of).collect();
+    for (index, patch) in patches.iter().enumerate() {
+        check(&mut roots, patch).map_err(|err| PatchError { index, err })?;
+    }
+    Ok(())
+}
+
+/// What `validate` knows of a node, as patched so far.
+struct Shadow<T> {
+    kind: NodeKind,
+    /// The node this stands for, until it had to be looked into.
+    source: Option<T>,
+    children: Vec<Shadow<T>>,
+    text_len: Option<usize>,
+}
+
+impl<T> Shadow<T>
+where
+    T: PatchTarget,
+{
+    fn of(source: T) -> Shadow<T> {
+        Shadow {
+            kind: source.kind(),
+            source: Some(source),
+            children: Vec::new(),
+            text_len: None,
+        }
+    }
+
+    fn of_dom<D>(node: &DomNode<D>) -> Shadow<T>
+    where
+        D: Driver,
+    {
+        let (kind, children, text_len) = match node {
+            DomNode::Tag(tag) => {
+                let children = tag.children.iter().map(Shadow::of_dom).collect();
+                (NodeKind::Element, children, None)
+            }
+            DomNode::Text(text) => (NodeKind::Text, Vec::new(), Some(text.get().chars().count())),
+            DomNode::Comment(comment) => {
+                let text_len = comment.get().chars().count();
+                (NodeKind::Comment, Vec::new(), Some(text_len))
+            }
+        };
+        Shadow {
+            kind,
+            source: None,
+            children,
+            text_len,
+        }
+    }
+
+    fn children_mut(&mut self) -> &mut Vec<Shadow<T>> {
+        if let Some(source) = self.source.take() {
+            self.children = source.children().into_iter().map(Shadow::of).collect();
+        }
+        &mut self.children
+    }
+
+    fn text_len(&mut self) -> usize {
+        match (self.text_len, &self.source) {
+            (Some(len), _) => len,
+            (None, Some(source)) => source.text_len(),
+            (None, None) => 0,
+        }
+    }
+}
+
+fn check<D, T>(roots: &mut Vec<Shadow<T>>, patch: &Patch<D>) -> Result<(), PathError>
+where
+    D: Driver,
+    T: PatchTarget,
+{
+    let path = patch.path();
+    let (siblings, index) = shadow_siblings(roots, path)?;
+    if let Patch::InsertNode { node, .. } = patch {
+        if index > siblings.len() {
+            return Err(out_of_bounds(path));
+        }
+        siblings.insert(index, Shadow::of_dom(node));
+        return Ok(());
+    }
+    if index >= siblings.len() {
+        return Err(out_of_bounds(path));
+    }
+    if let Patch::RemoveNode { .. } = patch {
+        siblings.remove(index);
+        return Ok(());
+    }
+    if let Patch::MoveNode { to, .. } = patch {
+        if *to >= siblings.len() {
+            return Err(out_of_bounds(&path.sibling(*to)));
+        }
+        let node = siblings.remove(index);
+        siblings.insert(*to, node);
+        return Ok(());
+    }
+
+    let node = &mut siblings[index];
+    let text_len = match patch {
+        Patch::SetAttr { .. } | Patch::RemoveAttr { .. } => {
+            if node.kind != NodeKind::Element {
+                return Err(PathError::NotAnElement(path.clone()));
+            }
+            return Ok(());
+        }
+        _ if node.kind != NodeKind::Text && node.kind != NodeKind::Comment => {
+            return Err(PathError::NotText(path.clone()))
+        }
+        Patch::SetText { text, .. } => text.chars().count(),
+        Patch::SpliceText {
+            start,
+            delete_len,
+            insert,
+            ..
+        } => {
+            let len = node.text_len();
+            match start.checked_add(*delete_len) {
+                Some(end) if end <= len => len - delete_len + insert.chars().count(),
+                _ => return Err(PathError::TextOutOfBounds(path.clone())),
+            }
+        }
+        Patch::InsertNode { .. } | Patch::RemoveNode { .. } | Patch::MoveNode { .. } => {
+            unreachable!()
+        }
+    };
+    node.text_len = Some(text_len);
+    Ok(())
+}
+
+/// Like `siblings_mut`, for a tree of shadows.
+fn shadow_siblings<'a, T>(
+    roots: &'a mut Vec<Shadow<T>>,
+    path: &Path,
+) -> Result<(&'a mut Vec<Shadow<T>>, usize), PathError>
+where
+    T: PatchTarget,
+{
+    let (index, parent) = match path.indices().split_last() {
+        Some(split) => split,
+        None => return Err(PathError::NotAnElement(Path::root())),
+    };
+    let mut siblings = roots;
+    for (depth, &i) in parent.iter().enumerate() {
+        siblings = match siblings.get_mut(i) {
+            Some(node) if node.kind == NodeKind::Element => node.children_mut(),
+            Some(_) => {
+                return Err(PathError::NotAnElement(Path::from(
+                    path.indices()[..=depth].to_vec(),
+                )))
+            }
+            None => {
+                return Err(PathError::OutOfBounds {
+                    parent: Path::from(path.indices()[..depth].to_vec()),
+                    index: i,
+                })
+            }
+        };
+    }
+    Ok((siblings, *index))
+}
+
+/// Applies `patches` in order to the DOM whose root list is `nodes`. The
+/// patches are validated first, so if one doesn't apply, `nodes` is left
+/// unchanged.
+pub fn apply<D>(nodes: &mut Vec<DomNode<D>>, patches: Vec<Patch<D>>) -> Result<(), PathError>
+where
+    D: Driver,
+{
+    validate(nodes.iter().collect(), &patches).map_err(|err| err.err)?;
+    for patch in patches {
+        match patch {
+            Patch::InsertNode { path, node } => {
+                let (siblings, index) = siblings_mut(nodes, &path)?;
+                if index > siblings.len() {
+                    return Err(out_of_bounds(&path));
+                }
+                siblings.insert(index, node);
+            }
+            Patch::RemoveNode { path } => {
+                let (siblings, index) = siblings_mut(nodes, &path)?;
+                if index >= siblings.len() {
+                    return Err(out_of_bounds(&path));
+                }
+                siblings.remove(index);
+            }
+            Patch::MoveNode { path, to, .. } => {
+                let (siblings, index) = siblings_mut(nodes, &path)?;
+                if index >= siblings.len() || to >= siblings.len() {
+                    return Err(out_of_bounds(&path));
+                }
+                let node = siblings.remove(index);
+                siblings.insert(to, node);
+            }
+            Patch::SetText { path, text } => {
+                update_text(node_mut(nodes, &path)?, &path, |_| Ok(text))?
+            }
+            Patch::SpliceText {
+                path,
+                start,
+                delete_len,
+                insert,
+            } => {
+                update_text(node_mut(nodes, &path)?, &path, |text| {
+                    splice_text(text, start, delete_len, &insert)
+                        .ok_or_else(|| PathError::TextOutOfBounds(path.clone()))
+                })?
+            }
+            Patch::SetAttr { path, name, value } => {
+                let tag = match node_mut(nodes, &path)? {
+                    DomNode::Tag(tag) => tag,
+                    DomNode::Text(_) | DomNode::Comment(_) => {
+                        return Err(PathError::NotAnElement(path))
+                    }
+                };
+                match tag.attrs.iter_mut().find(|attr| attr.name == name) {
+                    Some(attr) => attr.value = value,
+                    None => tag.attrs.push(DomAttr::new(name, value)),
+                }
+            }
+            Patch::RemoveAttr { path, name } => {
+                match node_mut(nodes, &path)? {
+                    DomNode::Tag(tag) => tag.attrs.retain(|attr| attr.name != name),
+                    DomNode::Text(_) | DomNode::Comment(_) => {
+                        return Err(PathError::NotAnElement(path))
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The list containing the node at `path` and the node's index within it.
+fn siblings_mut<'a, D>(
+    nodes: &'a mut Vec<DomNode<D>>,
+    path: &Path,
+) -> Result<(&'a mut Vec<DomNode<D>>, usize), PathError>
+where
+    D: Driver,
+{
+    let (index, parent) = match path.indices().split_last() {
+        Some(split) => split,
+        None => return Err(PathError::NotAnElement(Path::root())),
+    };
+    let mut siblings = nodes;
+    for (depth, &i) in parent.iter().enumerate() {
+        siblings = match siblings.get_mut(i) {
+            Some(DomNode::Tag(tag)) => &mut tag.children,
+            Some(DomNode::Text(_)) | Some(DomNode::Comment(_)) => {
+                return Err(PathError::NotAnElement(Path::from(
+                    path.indices()[..=depth].to_vec(),
+                )))
+            }
+            None => {
+                return Err(PathError::OutOfBounds {
+                    parent: Path::from(path.indices()[..depth].to_vec()),
+                    index: i,
+                })
+            }
+        };
+    }
+    Ok((siblings, *index))
+}
+
+/// Sets the text of `node`, a text or comment at `path`, to what `f` makes
+/// of it.
+fn update_text<D, F>(node: &mut DomNode<D>, path: &Path, f: F) -> Result<(), PathError>
+where
+    D: Driver,
+    F: FnOnce(&str) -> Result<String, PathError>,
+{
+    match node {
+        DomNode::Text(text) => {
+            let updated = f(text.get())?;
+            text.set(updated);
+        }
+        DomNode::Comment(comment) => {
+            let updated = f(comment.get())?;
+            comment.set(updated);
+        }
+        DomNode::Tag(_) => return Err(PathError::NotText(path.clone())),
+    }
+    Ok(())
+}
+
+fn node_mut<'a, D>(
+    nodes: &'a mut Vec<DomNode<D>>,
+    path: &Path,
+) -> Result<&'a mut DomNode<D>, PathError>
+where
+    D: Driver,
+{
+    let (siblings, index) = siblings_mut(nodes, path)?;
+    siblings.get_mut(index).ok_or_else(|| out_of_bounds(path))
+}
+
+fn out_of_bounds(path: &Path) -> PathError {
+    let mut parent = path.clone();
+    let index = parent.pop().unwrap_or(0);
+    PathError::OutOfBounds { parent, index }
+}
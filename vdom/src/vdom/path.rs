@@ -0,0 +1,318 @@
+use super::node::{Comp, CompNode, Node, NodeVisitor, Tag, Text};
+use crate::driver::{Driver, DriverCtx};
+use std::{error::Error, fmt, str::FromStr};
+
+/// The type of the indices of a path read from outside, by parsing or
+/// decoding, so they have the same range on every platform, e.g. a server
+/// and a wasm32 client. Larger indices are rejected with
+/// `PathParseError::OutOfRange`.
+pub type PathIndex = u32;
+
+/// Location of a node as the child indices leading to it from the root list.
+///
+/// Indices are flat: nested lists such as `((a, b), (c, d))`, `Vec`s and
+/// `Option`s don't add a level, so `c` has the index 2 within its parent.
+/// Components don't add a level either; their rendered nodes take their place.
+///
+/// A path is written as `/0/2` and parsed from that form, see `Display` and
+/// `FromStr`, and can be encoded into bytes with `to_bytes`.
+#[derive(Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Path(Vec<usize>);
+
+impl Path {
+    pub fn root() -> Path {
+        Path(Vec::new())
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn indices(&self) -> &[usize] {
+        &self.0
+    }
+
+    pub fn child(&self, index: usize) -> Path {
+        let mut path = self.clone();
+        path.push(index);
+        path
+    }
+
+    pub fn parent(&self) -> Option<Path> {
+        let mut path = self.clone();
+        path.pop().map(|_| path)
+    }
+
+    /// The path of the sibling at `index`, or the root path for the root.
+    pub fn sibling(&self, index: usize) -> Path {
+        match self.parent() {
+            Some(parent) => parent.child(index),
+            None => Path::root(),
+        }
+    }
+
+    pub fn push(&mut self, index: usize) {
+        self.0.push(index);
+    }
+
+    pub fn pop(&mut self) -> Option<usize> {
+        self.0.pop()
+    }
+
+    pub fn starts_with(&self, prefix: &Path) -> bool {
+        self.0.starts_with(&prefix.0)
+    }
+
+    /// The indices as unsigned LEB128 varints, one after another. Fails for
+    /// indices `from_bytes` would reject, those greater than `PathIndex::MAX`.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, PathParseError> {
+        let mut bytes = Vec::with_capacity(self.0.len());
+        for (segment, &index) in self.0.iter().enumerate() {
+            let mut index = checked_index(index as u64, segment)?;
+            while index >= 0x80 {
+                bytes.push((index & 0x7f) as u8 | 0x80);
+                index >>= 7;
+            }
+            bytes.push(index as u8);
+        }
+        Ok(bytes)
+    }
+
+    /// Decodes a path encoded with `to_bytes`, e.g. received from a client.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Path, PathParseError> {
+        let mut indices = Vec::new();
+        let mut index: u64 = 0;
+        let mut shift = 0;
+        for &byte in bytes {
+            // A `u32` takes at most 5 bytes, so the shifted value fits a
+            // `u64` before it's checked.
+            if shift > 28 {
+                return Err(PathParseError::OutOfRange {
+                    segment: indices.len(),
+                });
+            }
+            index |= u64::from(byte & 0x7f) << shift;
+            shift += 7;
+            if byte & 0x80 == 0 {
+                indices.push(checked_index(index, indices.len())?);
+                index = 0;
+                shift = 0;
+            }
+        }
+        if shift > 0 {
+            return Err(PathParseError::Truncated);
+        }
+        Ok(Path(indices))
+    }
+}
+
+fn checked_index(index: u64, segment: usize) -> Result<usize, PathParseError> {
+    if index > u64::from(PathIndex::MAX) {
+        return Err(PathParseError::OutOfRange { segment });
+    }
+    Ok(index as usize)
+}
+
+impl From<Vec<usize>> for Path {
+    fn from(indices: Vec<usize>) -> Path {
+        Path(indices)
+    }
+}
+
+impl fmt::Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("/")?;
+        for (i, index) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str("/")?;
+            }
+            write!(f, "{}", index)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Path {
+    type Err = PathParseError;
+
+    fn from_str(s: &str) -> Result<Path, PathParseError> {
+        if !s.starts_with('/') {
+            return Err(PathParseError::MissingSlash);
+        }
+        if s == "/" {
+            return Ok(Path::root());
+        }
+        s[1..]
+            .split('/')
+            .enumerate()
+            .map(|(segment, index)| {
+                if index.is_empty() || !index.bytes().all(|b| b.is_ascii_digit()) {
+                    return Err(PathParseError::InvalidIndex { segment });
+                }
+                // Digits only, so parsing can only fail by overflowing.
+                let index = index
+                    .parse::<u64>()
+                    .map_err(|_| PathParseError::OutOfRange { segment })?;
+                checked_index(index, segment)
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(Path)
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PathParseError {
+    /// The text doesn't start with `/`.
+    MissingSlash,
+    /// The index at position `segment` isn't a decimal number.
+    InvalidIndex { segment: usize },
+    /// The index at position `segment` is greater than `PathIndex::MAX`.
+    OutOfRange { segment: usize },
+    /// The bytes end within an index.
+    Truncated,
+}
+
+impl fmt::Display for PathParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathParseError::MissingSlash => f.write_str("path doesn't start with `/`"),
+            PathParseError::InvalidIndex { segment } => {
+                write!(f, "index {} of path isn't a decimal number", segment)
+            }
+            PathParseError::OutOfRange { segment } => {
+                write!(
+                    f,
+                    "index {} of path is greater than {}",
+                    segment,
+                    PathIndex::MAX
+                )
+            }
+            PathParseError::Truncated => f.write_str("path ends within an index"),
+        }
+    }
+}
+
+impl Error for PathParseError {}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PathError {
+    /// The node at `parent` has no child at `index`.
+    OutOfBounds { parent: Path, index: usize },
+    /// The node at the path is not an element, so it has no children.
+    NotAnElement(Path),
+    /// The node at the path is not a text node.
+    NotText(Path),
+    /// The text at the path is too short for the range spliced.
+    TextOutOfBounds(Path),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ResolveError<E> {
+    /// The nodes at the path, which starts with a component that isn't
+    /// mounted, weren't rendered, see `Unmounted`.
+    Unmounted(Path),
+    /// The visitor failed.
+    Visitor(E),
+}
+
+/// Hands the node at `path` within `root` to `visitor`. Returns whether such
+/// a node exists. The root path addresses no single node and is never found.
+/// Components on the way have to be mounted, as they're rendered to be
+/// searched.
+pub fn resolve<D, N, NV>(
+    root: &mut N,
+    path: &Path,
+    visitor: &mut NV,
+) -> Result<bool, ResolveError<NV::Err>>
+where
+    D: Driver,
+    N: Node<D> + ?Sized,
+    NV: NodeVisitor<D>,
+{
+    if path.is_root() {
+        return Ok(false);
+    }
+    let mut resolver = Resolver {
+        parent: Path::root(),
+        path: path.indices(),
+        visitor,
+        found: false,
+    };
+    root.visit(&mut 0, &mut resolver)?;
+    Ok(resolver.found)
+}
+
+struct Resolver<'a, NV> {
+    parent: Path,
+    path: &'a [usize],
+    visitor: &'a mut NV,
+    found: bool,
+}
+
+impl<'a, D, NV> NodeVisitor<D> for Resolver<'a, NV>
+where
+    D: Driver,
+    NV: NodeVisitor<D>,
+{
+    type Err = ResolveError<NV::Err>;
+
+    fn on_tag<T>(&mut self, index: usize, tag: &mut T) -> Result<(), Self::Err>
+    where
+        T: Tag<D>,
+    {
+        match self.path.split_first() {
+            Some((i, rest)) if *i == index => {
+                if rest.is_empty() {
+                    self.found = true;
+                    return self
+                        .visitor
+                        .on_tag(index, tag)
+                        .map_err(ResolveError::Visitor);
+                }
+                let mut resolver = Resolver {
+                    parent: self.parent.child(index),
+                    path: rest,
+                    visitor: &mut *self.visitor,
+                    found: false,
+                };
+                tag.visit_children(&mut resolver)?;
+                self.found = resolver.found;
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn on_text<T>(&mut self, index: usize, text: &mut T) -> Result<(), Self::Err>
+    where
+        T: Text<D>,
+    {
+        if self.path == [index] {
+            self.found = true;
+            self.visitor
+                .on_text(index, text)
+                .map_err(ResolveError::Visitor)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn on_comp<C>(&mut self, index: &mut usize, comp: &mut CompNode<D, C>) -> Result<(), Self::Err>
+    where
+        C: Comp<D>,
+    {
+        // Components after the node can't lead to it.
+        match self.path.first() {
+            Some(i) if *i >= *index => {}
+            _ => return Ok(()),
+        }
+        if comp.comp_ctx().is_none() {
+            return Err(ResolveError::Unmounted(self.parent.child(*index)));
+        }
+        comp.visit_rendered(index, self)
+    }
+
+    fn driver_ctx(&self) -> Option<DriverCtx<D>> {
+        self.visitor.driver_ctx()
+    }
+}
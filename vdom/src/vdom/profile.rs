@@ -0,0 +1,395 @@
+//! Times a diff per tag, to find the subtrees that are expensive to diff,
+//! see `ProfilingDiffer`.
+
+use super::{
+    attr::{AttrDiffer, AttrValue, AttrVisitor},
+    key::Key,
+    node::{Comp, CompNode, Node, NodeDiffer, NodeVisitor, Tag, Text, Unmounted},
+    path::Path,
+};
+use crate::driver::{Driver, DriverCtx};
+use std::time::Instant;
+
+/// Forwards every call to `differ`, timing the calls for tags. A tag's time
+/// includes diffing its children, whose tags are timed as well, as the tag
+/// hands `differ` the children wrapped in a differ of its own. Components
+/// are left to `differ`, which may prepare them, e.g. render those of a
+/// lazily hydrated app, so a component is timed as a whole, including the
+/// tags it renders.
+///
+/// The entries are in the order the tags were diffed, with each tag before
+/// its children and keyed by its path, which makes them easy to turn into a
+/// flame graph.
+pub struct ProfilingDiffer<ND> {
+    differ: ND,
+    now: fn() -> f64,
+    entries: Vec<ProfileEntry>,
+}
+
+/// How long diffing the tag or component at `path` took.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProfileEntry {
+    pub path: Path,
+    /// The tag, or the type of the component.
+    pub tag: String,
+    pub duration_ms: f64,
+}
+
+impl<ND> ProfilingDiffer<ND> {
+    /// Times with `Instant`, which isn't available in browsers, see
+    /// `with_clock`.
+    pub fn new(differ: ND) -> ProfilingDiffer<ND> {
+        ProfilingDiffer::with_clock(differ, instant_ms)
+    }
+
+    /// Times with `now`, which returns the current time in milliseconds,
+    /// e.g. from `performance.now()` in a browser.
+    pub fn with_clock(differ: ND, now: fn() -> f64) -> ProfilingDiffer<ND> {
+        ProfilingDiffer {
+            differ,
+            now,
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn entries(&self) -> &[ProfileEntry] {
+        &self.entries
+    }
+
+    pub fn into_inner(self) -> (ND, Vec<ProfileEntry>) {
+        (self.differ, self.entries)
+    }
+
+    fn profiler(&mut self) -> Profiler<'_, ND> {
+        Profiler {
+            differ: &mut self.differ,
+            parent: Path::root(),
+            now: self.now,
+            entries: &mut self.entries,
+        }
+    }
+}
+
+/// The milliseconds since this was first called on the current thread.
+fn instant_ms() -> f64 {
+    thread_local! {
+        static START: Instant = Instant::now();
+    }
+    START.with(|start| start.elapsed().as_secs_f64() * 1000.0)
+}
+
+impl<D, ND> NodeDiffer<D> for ProfilingDiffer<ND>
+where
+    D: Driver,
+    ND: NodeDiffer<D>,
+{
+    type Err = ND::Err;
+
+    fn on_node_added<N>(&mut self, index: &mut usize, curr: &mut N) -> Result<(), Self::Err>
+    where
+        N: Node<D>,
+    {
+        self.profiler().on_node_added(index, curr)
+    }
+
+    fn on_node_removed<N>(
+        &mut self,
+        curr_index: usize,
+        ancestor_index: &mut usize,
+        ancestor: &mut N,
+    ) -> Result<(), Self::Err>
+    where
+        N: Node<D>,
+    {
+        self.profiler()
+            .on_node_removed(curr_index, ancestor_index, ancestor)
+    }
+
+    fn on_node_moved(
+        &mut self,
+        from_index: usize,
+        to_index: usize,
+        key: &Key,
+    ) -> Result<(), Self::Err> {
+        self.profiler().on_node_moved(from_index, to_index, key)
+    }
+
+    fn on_invariant_violation(
+        &mut self,
+        curr_index: usize,
+        expected: &str,
+        found: &str,
+    ) -> Result<(), Self::Err> {
+        self.profiler()
+            .on_invariant_violation(curr_index, expected, found)
+    }
+
+    fn on_tag<T>(
+        &mut self,
+        curr_index: usize,
+        ancestor_index: usize,
+        curr: &mut T,
+        ancestor: &mut T,
+    ) -> Result<(), Self::Err>
+    where
+        T: Tag<D>,
+    {
+        self.profiler()
+            .on_tag(curr_index, ancestor_index, curr, ancestor)
+    }
+
+    fn on_text<T>(
+        &mut self,
+        curr_index: usize,
+        ancestor_index: usize,
+        curr: &mut T,
+        ancestor: &mut T,
+    ) -> Result<(), Self::Err>
+    where
+        T: Text<D>,
+    {
+        self.profiler()
+            .on_text(curr_index, ancestor_index, curr, ancestor)
+    }
+
+    fn on_comp<C>(
+        &mut self,
+        curr_index: &mut usize,
+        ancestor_index: &mut usize,
+        curr: &mut CompNode<D, C>,
+        ancestor: &mut CompNode<D, C>,
+    ) -> Result<(), Self::Err>
+    where
+        C: Comp<D>,
+    {
+        self.profiler()
+            .on_comp(curr_index, ancestor_index, curr, ancestor)
+    }
+
+    fn driver_ctx(&self) -> Option<DriverCtx<D>> {
+        self.differ.driver_ctx()
+    }
+}
+
+/// The differ of the children of the element at `parent`, or of the diffed
+/// list if that's the root.
+struct Profiler<'a, ND> {
+    differ: &'a mut ND,
+    parent: Path,
+    now: fn() -> f64,
+    entries: &'a mut Vec<ProfileEntry>,
+}
+
+impl<'a, ND> Profiler<'a, ND> {
+    /// Adds an entry for what's at `index`, timing `diff`.
+    fn time<F, R>(&mut self, index: usize, tag: String, diff: F) -> R
+    where
+        F: FnOnce(&mut ND, Path, &mut Vec<ProfileEntry>) -> R,
+    {
+        let path = self.parent.child(index);
+        let entry = self.entries.len();
+        self.entries.push(ProfileEntry {
+            path: path.clone(),
+            tag,
+            duration_ms: 0.0,
+        });
+        let start = (self.now)();
+        let result = diff(self.differ, path, self.entries);
+        self.entries[entry].duration_ms = (self.now)() - start;
+        result
+    }
+}
+
+impl<'a, D, ND> NodeDiffer<D> for Profiler<'a, ND>
+where
+    D: Driver,
+    ND: NodeDiffer<D>,
+{
+    type Err = ND::Err;
+
+    fn on_node_added<N>(&mut self, index: &mut usize, curr: &mut N) -> Result<(), Self::Err>
+    where
+        N: Node<D>,
+    {
+        self.differ.on_node_added(index, curr)
+    }
+
+    fn on_node_removed<N>(
+        &mut self,
+        curr_index: usize,
+        ancestor_index: &mut usize,
+        ancestor: &mut N,
+    ) -> Result<(), Self::Err>
+    where
+        N: Node<D>,
+    {
+        self.differ
+            .on_node_removed(curr_index, ancestor_index, ancestor)
+    }
+
+    fn on_node_moved(
+        &mut self,
+        from_index: usize,
+        to_index: usize,
+        key: &Key,
+    ) -> Result<(), Self::Err> {
+        self.differ.on_node_moved(from_index, to_index, key)
+    }
+
+    fn on_invariant_violation(
+        &mut self,
+        curr_index: usize,
+        expected: &str,
+        found: &str,
+    ) -> Result<(), Self::Err> {
+        self.differ
+            .on_invariant_violation(curr_index, expected, found)
+    }
+
+    fn on_tag<T>(
+        &mut self,
+        curr_index: usize,
+        ancestor_index: usize,
+        curr: &mut T,
+        ancestor: &mut T,
+    ) -> Result<(), Self::Err>
+    where
+        T: Tag<D>,
+    {
+        let now = self.now;
+        self.time(
+            curr_index,
+            curr.tag().to_string(),
+            |differ, path, entries| {
+                differ.on_tag(
+                    curr_index,
+                    ancestor_index,
+                    &mut ProfiledTag {
+                        tag: curr,
+                        children: Some((path, entries)),
+                        now,
+                    },
+                    &mut ProfiledTag {
+                        tag: ancestor,
+                        children: None,
+                        now,
+                    },
+                )
+            },
+        )
+    }
+
+    fn on_text<T>(
+        &mut self,
+        curr_index: usize,
+        ancestor_index: usize,
+        curr: &mut T,
+        ancestor: &mut T,
+    ) -> Result<(), Self::Err>
+    where
+        T: Text<D>,
+    {
+        self.differ
+            .on_text(curr_index, ancestor_index, curr, ancestor)
+    }
+
+    fn on_comp<C>(
+        &mut self,
+        curr_index: &mut usize,
+        ancestor_index: &mut usize,
+        curr: &mut CompNode<D, C>,
+        ancestor: &mut CompNode<D, C>,
+    ) -> Result<(), Self::Err>
+    where
+        C: Comp<D>,
+    {
+        let name = std::any::type_name::<C>().to_string();
+        self.time(*curr_index, name, |differ, _, _| {
+            differ.on_comp(curr_index, ancestor_index, curr, ancestor)
+        })
+    }
+
+    fn driver_ctx(&self) -> Option<DriverCtx<D>> {
+        self.differ.driver_ctx()
+    }
+}
+
+/// A tag as the wrapped differ sees it, which hands the differ of its
+/// children a `Profiler` around it. Only the current tag has `children`, as
+/// the ancestor's children aren't diffed on their own.
+struct ProfiledTag<'a, 'p, T> {
+    tag: &'a mut T,
+    children: Option<(Path, &'p mut Vec<ProfileEntry>)>,
+    now: fn() -> f64,
+}
+
+impl<'a, 'p, D, T> Tag<D> for ProfiledTag<'a, 'p, T>
+where
+    D: Driver,
+    T: Tag<D>,
+{
+    fn is_tag_static(&self) -> bool {
+        self.tag.is_tag_static()
+    }
+
+    fn tag(&self) -> &str {
+        self.tag.tag()
+    }
+
+    fn visit_children<NV>(&mut self, visitor: &mut NV) -> Result<(), NV::Err>
+    where
+        NV: NodeVisitor<D>,
+    {
+        self.tag.visit_children(visitor)
+    }
+
+    fn diff_children<ND>(&mut self, ancestor: &mut Self, differ: &mut ND) -> Result<(), ND::Err>
+    where
+        ND: NodeDiffer<D>,
+    {
+        match &mut self.children {
+            Some((path, entries)) => {
+                self.tag.diff_children(
+                    ancestor.tag,
+                    &mut Profiler {
+                        differ,
+                        parent: path.clone(),
+                        now: self.now,
+                        entries,
+                    },
+                )
+            }
+            None => self.tag.diff_children(ancestor.tag, differ),
+        }
+    }
+
+    fn visit_attrs<AV>(&mut self, visitor: &mut AV) -> Result<(), AV::Err>
+    where
+        AV: AttrVisitor<D>,
+    {
+        self.tag.visit_attrs(visitor)
+    }
+
+    fn diff_attrs<AD>(&mut self, ancestor: &mut Self, differ: &mut AD) -> Result<(), AD::Err>
+    where
+        AD: AttrDiffer<D>,
+    {
+        self.tag.diff_attrs(ancestor.tag, differ)
+    }
+
+    fn driver_store(&mut self) -> &mut D::TagStore {
+        self.tag.driver_store()
+    }
+
+    fn children_len(&mut self) -> Result<usize, Unmounted> {
+        self.tag.children_len()
+    }
+
+    fn skips_diff(&mut self) -> bool {
+        self.tag.skips_diff()
+    }
+
+    fn attr_value(&mut self, name: &str) -> Option<AttrValue> {
+        self.tag.attr_value(name)
+    }
+}
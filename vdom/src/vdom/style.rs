@@ -0,0 +1,484 @@
+use super::attr::AttrValue;
+use std::{
+    cell::RefCell,
+    collections::HashSet,
+    fmt::{self, Write},
+    rc::Rc,
+};
+
+/// CSS declarations for a `style` attribute, in the order they were set.
+///
+/// Properties and values are written with `;`, `{` and `}` escaped, so they
+/// can't end their declaration and add others. Within a string, as in
+/// `content: ";"`, the escape stands for the character itself.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StyleMap {
+    declarations: Vec<(String, String)>,
+}
+
+impl StyleMap {
+    pub fn new() -> StyleMap {
+        StyleMap::default()
+    }
+
+    /// Sets `property` to `value`, replacing an earlier value of it.
+    pub fn set<P, V>(mut self, property: P, value: V) -> StyleMap
+    where
+        P: Into<String>,
+        V: Into<String>,
+    {
+        let property = property.into();
+        let value = value.into();
+        match self.declarations.iter_mut().find(|(p, _)| *p == property) {
+            Some(declaration) => declaration.1 = value,
+            None => self.declarations.push((property, value)),
+        }
+        self
+    }
+
+    pub fn get(&self, property: &str) -> Option<&str> {
+        self.declarations
+            .iter()
+            .find(|(p, _)| p == property)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.declarations.is_empty()
+    }
+}
+
+impl fmt::Display for StyleMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (property, value)) in self.declarations.iter().enumerate() {
+            if i > 0 {
+                f.write_char(' ')?;
+            }
+            write_escaped(f, property)?;
+            f.write_str(": ")?;
+            write_escaped(f, value)?;
+            f.write_char(';')?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes `css` with a backslash before `;`, `{` and `}`. A backslash that
+/// escapes another character is kept as is, while one before those or at the
+/// end is escaped itself, so it can't escape the `;` written after the value.
+fn write_escaped(f: &mut fmt::Formatter<'_>, css: &str) -> fmt::Result {
+    let mut chars = css.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            ';' | '{' | '}' => {
+                f.write_char('\\')?;
+                f.write_char(c)?;
+            }
+            '\\' => {
+                match chars.clone().next() {
+                    Some(next) if next != ';' && next != '{' && next != '}' => {
+                        chars.next();
+                        f.write_char(c)?;
+                        f.write_char(next)?;
+                    }
+                    _ => f.write_str("\\\\")?,
+                }
+            }
+            _ => f.write_char(c)?,
+        }
+    }
+    Ok(())
+}
+
+impl From<StyleMap> for AttrValue {
+    fn from(style: StyleMap) -> AttrValue {
+        AttrValue::String(style.to_string())
+    }
+}
+
+/// The value of a `style` attribute that hides the element with
+/// `display: none;` unless `show`, e.g. to keep the state of a subtree while
+/// it's hidden, as removing it would drop the state. The declaration is
+/// appended to `style`, so it overrides a `display` set there.
+pub fn show_style<V>(style: V, show: bool) -> AttrValue
+where
+    V: Into<AttrValue>,
+{
+    let style = style.into();
+    if show {
+        return style;
+    }
+    let style = match &style {
+        AttrValue::Str(style) => style.trim_end(),
+        AttrValue::String(style) => style.trim_end(),
+        AttrValue::True | AttrValue::Null => "",
+    };
+    if style.is_empty() {
+        AttrValue::Str("display: none;")
+    } else if style.ends_with(';') {
+        AttrValue::String(format!("{} display: none;", style))
+    } else {
+        AttrValue::String(format!("{}; display: none;", style))
+    }
+}
+
+/// A `style` attribute made of typed values for common properties, so typos
+/// in names and values are caught at compile time. Other properties can be
+/// set with `custom`.
+///
+/// Lengths that aren't finite have no CSS form, so setting one is ignored.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TypedStyle {
+    map: StyleMap,
+}
+
+impl TypedStyle {
+    pub fn new() -> TypedStyle {
+        TypedStyle::default()
+    }
+
+    pub fn display(self, display: Display) -> TypedStyle {
+        self.custom("display", display)
+    }
+
+    pub fn position(self, position: Position) -> TypedStyle {
+        self.custom("position", position)
+    }
+
+    pub fn color(self, color: Color) -> TypedStyle {
+        self.custom("color", color)
+    }
+
+    pub fn background_color(self, color: Color) -> TypedStyle {
+        self.custom("background-color", color)
+    }
+
+    pub fn width(self, width: Length) -> TypedStyle {
+        self.length("width", width)
+    }
+
+    pub fn height(self, height: Length) -> TypedStyle {
+        self.length("height", height)
+    }
+
+    pub fn margin(self, margin: Length) -> TypedStyle {
+        self.length("margin", margin)
+    }
+
+    pub fn padding(self, padding: Length) -> TypedStyle {
+        self.length("padding", padding)
+    }
+
+    pub fn font_size(self, size: Length) -> TypedStyle {
+        self.length("font-size", size)
+    }
+
+    pub fn font_weight(self, weight: FontWeight) -> TypedStyle {
+        self.custom("font-weight", weight)
+    }
+
+    pub fn text_align(self, align: TextAlign) -> TypedStyle {
+        self.custom("text-align", align)
+    }
+
+    /// Sets a property that has no typed setter.
+    pub fn custom<V>(self, property: &str, value: V) -> TypedStyle
+    where
+        V: fmt::Display,
+    {
+        TypedStyle {
+            map: self.map.set(property, value.to_string()),
+        }
+    }
+
+    fn length(self, property: &str, length: Length) -> TypedStyle {
+        if length.is_finite() {
+            self.custom(property, length)
+        } else {
+            self
+        }
+    }
+
+    pub fn into_map(self) -> StyleMap {
+        self.map
+    }
+}
+
+impl fmt::Display for TypedStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.map.fmt(f)
+    }
+}
+
+impl From<TypedStyle> for AttrValue {
+    fn from(style: TypedStyle) -> AttrValue {
+        style.map.into()
+    }
+}
+
+macro_rules! keywords {
+    ($name:ident { $($variant:ident => $keyword:expr,)* }) => {
+        #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+        pub enum $name {
+            $($variant),*
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(match self {
+                    $($name::$variant => $keyword),*
+                })
+            }
+        }
+    };
+}
+
+keywords!(Display {
+    None => "none",
+    Block => "block",
+    Inline => "inline",
+    InlineBlock => "inline-block",
+    Flex => "flex",
+    InlineFlex => "inline-flex",
+    Grid => "grid",
+    Contents => "contents",
+});
+
+keywords!(Position {
+    Static => "static",
+    Relative => "relative",
+    Absolute => "absolute",
+    Fixed => "fixed",
+    Sticky => "sticky",
+});
+
+keywords!(TextAlign {
+    Left => "left",
+    Right => "right",
+    Center => "center",
+    Justify => "justify",
+    Start => "start",
+    End => "end",
+});
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FontWeight {
+    Normal,
+    Bold,
+    Lighter,
+    Bolder,
+    /// A numeric weight from 1 to 1000, e.g. 600.
+    Weight(u16),
+}
+
+impl fmt::Display for FontWeight {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FontWeight::Normal => f.write_str("normal"),
+            FontWeight::Bold => f.write_str("bold"),
+            FontWeight::Lighter => f.write_str("lighter"),
+            FontWeight::Bolder => f.write_str("bolder"),
+            FontWeight::Weight(weight) => write!(f, "{}", weight),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Length {
+    Zero,
+    Auto,
+    Px(f32),
+    Em(f32),
+    Rem(f32),
+    Percent(f32),
+    Vw(f32),
+    Vh(f32),
+}
+
+impl Length {
+    /// Whether the number of the length, if any, is neither infinite nor NaN.
+    pub fn is_finite(&self) -> bool {
+        match self {
+            Length::Zero | Length::Auto => true,
+            Length::Px(v)
+            | Length::Em(v)
+            | Length::Rem(v)
+            | Length::Percent(v)
+            | Length::Vw(v)
+            | Length::Vh(v) => v.is_finite(),
+        }
+    }
+}
+
+impl fmt::Display for Length {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Length::Zero => f.write_str("0"),
+            Length::Auto => f.write_str("auto"),
+            Length::Px(v) => write!(f, "{}px", v),
+            Length::Em(v) => write!(f, "{}em", v),
+            Length::Rem(v) => write!(f, "{}rem", v),
+            Length::Percent(v) => write!(f, "{}%", v),
+            Length::Vw(v) => write!(f, "{}vw", v),
+            Length::Vh(v) => write!(f, "{}vh", v),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Color {
+    Transparent,
+    CurrentColor,
+    Rgb(u8, u8, u8),
+    /// With an alpha from 0, transparent, to 255, opaque.
+    Rgba(u8, u8, u8, u8),
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Color::Transparent => f.write_str("transparent"),
+            Color::CurrentColor => f.write_str("currentcolor"),
+            Color::Rgb(r, g, b) => write!(f, "#{:02x}{:02x}{:02x}", r, g, b),
+            Color::Rgba(r, g, b, a) => write!(f, "#{:02x}{:02x}{:02x}{:02x}", r, g, b, a),
+        }
+    }
+}
+
+/// Collects the CSS of the `ScopedStyle`s visited or diffed while it's
+/// provided by a `ContextProvider`, with one rule per distinct snippet. Clones
+/// share the collected rules, so one can be provided while another is read
+/// after rendering.
+#[derive(Clone, Debug, Default)]
+pub struct StyleRegistry {
+    inner: Rc<RefCell<Registered>>,
+}
+
+#[derive(Debug, Default)]
+struct Registered {
+    snippets: HashSet<String>,
+    rules: Vec<String>,
+}
+
+impl StyleRegistry {
+    pub fn new() -> StyleRegistry {
+        StyleRegistry::default()
+    }
+
+    /// Adds the rules of `css` scoped to `scope_class(css)`, unless the same
+    /// snippet was registered before.
+    pub fn register(&self, css: &str) {
+        let mut inner = self.inner.borrow_mut();
+        if !inner.snippets.contains(css) {
+            inner.snippets.insert(css.to_string());
+            inner.rules.push(scoped_rules(css, &scope_class(css)));
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.borrow().rules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The rules registered so far, one per line.
+    pub fn css(&self) -> String {
+        self.inner.borrow().rules.join("\n")
+    }
+
+    /// The rules registered so far, in the order of registration.
+    pub fn rules(&self) -> Vec<String> {
+        self.inner.borrow().rules.clone()
+    }
+
+    /// A `<style>` element with the rules registered so far, or nothing if
+    /// there are none. The rules are written back to back, as the rendered
+    /// HTML has no whitespace of its own.
+    pub fn style_tag(&self) -> String {
+        if self.is_empty() {
+            return String::new();
+        }
+        let css = self.inner.borrow().rules.concat();
+        // `</` can't occur in CSS outside of strings, where `<\/` means the
+        // same, so this keeps the CSS from closing the element early.
+        format!("<style>{}</style>", css.replace("</", "<\\/"))
+    }
+}
+
+/// The class that `ScopedStyle` scopes `css` to, which is the same for the
+/// same snippet across renders, builds and platforms.
+pub fn scope_class(css: &str) -> String {
+    format!("vs-{:016x}", css_hash(css))
+}
+
+/// FNV-1a, whose output, unlike that of `DefaultHasher`, is specified.
+fn css_hash(css: &str) -> u64 {
+    css.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Scopes the rules of `css` to `class`, or the declarations if it has no
+/// rules. `&` stands for the element in selectors only, so one in a string,
+/// a comment or a declaration is kept as is.
+fn scoped_rules(css: &str, class: &str) -> String {
+    let css = css.trim();
+    let selector = format!(".{}", class);
+    let mut out = String::with_capacity(css.len());
+    // Where the current selector or declaration starts, and its `&`s.
+    let mut start = 0;
+    let mut ampersands = Vec::new();
+    let mut has_rules = false;
+    let mut quote = None;
+    let mut in_comment = false;
+    let mut chars = css.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if in_comment {
+            if c == '*' && chars.peek().map(|&(_, c)| c) == Some('/') {
+                chars.next();
+                in_comment = false;
+            }
+            continue;
+        }
+        if let Some(q) = quote {
+            if c == '\\' {
+                chars.next();
+            } else if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        match c {
+            '"' | '\'' => quote = Some(c),
+            '/' if chars.peek().map(|&(_, c)| c) == Some('*') => {
+                chars.next();
+                in_comment = true;
+            }
+            '&' => ampersands.push(i),
+            '{' => {
+                has_rules = true;
+                for &ampersand in &ampersands {
+                    out.push_str(&css[start..ampersand]);
+                    out.push_str(&selector);
+                    start = ampersand + 1;
+                }
+                out.push_str(&css[start..=i]);
+                ampersands.clear();
+                start = i + 1;
+            }
+            ';' | '}' => {
+                out.push_str(&css[start..=i]);
+                ampersands.clear();
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if !has_rules {
+        return format!("{} {{ {} }}", selector, css);
+    }
+    out.push_str(&css[start..]);
+    out
+}
@@ -0,0 +1,209 @@
+//! Combines two differs so that both observe a single diff, e.g. to apply
+//! the changes to the DOM while recording them as patches.
+
+use super::{
+    attr::{Attr, AttrDiffer},
+    key::Key,
+    node::{Comp, CompNode, Node, NodeDiffer, Tag, Text},
+};
+use crate::driver::{Driver, DriverCtx};
+
+/// Forwards every call to `first`, then to `second`. Each gets the same
+/// nodes, so a differ that moves driver stores from the ancestor to the
+/// current nodes, as those of drivers do, has to be `first`. The calls for
+/// the children of a tag or component are made by the differs themselves,
+/// each to its own differ for the children.
+///
+/// Indices passed by reference are advanced by `first`, while `second`
+/// advances a copy of them.
+pub struct TeeDiffer<A, B> {
+    pub first: A,
+    pub second: B,
+}
+
+impl<A, B> TeeDiffer<A, B> {
+    pub fn new(first: A, second: B) -> TeeDiffer<A, B> {
+        TeeDiffer { first, second }
+    }
+
+    pub fn into_inner(self) -> (A, B) {
+        (self.first, self.second)
+    }
+}
+
+/// An error of either differ of a `TeeDiffer`. An error of `first` stops the
+/// call before it reaches `second`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TeeError<A, B> {
+    First(A),
+    Second(B),
+}
+
+impl<D, A, B> NodeDiffer<D> for TeeDiffer<A, B>
+where
+    D: Driver,
+    A: NodeDiffer<D>,
+    B: NodeDiffer<D>,
+{
+    type Err = TeeError<A::Err, B::Err>;
+
+    fn on_node_added<N>(&mut self, index: &mut usize, curr: &mut N) -> Result<(), Self::Err>
+    where
+        N: Node<D>,
+    {
+        let mut second_index = *index;
+        self.first
+            .on_node_added(index, curr)
+            .map_err(TeeError::First)?;
+        self.second
+            .on_node_added(&mut second_index, curr)
+            .map_err(TeeError::Second)
+    }
+
+    fn on_node_removed<N>(
+        &mut self,
+        curr_index: usize,
+        ancestor_index: &mut usize,
+        ancestor: &mut N,
+    ) -> Result<(), Self::Err>
+    where
+        N: Node<D>,
+    {
+        let mut second_index = *ancestor_index;
+        self.first
+            .on_node_removed(curr_index, ancestor_index, ancestor)
+            .map_err(TeeError::First)?;
+        self.second
+            .on_node_removed(curr_index, &mut second_index, ancestor)
+            .map_err(TeeError::Second)
+    }
+
+    fn on_node_moved(
+        &mut self,
+        from_index: usize,
+        to_index: usize,
+        key: &Key,
+    ) -> Result<(), Self::Err> {
+        self.first
+            .on_node_moved(from_index, to_index, key)
+            .map_err(TeeError::First)?;
+        self.second
+            .on_node_moved(from_index, to_index, key)
+            .map_err(TeeError::Second)
+    }
+
+    fn on_invariant_violation(
+        &mut self,
+        curr_index: usize,
+        expected: &str,
+        found: &str,
+    ) -> Result<(), Self::Err> {
+        self.first
+            .on_invariant_violation(curr_index, expected, found)
+            .map_err(TeeError::First)?;
+        self.second
+            .on_invariant_violation(curr_index, expected, found)
+            .map_err(TeeError::Second)
+    }
+
+    fn on_tag<T>(
+        &mut self,
+        curr_index: usize,
+        ancestor_index: usize,
+        curr: &mut T,
+        ancestor: &mut T,
+    ) -> Result<(), Self::Err>
+    where
+        T: Tag<D>,
+    {
+        self.first
+            .on_tag(curr_index, ancestor_index, curr, ancestor)
+            .map_err(TeeError::First)?;
+        self.second
+            .on_tag(curr_index, ancestor_index, curr, ancestor)
+            .map_err(TeeError::Second)
+    }
+
+    fn on_text<T>(
+        &mut self,
+        curr_index: usize,
+        ancestor_index: usize,
+        curr: &mut T,
+        ancestor: &mut T,
+    ) -> Result<(), Self::Err>
+    where
+        T: Text<D>,
+    {
+        self.first
+            .on_text(curr_index, ancestor_index, curr, ancestor)
+            .map_err(TeeError::First)?;
+        self.second
+            .on_text(curr_index, ancestor_index, curr, ancestor)
+            .map_err(TeeError::Second)
+    }
+
+    fn on_comp<C>(
+        &mut self,
+        curr_index: &mut usize,
+        ancestor_index: &mut usize,
+        curr: &mut CompNode<D, C>,
+        ancestor: &mut CompNode<D, C>,
+    ) -> Result<(), Self::Err>
+    where
+        C: Comp<D>,
+    {
+        let (mut second_curr_index, mut second_ancestor_index) = (*curr_index, *ancestor_index);
+        self.first
+            .on_comp(curr_index, ancestor_index, curr, ancestor)
+            .map_err(TeeError::First)?;
+        self.second
+            .on_comp(
+                &mut second_curr_index,
+                &mut second_ancestor_index,
+                curr,
+                ancestor,
+            )
+            .map_err(TeeError::Second)
+    }
+
+    fn driver_ctx(&self) -> Option<DriverCtx<D>> {
+        self.first.driver_ctx().or_else(|| self.second.driver_ctx())
+    }
+}
+
+impl<D, A, B> AttrDiffer<D> for TeeDiffer<A, B>
+where
+    D: Driver,
+    A: AttrDiffer<D>,
+    B: AttrDiffer<D>,
+{
+    type Err = TeeError<A::Err, B::Err>;
+
+    fn on_diff<T>(&mut self, curr: &mut T, ancestor: &mut T) -> Result<(), Self::Err>
+    where
+        T: Attr<D>,
+    {
+        self.first
+            .on_diff(curr, ancestor)
+            .map_err(TeeError::First)?;
+        self.second
+            .on_diff(curr, ancestor)
+            .map_err(TeeError::Second)
+    }
+
+    fn on_added<T>(&mut self, curr: &mut T) -> Result<(), Self::Err>
+    where
+        T: Attr<D>,
+    {
+        self.first.on_added(curr).map_err(TeeError::First)?;
+        self.second.on_added(curr).map_err(TeeError::Second)
+    }
+
+    fn on_removed<T>(&mut self, ancestor: &mut T) -> Result<(), Self::Err>
+    where
+        T: Attr<D>,
+    {
+        self.first.on_removed(ancestor).map_err(TeeError::First)?;
+        self.second.on_removed(ancestor).map_err(TeeError::Second)
+    }
+}
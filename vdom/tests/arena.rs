@@ -0,0 +1,168 @@
+use vdom::{
+    driver::DriverCtx,
+    html::{render_to_string, HtmlDriver},
+    vdom::{
+        arena::{Frame, FrameLoop, FrameView, TextRef},
+        attr::AttrListEntry,
+        dom::DomNode,
+        elements::{td, tr},
+        node::TagStatic,
+        patch::{self, Patch},
+    },
+};
+
+type Row<'a> = TagStatic<
+    HtmlDriver,
+    (TextRef<'a, HtmlDriver>, TextRef<'a, HtmlDriver>),
+    AttrListEntry<vdom::vdom::arena::AttrRef<'a, HtmlDriver>>,
+>;
+
+fn row<'a>(frame: &'a Frame, id: usize, label: &str) -> Row<'a> {
+    TagStatic::new(
+        "tr",
+        AttrListEntry(frame.attr("data-id", &id)),
+        (frame.text(&id), frame.text(label)),
+    )
+}
+
+struct Table {
+    labels: Vec<String>,
+    /// The capacity of the frame at the start of each render.
+    capacities: Vec<usize>,
+}
+
+impl<'a> FrameView<'a, HtmlDriver> for Table {
+    type Node = TagStatic<HtmlDriver, Vec<Row<'a>>, ()>;
+
+    fn view(&mut self, frame: &'a Frame) -> Self::Node {
+        self.capacities.push(frame.capacity());
+        let rows = self
+            .labels
+            .iter()
+            .enumerate()
+            .map(|(id, label)| row(frame, id, label))
+            .collect();
+        TagStatic::new("table", (), rows)
+    }
+}
+
+fn table(len: usize) -> Table {
+    Table {
+        labels: (0..len).map(|i| format!("row {}", i)).collect(),
+        capacities: Vec::new(),
+    }
+}
+
+#[test]
+fn frame_strings_stay_in_place_as_it_grows() {
+    let frame = Frame::new();
+    let strings = (0..10_000).map(|i| frame.display(&i)).collect::<Vec<_>>();
+    for (i, s) in strings.iter().enumerate() {
+        assert_eq!(*s, i.to_string());
+    }
+    assert_eq!(frame.alloc_str(""), "");
+    assert_eq!(frame.fmt(format_args!("{}-{}", 1, "a")), "1-a");
+}
+
+#[test]
+fn reset_keeps_the_memory() {
+    let mut frame = Frame::new();
+    for i in 0..10_000 {
+        frame.display(&i);
+    }
+    let capacity = frame.capacity();
+    frame.reset();
+    assert_eq!(frame.capacity(), capacity);
+    for i in 0..10_000 {
+        frame.display(&i);
+    }
+    assert_eq!(frame.capacity(), capacity);
+}
+
+#[test]
+fn builder_nodes_render() {
+    let frame = Frame::new();
+    let mut node = tr((), (td((), frame.text(&1)), td((), frame.text("a&b"))));
+    assert_eq!(
+        render_to_string(&mut node),
+        "<tr><td>1</td><td>a&amp;b</td></tr>"
+    );
+    let mut node = row(&frame, 2, "x");
+    assert_eq!(render_to_string(&mut node), "<tr data-id=\"2\">2x</tr>");
+}
+
+#[test]
+fn frame_loop_patches_a_dom() {
+    let driver_ctx = DriverCtx::new(HtmlDriver);
+    let mut frames = FrameLoop::new(table(3));
+    let mut dom: Vec<DomNode<HtmlDriver>> = Vec::new();
+
+    patch::apply(&mut dom, frames.render(&driver_ctx)).unwrap();
+    assert_eq!(
+        render_to_string(&mut dom),
+        "<table><tr data-id=\"0\">0row 0</tr><tr data-id=\"1\">1row 1</tr>\
+         <tr data-id=\"2\">2row 2</tr></table>"
+    );
+
+    // Unchanged trees diff to nothing, though their text lives in another
+    // frame.
+    assert!(frames.render(&driver_ctx).is_empty());
+
+    frames.view().labels[1] = "changed".to_string();
+    frames.view().labels.pop();
+    let patches = frames.render(&driver_ctx);
+    assert!(patches.iter().any(|patch| sets_text(patch, "changed")));
+    patch::apply(&mut dom, patches).unwrap();
+    assert_eq!(
+        render_to_string(&mut dom),
+        "<table><tr data-id=\"0\">0row 0</tr><tr data-id=\"1\">1changed</tr></table>"
+    );
+
+    // Back and forth, so each frame is reused after being reset.
+    for i in 0..10 {
+        frames.view().labels[0] = format!("round {}", i);
+        let patches = frames.render(&driver_ctx);
+        patch::apply(&mut dom, patches).unwrap();
+    }
+    assert_eq!(
+        render_to_string(&mut dom),
+        "<table><tr data-id=\"0\">0round 9</tr><tr data-id=\"1\">1changed</tr></table>"
+    );
+}
+
+fn sets_text(patch: &Patch<HtmlDriver>, expected: &str) -> bool {
+    match patch {
+        Patch::SetText { text, .. } => text == expected,
+        Patch::SpliceText { insert, .. } => expected.ends_with(insert.as_str()),
+        _ => false,
+    }
+}
+
+#[test]
+fn frame_loop_survives_being_moved() {
+    let driver_ctx = DriverCtx::new(HtmlDriver);
+    let mut frames = FrameLoop::new(table(2));
+    frames.render(&driver_ctx);
+    let mut moved = [frames];
+    moved[0].view().labels[0] = "moved".to_string();
+    assert!(!moved[0].render(&driver_ctx).is_empty());
+    assert!(moved[0].render(&driver_ctx).is_empty());
+}
+
+#[test]
+fn frames_stop_growing() {
+    let driver_ctx = DriverCtx::new(HtmlDriver);
+    let mut frames = FrameLoop::new(table(1_000));
+    for i in 0..6 {
+        frames.view().labels[i] = format!("{} updated", i);
+        frames.render(&driver_ctx);
+    }
+    // Each frame grows into chunks on its first render, which are merged
+    // when it's reset, after which it's large enough.
+    let capacities = &frames.view().capacities;
+    assert!(capacities[3] > 0);
+    assert_eq!(
+        capacities[2..],
+        [capacities[2], capacities[3]].repeat(2)[..]
+    );
+}
@@ -0,0 +1,104 @@
+use std::{borrow::Cow, collections::BTreeMap, iter::FromIterator};
+use vdom::{
+    driver::DriverCtx,
+    html::{render_to_string, HtmlDriver},
+    vdom::{
+        attr::{AttrListEntry, ClassList, DynAttrs, TokenDiff, TokenList},
+        dom::to_dom,
+        node::TagStatic,
+        patch::{self, Patch},
+    },
+};
+
+type Div = TagStatic<HtmlDriver, (), DynAttrs<HtmlDriver>>;
+
+fn div(attrs: &BTreeMap<&'static str, &'static str>) -> Div {
+    TagStatic::new("div", DynAttrs::from_iter(attrs.clone()), ())
+}
+
+#[test]
+fn dyn_attrs_are_diffed_by_name() {
+    let driver_ctx = DriverCtx::new(HtmlDriver);
+    let mut attrs = BTreeMap::new();
+    attrs.insert("id", "a");
+    attrs.insert("lang", "en");
+    attrs.insert("title", "t");
+    let mut ancestor = div(&attrs);
+    assert_eq!(
+        render_to_string(&mut ancestor),
+        "<div id=\"a\" lang=\"en\" title=\"t\"></div>"
+    );
+
+    attrs.remove("lang");
+    attrs.insert("id", "b");
+    attrs.insert("role", "x");
+    let mut curr = div(&attrs);
+    let mut dom = to_dom(&driver_ctx, &mut ancestor);
+    let patches = patch::diff(&driver_ctx, &mut curr, &mut ancestor);
+    assert_eq!(patches.len(), 3);
+    assert!(patches.iter().all(|patch| {
+        match patch {
+            Patch::SetAttr { .. } | Patch::RemoveAttr { .. } => true,
+            _ => false,
+        }
+    }));
+    patch::apply(&mut dom, patches).unwrap();
+    // New attributes are appended to the live ones.
+    assert_eq!(
+        render_to_string(&mut dom),
+        "<div id=\"b\" title=\"t\" role=\"x\"></div>"
+    );
+}
+
+#[test]
+fn repeated_names_take_the_last_value_and_are_listed() {
+    let attrs: DynAttrs<HtmlDriver> = DynAttrs::new()
+        .set("a", "1")
+        .set("b", "2")
+        .set("a", "3")
+        .set("a", "4");
+    assert_eq!(format!("{:?}", attrs), "[a=\"4\", b=\"2\"]");
+    assert_eq!(attrs.duplicates(), [Cow::Borrowed("a")]);
+    assert!(DynAttrs::<HtmlDriver>::from_iter(vec![("a", "1")])
+        .duplicates()
+        .is_empty());
+}
+
+#[test]
+fn token_lists_skip_repeated_tokens() {
+    let rel = TokenList::<HtmlDriver>::new("rel")
+        .with("noopener")
+        .with("noreferrer")
+        .with("noopener");
+    assert_eq!(rel.tokens(), ["noopener", "noreferrer"]);
+    let mut a = TagStatic::<HtmlDriver, (), _>::new("a", AttrListEntry(rel), ());
+    assert_eq!(
+        render_to_string(&mut a),
+        "<a rel=\"noopener noreferrer\"></a>"
+    );
+}
+
+#[test]
+fn tokens_are_split_at_whitespace() {
+    let class = ClassList::<HtmlDriver>::new()
+        .with(" a\tb ")
+        .with(String::from("b  c"))
+        .with("")
+        .with(" ");
+    assert_eq!(class.tokens(), ["a", "b", "c"]);
+    assert_eq!(format!("{:?}", class), "class=\"a b c\"");
+    let empty = ClassList::<HtmlDriver>::new().with_if(false, "x");
+    let mut div = TagStatic::<HtmlDriver, (), _>::new("div", AttrListEntry(empty), ());
+    assert_eq!(render_to_string(&mut div), "<div></div>");
+}
+
+#[test]
+fn token_diffs_ignore_the_order() {
+    let a = ClassList::<HtmlDriver>::new().with("a b c");
+    let b = ClassList::<HtmlDriver>::new().with("c d a");
+    let diff = TokenDiff::new(b.tokens(), a.tokens());
+    assert_eq!(diff.added, ["d"]);
+    assert_eq!(diff.removed, ["b"]);
+    let c = ClassList::<HtmlDriver>::new().with("c b a");
+    assert!(TokenDiff::new(c.tokens(), a.tokens()).is_empty());
+}
@@ -0,0 +1,135 @@
+use std::cell::RefCell;
+use vdom::{
+    driver::DriverCtx,
+    html::{render_to_string, HtmlDriver},
+    vdom::{
+        dom::to_dom,
+        node::{
+            Boundary, Comp, CompCtx, CompNode, ErrorBoundary, Node, TagStatic, TextDyn, TextStatic,
+        },
+        patch::{diff, Patch},
+    },
+};
+
+/// Renders its first item, or panics without any as if indexing bad data.
+#[derive(Clone, PartialEq, Eq)]
+struct Item {
+    items: Vec<&'static str>,
+}
+
+thread_local! {
+    static ITEM: RefCell<Option<CompCtx<HtmlDriver, Item>>> = RefCell::new(None);
+}
+
+impl Comp<HtmlDriver> for Item {
+    type Input = Vec<&'static str>;
+    type Rendered = TextStatic<HtmlDriver>;
+
+    fn new(input: &Vec<&'static str>, ctx: CompCtx<HtmlDriver, Item>) -> Item {
+        ITEM.with(|item| *item.borrow_mut() = Some(ctx));
+        Item {
+            items: input.clone(),
+        }
+    }
+
+    fn render(&self, _input: &Vec<&'static str>) -> TextStatic<HtmlDriver> {
+        TextStatic::new(self.items[0])
+    }
+}
+
+fn set_items(items: Vec<&'static str>) {
+    ITEM.with(|item| {
+        let item = item.borrow();
+        item.as_ref()
+            .unwrap()
+            .with_instance_mut(|instance| instance.comp.items = items)
+            .unwrap()
+    })
+}
+
+fn page(boundary: &Boundary, item: Vec<&'static str>) -> impl Node<HtmlDriver> {
+    TagStatic::new(
+        "div",
+        (),
+        (
+            (
+                TagStatic::new("p", (), TextStatic::new("before")),
+                ErrorBoundary::new(
+                    boundary,
+                    || TagStatic::new("b", (), CompNode::<HtmlDriver, Item>::new(item)),
+                    |failure| TextDyn::new(format!("failed: {}", failure.message.is_some())),
+                ),
+            ),
+            TagStatic::new("p", (), TextStatic::new("after")),
+        ),
+    )
+}
+
+#[test]
+fn siblings_render_when_a_component_panics() {
+    let boundary = Boundary::new();
+    assert_eq!(
+        render_to_string(&mut page(&boundary, vec![])),
+        "<div><p>before</p>failed: true<p>after</p></div>"
+    );
+    assert_eq!(boundary.take_failures().len(), 1);
+    assert_eq!(
+        render_to_string(&mut page(&boundary, vec!["ok"])),
+        "<div><p>before</p><b>ok</b><p>after</p></div>"
+    );
+    assert!(boundary.take_failures().is_empty());
+}
+
+#[test]
+fn build_panics_are_caught() {
+    let boundary = Boundary::new();
+    let mut node = ErrorBoundary::new(
+        &boundary,
+        || -> TextStatic<HtmlDriver> { panic!("bad data") },
+        |failure| TextDyn::new(failure.message.clone().unwrap()),
+    );
+    assert!(node.is_failed());
+    assert_eq!(render_to_string(&mut node), "bad data");
+}
+
+#[test]
+fn diff_replaces_the_subtree_when_a_component_panics() {
+    let ctx = DriverCtx::new(HtmlDriver);
+    let boundary = Boundary::new();
+    let mut ancestor = page(&boundary, vec!["ok"]);
+    to_dom(&ctx, &mut ancestor);
+    set_items(vec![]);
+    let mut curr = page(&boundary, vec!["ok"]);
+    let patches = diff(&ctx, &mut curr, &mut ancestor);
+    match patches.as_slice() {
+        [Patch::RemoveNode { path: removed }, Patch::InsertNode { path: inserted, .. }] => {
+            assert_eq!(removed.indices(), &[0, 1]);
+            assert_eq!(inserted.indices(), &[0, 1]);
+        }
+        _ => panic!("expected a replace: {:?}", patches),
+    }
+    assert_eq!(boundary.take_failures().len(), 1);
+
+    // The subtree comes back on the next render, with a new component.
+    let mut fixed = page(&boundary, vec!["ok"]);
+    assert_eq!(diff(&ctx, &mut fixed, &mut curr).len(), 2);
+    assert!(boundary.take_failures().is_empty());
+}
+
+#[test]
+fn latched_fallback_can_use_the_boundary() {
+    let boundary = Boundary::latching();
+    render_to_string(&mut page(&boundary, vec![]));
+    let latched = boundary.clone();
+    let mut node = ErrorBoundary::new(
+        &boundary,
+        || TextStatic::<HtmlDriver>::new("ok"),
+        move |_failure| TextDyn::new(latched.take_failures().len().to_string()),
+    );
+    assert_eq!(render_to_string(&mut node), "1");
+    boundary.reset();
+    assert_eq!(
+        render_to_string(&mut page(&boundary, vec!["ok"])),
+        "<div><p>before</p><b>ok</b><p>after</p></div>"
+    );
+}
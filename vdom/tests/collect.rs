@@ -0,0 +1,225 @@
+use vdom::{
+    driver::DriverCtx,
+    html::HtmlDriver,
+    vdom::{
+        attr::{AttrDyn, AttrListEntry, AttrListener, AttrNs, AttrStr},
+        collect::{
+            fold, AttrNameCollector, DuplicateId, FoldNode, IdUniquenessValidator, LiveRegion,
+            LiveRegionCollector, Politeness,
+        },
+        dom::DomNode,
+        node::{Comp, CompCtx, CompNode, Node, TagStatic, TextDyn, TextStatic},
+        path::Path,
+    },
+};
+
+fn status(live: &'static str, count: usize) -> impl Node<HtmlDriver> {
+    TagStatic::new(
+        "main",
+        (),
+        (
+            TagStatic::new("p", (), TextStatic::new("Not announced")),
+            TagStatic::new(
+                "div",
+                AttrListEntry(AttrStr::new("aria-live", live)),
+                (
+                    TextStatic::new("  Saved "),
+                    TagStatic::new("b", (), TextDyn::new(format!("{} items\n", count))),
+                ),
+            ),
+        ),
+    )
+}
+
+fn announced<N>(driver_ctx: &DriverCtx<HtmlDriver>, node: &mut N) -> Vec<LiveRegion>
+where
+    N: Node<HtmlDriver>,
+{
+    let mut collector = LiveRegionCollector::new(driver_ctx);
+    node.visit(&mut 0, &mut collector).unwrap();
+    collector.regions().to_vec()
+}
+
+#[test]
+fn polite_regions_announce_their_text() {
+    let driver_ctx = DriverCtx::new(HtmlDriver);
+    assert_eq!(
+        announced(&driver_ctx, &mut status("polite", 3)),
+        [LiveRegion {
+            politeness: Politeness::Polite,
+            text: "Saved 3 items".to_string(),
+        }]
+    );
+    // After an update, the region announces the new text.
+    assert_eq!(
+        announced(&driver_ctx, &mut status("polite", 4))[0].text,
+        "Saved 4 items"
+    );
+    assert!(announced(&driver_ctx, &mut status("off", 3)).is_empty());
+}
+
+#[test]
+fn folding_counts_links() {
+    let driver_ctx = DriverCtx::new(HtmlDriver);
+    let link = |text: &'static str| DomNode::tag("a", vec![], vec![DomNode::text(text)]);
+    let mut nodes: Vec<DomNode<HtmlDriver>> = vec![
+        link("Home"),
+        DomNode::tag(
+            "nav",
+            vec![],
+            vec![
+                link("About"),
+                DomNode::tag("p", vec![], vec![DomNode::text("a"), link("Blog")]),
+            ],
+        ),
+    ];
+    let links = fold(&driver_ctx, &mut nodes, 0, |count, node| {
+        match node {
+            FoldNode::Tag { tag: "a", .. } => count + 1,
+            _ => count,
+        }
+    });
+    assert_eq!(links, 3);
+
+    // Nodes come in document order, with their paths.
+    let paths = fold(&driver_ctx, &mut nodes, Vec::new(), |mut paths, node| {
+        if let FoldNode::Tag { path, tag: "a" } = node {
+            paths.push(path.to_string());
+        }
+        paths
+    });
+    assert_eq!(paths, ["/0", "/1/0", "/1/1/1"]);
+}
+
+#[test]
+fn attr_names_are_collected_once() {
+    let driver_ctx = DriverCtx::new(HtmlDriver);
+    let mut node = TagStatic::<HtmlDriver, _, _>::new(
+        "main",
+        (
+            AttrListEntry(AttrStr::new("class", "page  dark")),
+            (
+                // Neither listeners nor null values end up in the DOM.
+                AttrListEntry(AttrListener::new("click", |_| {})),
+                AttrListEntry(AttrDyn::new("title", None::<&'static str>)),
+            ),
+        ),
+        (
+            TagStatic::new(
+                "a",
+                (
+                    AttrListEntry(AttrStr::new("class", "link dark")),
+                    AttrListEntry(AttrStr::new("href", "#top").ns(AttrNs::XLINK)),
+                ),
+                (),
+            ),
+            TagStatic::new("p", AttrListEntry(AttrStr::new("id", "intro")), ()),
+        ),
+    );
+
+    let mut collector = AttrNameCollector::new(&driver_ctx);
+    node.visit(&mut 0, &mut collector).unwrap();
+    assert_eq!(collector.names(), ["class", "id", "xlink:href"]);
+    assert!(collector.class_names().is_empty());
+
+    let mut collector = AttrNameCollector::new(&driver_ctx).with_class_names();
+    node.visit(&mut 0, &mut collector).unwrap();
+    assert_eq!(collector.class_names(), ["dark", "link", "page"]);
+}
+
+type Intro = TagStatic<HtmlDriver, TextStatic<HtmlDriver>, AttrListEntry<AttrStr<HtmlDriver>>>;
+
+fn intro() -> Intro {
+    TagStatic::new(
+        "p",
+        AttrListEntry(AttrStr::new("id", "intro")),
+        TextStatic::new("Hello"),
+    )
+}
+
+/// Renders a paragraph with the `id` `intro`.
+#[derive(Clone, PartialEq, Eq)]
+struct IntroComp;
+
+impl Comp<HtmlDriver> for IntroComp {
+    type Input = ();
+    type Rendered = Intro;
+
+    fn new(_input: &(), _ctx: CompCtx<HtmlDriver, IntroComp>) -> IntroComp {
+        IntroComp
+    }
+
+    fn render(&self, _input: &()) -> Intro {
+        intro()
+    }
+}
+
+fn duplicates<N>(driver_ctx: &DriverCtx<HtmlDriver>, node: &mut N) -> Vec<DuplicateId>
+where
+    N: Node<HtmlDriver>,
+{
+    let mut validator = IdUniquenessValidator::new(driver_ctx);
+    node.visit(&mut 0, &mut validator).unwrap();
+    validator.duplicates()
+}
+
+fn path(s: &str) -> Path {
+    s.parse().unwrap()
+}
+
+#[test]
+fn unique_ids_are_valid() {
+    let driver_ctx = DriverCtx::new(HtmlDriver);
+    let mut node = (
+        intro(),
+        TagStatic::<HtmlDriver, _, _>::new("p", AttrListEntry(AttrStr::new("id", "outro")), ()),
+    );
+    assert!(duplicates(&driver_ctx, &mut node).is_empty());
+}
+
+#[test]
+fn duplicate_ids_are_found_with_their_paths() {
+    let driver_ctx = DriverCtx::new(HtmlDriver);
+    let mut node = TagStatic::<HtmlDriver, _, ()>::new(
+        "main",
+        (),
+        (
+            intro(),
+            TagStatic::new("section", (), (TextStatic::new("a"), intro())),
+        ),
+    );
+    assert_eq!(
+        duplicates(&driver_ctx, &mut node),
+        [DuplicateId {
+            id: "intro".to_string(),
+            paths: vec![path("/0/0"), path("/0/1/1")],
+        }]
+    );
+}
+
+#[test]
+fn duplicate_ids_are_found_within_components() {
+    let driver_ctx = DriverCtx::new(HtmlDriver);
+    let mut node = TagStatic::<HtmlDriver, _, ()>::new(
+        "main",
+        (),
+        (
+            intro(),
+            TagStatic::new(
+                "section",
+                (),
+                (
+                    TextStatic::new("a"),
+                    CompNode::<HtmlDriver, IntroComp>::new(()),
+                ),
+            ),
+        ),
+    );
+    assert_eq!(
+        duplicates(&driver_ctx, &mut node),
+        [DuplicateId {
+            id: "intro".to_string(),
+            paths: vec![path("/0/0"), path("/0/1/1")],
+        }]
+    );
+}
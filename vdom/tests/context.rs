@@ -0,0 +1,73 @@
+use vdom::{
+    driver::DriverCtx,
+    html::{render_to_string, HtmlDriver},
+    vdom::{
+        node::{use_context, Comp, CompCtx, CompNode, ContextProvider, TextDyn},
+        patch::diff,
+    },
+};
+
+struct Theme(&'static str);
+
+/// Renders the name of the theme provided to it, or `none`.
+#[derive(Clone, PartialEq, Eq)]
+struct Themed;
+
+impl Comp<HtmlDriver> for Themed {
+    type Input = ();
+    type Rendered = TextDyn<HtmlDriver>;
+
+    fn new(_input: &(), _ctx: CompCtx<HtmlDriver, Themed>) -> Themed {
+        Themed
+    }
+
+    fn render(&self, _input: &()) -> Self::Rendered {
+        let theme = use_context::<Theme>();
+        TextDyn::new(theme.map_or("none", |theme| theme.0))
+    }
+}
+
+fn themed() -> CompNode<HtmlDriver, Themed> {
+    CompNode::new(())
+}
+
+#[test]
+fn provided_values_reach_components() {
+    let mut node = ContextProvider::new(Theme("dark"), themed());
+    assert_eq!(render_to_string(&mut node), "dark");
+}
+
+#[test]
+fn values_are_missing_without_a_provider() {
+    assert!(use_context::<Theme>().is_none());
+    assert_eq!(render_to_string(&mut themed()), "none");
+
+    // The value is only provided while the provider's subtree is rendered.
+    let mut node = (ContextProvider::new(Theme("dark"), themed()), themed());
+    assert_eq!(render_to_string(&mut node), "darknone");
+    assert!(use_context::<Theme>().is_none());
+}
+
+#[test]
+fn nested_providers_shadow_outer_ones() {
+    let mk = || {
+        ContextProvider::new(
+            Theme("dark"),
+            (
+                (themed(), ContextProvider::new(Theme("light"), themed())),
+                (
+                    themed(),
+                    // Values of other types don't shadow it.
+                    ContextProvider::new(0u32, themed()),
+                ),
+            ),
+        )
+    };
+    let mut node = mk();
+    assert_eq!(render_to_string(&mut node), "darklightdarkdark");
+
+    // Diffing provides the values too.
+    let mut next = mk();
+    assert!(diff(&DriverCtx::new(HtmlDriver), &mut next, &mut node).is_empty());
+    assert_eq!(render_to_string(&mut next), "darklightdarkdark");
+}
@@ -0,0 +1,134 @@
+use vdom::{
+    driver::DriverCtx,
+    html::HtmlDriver,
+    vdom::{
+        attr::{AttrDyn, AttrListEntry, AttrTrue},
+        debug::DebugHistory,
+        dom::to_dom,
+        node::{Comp, CompCtx, CompNode, TagDyn, TagStatic, TextDyn, TextStatic},
+        patch,
+    },
+};
+
+#[derive(Clone, PartialEq, Eq)]
+struct Greeting;
+
+impl Comp<HtmlDriver> for Greeting {
+    type Input = ();
+    type Rendered = TextStatic<HtmlDriver>;
+
+    fn new(_input: &(), _ctx: CompCtx<HtmlDriver, Greeting>) -> Greeting {
+        Greeting
+    }
+
+    fn render(&self, _input: &()) -> Self::Rendered {
+        TextStatic::new("hi")
+    }
+}
+
+type List = TagStatic<
+    HtmlDriver,
+    Vec<TagDyn<HtmlDriver, TextDyn<HtmlDriver>, ()>>,
+    AttrListEntry<AttrDyn<HtmlDriver>>,
+>;
+
+fn list(items: &[&'static str]) -> List {
+    TagStatic::new(
+        "ul",
+        AttrListEntry(AttrDyn::new("class", "x")),
+        items
+            .iter()
+            .map(|&item| TagDyn::new("li", (), TextDyn::new(item)))
+            .collect(),
+    )
+}
+
+#[test]
+fn tags_are_abbreviated_to_their_start_tag() {
+    assert_eq!(format!("{:?}", list(&["a", "b"])), "<ul class=\"x\"> (..)");
+    let input = TagStatic::<HtmlDriver, (), _>::new(
+        "input",
+        (
+            AttrListEntry(AttrTrue::new("disabled")),
+            AttrListEntry(AttrDyn::new("value", "v")),
+        ),
+        (),
+    );
+    assert_eq!(format!("{:?}", input), "<input disabled value=\"v\"> (..)");
+}
+
+#[test]
+fn tags_are_written_out_in_full_when_alternate() {
+    assert_eq!(
+        format!("{:#?}", list(&["a"])),
+        "<ul class=\"x\"> [\n    <li> \"a\",\n]"
+    );
+    assert_eq!(
+        format!("{:#?}", TextStatic::<HtmlDriver>::new("a")),
+        "\"a\""
+    );
+}
+
+#[test]
+fn dom_tags_count_their_children() {
+    let driver_ctx = DriverCtx::new(HtmlDriver);
+    let dom = to_dom(&driver_ctx, &mut list(&["a", "b"]));
+    assert_eq!(format!("{:?}", dom), "[<ul class=\"x\"> (2 children)]");
+    let dom = to_dom(&driver_ctx, &mut list(&["a"]));
+    assert_eq!(format!("{:?}", dom), "[<ul class=\"x\"> (1 child)]");
+}
+
+#[test]
+fn patches_show_their_paths_and_nodes() {
+    let driver_ctx = DriverCtx::new(HtmlDriver);
+    let patches = patch::diff(&driver_ctx, &mut list(&["a", "c"]), &mut list(&["b"]));
+    assert_eq!(
+        format!("{:?}", patches),
+        "[SetText { path: Path([0, 0, 0]), text: \"a\" }, \
+         InsertNode { path: Path([0, 1]), node: <li> (1 child) }]"
+    );
+}
+
+#[test]
+fn components_show_their_type() {
+    let comp = CompNode::<HtmlDriver, Greeting>::new(());
+    assert_eq!(
+        format!("{:?}", comp),
+        format!("CompNode({})", std::any::type_name::<Greeting>())
+    );
+}
+
+#[test]
+fn histories_evict_their_oldest_frames() {
+    let driver_ctx = DriverCtx::new(HtmlDriver);
+    let mut history = DebugHistory::new(&driver_ctx, &mut list(&[]), 2);
+    history.record(&driver_ctx, &mut list(&["a"]), 1.0);
+    history.record(&driver_ctx, &mut list(&["a", "b"]), 2.0);
+    history.record(&driver_ctx, &mut list(&["c", "b"]), 3.0);
+
+    // The frame inserting `a` is gone, the latest two are kept in order.
+    let frames = history.frames().collect::<Vec<_>>();
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0].duration_ms, 2.0);
+    assert_eq!(
+        format!("{:?}", frames[1].patches),
+        "[SetText { path: Path([0, 0, 0]), text: \"c\" }]"
+    );
+    let snapshot = history.snapshot();
+    assert_eq!(snapshot.frame_times_ms, vec![2.0, 3.0]);
+    assert_eq!(snapshot.patches.len(), 1);
+    assert_eq!(
+        format!("{:#?}", history.tree()),
+        format!("{:#?}", to_dom(&driver_ctx, &mut list(&["c", "b"])))
+    );
+}
+
+#[test]
+fn histories_without_capacity_keep_the_tree_only() {
+    let driver_ctx = DriverCtx::new(HtmlDriver);
+    let mut history = DebugHistory::new(&driver_ctx, &mut list(&[]), 0);
+    history.record(&driver_ctx, &mut list(&["a"]), 1.0);
+    assert_eq!(history.frames().count(), 0);
+    assert!(history.snapshot().patches.is_empty());
+    assert_eq!(history.tree().len(), 1);
+}
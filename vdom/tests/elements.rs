@@ -0,0 +1,55 @@
+use vdom::{
+    html::{render_to_string, HtmlDriver},
+    vdom::{
+        attr::{AttrListEntry, AttrStr},
+        elements::{a, br, div},
+        node::{Node, TagStatic, TextStatic},
+    },
+};
+
+#[test]
+fn constructors_build_static_tags() {
+    let mut node: TagStatic<HtmlDriver, _, _> = div(
+        AttrListEntry(AttrStr::new("class", "links")),
+        (
+            a(
+                AttrListEntry(AttrStr::new("href", "/")),
+                TextStatic::new("Home"),
+            ),
+            (br(()), a((), TextStatic::new("Back"))),
+        ),
+    );
+    assert_eq!(
+        render_to_string(&mut node),
+        "<div class=\"links\"><a href=\"/\">Home</a><br><a>Back</a></div>"
+    );
+}
+
+#[test]
+fn constructors_match_tag_static() {
+    fn is_static<N>(_node: &N) -> bool
+    where
+        N: Node<HtmlDriver>,
+    {
+        N::IS_STATIC
+    }
+    let link = a(
+        AttrListEntry(AttrStr::new("href", "/")),
+        TextStatic::new("Home"),
+    );
+    assert!(is_static(&link));
+    let mut link = div((), link);
+    let mut tag_static = TagStatic::new(
+        "div",
+        (),
+        TagStatic::new(
+            "a",
+            AttrListEntry(AttrStr::new("href", "/")),
+            TextStatic::new("Home"),
+        ),
+    );
+    assert_eq!(
+        render_to_string(&mut link),
+        render_to_string(&mut tag_static)
+    );
+}
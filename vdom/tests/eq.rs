@@ -0,0 +1,80 @@
+use vdom::{
+    html::HtmlDriver,
+    vdom::dom::{nodes_eq, DomAttr, DomNode, EqOptions},
+};
+
+type Node = DomNode<HtmlDriver>;
+
+fn tag(name: &'static str, attrs: &[(&'static str, &'static str)]) -> Node {
+    let attrs = attrs
+        .iter()
+        .map(|&(name, value)| DomAttr::new(name, value))
+        .collect();
+    DomNode::tag(name, attrs, vec![])
+}
+
+fn p(text: &'static str) -> Node {
+    DomNode::tag("p", vec![], vec![DomNode::text(text)])
+}
+
+fn pre(children: Vec<Node>) -> Node {
+    DomNode::tag("pre", vec![], children)
+}
+
+#[test]
+fn trees_are_compared_exactly_by_default() {
+    let options = EqOptions::new();
+    let a = [tag("a", &[("class", "x"), ("id", "y")])];
+    assert!(nodes_eq(&a, &a, &options));
+    let other = [tag("a", &[("class", "x"), ("id", "z")])];
+    assert!(!nodes_eq(&a, &other, &options));
+    assert!(!nodes_eq(&[p("a b")], &[p("a  b")], &options));
+    let spaced = [p("a"), DomNode::text(" ")];
+    assert!(!nodes_eq(&spaced, &[p("a")], &options));
+
+    // Attributes are compared regardless of their order.
+    let reordered = [tag("a", &[("id", "y"), ("class", "x")])];
+    assert!(nodes_eq(&a, &reordered, &options));
+}
+
+#[test]
+fn whitespace_text_can_be_ignored() {
+    let options = EqOptions::new().ignore_whitespace_text();
+    let spaced = [DomNode::text("\n  "), p("a"), DomNode::text("\n")];
+    assert!(nodes_eq(&spaced, &[p("a")], &options));
+    // Other text is still compared.
+    let texts = [p("a"), DomNode::text("b")];
+    assert!(!nodes_eq(&texts, &[p("a")], &options));
+
+    // Whitespace is kept within `pre`.
+    let pre_spaced = pre(vec![p("a"), DomNode::text("\n")]);
+    let pre_tight = pre(vec![p("a")]);
+    assert!(!nodes_eq(&[pre_spaced], &[pre_tight], &options));
+}
+
+#[test]
+fn whitespace_can_be_collapsed() {
+    let options = EqOptions::new().collapse_whitespace();
+    assert!(nodes_eq(&[p("a \n\t b")], &[p("a b")], &options));
+    // Whitespace is collapsed but not removed.
+    assert!(!nodes_eq(&[p("a b")], &[p("ab")], &options));
+
+    // Whitespace is kept within `pre`.
+    let pre_spaced = pre(vec![DomNode::text("a \n b")]);
+    let pre_tight = pre(vec![DomNode::text("a b")]);
+    assert!(!nodes_eq(&[pre_spaced], &[pre_tight], &options));
+}
+
+#[test]
+fn names_can_be_compared_ignoring_case() {
+    let options = EqOptions::new().ignore_case();
+    let upper = [tag("P", &[("CLASS", "x")])];
+    let lower = [tag("p", &[("class", "x")])];
+    assert!(nodes_eq(&upper, &lower, &options));
+    assert!(!nodes_eq(&upper, &lower, &EqOptions::new()));
+
+    // Values and text keep their case.
+    let value = [tag("p", &[("class", "X")])];
+    assert!(!nodes_eq(&value, &lower, &options));
+    assert!(!nodes_eq(&[p("A")], &[p("a")], &options));
+}
@@ -0,0 +1,84 @@
+use vdom::{
+    driver::DriverCtx,
+    html::{render_to_string, HtmlDriver},
+    vdom::{
+        attr::{AttrListEntry, AttrStr},
+        node::{Comp, CompCtx, CompNode, DynNode, TagStatic, TextDyn, TextStatic},
+        patch::{diff, Patch},
+    },
+};
+
+fn card(title: &'static str) -> impl vdom::vdom::node::Node<HtmlDriver> {
+    TagStatic::new(
+        "div",
+        AttrListEntry(AttrStr::new("class", "card")),
+        (
+            TagStatic::new("h2", (), TextDyn::new(title)),
+            TextStatic::new("!"),
+        ),
+    )
+}
+
+fn link(href: &'static str) -> impl vdom::vdom::node::Node<HtmlDriver> {
+    TagStatic::new(
+        "a",
+        AttrListEntry(AttrStr::new("href", href)),
+        TextStatic::new("go"),
+    )
+}
+
+#[derive(Clone, PartialEq, Eq)]
+struct Empty;
+
+impl Comp<HtmlDriver> for Empty {
+    type Input = ();
+    type Rendered = ();
+
+    fn new(_input: &(), _ctx: CompCtx<HtmlDriver, Empty>) -> Empty {
+        Empty
+    }
+
+    fn render(&self, _input: &()) {}
+}
+
+#[test]
+fn renders_trees_of_different_types() {
+    let mut nodes = vec![
+        DynNode::new(card("a")).unwrap(),
+        DynNode::new(link("/x")).unwrap(),
+        DynNode::new(()).unwrap(),
+    ];
+    assert_eq!(
+        render_to_string(&mut nodes),
+        "<div class=\"card\"><h2>a</h2>!</div><a href=\"/x\">go</a>"
+    );
+    assert!(nodes[2].is::<()>());
+    assert!(!nodes[1].is::<()>());
+}
+
+#[test]
+fn diffs_same_types_and_replaces_others() {
+    let ctx = DriverCtx::new(HtmlDriver);
+    let mut a = DynNode::new(card("a")).unwrap();
+    let mut b = DynNode::new(card("b")).unwrap();
+    match diff(&ctx, &mut b, &mut a).as_slice() {
+        [Patch::SetText { text, .. }] => assert_eq!(text, "b"),
+        _ => panic!("expected one SetText"),
+    }
+    let mut l = DynNode::new(link("/x")).unwrap();
+    match diff(&ctx, &mut l, &mut b).as_slice() {
+        [Patch::RemoveNode { .. }, Patch::InsertNode { .. }] => {}
+        _ => panic!("expected a replace"),
+    }
+}
+
+#[test]
+fn rejects_components() {
+    let node = TagStatic::new(
+        "div",
+        (),
+        (TextStatic::new("a"), CompNode::<HtmlDriver, Empty>::new(())),
+    );
+    let err = DynNode::new(node).err().unwrap();
+    assert_eq!(err.path.indices(), &[0, 1]);
+}
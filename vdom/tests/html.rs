@@ -0,0 +1,396 @@
+use std::cell::Cell;
+use vdom::{
+    html::{
+        render_oob, render_to_string, CachingRenderer, HtmlDriver, HtmlRenderer, Quote,
+        TruncatingRenderer,
+    },
+    vdom::{
+        attr::{AttrDyn, AttrListEntry, AttrNs, AttrProp, AttrStr},
+        dom::{DomAttr, DomNode},
+        node::{Node, NodeDiffer, NodeVisitor, TagStatic, TextStatic},
+    },
+};
+
+#[test]
+fn render_skips_invalid_attr_names() {
+    let attrs = vec![
+        DomAttr::new("class", "a"),
+        DomAttr::new("x><script>alert(1)</script", "b"),
+        DomAttr::new("title onclick", "c"),
+        DomAttr::new("", "d"),
+        DomAttr::new("data-y", "e"),
+    ];
+    let mut node: DomNode<HtmlDriver> = DomNode::tag("p", attrs, vec![DomNode::text("t")]);
+    assert_eq!(
+        render_to_string(&mut node),
+        r#"<p class="a" data-y="e">t</p>"#
+    );
+}
+
+fn card(title: &str, class: &str) -> DomNode<HtmlDriver> {
+    DomNode::tag(
+        "div",
+        vec![DomAttr::new("class", class.to_string())],
+        vec![
+            DomNode::tag("b", vec![], vec![DomNode::text(title.to_string())]),
+            DomNode::tag("br", vec![], vec![]),
+        ],
+    )
+}
+
+#[test]
+fn caching_renderer_copies_repeated_subtrees() {
+    let mut node: DomNode<HtmlDriver> = DomNode::tag(
+        "main",
+        vec![],
+        vec![card("a", "card"), card("b", "card"), card("a", "card")],
+    );
+    let mut renderer = CachingRenderer::new();
+    assert_eq!(
+        renderer.render_to_string(&mut node),
+        render_to_string(&mut node)
+    );
+    // The second `a` card, and the `<br>` of the `b` one.
+    assert_eq!(renderer.hits(), 2);
+}
+
+#[test]
+fn caching_renderer_tells_similar_subtrees_apart() {
+    let mut node: DomNode<HtmlDriver> = DomNode::tag(
+        "main",
+        vec![],
+        vec![
+            card("a", "card"),
+            card("a", "card wide"),
+            card("ab", "card"),
+            DomNode::tag(
+                "div",
+                vec![DomAttr::new("class", "card")],
+                vec![
+                    DomNode::tag("b", vec![], vec![DomNode::text("a"), DomNode::text("b")]),
+                    DomNode::tag("br", vec![], vec![]),
+                ],
+            ),
+            DomNode::tag("p", vec![], vec![DomNode::text("<a>")]),
+            DomNode::tag("script", vec![], vec![DomNode::text("<a>")]),
+            DomNode::tag("p", vec![], vec![DomNode::text("<a>")]),
+        ],
+    );
+    let mut renderer = CachingRenderer::new();
+    assert_eq!(
+        renderer.render_to_string(&mut node),
+        render_to_string(&mut node)
+    );
+    // The `<b>` of the second card, the `<br>`s after the first, and the
+    // second `<p>`.
+    assert_eq!(renderer.hits(), 5);
+}
+
+/// A node that visits one more tag each time, as no node should.
+struct Growing(Cell<usize>);
+
+impl Node<HtmlDriver> for Growing {
+    fn visit<NV>(&mut self, index: &mut usize, visitor: &mut NV) -> Result<(), NV::Err>
+    where
+        NV: NodeVisitor<HtmlDriver>,
+    {
+        self.0.set(self.0.get() + 1);
+        let mut tags = (0..self.0.get())
+            .map(|_| DomNode::tag("hr", vec![], vec![]))
+            .collect::<Vec<DomNode<HtmlDriver>>>();
+        tags.visit(index, visitor)
+    }
+
+    fn diff<ND>(
+        &mut self,
+        _curr_index: &mut usize,
+        _ancestor_index: &mut usize,
+        _ancestor: &mut Growing,
+        _differ: &mut ND,
+    ) -> Result<(), ND::Err>
+    where
+        ND: NodeDiffer<HtmlDriver>,
+    {
+        Ok(())
+    }
+}
+
+#[test]
+fn caching_renderer_survives_nodes_changing_between_passes() {
+    let mut node = Growing(Cell::new(0));
+    let html = CachingRenderer::new().render_to_string(&mut node);
+    assert_eq!(html, "<hr><hr>");
+}
+
+#[test]
+fn raw_text_cannot_end_its_element_early() {
+    let mut node: DomNode<HtmlDriver> = DomNode::tag(
+        "div",
+        vec![],
+        vec![
+            DomNode::tag(
+                "script",
+                vec![],
+                vec![DomNode::text(
+                    "if (a < b) s = \"</script><p>\" + '</SCRIPT'</p>",
+                )],
+            ),
+            DomNode::tag(
+                "style",
+                vec![],
+                vec![DomNode::text("a::after { content: \"</style>\" }")],
+            ),
+            DomNode::tag("p", vec![], vec![DomNode::text("</script>")]),
+        ],
+    );
+    assert_eq!(
+        render_to_string(&mut node),
+        "<div><script>if (a < b) s = \"<\\/script><p>\" + '<\\/SCRIPT'</p></script>\
+         <style>a::after { content: \"<\\/style>\" }</style>\
+         <p>&lt;/script&gt;</p></div>"
+    );
+}
+
+#[test]
+fn hydration_keys_are_the_paths_of_elements() {
+    let mut node: Vec<DomNode<HtmlDriver>> = vec![
+        DomNode::text("intro"),
+        DomNode::tag(
+            "ul",
+            vec![DomAttr::new("class", "list")],
+            vec![
+                DomNode::tag("li", vec![], vec![DomNode::text("a")]),
+                DomNode::text(" "),
+                DomNode::tag("li", vec![], vec![DomNode::tag("br", vec![], vec![])]),
+            ],
+        ),
+    ];
+    let mut out = String::new();
+    node.visit(&mut 0, &mut HtmlRenderer::new(&mut out).hydration_keys())
+        .unwrap();
+    assert_eq!(
+        out,
+        "intro<ul class=\"list\" data-hk=\"/1\"><li data-hk=\"/1/0\">a</li> \
+         <li data-hk=\"/1/2\"><br data-hk=\"/1/2/0\"></li></ul>"
+    );
+}
+
+#[test]
+fn lists_render_their_entries_in_a_row() {
+    let p = |text| TagStatic::<HtmlDriver, _, ()>::new("p", (), TextStatic::new(text));
+    assert_eq!(render_to_string(&mut (p("a"), p("b"))), "<p>a</p><p>b</p>");
+    let mut nodes = vec![p("a"), p("b")];
+    assert_eq!(render_to_string(&mut nodes[..]), "<p>a</p><p>b</p>");
+
+    assert_eq!(render_to_string(&mut Vec::<DomNode<HtmlDriver>>::new()), "");
+    assert_eq!(render_to_string(&mut nodes[..0]), "");
+    assert_eq!(render_to_string(&mut ()), "");
+}
+
+/// A page with two sections with an `id`, the second one containing a
+/// paragraph with an `id`, and a footer without one.
+fn oob_page(a: &str, b_class: &str, c: &str, footer: &str) -> Vec<DomNode<HtmlDriver>> {
+    let id = |id: &str| DomAttr::new("id", id.to_string());
+    let p = |text: &str| DomNode::tag("p", vec![], vec![DomNode::text(text.to_string())]);
+    vec![
+        DomNode::tag("section", vec![id("a")], vec![p(a)]),
+        DomNode::tag(
+            "section",
+            vec![id("b"), DomAttr::new("class", b_class.to_string())],
+            vec![DomNode::tag(
+                "p",
+                vec![id("c")],
+                vec![DomNode::text(c.to_string())],
+            )],
+        ),
+        DomNode::tag("footer", vec![], vec![p(footer)]),
+    ]
+}
+
+#[test]
+fn render_oob_renders_the_changed_elements_with_an_id() {
+    let mut ancestor = oob_page("a", "x", "c", "f");
+    assert!(render_oob(&mut oob_page("a", "x", "c", "f"), &mut ancestor).is_empty());
+
+    // The footer has no `id`, so its change can't be targeted.
+    let mut curr = oob_page("a2", "x", "c2", "f2");
+    assert_eq!(
+        render_oob(&mut curr, &mut ancestor),
+        [
+            (
+                "a".to_string(),
+                r#"<section id="a"><p>a2</p></section>"#.to_string()
+            ),
+            ("c".to_string(), r#"<p id="c">c2</p>"#.to_string()),
+        ]
+    );
+
+    // A changed element is rendered as a whole, including changed elements
+    // within it.
+    let mut curr = oob_page("a", "y", "c2", "f");
+    assert_eq!(
+        render_oob(&mut curr, &mut ancestor),
+        [(
+            "b".to_string(),
+            r#"<section id="b" class="y"><p id="c">c2</p></section>"#.to_string()
+        )]
+    );
+}
+
+fn article() -> Vec<DomNode<HtmlDriver>> {
+    let text = |text: &'static str| DomNode::text(text);
+    vec![
+        DomNode::tag(
+            "article",
+            vec![],
+            vec![
+                DomNode::tag("h1", vec![], vec![text("Title")]),
+                DomNode::tag(
+                    "p",
+                    vec![],
+                    vec![
+                        text("Some "),
+                        DomNode::tag("b", vec![], vec![text("bold")]),
+                        text(" text"),
+                    ],
+                ),
+            ],
+        ),
+        DomNode::tag("footer", vec![], vec![text("f")]),
+    ]
+}
+
+fn truncate(budget: usize) -> (String, bool) {
+    let mut out = String::new();
+    let mut renderer = TruncatingRenderer::new(&mut out, budget);
+    article().visit(&mut 0, &mut renderer).unwrap();
+    let truncated = renderer.is_truncated();
+    (out, truncated)
+}
+
+#[test]
+fn truncating_closes_the_open_elements() {
+    // The budget runs out within the paragraph, after "Title" and "Som".
+    let (html, truncated) = truncate(8);
+    assert_eq!(html, "<article><h1>Title</h1><p>Som\u{2026}</p></article>");
+    assert!(truncated);
+
+    // Within the nested element.
+    let (html, truncated) = truncate(12);
+    assert_eq!(
+        html,
+        "<article><h1>Title</h1><p>Some <b>bo\u{2026}</b></p></article>"
+    );
+    assert!(truncated);
+
+    // Text that fits the budget exactly is not cut off.
+    let (html, truncated) = truncate(20);
+    assert_eq!(html, render_to_string(&mut article()));
+    assert!(!truncated);
+}
+
+fn render_quoted(quote: Quote) -> String {
+    let mut node: DomNode<HtmlDriver> = DomNode::tag(
+        "p",
+        vec![
+            DomAttr::new("class", "a"),
+            DomAttr::new("title", "it's \"quoted\""),
+        ],
+        vec![DomNode::text("it's \"quoted\"")],
+    );
+    let mut out = String::new();
+    node.visit(&mut 0, &mut HtmlRenderer::new(&mut out).quote(quote))
+        .unwrap();
+    out
+}
+
+#[test]
+fn values_escape_only_the_quote_they_are_in() {
+    assert_eq!(
+        render_quoted(Quote::Double),
+        r#"<p class="a" title="it's &quot;quoted&quot;">it's "quoted"</p>"#
+    );
+    assert_eq!(
+        render_quoted(Quote::Single),
+        r#"<p class='a' title='it&#39;s "quoted"'>it's "quoted"</p>"#
+    );
+}
+
+#[test]
+fn namespaced_attrs_render_with_their_prefix() {
+    let mut node = TagStatic::<HtmlDriver, _, ()>::new(
+        "svg",
+        (),
+        TagStatic::new(
+            "use",
+            (
+                AttrListEntry(AttrStr::new("href", "#icon").ns(AttrNs::XLINK)),
+                AttrListEntry(AttrDyn::new("lang", "en").ns(AttrNs::XML)),
+            ),
+            (),
+        ),
+    );
+    assert_eq!(
+        render_to_string(&mut node),
+        r##"<svg><use xlink:href="#icon" xml:lang="en"></use></svg>"##
+    );
+}
+
+#[test]
+fn props_render_as_attrs() {
+    // There are no properties without a DOM, so the initial state of an
+    // input is written as its attributes instead.
+    let input = |value, checked| {
+        TagStatic::<HtmlDriver, (), _>::new(
+            "input",
+            (
+                AttrListEntry(AttrProp::new("value", value)),
+                AttrListEntry(AttrProp::new("checked", checked)),
+            ),
+            (),
+        )
+    };
+    assert_eq!(
+        render_to_string(&mut input("a \"b\"", true)),
+        r#"<input value="a &quot;b&quot;" checked>"#
+    );
+    assert_eq!(
+        render_to_string(&mut input("", false)),
+        r#"<input value="">"#
+    );
+}
+
+#[test]
+fn template_children_render_inside_the_template() {
+    let p = |text| TagStatic::<HtmlDriver, _, ()>::new("p", (), TextStatic::new(text));
+    let mut node = TagStatic::<HtmlDriver, _, ()>::new("template", (), (p("a"), p("b")));
+    assert_eq!(
+        render_to_string(&mut node),
+        "<template><p>a</p><p>b</p></template>"
+    );
+}
+
+#[test]
+fn no_whitespace_is_added_between_elements() {
+    let li = |text| TagStatic::<HtmlDriver, _, ()>::new("li", (), TextStatic::new(text));
+    let mut node = TagStatic::<HtmlDriver, _, ()>::new(
+        "ul",
+        (),
+        ((li("a"), li(" b \n")), TagStatic::new("li", (), ())),
+    );
+    assert_eq!(
+        render_to_string(&mut node),
+        "<ul><li>a</li><li> b \n</li><li></li></ul>"
+    );
+}
+
+#[test]
+fn comments_cant_end_early() {
+    let comment = |text| render_to_string(&mut DomNode::<HtmlDriver>::comment(text));
+    assert_eq!(comment(" note "), "<!-- note -->");
+    assert_eq!(comment("a-->b"), "<!--a- ->b-->");
+    assert_eq!(comment("a---b"), "<!--a- - -b-->");
+    assert_eq!(comment(">a"), "<!-- >a-->");
+    assert_eq!(comment("->a"), "<!-- ->a-->");
+    assert_eq!(comment("a<b>&"), "<!--a<b>&-->");
+}
@@ -0,0 +1,113 @@
+use vdom::{
+    html::{render_to_string, HtmlDriver, IncrementalRenderer},
+    vdom::{
+        attr::{AttrDyn, AttrListEntry},
+        node::{TagDyn, TagStatic, TextDyn, TextStatic},
+    },
+};
+
+/// A list item with a text, an optional class and bold parts.
+struct Item {
+    text: String,
+    class: Option<String>,
+    parts: Vec<String>,
+}
+
+type ItemNode = TagDyn<
+    HtmlDriver,
+    (
+        TextDyn<HtmlDriver>,
+        Vec<TagStatic<HtmlDriver, TextDyn<HtmlDriver>, ()>>,
+    ),
+    AttrListEntry<AttrDyn<HtmlDriver>>,
+>;
+
+type Page = (
+    TagStatic<HtmlDriver, TextStatic<HtmlDriver>, ()>,
+    TagStatic<HtmlDriver, Vec<ItemNode>, ()>,
+);
+
+fn page(items: &[Item]) -> Page {
+    let items = items
+        .iter()
+        .map(|item| {
+            let parts = item
+                .parts
+                .iter()
+                .map(|part| TagStatic::new("b", (), TextDyn::new(part.clone())))
+                .collect();
+            TagDyn::new(
+                "li",
+                AttrListEntry(AttrDyn::new("class", item.class.clone())),
+                (TextDyn::new(item.text.clone()), parts),
+            )
+        })
+        .collect();
+    (
+        TagStatic::new("h1", (), TextStatic::new("Items")),
+        TagStatic::new("ul", (), items),
+    )
+}
+
+#[test]
+fn small_edits_render_what_a_fresh_render_does() {
+    // A linear congruential generator, so the edits are the same on each run.
+    let mut seed = 7u64;
+    let mut next = |n: usize| {
+        seed = seed
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        (seed >> 33) as usize % n
+    };
+    for _ in 0..50 {
+        let mut items = (0..5)
+            .map(|i| {
+                Item {
+                    text: format!("item {}", i),
+                    class: None,
+                    parts: Vec::new(),
+                }
+            })
+            .collect::<Vec<_>>();
+        let mut renderer = IncrementalRenderer::new();
+        for _ in 0..30 {
+            let len = items.len();
+            match next(6) {
+                0 if len > 0 => items[next(len)].text = format!("x<{}", next(100)),
+                1 if len > 0 => {
+                    items[next(len)].class = if next(2) == 0 {
+                        None
+                    } else {
+                        Some(format!("c\"{}", next(9)))
+                    };
+                }
+                2 if len > 0 => {
+                    let parts = &mut items[next(len)].parts;
+                    let at = next(parts.len() + 1);
+                    parts.insert(at, format!("part {}", next(50)));
+                }
+                3 if len > 0 => {
+                    let parts = &mut items[next(len)].parts;
+                    if !parts.is_empty() {
+                        let at = next(parts.len());
+                        parts.remove(at);
+                    }
+                }
+                4 => {
+                    let item = Item {
+                        text: format!("new {}", next(9)),
+                        class: None,
+                        parts: Vec::new(),
+                    };
+                    items.insert(next(len + 1), item);
+                }
+                _ if len > 1 => {
+                    items.remove(next(len));
+                }
+                _ => {}
+            }
+            let expected = render_to_string(&mut page(&items));
+            assert_eq!(renderer.render(page(&items)), expected);
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use vdom::{
+    html::{render_to_string, HtmlDriver},
+    vdom::{
+        integrity::{subresource_integrity, CrossOrigin, Integrity, IntegrityError},
+        node::TagStatic,
+    },
+};
+
+const SHA256: &str = "sha256-ypeBEsobvcr6wjGzmiPcTaeG7/gUfE5yuYB3ha/uSLs=";
+const SHA384: &str = "sha384-VKWbnyKwuAiA2EJ+VIt8I6vYc0huHwNdzpzWl+hRdQM8qojm1XvDXvrgta/TFF8x";
+const SHA512_URL_SAFE: &str = "sha512-H0D8ktokFpR1CXnubPWC8tXX0o4YM13gWrxU0FYOD1MChgxlK_\
+                               CNVgJSql50IQVG82n7u86MEs_HlXsmUv6adQ==";
+
+#[test]
+fn valid_hashes_are_accepted() {
+    let values = vec![
+        SHA256.to_string(),
+        SHA384.to_string(),
+        SHA512_URL_SAFE.to_string(),
+        // Without padding, with options and next to another hash.
+        SHA256.trim_end_matches('=').to_string(),
+        format!("{}?v=1", SHA256),
+        format!(" {}  {} ", SHA256, SHA384),
+    ];
+    for value in values {
+        assert_eq!(Integrity::new(value.clone()).unwrap().as_str(), value);
+    }
+}
+
+#[test]
+fn malformed_hashes_are_rejected() {
+    let digest = |hash: &str| hash[hash.find('-').unwrap() + 1..].to_string();
+    let cases = vec![
+        (String::new(), IntegrityError::Empty),
+        ("  ".to_string(), IntegrityError::Empty),
+        (
+            "md5-1B2M2Y8AsgTpgAmY7PhCfg==".to_string(),
+            IntegrityError::UnknownAlgorithm { hash: 0 },
+        ),
+        (digest(SHA256), IntegrityError::UnknownAlgorithm { hash: 0 }),
+        (
+            format!("{} sha1-x", SHA256),
+            IntegrityError::UnknownAlgorithm { hash: 1 },
+        ),
+        (
+            "sha256-".to_string(),
+            IntegrityError::InvalidDigest { hash: 0 },
+        ),
+        // The digest of another algorithm.
+        (
+            format!("sha256-{}", digest(SHA384)),
+            IntegrityError::InvalidDigest { hash: 0 },
+        ),
+        // Both alphabets at once.
+        (
+            SHA384.replacen('+', "-", 1),
+            IntegrityError::InvalidDigest { hash: 0 },
+        ),
+        (
+            format!("{}=", SHA256),
+            IntegrityError::InvalidDigest { hash: 0 },
+        ),
+        (
+            SHA256.replace('/', "!"),
+            IntegrityError::InvalidDigest { hash: 0 },
+        ),
+    ];
+    for (value, err) in cases {
+        assert_eq!(Integrity::new(value.clone()), Err(err), "{:?}", value);
+    }
+}
+
+#[test]
+fn errors_tell_which_hash_is_malformed() {
+    let err: Box<dyn std::error::Error> = Box::new(IntegrityError::InvalidDigest { hash: 1 });
+    assert_eq!(err.to_string(), "hash 1 of integrity has an invalid digest");
+    assert_eq!(
+        IntegrityError::UnknownAlgorithm { hash: 0 }.to_string(),
+        "hash 0 of integrity isn't sha256, sha384 or sha512"
+    );
+    assert_eq!(IntegrityError::Empty.to_string(), "integrity has no hash");
+}
+
+#[test]
+fn attributes_are_rendered() {
+    let attrs = subresource_integrity(Integrity::new(SHA384).unwrap(), CrossOrigin::Anonymous);
+    let mut script = TagStatic::<HtmlDriver, (), _>::new("script", attrs, ());
+    assert_eq!(
+        render_to_string(&mut script),
+        format!(
+            "<script integrity=\"{}\" crossorigin=\"anonymous\"></script>",
+            SHA384
+        )
+    );
+}
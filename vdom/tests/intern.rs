@@ -0,0 +1,40 @@
+use std::sync::Arc;
+use vdom::{
+    html::{render_to_string, HtmlDriver},
+    vdom::{
+        attr::{AttrDyn, AttrListEntry},
+        intern::{Interner, Name},
+        node::{TagDyn, TextStatic},
+    },
+};
+
+#[test]
+fn equal_names_share_storage() {
+    let mut interner = Interner::new();
+    let a = interner.intern("data-x");
+    let b = interner.intern(&String::from("data-x"));
+    assert!(std::ptr::eq(&*a, &*b));
+    assert_eq!(a, Name::from("data-x"));
+    assert_eq!(interner.len(), 1);
+}
+
+#[test]
+fn interned_names_are_freed_with_their_users() {
+    let mut interner = Interner::new();
+    let attr = AttrDyn::interned(&mut interner, "data-x", "1");
+    let mut node = TagDyn::<HtmlDriver, _, _>::interned(
+        &mut interner,
+        "x-row",
+        AttrListEntry(attr),
+        TextStatic::new("a"),
+    );
+    assert_eq!(render_to_string(&mut node), "<x-row data-x=\"1\">a</x-row>");
+
+    let name = match interner.intern("data-x") {
+        Name::Interned(name) => name,
+        _ => panic!("expected an interned name"),
+    };
+    assert_eq!(Arc::strong_count(&name), 3);
+    drop((interner, node));
+    assert_eq!(Arc::strong_count(&name), 1);
+}
@@ -0,0 +1,363 @@
+#![cfg(feature = "serde_json")]
+
+use serde_json::{Map, Value};
+use vdom::{
+    driver::DriverCtx,
+    html::{render_to_string, HtmlDriver},
+    vdom::{
+        attr::{AttrDyn, AttrListEntry},
+        dom::{to_dom, DomNode},
+        json::{diff_to_json_patch, from_json, json_patch, to_json, JsonError},
+        node::{Keyed, TagStatic, TextDyn},
+        patch::{diff_spliced, splice_text},
+    },
+};
+
+fn element(tag: &str, attrs: Vec<(&str, Value)>, children: Vec<Value>) -> Value {
+    let mut object = Map::new();
+    object.insert("tag".to_string(), Value::String(tag.to_string()));
+    let attrs = attrs
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value))
+        .collect();
+    object.insert("attrs".to_string(), Value::Object(attrs));
+    object.insert("children".to_string(), Value::Array(children));
+    Value::Object(object)
+}
+
+fn text(text: &str) -> Value {
+    let mut object = Map::new();
+    object.insert("text".to_string(), Value::String(text.to_string()));
+    Value::Object(object)
+}
+
+#[test]
+fn from_json_builds_nested_nodes() {
+    let json = Value::Array(vec![element(
+        "ul",
+        vec![("class", Value::String("list".to_string()))],
+        vec![
+            element("li", vec![("hidden", Value::Bool(true))], vec![text("a")]),
+            element("li", vec![("data-x", Value::Null)], vec![text("b")]),
+        ],
+    )]);
+    let mut nodes: Vec<DomNode<HtmlDriver>> = from_json(&json).unwrap();
+    assert_eq!(
+        render_to_string(&mut nodes),
+        r#"<ul class="list"><li hidden>a</li><li>b</li></ul>"#
+    );
+}
+
+#[test]
+fn from_json_rejects_invalid_tag_names() {
+    for tag in &["", "1p", "p><script", "p onclick", "p/"] {
+        let json = element(tag, vec![], vec![]);
+        assert_eq!(
+            DomNode::<HtmlDriver>::from_json(&json).unwrap_err(),
+            JsonError::InvalidName {
+                pointer: "/tag".to_string(),
+                name: tag.to_string(),
+            }
+        );
+    }
+}
+
+#[test]
+fn from_json_rejects_invalid_attr_names() {
+    let name = r#"x="" onclick"#;
+    let child = element("a", vec![(name, Value::Bool(true))], vec![]);
+    let json = Value::Array(vec![element("p", vec![], vec![child])]);
+    assert_eq!(
+        from_json::<HtmlDriver>(&json).unwrap_err(),
+        JsonError::InvalidName {
+            pointer: "/0/children/0/attrs/x=\"\" onclick".to_string(),
+            name: name.to_string(),
+        }
+    );
+    for name in &["", "a>b", "a/b", "a'b", "a\tb", "a\u{0}b"] {
+        let json = element("p", vec![(name, Value::Bool(true))], vec![]);
+        match DomNode::<HtmlDriver>::from_json(&json) {
+            Err(JsonError::InvalidName { .. }) => {}
+            result => panic!("{:?} was accepted: {:?}", name, result.is_ok()),
+        }
+    }
+}
+
+/// Applies the operations of a JSON Patch the way a client's library would,
+/// as far as `json_patch` uses them, and the `splice` operations it writes
+/// for a `SpliceText`.
+fn apply_json_patch(doc: &mut Value, patch: &Value) {
+    let ops = match patch {
+        Value::Array(ops) => ops,
+        patch => panic!("not a JSON Patch: {:?}", patch),
+    };
+    for op in ops {
+        let op = match op {
+            Value::Object(op) => op,
+            op => panic!("not an operation: {:?}", op),
+        };
+        let member = |name: &str| op.get(name).cloned();
+        let path = match member("path") {
+            Some(Value::String(path)) => tokens(&path),
+            path => panic!("invalid path: {:?}", path),
+        };
+        match member("op") {
+            Some(Value::String(ref name)) if name == "add" => {
+                add(doc, &path, member("value").unwrap());
+            }
+            Some(Value::String(ref name)) if name == "remove" => {
+                remove(doc, &path);
+            }
+            Some(Value::String(ref name)) if name == "replace" => {
+                *lookup(doc, &path) = member("value").unwrap();
+            }
+            Some(Value::String(ref name)) if name == "move" => {
+                let from = match member("from") {
+                    Some(Value::String(from)) => tokens(&from),
+                    from => panic!("invalid from: {:?}", from),
+                };
+                let value = remove(doc, &from);
+                add(doc, &path, value);
+            }
+            Some(Value::String(ref name)) if name == "splice" => {
+                let count = |name| member(name).and_then(|value| value.as_u64()).unwrap();
+                let insert = match member("value") {
+                    Some(Value::String(insert)) => insert,
+                    value => panic!("invalid value: {:?}", value),
+                };
+                let text = lookup(doc, &path);
+                let spliced = match text {
+                    Value::String(text) => {
+                        splice_text(
+                            text,
+                            count("start") as usize,
+                            count("delete") as usize,
+                            &insert,
+                        )
+                        .unwrap()
+                    }
+                    text => panic!("can't splice {:?}", text),
+                };
+                *text = Value::String(spliced);
+            }
+            name => panic!("unexpected operation: {:?}", name),
+        }
+    }
+}
+
+fn tokens(pointer: &str) -> Vec<String> {
+    pointer
+        .split('/')
+        .skip(1)
+        .map(|token| token.replace("~1", "/").replace("~0", "~"))
+        .collect()
+}
+
+fn lookup<'a>(doc: &'a mut Value, tokens: &[String]) -> &'a mut Value {
+    tokens.iter().fold(doc, |value, token| {
+        match value {
+            Value::Array(items) => &mut items[token.parse::<usize>().unwrap()],
+            Value::Object(members) => members.get_mut(token.as_str()).unwrap(),
+            value => panic!("{:?} has no member {:?}", value, token),
+        }
+    })
+}
+
+fn add(doc: &mut Value, tokens: &[String], value: Value) {
+    let (last, parent) = tokens.split_last().unwrap();
+    match lookup(doc, parent) {
+        Value::Array(items) => items.insert(last.parse().unwrap(), value),
+        Value::Object(members) => {
+            members.insert(last.clone(), value);
+        }
+        parent => panic!("can't add to {:?}", parent),
+    }
+}
+
+fn remove(doc: &mut Value, tokens: &[String]) -> Value {
+    let (last, parent) = tokens.split_last().unwrap();
+    match lookup(doc, parent) {
+        Value::Array(items) => items.remove(last.parse().unwrap()),
+        Value::Object(members) => members.remove(last.as_str()).unwrap(),
+        parent => panic!("can't remove from {:?}", parent),
+    }
+}
+
+type Item = TagStatic<HtmlDriver, TextDyn<HtmlDriver>, AttrListEntry<AttrDyn<HtmlDriver>>>;
+type List =
+    TagStatic<HtmlDriver, (TextDyn<HtmlDriver>, Option<Item>), AttrListEntry<AttrDyn<HtmlDriver>>>;
+
+/// A list titled `title`, with a text and optionally an item.
+fn list(title: &'static str, text: &'static str, item: Option<&'static str>) -> List {
+    let item = item.map(|item| {
+        TagStatic::new(
+            "li",
+            AttrListEntry(AttrDyn::new("data-a/b", item)),
+            TextDyn::new(item),
+        )
+    });
+    TagStatic::new(
+        "ul",
+        AttrListEntry(AttrDyn::new("title", title)),
+        (TextDyn::new(text), item),
+    )
+}
+
+fn ops(patch: &Value) -> Vec<(String, String)> {
+    let ops = match patch {
+        Value::Array(ops) => ops,
+        patch => panic!("not a JSON Patch: {:?}", patch),
+    };
+    ops.iter()
+        .map(|op| {
+            let op = match op {
+                Value::Object(op) => op,
+                op => panic!("not an operation: {:?}", op),
+            };
+            match (op.get("op"), op.get("path")) {
+                (Some(Value::String(name)), Some(Value::String(path))) => {
+                    (name.clone(), path.clone())
+                }
+                _ => panic!("invalid operation: {:?}", op),
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn json_patches_add_remove_and_replace() {
+    let driver_ctx = DriverCtx::new(HtmlDriver);
+    let op = |name: &str, path: &str| (name.to_string(), path.to_string());
+
+    let patch = diff_to_json_patch(
+        &driver_ctx,
+        &mut list("a", "x", Some("1")),
+        &mut list("a", "x", None),
+    );
+    assert_eq!(ops(&patch), [op("add", "/0/children/1")]);
+    let patch = diff_to_json_patch(
+        &driver_ctx,
+        &mut list("a", "x", None),
+        &mut list("a", "x", Some("1")),
+    );
+    assert_eq!(ops(&patch), [op("remove", "/0/children/1")]);
+    let patch = diff_to_json_patch(
+        &driver_ctx,
+        &mut list("b", "y", Some("2")),
+        &mut list("a", "x", Some("1")),
+    );
+    assert_eq!(
+        ops(&patch),
+        [
+            op("add", "/0/attrs/title"),
+            op("replace", "/0/children/0/text"),
+            op("add", "/0/children/1/attrs/data-a~1b"),
+            op("replace", "/0/children/1/children/0/text"),
+        ]
+    );
+}
+
+#[test]
+fn json_patches_apply_to_the_json_of_the_ancestor() {
+    let driver_ctx = DriverCtx::new(HtmlDriver);
+    let lists = [
+        ("a", "x", None),
+        ("a", "x", Some("1")),
+        ("b", "x", Some("2")),
+        ("b", "y", None),
+    ];
+    for &(title, text, item) in &lists {
+        for &(curr_title, curr_text, curr_item) in &lists {
+            let mut ancestor = list(title, text, item);
+            let mut curr = list(curr_title, curr_text, curr_item);
+            let mut json = to_json(&to_dom(&driver_ctx, &mut ancestor));
+            let patch = diff_to_json_patch(&driver_ctx, &mut curr, &mut ancestor);
+            apply_json_patch(&mut json, &patch);
+            assert_eq!(json, to_json(&to_dom(&driver_ctx, &mut curr)));
+        }
+    }
+}
+
+#[test]
+fn spliced_json_patches_apply_to_the_json_of_the_ancestor() {
+    let driver_ctx = DriverCtx::new(HtmlDriver);
+    let patch = json_patch(&diff_spliced(
+        &driver_ctx,
+        &mut list("a", "Hello, world!", None),
+        &mut list("a", "Hello, world", None),
+    ));
+    assert_eq!(
+        ops(&patch),
+        [("splice".to_string(), "/0/children/0/text".to_string())]
+    );
+
+    let texts = [
+        "Hello, world",
+        "Hello, wide world",
+        "Hëllo, world",
+        "Hello",
+        "",
+    ];
+    for &text in &texts {
+        for &curr_text in &texts {
+            let mut ancestor = list("a", text, None);
+            let mut curr = list("a", curr_text, None);
+            let mut json = to_json(&to_dom(&driver_ctx, &mut ancestor));
+            let patch = json_patch(&diff_spliced(&driver_ctx, &mut curr, &mut ancestor));
+            apply_json_patch(&mut json, &patch);
+            assert_eq!(json, to_json(&to_dom(&driver_ctx, &mut curr)));
+        }
+    }
+}
+
+#[test]
+fn comments_are_text_marked_as_comments() {
+    let nodes: Vec<DomNode<HtmlDriver>> = vec![DomNode::comment(" note "), DomNode::text("a")];
+    let json = to_json(&nodes);
+    let mut comment = Map::new();
+    comment.insert("text".to_string(), Value::String(" note ".to_string()));
+    comment.insert("comment".to_string(), Value::Bool(true));
+    assert_eq!(json, Value::Array(vec![Value::Object(comment), text("a")]));
+
+    let mut nodes: Vec<DomNode<HtmlDriver>> = from_json(&json).unwrap();
+    assert_eq!(render_to_string(&mut nodes), "<!-- note -->a");
+
+    let mut invalid = Map::new();
+    invalid.insert("text".to_string(), Value::String("a".to_string()));
+    invalid.insert("comment".to_string(), Value::Null);
+    assert_eq!(
+        from_json::<HtmlDriver>(&Value::Array(vec![Value::Object(invalid)])).unwrap_err(),
+        JsonError::InvalidType {
+            pointer: "/0/comment".to_string(),
+            expected: "boolean",
+        }
+    );
+}
+
+#[test]
+fn moves_carry_the_key_of_the_item() {
+    let driver_ctx = DriverCtx::new(HtmlDriver);
+    let keyed = |keys: &[&'static str]| {
+        Keyed::new(
+            keys.iter()
+                .map(|&key| (key, TextDyn::<HtmlDriver>::new(key))),
+        )
+    };
+    let mut ancestor = keyed(&["a", "b"]);
+    let mut curr = keyed(&["b", "a"]);
+    let mut json = to_json(&to_dom(&driver_ctx, &mut ancestor));
+    let patch = diff_to_json_patch(&driver_ctx, &mut curr, &mut ancestor);
+    let op = match &patch {
+        Value::Array(ops) if ops.len() == 1 => {
+            match &ops[0] {
+                Value::Object(op) => op,
+                op => panic!("not an operation: {:?}", op),
+            }
+        }
+        patch => panic!("expected a single move, got {:?}", patch),
+    };
+    assert_eq!(op.get("op"), Some(&Value::String("move".to_string())));
+    assert_eq!(op.get("key"), Some(&Value::String("str:b".to_string())));
+    apply_json_patch(&mut json, &patch);
+    assert_eq!(json, to_json(&to_dom(&driver_ctx, &mut curr)));
+}
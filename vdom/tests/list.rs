@@ -0,0 +1,175 @@
+use vdom::{
+    driver::DriverCtx,
+    html::{render_to_string, HtmlDriver},
+    vdom::{
+        dom::to_dom,
+        key::Key,
+        node::{FilterList, Keyed, Separated, Text, TextDyn, TextStatic},
+        patch::{self, Patch},
+    },
+};
+
+type Crumbs = Separated<TextDyn<HtmlDriver>, TextStatic<HtmlDriver>>;
+
+fn crumbs(items: &[&'static str]) -> Crumbs {
+    Separated::new(items.iter().map(|&item| TextDyn::new(item)), || {
+        TextStatic::new(" › ")
+    })
+}
+
+#[test]
+fn separators_go_between_items_only() {
+    assert_eq!(render_to_string(&mut crumbs(&[])), "");
+    assert_eq!(render_to_string(&mut crumbs(&["a"])), "a");
+    assert_eq!(render_to_string(&mut crumbs(&["a", "b", "c"])), "a › b › c");
+}
+
+#[test]
+fn length_changes_patch_to_a_fresh_render() {
+    let driver_ctx = DriverCtx::new(HtmlDriver);
+    let lists: &[&[&'static str]] = &[&[], &["a"], &["a", "b", "c"], &["x", "b"], &["a"], &[]];
+    for ancestor_items in lists {
+        for curr_items in lists {
+            let mut ancestor = crumbs(ancestor_items);
+            let mut curr = crumbs(curr_items);
+            let mut dom = to_dom(&driver_ctx, &mut ancestor);
+            let patches = patch::diff(&driver_ctx, &mut curr, &mut ancestor);
+            patch::apply(&mut dom, patches).unwrap();
+            assert_eq!(render_to_string(&mut dom), render_to_string(&mut curr));
+            assert_eq!(dom.len(), (2 * curr_items.len()).saturating_sub(1));
+        }
+    }
+}
+
+fn at_least(
+    items: &[u32],
+    min: u32,
+) -> FilterList<TextDyn<HtmlDriver>, impl Fn(&TextDyn<HtmlDriver>) -> bool> {
+    FilterList::new(
+        items.iter().map(|item| TextDyn::new(item.to_string())),
+        move |text: &TextDyn<HtmlDriver>| text.get().parse::<u32>().unwrap() >= min,
+    )
+}
+
+#[test]
+fn filtered_items_are_paired_by_position_in_the_list() {
+    let driver_ctx = DriverCtx::new(HtmlDriver);
+    let mut ancestor = at_least(&[1, 2, 3], 2);
+    let mut curr = at_least(&[1, 2, 3], 1);
+    assert_eq!(render_to_string(&mut ancestor), "23");
+
+    let mut dom = to_dom(&driver_ctx, &mut ancestor);
+    let patches = patch::diff(&driver_ctx, &mut curr, &mut ancestor);
+    // `1` is inserted in front, rather than `2` and `3` being rewritten.
+    match &patches[..] {
+        [Patch::InsertNode { path, .. }] => assert_eq!(path.indices(), &[0]),
+        patches => panic!("expected a single insert, got {:?}", patches),
+    }
+    patch::apply(&mut dom, patches).unwrap();
+    assert_eq!(render_to_string(&mut dom), "123");
+
+    // And removed again, with the other items and a grown list.
+    let mut ancestor = curr;
+    let mut curr = at_least(&[1, 2, 3, 4], 2);
+    let patches = patch::diff(&driver_ctx, &mut curr, &mut ancestor);
+    match &patches[..] {
+        [Patch::RemoveNode { .. }, Patch::InsertNode { .. }] => {}
+        patches => panic!("expected a removal and an insert, got {:?}", patches),
+    }
+    patch::apply(&mut dom, patches).unwrap();
+    assert_eq!(render_to_string(&mut dom), "234");
+}
+
+/// An item with `len` texts, so moving it moves several nodes.
+fn keyed(items: &[(u32, usize)]) -> Keyed<Vec<TextDyn<HtmlDriver>>> {
+    Keyed::new(items.iter().map(|&(key, len)| {
+        let texts = (0..len)
+            .map(|i| TextDyn::new(format!("{}.{} ", key, i)))
+            .collect();
+        (key, texts)
+    }))
+}
+
+#[test]
+fn keyed_items_patch_to_a_fresh_render() {
+    let driver_ctx = DriverCtx::new(HtmlDriver);
+    // A linear congruential generator, so the lists are the same on each run.
+    let mut seed = 42u64;
+    let mut next = |n: u64| {
+        seed = seed
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        (seed >> 33) % n
+    };
+    let mut random_list = || {
+        let mut keys = (0..next(12) as u32).collect::<Vec<_>>();
+        for i in (1..keys.len()).rev() {
+            keys.swap(i, next(i as u64 + 1) as usize);
+        }
+        keys.truncate(next(keys.len() as u64 + 1) as usize);
+        keys.iter()
+            .map(|&key| (key, next(3) as usize))
+            .collect::<Vec<_>>()
+    };
+    for _ in 0..500 {
+        let (ancestor_items, curr_items) = (random_list(), random_list());
+        let mut ancestor = (
+            TextDyn::new("<"),
+            (keyed(&ancestor_items), TextDyn::new(">")),
+        );
+        let mut curr = (TextDyn::new("<"), (keyed(&curr_items), TextDyn::new(">")));
+        let mut dom = to_dom(&driver_ctx, &mut ancestor);
+        let patches = patch::diff(&driver_ctx, &mut curr, &mut ancestor);
+        patch::apply(&mut dom, patches).unwrap();
+        assert_eq!(
+            render_to_string(&mut dom),
+            render_to_string(&mut curr),
+            "{:?} -> {:?}",
+            ancestor_items,
+            curr_items
+        );
+    }
+}
+
+#[test]
+fn keyed_items_are_moved_as_little_as_possible() {
+    let driver_ctx = DriverCtx::new(HtmlDriver);
+    let items = [(1, 1), (2, 2), (3, 1), (4, 1)];
+    let patches = patch::diff(&driver_ctx, &mut keyed(&items), &mut keyed(&items));
+    assert!(patches.is_empty());
+
+    // Moving the item with two nodes moves each of them once.
+    let mut ancestor = keyed(&items);
+    let mut curr = keyed(&[(1, 1), (3, 1), (4, 1), (2, 2)]);
+    let mut dom = to_dom(&driver_ctx, &mut ancestor);
+    let patches = patch::diff(&driver_ctx, &mut curr, &mut ancestor);
+    match &patches[..] {
+        [Patch::MoveNode { key: first, .. }, Patch::MoveNode { key: second, .. }]
+            if *first == Key::U64(2) && *second == Key::U64(2) => {}
+        patches => panic!("expected two moves, got {:?}", patches),
+    }
+    patch::apply(&mut dom, patches).unwrap();
+    assert_eq!(render_to_string(&mut dom), "1.0 3.0 4.0 2.0 2.1 ");
+}
+
+#[test]
+fn keys_can_be_sent_to_other_threads() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Key>();
+    let key = Key::from(vec![0xab, 0x01]);
+    let sent = std::thread::spawn(move || key).join().unwrap();
+    assert_eq!(sent.to_string(), "bytes:ab01");
+}
+
+#[test]
+fn keys_of_different_variants_differ() {
+    assert_ne!(Key::U64(1), Key::I64(1));
+    assert_ne!(Key::from("1"), Key::from(1u64));
+    assert_ne!(Key::from(vec![1]), Key::U64(1));
+    assert_eq!(Key::from(1u32), Key::U64(1));
+    assert_eq!(Key::from(-1), Key::I64(-1));
+
+    assert_eq!(Key::U64(1).to_string(), "u64:1");
+    assert_eq!(Key::I64(1).to_string(), "i64:1");
+    assert_eq!(Key::from("1").to_string(), "str:1");
+}
@@ -0,0 +1,139 @@
+#![cfg(feature = "markdown")]
+
+use vdom::{
+    html::{render_to_string, HtmlDriver},
+    vdom::{
+        dom::{
+            from_markdown, from_markdown_limited, from_markdown_unsanitized, DomNode, Limit,
+            LimitError, Limits,
+        },
+        path::Path,
+    },
+};
+
+fn markdown(markdown: &str) -> String {
+    let mut nodes = from_markdown::<HtmlDriver>(markdown);
+    render_to_string(&mut nodes)
+}
+
+fn limited(markdown: &str, limits: Limits) -> Result<Vec<DomNode<HtmlDriver>>, LimitError> {
+    from_markdown_limited(markdown, limits)
+}
+
+#[test]
+fn headings_lists_and_links() {
+    let cases = [
+        (
+            "# Title\n\n## Sub ##\n####### no",
+            "<h1>Title</h1><h2>Sub</h2><p>####### no</p>",
+        ),
+        (
+            "a *b* **c** ***d*** _e_ snake_case_x `co*de*`\nnext  \nline",
+            "<p>a <em>b</em> <strong>c</strong> <strong><em>d</em></strong> <em>e</em> \
+             snake_case_x <code>co*de*</code>\nnext<br>line</p>",
+        ),
+        (
+            "- a\n- b\n  - c\n- d",
+            "<ul><li>a</li><li>b<ul><li>c</li></ul></li><li>d</li></ul>",
+        ),
+        (
+            "3. x\n4. y\n\n5. z",
+            r#"<ol start="3"><li><p>x</p></li><li><p>y</p></li><li><p>z</p></li></ol>"#,
+        ),
+        (
+            r#"[a *b*](http://x.y/(1) "T") ![i](/p.png) <https://q.r> <m@x.io>"#,
+            r#"<p><a href="http://x.y/(1)" title="T">a <em>b</em></a> <img src="/p.png" alt="i"> <a href="https://q.r">https://q.r</a> <a href="mailto:m@x.io">m@x.io</a></p>"#,
+        ),
+        ("[x](javascript:alert(1))", "<p><a>x</a></p>"),
+        (
+            "> q\nlazy\n\n---\n```rust\nfn x() {}\n```\n\n    code\n<b>raw</b> \\*no\\*",
+            "<blockquote><p>q\nlazy</p></blockquote><hr><pre><code>fn x() {}\n</code></pre>\
+             <pre><code>code\n</code></pre><p>&lt;b&gt;raw&lt;/b&gt; *no*</p>",
+        ),
+        (
+            "para\n- item\n2. not",
+            "<p>para</p><ul><li>item\n2. not</li></ul>",
+        ),
+    ];
+    for (markdown_text, html) in &cases {
+        assert_eq!(markdown(markdown_text), *html, "{}", markdown_text);
+    }
+    let mut nodes = from_markdown_unsanitized::<HtmlDriver>("[x](javascript:y)");
+    assert_eq!(
+        render_to_string(&mut nodes),
+        r#"<p><a href="javascript:y">x</a></p>"#
+    );
+}
+
+#[test]
+fn limited_rejects_deep_nesting_at_the_limit() {
+    let limits = Limits {
+        max_depth: Some(32),
+        ..Limits::default()
+    };
+    let deep = [
+        ">".repeat(10_000),
+        format!("{}x{}", "*".repeat(10_000), "*".repeat(10_000)),
+        format!("{}x{}", "[".repeat(10_000), "](u)".repeat(10_000)),
+        "- ".repeat(10_000) + "x",
+    ];
+    for markdown in &deep {
+        let err = limited(markdown, limits).unwrap_err();
+        assert_eq!(err.limit, Limit::Depth);
+        assert_eq!(err.path.indices().len(), 33);
+    }
+
+    // 30 quotes, the paragraph and its text.
+    let shallow = ">".repeat(30) + " q";
+    let mut nodes = limited(&shallow, limits).unwrap();
+    assert_eq!(
+        render_to_string(&mut nodes),
+        "<blockquote>".repeat(30) + "<p>q</p>" + &"</blockquote>".repeat(30)
+    );
+}
+
+#[test]
+fn limited_checks_text_and_attr_lengths() {
+    let limits = Limits {
+        max_text_len: Some(8),
+        ..Limits::default()
+    };
+    assert!(limited("# short\n\n*emphasis*", limits).is_ok());
+    assert_eq!(
+        limited("# short\n\n*too long text*", limits).unwrap_err(),
+        LimitError {
+            limit: Limit::TextLen,
+            path: Path::root().child(1).child(0).child(0),
+        }
+    );
+    assert_eq!(
+        limited("[a](https://a.long/url)", limits).unwrap_err(),
+        LimitError {
+            limit: Limit::TextLen,
+            path: Path::root().child(0).child(0),
+        }
+    );
+}
+
+#[test]
+fn limited_checks_children_and_total_nodes() {
+    let children = Limits {
+        max_children: Some(2),
+        ..Limits::default()
+    };
+    assert!(limited("- a\n- b", children).is_ok());
+    assert_eq!(
+        limited("- a\n- b\n- c", children).unwrap_err(),
+        LimitError {
+            limit: Limit::Children,
+            path: Path::root().child(0),
+        }
+    );
+
+    let total = Limits {
+        max_total_nodes: Some(3),
+        ..Limits::default()
+    };
+    assert!(limited("# a\n\nb", total).is_err());
+    assert!(limited("# a", total).is_ok());
+}
@@ -0,0 +1,76 @@
+use vdom::{
+    driver::DriverCtx,
+    html::{render_to_string, HtmlDriver},
+    vdom::{
+        dom::to_dom,
+        node::{meta_at, Comp, CompCtx, CompNode, TagStatic, TextStatic, Unmounted, WithMeta},
+        patch::diff,
+        path::Path,
+    },
+};
+
+#[derive(Clone, PartialEq, Eq)]
+struct Labelled;
+
+impl Comp<HtmlDriver> for Labelled {
+    type Input = ();
+    type Rendered = WithMeta<&'static str, TextStatic<HtmlDriver>>;
+
+    fn new(_input: &(), _ctx: CompCtx<HtmlDriver, Labelled>) -> Labelled {
+        Labelled
+    }
+
+    fn render(&self, _input: &()) -> Self::Rendered {
+        WithMeta::new("label", TextStatic::new("x"))
+    }
+}
+
+fn path(s: &str) -> Path {
+    s.parse().unwrap()
+}
+
+#[test]
+fn finds_innermost_meta() {
+    let mk = |m: u32| {
+        (
+            TagStatic::<HtmlDriver, _, _>::new(
+                "ul",
+                (),
+                (
+                    WithMeta::new("a", TagStatic::new("li", (), TextStatic::new("x"))),
+                    TagStatic::new("li", (), ()),
+                ),
+            ),
+            WithMeta::new(m, TextStatic::new("t")),
+        )
+    };
+    let mut node = mk(1);
+    let find = |node: &mut _, p| meta_at::<HtmlDriver, _, &str>(node, &path(p)).unwrap();
+    assert_eq!(find(&mut node, "/0/0").as_deref(), Some(&"a"));
+    assert_eq!(find(&mut node, "/0/0/0"), None);
+    assert_eq!(find(&mut node, "/0/1"), None);
+    assert_eq!(find(&mut node, "/1"), None);
+    assert_eq!(
+        meta_at::<HtmlDriver, _, u32>(&mut node, &path("/1")).map(|m| m.as_deref().copied()),
+        Ok(Some(1))
+    );
+    assert_eq!(render_to_string(&mut node), "<ul><li>x</li><li></li></ul>t");
+    let mut next = mk(2);
+    assert!(diff(&DriverCtx::new(HtmlDriver), &mut next, &mut node).is_empty());
+}
+
+#[test]
+fn unmounted_components_are_an_error() {
+    let mut node = TagStatic::new("p", (), CompNode::<HtmlDriver, Labelled>::new(()));
+    assert_eq!(
+        meta_at::<HtmlDriver, _, &str>(&mut node, &path("/0/0")),
+        Err(Unmounted)
+    );
+    to_dom(&DriverCtx::new(HtmlDriver), &mut node);
+    assert_eq!(
+        meta_at::<HtmlDriver, _, &str>(&mut node, &path("/0/0"))
+            .unwrap()
+            .as_deref(),
+        Some(&"label")
+    );
+}
@@ -0,0 +1,506 @@
+use std::convert::Infallible;
+use vdom::{
+    driver::DriverCtx,
+    html::{render_to_string, HtmlDriver},
+    vdom::{
+        dom::{to_dom, DomNode},
+        node::{
+            Choice3, Comp, CompCtx, CompNode, Keyed, Node, NodeDiffer, Shared, Tag, TagStatic,
+            TextDyn, TextStatic, Unmounted,
+        },
+        patch::{self, Patch},
+        path::Path,
+    },
+};
+
+type Text = TextStatic<HtmlDriver>;
+
+#[derive(Clone, PartialEq, Eq)]
+struct Pair;
+
+impl Comp<HtmlDriver> for Pair {
+    type Input = ();
+    type Rendered = (TextStatic<HtmlDriver>, TextStatic<HtmlDriver>);
+
+    fn new(_input: &(), _ctx: CompCtx<HtmlDriver, Pair>) -> Pair {
+        Pair
+    }
+
+    fn render(&self, _input: &()) -> Self::Rendered {
+        (TextStatic::new("p"), TextStatic::new("q"))
+    }
+}
+
+#[test]
+fn counts_nodes_as_visit_indexes_them() {
+    let mut list: ((Text, Option<Text>), Vec<Text>) = (
+        (TextStatic::new("a"), None),
+        vec![TextStatic::new("b"), TextStatic::new("c")],
+    );
+    assert_eq!(list.node_count(), Ok(3));
+    assert_eq!(Node::<HtmlDriver>::node_count(&mut ()), Ok(0));
+    let mut tag = TagStatic::new("ul", (), list);
+    assert_eq!(tag.children_len(), Ok(3));
+    assert_eq!(tag.node_count(), Ok(1));
+    let mut empty = TagStatic::<HtmlDriver, (), ()>::new("br", (), ());
+    assert_eq!(empty.children_len(), Ok(0));
+}
+
+#[test]
+fn counting_unmounted_components_is_an_error() {
+    let mut tag = TagStatic::new(
+        "p",
+        (),
+        (TextStatic::new("a"), CompNode::<HtmlDriver, Pair>::new(())),
+    );
+    assert_eq!(tag.children_len(), Err(Unmounted));
+    to_dom(&DriverCtx::new(HtmlDriver), &mut tag);
+    assert_eq!(tag.children_len(), Ok(3));
+}
+
+/// A differ outside of the crate, implementing only the methods it must.
+struct ChangedTexts(Vec<String>);
+
+impl NodeDiffer<HtmlDriver> for ChangedTexts {
+    type Err = Infallible;
+
+    fn on_node_added<N>(&mut self, index: &mut usize, curr: &mut N) -> Result<(), Infallible>
+    where
+        N: Node<HtmlDriver>,
+    {
+        *index += curr.node_count().unwrap();
+        Ok(())
+    }
+
+    fn on_node_removed<N>(
+        &mut self,
+        _curr_index: usize,
+        ancestor_index: &mut usize,
+        ancestor: &mut N,
+    ) -> Result<(), Infallible>
+    where
+        N: Node<HtmlDriver>,
+    {
+        *ancestor_index += ancestor.node_count().unwrap();
+        Ok(())
+    }
+
+    fn on_tag<T>(
+        &mut self,
+        _curr_index: usize,
+        _ancestor_index: usize,
+        curr: &mut T,
+        ancestor: &mut T,
+    ) -> Result<(), Infallible>
+    where
+        T: Tag<HtmlDriver>,
+    {
+        curr.diff_children(ancestor, self)
+    }
+
+    fn on_text<T>(
+        &mut self,
+        _curr_index: usize,
+        _ancestor_index: usize,
+        curr: &mut T,
+        ancestor: &mut T,
+    ) -> Result<(), Infallible>
+    where
+        T: vdom::vdom::node::Text<HtmlDriver>,
+    {
+        if curr.get() != ancestor.get() {
+            self.0.push(curr.get().to_string());
+        }
+        Ok(())
+    }
+
+    fn on_comp<C>(
+        &mut self,
+        _curr_index: &mut usize,
+        _ancestor_index: &mut usize,
+        _curr: &mut CompNode<HtmlDriver, C>,
+        _ancestor: &mut CompNode<HtmlDriver, C>,
+    ) -> Result<(), Infallible>
+    where
+        C: Comp<HtmlDriver>,
+    {
+        Ok(())
+    }
+}
+
+#[test]
+fn differs_need_not_handle_moves_or_invariant_violations() {
+    let list = |first: &'static str, items: Vec<(u32, &'static str)>| {
+        let items = items
+            .into_iter()
+            .map(|(key, text)| (key, TextStatic::new(text)));
+        TagStatic::<HtmlDriver, _, ()>::new("p", (), (TextStatic::new(first), Keyed::new(items)))
+    };
+    let mut ancestor = list("x", vec![(1, "a"), (2, "b")]);
+    let mut curr = list("y", vec![(2, "c"), (1, "a")]);
+    let mut differ = ChangedTexts(Vec::new());
+    match curr.diff(&mut 0, &mut 0, &mut ancestor, &mut differ) {
+        Ok(()) => {}
+        Err(never) => match never {},
+    }
+    // The static text that changed is diffed like a dynamic one, with the
+    // `strict` feature or without.
+    assert_eq!(differ.0, ["y", "c"]);
+}
+
+#[test]
+fn shared_subtrees_are_diffed_unless_the_same() {
+    let driver_ctx = DriverCtx::new(HtmlDriver);
+    let shared = Shared::new((TextDyn::new("a"), TextDyn::new("b")));
+    let mut ancestor = (shared.clone(), TextStatic::new("c"));
+    let mut curr = (shared, TextStatic::new("c"));
+    assert!(patch::diff(&driver_ctx, &mut curr, &mut ancestor).is_empty());
+
+    let mut curr = (
+        Shared::new((TextDyn::new("a"), TextDyn::new("x"))),
+        TextStatic::new("c"),
+    );
+    let mut dom = to_dom(&driver_ctx, &mut ancestor);
+    let patches = patch::diff(&driver_ctx, &mut curr, &mut ancestor);
+    assert_eq!(patches.len(), 1);
+    patch::apply(&mut dom, patches).unwrap();
+    assert_eq!(render_to_string(&mut dom), "axc");
+}
+
+/// Mounts the first render into a copy of the DOM through `NodeDiffer`.
+struct Mount {
+    driver_ctx: DriverCtx<HtmlDriver>,
+    dom: Vec<DomNode<HtmlDriver>>,
+}
+
+impl NodeDiffer<HtmlDriver> for Mount {
+    type Err = Infallible;
+
+    fn on_node_added<N>(&mut self, index: &mut usize, curr: &mut N) -> Result<(), Infallible>
+    where
+        N: Node<HtmlDriver>,
+    {
+        let added = to_dom(&self.driver_ctx, curr);
+        let start = *index;
+        *index += added.len();
+        self.dom.splice(start..start, added);
+        Ok(())
+    }
+
+    fn on_node_removed<N>(
+        &mut self,
+        _curr_index: usize,
+        _ancestor_index: &mut usize,
+        _ancestor: &mut N,
+    ) -> Result<(), Infallible>
+    where
+        N: Node<HtmlDriver>,
+    {
+        panic!("nothing to remove on the first render")
+    }
+
+    fn on_tag<T>(
+        &mut self,
+        _curr_index: usize,
+        _ancestor_index: usize,
+        _curr: &mut T,
+        _ancestor: &mut T,
+    ) -> Result<(), Infallible>
+    where
+        T: Tag<HtmlDriver>,
+    {
+        panic!("nothing to diff on the first render")
+    }
+
+    fn on_text<T>(
+        &mut self,
+        _curr_index: usize,
+        _ancestor_index: usize,
+        _curr: &mut T,
+        _ancestor: &mut T,
+    ) -> Result<(), Infallible>
+    where
+        T: vdom::vdom::node::Text<HtmlDriver>,
+    {
+        panic!("nothing to diff on the first render")
+    }
+
+    fn on_comp<C>(
+        &mut self,
+        _curr_index: &mut usize,
+        _ancestor_index: &mut usize,
+        _curr: &mut CompNode<HtmlDriver, C>,
+        _ancestor: &mut CompNode<HtmlDriver, C>,
+    ) -> Result<(), Infallible>
+    where
+        C: Comp<HtmlDriver>,
+    {
+        panic!("nothing to diff on the first render")
+    }
+}
+
+#[test]
+fn diff_new_mounts_what_visiting_does() {
+    let driver_ctx = DriverCtx::new(HtmlDriver);
+    let page = |title: &'static str| {
+        (
+            TagStatic::new("h1", (), TextDyn::new(title)),
+            (
+                vec![TextStatic::new("a"), TextStatic::new("b")],
+                TagStatic::new("p", (), CompNode::<HtmlDriver, Pair>::new(())),
+            ),
+        )
+    };
+
+    let mut mount = Mount {
+        driver_ctx: driver_ctx.clone(),
+        dom: Vec::new(),
+    };
+    let mut ancestor = page("x");
+    let mut index = 0;
+    match ancestor.diff_new(&mut index, &mut mount) {
+        Ok(()) => {}
+        Err(never) => match never {},
+    }
+    let mut visited = to_dom(&driver_ctx, &mut page("x"));
+    assert_eq!(index, 4);
+    assert_eq!(
+        render_to_string(&mut mount.dom),
+        render_to_string(&mut visited)
+    );
+    assert_eq!(render_to_string(&mut mount.dom), "<h1>x</h1>ab<p>pq</p>");
+
+    // The components were mounted, so updates diff against the first render.
+    let mut curr = page("y");
+    let patches = patch::diff(&driver_ctx, &mut curr, &mut ancestor);
+    assert_eq!(patches.len(), 1);
+    patch::apply(&mut mount.dom, patches).unwrap();
+    assert_eq!(render_to_string(&mut mount.dom), "<h1>y</h1>ab<p>pq</p>");
+}
+
+/// Fails on the first invariant violation, and otherwise records the texts
+/// diffed and the nodes added and removed.
+struct Violations(Vec<String>);
+
+impl NodeDiffer<HtmlDriver> for Violations {
+    type Err = (usize, String, String);
+
+    fn on_node_added<N>(&mut self, index: &mut usize, curr: &mut N) -> Result<(), Self::Err>
+    where
+        N: Node<HtmlDriver>,
+    {
+        self.0.push(format!("+{}", index));
+        *index += curr.node_count().unwrap();
+        Ok(())
+    }
+
+    fn on_node_removed<N>(
+        &mut self,
+        curr_index: usize,
+        ancestor_index: &mut usize,
+        ancestor: &mut N,
+    ) -> Result<(), Self::Err>
+    where
+        N: Node<HtmlDriver>,
+    {
+        self.0.push(format!("-{}", curr_index));
+        *ancestor_index += ancestor.node_count().unwrap();
+        Ok(())
+    }
+
+    fn on_invariant_violation(
+        &mut self,
+        curr_index: usize,
+        expected: &str,
+        found: &str,
+    ) -> Result<(), Self::Err> {
+        Err((curr_index, expected.to_string(), found.to_string()))
+    }
+
+    fn on_tag<T>(
+        &mut self,
+        _curr_index: usize,
+        _ancestor_index: usize,
+        curr: &mut T,
+        ancestor: &mut T,
+    ) -> Result<(), Self::Err>
+    where
+        T: Tag<HtmlDriver>,
+    {
+        curr.diff_children(ancestor, self)
+    }
+
+    fn on_text<T>(
+        &mut self,
+        _curr_index: usize,
+        _ancestor_index: usize,
+        curr: &mut T,
+        ancestor: &mut T,
+    ) -> Result<(), Self::Err>
+    where
+        T: vdom::vdom::node::Text<HtmlDriver>,
+    {
+        if curr.get() != ancestor.get() {
+            self.0.push(curr.get().to_string());
+        }
+        Ok(())
+    }
+
+    fn on_comp<C>(
+        &mut self,
+        _curr_index: &mut usize,
+        _ancestor_index: &mut usize,
+        _curr: &mut CompNode<HtmlDriver, C>,
+        _ancestor: &mut CompNode<HtmlDriver, C>,
+    ) -> Result<(), Self::Err>
+    where
+        C: Comp<HtmlDriver>,
+    {
+        Ok(())
+    }
+}
+
+#[test]
+fn mismatched_static_text_is_diffed_or_reported() {
+    let driver_ctx = DriverCtx::new(HtmlDriver);
+    let list = |text| (TextStatic::new("a"), TextStatic::new(text));
+    let mut ancestor = list("b");
+    let mut curr = list("c");
+
+    // Patches can't fail, so they are right with the `strict` feature or
+    // without.
+    let mut dom = to_dom(&driver_ctx, &mut ancestor);
+    let patches = patch::diff(&driver_ctx, &mut curr, &mut ancestor);
+    patch::apply(&mut dom, patches).unwrap();
+    assert_eq!(render_to_string(&mut dom), "ac");
+
+    let mut differ = Violations(Vec::new());
+    let res = curr.diff(&mut 0, &mut 0, &mut ancestor, &mut differ);
+    #[cfg(feature = "strict")]
+    assert_eq!(res, Err((1, "b".to_string(), "c".to_string())));
+    #[cfg(not(feature = "strict"))]
+    {
+        assert_eq!(res, Ok(()));
+        assert_eq!(differ.0, ["c"]);
+    }
+}
+
+#[test]
+fn mismatched_static_tags_are_replaced_or_reported() {
+    let driver_ctx = DriverCtx::new(HtmlDriver);
+    let list = |tag| {
+        TagStatic::<HtmlDriver, _, ()>::new(
+            "div",
+            (),
+            (
+                TextStatic::new("a"),
+                TagStatic::new(tag, (), TextStatic::new("b")),
+            ),
+        )
+    };
+    let mut ancestor = list("p");
+    let mut curr = list("span");
+
+    let mut dom = to_dom(&driver_ctx, &mut ancestor);
+    let patches = patch::diff(&driver_ctx, &mut curr, &mut ancestor);
+    patch::apply(&mut dom, patches).unwrap();
+    assert_eq!(render_to_string(&mut dom), "<div>a<span>b</span></div>");
+
+    let mut differ = Violations(Vec::new());
+    let res = curr.diff(&mut 0, &mut 0, &mut ancestor, &mut differ);
+    #[cfg(feature = "strict")]
+    assert_eq!(res, Err((1, "p".to_string(), "span".to_string())));
+    #[cfg(not(feature = "strict"))]
+    {
+        assert_eq!(res, Ok(()));
+        assert_eq!(differ.0, ["-1", "+1"]);
+    }
+}
+
+type Choice = Choice3<
+    TextDyn<HtmlDriver>,
+    TagStatic<HtmlDriver, TextDyn<HtmlDriver>, ()>,
+    TagStatic<HtmlDriver, (), ()>,
+>;
+
+fn choice(variant: usize, text: &'static str) -> Choice {
+    match variant {
+        0 => Choice3::A(TextDyn::new(text)),
+        1 => Choice3::B(TagStatic::new("p", (), TextDyn::new(text))),
+        _ => Choice3::C(TagStatic::new("hr", (), ())),
+    }
+}
+
+#[test]
+fn other_choice_variants_are_replaced() {
+    let driver_ctx = DriverCtx::new(HtmlDriver);
+    let mut dom = to_dom(&driver_ctx, &mut choice(0, "a"));
+    let patches = patch::diff(&driver_ctx, &mut choice(1, "a"), &mut choice(0, "a"));
+    match &patches[..] {
+        [Patch::RemoveNode { path }, Patch::InsertNode { path: inserted, .. }] => {
+            assert_eq!(path, &Path::from(vec![0]));
+            assert_eq!(inserted, &Path::from(vec![0]));
+        }
+        patches => panic!("expected the node to be replaced, got {:?}", patches),
+    }
+    patch::apply(&mut dom, patches).unwrap();
+    assert_eq!(render_to_string(&mut dom), "<p>a</p>");
+
+    let patches = patch::diff(&driver_ctx, &mut choice(2, "a"), &mut choice(1, "a"));
+    patch::apply(&mut dom, patches).unwrap();
+    assert_eq!(render_to_string(&mut dom), "<hr>");
+}
+
+#[test]
+fn the_same_choice_variant_is_diffed() {
+    let driver_ctx = DriverCtx::new(HtmlDriver);
+    let patches = patch::diff(&driver_ctx, &mut choice(1, "b"), &mut choice(1, "a"));
+    match &patches[..] {
+        [Patch::SetText { path, text }] => {
+            assert_eq!(path, &Path::from(vec![0, 0]));
+            assert_eq!(text, "b");
+        }
+        patches => panic!("expected the text to be set, got {:?}", patches),
+    }
+    assert!(patch::diff(&driver_ctx, &mut choice(2, "b"), &mut choice(2, "a")).is_empty());
+}
+
+fn texts(texts: &[&'static str]) -> Vec<TextDyn<HtmlDriver>> {
+    texts.iter().map(|&text| TextDyn::new(text)).collect()
+}
+
+#[test]
+fn slices_diff_length_changes() {
+    let driver_ctx = DriverCtx::new(HtmlDriver);
+
+    // Borrowed from elsewhere, e.g. an arena.
+    let mut ancestor_texts = texts(&["a", "b", "c"]);
+    let mut curr_texts = texts(&["a", "x"]);
+    let mut ancestor = TagStatic::<HtmlDriver, _, ()>::new("p", (), &mut ancestor_texts[..]);
+    let mut curr = TagStatic::<HtmlDriver, _, ()>::new("p", (), &mut curr_texts[..]);
+    let mut dom = to_dom(&driver_ctx, &mut ancestor);
+    let patches = patch::diff(&driver_ctx, &mut curr, &mut ancestor);
+    assert_eq!(
+        format!("{:?}", patches),
+        format!(
+            "{:?}",
+            vec![
+                Patch::<HtmlDriver>::SetText {
+                    path: Path::from(vec![0, 1]),
+                    text: "x".to_string(),
+                },
+                Patch::RemoveNode {
+                    path: Path::from(vec![0, 2]),
+                },
+            ]
+        )
+    );
+    patch::apply(&mut dom, patches).unwrap();
+    assert_eq!(render_to_string(&mut dom), "<p>ax</p>");
+
+    let mut ancestor = texts(&["a", "b", "c"]).into_boxed_slice();
+    let mut curr = texts(&["b"]).into_boxed_slice();
+    let mut dom = to_dom(&driver_ctx, &mut ancestor);
+    patch::apply(&mut dom, patch::diff(&driver_ctx, &mut curr, &mut ancestor)).unwrap();
+    assert_eq!(render_to_string(&mut dom), "b");
+}
@@ -0,0 +1,126 @@
+#![cfg(feature = "rayon")]
+
+use vdom::{
+    html::{render_to_string, render_to_string_parallel, HtmlDriver, Sequential},
+    vdom::{
+        attr::AttrValue,
+        dom::{DomAttr, DomNode},
+        node::{TagStatic, TextDyn, TextStatic},
+    },
+};
+
+/// A linear congruential generator, so the trees are the same on each run.
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self, n: u64) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        (self.0 >> 33) % n
+    }
+}
+
+const TAGS: &[&str] = &[
+    "div", "p", "br", "script", "pre", "span", "textarea", "style",
+];
+const TEXTS: &[&str] = &["a<b", "x&y", "", "héllo \"q\"", "</script>"];
+const MIN_SUBTREE_SIZES: &[usize] = &[0, 1, 2, 5, 17, 1000];
+
+fn random_tree(rng: &mut Lcg, depth: u32) -> DomNode<HtmlDriver> {
+    if depth == 0 || rng.next(3) == 0 {
+        return DomNode::text(TEXTS[rng.next(TEXTS.len() as u64) as usize]);
+    }
+    let attrs = if rng.next(2) == 0 {
+        vec![
+            DomAttr::new("title", "a\"b"),
+            DomAttr::new("hidden", AttrValue::True),
+        ]
+    } else {
+        vec![]
+    };
+    let children = (0..rng.next(5))
+        .map(|_| random_tree(rng, depth - 1))
+        .collect();
+    DomNode::tag(TAGS[rng.next(TAGS.len() as u64) as usize], attrs, children)
+}
+
+#[test]
+fn random_trees_render_as_sequentially() {
+    let mut rng = Lcg(42);
+    for _ in 0..300 {
+        let len = rng.next(12) as usize;
+        let mut node = TagStatic::<HtmlDriver, _, ()>::new(
+            TAGS[rng.next(TAGS.len() as u64) as usize],
+            (),
+            (0..len)
+                .map(|_| random_tree(&mut rng, 4))
+                .collect::<Vec<_>>(),
+        );
+        let expected = render_to_string(&mut node);
+        for &min_subtree_size in MIN_SUBTREE_SIZES {
+            assert_eq!(
+                render_to_string_parallel(&mut node, min_subtree_size),
+                expected
+            );
+        }
+    }
+}
+
+type Section = TagStatic<
+    HtmlDriver,
+    (
+        TagStatic<HtmlDriver, TextStatic<HtmlDriver>, ()>,
+        Vec<TagStatic<HtmlDriver, Option<TextDyn<HtmlDriver>>, ()>>,
+    ),
+    (),
+>;
+
+fn section(rng: &mut Lcg) -> Section {
+    let items = (0..rng.next(20))
+        .map(|i| {
+            let text = if i % 3 == 0 {
+                None
+            } else {
+                Some(TextDyn::new(TEXTS[rng.next(TEXTS.len() as u64) as usize]))
+            };
+            TagStatic::new("li", (), text)
+        })
+        .collect();
+    TagStatic::new(
+        "section",
+        (),
+        (TagStatic::new("h2", (), TextStatic::new("a & b")), items),
+    )
+}
+
+#[test]
+fn static_trees_render_as_sequentially() {
+    let mut rng = Lcg(7);
+    for _ in 0..50 {
+        let len = rng.next(30) as usize;
+        let mut page = TagStatic::<HtmlDriver, _, ()>::new(
+            "body",
+            (),
+            (
+                (0..len).map(|_| section(&mut rng)).collect::<Vec<_>>(),
+                TagStatic::<HtmlDriver, _, ()>::new(
+                    "script",
+                    (),
+                    (
+                        TextStatic::new("a < b"),
+                        Sequential(TextStatic::new("</p>")),
+                    ),
+                ),
+            ),
+        );
+        let expected = render_to_string(&mut page);
+        for &min_subtree_size in MIN_SUBTREE_SIZES {
+            assert_eq!(
+                render_to_string_parallel(&mut page, min_subtree_size),
+                expected
+            );
+        }
+    }
+}
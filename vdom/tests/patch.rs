@@ -0,0 +1,495 @@
+use vdom::{
+    driver::DriverCtx,
+    html::{render_to_string, HtmlDriver},
+    vdom::{
+        attr::{AttrDyn, AttrListEntry},
+        dom::{check_depth, replace_at, to_dom, DomNode, Limit},
+        key::Key,
+        node::{Comp, CompCtx, CompNode, TagStatic, TextDyn},
+        patch::{apply, coalesce, diff, diff_at, diff_limited, diff_spliced, Patch, ScopeError},
+        path::{Path, PathError},
+    },
+};
+
+fn deep(depth: usize) -> Vec<DomNode<HtmlDriver>> {
+    let mut node = DomNode::text("x");
+    for _ in 0..depth {
+        node = DomNode::tag("div", vec![], vec![node]);
+    }
+    vec![node]
+}
+
+#[derive(Clone, PartialEq, Eq)]
+struct Counter {
+    count: usize,
+}
+
+impl Comp<HtmlDriver> for Counter {
+    type Input = ();
+    type Rendered = TextDyn<HtmlDriver>;
+
+    fn new(_input: &(), _ctx: CompCtx<HtmlDriver, Counter>) -> Counter {
+        Counter { count: 0 }
+    }
+
+    fn render(&self, _input: &()) -> TextDyn<HtmlDriver> {
+        TextDyn::new(self.count.to_string())
+    }
+}
+
+#[test]
+fn check_depth_stops_at_max_depth() {
+    let ctx = DriverCtx::new(HtmlDriver);
+    assert!(check_depth(&ctx, &mut deep(3), 4).is_ok());
+    let err = check_depth(&ctx, &mut deep(4), 4).unwrap_err();
+    assert_eq!(err.limit, Limit::Depth);
+    assert_eq!(err.path.indices(), &[0, 0, 0, 0, 0]);
+}
+
+fn list() -> DomNode<HtmlDriver> {
+    DomNode::tag(
+        "ul",
+        vec![],
+        vec![
+            DomNode::tag("li", vec![], vec![DomNode::text("a")]),
+            DomNode::tag("li", vec![], vec![DomNode::text("b")]),
+        ],
+    )
+}
+
+#[test]
+fn replace_at_returns_the_replaced_subtree() {
+    let mut tree = list();
+    let path = "/1/0".parse().unwrap();
+    let mut old = replace_at(&mut tree, &path, DomNode::text("c")).unwrap();
+    assert_eq!(render_to_string(&mut old), "b");
+    assert_eq!(render_to_string(&mut tree), "<ul><li>a</li><li>c</li></ul>");
+}
+
+#[test]
+fn replace_at_rejects_paths_out_of_bounds() {
+    let mut tree = list();
+    let err = replace_at(&mut tree, &"/2".parse().unwrap(), DomNode::text("c")).err();
+    assert_eq!(
+        err,
+        Some(PathError::OutOfBounds {
+            parent: Path::root(),
+            index: 2,
+        })
+    );
+    let err = replace_at(&mut tree, &"/0/0/0".parse().unwrap(), DomNode::text("c")).err();
+    assert_eq!(err, Some(PathError::NotAnElement("/0/0".parse().unwrap())));
+    assert_eq!(render_to_string(&mut tree), "<ul><li>a</li><li>b</li></ul>");
+}
+
+#[test]
+fn replace_at_the_root_replaces_the_tree() {
+    let mut tree = list();
+    let mut old = replace_at(&mut tree, &Path::root(), DomNode::text("c")).unwrap();
+    assert_eq!(render_to_string(&mut old), "<ul><li>a</li><li>b</li></ul>");
+    assert_eq!(render_to_string(&mut tree), "c");
+}
+
+#[test]
+fn diff_limited_rejects_deep_trees() {
+    let ctx = DriverCtx::new(HtmlDriver);
+    assert_eq!(
+        diff_limited(&ctx, &mut deep(3), &mut deep(2), 4)
+            .unwrap()
+            .len(),
+        2
+    );
+    // Both diffed and added nodes are checked.
+    assert!(diff_limited(&ctx, &mut deep(5), &mut deep(5), 4).is_err());
+    assert!(diff_limited(&ctx, &mut deep(5), &mut deep(3), 4).is_err());
+    assert!(diff_limited(&ctx, &mut deep(5), &mut vec![], 4).is_err());
+}
+
+#[test]
+fn diff_limited_keeps_component_state() {
+    let ctx = DriverCtx::new(HtmlDriver);
+    let mut ancestor = CompNode::<HtmlDriver, Counter>::new(());
+    vdom::vdom::dom::to_dom(&ctx, &mut ancestor);
+    ancestor.comp_ctx().unwrap().instance_mut().comp.count = 5;
+
+    let mut curr = CompNode::<HtmlDriver, Counter>::new(());
+    let patches = diff_limited(&ctx, &mut curr, &mut ancestor, 4).unwrap();
+    assert!(curr.comp_ctx() == ancestor.comp_ctx());
+    match patches.as_slice() {
+        [Patch::SetText { text, .. }] => assert_eq!(text, "5"),
+        _ => panic!("expected one SetText"),
+    }
+}
+
+#[test]
+fn noscript_children_are_copied_but_not_patched() {
+    let ctx = DriverCtx::new(HtmlDriver);
+    let page = |msg| TagStatic::new("noscript", (), TagStatic::new("p", (), TextDyn::new(msg)));
+
+    let mut dom = to_dom(&ctx, &mut page("Enable JS"));
+    assert_eq!(
+        render_to_string(&mut dom),
+        "<noscript><p>Enable JS</p></noscript>"
+    );
+    // Drivers leave the children to the browser, see `Tag::skips_diff`.
+    assert!(diff(&ctx, &mut page("b"), &mut page("a")).is_empty());
+}
+
+#[test]
+fn iframes_loading_another_document_are_replaced() {
+    let ctx = DriverCtx::new(HtmlDriver);
+    let frame = |src, title| {
+        TagStatic::new(
+            "iframe",
+            (
+                AttrListEntry(AttrDyn::new("src", src)),
+                AttrListEntry(AttrDyn::new("title", title)),
+            ),
+            (),
+        )
+    };
+
+    let patches = diff(&ctx, &mut frame("/a", "new"), &mut frame("/a", "old"));
+    match &patches[..] {
+        [Patch::SetAttr { name, .. }] => assert_eq!(name, "title"),
+        patches => panic!("expected the title to be set, got {:?}", patches),
+    }
+
+    let mut dom = to_dom(&ctx, &mut frame("/a", "old"));
+    let patches = diff(&ctx, &mut frame("/b", "old"), &mut frame("/a", "old"));
+    match &patches[..] {
+        [Patch::RemoveNode { .. }, Patch::InsertNode { .. }] => {}
+        patches => panic!("expected the iframe to be replaced, got {:?}", patches),
+    }
+    apply(&mut dom, patches).unwrap();
+    assert_eq!(
+        render_to_string(&mut dom),
+        "<iframe src=\"/b\" title=\"old\"></iframe>"
+    );
+}
+
+#[test]
+fn scripts_are_not_patched() {
+    let ctx = DriverCtx::new(HtmlDriver);
+    let script = |code| TagStatic::new("script", (), TextDyn::new(code));
+    assert!(diff(&ctx, &mut script("b()"), &mut script("a()")).is_empty());
+}
+
+type Item = TagStatic<HtmlDriver, TextDyn<HtmlDriver>, AttrListEntry<AttrDyn<HtmlDriver>>>;
+
+/// A list of items titled and labelled by `items`, next to a text.
+fn items(
+    items: &[(&'static str, &'static str)],
+) -> (TagStatic<HtmlDriver, Vec<Item>, ()>, TextDyn<HtmlDriver>) {
+    let items = items
+        .iter()
+        .map(|&(title, text)| {
+            TagStatic::new(
+                "li",
+                AttrListEntry(AttrDyn::new("title", title)),
+                TextDyn::new(text),
+            )
+        })
+        .collect();
+    (TagStatic::new("ul", (), items), TextDyn::new("end"))
+}
+
+#[test]
+fn diff_at_only_diffs_the_subtree_at_the_path() {
+    let ctx = DriverCtx::new(HtmlDriver);
+    let mut ancestor = items(&[("a", "1"), ("b", "2")]);
+    let mut curr = items(&[("x", "3"), ("y", "4")]);
+    let mut dom = to_dom(&ctx, &mut ancestor);
+    let patches = diff_at(&ctx, &mut curr, &mut ancestor, &Path::from(vec![0, 1])).unwrap();
+    let paths = patches
+        .iter()
+        .map(|patch| patch.path().indices().to_vec())
+        .collect::<Vec<_>>();
+    assert_eq!(paths, [vec![0, 1], vec![0, 1, 0]]);
+    apply(&mut dom, patches).unwrap();
+    assert_eq!(
+        render_to_string(&mut dom),
+        "<ul><li title=\"a\">1</li><li title=\"y\">4</li></ul>end"
+    );
+}
+
+#[test]
+fn diff_at_skips_nodes_added_after_the_path() {
+    let ctx = DriverCtx::new(HtmlDriver);
+    let mut ancestor = items(&[("a", "1")]);
+    to_dom(&ctx, &mut ancestor);
+    let mut curr = items(&[("a", "2"), ("b", "3")]);
+    let patches = diff_at(&ctx, &mut curr, &mut ancestor, &Path::from(vec![0, 0, 0])).unwrap();
+    match &patches[..] {
+        [Patch::SetText { path, text }] => {
+            assert_eq!(path.indices(), &[0, 0, 0]);
+            assert_eq!(text, "2");
+        }
+        patches => panic!("expected the text to be set, got {:?}", patches),
+    }
+
+    // Removing the item at the path replaces what the path addresses.
+    let mut curr = items(&[]);
+    assert_eq!(
+        diff_at(&ctx, &mut curr, &mut ancestor, &Path::from(vec![0, 0, 0])).unwrap_err(),
+        ScopeError::Changed(Path::from(vec![0, 0]))
+    );
+}
+
+#[test]
+fn diff_at_rejects_paths_not_in_the_ancestor() {
+    let ctx = DriverCtx::new(HtmlDriver);
+    let mut ancestor = items(&[("a", "1")]);
+    to_dom(&ctx, &mut ancestor);
+    let mut curr = items(&[("a", "1")]);
+    for &path in &[&[0, 1][..], &[1, 0], &[2]] {
+        let path = Path::from(path.to_vec());
+        assert_eq!(
+            diff_at(&ctx, &mut curr, &mut ancestor, &path).unwrap_err(),
+            ScopeError::NotFound(path)
+        );
+    }
+}
+
+fn li(title: &'static str, text: &'static str) -> Item {
+    TagStatic::new(
+        "li",
+        AttrListEntry(AttrDyn::new("title", title)),
+        TextDyn::new(text),
+    )
+}
+
+#[test]
+fn paths_after_a_removal_are_where_the_node_is_then() {
+    let ctx = DriverCtx::new(HtmlDriver);
+    // The second of four items is removed and the text of the last, which is
+    // then the third, changed.
+    let mut ancestor = (
+        Some(li("a", "1")),
+        (Some(li("b", "2")), (Some(li("c", "3")), Some(li("d", "4")))),
+    );
+    let mut curr = (
+        Some(li("a", "1")),
+        (None, (Some(li("c", "3")), Some(li("d", "5")))),
+    );
+    let mut dom = to_dom(&ctx, &mut ancestor);
+    let patches = diff(&ctx, &mut curr, &mut ancestor);
+    match &patches[..] {
+        [Patch::RemoveNode { path }, Patch::SetText {
+            path: text_path, ..
+        }] => {
+            assert_eq!(path.indices(), &[1]);
+            assert_eq!(text_path.indices(), &[2, 0]);
+        }
+        patches => panic!("expected a removal and a text, got {:?}", patches),
+    }
+    apply(&mut dom, patches).unwrap();
+    assert_eq!(render_to_string(&mut dom), render_to_string(&mut curr));
+}
+
+#[test]
+fn paths_after_an_insert_are_where_the_node_is_then() {
+    let ctx = DriverCtx::new(HtmlDriver);
+    let mut ancestor = (vec![], li("a", "1"));
+    let mut curr = (vec![li("x", "2"), li("y", "3")], li("b", "1"));
+    let mut dom = to_dom(&ctx, &mut ancestor);
+    let patches = diff(&ctx, &mut curr, &mut ancestor);
+    match &patches[..] {
+        [Patch::InsertNode { .. }, Patch::InsertNode { .. }, Patch::SetAttr { path, name, .. }] => {
+            assert_eq!(path.indices(), &[2]);
+            assert_eq!(name, "title");
+        }
+        patches => panic!("expected two inserts and an attribute, got {:?}", patches),
+    }
+    apply(&mut dom, patches).unwrap();
+    assert_eq!(render_to_string(&mut dom), render_to_string(&mut curr));
+}
+
+#[test]
+fn single_insertions_are_small_splices() {
+    let ctx = DriverCtx::new(HtmlDriver);
+    let p = |text| TagStatic::<HtmlDriver, _, ()>::new("p", (), TextDyn::new(text));
+    let old = "The quick brown fox jumps over the lazy dog";
+    let new = "The quick brown fox jumps over the lazy dog!";
+
+    let mut dom = to_dom(&ctx, &mut p(old));
+    let patches = diff_spliced(&ctx, &mut p(new), &mut p(old));
+    match &patches[..] {
+        [Patch::SpliceText {
+            path,
+            start,
+            delete_len,
+            insert,
+        }] => {
+            assert_eq!(path, &Path::from(vec![0, 0]));
+            assert_eq!((*start, *delete_len, insert.as_str()), (43, 0, "!"));
+        }
+        patches => panic!("expected a splice, got {:?}", patches),
+    }
+    apply(&mut dom, patches).unwrap();
+    assert_eq!(render_to_string(&mut dom), format!("<p>{}</p>", new));
+
+    // Counted in chars, in the middle of the text.
+    let old = "Grüße, Welt";
+    let new = "Grüße, die Welt";
+    let patches = diff_spliced(&ctx, &mut p(new), &mut p(old));
+    match &patches[..] {
+        [Patch::SpliceText {
+            start,
+            delete_len,
+            insert,
+            ..
+        }] => assert_eq!((*start, *delete_len, insert.as_str()), (7, 0, "die ")),
+        patches => panic!("expected a splice, got {:?}", patches),
+    }
+
+    // Without a common prefix or suffix, the text is set.
+    match &diff_spliced(&ctx, &mut p("b"), &mut p("a"))[..] {
+        [Patch::SetText { text, .. }] => assert_eq!(text, "b"),
+        patches => panic!("expected the text to be set, got {:?}", patches),
+    }
+}
+
+fn path(s: &str) -> Path {
+    s.parse().unwrap()
+}
+
+fn set_text(p: &str, text: &str) -> Patch<HtmlDriver> {
+    Patch::SetText {
+        path: path(p),
+        text: text.to_string(),
+    }
+}
+
+fn splice(p: &str, start: usize, insert: &str) -> Patch<HtmlDriver> {
+    Patch::SpliceText {
+        path: path(p),
+        start,
+        delete_len: 0,
+        insert: insert.to_string(),
+    }
+}
+
+fn set_class(p: &str, class: &'static str) -> Patch<HtmlDriver> {
+    Patch::SetAttr {
+        path: path(p),
+        name: "class".to_string(),
+        value: class.into(),
+    }
+}
+
+/// The two paragraphs `coalesce` tests patch.
+fn paragraphs() -> Vec<DomNode<HtmlDriver>> {
+    vec![
+        DomNode::tag("p", vec![], vec![DomNode::text("a")]),
+        DomNode::tag("p", vec![], vec![DomNode::text("b")]),
+    ]
+}
+
+/// Checks that `coalesce` keeps the outcome of the patches built by
+/// `patches` and returns the patches it kept.
+fn coalesced<F>(patches: F) -> String
+where
+    F: Fn() -> Vec<Patch<HtmlDriver>>,
+{
+    let mut expected = paragraphs();
+    apply(&mut expected, patches()).unwrap();
+    let coalesced = coalesce(patches());
+    let debug = format!("{:?}", coalesced);
+    let mut dom = paragraphs();
+    apply(&mut dom, coalesced).unwrap();
+    assert_eq!(render_to_string(&mut dom), render_to_string(&mut expected));
+    debug
+}
+
+#[test]
+fn coalesce_drops_overwritten_writes() {
+    let patches = || {
+        vec![
+            set_text("/0/0", "x"),
+            set_class("/1", "x"),
+            set_text("/0/0", "y"),
+            set_class("/1", "y"),
+        ]
+    };
+    assert_eq!(
+        coalesced(patches),
+        format!("{:?}", vec![set_text("/0/0", "y"), set_class("/1", "y")])
+    );
+}
+
+#[test]
+fn coalesce_keeps_adjacent_splices() {
+    // Splices edit the text before them, so they all apply in turn.
+    let patches = || {
+        vec![
+            set_text("/0/0", "x"),
+            splice("/0/0", 1, "y"),
+            splice("/0/0", 2, "z"),
+        ]
+    };
+    assert_eq!(coalesced(patches), format!("{:?}", patches()));
+
+    // A later write drops them.
+    let overwritten = || {
+        let mut overwritten = patches();
+        overwritten.push(set_text("/0/0", "w"));
+        overwritten
+    };
+    assert_eq!(
+        coalesced(overwritten),
+        format!("{:?}", vec![set_text("/0/0", "w")])
+    );
+}
+
+#[test]
+fn coalesce_keeps_writes_to_moved_nodes() {
+    // After the move, `/0/0` is the text of the other paragraph.
+    let patches = || {
+        vec![
+            set_text("/0/0", "x"),
+            Patch::MoveNode {
+                path: path("/0"),
+                to: 1,
+                key: Key::U64(0),
+            },
+            set_text("/0/0", "y"),
+        ]
+    };
+    assert_eq!(coalesced(patches), format!("{:?}", patches()));
+}
+
+fn commented(comment: &'static str) -> Vec<DomNode<HtmlDriver>> {
+    vec![DomNode::tag(
+        "p",
+        vec![],
+        vec![DomNode::comment(comment), DomNode::text("a")],
+    )]
+}
+
+#[test]
+fn comments_are_patched_like_text() {
+    let ctx = DriverCtx::new(HtmlDriver);
+    let mut dom = commented("x");
+    let patches = diff(&ctx, &mut commented("y"), &mut commented("x"));
+    assert_eq!(
+        format!("{:?}", patches),
+        format!("{:?}", vec![set_text("/0/0", "y")])
+    );
+    apply(&mut dom, patches).unwrap();
+    assert_eq!(render_to_string(&mut dom), "<p><!--y-->a</p>");
+    apply(&mut dom, vec![splice("/0/0", 1, "z")]).unwrap();
+    assert_eq!(render_to_string(&mut dom), "<p><!--yz-->a</p>");
+
+    // A comment replacing text is a node of its own.
+    let mut text = vec![DomNode::tag("p", vec![], vec![DomNode::text("y")])];
+    let patches = diff(&ctx, &mut commented("y"), &mut text);
+    match &patches[..] {
+        [Patch::RemoveNode { .. }, Patch::InsertNode { node, .. }, Patch::InsertNode { .. }] => {
+            assert_eq!(format!("{:?}", node), "<!--\"y\"-->")
+        }
+        patches => panic!("expected the text to be replaced, got {:?}", patches),
+    }
+    apply(&mut text, patches).unwrap();
+    assert_eq!(render_to_string(&mut text), "<p><!--y-->a</p>");
+}
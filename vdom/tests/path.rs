@@ -0,0 +1,150 @@
+use std::convert::Infallible;
+use vdom::{
+    driver::DriverCtx,
+    html::HtmlDriver,
+    vdom::{
+        dom::to_dom,
+        node::{Comp, CompCtx, CompNode, NodeVisitor, Tag, TagStatic, Text, TextStatic},
+        path::{resolve, Path, PathIndex, PathParseError, ResolveError},
+    },
+};
+
+/// Records the tag or text it's handed.
+#[derive(Default)]
+struct Found(Option<String>);
+
+impl NodeVisitor<HtmlDriver> for Found {
+    type Err = Infallible;
+
+    fn on_tag<T>(&mut self, _index: usize, tag: &mut T) -> Result<(), Infallible>
+    where
+        T: Tag<HtmlDriver>,
+    {
+        self.0 = Some(format!("<{}>", tag.tag()));
+        Ok(())
+    }
+
+    fn on_text<T>(&mut self, _index: usize, text: &mut T) -> Result<(), Infallible>
+    where
+        T: Text<HtmlDriver>,
+    {
+        self.0 = Some(text.get().to_string());
+        Ok(())
+    }
+
+    fn on_comp<C>(
+        &mut self,
+        index: &mut usize,
+        comp: &mut CompNode<HtmlDriver, C>,
+    ) -> Result<(), Infallible>
+    where
+        C: Comp<HtmlDriver>,
+    {
+        comp.visit_rendered(index, self)
+    }
+}
+
+#[derive(Clone, PartialEq, Eq)]
+struct Pair;
+
+impl Comp<HtmlDriver> for Pair {
+    type Input = ();
+    type Rendered = (TextStatic<HtmlDriver>, TextStatic<HtmlDriver>);
+
+    fn new(_input: &(), _ctx: CompCtx<HtmlDriver, Pair>) -> Pair {
+        Pair
+    }
+
+    fn render(&self, _input: &()) -> Self::Rendered {
+        (TextStatic::new("p"), TextStatic::new("q"))
+    }
+}
+
+fn find<N>(node: &mut N, path: &[usize]) -> Result<Option<String>, ResolveError<Infallible>>
+where
+    N: vdom::vdom::node::Node<HtmlDriver>,
+{
+    let mut found = Found::default();
+    resolve(node, &Path::from(path.to_vec()), &mut found)?;
+    Ok(found.0)
+}
+
+#[test]
+fn resolves_flat_indices() {
+    let mut node = TagStatic::new(
+        "ul",
+        (),
+        (
+            (TextStatic::new("a"), TextStatic::new("b")),
+            (
+                TextStatic::new("c"),
+                TagStatic::new("li", (), TextStatic::new("d")),
+            ),
+        ),
+    );
+    assert_eq!(find(&mut node, &[0, 2]), Ok(Some("c".to_string())));
+    assert_eq!(find(&mut node, &[0, 3]), Ok(Some("<li>".to_string())));
+    assert_eq!(find(&mut node, &[0, 3, 0]), Ok(Some("d".to_string())));
+    assert_eq!(find(&mut node, &[0, 9]), Ok(None));
+    assert_eq!(find(&mut node, &[]), Ok(None));
+}
+
+#[test]
+fn unmounted_components_are_an_error() {
+    let mk = || {
+        TagStatic::new(
+            "p",
+            (),
+            (TextStatic::new("a"), CompNode::<HtmlDriver, Pair>::new(())),
+        )
+    };
+    let mut node = mk();
+    assert_eq!(
+        find(&mut node, &[0, 2]),
+        Err(ResolveError::Unmounted(Path::from(vec![0, 1])))
+    );
+    // Paths that end before the component don't render it.
+    assert_eq!(find(&mut node, &[0, 0]), Ok(Some("a".to_string())));
+
+    let mut node = mk();
+    to_dom(&DriverCtx::new(HtmlDriver), &mut node);
+    assert_eq!(find(&mut node, &[0, 2]), Ok(Some("q".to_string())));
+}
+
+#[test]
+fn paths_round_trip_through_bytes() {
+    let max = PathIndex::MAX as usize;
+    for indices in &[vec![], vec![0, 127, 128], vec![300, max]] {
+        let path = Path::from(indices.clone());
+        let bytes = path.to_bytes().unwrap();
+        assert_eq!(Path::from_bytes(&bytes), Ok(path));
+    }
+    assert_eq!(Path::from_bytes(&[0x80]), Err(PathParseError::Truncated));
+}
+
+#[test]
+fn indices_out_of_range_are_not_encoded() {
+    // Only larger than any index on platforms where `usize` is wider.
+    if let Some(index) = (PathIndex::MAX as usize).checked_add(1) {
+        assert_eq!(
+            Path::from(vec![0, index]).to_bytes(),
+            Err(PathParseError::OutOfRange { segment: 1 })
+        );
+    }
+}
+
+#[test]
+fn parse_errors_name_the_index() {
+    let err = "/0/x".parse::<Path>().unwrap_err();
+    assert_eq!(err, PathParseError::InvalidIndex { segment: 1 });
+    let err: Box<dyn std::error::Error> = Box::new(err);
+    assert_eq!(err.to_string(), "index 1 of path isn't a decimal number");
+    assert_eq!(
+        "/4294967296".parse::<Path>().unwrap_err().to_string(),
+        "index 0 of path is greater than 4294967295"
+    );
+    assert_eq!(
+        PathParseError::MissingSlash.to_string(),
+        "path doesn't start with `/`"
+    );
+}
@@ -0,0 +1,182 @@
+use std::{cell::Cell, convert::Infallible};
+use vdom::{
+    driver::DriverCtx,
+    html::HtmlDriver,
+    vdom::{
+        dom::to_dom,
+        node::{Comp, CompCtx, CompNode, Node, NodeDiffer, Tag, TagStatic, Text, TextDyn},
+        profile::ProfilingDiffer,
+    },
+};
+
+thread_local! {
+    static TICKS: Cell<f64> = Cell::new(0.0);
+}
+
+/// A clock that advances by a millisecond each time it's read.
+fn tick() -> f64 {
+    TICKS.with(|ticks| {
+        ticks.set(ticks.get() + 1.0);
+        ticks.get()
+    })
+}
+
+#[derive(Clone, PartialEq, Eq)]
+struct Greeting;
+
+impl Comp<HtmlDriver> for Greeting {
+    type Input = ();
+    type Rendered = TagStatic<HtmlDriver, TextDyn<HtmlDriver>, ()>;
+
+    fn new(_input: &(), _ctx: CompCtx<HtmlDriver, Greeting>) -> Greeting {
+        Greeting
+    }
+
+    fn render(&self, _input: &()) -> Self::Rendered {
+        TagStatic::new("p", (), TextDyn::new("hi"))
+    }
+}
+
+/// Notes the texts that changed and the components it diffed.
+#[derive(Default)]
+struct Changes {
+    texts: Vec<String>,
+    comps: usize,
+}
+
+impl NodeDiffer<HtmlDriver> for Changes {
+    type Err = Infallible;
+
+    fn on_node_added<N>(&mut self, index: &mut usize, curr: &mut N) -> Result<(), Infallible>
+    where
+        N: Node<HtmlDriver>,
+    {
+        *index += curr.node_count().unwrap();
+        Ok(())
+    }
+
+    fn on_node_removed<N>(
+        &mut self,
+        _curr_index: usize,
+        ancestor_index: &mut usize,
+        ancestor: &mut N,
+    ) -> Result<(), Infallible>
+    where
+        N: Node<HtmlDriver>,
+    {
+        *ancestor_index += ancestor.node_count().unwrap();
+        Ok(())
+    }
+
+    fn on_tag<T>(
+        &mut self,
+        _curr_index: usize,
+        _ancestor_index: usize,
+        curr: &mut T,
+        ancestor: &mut T,
+    ) -> Result<(), Infallible>
+    where
+        T: Tag<HtmlDriver>,
+    {
+        curr.diff_children(ancestor, self)
+    }
+
+    fn on_text<T>(
+        &mut self,
+        _curr_index: usize,
+        _ancestor_index: usize,
+        curr: &mut T,
+        ancestor: &mut T,
+    ) -> Result<(), Infallible>
+    where
+        T: Text<HtmlDriver>,
+    {
+        if curr.get() != ancestor.get() {
+            self.texts.push(curr.get().to_string());
+        }
+        Ok(())
+    }
+
+    fn on_comp<C>(
+        &mut self,
+        curr_index: &mut usize,
+        ancestor_index: &mut usize,
+        curr: &mut CompNode<HtmlDriver, C>,
+        ancestor: &mut CompNode<HtmlDriver, C>,
+    ) -> Result<(), Infallible>
+    where
+        C: Comp<HtmlDriver>,
+    {
+        self.comps += 1;
+        if curr.comp_ctx().is_none() {
+            let ctx = ancestor.comp_ctx().expect("ancestor.comp_ctx is None");
+            curr.set_comp_ctx(ctx.clone());
+        }
+        curr.diff_rendered(curr_index, ancestor_index, ancestor, self)
+    }
+}
+
+type Page = (
+    TagStatic<
+        HtmlDriver,
+        (
+            TagStatic<HtmlDriver, TextDyn<HtmlDriver>, ()>,
+            TagStatic<HtmlDriver, TagStatic<HtmlDriver, TextDyn<HtmlDriver>, ()>, ()>,
+        ),
+        (),
+    >,
+    CompNode<HtmlDriver, Greeting>,
+);
+
+fn page(text: &'static str) -> Page {
+    (
+        TagStatic::new(
+            "ul",
+            (),
+            (
+                TagStatic::new("li", (), TextDyn::new(text)),
+                TagStatic::new("li", (), TagStatic::new("b", (), TextDyn::new(text))),
+            ),
+        ),
+        CompNode::new(()),
+    )
+}
+
+#[test]
+fn every_tag_is_timed_with_its_children() {
+    let mut ancestor = page("a");
+    to_dom(&DriverCtx::new(HtmlDriver), &mut ancestor);
+    let mut curr = page("b");
+    let mut differ = ProfilingDiffer::with_clock(Changes::default(), tick);
+    curr.diff(&mut 0, &mut 0, &mut ancestor, &mut differ)
+        .unwrap();
+    let (changes, entries) = differ.into_inner();
+
+    assert_eq!(changes.texts, ["b", "b"]);
+    assert_eq!(changes.comps, 1);
+    let entries = entries
+        .iter()
+        .map(|entry| {
+            (
+                entry.path.to_string(),
+                entry.tag.as_str(),
+                entry.duration_ms,
+            )
+        })
+        .collect::<Vec<_>>();
+    // Each tag takes a tick for itself and two for each timed tag within.
+    assert_eq!(
+        entries[..4],
+        [
+            ("/0".to_string(), "ul", 7.0),
+            ("/0/0".to_string(), "li", 1.0),
+            ("/0/1".to_string(), "li", 3.0),
+            ("/0/1/0".to_string(), "b", 1.0),
+        ]
+    );
+    // The component is timed as a whole, with the tag it rendered.
+    assert_eq!(entries.len(), 5);
+    assert_eq!(entries[4].0, "/1");
+    assert!(entries[4].1.ends_with("Greeting"));
+    assert_eq!(entries[4].2, 1.0);
+}
@@ -0,0 +1,147 @@
+use futures::io::AsyncWrite;
+use std::{
+    future::Future,
+    io,
+    pin::Pin,
+    ptr,
+    task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+};
+use vdom::{
+    html::{HtmlDriver, StreamingRenderer},
+    vdom::node::{Node, Resource, Suspense, TagStatic, TextDyn, TextStatic},
+};
+
+/// Polls `fut` until it's ready, as the tasks here wake themselves.
+fn block_on<F>(fut: F) -> F::Output
+where
+    F: Future,
+{
+    fn raw_waker() -> RawWaker {
+        fn clone(_: *const ()) -> RawWaker {
+            raw_waker()
+        }
+        fn noop(_: *const ()) {}
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+        RawWaker::new(ptr::null(), &VTABLE)
+    }
+    let waker = unsafe { Waker::from_raw(raw_waker()) };
+    let mut cx = Context::from_waker(&waker);
+    let mut fut = Box::pin(fut);
+    loop {
+        if let Poll::Ready(output) = fut.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+/// Pending for `0` polls.
+struct Delay(usize);
+
+impl Future for Delay {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 == 0 {
+            return Poll::Ready(());
+        }
+        self.0 -= 1;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+/// Records each write as a chunk.
+#[derive(Default)]
+struct Chunks(Vec<String>);
+
+impl AsyncWrite for Chunks {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.0.push(String::from_utf8(buf.to_vec()).unwrap());
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+type Task = Pin<Box<dyn Future<Output = ()>>>;
+
+fn resource(name: &'static str, delay: usize) -> (Resource<&'static str, ()>, Task) {
+    let (resource, task) = Resource::from_future(async move {
+        Delay(delay).await;
+        Ok(name)
+    });
+    (resource, Box::pin(task))
+}
+
+fn page(a: &Resource<&'static str, ()>, b: &Resource<&'static str, ()>) -> impl Node<HtmlDriver> {
+    let suspense = |resource| {
+        Suspense::new(
+            resource,
+            || TextStatic::new("..."),
+            |name: &&str| TextDyn::new(name.to_string()),
+            |_: &()| TextStatic::new("failed"),
+        )
+    };
+    TagStatic::new("main", (), (suspense(a), suspense(b)))
+}
+
+/// The chunks written when `a` settles after `delay_a` polls and `b` after
+/// `delay_b`, running `concurrency` at once.
+fn render(delay_a: usize, delay_b: usize, concurrency: usize) -> Vec<String> {
+    let (a, task_a) = resource("a", delay_a);
+    let (b, task_b) = resource("b", delay_b);
+    let mut renderer = StreamingRenderer::new(move || page(&a, &b));
+    let mut out = Chunks::default();
+    block_on(renderer.render_stream(&mut out, vec![task_a, task_b], concurrency)).unwrap();
+    assert!(renderer.is_done());
+    out.0
+}
+
+/// The id of the template the `chunk` streams.
+fn template_id(chunk: &str) -> &str {
+    let start = chunk.find("<template id=\"vs-c").unwrap() + 18;
+    let end = start + chunk[start..].find('"').unwrap();
+    &chunk[start..end]
+}
+
+#[test]
+fn shell_then_boundaries_as_they_settle() {
+    let chunks = render(3, 1, 2);
+    assert_eq!(
+        chunks[0],
+        r#"<main><vdom-boundary id="vs-b0">...</vdom-boundary><vdom-boundary id="vs-b1">...</vdom-boundary></main>"#
+    );
+    assert_eq!(chunks.len(), 3);
+    assert_eq!(template_id(&chunks[1]), "1");
+    assert!(chunks[1].starts_with(r#"<template id="vs-c1">b</template>"#));
+    assert!(chunks[1].contains(r#"getElementById("vs-b1").replaceWith"#));
+    assert_eq!(template_id(&chunks[2]), "0");
+    assert!(chunks[2].starts_with(r#"<template id="vs-c0">a</template>"#));
+}
+
+#[test]
+fn concurrency_limits_the_tasks_running() {
+    // `b` would settle first, but only starts once `a` settled.
+    let chunks = render(3, 1, 1);
+    assert_eq!(chunks.len(), 3);
+    assert_eq!(template_id(&chunks[1]), "0");
+    assert_eq!(template_id(&chunks[2]), "1");
+}
+
+#[test]
+fn boundaries_settling_together_share_a_chunk() {
+    let chunks = render(2, 2, 2);
+    assert_eq!(chunks.len(), 2);
+    assert!(chunks[1].contains(r#"<template id="vs-c0">a</template>"#));
+    assert!(chunks[1].contains(r#"<template id="vs-c1">b</template>"#));
+}
@@ -0,0 +1,60 @@
+use vdom::{
+    html::{render_to_string, HtmlDriver},
+    vdom::dom::{sanitize, Allowlist, DomAttr, DomNode},
+};
+
+#[test]
+fn scripts_and_handlers_are_removed() {
+    let mut nodes: Vec<DomNode<HtmlDriver>> = vec![
+        DomNode::tag(
+            "b",
+            vec![DomAttr::new("onclick", "x()")],
+            vec![DomNode::text("bold")],
+        ),
+        DomNode::tag("script", vec![], vec![DomNode::text("x()")]),
+        DomNode::comment("[if IE]><script>x()</script><![endif]"),
+        DomNode::tag(
+            "a",
+            vec![DomAttr::new("href", "/page")],
+            vec![DomNode::text("link")],
+        ),
+    ];
+    sanitize(&mut nodes, &Allowlist::basic());
+    assert_eq!(
+        render_to_string(&mut nodes),
+        "<b>bold</b><a href=\"/page\">link</a>"
+    );
+}
+
+#[test]
+fn names_are_matched_in_any_case() {
+    let mut nodes: Vec<DomNode<HtmlDriver>> = vec![
+        DomNode::tag(
+            "B",
+            vec![
+                DomAttr::new("ONCLICK", "x()"),
+                DomAttr::new("OnMouseOver", "x()"),
+                DomAttr::new("TITLE", "t"),
+            ],
+            vec![],
+        ),
+        DomNode::tag("SCRIPT", vec![], vec![]),
+        DomNode::tag("a", vec![DomAttr::new("HREF", "JavaScript:x()")], vec![]),
+    ];
+    sanitize(&mut nodes, &Allowlist::basic());
+    assert_eq!(render_to_string(&mut nodes), "<B TITLE=\"t\"></B><a></a>");
+
+    // Entries of the allowlist, too.
+    let mut allowlist = Allowlist::new();
+    allowlist
+        .allow_tag("MARK")
+        .allow_attr("Data-Id")
+        .allow_attr("ONCLICK");
+    let mut nodes: Vec<DomNode<HtmlDriver>> = vec![DomNode::tag(
+        "mark",
+        vec![DomAttr::new("data-id", "1"), DomAttr::new("onclick", "x()")],
+        vec![],
+    )];
+    sanitize(&mut nodes, &allowlist);
+    assert_eq!(render_to_string(&mut nodes), "<mark data-id=\"1\"></mark>");
+}
@@ -0,0 +1,52 @@
+#![cfg(feature = "smallvec")]
+
+use smallvec::SmallVec;
+use vdom::{
+    driver::DriverCtx,
+    html::{render_to_string, HtmlDriver},
+    vdom::{
+        dom::to_dom,
+        node::{TagStatic, TextDyn},
+        patch,
+    },
+};
+
+type Items = SmallVec<[TextDyn<HtmlDriver>; 2]>;
+
+fn items(items: &[&'static str]) -> Items {
+    items.iter().map(|&item| TextDyn::new(item)).collect()
+}
+
+fn list(items: &[&'static str]) -> TagStatic<HtmlDriver, Items, ()> {
+    TagStatic::new("ul", (), self::items(items))
+}
+
+#[test]
+fn spilled_lists_diff_like_inline_ones() {
+    let driver_ctx = DriverCtx::new(HtmlDriver);
+    let lists: [&[&'static str]; 5] = [
+        &[],
+        &["a", "b"],
+        &["a", "c", "d"],
+        &["e", "b", "c", "d", "f"],
+        &["a", "b", "c", "d", "e", "f"],
+    ];
+    assert!(!items(lists[1]).spilled());
+    assert!(items(lists[2]).spilled());
+    for ancestor_items in &lists {
+        for curr_items in &lists {
+            let mut ancestor = list(ancestor_items);
+            let mut curr = list(curr_items);
+            let mut dom = to_dom(&driver_ctx, &mut ancestor);
+            let patches = patch::diff(&driver_ctx, &mut curr, &mut ancestor);
+            patch::apply(&mut dom, patches).unwrap();
+            assert_eq!(
+                render_to_string(&mut dom),
+                render_to_string(&mut curr),
+                "{:?} -> {:?}",
+                ancestor_items,
+                curr_items
+            );
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use vdom::{
+    html::{render_to_string_with_styles, HtmlDriver},
+    vdom::{
+        node::{ScopedStyle, TagStatic, TextStatic},
+        style::{scope_class, Length, StyleMap, StyleRegistry, TypedStyle},
+    },
+};
+
+type Styled = ScopedStyle<HtmlDriver, TagStatic<HtmlDriver, TextStatic<HtmlDriver>, ()>>;
+
+fn styled(css: &'static str, text: &'static str) -> Styled {
+    ScopedStyle::new(css, TagStatic::new("p", (), TextStatic::new(text)))
+}
+
+#[test]
+fn identical_snippets_share_one_rule() {
+    let mut page = TagStatic::<HtmlDriver, _, ()>::new(
+        "div",
+        (),
+        (
+            styled("color: red;", "a"),
+            (styled("color: red;", "b"), styled("color: blue;", "c")),
+        ),
+    );
+    let html = render_to_string_with_styles(&mut page);
+    let (red, blue) = (scope_class("color: red;"), scope_class("color: blue;"));
+    assert_eq!(html.matches("<style>").count(), 1);
+    assert_eq!(html.matches(&format!(".{} {{", red)).count(), 1);
+    assert_eq!(html.matches(&format!(".{} {{", blue)).count(), 1);
+    assert_eq!(html.matches(&format!("class=\"{}\"", red)).count(), 2);
+}
+
+#[test]
+fn snippets_are_told_apart_by_their_text() {
+    let styles = StyleRegistry::new();
+    styles.register("color: red;");
+    styles.register("color: red;");
+    styles.register("color: red; ");
+    assert_eq!(styles.len(), 2);
+}
+
+#[test]
+fn style_tags_write_rules_back_to_back() {
+    let styles = StyleRegistry::new();
+    assert_eq!(styles.style_tag(), "");
+    styles.register("color: red;");
+    styles.register("color: blue;");
+    let rules = styles.rules();
+    assert_eq!(
+        styles.style_tag(),
+        format!("<style>{}</style>", rules.concat())
+    );
+    assert!(!styles.style_tag().contains('\n'));
+}
+
+#[test]
+fn ampersands_stand_for_the_element_in_selectors_only() {
+    let css = "& a:hover, &.on { content: \"&\"; /* & */ background: url(a?b&c); }\n\
+               @media print { & { color: red; } }";
+    let class = scope_class(css);
+    let styles = StyleRegistry::new();
+    styles.register(css);
+    assert_eq!(
+        styles.css(),
+        format!(
+            ".{0} a:hover, .{0}.on {{ content: \"&\"; /* & */ background: url(a?b&c); }}\n\
+             @media print {{ .{0} {{ color: red; }} }}",
+            class
+        )
+    );
+
+    // Without rules, the snippet is the declarations of the element.
+    let styles = StyleRegistry::new();
+    styles.register(" content: \"{&}\"; ");
+    assert_eq!(
+        styles.css(),
+        format!(
+            ".{} {{ content: \"{{&}}\"; }}",
+            scope_class(" content: \"{&}\"; ")
+        )
+    );
+}
+
+#[test]
+fn style_values_cant_add_declarations() {
+    let style = StyleMap::new()
+        .set("color", "red; position: fixed")
+        .set("content", "\"}\"")
+        .set("font-family", "a\\");
+    assert_eq!(style.get("color"), Some("red; position: fixed"));
+    assert_eq!(
+        style.to_string(),
+        "color: red\\; position: fixed; content: \"\\}\"; font-family: a\\\\;"
+    );
+    // Escapes of other characters are kept.
+    let style = StyleMap::new().set("content", "\"\\201C\"");
+    assert_eq!(style.to_string(), "content: \"\\201C\";");
+}
+
+#[test]
+fn lengths_that_arent_finite_are_ignored() {
+    let style = TypedStyle::new()
+        .width(Length::Px(10.0))
+        .width(Length::Px(std::f32::NAN))
+        .height(Length::Em(std::f32::INFINITY))
+        .margin(Length::Zero);
+    assert_eq!(style.to_string(), "width: 10px; margin: 0;");
+    assert!(!Length::Percent(std::f32::NEG_INFINITY).is_finite());
+}
@@ -0,0 +1,414 @@
+use std::{
+    cell::Cell,
+    convert::Infallible,
+    future::Future,
+    panic::{self, AssertUnwindSafe},
+    ptr,
+    rc::Rc,
+    task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+};
+use vdom::{
+    driver::DriverCtx,
+    html::{render_to_string, HtmlDriver, StreamingRenderer},
+    vdom::{
+        dom::to_dom,
+        node::{
+            Comp, CompCtx, CompNode, Node, NodeDiffer, NodeVisitor, Resource, Suspense, Tag,
+            TagStatic, TextDyn, TextStatic,
+        },
+        patch::{self, Patch},
+    },
+};
+
+type Text = TextStatic<HtmlDriver>;
+
+/// Panics when it's rendered.
+struct Panics;
+
+impl Node<HtmlDriver> for Panics {
+    fn visit<NV>(&mut self, _index: &mut usize, _visitor: &mut NV) -> Result<(), NV::Err>
+    where
+        NV: NodeVisitor<HtmlDriver>,
+    {
+        panic!("render failed")
+    }
+
+    fn diff<ND>(
+        &mut self,
+        _curr_index: &mut usize,
+        _ancestor_index: &mut usize,
+        _ancestor: &mut Self,
+        _differ: &mut ND,
+    ) -> Result<(), ND::Err>
+    where
+        ND: NodeDiffer<HtmlDriver>,
+    {
+        panic!("render failed")
+    }
+}
+
+#[test]
+fn placeholder_then_content() {
+    let ready = Rc::new(Cell::new(false));
+    let is_ready = ready.clone();
+    let mut renderer = StreamingRenderer::new(move || {
+        let content: Suspense<Text, Text, ()> = if is_ready.get() {
+            Suspense::Ready(TextStatic::new("content"))
+        } else {
+            Suspense::Pending(TextStatic::new("loading"))
+        };
+        TagStatic::<HtmlDriver, _, _>::new("main", (), content)
+    });
+
+    assert_eq!(
+        renderer.shell(),
+        r#"<main><vdom-boundary id="vs-b0">loading</vdom-boundary></main>"#
+    );
+    assert_eq!(renderer.flush(), "");
+    assert!(!renderer.is_done());
+
+    ready.set(true);
+    let flushed = renderer.flush();
+    assert!(
+        flushed.starts_with(r#"<template id="vs-c0">content</template><script>"#),
+        "{}",
+        flushed
+    );
+    assert!(flushed.contains(r#"getElementById("vs-b0").replaceWith(t.content)"#));
+    assert!(renderer.is_done());
+    assert_eq!(renderer.flush(), "");
+}
+
+#[test]
+fn flushed_raw_text_is_not_escaped() {
+    let ready = Rc::new(Cell::new(false));
+    let is_ready = ready.clone();
+    let mut renderer = StreamingRenderer::new(move || {
+        let content: Suspense<Text, Text, ()> = if is_ready.get() {
+            Suspense::Ready(TextStatic::new("a > b </style>"))
+        } else {
+            Suspense::Pending(TextStatic::new(""))
+        };
+        TagStatic::<HtmlDriver, _, _>::new("style", (), content)
+    });
+    renderer.shell();
+    ready.set(true);
+    let flushed = renderer.flush();
+    assert!(
+        flushed.starts_with(r#"<template id="vs-c0">a > b <\/style></template>"#),
+        "{}",
+        flushed
+    );
+}
+
+#[test]
+fn panic_while_streaming_ends_the_stream() {
+    let mut renderer = StreamingRenderer::new(|| {
+        let content: Suspense<Text, Text, ()> = Suspense::Pending(TextStatic::new("loading"));
+        TagStatic::<HtmlDriver, _, _>::new("main", (), (content, Panics))
+    });
+    let shell = panic::catch_unwind(AssertUnwindSafe(|| renderer.shell()));
+    assert!(shell.is_err());
+
+    // Without the stream, boundaries render without placeholders.
+    let mut node: Suspense<Text, Text, ()> = Suspense::Pending(TextStatic::new("loading"));
+    assert_eq!(render_to_string(&mut node), "loading");
+}
+
+/// Polls `fut` until it's ready, for futures that don't wait for anything.
+fn block_on<F>(fut: F) -> F::Output
+where
+    F: Future,
+{
+    fn raw_waker() -> RawWaker {
+        fn clone(_: *const ()) -> RawWaker {
+            raw_waker()
+        }
+        fn noop(_: *const ()) {}
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+        RawWaker::new(ptr::null(), &VTABLE)
+    }
+    let waker = unsafe { Waker::from_raw(raw_waker()) };
+    let mut cx = Context::from_waker(&waker);
+    let mut fut = Box::pin(fut);
+    loop {
+        if let Poll::Ready(output) = fut.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+/// A resource compared by identity, as inputs have to be `Eq`.
+#[derive(Clone)]
+struct Shared(Rc<Resource<&'static str, ()>>);
+
+impl PartialEq for Shared {
+    fn eq(&self, other: &Shared) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for Shared {}
+
+/// Renders the resource of its input, or a placeholder while it's pending.
+#[derive(Clone, Eq, PartialEq)]
+struct Loader;
+
+impl Comp<HtmlDriver> for Loader {
+    type Input = Shared;
+    type Rendered = Suspense<Text, TextDyn<HtmlDriver>, Text>;
+
+    fn new(_input: &Shared, _ctx: CompCtx<HtmlDriver, Loader>) -> Loader {
+        Loader
+    }
+
+    fn render(&self, input: &Shared) -> Self::Rendered {
+        Suspense::new(
+            &input.0,
+            || TextStatic::new("loading"),
+            |data: &&str| TextDyn::new(data.to_string()),
+            |_: &()| TextStatic::new("failed"),
+        )
+    }
+}
+
+/// Collects the texts of the visited nodes.
+struct Texts(Vec<String>);
+
+impl NodeVisitor<HtmlDriver> for Texts {
+    type Err = Infallible;
+
+    fn on_tag<T>(&mut self, _index: usize, tag: &mut T) -> Result<(), Infallible>
+    where
+        T: Tag<HtmlDriver>,
+    {
+        tag.visit_children(self)
+    }
+
+    fn on_text<T>(&mut self, _index: usize, text: &mut T) -> Result<(), Infallible>
+    where
+        T: vdom::vdom::node::Text<HtmlDriver>,
+    {
+        self.0.push(text.get().to_string());
+        Ok(())
+    }
+
+    fn on_comp<C>(
+        &mut self,
+        index: &mut usize,
+        comp: &mut CompNode<HtmlDriver, C>,
+    ) -> Result<(), Infallible>
+    where
+        C: Comp<HtmlDriver>,
+    {
+        comp.visit_rendered(index, self)
+    }
+}
+
+/// Records the texts of the nodes added and removed.
+struct Changes(Vec<String>);
+
+impl Changes {
+    fn record<N>(&mut self, sign: &str, index: &mut usize, node: &mut N) -> Result<(), Infallible>
+    where
+        N: Node<HtmlDriver>,
+    {
+        let mut texts = Texts(Vec::new());
+        node.visit(index, &mut texts)?;
+        for text in texts.0 {
+            self.0.push(format!("{}{}", sign, text));
+        }
+        Ok(())
+    }
+}
+
+impl NodeDiffer<HtmlDriver> for Changes {
+    type Err = Infallible;
+
+    fn on_node_added<N>(&mut self, index: &mut usize, curr: &mut N) -> Result<(), Infallible>
+    where
+        N: Node<HtmlDriver>,
+    {
+        self.record("+", index, curr)
+    }
+
+    fn on_node_removed<N>(
+        &mut self,
+        _curr_index: usize,
+        ancestor_index: &mut usize,
+        ancestor: &mut N,
+    ) -> Result<(), Infallible>
+    where
+        N: Node<HtmlDriver>,
+    {
+        self.record("-", ancestor_index, ancestor)
+    }
+
+    fn on_tag<T>(
+        &mut self,
+        _curr_index: usize,
+        _ancestor_index: usize,
+        curr: &mut T,
+        ancestor: &mut T,
+    ) -> Result<(), Infallible>
+    where
+        T: Tag<HtmlDriver>,
+    {
+        curr.diff_children(ancestor, self)
+    }
+
+    fn on_text<T>(
+        &mut self,
+        _curr_index: usize,
+        _ancestor_index: usize,
+        curr: &mut T,
+        ancestor: &mut T,
+    ) -> Result<(), Infallible>
+    where
+        T: vdom::vdom::node::Text<HtmlDriver>,
+    {
+        if curr.get() != ancestor.get() {
+            self.0.push(format!("{} -> {}", ancestor.get(), curr.get()));
+        }
+        Ok(())
+    }
+
+    fn on_comp<C>(
+        &mut self,
+        curr_index: &mut usize,
+        ancestor_index: &mut usize,
+        curr: &mut CompNode<HtmlDriver, C>,
+        ancestor: &mut CompNode<HtmlDriver, C>,
+    ) -> Result<(), Infallible>
+    where
+        C: Comp<HtmlDriver>,
+    {
+        if curr.comp_ctx().is_none() {
+            curr.set_comp_ctx(ancestor.comp_ctx().unwrap().clone());
+        }
+        curr.diff_rendered(curr_index, ancestor_index, ancestor, self)
+    }
+}
+
+/// Updates the components of the visited nodes in place, invalidating them
+/// first if `invalidate` is set.
+struct Updater {
+    invalidate: bool,
+    changes: Changes,
+}
+
+impl NodeVisitor<HtmlDriver> for Updater {
+    type Err = Infallible;
+
+    fn on_tag<T>(&mut self, _index: usize, tag: &mut T) -> Result<(), Infallible>
+    where
+        T: Tag<HtmlDriver>,
+    {
+        tag.visit_children(self)
+    }
+
+    fn on_text<T>(&mut self, _index: usize, _text: &mut T) -> Result<(), Infallible>
+    where
+        T: vdom::vdom::node::Text<HtmlDriver>,
+    {
+        Ok(())
+    }
+
+    fn on_comp<C>(
+        &mut self,
+        index: &mut usize,
+        comp: &mut CompNode<HtmlDriver, C>,
+    ) -> Result<(), Infallible>
+    where
+        C: Comp<HtmlDriver>,
+    {
+        if self.invalidate {
+            comp.comp_ctx().unwrap().downgrade().invalidate();
+        }
+        if comp.update_rendered(index, &mut self.changes)? {
+            return Ok(());
+        }
+        comp.visit_rendered(index, self)
+    }
+}
+
+#[test]
+fn settled_boundaries_update_without_their_owner() {
+    let (resource, settle) = Resource::from_future(async { Ok("data") });
+    let resource = Shared(Rc::new(resource));
+    let mut node = TagStatic::<HtmlDriver, _, ()>::new(
+        "main",
+        (),
+        (
+            TextStatic::new("owner"),
+            TagStatic::<HtmlDriver, _, ()>::new(
+                "p",
+                (),
+                CompNode::<HtmlDriver, Loader>::new(resource),
+            ),
+        ),
+    );
+    to_dom(&DriverCtx::new(HtmlDriver), &mut node);
+    let mut updater = Updater {
+        invalidate: false,
+        changes: Changes(Vec::new()),
+    };
+
+    block_on(settle);
+    // Neither the component nor its input changed.
+    node.visit(&mut 0, &mut updater).unwrap();
+    assert!(updater.changes.0.is_empty());
+    assert_eq!(
+        render_to_string(&mut node),
+        "<main>owner<p>loading</p></main>"
+    );
+
+    updater.invalidate = true;
+    node.visit(&mut 0, &mut updater).unwrap();
+    assert_eq!(updater.changes.0, ["-loading", "+data"]);
+    assert_eq!(render_to_string(&mut node), "<main>owner<p>data</p></main>");
+}
+
+#[test]
+fn settling_replaces_the_placeholder() {
+    let driver_ctx = DriverCtx::new(HtmlDriver);
+    let (resource, settle) =
+        Resource::<&'static str, &'static str>::from_future(async { Ok("data") });
+    let render = |resource: &Resource<&'static str, &'static str>| {
+        Suspense::new(
+            resource,
+            || TextStatic::<HtmlDriver>::new("loading"),
+            |data| TextDyn::new(*data),
+            |err| TextDyn::new(format!("failed: {}", err)),
+        )
+    };
+
+    assert!(resource.is_pending());
+    let mut ancestor = render(&resource);
+    let mut dom = to_dom(&driver_ctx, &mut ancestor);
+    assert_eq!(render_to_string(&mut dom), "loading");
+
+    block_on(settle);
+    assert!(!resource.is_pending());
+    let mut curr = render(&resource);
+    let patches = patch::diff(&driver_ctx, &mut curr, &mut ancestor);
+    match &patches[..] {
+        [Patch::RemoveNode { path }, Patch::InsertNode { path: inserted, .. }] => {
+            assert_eq!(path.indices(), &[0]);
+            assert_eq!(inserted.indices(), &[0]);
+        }
+        patches => panic!("expected the placeholder to be replaced, got {:?}", patches),
+    }
+    patch::apply(&mut dom, patches).unwrap();
+    assert_eq!(render_to_string(&mut dom), "data");
+
+    // A resource that stays ready is diffed like its content.
+    let mut ancestor = curr;
+    let mut curr = render(&Resource::ready("more"));
+    match &patch::diff(&driver_ctx, &mut curr, &mut ancestor)[..] {
+        [Patch::SetText { text, .. }] => assert_eq!(text, "more"),
+        patches => panic!("expected the text to be set, got {:?}", patches),
+    }
+    let mut failed = render(&Resource::failed("timeout"));
+    assert_eq!(render_to_string(&mut failed), "failed: timeout");
+}
@@ -0,0 +1,183 @@
+use vdom::{
+    html::HtmlDriver,
+    vdom::{
+        attr::{Attr, AttrDiffer, AttrDyn, AttrListEntry},
+        node::{Comp, CompNode, Node, NodeDiffer, Tag, TagStatic, Text, TextDyn},
+        tee::{TeeDiffer, TeeError},
+    },
+};
+
+type Para = TagStatic<HtmlDriver, TextDyn<HtmlDriver>, ()>;
+type Div =
+    TagStatic<HtmlDriver, (TextDyn<HtmlDriver>, Option<Para>), AttrListEntry<AttrDyn<HtmlDriver>>>;
+
+/// Records the calls it gets, and fails the first call for a text equal to
+/// `fail_on`, if any.
+#[derive(Default)]
+struct Log {
+    calls: Vec<String>,
+    fail_on: Option<&'static str>,
+}
+
+impl NodeDiffer<HtmlDriver> for Log {
+    type Err = String;
+
+    fn on_node_added<N>(&mut self, index: &mut usize, curr: &mut N) -> Result<(), Self::Err>
+    where
+        N: Node<HtmlDriver>,
+    {
+        self.calls.push(format!("added {}", index));
+        *index += curr.node_count().unwrap();
+        Ok(())
+    }
+
+    fn on_node_removed<N>(
+        &mut self,
+        curr_index: usize,
+        ancestor_index: &mut usize,
+        ancestor: &mut N,
+    ) -> Result<(), Self::Err>
+    where
+        N: Node<HtmlDriver>,
+    {
+        self.calls.push(format!("removed {}", curr_index));
+        *ancestor_index += ancestor.node_count().unwrap();
+        Ok(())
+    }
+
+    fn on_tag<T>(
+        &mut self,
+        curr_index: usize,
+        _ancestor_index: usize,
+        curr: &mut T,
+        ancestor: &mut T,
+    ) -> Result<(), Self::Err>
+    where
+        T: Tag<HtmlDriver>,
+    {
+        self.calls
+            .push(format!("tag {} {}", curr_index, curr.tag()));
+        curr.diff_attrs(ancestor, self)?;
+        curr.diff_children(ancestor, self)
+    }
+
+    fn on_text<T>(
+        &mut self,
+        curr_index: usize,
+        _ancestor_index: usize,
+        curr: &mut T,
+        _ancestor: &mut T,
+    ) -> Result<(), Self::Err>
+    where
+        T: Text<HtmlDriver>,
+    {
+        if self.fail_on == Some(curr.get()) {
+            return Err(curr.get().to_string());
+        }
+        self.calls
+            .push(format!("text {} {}", curr_index, curr.get()));
+        Ok(())
+    }
+
+    fn on_comp<C>(
+        &mut self,
+        _curr_index: &mut usize,
+        _ancestor_index: &mut usize,
+        _curr: &mut CompNode<HtmlDriver, C>,
+        _ancestor: &mut CompNode<HtmlDriver, C>,
+    ) -> Result<(), Self::Err>
+    where
+        C: Comp<HtmlDriver>,
+    {
+        Ok(())
+    }
+}
+
+impl AttrDiffer<HtmlDriver> for Log {
+    type Err = String;
+
+    fn on_diff<A>(&mut self, curr: &mut A, _ancestor: &mut A) -> Result<(), Self::Err>
+    where
+        A: Attr<HtmlDriver>,
+    {
+        self.calls.push(format!("attr {}", curr.name()));
+        Ok(())
+    }
+
+    fn on_added<A>(&mut self, curr: &mut A) -> Result<(), Self::Err>
+    where
+        A: Attr<HtmlDriver>,
+    {
+        self.calls.push(format!("attr added {}", curr.name()));
+        Ok(())
+    }
+
+    fn on_removed<A>(&mut self, ancestor: &mut A) -> Result<(), Self::Err>
+    where
+        A: Attr<HtmlDriver>,
+    {
+        self.calls.push(format!("attr removed {}", ancestor.name()));
+        Ok(())
+    }
+}
+
+fn div(title: &str, text: &str, para: Option<&str>) -> Div {
+    TagStatic::new(
+        "div",
+        AttrListEntry(AttrDyn::new("title", title.to_string())),
+        (
+            TextDyn::new(text.to_string()),
+            para.map(|text| TagStatic::new("p", (), TextDyn::new(text.to_string()))),
+        ),
+    )
+}
+
+#[test]
+fn both_differs_get_the_same_calls() {
+    let mut ancestor = div("a", "x", None);
+    let mut curr = div("b", "y", Some("z"));
+    let mut differ = TeeDiffer::new(Log::default(), Log::default());
+    curr.diff(&mut 0, &mut 0, &mut ancestor, &mut differ)
+        .unwrap();
+    let (first, second) = differ.into_inner();
+    assert_eq!(
+        first.calls,
+        ["tag 0 div", "attr title", "text 0 y", "added 1"]
+    );
+    assert_eq!(first.calls, second.calls);
+}
+
+#[test]
+fn an_error_of_first_stops_the_call() {
+    let mut ancestor = div("a", "x", Some("z"));
+    let mut curr = div("a", "y", None);
+    let first = Log {
+        calls: Vec::new(),
+        fail_on: Some("y"),
+    };
+    let mut differ = TeeDiffer::new(first, Log::default());
+    let res = curr.diff(&mut 0, &mut 0, &mut ancestor, &mut differ);
+    assert_eq!(res, Err(TeeError::First("y".to_string())));
+    let (first, second) = differ.into_inner();
+    assert_eq!(first.calls, ["tag 0 div", "attr title"]);
+    assert!(second.calls.is_empty());
+}
+
+#[test]
+fn an_error_of_second_is_returned_after_first_finished() {
+    let mut ancestor = div("a", "x", Some("z"));
+    let mut curr = div("a", "y", None);
+    let second = Log {
+        calls: Vec::new(),
+        fail_on: Some("y"),
+    };
+    let mut differ = TeeDiffer::new(Log::default(), second);
+    let res = curr.diff(&mut 0, &mut 0, &mut ancestor, &mut differ);
+    assert_eq!(res, Err(TeeError::Second("y".to_string())));
+    let (first, second) = differ.into_inner();
+    assert_eq!(
+        first.calls,
+        ["tag 0 div", "attr title", "text 0 y", "removed 1"]
+    );
+    assert_eq!(second.calls, ["tag 0 div", "attr title"]);
+}
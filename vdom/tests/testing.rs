@@ -0,0 +1,75 @@
+#![cfg(feature = "testing")]
+
+use std::{env, fs, process};
+use vdom::{
+    assert_html_snapshot,
+    html::{render_to_string, HtmlDriver},
+    testing::Snapshot,
+    vdom::node::{TagStatic, TextStatic},
+};
+
+type Item = TagStatic<HtmlDriver, TextStatic<HtmlDriver>, ()>;
+
+fn list(second: &'static str) -> TagStatic<HtmlDriver, (Item, Item), ()> {
+    TagStatic::new(
+        "ul",
+        (),
+        (
+            TagStatic::new("li", (), TextStatic::new("a")),
+            TagStatic::new("li", (), TextStatic::new(second)),
+        ),
+    )
+}
+
+// A single test, as `UPDATE_SNAPSHOTS` applies to the whole process.
+#[test]
+fn snapshots_are_written_compared_and_updated() {
+    let dir = env::temp_dir().join(format!("vdom-snapshots-{}", process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let path = dir.join("lists/list.html");
+    let new_path = dir.join("lists/list.html.new");
+    let snapshot = Snapshot::new(&path).pretty();
+
+    // A missing snapshot fails, leaving the actual HTML to be moved into
+    // place.
+    let err = snapshot
+        .check(&render_to_string(&mut list("b")))
+        .unwrap_err();
+    assert!(err.contains("doesn't exist"), "{}", err);
+    assert_eq!(
+        fs::read_to_string(&new_path).unwrap(),
+        "<ul>\n<li>\na\n</li>\n<li>\nb\n</li>\n</ul>\n"
+    );
+    fs::rename(&new_path, &path).unwrap();
+    snapshot.check(&render_to_string(&mut list("b"))).unwrap();
+    assert_html_snapshot!(list("b"), &path, pretty);
+    // Whitespace between tags doesn't matter when pretty printed.
+    snapshot
+        .check("<ul>\n  <li>a</li>\n  <li>b</li>\n</ul>")
+        .unwrap();
+    assert!(Snapshot::new(&path)
+        .check(&render_to_string(&mut list("b")))
+        .is_err());
+
+    // A mismatch fails with a diff and writes the actual HTML next to it.
+    let err = snapshot
+        .check(&render_to_string(&mut list("c")))
+        .unwrap_err();
+    assert!(err.contains("doesn't match"), "{}", err);
+    assert!(
+        err.contains("@@ -3,6 +3,6 @@\n a\n </li>\n <li>\n-b\n+c\n"),
+        "{}",
+        err
+    );
+    assert!(fs::read_to_string(&new_path).unwrap().contains("\nc\n"));
+
+    // Updating accepts the change and removes the stale `.new` file.
+    env::set_var("UPDATE_SNAPSHOTS", "1");
+    let updated = snapshot.check(&render_to_string(&mut list("c")));
+    env::remove_var("UPDATE_SNAPSHOTS");
+    updated.unwrap();
+    assert!(!new_path.exists());
+    snapshot.check(&render_to_string(&mut list("c"))).unwrap();
+
+    fs::remove_dir_all(&dir).unwrap();
+}
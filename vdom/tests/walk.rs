@@ -0,0 +1,88 @@
+use vdom::{
+    driver::DriverCtx,
+    html::HtmlDriver,
+    vdom::{
+        dom::{iter, iter_dom, to_dom, DomNode},
+        node::{Comp, CompCtx, CompNode, TagStatic, Text, TextStatic},
+        path::Path,
+    },
+};
+
+#[derive(Clone, Eq, PartialEq)]
+struct Item;
+
+impl Comp<HtmlDriver> for Item {
+    type Input = &'static str;
+    type Rendered = TagStatic<HtmlDriver, TextStatic<HtmlDriver>, ()>;
+
+    fn new(_input: &&'static str, _ctx: CompCtx<HtmlDriver, Item>) -> Item {
+        Item
+    }
+
+    fn render(&self, input: &&'static str) -> Self::Rendered {
+        TagStatic::new("li", (), TextStatic::new(input))
+    }
+}
+
+/// The path and the tag or text of each node.
+fn describe<I, N>(nodes: I) -> Vec<(Vec<usize>, String)>
+where
+    I: Iterator<Item = (Path, N)>,
+    N: std::borrow::Borrow<DomNode<HtmlDriver>>,
+{
+    nodes
+        .map(|(path, node)| {
+            let node = match node.borrow() {
+                DomNode::Tag(tag) => format!("<{}>", tag.tag),
+                DomNode::Text(text) => text.get().to_string(),
+                DomNode::Comment(comment) => format!("<!--{}-->", comment.get()),
+            };
+            (path.indices().to_vec(), node)
+        })
+        .collect()
+}
+
+#[test]
+fn nodes_are_yielded_in_pre_order() {
+    let mut node = (
+        TagStatic::<HtmlDriver, _, ()>::new(
+            "ul",
+            (),
+            (
+                CompNode::<HtmlDriver, Item>::new("a"),
+                CompNode::<HtmlDriver, Item>::new("b"),
+            ),
+        ),
+        TextStatic::new("c"),
+    );
+    let expected = vec![
+        (vec![0], "<ul>".to_string()),
+        (vec![0, 0], "<li>".to_string()),
+        (vec![0, 0, 0], "a".to_string()),
+        (vec![0, 1], "<li>".to_string()),
+        (vec![0, 1, 0], "b".to_string()),
+        (vec![1], "c".to_string()),
+    ];
+    let ctx = DriverCtx::new(HtmlDriver);
+    assert_eq!(describe(iter(&ctx, &mut node)), expected);
+
+    let nodes = to_dom(&ctx, &mut node);
+    assert_eq!(describe(iter_dom(&nodes)), expected);
+    assert_eq!(iter_dom::<HtmlDriver>(&[]).count(), 0);
+}
+
+#[test]
+fn elements_are_yielded_without_their_children() {
+    let mut nodes = vec![DomNode::<HtmlDriver>::tag(
+        "p",
+        vec![],
+        vec![DomNode::text("a")],
+    )];
+    let ctx = DriverCtx::new(HtmlDriver);
+    let (path, node) = iter(&ctx, &mut nodes).next().unwrap();
+    assert_eq!(path, Path::root().child(0));
+    match node {
+        DomNode::Tag(tag) => assert!(tag.children.is_empty()),
+        DomNode::Text(_) | DomNode::Comment(_) => panic!("expected an element"),
+    }
+}
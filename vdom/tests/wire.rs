@@ -0,0 +1,211 @@
+use futures::Future;
+use std::{cell::RefCell, rc::Rc};
+use vdom::{
+    driver::{Driver, DriverCtx},
+    events::wire::{
+        DispatchError, DropStale, EventPayload, FrameId, HandlerRegistry, RejectStale, RemapStale,
+        RemoteEvent,
+    },
+    vdom::{
+        attr::{AttrListEntry, AttrListener},
+        node::{Node, TagStatic},
+        path::Path,
+    },
+};
+
+/// A server whose listeners are called with the payloads of remote events.
+struct Server;
+
+impl Driver for Server {
+    type AttrStore = ();
+    type TagStore = ();
+    type TextStore = ();
+    type CompStore = ();
+    type Event = EventPayload;
+
+    fn new_attr_store() {}
+
+    fn new_tag_store() {}
+
+    fn new_text_store() {}
+
+    fn new_comp_store() {}
+
+    fn spawn<F>(&mut self, _fut: F)
+    where
+        F: Future<Output = ()> + 'static,
+    {
+    }
+}
+
+type Log = Rc<RefCell<Vec<String>>>;
+
+fn input(log: &Log) -> impl Node<Server> {
+    let log = log.clone();
+    let listener = AttrListener::new("input", move |payload: EventPayload| {
+        log.borrow_mut().push(payload.value.unwrap())
+    });
+    TagStatic::new("input", AttrListEntry(listener), ())
+}
+
+fn event(frame: u64, value: &str) -> RemoteEvent {
+    RemoteEvent {
+        frame: FrameId(frame),
+        path: Path::root().child(0),
+        kind: "input".to_string(),
+        payload: EventPayload {
+            value: Some(value.to_string()),
+            ..EventPayload::default()
+        },
+    }
+}
+
+#[test]
+fn older_tokens_are_dropped() {
+    let log = Log::default();
+    let driver_ctx = DriverCtx::new(Server);
+    let mut registry = HandlerRegistry::new();
+    let frame = registry.collect(&driver_ctx, &mut (input(&log), ()));
+
+    let dispatch =
+        |value, token| registry.dispatch_with_token(event(frame.0, value), token, &RejectStale);
+    assert_eq!(dispatch("ab", 2), Ok(true));
+    assert_eq!(dispatch("a", 1), Ok(false));
+    assert_eq!(dispatch("abc", 3), Ok(true));
+    assert_eq!(registry.value_token(&Path::root().child(0)), Some(3));
+    // Events without a token aren't ordered.
+    assert_eq!(
+        registry.dispatch(event(frame.0, "x"), &RejectStale),
+        Ok(true)
+    );
+    assert_eq!(*log.borrow(), ["ab", "abc", "x"]);
+}
+
+#[test]
+fn tokens_are_pruned_with_their_elements() {
+    let log = Log::default();
+    let driver_ctx = DriverCtx::new(Server);
+    let mut registry = HandlerRegistry::new();
+    let frame = registry.collect(&driver_ctx, &mut (input(&log), ()));
+    let path = Path::root().child(0);
+    registry
+        .dispatch_with_token(event(frame.0, "a"), 5, &RejectStale)
+        .unwrap();
+
+    // Still there, so the token is kept.
+    registry.collect(&driver_ctx, &mut (input(&log), ()));
+    assert_eq!(registry.value_token(&path), Some(5));
+
+    // Gone, so is the token, and a new element at the path starts over.
+    registry.collect(&driver_ctx, &mut ((), ()));
+    assert_eq!(registry.value_token(&path), None);
+    let frame = registry.collect(&driver_ctx, &mut (input(&log), ()));
+    assert_eq!(
+        registry.dispatch_with_token(event(frame.0, "b"), 1, &RejectStale),
+        Ok(true)
+    );
+    assert_eq!(*log.borrow(), ["a", "b"]);
+}
+
+fn textarea(log: &Log) -> impl Node<Server> {
+    let log = log.clone();
+    let listener = AttrListener::new("input", move |payload: EventPayload| {
+        log.borrow_mut().push(payload.value.unwrap())
+    });
+    TagStatic::new("textarea", AttrListEntry(listener), ())
+}
+
+#[test]
+fn late_events_are_handled_by_the_policy() {
+    let log = Log::default();
+    let driver_ctx = DriverCtx::new(Server);
+    let mut registry = HandlerRegistry::new();
+    let first = registry.collect(&driver_ctx, &mut (input(&log), ()));
+    let second = registry.collect(&driver_ctx, &mut (input(&log), ()));
+
+    assert_eq!(
+        registry.dispatch(event(first.0, "a"), &DropStale),
+        Ok(false)
+    );
+    assert_eq!(
+        registry.dispatch(event(first.0, "b"), &RejectStale),
+        Err(DispatchError::Stale {
+            frame: first,
+            current: second,
+        })
+    );
+    assert_eq!(
+        registry.dispatch(event(first.0, "c"), &RemapStale),
+        Ok(true)
+    );
+
+    // Another element is at the path by now.
+    registry.collect(&driver_ctx, &mut (textarea(&log), ()));
+    assert_eq!(
+        registry.dispatch(event(second.0, "d"), &RemapStale),
+        Ok(false)
+    );
+    assert_eq!(*log.borrow(), ["c"]);
+}
+
+#[test]
+fn late_events_are_not_remapped_to_registered_listeners() {
+    let log = Log::default();
+    let driver_ctx = DriverCtx::new(Server);
+    let mut registry = HandlerRegistry::new();
+    let path = Path::root().child(0);
+    let register = |registry: &mut HandlerRegistry<Server>| {
+        let log = log.clone();
+        let handler = move |payload: EventPayload| log.borrow_mut().push(payload.value.unwrap());
+        registry.register(path.clone(), "input", Rc::new(handler));
+    };
+    let first = registry.collect(&driver_ctx, &mut ((), ()));
+    register(&mut registry);
+    let second = registry.collect(&driver_ctx, &mut ((), ()));
+    register(&mut registry);
+
+    assert_eq!(
+        registry.dispatch(event(first.0, "a"), &RemapStale),
+        Ok(false)
+    );
+    assert_eq!(
+        registry.dispatch(event(second.0, "b"), &RemapStale),
+        Ok(true)
+    );
+    assert_eq!(*log.borrow(), ["b"]);
+}
+
+#[cfg(feature = "serde_json")]
+#[test]
+fn tokens_from_json() {
+    let json = event(1, "a").to_json();
+    assert_eq!(RemoteEvent::token_from_json(&json), None);
+
+    let mut json = json;
+    if let serde_json::Value::Object(event) = &mut json {
+        if let Some(serde_json::Value::Object(payload)) = event.get_mut("payload") {
+            payload.insert("token".to_string(), serde_json::Value::Number(7u64.into()));
+        }
+    }
+    assert_eq!(RemoteEvent::from_json(&json), Ok(event(1, "a")));
+    assert_eq!(RemoteEvent::token_from_json(&json), Some(7));
+}
+
+#[cfg(feature = "serde_json")]
+#[test]
+fn coords_from_json_must_fit_32_bits() {
+    let with_coords = |x: i64, y: i64| {
+        let mut json = event(1, "a").to_json();
+        if let serde_json::Value::Object(event) = &mut json {
+            if let Some(serde_json::Value::Object(payload)) = event.get_mut("payload") {
+                let coords = serde_json::Value::Array(vec![x.into(), y.into()]);
+                payload.insert("coords".to_string(), coords);
+            }
+        }
+        RemoteEvent::from_json(&json)
+    };
+    let event = with_coords(-3, i64::from(i32::MAX)).unwrap();
+    assert_eq!(event.payload.coords, Some((-3, i32::MAX)));
+    assert!(with_coords(0, i64::from(i32::MAX) + 1).is_err());
+    assert!(with_coords(i64::from(i32::MIN) - 1, 0).is_err());
+}
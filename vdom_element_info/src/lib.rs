@@ -0,0 +1,89 @@
+//! How HTML treats elements of some tags, for the renderers, the text and
+//! whitespace handling, and the checks of the `html!` macro to agree on.
+//! Tags are expected in lower case.
+
+/// Whether `tag` is a void element, which has no end tag and no children.
+pub fn is_void(tag: &str) -> bool {
+    match tag {
+        "area" | "base" | "br" | "col" | "embed" | "hr" | "img" | "input" | "link" | "meta"
+        | "param" | "source" | "track" | "wbr" => true,
+        _ => false,
+    }
+}
+
+/// Whether the content of `tag` is raw text, which is neither parsed as HTML
+/// nor escaped when rendered, so it must not contain the end tag.
+pub fn raw_text_content(tag: &str) -> bool {
+    match tag {
+        "script" | "style" => true,
+        _ => false,
+    }
+}
+
+/// Whether the content of `tag` is only used where scripting is disabled,
+/// as for `<noscript>`. Where it's enabled, as wherever vdom runs in a
+/// browser, the browser parses it as a single text node and ignores it, so
+/// only servers render the children while drivers leave them alone.
+pub fn is_noscript_content(tag: &str) -> bool {
+    tag == "noscript"
+}
+
+/// Whether the content of `tag` is run once the element is inserted, as for
+/// `<script>`. Changing it afterwards neither runs it again nor undoes what
+/// it did, so drivers mount the children but don't patch them later.
+pub fn runs_once(tag: &str) -> bool {
+    tag == "script"
+}
+
+/// The attributes of `tag` that select the document it embeds, as `src` of
+/// `<iframe>`. Changing them navigates the embedded document, so drivers
+/// replace the element instead, unmounting it as any other removed element.
+pub fn document_attrs(tag: &str) -> &'static [&'static str] {
+    match tag {
+        "iframe" => &["src", "srcdoc"],
+        _ => &[],
+    }
+}
+
+/// Whether whitespace in the text within `tag` is significant, so it must
+/// be kept as is rather than collapsed or trimmed.
+pub fn preserves_whitespace(tag: &str) -> bool {
+    match tag {
+        "listing" | "plaintext" | "pre" | "textarea" => true,
+        tag => raw_text_content(tag),
+    }
+}
+
+/// Whether `tag` is laid out within a line of text by default, rather than
+/// as a block on lines of its own.
+pub fn is_inline(tag: &str) -> bool {
+    match tag {
+        "a" | "abbr" | "b" | "bdi" | "bdo" | "br" | "button" | "cite" | "code" | "data" | "dfn"
+        | "em" | "i" | "img" | "input" | "kbd" | "label" | "mark" | "q" | "s" | "samp"
+        | "select" | "small" | "span" | "strong" | "sub" | "sup" | "textarea" | "time" | "u"
+        | "var" | "wbr" => true,
+        _ => false,
+    }
+}
+
+/// Whether `name` can be written as a tag name, which starts with an ASCII
+/// letter and can't end the tag early, see `is_valid_attr_name`.
+pub fn is_valid_tag_name(name: &str) -> bool {
+    match name.chars().next() {
+        Some(first) => first.is_ascii_alphabetic() && is_valid_attr_name(name),
+        None => false,
+    }
+}
+
+/// Whether `name` can be written as an attribute name, which can't be empty
+/// or contain whitespace, control characters, quotes, `<`, `>`, `/` or `=`,
+/// any of which would end the name and let the rest be read as markup.
+pub fn is_valid_attr_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.chars().any(|c| {
+            match c {
+                '"' | '\'' | '<' | '>' | '/' | '=' => true,
+                c => c.is_whitespace() || c.is_control(),
+            }
+        })
+}
@@ -1,36 +1,202 @@
-use crate::parser::{Attr, AttrValue, Node, Tag};
-use quote::{__rt::TokenStream, quote, ToTokens};
-use syn::LitStr;
+use crate::{
+    parser::{Attr, AttrValue, Cfg, Doctype, Match, Node, Nodes, Tag},
+    validate,
+};
+use quote::{
+    __rt::{Span, TokenStream},
+    quote, quote_spanned, ToTokens,
+};
+use syn::{Expr, FnArg, Ident, LitStr};
 
-pub fn gen_nodes(nodes: Vec<Node>) -> TokenStream {
-    nodes
-        .into_iter()
-        .map(gen_node)
-        .fold(None, |prev_nodes, node| {
-            match prev_nodes {
-                Some(prev_nodes) => Some(quote! {(#prev_nodes, #node)}),
-                None => Some(node),
+pub fn gen_root(nodes: Nodes) -> TokenStream {
+    match nodes.doctype {
+        Some(doctype) => gen_document(doctype, nodes.nodes, false),
+        None => gen_nodes(nodes.nodes, false),
+    }
+}
+
+/// Like `gen_root`, with `{expr}` formatted into `frame`, a
+/// `vdom::vdom::arena::Frame`, instead of a `String` of its own.
+pub fn gen_root_in(frame: Expr, nodes: Nodes) -> TokenStream {
+    let root = match nodes.doctype {
+        Some(doctype) => gen_document(doctype, nodes.nodes, true),
+        None => gen_nodes(nodes.nodes, true),
+    };
+    quote! {
+        {
+            let __vdom_frame: &vdom::vdom::arena::Frame = &(#frame);
+            #root
+        }
+    }
+}
+
+fn gen_document(doctype: Doctype, mut nodes: Vec<Node>, in_frame: bool) -> TokenStream {
+    let is_html = |node: &Node| {
+        match node {
+            Node::Tag(tag) => tag.tag == "html",
+            _ => false,
+        }
+    };
+    if nodes.len() != 1 || !is_html(&nodes[0]) {
+        let msg = "a document must have a single `html` element as its root";
+        return syn::Error::new(doctype.doctype.span(), msg).to_compile_error();
+    }
+
+    let doctype = doctype.doctype;
+    let root = gen_node(nodes.remove(0), in_frame);
+    quote! {
+        vdom::html::Document::new(#doctype, #root)
+    }
+}
+
+fn gen_nodes(nodes: Vec<Node>, in_frame: bool) -> TokenStream {
+    let mut nodes = merge_texts(nodes).into_iter();
+    let mut prev_nodes = None;
+    while let Some(node) = nodes.next() {
+        let node = match node {
+            // The following siblings are generated within the scope of the
+            // binding, which ends with them.
+            Node::Let(binding) => {
+                let rest = gen_nodes(nodes.by_ref().collect(), in_frame);
+                quote! {
+                    {
+                        let #binding;
+                        #rest
+                    }
+                }
             }
-        })
-        .unwrap_or_else(|| quote! {()})
+            node => gen_node(node, in_frame),
+        };
+        prev_nodes = Some(match prev_nodes {
+            Some(prev_nodes) => quote! {(#prev_nodes, #node)},
+            None => node,
+        });
+    }
+    prev_nodes.unwrap_or_else(|| quote! {()})
 }
 
-fn gen_node(node: Node) -> TokenStream {
+/// Merges adjacent text literals, e.g. of a text split over several lines,
+/// into one so they render as a single text node.
+fn merge_texts(nodes: Vec<Node>) -> Vec<Node> {
+    let mut merged: Vec<Node> = Vec::with_capacity(nodes.len());
+    for node in nodes {
+        if let (Some(Node::Text(prev)), Node::Text(text)) = (merged.last_mut(), &node) {
+            *prev = LitStr::new(&(prev.value() + &text.value()), prev.span());
+            continue;
+        }
+        merged.push(node);
+    }
+    merged
+}
+
+fn gen_node(node: Node, in_frame: bool) -> TokenStream {
     match node {
-        Node::Tag(tag) => gen_tag(tag),
+        Node::Tag(tag) => gen_tag(tag, in_frame),
         Node::Text(lit_str) => quote! {vdom::vdom::node::TextStatic::new(#lit_str)},
         Node::Expr(expr) => expr.into_token_stream(),
+        Node::Display(expr) if in_frame => quote! {__vdom_frame.text(&(#expr))},
+        Node::Display(expr) => {
+            quote! {
+                vdom::vdom::node::TextDyn::new(::std::string::ToString::to_string(&(#expr)))
+            }
+        }
+        Node::Match(match_) => gen_match(match_, in_frame),
+        Node::Cfg(cfg) => gen_cfg(cfg, in_frame),
+        Node::Let(_) => unreachable!("`let` outside of a list of children"),
+    }
+}
+
+/// Builds without `predicate` get `()` in place of the node, so it renders
+/// nothing and the node's type doesn't have to exist there, while its
+/// siblings keep their place in the tuple of children either way. `#[cfg]`
+/// on a tail expression isn't stable, so the node is bound with `let`.
+fn gen_cfg(cfg: Cfg, in_frame: bool) -> TokenStream {
+    let predicate = cfg.predicate;
+    let node = gen_node(*cfg.node, in_frame);
+    quote! {
+        {
+            #[cfg(#predicate)]
+            let __vdom_node = #node;
+            #[cfg(not(#predicate))]
+            let __vdom_node = ();
+            __vdom_node
+        }
+    }
+}
+
+/// Each arm renders a different node type, so the arms are wrapped in the
+/// variants of a `ChoiceN` node. Switching to another arm between renders
+/// replaces the node.
+fn gen_match(match_: Match, in_frame: bool) -> TokenStream {
+    const VARIANTS: [&str; 12] = ["A", "B", "C", "D", "E", "F", "G", "H", "I", "J", "K", "L"];
+
+    let len = match_.arms.len();
+    if len > VARIANTS.len() {
+        let msg = format!("a match can have at most {} arms", VARIANTS.len());
+        return syn::Error::new(match_.match_token.span, msg).to_compile_error();
+    }
+    let choice = Ident::new(&format!("Choice{}", len), Span::call_site());
+
+    let expr = match_.expr;
+    let arms = match_
+        .arms
+        .into_iter()
+        .zip(VARIANTS.iter())
+        .map(|(arm, variant)| {
+            let pats = arm.pats;
+            let guard = arm.guard.map(|guard| quote! {if #guard});
+            let body = gen_node(arm.body, in_frame);
+            let body = if len > 1 {
+                let variant = Ident::new(variant, Span::call_site());
+                quote! {vdom::vdom::node::#choice::#variant(#body)}
+            } else {
+                body
+            };
+            quote! {
+                #(#pats)|* #guard => #body,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    quote! {
+        match #expr {
+            #(#arms)*
+        }
     }
 }
 
-fn gen_tag(tag: Tag) -> TokenStream {
+fn gen_tag(tag: Tag, in_frame: bool) -> TokenStream {
     let tag_tag = LitStr::new(&tag.tag.to_string(), tag.tag.span());
 
-    let attrs = tag
+    let warnings = tag
         .attrs
+        .iter()
+        .filter_map(|attr| {
+            validate::check_attr(&tag.tag.to_string(), &attr.name.to_string())
+                .map(|msg| gen_warning(attr.name.span(), &msg))
+        })
+        .collect::<Vec<_>>();
+
+    if let Err(err) = check_duplicates(&tag.attrs) {
+        return err.to_compile_error();
+    }
+    if let Some(msg) = validate::check_children(&tag.tag.to_string(), !tag.children.is_empty()) {
+        return syn::Error::new(tag.tag.span(), msg).to_compile_error();
+    }
+
+    let mut attrs = match gen_attrs(tag.attrs) {
+        Ok(attrs) => attrs,
+        Err(err) => return err.to_compile_error(),
+    };
+    if cfg!(feature = "dev-locations") {
+        attrs.push(gen_location(&tag.tag));
+    }
+
+    let spreads = tag.spreads.into_iter().map(|expr| quote! {(#expr)});
+    let attrs = attrs
         .into_iter()
-        .map(gen_attr)
         .map(|attr| quote! {vdom::vdom::attr::AttrListEntry(#attr)})
+        .chain(spreads)
         .fold(None, |prev_attrs, attr| {
             match prev_attrs {
                 Some(prev_attrs) => Some(quote! {(#prev_attrs, #attr)}),
@@ -39,17 +205,103 @@ fn gen_tag(tag: Tag) -> TokenStream {
         })
         .unwrap_or_else(|| quote! {()});
 
-    let children = gen_nodes(tag.children);
+    let children = gen_nodes(tag.children, in_frame);
 
     quote! {
-        vdom::vdom::node::TagStatic::new(
-            #tag_tag,
-            #attrs,
-            #children,
+        {
+            #(#warnings)*
+            vdom::vdom::node::TagStatic::new(
+                #tag_tag,
+                #attrs,
+                #children,
+            )
+        }
+    }
+}
+
+/// `data-vdom-loc="file:line:col"` of where `tag` is declared. The macros
+/// are spanned at the tag so they expand to its location, and the value is
+/// null in builds without debug assertions so release output is unchanged.
+fn gen_location(tag: &Ident) -> TokenStream {
+    quote_spanned! {tag.span()=>
+        vdom::vdom::attr::AttrDyn::new(
+            "data-vdom-loc",
+            if cfg!(debug_assertions) {
+                Some(concat!(file!(), ":", line!(), ":", column!()))
+            } else {
+                None
+            },
         )
     }
 }
 
+/// An attribute given twice would be set twice, with the result depending on
+/// the driver, so it is rejected.
+fn check_duplicates(attrs: &[Attr]) -> syn::Result<()> {
+    for (i, attr) in attrs.iter().enumerate() {
+        if attrs[..i].iter().any(|prev| prev.name == attr.name) {
+            let msg = format!("attribute `{}` is given more than once", attr.name);
+            return Err(syn::Error::new(attr.name.span(), msg));
+        }
+    }
+    Ok(())
+}
+
+/// Proc macros can't emit warnings directly, so use of a deprecated item
+/// spanned at `at` makes the compiler emit `msg` for us.
+fn gen_warning(at: Span, msg: &str) -> TokenStream {
+    let ident = Ident::new("__vdom_warning", at);
+    quote_spanned! {at=>
+        {
+            #[deprecated(note = #msg)]
+            #[allow(non_camel_case_types)]
+            struct #ident;
+            let _ = #ident;
+        }
+    }
+}
+
+/// Generates the attributes of a tag. `show=(bool)` isn't an attribute of
+/// its own but is merged into the `style` attribute, see `show_style`.
+fn gen_attrs(mut attrs: Vec<Attr>) -> syn::Result<Vec<TokenStream>> {
+    let show = match attrs.iter().position(|attr| attr.name == "show") {
+        Some(i) => i,
+        None => return Ok(attrs.into_iter().map(gen_attr).collect()),
+    };
+    let show_attr = attrs.remove(show);
+    let show_expr = match show_attr.value {
+        AttrValue::Expr(expr) => expr,
+        _ => {
+            let msg = "`show` takes a `bool` expression, e.g. `show=(is_open)`";
+            return Err(syn::Error::new(show_attr.name.span(), msg));
+        }
+    };
+    let (i, style) = match attrs.iter().position(|attr| attr.name == "style") {
+        Some(i) => {
+            let style = match attrs.remove(i).value {
+                AttrValue::Str(lit_str) => lit_str.into_token_stream(),
+                AttrValue::Expr(expr) => expr.into_token_stream(),
+                AttrValue::True => quote! {vdom::vdom::attr::AttrValue::True},
+            };
+            (i, style)
+        }
+        None => (show, quote! {vdom::vdom::attr::AttrValue::Null}),
+    };
+
+    let mut attrs = attrs.into_iter().map(gen_attr).collect::<Vec<_>>();
+    let name = LitStr::new("style", show_attr.name.span());
+    attrs.insert(
+        i.min(attrs.len()),
+        quote! {
+            vdom::vdom::attr::AttrDyn::new(
+                #name,
+                vdom::vdom::style::show_style(#style, #show_expr),
+            )
+        },
+    );
+    Ok(attrs)
+}
+
 fn gen_attr(attr: Attr) -> TokenStream {
     let name = LitStr::new(&attr.name.to_string(), attr.name.span());
 
@@ -63,8 +315,13 @@ fn gen_attr(attr: Attr) -> TokenStream {
             }
         }
         AttrValue::Expr(expr) => {
-            quote! {
-                vdom::vdom::attr::AttrDyn::new(#name, #expr)
+            match gen_listener(&attr.name.to_string(), &expr) {
+                Some(listener) => listener,
+                None => {
+                    quote! {
+                        vdom::vdom::attr::AttrDyn::new(#name, #expr)
+                    }
+                }
             }
         }
         AttrValue::True => {
@@ -74,3 +331,29 @@ fn gen_attr(attr: Attr) -> TokenStream {
         }
     }
 }
+
+/// A listener for `on<event>=(closure)`. If the parameter of the closure has
+/// a type, e.g. `oninput=(|e: InputEvent| ..)`, the event is converted to it
+/// with `FromEvent`, otherwise the closure takes the event of the driver.
+/// Other expressions, e.g. the code of an inline handler, are attribute
+/// values.
+fn gen_listener(name: &str, expr: &Expr) -> Option<TokenStream> {
+    let closure = match expr {
+        Expr::Closure(closure) if name.starts_with("on") && name.len() > 2 => closure,
+        _ => return None,
+    };
+    let event = LitStr::new(&name[2..], Span::call_site());
+    let typed = match closure.inputs.first().map(|input| input.into_value()) {
+        Some(FnArg::Captured(_)) => true,
+        _ => false,
+    };
+    Some(if typed {
+        quote! {
+            vdom::vdom::attr::AttrListener::typed(#event, #expr)
+        }
+    } else {
+        quote! {
+            vdom::vdom::attr::AttrListener::new(#event, #expr)
+        }
+    })
+}
@@ -4,13 +4,210 @@ extern crate proc_macro;
 
 mod code_gen;
 mod parser;
+mod validate;
 
-use crate::parser::Nodes;
+use crate::parser::{FrameNodes, Nodes};
 use crate::proc_macro::TokenStream;
 use syn::parse_macro_input;
 
+/// Builds a node list from HTML-like markup. Literal text and attribute
+/// values become static nodes and attributes, while `{expr}` becomes dynamic.
+/// A node preceded by `#[cfg(..)]`, e.g. `#[cfg(feature = "beta")] p "Beta"`,
+/// is only rendered in builds where the predicate holds.
+/// Wrapping a template that should be fully static in `static_node` turns an
+/// accidental expression into a compile error:
+///
+/// ```
+/// # use vdom::{html::HtmlDriver, vdom::node::static_node};
+/// # use vdom_macro::html;
+/// let header = static_node::<HtmlDriver, _>(html! { h1 "Docs" });
+/// ```
+///
+/// ```compile_fail
+/// # use vdom::{html::HtmlDriver, vdom::node::static_node};
+/// # use vdom_macro::html;
+/// # const TITLE: &str = "Docs";
+/// // Fails to compile with "node is not static", as `format!` made the
+/// // text dynamic.
+/// let header = static_node::<HtmlDriver, _>(html! { h1 { {format!("{}", TITLE)} } });
+/// ```
+///
+/// Attribute names may contain hyphens, written without spaces around them,
+/// which is checked on nightly compilers, as only those tell where tokens are:
+///
+/// ```
+/// # use vdom::html::render_to_string;
+/// # use vdom_macro::html;
+/// let mut node = html! { div data-id="a" aria-hidden {} };
+/// assert_eq!(
+///     render_to_string(&mut node),
+///     "<div data-id=\"a\" aria-hidden></div>"
+/// );
+/// ```
+///
+/// ```compile_fail
+/// # use vdom::html::render_to_string;
+/// # use vdom_macro::html;
+/// // Fails to compile, as `data - id` isn't taken for `data-id`.
+/// let mut node = html! { div data - id="a" {} };
+/// render_to_string(&mut node);
+/// ```
+///
+/// A `match` renders the node of the matching arm, of at most 12 arms.
+/// Switching to another arm between renders replaces the node:
+///
+/// ```
+/// # use vdom::html::render_to_string;
+/// # use vdom_macro::html;
+/// let count = 3;
+/// let mut node = html! {
+///     match count {
+///         0 => p "None",
+///         n if n < 10 => p { {n} },
+///         _ => p "Many",
+///     }
+/// };
+/// assert_eq!(render_to_string(&mut node), "<p>3</p>");
+/// ```
+///
+/// ```compile_fail
+/// # use vdom::html::render_to_string;
+/// # use vdom_macro::html;
+/// # let count = 3;
+/// // Fails to compile with "a match can have at most 12 arms".
+/// let mut node = html! {
+///     match count {
+///         0 => p "0", 1 => p "1", 2 => p "2", 3 => p "3", 4 => p "4",
+///         5 => p "5", 6 => p "6", 7 => p "7", 8 => p "8", 9 => p "9",
+///         10 => p "10", 11 => p "11", _ => p "Many",
+///     }
+/// };
+/// render_to_string(&mut node);
+/// ```
+///
+/// Braces meant as text go in string literals, as `{}` and `{{..}}` are not
+/// taken for escapes as in `format!`:
+///
+/// ```
+/// # use vdom::html::render_to_string;
+/// # use vdom_macro::html;
+/// let mut node = html! { code "{}" code "{{nested}}" };
+/// assert_eq!(
+///     render_to_string(&mut node),
+///     "<code>{}</code><code>{{nested}}</code>"
+/// );
+/// ```
+///
+/// ```compile_fail
+/// # use vdom::html::render_to_string;
+/// # use vdom_macro::html;
+/// // Fails to compile with "`{expr}` embeds an expression as text; ...".
+/// let mut node = html! { code { {} } };
+/// render_to_string(&mut node);
+/// ```
+///
+/// ```compile_fail
+/// # use vdom::html::render_to_string;
+/// # use vdom_macro::html;
+/// # let nested = 1;
+/// // Fails to compile with "`{expr}` embeds an expression as text; ...".
+/// let mut node = html! { code { {{nested}} } };
+/// render_to_string(&mut node);
+/// ```
+///
+/// `let pat = expr;` among the children of an element binds `pat` for the
+/// siblings after it, e.g. to compute a value once for several nodes:
+///
+/// ```
+/// # use vdom::html::render_to_string;
+/// # use vdom_macro::html;
+/// let items = ["a", "b", "c"];
+/// let mut node = html! {
+///     ul {
+///         let (first, rest) = items.split_first().unwrap();
+///         li { {first} }
+///         li { {rest.len()} " more" }
+///     }
+/// };
+/// assert_eq!(
+///     render_to_string(&mut node),
+///     "<ul><li>a</li><li>2 more</li></ul>"
+/// );
+/// ```
+///
+/// The binding ends with the list it's in:
+///
+/// ```compile_fail
+/// # use vdom::html::render_to_string;
+/// # use vdom_macro::html;
+/// // Fails to compile with "cannot find value `name` in this scope".
+/// let mut node = html! {
+///     div { let name = "a"; p { {name} } }
+///     p { {name} }
+/// };
+/// render_to_string(&mut node);
+/// ```
+///
+/// `{name}` is short for `name={name}`, with a raw identifier such as
+/// `{r#type}` naming the `type` attribute. The variable has to be in scope,
+/// and the error for a missing one points at the shorthand:
+///
+/// ```
+/// # use vdom::html::render_to_string;
+/// # use vdom_macro::html;
+/// let value = "a";
+/// let mut node = html! { input {value} maxlength="8"; };
+/// assert_eq!(
+///     render_to_string(&mut node),
+///     "<input value=\"a\" maxlength=\"8\">"
+/// );
+/// ```
+///
+/// ```compile_fail
+/// # use vdom::html::render_to_string;
+/// # use vdom_macro::html;
+/// // Fails to compile with "cannot find value `value` in this scope".
+/// let mut node = html! { input {value}; };
+/// render_to_string(&mut node);
+/// ```
+///
+/// Attributes that only apply to some elements, such as `href` or `selected`,
+/// give a warning on other elements:
+///
+/// ```
+/// #![deny(deprecated)]
+/// # use vdom::html::render_to_string;
+/// # use vdom_macro::html;
+/// let mut node = html! { svg { use href="#icon"; } a href="/" "Home" };
+/// render_to_string(&mut node);
+/// ```
+///
+/// ```compile_fail
+/// #![deny(deprecated)]
+/// # use vdom::html::render_to_string;
+/// # use vdom_macro::html;
+/// let mut node = html! { div href="/" "Home" };
+/// render_to_string(&mut node);
+/// ```
 #[proc_macro]
 pub fn html(input: TokenStream) -> TokenStream {
     let nodes = parse_macro_input!(input as Nodes);
-    code_gen::gen_nodes(nodes.nodes).into()
+    code_gen::gen_root(nodes).into()
+}
+
+/// Like `html!`, with the text of `{expr}` formatted into a
+/// `vdom::vdom::arena::Frame` rather than a `String` of its own, so the nodes
+/// borrow from the frame, e.g. in a `FrameView`:
+///
+/// ```ignore
+/// fn view(&mut self, frame: &'a Frame) -> Self::Node {
+///     html_in!(frame, p { "Count: " {self.count} })
+/// }
+/// ```
+///
+/// Attribute values are taken as they are by `html!`.
+#[proc_macro]
+pub fn html_in(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as FrameNodes);
+    code_gen::gen_root_in(input.frame, input.nodes).into()
 }
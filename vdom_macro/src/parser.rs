@@ -1,41 +1,143 @@
+use crate::validate;
+use quote::__rt::{Span, TokenStream, TokenTree};
+use std::fmt;
 use syn::{
     braced, bracketed,
     ext::IdentExt,
     parenthesized,
     parse::{Parse, ParseStream, Result},
-    token, Expr, Ident, LitStr, Token,
+    token, Expr, ExprPath, Ident, LitStr, Pat, Token,
 };
 
 #[derive(Debug)]
 pub struct Nodes {
+    pub doctype: Option<Doctype>,
     pub nodes: Vec<Node>,
 }
 
 impl Parse for Nodes {
     fn parse(input: ParseStream<'_>) -> Result<Self> {
-        let mut nodes = Vec::new();
-        while !input.is_empty() {
+        let doctype = if input.peek(Token![<]) && input.peek2(Token![!]) {
+            Some(input.parse()?)
+        } else {
+            None
+        };
+        Ok(Nodes {
+            doctype,
+            nodes: parse_children(input)?,
+        })
+    }
+}
+
+/// The input of `html_in!`, the frame to allocate in followed by a comma and
+/// the nodes.
+#[derive(Debug)]
+pub struct FrameNodes {
+    pub frame: Expr,
+    pub nodes: Nodes,
+}
+
+impl Parse for FrameNodes {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let frame = input.parse()?;
+        input.parse::<Token![,]>()?;
+        Ok(FrameNodes {
+            frame,
+            nodes: input.parse()?,
+        })
+    }
+}
+
+/// `<!DOCTYPE html>` in front of the nodes, which makes them a document.
+#[derive(Debug)]
+pub struct Doctype {
+    pub doctype: LitStr,
+}
+
+impl Parse for Doctype {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        input.parse::<Token![<]>()?;
+        input.parse::<Token![!]>()?;
+        let keyword = Ident::parse_any(input)?;
+        if !keyword.to_string().eq_ignore_ascii_case("doctype") {
+            return Err(syn::Error::new(keyword.span(), "expected `DOCTYPE`"));
+        }
+
+        let mut words = Vec::new();
+        while !input.peek(Token![>]) {
+            words.push(Ident::parse_any(input)?.to_string());
+        }
+        input.parse::<Token![>]>()?;
+
+        Ok(Doctype {
+            doctype: LitStr::new(&words.join(" "), keyword.span()),
+        })
+    }
+}
+
+/// Nodes up to the end of `input`, which may include `let` bindings for the
+/// nodes after them.
+fn parse_children(input: ParseStream<'_>) -> Result<Vec<Node>> {
+    let mut nodes = Vec::new();
+    while !input.is_empty() {
+        if input.peek(Token![let]) {
+            input.parse::<Token![let]>()?;
+            nodes.push(Node::Let(parse_until(input, |input| {
+                input.peek(Token![;])
+            })?));
+            input.parse::<Token![;]>()?;
+        } else {
             nodes.push(input.parse()?);
         }
-        Ok(Nodes { nodes })
     }
+    Ok(nodes)
 }
 
 #[derive(Debug)]
 pub enum Node {
     Tag(Tag),
     Text(LitStr),
+    /// `(expr)`, a value implementing `Node` embedded as is.
     Expr(Expr),
+    /// `{expr}`, a value implementing `Display` embedded as text. Braces
+    /// meant as text go in string literals, e.g. `"{}"`, so `{}` and `{{..}}`
+    /// are rejected rather than taken for escapes as in `format!`.
+    Display(Expr),
+    Match(Match),
+    /// `let pat = expr;`, binding `pat` for the following siblings. Only
+    /// allowed in lists of children.
+    Let(TokenStream),
+    Cfg(Cfg),
+}
+
+const BRACE_ESCAPE_MSG: &str = "`{expr}` embeds an expression as text; to write braces as \
+                                text, put them in a string literal such as \"{}\" or \"{{nested}}\"";
+
+/// Whether `input` is a single `{..}` group, as in `{{..}}`.
+fn is_braced_only(input: ParseStream<'_>) -> bool {
+    let fork = input.fork();
+    input.peek(token::Brace) && fork.parse::<TokenTree>().is_ok() && fork.is_empty()
 }
 
 impl Parse for Node {
     fn parse(input: ParseStream<'_>) -> Result<Self> {
-        let res = if input.peek(token::Paren) {
+        let res = if input.peek(Token![#]) {
+            Node::Cfg(input.parse()?)
+        } else if input.peek(token::Paren) {
             let expr;
             parenthesized!(expr in input);
             Node::Expr(expr.parse()?)
+        } else if input.peek(token::Brace) {
+            let expr;
+            let brace = braced!(expr in input);
+            if expr.is_empty() || is_braced_only(&expr) {
+                return Err(syn::Error::new(brace.span, BRACE_ESCAPE_MSG));
+            }
+            Node::Display(expr.parse()?)
         } else if input.peek(LitStr) {
             Node::Text(input.parse()?)
+        } else if input.peek(Token![match]) {
+            Node::Match(input.parse()?)
         } else {
             Node::Tag(input.parse()?)
         };
@@ -43,10 +145,131 @@ impl Parse for Node {
     }
 }
 
+/// `#[cfg(predicate)] node`, which only renders `node` in builds where
+/// `predicate` holds, as with `#[cfg]` on Rust items.
+#[derive(Debug)]
+pub struct Cfg {
+    pub predicate: TokenStream,
+    pub node: Box<Node>,
+}
+
+impl Parse for Cfg {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        input.parse::<Token![#]>()?;
+        let content;
+        let bracket = bracketed!(content in input);
+        let name = content.parse::<Ident>()?;
+        if name != "cfg" {
+            let msg = "only `#[cfg(..)]` is supported on nodes";
+            return Err(syn::Error::new(name.span(), msg));
+        }
+        let predicate;
+        parenthesized!(predicate in content);
+        let predicate = predicate.parse()?;
+        if !content.is_empty() {
+            return Err(content.error("expected `]`"));
+        }
+
+        // The binding would be missing for the following siblings in builds
+        // where `predicate` doesn't hold.
+        if input.peek(Token![let]) {
+            let msg = "`#[cfg(..)]` is not supported on `let`";
+            return Err(syn::Error::new(bracket.span, msg));
+        }
+        Ok(Cfg {
+            predicate,
+            node: Box::new(input.parse()?),
+        })
+    }
+}
+
+/// `match expr { pat if guard => node, ... }`, where each arm renders a
+/// single node.
+#[derive(Debug)]
+pub struct Match {
+    pub match_token: Token![match],
+    pub expr: Expr,
+    pub arms: Vec<MatchArm>,
+}
+
+impl Parse for Match {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let match_token = input.parse()?;
+        // A struct literal can't be the scrutinee, so the first brace opens
+        // the arms.
+        let expr = syn::parse2(parse_until(input, |input| input.peek(token::Brace))?)?;
+
+        let content;
+        braced!(content in input);
+        let mut arms = Vec::new();
+        while !content.is_empty() {
+            arms.push(content.parse()?);
+        }
+
+        Ok(Match {
+            match_token,
+            expr,
+            arms,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct MatchArm {
+    pub pats: Vec<Pat>,
+    pub guard: Option<Expr>,
+    pub body: Node,
+}
+
+impl Parse for MatchArm {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let mut pats = vec![input.parse()?];
+        while input.peek(Token![|]) {
+            input.parse::<Token![|]>()?;
+            pats.push(input.parse()?);
+        }
+
+        let guard = if input.peek(Token![if]) {
+            input.parse::<Token![if]>()?;
+            Some(syn::parse2(parse_until(input, |input| {
+                input.peek(Token![=>])
+            })?)?)
+        } else {
+            None
+        };
+
+        input.parse::<Token![=>]>()?;
+        let body = input.parse()?;
+        if input.peek(Token![,]) {
+            input.parse::<Token![,]>()?;
+        }
+
+        Ok(MatchArm { pats, guard, body })
+    }
+}
+
+/// The tokens up to where `stop` matches, for expressions that `Expr` would
+/// otherwise parse too far, e.g. into a following block.
+fn parse_until<F>(input: ParseStream<'_>, stop: F) -> Result<TokenStream>
+where
+    F: Fn(ParseStream<'_>) -> bool,
+{
+    let mut tokens = Vec::new();
+    while !input.is_empty() && !stop(input) {
+        tokens.push(input.parse::<TokenTree>()?);
+    }
+    if tokens.is_empty() {
+        return Err(input.error("expected an expression"));
+    }
+    Ok(tokens.into_iter().collect())
+}
+
 #[derive(Debug)]
 pub struct Tag {
     pub tag: Ident,
     pub attrs: Vec<Attr>,
+    /// `{..expr}`, lists of attributes spread into the tag after `attrs`.
+    pub spreads: Vec<Expr>,
     pub children: Vec<Node>,
 }
 
@@ -55,17 +278,24 @@ impl Parse for Tag {
         let tag = Ident::parse_any(input)?;
 
         let mut attrs = Vec::new();
-        while input.fork().parse::<Attr>().is_ok() {
-            attrs.push(input.parse()?);
+        let mut spreads = Vec::new();
+        loop {
+            if input.peek(token::Brace) && parse_spread(&input.fork()).is_ok() {
+                spreads.push(parse_spread(input)?);
+            } else if input.peek(token::Brace) && parse_shorthand(&input.fork()).is_ok() {
+                attrs.push(parse_shorthand(input)?);
+            } else if input.fork().parse::<Attr>().is_ok() {
+                attrs.push(input.parse()?);
+            } else {
+                break;
+            }
         }
 
         let mut children = Vec::new();
         if input.peek(token::Brace) {
             let content;
             braced!(content in input);
-            while !content.is_empty() {
-                children.push(content.parse()?);
-            }
+            children = parse_children(&content)?;
         } else if input.peek(Token![;]) {
             input.parse::<Token![;]>()?;
         } else {
@@ -75,21 +305,125 @@ impl Parse for Tag {
         Ok(Tag {
             tag,
             attrs,
+            spreads,
             children,
         })
     }
 }
 
+/// `{..expr}`, which can't be taken for children since `..` doesn't start a
+/// node.
+fn parse_spread(input: ParseStream<'_>) -> Result<Expr> {
+    let content;
+    braced!(content in input);
+    content.parse::<Token![..]>()?;
+    content.parse()
+}
+
+/// `{name}`, short for `name=(name)` like the field shorthand of struct
+/// literals, e.g. `input {value};`. It can't be taken for a block of
+/// children, since a tag name alone isn't a node, and only works for names
+/// without hyphens, which are Rust identifiers. A raw identifier such as
+/// `{r#type}` names the attribute `type`.
+fn parse_shorthand(input: ParseStream<'_>) -> Result<Attr> {
+    let content;
+    braced!(content in input);
+    let ident = content.parse::<Ident>()?;
+    if !content.is_empty() {
+        return Err(content.error("expected `}`"));
+    }
+    let name = ident.to_string();
+    let name = name.trim_start_matches("r#").to_string();
+    Ok(Attr {
+        name: AttrName {
+            name,
+            span: ident.span(),
+        },
+        value: AttrValue::Expr(Expr::Path(ExprPath {
+            attrs: Vec::new(),
+            qself: None,
+            path: ident.into(),
+        })),
+        condition: parse_condition(input)?,
+    })
+}
+
+/// The name of an attribute, which unlike the name of a tag in `html!` may
+/// contain hyphens, e.g. `data-id`. The hyphens have to be written right
+/// next to the parts around them, so `data - id` isn't taken for `data-id`.
+#[derive(Debug)]
+pub struct AttrName {
+    name: String,
+    span: Span,
+}
+
+impl AttrName {
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Parse for AttrName {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let first = Ident::parse_any(input)?;
+        let mut name = first.to_string();
+        let mut end = first.span();
+        while input.peek(Token![-]) {
+            let hyphen = input.parse::<Token![-]>()?;
+            let part = Ident::parse_any(input)?;
+            if !adjacent(end, hyphen.spans[0]) || !adjacent(hyphen.spans[0], part.span()) {
+                let msg = "expected no space around `-` in an attribute name";
+                return Err(syn::Error::new(hyphen.spans[0], msg));
+            }
+            name.push('-');
+            name.push_str(&part.to_string());
+            end = part.span();
+        }
+        Ok(AttrName {
+            name,
+            span: first.span(),
+        })
+    }
+}
+
+/// Whether `next` starts where `prev` ends. Only nightly compilers tell where
+/// a span is, so spans without a location, with line 0, count as adjacent.
+fn adjacent(prev: Span, next: Span) -> bool {
+    let (end, start) = (prev.end(), next.start());
+    end.line == 0 || start.line == 0 || (end.line, end.column) == (start.line, start.column)
+}
+
+impl<T> PartialEq<T> for AttrName
+where
+    T: AsRef<str> + ?Sized,
+{
+    fn eq(&self, other: &T) -> bool {
+        self.name == other.as_ref()
+    }
+}
+
+impl AsRef<str> for AttrName {
+    fn as_ref(&self) -> &str {
+        &self.name
+    }
+}
+
+impl fmt::Display for AttrName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
 #[derive(Debug)]
 pub struct Attr {
-    pub name: Ident,
+    pub name: AttrName,
     pub value: AttrValue,
     pub condition: Option<Expr>,
 }
 
 impl Parse for Attr {
     fn parse(input: ParseStream<'_>) -> Result<Self> {
-        let name = Ident::parse_any(input)?;
+        let name = input.parse::<AttrName>()?;
 
         let value = if input.peek(Token![=]) {
             input.parse::<Token![=]>()?;
@@ -103,26 +437,33 @@ impl Parse for Attr {
         } else if input.peek(Token![?]) {
             input.parse::<Token![?]>()?;
             AttrValue::True
+        } else if name.name.contains('-') || validate::is_boolean_attr(&name.name) {
+            // A bare name as in HTML, which can't be taken for a child since
+            // tags in `html!` are single identifiers without hyphens, and
+            // none is named like a boolean attribute.
+            AttrValue::True
         } else {
             Err(input.error("expected `?` or `=`"))?
         };
 
-        let condition = if input.peek(token::Bracket) {
-            let condition;
-            bracketed!(condition in input);
-            Some(condition.parse()?)
-        } else {
-            None
-        };
-
         Ok(Attr {
             name,
             value,
-            condition,
+            condition: parse_condition(input)?,
         })
     }
 }
 
+/// `[expr]` after an attribute, which only sets it if `expr` is true.
+fn parse_condition(input: ParseStream<'_>) -> Result<Option<Expr>> {
+    if !input.peek(token::Bracket) {
+        return Ok(None);
+    }
+    let condition;
+    bracketed!(condition in input);
+    Ok(Some(condition.parse()?))
+}
+
 #[derive(Debug)]
 pub enum AttrValue {
     Str(LitStr),
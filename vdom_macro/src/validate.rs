@@ -0,0 +1,111 @@
+use vdom_element_info as element_info;
+
+/// Attributes that only apply to some elements, and the elements they apply
+/// to. Attributes not listed here are accepted on any element.
+const ELEMENT_ATTRS: &[(&str, &[&str])] = &[
+    ("action", &["form"]),
+    ("alt", &["area", "img", "input"]),
+    ("checked", &["input"]),
+    ("cols", &["textarea"]),
+    ("colspan", &["td", "th"]),
+    ("crossorigin", &["audio", "img", "link", "script", "video"]),
+    ("for", &["label", "output"]),
+    // SVG elements link with `href` as well.
+    (
+        "href",
+        &[
+            "a",
+            "animate",
+            "animateMotion",
+            "animateTransform",
+            "area",
+            "base",
+            "feImage",
+            "image",
+            "linearGradient",
+            "link",
+            "mpath",
+            "pattern",
+            "radialGradient",
+            "script",
+            "set",
+            "textPath",
+            "use",
+        ],
+    ),
+    ("integrity", &["link", "script"]),
+    ("method", &["form"]),
+    ("multiple", &["input", "select"]),
+    ("placeholder", &["input", "textarea"]),
+    ("rows", &["textarea"]),
+    ("rowspan", &["td", "th"]),
+    ("selected", &["option"]),
+    (
+        "src",
+        &[
+            "audio", "embed", "iframe", "img", "input", "script", "source", "track", "video",
+        ],
+    ),
+    ("target", &["a", "area", "base", "form"]),
+];
+
+/// The boolean attributes of HTML, which may be written as a bare name
+/// without `?`, e.g. `input disabled;`. None is also the name of an element.
+const BOOLEAN_ATTRS: &[&str] = &[
+    "allowfullscreen",
+    "async",
+    "autofocus",
+    "autoplay",
+    "checked",
+    "controls",
+    "default",
+    "defer",
+    "disabled",
+    "formnovalidate",
+    "hidden",
+    "inert",
+    "ismap",
+    "itemscope",
+    "loop",
+    "multiple",
+    "muted",
+    "nomodule",
+    "novalidate",
+    "open",
+    "playsinline",
+    "readonly",
+    "required",
+    "reversed",
+    "selected",
+];
+
+pub fn is_boolean_attr(attr: &str) -> bool {
+    BOOLEAN_ATTRS.contains(&attr)
+}
+
+/// Returns a warning message if `attr` is known not to apply to `tag`.
+pub fn check_attr(tag: &str, attr: &str) -> Option<String> {
+    let (_, tags) = ELEMENT_ATTRS.iter().find(|(name, _)| *name == attr)?;
+    if tags.contains(&tag) {
+        None
+    } else {
+        Some(format!(
+            "attribute `{}` is not supported on `<{}>`, expected one of: {}",
+            attr,
+            tag,
+            tags.join(", ")
+        ))
+    }
+}
+
+/// Returns an error message if `tag` can't have children but does.
+pub fn check_children(tag: &str, has_children: bool) -> Option<String> {
+    if has_children && element_info::is_void(tag) {
+        Some(format!(
+            "`<{}>` is a void element and can't have children",
+            tag
+        ))
+    } else {
+        None
+    }
+}
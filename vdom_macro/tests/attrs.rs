@@ -0,0 +1,39 @@
+use vdom::{
+    driver::DriverCtx,
+    html::{render_to_string, HtmlDriver},
+    vdom::{
+        dom::to_dom,
+        patch::{apply, diff, Patch},
+    },
+};
+use vdom_macro::html;
+
+#[test]
+fn bare_names_render_as_written() {
+    let mut node = html! { input disabled required data-foo; };
+    assert_eq!(
+        render_to_string(&mut node),
+        "<input disabled required data-foo>"
+    );
+}
+
+#[test]
+fn attributes_turning_false_are_removed() {
+    let driver_ctx = DriverCtx::new(HtmlDriver);
+    let input = |required: bool| html! { input disabled required=(required); };
+    let mut ancestor = input(true);
+    let mut dom = to_dom(&driver_ctx, &mut ancestor);
+    assert_eq!(render_to_string(&mut dom), "<input disabled required>");
+
+    let mut curr = input(false);
+    let patches = diff(&driver_ctx, &mut curr, &mut ancestor);
+    match &patches[..] {
+        [Patch::RemoveAttr { path, name }] => {
+            assert_eq!(path.indices(), &[0]);
+            assert_eq!(name, "required");
+        }
+        patches => panic!("expected `required` to be removed, got {:?}", patches),
+    }
+    apply(&mut dom, patches).unwrap();
+    assert_eq!(render_to_string(&mut dom), "<input disabled>");
+}
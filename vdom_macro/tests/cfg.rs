@@ -0,0 +1,31 @@
+use vdom::html::render_to_string;
+use vdom_macro::html;
+
+#[test]
+fn disabled_nodes_render_nothing() {
+    let count = 2;
+    let mut node = html! {
+        ul {
+            #[cfg(all())] li "a"
+            #[cfg(any())] li "b"
+            #[cfg(any())] {count}
+            li { {count} }
+        }
+    };
+    assert_eq!(render_to_string(&mut node), "<ul><li>a</li><li>2</li></ul>");
+}
+
+#[test]
+fn nodes_follow_the_feature() {
+    let mut node = html! {
+        p "Stable"
+        #[cfg(feature = "cfg-test")] p "Beta"
+        #[cfg(not(feature = "cfg-test"))] p { "Coming " em "soon" }
+    };
+    let expected = if cfg!(feature = "cfg-test") {
+        "<p>Stable</p><p>Beta</p>"
+    } else {
+        "<p>Stable</p><p>Coming <em>soon</em></p>"
+    };
+    assert_eq!(render_to_string(&mut node), expected);
+}
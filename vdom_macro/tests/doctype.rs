@@ -0,0 +1,23 @@
+use vdom_macro::html;
+
+#[test]
+fn documents_start_with_their_doctype() {
+    let mut document = html! {
+        <!DOCTYPE html>
+        html lang="en" {
+            head { title "Docs" }
+            body { p "Hello" }
+        }
+    };
+    assert_eq!(
+        document.render_to_string(),
+        "<!DOCTYPE html><html lang=\"en\"><head><title>Docs</title></head>\
+         <body><p>Hello</p></body></html>"
+    );
+}
+
+#[test]
+fn doctypes_are_case_insensitive() {
+    let mut document = html! { <!doctype html> html {} };
+    assert_eq!(document.render_to_string(), "<!DOCTYPE html><html></html>");
+}
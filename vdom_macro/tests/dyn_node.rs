@@ -0,0 +1,44 @@
+use vdom::{
+    html::{render_to_string, HtmlDriver},
+    vdom::node::{DynNode, TagStatic, TextStatic},
+};
+use vdom_macro::html;
+
+#[test]
+fn vec_of_macro_trees() {
+    let title = "a";
+    let mut nodes: Vec<DynNode<HtmlDriver>> = vec![
+        DynNode::new(html! {
+            div class="card" {
+                h2 { {title} }
+                "!"
+            }
+        })
+        .unwrap(),
+        DynNode::new(html! { a href="/x" "go" }).unwrap(),
+        DynNode::new(html! { br; p { "one" "two" } }).unwrap(),
+    ];
+    assert_eq!(
+        render_to_string(&mut nodes),
+        "<div class=\"card\"><h2>a</h2>!</div><a href=\"/x\">go</a><br><p>onetwo</p>"
+    );
+}
+
+#[test]
+fn prebuilt_nodes_are_embedded_with_parens() {
+    let sidebar = TagStatic::<HtmlDriver, _, ()>::new("aside", (), TextStatic::new("menu"));
+    let markup = "<b>";
+    let mut node = html! { div { (sidebar) {markup} } };
+    // Unlike `(..)`, `{..}` makes a text, which is escaped.
+    assert_eq!(
+        render_to_string(&mut node),
+        "<div><aside>menu</aside>&lt;b&gt;</div>"
+    );
+
+    let footer = html! { footer { "end" } };
+    let mut node = html! { main { (node) (footer) } };
+    assert_eq!(
+        render_to_string(&mut node),
+        "<main><div><aside>menu</aside>&lt;b&gt;</div><footer>end</footer></main>"
+    );
+}
@@ -0,0 +1,38 @@
+use vdom::{
+    html::{render_to_string, HtmlDriver},
+    vdom::{
+        arena::{Frame, TextRef},
+        node::{TagStatic, TextStatic},
+    },
+};
+use vdom_macro::html_in;
+
+#[test]
+fn display_text_borrows_from_the_frame() {
+    let frame = Frame::new();
+    let count = 3;
+    let mut node: TagStatic<HtmlDriver, (TextStatic<HtmlDriver>, TextRef<'_, HtmlDriver>), ()> =
+        html_in!(frame, p { "Count: " {count} });
+    // Formatted into the frame rather than a `String` of its own.
+    assert_eq!(render_to_string(&mut node), "<p>Count: 3</p>");
+    assert!(frame.capacity() > 0);
+}
+
+#[test]
+fn nested_and_matched_text() {
+    let frame = Frame::new();
+    let items = vec!["a", "b"];
+    let mut node = html_in!(&frame,
+        ul class="list" {
+            li { {items.len()} }
+            match items.first() {
+                Some(item) => li { {item} },
+                None => li "none",
+            }
+        }
+    );
+    assert_eq!(
+        render_to_string(&mut node),
+        "<ul class=\"list\"><li>2</li><li>a</li></ul>"
+    );
+}
@@ -0,0 +1,86 @@
+use std::{cell::RefCell, convert::Infallible, rc::Rc};
+use vdom::{
+    html::{render_to_string, HtmlDriver},
+    vdom::{
+        attr::{Attr, AttrVisitor, FromEvent},
+        node::{Comp, CompNode, Node, NodeVisitor, Tag, Text},
+    },
+};
+use vdom_macro::html;
+
+struct Clicked;
+
+impl FromEvent<HtmlDriver> for Clicked {
+    fn from_event(_event: ()) -> Option<Clicked> {
+        Some(Clicked)
+    }
+}
+
+/// Calls the listeners of the top-level tags, noting the events they listen
+/// for.
+#[derive(Default)]
+struct Dispatch(Vec<String>);
+
+impl NodeVisitor<HtmlDriver> for Dispatch {
+    type Err = Infallible;
+
+    fn on_tag<T>(&mut self, _index: usize, tag: &mut T) -> Result<(), Infallible>
+    where
+        T: Tag<HtmlDriver>,
+    {
+        tag.visit_attrs(self)
+    }
+
+    fn on_text<T>(&mut self, _index: usize, _text: &mut T) -> Result<(), Infallible>
+    where
+        T: Text<HtmlDriver>,
+    {
+        Ok(())
+    }
+
+    fn on_comp<C>(
+        &mut self,
+        index: &mut usize,
+        comp: &mut CompNode<HtmlDriver, C>,
+    ) -> Result<(), Infallible>
+    where
+        C: Comp<HtmlDriver>,
+    {
+        comp.visit_rendered(index, self)
+    }
+}
+
+impl AttrVisitor<HtmlDriver> for Dispatch {
+    type Err = Infallible;
+
+    fn on_attr<A>(&mut self, attr: &mut A) -> Result<(), Infallible>
+    where
+        A: Attr<HtmlDriver>,
+    {
+        if let Some(listener) = attr.listener() {
+            self.0.push(attr.name().to_string());
+            listener(());
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn closures_of_on_attributes_are_listeners() {
+    let clicks = Rc::new(RefCell::new(Vec::new()));
+    let (typed, raw) = (clicks.clone(), clicks.clone());
+    let mut node = html! {
+        button onclick=(move |_: Clicked| typed.borrow_mut().push("typed")) "a"
+        input onchange=(move |_| raw.borrow_mut().push("raw"));
+        a onclick="return false" "b"
+    };
+    assert_eq!(
+        render_to_string(&mut node),
+        "<button>a</button><input><a onclick=\"return false\">b</a>"
+    );
+
+    let mut dispatch = Dispatch::default();
+    node.visit(&mut 0, &mut dispatch).unwrap();
+    assert_eq!(dispatch.0, ["click", "change"]);
+    assert_eq!(*clicks.borrow(), ["typed", "raw"]);
+}
@@ -0,0 +1,21 @@
+use vdom::html::render_to_string;
+use vdom_macro::html;
+
+#[test]
+fn elements_carry_their_location_with_the_feature() {
+    let line = line!() + 2;
+    let mut node = html! {
+        p { em "a" }
+    };
+    let html = render_to_string(&mut node);
+    if cfg!(all(feature = "dev-locations", debug_assertions)) {
+        // Each element is located where its tag is.
+        let loc = |column| format!("locations.rs:{}:{}\"", line, column);
+        assert!(html.starts_with("<p data-vdom-loc=\""), "{}", html);
+        assert!(html.contains(&loc(9)), "{}", html);
+        assert!(html.contains(&loc(13)), "{}", html);
+        assert!(html.ends_with("\">a</em></p>"), "{}", html);
+    } else {
+        assert_eq!(html, "<p><em>a</em></p>");
+    }
+}
@@ -0,0 +1,80 @@
+use vdom::{
+    driver::DriverCtx,
+    html::{render_to_string, HtmlDriver},
+    vdom::{
+        dom::to_dom,
+        patch::{self, Patch},
+    },
+};
+use vdom_macro::html;
+
+enum State {
+    Loading,
+    Empty,
+    Ready(u32),
+    Failed(&'static str),
+}
+
+#[test]
+fn four_arms_render_their_own_nodes() {
+    let view = |state: &State| {
+        html! {
+            match state {
+                State::Loading => span class="spinner" {},
+                State::Empty => p "Nothing yet",
+                State::Ready(count) => div { {count} " items" },
+                State::Failed(err) => p class="err" { {err} },
+            }
+        }
+    };
+    let states = [
+        (State::Loading, "<span class=\"spinner\"></span>"),
+        (State::Empty, "<p>Nothing yet</p>"),
+        (State::Ready(3), "<div>3 items</div>"),
+        (State::Failed("timeout"), "<p class=\"err\">timeout</p>"),
+    ];
+    for (state, html) in &states {
+        assert_eq!(render_to_string(&mut view(state)), *html);
+    }
+
+    // Another arm replaces the node, while the same one is diffed.
+    let driver_ctx = DriverCtx::new(HtmlDriver);
+    let patches = patch::diff(
+        &driver_ctx,
+        &mut view(&State::Ready(4)),
+        &mut view(&State::Loading),
+    );
+    match &patches[..] {
+        [Patch::RemoveNode { .. }, Patch::InsertNode { .. }] => {}
+        patches => panic!("expected the node to be replaced, got {:?}", patches),
+    }
+    let mut dom = to_dom(&driver_ctx, &mut view(&State::Ready(3)));
+    let patches = patch::diff(
+        &driver_ctx,
+        &mut view(&State::Ready(4)),
+        &mut view(&State::Ready(3)),
+    );
+    match &patches[..] {
+        [Patch::SetText { text, .. }] => assert_eq!(text, "4"),
+        patches => panic!("expected the text to be set, got {:?}", patches),
+    }
+    patch::apply(&mut dom, patches).unwrap();
+    assert_eq!(render_to_string(&mut dom), "<div>4 items</div>");
+}
+
+#[test]
+fn arms_take_guards_and_bindings() {
+    let view = |count: Option<u32>| {
+        html! {
+            match count {
+                Some(n) if n > 99 => b "99+",
+                Some(n @ 1..=99) => b { {n} },
+                Some(_) | None => i "none",
+            }
+        }
+    };
+    assert_eq!(render_to_string(&mut view(Some(120))), "<b>99+</b>");
+    assert_eq!(render_to_string(&mut view(Some(7))), "<b>7</b>");
+    assert_eq!(render_to_string(&mut view(Some(0))), "<i>none</i>");
+    assert_eq!(render_to_string(&mut view(None)), "<i>none</i>");
+}
@@ -0,0 +1,20 @@
+use vdom::html::render_to_string;
+use vdom_macro::html;
+
+#[test]
+fn shorthand_mixes_with_other_attributes() {
+    let value = "a";
+    let placeholder = String::from("Name");
+    let mut node = html! { input type="text" {value} disabled {placeholder}; };
+    assert_eq!(
+        render_to_string(&mut node),
+        "<input type=\"text\" value=\"a\" disabled placeholder=\"Name\">"
+    );
+}
+
+#[test]
+fn raw_identifiers_name_the_attribute() {
+    let r#type = "email";
+    let mut node = html! { input {r#type}; };
+    assert_eq!(render_to_string(&mut node), "<input type=\"email\">");
+}
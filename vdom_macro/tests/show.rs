@@ -0,0 +1,62 @@
+use vdom::{
+    driver::DriverCtx,
+    html::{render_to_string, HtmlDriver},
+    vdom::{
+        attr::AttrValue,
+        dom::to_dom,
+        patch::{apply, diff, Patch},
+    },
+};
+use vdom_macro::html;
+
+#[test]
+fn show_is_merged_into_the_style() {
+    let mut node = html! {
+        p id="a" style="color: red" show=(false) class="b" { "x" }
+    };
+    assert_eq!(
+        render_to_string(&mut node),
+        r#"<p id="a" style="color: red; display: none;" class="b">x</p>"#
+    );
+
+    // Without a `style`, one is added where `show` is.
+    let mut node = html! { p id="a" show=(false) class="b" { "x" } };
+    assert_eq!(
+        render_to_string(&mut node),
+        r#"<p id="a" style="display: none;" class="b">x</p>"#
+    );
+
+    let mut node = html! { p style="color: red;" show=(true) { "x" } };
+    assert_eq!(
+        render_to_string(&mut node),
+        r#"<p style="color: red;">x</p>"#
+    );
+}
+
+#[test]
+fn toggling_show_sets_the_style() {
+    let driver_ctx = DriverCtx::new(HtmlDriver);
+    let p = |show: bool| html! { p style="color: red" show=(show) { "x" } };
+    let mut ancestor = p(false);
+    let mut dom = to_dom(&driver_ctx, &mut ancestor);
+
+    let mut curr = p(true);
+    let patches = diff(&driver_ctx, &mut curr, &mut ancestor);
+    match &patches[..] {
+        [Patch::SetAttr { path, name, value }] => {
+            assert_eq!(path.indices(), &[0]);
+            assert_eq!(name, "style");
+            assert_eq!(*value, AttrValue::String("color: red".to_owned()));
+        }
+        patches => panic!("expected `style` to be set, got {:?}", patches),
+    }
+    apply(&mut dom, patches).unwrap();
+    assert_eq!(render_to_string(&mut dom), r#"<p style="color: red">x</p>"#);
+
+    let mut next = p(false);
+    apply(&mut dom, diff(&driver_ctx, &mut next, &mut curr)).unwrap();
+    assert_eq!(
+        render_to_string(&mut dom),
+        r#"<p style="color: red; display: none;">x</p>"#
+    );
+}
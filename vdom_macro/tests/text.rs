@@ -0,0 +1,47 @@
+use vdom::{
+    html::{render_to_string, HtmlDriver},
+    vdom::node::Tag,
+};
+use vdom_macro::html;
+
+fn children_len<T>(tag: &mut T) -> usize
+where
+    T: Tag<HtmlDriver>,
+{
+    tag.children_len().unwrap()
+}
+
+#[test]
+fn adjacent_literals_are_one_text_node() {
+    let mut merged = html! {
+        p {
+            "Split over "
+            "several "
+            "lines"
+        }
+    };
+    assert_eq!(
+        render_to_string(&mut merged),
+        "<p>Split over several lines</p>"
+    );
+    assert_eq!(children_len(&mut merged), 1);
+}
+
+#[test]
+fn literals_are_not_merged_across_expressions() {
+    let count = 3;
+    let mut node = html! { p { "a" "b" {count} "c" "d" } };
+    assert_eq!(render_to_string(&mut node), "<p>ab3cd</p>");
+    assert_eq!(children_len(&mut node), 3);
+
+    let mut node = html! { p { "a" br; "b" } };
+    assert_eq!(render_to_string(&mut node), "<p>a<br>b</p>");
+    assert_eq!(children_len(&mut node), 3);
+}
+
+#[test]
+fn braces_in_literals_are_text() {
+    let nested = 1;
+    let mut node = html! { p { "{}" "{{nested}}" {nested} "{" } };
+    assert_eq!(render_to_string(&mut node), "<p>{}{{nested}}1{</p>");
+}
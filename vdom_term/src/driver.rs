@@ -0,0 +1,97 @@
+use crate::grid::Grid;
+use futures::Future;
+use std::io;
+use vdom::{
+    driver::{Driver, DriverCtx},
+    vdom::{
+        dom::{to_dom, DomNode},
+        node::Node,
+        patch::{apply, diff},
+        path::PathError,
+    },
+};
+
+/// Driver for rendering nodes to a terminal, e.g. for CLI dashboards.
+///
+/// Futures can't be spawned, since there is no event loop to run them on.
+pub struct TermDriver;
+
+impl Driver for TermDriver {
+    type AttrStore = ();
+    type TagStore = ();
+    type TextStore = ();
+    type CompStore = ();
+    type Event = ();
+
+    fn new_attr_store() {}
+
+    fn new_tag_store() {}
+
+    fn new_text_store() {}
+
+    fn new_comp_store() {}
+
+    fn spawn<F>(&mut self, _fut: F)
+    where
+        F: Future<Output = ()> + 'static,
+    {
+    }
+}
+
+/// A tree of nodes mounted into a grid of terminal cells, see `Grid` for
+/// how it's laid out. Updates are diffed against the mounted tree and
+/// patched into a copy of its nodes, which is then laid out again.
+pub struct Terminal<N> {
+    driver_ctx: DriverCtx<TermDriver>,
+    root: N,
+    dom: Vec<DomNode<TermDriver>>,
+    grid: Grid,
+}
+
+impl<N> Terminal<N>
+where
+    N: Node<TermDriver>,
+{
+    pub fn mount(width: usize, height: usize, mut root: N) -> Terminal<N> {
+        let driver_ctx = DriverCtx::new(TermDriver);
+        let dom = to_dom(&driver_ctx, &mut root);
+        let mut grid = Grid::new(width, height);
+        grid.layout(&dom);
+        Terminal {
+            driver_ctx,
+            root,
+            dom,
+            grid,
+        }
+    }
+
+    /// Diffs `root` against the mounted tree and lays out the result. If
+    /// the patches don't apply to the nodes, they're built from `root` again
+    /// and the error is returned, so the grid still shows `root`.
+    pub fn update(&mut self, mut root: N) -> Result<(), PathError> {
+        let patches = diff(&self.driver_ctx, &mut root, &mut self.root);
+        if patches.is_empty() {
+            self.root = root;
+            return Ok(());
+        }
+        let res = apply(&mut self.dom, patches);
+        if res.is_err() {
+            self.dom = to_dom(&self.driver_ctx, &mut root);
+        }
+        self.root = root;
+        self.grid.layout(&self.dom);
+        res
+    }
+
+    pub fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    /// Draws the grid over the terminal's screen, see `Grid::flush`.
+    pub fn flush<W>(&self, out: &mut W) -> io::Result<()>
+    where
+        W: io::Write,
+    {
+        self.grid.flush(out)
+    }
+}
@@ -0,0 +1,247 @@
+use crate::driver::TermDriver;
+use std::io;
+use vdom::vdom::{
+    attr::AttrRefValue,
+    dom::{DomNode, DomTag},
+    node::Text,
+};
+
+/// One of the 8 standard terminal colors.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+}
+
+impl Color {
+    fn from_name(name: &str) -> Option<Color> {
+        Some(match name {
+            "black" => Color::Black,
+            "red" => Color::Red,
+            "green" => Color::Green,
+            "yellow" => Color::Yellow,
+            "blue" => Color::Blue,
+            "magenta" => Color::Magenta,
+            "cyan" => Color::Cyan,
+            "white" => Color::White,
+            _ => return None,
+        })
+    }
+
+    /// The offset of the color from the first SGR code of its kind.
+    fn sgr_offset(self) -> u8 {
+        self as u8
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Style {
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+    pub bold: bool,
+}
+
+impl Style {
+    /// The style of the contents of `tag`, whose `fg` and `bg` attributes
+    /// name a `Color` and whose `bold` attribute makes text bold unless it's
+    /// `"false"`. Anything not set is inherited from `self`.
+    fn apply(self, tag: &DomTag<TermDriver>) -> Style {
+        let color = |name| {
+            match tag.attr(name) {
+                Some(AttrRefValue::Str(color)) => Color::from_name(color),
+                _ => None,
+            }
+        };
+        let bold = match tag.attr("bold") {
+            Some(AttrRefValue::True) => true,
+            Some(AttrRefValue::Str(value)) => value != "false",
+            Some(AttrRefValue::Null) | None => false,
+        };
+        Style {
+            fg: color("fg").or(self.fg),
+            bg: color("bg").or(self.bg),
+            bold: bold || self.bold,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Cell {
+    pub ch: char,
+    pub style: Style,
+}
+
+impl Default for Cell {
+    fn default() -> Cell {
+        Cell {
+            ch: ' ',
+            style: Style::default(),
+        }
+    }
+}
+
+/// The cells of a terminal screen, which nodes are laid out on without a box
+/// model: `div`s start on a new line and end their line, and all other
+/// elements flow inline. Text wraps at the right edge, and anything below the
+/// bottom is cut off. Tabs are laid out as spaces, and other control
+/// characters are left out, so text can't move the cursor or change the
+/// style with escape sequences when the grid is flushed.
+pub struct Grid {
+    width: usize,
+    height: usize,
+    cells: Vec<Cell>,
+}
+
+impl Grid {
+    pub fn new(width: usize, height: usize) -> Grid {
+        Grid {
+            width,
+            height,
+            cells: vec![Cell::default(); width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn cell(&self, x: usize, y: usize) -> Option<&Cell> {
+        if x < self.width {
+            self.cells.get(y * self.width + x)
+        } else {
+            None
+        }
+    }
+
+    /// The characters of row `y`, without trailing spaces.
+    pub fn row(&self, y: usize) -> Option<String> {
+        if y >= self.height {
+            return None;
+        }
+        let cells = &self.cells[y * self.width..(y + 1) * self.width];
+        let row = cells.iter().map(|cell| cell.ch).collect::<String>();
+        Some(row.trim_end().to_string())
+    }
+
+    /// Clears the grid and lays out `nodes` on it.
+    pub fn layout(&mut self, nodes: &[DomNode<TermDriver>]) {
+        for cell in &mut self.cells {
+            *cell = Cell::default();
+        }
+        let mut cursor = Cursor { x: 0, y: 0 };
+        self.layout_nodes(nodes, Style::default(), &mut cursor);
+    }
+
+    fn layout_nodes(&mut self, nodes: &[DomNode<TermDriver>], style: Style, cursor: &mut Cursor) {
+        for node in nodes {
+            match node {
+                DomNode::Tag(tag) => {
+                    let is_block = tag.tag == "div";
+                    if is_block {
+                        cursor.end_line();
+                    }
+                    self.layout_nodes(&tag.children, style.apply(tag), cursor);
+                    if is_block {
+                        cursor.end_line();
+                    }
+                }
+                DomNode::Text(text) => {
+                    for ch in text.get().chars() {
+                        self.put(ch, style, cursor);
+                    }
+                }
+                DomNode::Comment(_) => {}
+            }
+        }
+    }
+
+    fn put(&mut self, ch: char, style: Style, cursor: &mut Cursor) {
+        let ch = match ch {
+            '\n' => {
+                cursor.new_line();
+                return;
+            }
+            '\t' => ' ',
+            ch if ch.is_control() => return,
+            ch => ch,
+        };
+        if cursor.x >= self.width {
+            cursor.new_line();
+        }
+        if cursor.y < self.height && cursor.x < self.width {
+            self.cells[cursor.y * self.width + cursor.x] = Cell { ch, style };
+        }
+        cursor.x += 1;
+    }
+
+    /// Draws the grid from the top left corner of the terminal's screen,
+    /// using ANSI escape codes for the cursor and styles.
+    pub fn flush<W>(&self, out: &mut W) -> io::Result<()>
+    where
+        W: io::Write,
+    {
+        write!(out, "\x1b[H")?;
+        for y in 0..self.height {
+            let mut style = None;
+            for cell in &self.cells[y * self.width..(y + 1) * self.width] {
+                if style != Some(cell.style) {
+                    write_sgr(out, cell.style)?;
+                    style = Some(cell.style);
+                }
+                write!(out, "{}", cell.ch)?;
+            }
+            write!(out, "\x1b[0m")?;
+            if y + 1 < self.height {
+                write!(out, "\r\n")?;
+            }
+        }
+        out.flush()
+    }
+}
+
+struct Cursor {
+    x: usize,
+    y: usize,
+}
+
+impl Cursor {
+    fn new_line(&mut self) {
+        self.x = 0;
+        self.y += 1;
+    }
+
+    /// Moves to the start of the next line, unless already at the start of
+    /// one.
+    fn end_line(&mut self) {
+        if self.x > 0 {
+            self.new_line();
+        }
+    }
+}
+
+fn write_sgr<W>(out: &mut W, style: Style) -> io::Result<()>
+where
+    W: io::Write,
+{
+    write!(out, "\x1b[0")?;
+    if style.bold {
+        write!(out, ";1")?;
+    }
+    if let Some(fg) = style.fg {
+        write!(out, ";{}", 30 + fg.sgr_offset())?;
+    }
+    if let Some(bg) = style.bg {
+        write!(out, ";{}", 40 + bg.sgr_offset())?;
+    }
+    write!(out, "m")
+}
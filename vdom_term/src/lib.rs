@@ -0,0 +1,4 @@
+#![deny(bare_trait_objects, anonymous_parameters, elided_lifetimes_in_paths)]
+
+pub mod driver;
+pub mod grid;
@@ -0,0 +1,100 @@
+use vdom::vdom::{
+    attr::{AttrListEntry, AttrStr, AttrTrue},
+    node::{Node, TagStatic, TextDyn, TextStatic},
+};
+use vdom_term::{
+    driver::{TermDriver, Terminal},
+    grid::{Color, Grid},
+};
+
+fn status(status: &'static str) -> impl Node<TermDriver> {
+    let fg = AttrListEntry(AttrStr::new("fg", "red"));
+    let bold = AttrListEntry(AttrTrue::new("bold"));
+    (
+        TagStatic::new(
+            "div",
+            fg,
+            (
+                TextStatic::new("Status: "),
+                TagStatic::new("span", bold, TextDyn::new(status)),
+            ),
+        ),
+        TagStatic::new("div", (), TextStatic::new("a long line that wraps")),
+    )
+}
+
+fn text(text: &'static str) -> impl Node<TermDriver> {
+    TagStatic::new("div", (), TextStatic::new(text))
+}
+
+fn row(terminal: &Terminal<impl Node<TermDriver>>, y: usize) -> String {
+    terminal.grid().row(y).unwrap()
+}
+
+#[test]
+fn mount_and_update() {
+    let mut terminal = Terminal::mount(12, 4, status("ok"));
+    assert_eq!(row(&terminal, 0), "Status: ok");
+    assert_eq!(row(&terminal, 1), "a long line");
+    assert_eq!(row(&terminal, 2), "that wraps");
+    assert!(terminal.grid().cell(8, 0).unwrap().style.bold);
+    assert_eq!(
+        terminal.grid().cell(0, 0).unwrap().style.fg,
+        Some(Color::Red)
+    );
+
+    terminal.update(status("down")).unwrap();
+    assert_eq!(row(&terminal, 0), "Status: down");
+    let mut out = Vec::new();
+    terminal.flush(&mut out).unwrap();
+    assert!(String::from_utf8(out).unwrap().contains("\x1b[0;1;31md"));
+}
+
+#[test]
+fn rows_outside_the_grid() {
+    let terminal = Terminal::mount(4, 2, text("abcdefghij"));
+    assert_eq!(row(&terminal, 1), "efgh");
+    assert_eq!(terminal.grid().row(2), None);
+    assert_eq!(terminal.grid().cell(0, 2), None);
+}
+
+#[test]
+fn empty_grids() {
+    for &(width, height) in &[(0, 3), (3, 0), (0, 0)] {
+        let mut terminal = Terminal::mount(width, height, text("abc"));
+        terminal.update(text("abcd")).unwrap();
+        assert_eq!(terminal.grid().cell(0, 0), None);
+        let mut out = Vec::new();
+        terminal.flush(&mut out).unwrap();
+    }
+}
+
+#[test]
+fn control_characters_are_left_out() {
+    let terminal = Terminal::mount(20, 2, text("a\x1b[2Jb\tc\rd\u{9b}31me\x07"));
+    assert_eq!(row(&terminal, 0), "a[2Jb cd31me");
+    let mut out = Vec::new();
+    terminal.flush(&mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    assert!(!out.contains("\x1b[2J"));
+    assert!(!out.contains('\u{9b}'));
+}
+
+#[test]
+fn bold_false_is_not_bold() {
+    let bold = |value: &'static str| {
+        let attrs = AttrListEntry(AttrStr::new("bold", value));
+        let terminal = Terminal::mount(4, 1, TagStatic::new("span", attrs, TextStatic::new("x")));
+        terminal.grid().cell(0, 0).unwrap().style.bold
+    };
+    assert!(!bold("false"));
+    assert!(bold("true"));
+    assert!(bold(""));
+}
+
+#[test]
+fn grid_layout_without_terminal() {
+    let mut grid = Grid::new(0, 1);
+    grid.layout(&[]);
+    assert_eq!(grid.row(0), Some(String::new()));
+}
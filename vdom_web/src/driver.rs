@@ -6,17 +6,39 @@ use futures::{
 };
 use vdom::{
     driver::{Driver, DriverCtx},
+    html::HYDRATION_KEY,
     vdom::{
-        attr::{Attr, AttrDiffer, AttrRefValue, AttrVisitor},
+        attr::{Attr, AttrDiffer, AttrNs, AttrRefValue, AttrVisitor, TokenDiff},
+        debug::{DebugHistory, DebugSnapshot},
+        dom::{DomAttr, DomNode},
+        element_info,
+        key::Key,
         node::{Comp, CompNode, Node, NodeDiffer, NodeVisitor, Tag, Text},
+        patch::{splice_text, text_splice, validate, NodeKind, Patch, PatchError, PatchTarget},
+        path::Path,
+        profile::{ProfileEntry, ProfilingDiffer},
     },
 };
+use std::{
+    cell::{Cell, RefCell},
+    collections::{BTreeSet, HashSet},
+    convert::Infallible,
+    rc::Rc,
+};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
 use web_sys as web;
 
 pub struct WebDriver {}
 
 #[derive(Default)]
-pub struct AttrStore;
+pub struct AttrStore {
+    listener: Option<Listener>,
+}
+
+struct Listener {
+    handler: Rc<RefCell<Rc<dyn Fn(web::Event)>>>,
+    closure: Closure<dyn FnMut(web::Event)>,
+}
 
 #[derive(Default)]
 pub struct TagStore {
@@ -25,7 +47,8 @@ pub struct TagStore {
 
 #[derive(Default)]
 pub struct TextStore {
-    text: Option<web::Text>,
+    /// The text node, or the comment node of a comment.
+    text: Option<web::CharacterData>,
 }
 
 #[derive(Default)]
@@ -36,6 +59,7 @@ impl Driver for WebDriver {
     type TagStore = TagStore;
     type TextStore = TextStore;
     type CompStore = CompStore;
+    type Event = web::Event;
 
     fn new_attr_store() -> AttrStore {
         Default::default()
@@ -70,6 +94,13 @@ where
     root_element: web::Element,
     driver_ctx: DriverCtx<WebDriver>,
     node: N,
+    /// Whether the app was hydrated lazily, so nodes may not be hydrated yet.
+    lazy: bool,
+    /// The paths whose subtrees were hydrated by `hydrate_path` since the
+    /// last render, which may have moved them.
+    hydrated: HashSet<Path>,
+    /// The renders recorded since `record_debug_history` was called.
+    debug: Option<DebugHistory<WebDriver>>,
 }
 
 impl<N> App<N>
@@ -78,35 +109,729 @@ where
 {
     pub fn new(mut node: N, root_element: web::Element) -> Result<App<N>, Error> {
         let driver_ctx = DriverCtx::new(WebDriver {});
-        node.visit(
+        node.diff_new(
             &mut 0,
-            &mut NodeAddVisitor {
-                parent_element: &root_element,
+            &mut NodeStdDiffer {
                 driver_ctx: &driver_ctx,
+                parent_element: &root_element,
+                lazy: false,
             },
         )?;
         Ok(App {
             root_element,
             driver_ctx,
             node,
+            lazy: false,
+            hydrated: HashSet::new(),
+            debug: None,
+        })
+    }
+
+    /// Mounts `node` onto the DOM that already exists under `root_element`,
+    /// reusing matching elements and text nodes and only mutating
+    /// differences, instead of building everything from scratch.
+    pub fn reconcile(node: N, root_element: web::Element) -> Result<App<N>, Error> {
+        App::reconcile_with(node, root_element, None)
+    }
+
+    /// Like `reconcile`, for a DOM rendered with
+    /// `HtmlRenderer::hydration_keys`. Elements are matched to the live
+    /// element with the same key among the children of their parent, which is
+    /// moved into place, so they still line up if the text between them
+    /// differs or siblings were rendered in another order.
+    pub fn hydrate(node: N, root_element: web::Element) -> Result<App<N>, Error> {
+        App::reconcile_with(node, root_element, Some(Path::root()))
+    }
+
+    /// Like `hydrate`, without walking the DOM up front. Only the root is
+    /// recorded, and listeners on it hydrate the subtree of the target of an
+    /// event, and the elements leading to it, the first time an event of one
+    /// of `LAZY_EVENTS`, or of a type a listener in `node` handles, is
+    /// dispatched to it. As the listeners capture the event, the handlers
+    /// attached along the way still receive it. A render hydrates the nodes
+    /// it changes first, so listeners it adds are attached right away.
+    pub fn hydrate_lazily(mut node: N, root_element: web::Element) -> Result<LazyApp<N>, Error>
+    where
+        N: 'static,
+    {
+        let driver_ctx = DriverCtx::new(WebDriver {});
+        let mut events = ListenerEventCollector {
+            driver_ctx: &driver_ctx,
+            events: LAZY_EVENTS.iter().map(|&event| event.to_string()).collect(),
+        };
+        match node.visit(&mut 0, &mut events) {
+            Ok(()) => {}
+            Err(never) => match never {},
+        }
+        let events = events.events;
+        let app = Rc::new(RefCell::new(App {
+            root_element: root_element.clone(),
+            driver_ctx,
+            node,
+            lazy: true,
+            hydrated: HashSet::new(),
+            debug: None,
+        }));
+        let mut listeners = Vec::new();
+        for event in events {
+            let weak = Rc::downgrade(&app);
+            let closure = Closure::wrap(Box::new(move |event: web::Event| {
+                let app = match weak.upgrade() {
+                    Some(app) => app,
+                    None => return,
+                };
+                // Events dispatched while rendering, e.g. when a focused
+                // element is removed, target nodes the render hydrates.
+                let mut app = match app.try_borrow_mut() {
+                    Ok(app) => app,
+                    Err(_) => return,
+                };
+                let target = event
+                    .target()
+                    .and_then(|target| target.dyn_into::<web::Node>().ok());
+                let path = match target {
+                    Some(target) => target_path(&app.root_element, &target),
+                    None => None,
+                };
+                if let Some(path) = path {
+                    if let Err(err) = app.hydrate_path(&path) {
+                        web::console::error_1(&format!("vdom: {:?}", err).into());
+                    }
+                }
+            }) as Box<dyn FnMut(web::Event)>);
+            root_element.add_event_listener_with_callback_and_bool(
+                &event,
+                closure.as_ref().unchecked_ref(),
+                true,
+            )?;
+            listeners.push((event, closure));
+        }
+        Ok(LazyApp { app, listeners })
+    }
+
+    fn reconcile_with(
+        mut node: N,
+        root_element: web::Element,
+        parent: Option<Path>,
+    ) -> Result<App<N>, Error> {
+        let driver_ctx = DriverCtx::new(WebDriver {});
+        let mut visitor = NodeReconcileVisitor {
+            driver_ctx: &driver_ctx,
+            parent_element: &root_element,
+            parent,
+            len: 0,
+        };
+        node.visit(&mut 0, &mut visitor)?;
+        let len = visitor.len;
+        truncate_children(&root_element, len)?;
+        Ok(App {
+            root_element,
+            driver_ctx,
+            node,
+            lazy: false,
+            hydrated: HashSet::new(),
+            debug: None,
         })
     }
 
-    pub fn set(&mut self, mut node: N) -> Result<(), Error> {
-        node.diff(
+    pub fn set(&mut self, node: N) -> Result<(), Error> {
+        self.render(node, false).map(|_| ())
+    }
+
+    /// Like `set`, but returns how long diffing each tag took, including its
+    /// children, as of `ProfilingDiffer`. Timing each tag makes rendering
+    /// slower, so it's meant for debugging only.
+    pub fn set_profiled(&mut self, node: N) -> Result<Vec<ProfileEntry>, Error> {
+        self.render(node, true)
+    }
+
+    fn render(&mut self, mut node: N, profile: bool) -> Result<Vec<ProfileEntry>, Error> {
+        let start = self.debug.as_ref().map(|_| now_ms());
+        let prev = &mut self.node;
+        resync_children(&self.root_element, |live| prev.visit(&mut 0, live))?;
+        let mut differ = NodeStdDiffer {
+            driver_ctx: &self.driver_ctx,
+            parent_element: &self.root_element,
+            lazy: self.lazy,
+        };
+        let entries = if profile {
+            let mut differ = ProfilingDiffer::with_clock(differ, now_ms);
+            node.diff(&mut 0, &mut 0, &mut self.node, &mut differ)?;
+            differ.into_inner().1
+        } else {
+            node.diff(&mut 0, &mut 0, &mut self.node, &mut differ)?;
+            Vec::new()
+        };
+        self.node = node;
+        self.hydrated.clear();
+        if let (Some(debug), Some(start)) = (&mut self.debug, start) {
+            debug.record(&self.driver_ctx, &mut self.node, now_ms() - start);
+        }
+        Ok(entries)
+    }
+
+    /// Renders the components that were invalidated with
+    /// `CompCtx::invalidate`, or whose state changed, since they were
+    /// rendered, without rendering the rest of the tree, e.g. one showing a
+    /// `Suspense` whose `Resource` settled.
+    pub fn update(&mut self) -> Result<(), Error> {
+        let node = &mut self.node;
+        resync_children(&self.root_element, |live| node.visit(&mut 0, live))?;
+        self.node.visit(
             &mut 0,
+            &mut CompUpdater {
+                driver_ctx: &self.driver_ctx,
+                parent_element: &self.root_element,
+                lazy: self.lazy,
+            },
+        )?;
+        self.hydrated.clear();
+        Ok(())
+    }
+
+    /// Starts keeping a copy of the rendered tree and the patches and
+    /// durations of the latest `frames` renders, for `debug_snapshot`.
+    /// Copying and diffing the trees makes every render slower, so it's
+    /// meant for debugging only. Calling it again starts over.
+    pub fn record_debug_history(&mut self, frames: usize) {
+        self.debug = Some(DebugHistory::new(&self.driver_ctx, &mut self.node, frames));
+    }
+
+    /// The current tree, the patches of the latest render and the durations
+    /// of the recorded renders, if `record_debug_history` was called. With
+    /// the `serde_json` feature of `vdom`, `DebugSnapshot::to_json` converts
+    /// it for logging or sending to a devtools panel.
+    pub fn debug_snapshot(&self) -> Option<DebugSnapshot<'_, WebDriver>> {
+        self.debug.as_ref().map(|debug| {
+            debug
+                .snapshot()
+                .with_registry("hydrated", self.hydrated.len())
+        })
+    }
+
+    /// Hydrates the subtree at `path` and the elements leading to it, unless
+    /// it's hydrated already.
+    fn hydrate_path(&mut self, path: &Path) -> Result<(), Error> {
+        if !self.lazy
+            || self
+                .hydrated
+                .iter()
+                .any(|hydrated| path.starts_with(hydrated))
+        {
+            return Ok(());
+        }
+        self.node.visit(
             &mut 0,
-            &mut self.node,
-            &mut NodeStdDiffer {
+            &mut PathHydrator {
                 driver_ctx: &self.driver_ctx,
                 parent_element: &self.root_element,
+                parent: Path::root(),
+                path: path.indices(),
             },
         )?;
-        self.node = node;
+        self.hydrated.insert(path.clone());
+        // Renders clear the paths, so none of them can lead to a subtree
+        // that was removed since.
+        debug_assert!(
+            self.hydrated
+                .iter()
+                .all(|path| resolve_node(&self.root_element, path.indices()).is_ok()),
+            "a hydrated path no longer resolves"
+        );
         Ok(())
     }
 }
 
+/// The current time in milliseconds, or zero if there's no `Performance`.
+fn now_ms() -> f64 {
+    web::window()
+        .and_then(|window| window.performance())
+        .map_or(0.0, |performance| performance.now())
+}
+
+/// The events that hydrate their target in a lazily hydrated app, along with
+/// those the tree has listeners for, see `App::hydrate_lazily`.
+pub const LAZY_EVENTS: &[&str] = &[
+    "auxclick",
+    "change",
+    "click",
+    "contextmenu",
+    "dblclick",
+    "focusin",
+    "input",
+    "keydown",
+    "keyup",
+    "mousedown",
+    "mouseover",
+    "mouseup",
+    "pointerdown",
+    "pointerup",
+    "reset",
+    "submit",
+    "touchend",
+    "touchstart",
+    "wheel",
+];
+
+/// An `App` hydrated with `App::hydrate_lazily`. The listeners hydrating
+/// event targets are removed when it's dropped.
+pub struct LazyApp<N>
+where
+    N: Node<WebDriver>,
+{
+    app: Rc<RefCell<App<N>>>,
+    listeners: Vec<(String, Closure<dyn FnMut(web::Event)>)>,
+}
+
+impl<N> LazyApp<N>
+where
+    N: Node<WebDriver>,
+{
+    pub fn set(&mut self, node: N) -> Result<(), Error> {
+        self.app.borrow_mut().set(node)
+    }
+
+    /// See `App::update`.
+    pub fn update(&mut self) -> Result<(), Error> {
+        self.app.borrow_mut().update()
+    }
+
+    /// Hydrates the subtree at `path` and the elements leading to it ahead of
+    /// an event, e.g. for the part of the page in view. Hydrating the root
+    /// path hydrates everything.
+    pub fn hydrate_path(&mut self, path: &Path) -> Result<(), Error> {
+        self.app.borrow_mut().hydrate_path(path)
+    }
+}
+
+impl<N> Drop for LazyApp<N>
+where
+    N: Node<WebDriver>,
+{
+    fn drop(&mut self) {
+        let app = self.app.borrow();
+        for (event, closure) in &self.listeners {
+            let _ = app
+                .root_element
+                .remove_event_listener_with_callback_and_bool(
+                    event,
+                    closure.as_ref().unchecked_ref(),
+                    true,
+                );
+        }
+    }
+}
+
+/// The path of the element `target` is or is in below `root`, or `None` if
+/// it isn't below `root`. The hydration key of the closest element that has
+/// one takes the place of the indices leading to it, as elements may be
+/// rendered in another order. Text nodes carry no listeners, so the path of
+/// their parent is used instead, which their siblings being out of order
+/// can't throw off.
+fn target_path(root: &web::Element, target: &web::Node) -> Option<Path> {
+    let root = AsRef::<web::Node>::as_ref(root);
+    let mut indices = Vec::new();
+    let mut node = if target.dyn_ref::<web::Element>().is_some() {
+        target.clone()
+    } else {
+        target.parent_node()?
+    };
+    let mut path = loop {
+        if &node == root {
+            break Path::root();
+        }
+        let key = node
+            .dyn_ref::<web::Element>()
+            .and_then(|elem| elem.get_attribute(HYDRATION_KEY))
+            .and_then(|key| key.parse::<Path>().ok());
+        if let Some(key) = key {
+            break key;
+        }
+        // Elements kept in place after they were removed aren't in the tree.
+        let parent = node.parent_node()?;
+        let index = rendered_children(&parent)
+            .iter()
+            .position(|sibling| sibling == &node)?;
+        indices.push(index);
+        node = parent;
+    };
+    for index in indices.into_iter().rev() {
+        path.push(index);
+    }
+    Some(path)
+}
+
+/// Applies `patches`, e.g. recorded on a server with `vdom::vdom::patch::diff`,
+/// to the DOM under `root_element`. The patches are validated against the DOM
+/// first, so a patch whose path doesn't resolve fails with
+/// `Error::PathNotFound` before anything is changed.
+pub fn apply_patches(
+    root_element: &web::Element,
+    patches: Vec<Patch<WebDriver>>,
+) -> Result<(), Error> {
+    let roots = LiveNode(AsRef::<web::Node>::as_ref(root_element).clone()).children();
+    if let Err(PatchError { index, err }) = validate(roots, &patches) {
+        let patch = patches
+            .into_iter()
+            .nth(index)
+            .ok_or("patch index out of range")?;
+        return Err(Error::PathNotFound { patch, err });
+    }
+
+    let driver_ctx = DriverCtx::new(WebDriver {});
+    for patch in patches {
+        match patch {
+            Patch::InsertNode { path, mut node } => {
+                let (index, parent) = path.indices().split_last().ok_or("path is root")?;
+                let parent_element = resolve_node(root_element, parent)?
+                    .dyn_into::<web::Element>()
+                    .map_err(|_| "parent is not an element")?;
+                let mut index = *index;
+                node.visit(
+                    &mut index,
+                    &mut NodeAddVisitor {
+                        driver_ctx: &driver_ctx,
+                        parent_element: &parent_element,
+                    },
+                )?;
+            }
+            Patch::RemoveNode { path } => {
+                let node = resolve_node(root_element, path.indices())?;
+                node.parent_node()
+                    .ok_or("node has no parent")?
+                    .remove_child(&node)?;
+                forget_exiting(&node);
+            }
+            Patch::MoveNode { path, to, .. } => {
+                let (index, parent) = path.indices().split_last().ok_or("path is root")?;
+                let parent = child_container(&resolve_node(root_element, parent)?);
+                move_child(&parent, *index, to)?;
+            }
+            Patch::SetText { path, text } => {
+                let node = resolve_node(root_element, path.indices())?;
+                set_text_data(
+                    node.dyn_ref::<web::CharacterData>()
+                        .ok_or("node is not text")?,
+                    &text,
+                )?;
+            }
+            Patch::SpliceText {
+                path,
+                start,
+                delete_len,
+                insert,
+            } => {
+                let node = resolve_node(root_element, path.indices())?;
+                let node = node
+                    .dyn_ref::<web::CharacterData>()
+                    .ok_or("node is not text")?;
+                let text = splice_text(&node.data(), start, delete_len, &insert)
+                    .ok_or("splice is out of bounds")?;
+                set_text_data(node, &text)?;
+            }
+            Patch::SetAttr { path, name, value } => {
+                let elem = resolve_element(root_element, path.indices())?;
+                if name == "value" && has_value_prop(&elem) {
+                    set_value_prop(&elem, AttrRefValue::from(&value))?;
+                }
+                match AttrRefValue::from(&value) {
+                    AttrRefValue::True => set_qualified_attr(&elem, &name, &name)?,
+                    AttrRefValue::Null => remove_qualified_attr(&elem, &name)?,
+                    AttrRefValue::Str(value) => set_qualified_attr(&elem, &name, value)?,
+                }
+            }
+            Patch::RemoveAttr { path, name } => {
+                let elem = resolve_element(root_element, path.indices())?;
+                remove_qualified_attr(&elem, &name)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A node of the live DOM that patches are validated against.
+struct LiveNode(web::Node);
+
+impl PatchTarget for LiveNode {
+    fn kind(&self) -> NodeKind {
+        if self.0.dyn_ref::<web::Element>().is_some() {
+            NodeKind::Element
+        } else if self.0.dyn_ref::<web::Text>().is_some() {
+            NodeKind::Text
+        } else if self.0.dyn_ref::<web::Comment>().is_some() {
+            NodeKind::Comment
+        } else {
+            NodeKind::Other
+        }
+    }
+
+    fn children(&self) -> Vec<LiveNode> {
+        rendered_children(&child_container(&self.0))
+            .into_iter()
+            .map(LiveNode)
+            .collect()
+    }
+
+    fn text_len(&self) -> usize {
+        self.0
+            .dyn_ref::<web::CharacterData>()
+            .map_or(0, |text| text.data().chars().count())
+    }
+}
+
+/// Reads the DOM under `root_element` into owned `DomNode`s, e.g. to diff
+/// markup rendered without vdom against nodes built in Rust, or to render it
+/// again with `App::new`. Attributes are named as in the markup, which is
+/// `prefix:name` for namespaced ones like `to_dom` names them, and the
+/// children of a `<template>` are read from its content. Comments are read as
+/// `DomNode::Comment`s. Other nodes that a `DomNode` can't represent, such as
+/// processing instructions, are skipped, so the paths of later siblings are
+/// one less than in the live DOM for each.
+pub fn read_dom(root_element: &web::Element) -> Vec<DomNode<WebDriver>> {
+    read_children(root_element.as_ref())
+}
+
+fn read_children(node: &web::Node) -> Vec<DomNode<WebDriver>> {
+    let children = child_container(node).child_nodes();
+    (0..children.length())
+        .filter_map(|i| children.get(i))
+        .filter_map(|child| read_node(&child))
+        .collect()
+}
+
+fn read_node(node: &web::Node) -> Option<DomNode<WebDriver>> {
+    if let Some(elem) = node.dyn_ref::<web::Element>() {
+        let attributes = elem.attributes();
+        let attrs = (0..attributes.length())
+            .filter_map(|i| attributes.item(i))
+            .map(|attr| DomAttr::new(attr.name(), attr.value()))
+            .collect();
+        return Some(DomNode::tag(elem.local_name(), attrs, read_children(node)));
+    }
+    if let Some(comment) = node.dyn_ref::<web::Comment>() {
+        let data = AsRef::<web::CharacterData>::as_ref(comment).data();
+        return Some(DomNode::comment(data));
+    }
+    node.dyn_ref::<web::Text>()
+        .map(|text| DomNode::text(AsRef::<web::CharacterData>::as_ref(text).data()))
+}
+
+fn resolve_node(root_element: &web::Element, indices: &[usize]) -> Result<web::Node, Error> {
+    let mut node = AsRef::<web::Node>::as_ref(root_element).clone();
+    for &index in indices {
+        node = rendered_child(&child_container(&node), index).ok_or("path is out of bounds")?;
+    }
+    Ok(node)
+}
+
+/// Moves the child of `parent` at `from` so that it ends up at `to`. Moving
+/// an element blurs the element focused within it, which is focused again.
+fn move_child(parent: &web::Node, from: usize, to: usize) -> Result<(), Error> {
+    let children = rendered_children(parent);
+    let node = children.get(from).ok_or("child is out of bounds")?;
+    let before = if to < from { to } else { to + 1 };
+    let before = children.get(before);
+    let focused = web::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.active_element())
+        .filter(|focused| node.contains(Some(focused.as_ref())));
+    parent.insert_before(node, before)?;
+    if let Some(focused) = focused
+        .as_ref()
+        .and_then(|focused| focused.dyn_ref::<web::HtmlElement>())
+    {
+        focused.focus()?;
+    }
+    Ok(())
+}
+
+/// The node holding the children of `node`. Those of a `<template>` aren't
+/// rendered, so they go into its content fragment instead of the element.
+fn child_container(node: &web::Node) -> web::Node {
+    match node.dyn_ref::<web::HtmlTemplateElement>() {
+        Some(template) => template.content().into(),
+        None => node.clone(),
+    }
+}
+
+/// The type of the event dispatched to an element before it's removed. A
+/// listener calling `preventDefault` keeps the element in place, e.g. for an
+/// exit animation, until it calls `finish_unmount` or `UNMOUNT_TIMEOUT_MS`
+/// passed. An element with an `EXIT_MS_ATTR` attribute is kept that long
+/// without a listener.
+///
+/// Elements kept in place get the `EXITING_ATTR` and `inert` attributes and
+/// are no longer rendered children of their parent, so nodes rendered at
+/// their index are inserted after them.
+pub const BEFORE_UNMOUNT: &str = "vdom-beforeunmount";
+
+/// The attribute giving how many milliseconds an element is kept in place
+/// when it's removed, see `BEFORE_UNMOUNT`, e.g. the duration of its CSS exit
+/// animation. It's also the timeout of a listener deferring the removal.
+pub const EXIT_MS_ATTR: &str = "data-vdom-exit-ms";
+
+/// The attribute set on elements kept in place after they were removed, see
+/// `BEFORE_UNMOUNT`, which can start their exit animation in CSS. It's
+/// removed again once they left the DOM, also along with their parent.
+pub const EXITING_ATTR: &str = "data-vdom-exiting";
+
+/// How long a `BEFORE_UNMOUNT` listener may defer the removal of an element
+/// without an `EXIT_MS_ATTR` attribute, in case it never finishes it.
+pub const UNMOUNT_TIMEOUT_MS: i32 = 5000;
+
+thread_local! {
+    /// The number of elements kept in place after they were removed, so
+    /// looking up rendered children needn't check for them while there are
+    /// none.
+    static EXITING: Cell<usize> = Cell::new(0);
+}
+
+/// Removes `element`, or keeps it in place if it defers its removal, see
+/// `BEFORE_UNMOUNT`.
+fn remove_element(element: &web::Element) -> Result<(), Error> {
+    let mut init = web::EventInit::new();
+    init.cancelable(true);
+    let event = web::Event::new_with_event_init_dict(BEFORE_UNMOUNT, &init)?;
+    let deferred = !element.dispatch_event(&event)?;
+    let exit_ms = element
+        .get_attribute(EXIT_MS_ATTR)
+        .and_then(|ms| ms.trim().parse::<i32>().ok());
+    if !deferred && exit_ms.is_none() {
+        element.remove();
+        forget_exiting(element.as_ref());
+        return Ok(());
+    }
+
+    element.set_attribute(EXITING_ATTR, "")?;
+    element.set_attribute("inert", "")?;
+    EXITING.with(|exiting| exiting.set(exiting.get() + 1));
+    let finish = {
+        let element = element.clone();
+        Closure::once_into_js(move || finish_unmount(&element))
+    };
+    web::window()
+        .ok_or("window is None")?
+        .set_timeout_with_callback_and_timeout_and_arguments_0(
+            finish.unchecked_ref(),
+            exit_ms.unwrap_or(UNMOUNT_TIMEOUT_MS),
+        )?;
+    Ok(())
+}
+
+/// Removes `element` if it was kept in place after it was removed, see
+/// `BEFORE_UNMOUNT`, e.g. once its exit animation ended.
+pub fn finish_unmount(element: &web::Element) {
+    if !element.has_attribute(EXITING_ATTR) {
+        return;
+    }
+    element.remove();
+    forget_exiting(element.as_ref());
+}
+
+/// Stops counting the elements kept in place after they were removed within
+/// `node`, including itself, as they left the DOM along with it, e.g. as
+/// their parent was replaced.
+fn forget_exiting(node: &web::Node) {
+    if EXITING.with(Cell::get) == 0 {
+        return;
+    }
+    let elem = match node.dyn_ref::<web::Element>() {
+        Some(elem) => elem,
+        None => return,
+    };
+    let mut exiting = Vec::new();
+    if elem.has_attribute(EXITING_ATTR) {
+        exiting.push(elem.clone());
+    }
+    if let Ok(descendants) = elem.query_selector_all(&format!("[{}]", EXITING_ATTR)) {
+        exiting.extend(
+            (0..descendants.length())
+                .filter_map(|i| descendants.get(i))
+                .filter_map(|node| node.dyn_into::<web::Element>().ok()),
+        );
+    }
+    for elem in exiting {
+        let _ = elem.remove_attribute(EXITING_ATTR);
+        EXITING.with(|exiting| exiting.set(exiting.get().saturating_sub(1)));
+    }
+}
+
+fn is_exiting(node: &web::Node) -> bool {
+    node.dyn_ref::<web::Element>()
+        .map_or(false, |elem| elem.has_attribute(EXITING_ATTR))
+}
+
+/// The children of `container` that are rendered, i.e. without elements kept
+/// in place after they were removed.
+fn rendered_children(container: &web::Node) -> Vec<web::Node> {
+    let children = container.child_nodes();
+    let exiting = EXITING.with(Cell::get) > 0;
+    (0..children.length())
+        .filter_map(|i| children.get(i))
+        .filter(|child| !exiting || !is_exiting(child))
+        .collect()
+}
+
+/// The number of rendered children of `container`, see `rendered_children`.
+fn rendered_len(container: &web::Node) -> usize {
+    if EXITING.with(Cell::get) == 0 {
+        return container.child_nodes().length() as usize;
+    }
+    rendered_children(container).len()
+}
+
+/// The rendered child of `container` at `index`, see `rendered_children`.
+fn rendered_child(container: &web::Node, index: usize) -> Option<web::Node> {
+    if EXITING.with(Cell::get) == 0 {
+        return container.child_nodes().get(index as u32);
+    }
+    rendered_children(container).into_iter().nth(index)
+}
+
+fn resolve_element(root_element: &web::Element, indices: &[usize]) -> Result<web::Element, Error> {
+    Ok(resolve_node(root_element, indices)?
+        .dyn_into::<web::Element>()
+        .map_err(|_| "node is not an element")?)
+}
+
+/// The namespace of an attribute named `prefix:name` in a patch.
+fn qualified_ns(name: &str) -> Option<(AttrNs, &str)> {
+    let mut parts = name.splitn(2, ':');
+    let (prefix, local_name) = (parts.next()?, parts.next()?);
+    [AttrNs::XLINK, AttrNs::XML]
+        .iter()
+        .find(|ns| ns.prefix == prefix)
+        .map(|ns| (*ns, local_name))
+}
+
+/// Sets the attribute `name` of an element from a patch unless it's `value`
+/// already, since setting some, as `src` of `<iframe>`, reloads what they
+/// refer to even if the value is unchanged.
+fn set_qualified_attr(elem: &web::Element, name: &str, value: &str) -> Result<(), Error> {
+    match qualified_ns(name) {
+        Some((ns, local_name)) => {
+            let live = elem.get_attribute_ns(Some(ns.uri), local_name);
+            if live.as_deref() != Some(value) {
+                elem.set_attribute_ns(Some(ns.uri), name, value)?;
+            }
+        }
+        None => {
+            if elem.get_attribute(name).as_deref() != Some(value) {
+                elem.set_attribute(name, value)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn remove_qualified_attr(elem: &web::Element, name: &str) -> Result<(), Error> {
+    match qualified_ns(name) {
+        Some((ns, local_name)) => elem.remove_attribute_ns(Some(ns.uri), local_name)?,
+        None => elem.remove_attribute(name)?,
+    }
+    Ok(())
+}
+
 struct NodeAddVisitor<'a> {
     driver_ctx: &'a DriverCtx<WebDriver>,
     parent_element: &'a web::Element,
@@ -127,15 +852,14 @@ impl<'a> NodeVisitor<WebDriver> for NodeAddVisitor<'a> {
         tag.visit_attrs(&mut AttrAddVisitor {
             parent_element: &elem,
         })?;
-        tag.visit_children(&mut NodeAddVisitor {
-            driver_ctx: &self.driver_ctx,
-            parent_element: &elem,
-        })?;
-        let parent_node = AsRef::<web::Node>::as_ref(&self.parent_element);
-        parent_node.insert_before(
-            elem.as_ref(),
-            parent_node.child_nodes().get(index as u32).as_ref(),
-        )?;
+        if !tag.skips_diff() {
+            tag.visit_children(&mut NodeAddVisitor {
+                driver_ctx: &self.driver_ctx,
+                parent_element: &elem,
+            })?;
+        }
+        let parent_node = child_container(self.parent_element.as_ref());
+        parent_node.insert_before(elem.as_ref(), rendered_child(&parent_node, index).as_ref())?;
         tag.driver_store().element = Some(elem);
         Ok(())
     }
@@ -144,15 +868,19 @@ impl<'a> NodeVisitor<WebDriver> for NodeAddVisitor<'a> {
     where
         T: Text<WebDriver>,
     {
-        let text_node = web::window()
+        let document = web::window()
             .ok_or("window is None")?
             .document()
-            .ok_or("document is None")?
-            .create_text_node(text.get());
-        let parent_node = AsRef::<web::Node>::as_ref(&self.parent_element);
+            .ok_or("document is None")?;
+        let text_node: web::CharacterData = if text.is_comment() {
+            document.create_comment(text.get()).into()
+        } else {
+            document.create_text_node(text.get()).into()
+        };
+        let parent_node = child_container(self.parent_element.as_ref());
         parent_node.insert_before(
             text_node.as_ref(),
-            parent_node.child_nodes().get(index as u32).as_ref(),
+            rendered_child(&parent_node, index).as_ref(),
         )?;
         text.driver_store().text = Some(text_node);
         Ok(())
@@ -166,34 +894,56 @@ impl<'a> NodeVisitor<WebDriver> for NodeAddVisitor<'a> {
     where
         C: Comp<WebDriver>,
     {
-        comp.init_comp_ctx(self.driver_ctx.clone());
+        if comp.comp_ctx().is_none() {
+            comp.init_comp_ctx(self.driver_ctx.clone());
+        }
         comp.visit_rendered(index, self)
     }
+
+    fn driver_ctx(&self) -> Option<DriverCtx<WebDriver>> {
+        Some(self.driver_ctx.clone())
+    }
+}
+
+struct NodeRemoveVisitor<'a> {
+    parent_element: &'a web::Element,
+    /// The index of the removed nodes among the live children, for the nodes
+    /// of a lazily hydrated app that weren't hydrated, or `None`.
+    lazy_index: Option<usize>,
 }
 
-struct NodeRemoveVisitor;
+impl<'a> NodeRemoveVisitor<'a> {
+    fn remove_unhydrated(&self, what: &'static str) -> Result<(), Error> {
+        let index = self.lazy_index.ok_or(what)?;
+        let container = child_container(self.parent_element.as_ref());
+        let live = rendered_child(&container, index).ok_or(what)?;
+        container.remove_child(&live)?;
+        forget_exiting(&live);
+        Ok(())
+    }
+}
 
-impl NodeVisitor<WebDriver> for NodeRemoveVisitor {
+impl<'a> NodeVisitor<WebDriver> for NodeRemoveVisitor<'a> {
     type Err = Error;
 
     fn on_tag<T>(&mut self, _index: usize, tag: &mut T) -> Result<(), Error>
     where
         T: Tag<WebDriver>,
     {
-        let elem = tag
-            .driver_store()
-            .element
-            .as_ref()
-            .ok_or("element is None")?;
-        elem.remove();
-        Ok(())
+        match tag.driver_store().element.as_ref() {
+            Some(elem) => remove_element(elem),
+            None => self.remove_unhydrated("element is None"),
+        }
     }
 
     fn on_text<T>(&mut self, _index: usize, text: &mut T) -> Result<(), Error>
     where
         T: Text<WebDriver>,
     {
-        let text_node = text.driver_store().text.as_ref().ok_or("text is None")?;
+        let text_node = match text.driver_store().text.as_ref() {
+            Some(text_node) => text_node,
+            None => return self.remove_unhydrated("text is None"),
+        };
         let node = AsRef::<web::Node>::as_ref(text_node);
         node.parent_node()
             .ok_or("text has no parent")?
@@ -213,91 +963,840 @@ impl NodeVisitor<WebDriver> for NodeRemoveVisitor {
     }
 }
 
-struct AttrAddVisitor<'a> {
+struct NodeReconcileVisitor<'a> {
+    driver_ctx: &'a DriverCtx<WebDriver>,
     parent_element: &'a web::Element,
+    /// The path of `parent_element` if elements are matched by hydration
+    /// key.
+    parent: Option<Path>,
+    len: usize,
 }
 
-impl<'a> AttrVisitor<WebDriver> for AttrAddVisitor<'a> {
-    type Err = Error;
+impl<'a> NodeReconcileVisitor<'a> {
+    fn live_child(&self, index: usize) -> Option<web::Node> {
+        rendered_child(&child_container(self.parent_element.as_ref()), index)
+    }
 
-    fn on_attr<A>(&mut self, attr: &mut A) -> Result<(), Error>
+    /// Moves the live child whose hydration key is `key` to `index`, if
+    /// there is one.
+    fn align_by_key(&self, index: usize, key: &str) -> Result<(), Error> {
+        let container = child_container(self.parent_element.as_ref());
+        let children = rendered_children(&container);
+        let keyed = children.iter().find(|child| {
+            child
+                .dyn_ref::<web::Element>()
+                .and_then(|elem| elem.get_attribute(HYDRATION_KEY))
+                .map_or(false, |live_key| live_key == key)
+        });
+        if let Some(keyed) = keyed {
+            let at = children.get(index);
+            if at != Some(keyed) {
+                container.insert_before(keyed, at)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Hydrates `tag` without its children, which are left for later, if the
+    /// live node at `index` is a matching element. Otherwise the live node is
+    /// replaced along with its subtree, which hydrates the children as well.
+    fn enter_tag<T>(&mut self, index: usize, tag: &mut T) -> Result<(), Error>
     where
-        A: Attr<WebDriver>,
+        T: Tag<WebDriver>,
     {
-        if let Some(value) = attr_to_str(attr) {
-            self.parent_element.set_attribute(attr.name(), value)?;
+        if let Some(parent) = &self.parent {
+            self.align_by_key(index, &parent.child(index).to_string())?;
+        }
+        let elem = self
+            .live_child(index)
+            .and_then(|live| live.dyn_into::<web::Element>().ok())
+            .filter(|elem| elem.tag_name().eq_ignore_ascii_case(tag.tag()));
+        match elem {
+            Some(elem) => {
+                reconcile_attrs(&elem, tag)?;
+                tag.driver_store().element = Some(elem);
+                Ok(())
+            }
+            None => self.on_tag(index, tag),
         }
-        Ok(())
     }
-}
 
-struct NodeStdDiffer<'a> {
-    driver_ctx: &'a DriverCtx<WebDriver>,
-    parent_element: &'a web::Element,
+    fn add_visitor(&self) -> NodeAddVisitor<'a> {
+        NodeAddVisitor {
+            driver_ctx: self.driver_ctx,
+            parent_element: self.parent_element,
+        }
+    }
 }
 
-impl<'a> NodeDiffer<WebDriver> for NodeStdDiffer<'a> {
+impl<'a> NodeVisitor<WebDriver> for NodeReconcileVisitor<'a> {
     type Err = Error;
 
-    fn on_node_added<N>(&mut self, index: &mut usize, curr: &mut N) -> Result<(), Error>
+    fn on_tag<T>(&mut self, index: usize, tag: &mut T) -> Result<(), Error>
     where
-        N: Node<WebDriver>,
+        T: Tag<WebDriver>,
     {
-        curr.visit(
-            index,
-            &mut NodeAddVisitor {
-                driver_ctx: self.driver_ctx,
-                parent_element: &self.parent_element,
-            },
-        )
+        self.len = index + 1;
+        let path = self.parent.as_ref().map(|parent| parent.child(index));
+        if let Some(path) = &path {
+            self.align_by_key(index, &path.to_string())?;
+        }
+        let live = self.live_child(index);
+        let elem = live
+            .as_ref()
+            .and_then(|live| live.dyn_ref::<web::Element>())
+            .filter(|elem| elem.tag_name().eq_ignore_ascii_case(tag.tag()))
+            .cloned();
+        let elem = match elem {
+            Some(elem) => elem,
+            None => {
+                if let Some(live) = live {
+                    child_container(self.parent_element.as_ref()).remove_child(&live)?;
+                    forget_exiting(&live);
+                }
+                return self.add_visitor().on_tag(index, tag);
+            }
+        };
+
+        reconcile_attrs(&elem, tag)?;
+        if tag.skips_diff() {
+            tag.driver_store().element = Some(elem);
+            return Ok(());
+        }
+
+        let mut children_visitor = NodeReconcileVisitor {
+            driver_ctx: self.driver_ctx,
+            parent_element: &elem,
+            parent: path,
+            len: 0,
+        };
+        tag.visit_children(&mut children_visitor)?;
+        truncate_children(&elem, children_visitor.len)?;
+
+        tag.driver_store().element = Some(elem);
+        Ok(())
     }
 
-    fn on_node_removed<N>(
-        &mut self,
-        ancestor_index: &mut usize,
-        ancestor: &mut N,
-    ) -> Result<(), Error>
+    fn on_text<T>(&mut self, index: usize, text: &mut T) -> Result<(), Error>
     where
-        N: Node<WebDriver>,
+        T: Text<WebDriver>,
     {
-        ancestor.visit(ancestor_index, &mut NodeRemoveVisitor)
+        self.len = index + 1;
+        let live = self.live_child(index);
+        let text_node = live.as_ref().and_then(|live| {
+            if text.is_comment() {
+                live.dyn_ref::<web::Comment>().map(AsRef::<web::CharacterData>::as_ref)
+            } else {
+                live.dyn_ref::<web::Text>().map(AsRef::<web::CharacterData>::as_ref)
+            }
+        });
+        match text_node {
+            Some(text_node) => {
+                set_text_data(text_node, text.get())?;
+                text.driver_store().text = Some(text_node.clone());
+                Ok(())
+            }
+            None => {
+                if let Some(live) = live {
+                    child_container(self.parent_element.as_ref()).remove_child(&live)?;
+                    forget_exiting(&live);
+                }
+                self.add_visitor().on_text(index, text)
+            }
+        }
     }
 
-    fn on_tag<T>(
+    fn on_comp<C>(
         &mut self,
-        _curr_index: usize,
-        _ancestor_index: usize,
+        index: &mut usize,
+        comp: &mut CompNode<WebDriver, C>,
+    ) -> Result<(), Self::Err>
+    where
+        C: Comp<WebDriver>,
+    {
+        // Components within elements that a lazily hydrated app diffed
+        // before hydrating them were rendered already.
+        if comp.comp_ctx().is_none() {
+            comp.init_comp_ctx(self.driver_ctx.clone());
+        }
+        comp.visit_rendered(index, self)
+    }
+
+    fn driver_ctx(&self) -> Option<DriverCtx<WebDriver>> {
+        Some(self.driver_ctx.clone())
+    }
+}
+
+/// Reconciles the attributes of `elem` with those of `tag`, removing any
+/// others.
+fn reconcile_attrs<T>(elem: &web::Element, tag: &mut T) -> Result<(), Error>
+where
+    T: Tag<WebDriver>,
+{
+    let mut attr_visitor = AttrReconcileVisitor {
+        parent_element: elem,
+        names: Vec::new(),
+    };
+    tag.visit_attrs(&mut attr_visitor)?;
+    let attributes = elem.attributes();
+    let stale = (0..attributes.length())
+        .filter_map(|i| attributes.item(i))
+        .map(|attr| attr.name())
+        .filter(|name| !attr_visitor.names.contains(name))
+        .collect::<Vec<_>>();
+    for name in stale {
+        elem.remove_attribute(&name)?;
+    }
+    Ok(())
+}
+
+/// Hydrates the elements along `path`, and the subtree at its end, of a
+/// lazily hydrated app. Elements hydrated along the way have their
+/// attributes reconciled, which attaches their listeners, while their other
+/// children are left for later.
+struct PathHydrator<'a, 'p> {
+    driver_ctx: &'a DriverCtx<WebDriver>,
+    parent_element: &'a web::Element,
+    parent: Path,
+    /// The rest of the path, relative to `parent`.
+    path: &'p [usize],
+}
+
+impl<'a, 'p> PathHydrator<'a, 'p> {
+    fn subtree_hydrator(&self) -> SubtreeHydrator<'a> {
+        SubtreeHydrator {
+            driver_ctx: self.driver_ctx,
+            parent_element: self.parent_element,
+            parent: Some(self.parent.clone()),
+        }
+    }
+}
+
+impl<'a, 'p> NodeVisitor<WebDriver> for PathHydrator<'a, 'p> {
+    type Err = Error;
+
+    fn on_tag<T>(&mut self, index: usize, tag: &mut T) -> Result<(), Error>
+    where
+        T: Tag<WebDriver>,
+    {
+        let rest = match self.path.split_first() {
+            None => return self.subtree_hydrator().on_tag(index, tag),
+            Some((&i, rest)) if i == index => rest,
+            Some(_) => return Ok(()),
+        };
+        let path = self.parent.child(index);
+        let elem = match tag.driver_store().element.clone() {
+            Some(elem) => elem,
+            None => {
+                NodeReconcileVisitor {
+                    driver_ctx: self.driver_ctx,
+                    parent_element: self.parent_element,
+                    parent: Some(self.parent.clone()),
+                    len: 0,
+                }
+                .enter_tag(index, tag)?;
+                match tag.driver_store().element.clone() {
+                    Some(elem) => elem,
+                    None => return Ok(()),
+                }
+            }
+        };
+        if tag.skips_diff() {
+            return Ok(());
+        }
+        tag.visit_children(&mut PathHydrator {
+            driver_ctx: self.driver_ctx,
+            parent_element: &elem,
+            parent: path,
+            path: rest,
+        })
+    }
+
+    fn on_text<T>(&mut self, index: usize, text: &mut T) -> Result<(), Error>
+    where
+        T: Text<WebDriver>,
+    {
+        match self.path {
+            [] => self.subtree_hydrator().on_text(index, text),
+            [i] if *i == index => self.subtree_hydrator().on_text(index, text),
+            _ => Ok(()),
+        }
+    }
+
+    fn on_comp<C>(
+        &mut self,
+        index: &mut usize,
+        comp: &mut CompNode<WebDriver, C>,
+    ) -> Result<(), Error>
+    where
+        C: Comp<WebDriver>,
+    {
+        if comp.comp_ctx().is_none() {
+            comp.init_comp_ctx(self.driver_ctx.clone());
+        }
+        comp.visit_rendered(index, self)
+    }
+
+    fn driver_ctx(&self) -> Option<DriverCtx<WebDriver>> {
+        Some(self.driver_ctx.clone())
+    }
+}
+
+/// Collects the event types the listeners of a tree handle, for
+/// `App::hydrate_lazily` to hydrate the targets of those events as well.
+struct ListenerEventCollector<'a> {
+    driver_ctx: &'a DriverCtx<WebDriver>,
+    events: BTreeSet<String>,
+}
+
+impl<'a> NodeVisitor<WebDriver> for ListenerEventCollector<'a> {
+    type Err = Infallible;
+
+    fn on_tag<T>(&mut self, _index: usize, tag: &mut T) -> Result<(), Infallible>
+    where
+        T: Tag<WebDriver>,
+    {
+        tag.visit_attrs(self)?;
+        tag.visit_children(self)
+    }
+
+    fn on_text<T>(&mut self, _index: usize, _text: &mut T) -> Result<(), Infallible>
+    where
+        T: Text<WebDriver>,
+    {
+        Ok(())
+    }
+
+    fn on_comp<C>(
+        &mut self,
+        index: &mut usize,
+        comp: &mut CompNode<WebDriver, C>,
+    ) -> Result<(), Infallible>
+    where
+        C: Comp<WebDriver>,
+    {
+        if comp.comp_ctx().is_none() {
+            comp.init_comp_ctx(self.driver_ctx.clone());
+        }
+        comp.visit_rendered(index, self)
+    }
+
+    fn driver_ctx(&self) -> Option<DriverCtx<WebDriver>> {
+        Some(self.driver_ctx.clone())
+    }
+}
+
+impl<'a> AttrVisitor<WebDriver> for ListenerEventCollector<'a> {
+    type Err = Infallible;
+
+    fn on_attr<A>(&mut self, attr: &mut A) -> Result<(), Infallible>
+    where
+        A: Attr<WebDriver>,
+    {
+        if attr.listener().is_some() && !self.events.contains(attr.name()) {
+            self.events.insert(attr.name().to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Hydrates the nodes of a lazily hydrated app that weren't hydrated yet,
+/// descending into the elements that were to find them.
+struct SubtreeHydrator<'a> {
+    driver_ctx: &'a DriverCtx<WebDriver>,
+    parent_element: &'a web::Element,
+    /// The path of `parent_element` if elements are matched by hydration
+    /// key.
+    parent: Option<Path>,
+}
+
+impl<'a> SubtreeHydrator<'a> {
+    fn reconciler(&self) -> NodeReconcileVisitor<'a> {
+        NodeReconcileVisitor {
+            driver_ctx: self.driver_ctx,
+            parent_element: self.parent_element,
+            parent: self.parent.clone(),
+            len: 0,
+        }
+    }
+}
+
+impl<'a> NodeVisitor<WebDriver> for SubtreeHydrator<'a> {
+    type Err = Error;
+
+    fn on_tag<T>(&mut self, index: usize, tag: &mut T) -> Result<(), Error>
+    where
+        T: Tag<WebDriver>,
+    {
+        let elem = match tag.driver_store().element.clone() {
+            Some(elem) => elem,
+            // Nothing below an element that wasn't hydrated was either.
+            None => return self.reconciler().on_tag(index, tag),
+        };
+        if tag.skips_diff() {
+            return Ok(());
+        }
+        tag.visit_children(&mut SubtreeHydrator {
+            driver_ctx: self.driver_ctx,
+            parent_element: &elem,
+            parent: self.parent.as_ref().map(|parent| parent.child(index)),
+        })
+    }
+
+    fn on_text<T>(&mut self, index: usize, text: &mut T) -> Result<(), Error>
+    where
+        T: Text<WebDriver>,
+    {
+        if text.driver_store().text.is_some() {
+            return Ok(());
+        }
+        self.reconciler().on_text(index, text)
+    }
+
+    fn on_comp<C>(
+        &mut self,
+        index: &mut usize,
+        comp: &mut CompNode<WebDriver, C>,
+    ) -> Result<(), Error>
+    where
+        C: Comp<WebDriver>,
+    {
+        if comp.comp_ctx().is_none() {
+            comp.init_comp_ctx(self.driver_ctx.clone());
+        }
+        comp.visit_rendered(index, self)
+    }
+
+    fn driver_ctx(&self) -> Option<DriverCtx<WebDriver>> {
+        Some(self.driver_ctx.clone())
+    }
+}
+
+/// Renders the components at the top of the visited nodes, without hydrating
+/// anything.
+struct CompRenderer<'a> {
+    driver_ctx: &'a DriverCtx<WebDriver>,
+}
+
+impl<'a> NodeVisitor<WebDriver> for CompRenderer<'a> {
+    type Err = Error;
+
+    fn on_tag<T>(&mut self, _index: usize, _tag: &mut T) -> Result<(), Error>
+    where
+        T: Tag<WebDriver>,
+    {
+        Ok(())
+    }
+
+    fn on_text<T>(&mut self, _index: usize, _text: &mut T) -> Result<(), Error>
+    where
+        T: Text<WebDriver>,
+    {
+        Ok(())
+    }
+
+    fn on_comp<C>(
+        &mut self,
+        index: &mut usize,
+        comp: &mut CompNode<WebDriver, C>,
+    ) -> Result<(), Error>
+    where
+        C: Comp<WebDriver>,
+    {
+        if comp.comp_ctx().is_none() {
+            comp.init_comp_ctx(self.driver_ctx.clone());
+        }
+        comp.visit_rendered(index, self)
+    }
+
+    fn driver_ctx(&self) -> Option<DriverCtx<WebDriver>> {
+        Some(self.driver_ctx.clone())
+    }
+}
+
+/// Renders the components below the visited nodes that were invalidated or
+/// changed, see `App::update`, and diffs their nodes in place. Elements that
+/// weren't hydrated yet are skipped, as their components are rendered when
+/// they are.
+struct CompUpdater<'a> {
+    driver_ctx: &'a DriverCtx<WebDriver>,
+    parent_element: &'a web::Element,
+    lazy: bool,
+}
+
+impl<'a> NodeVisitor<WebDriver> for CompUpdater<'a> {
+    type Err = Error;
+
+    fn on_tag<T>(&mut self, _index: usize, tag: &mut T) -> Result<(), Error>
+    where
+        T: Tag<WebDriver>,
+    {
+        if tag.skips_diff() || element_info::runs_once(tag.tag()) {
+            return Ok(());
+        }
+        let elem = match tag.driver_store().element.clone() {
+            Some(elem) => elem,
+            None => return Ok(()),
+        };
+        resync_children(&elem, |live| tag.visit_children(live))?;
+        tag.visit_children(&mut CompUpdater {
+            driver_ctx: self.driver_ctx,
+            parent_element: &elem,
+            lazy: self.lazy,
+        })
+    }
+
+    fn on_text<T>(&mut self, _index: usize, _text: &mut T) -> Result<(), Error>
+    where
+        T: Text<WebDriver>,
+    {
+        Ok(())
+    }
+
+    fn on_comp<C>(
+        &mut self,
+        index: &mut usize,
+        comp: &mut CompNode<WebDriver, C>,
+    ) -> Result<(), Error>
+    where
+        C: Comp<WebDriver>,
+    {
+        let mut differ = NodeStdDiffer {
+            driver_ctx: self.driver_ctx,
+            parent_element: self.parent_element,
+            lazy: self.lazy,
+        };
+        if comp.update_rendered(index, &mut differ)? {
+            return Ok(());
+        }
+        comp.visit_rendered(index, self)
+    }
+
+    fn driver_ctx(&self) -> Option<DriverCtx<WebDriver>> {
+        Some(self.driver_ctx.clone())
+    }
+}
+
+struct AttrReconcileVisitor<'a> {
+    parent_element: &'a web::Element,
+    names: Vec<String>,
+}
+
+impl<'a> AttrVisitor<WebDriver> for AttrReconcileVisitor<'a> {
+    type Err = Error;
+
+    fn on_attr<A>(&mut self, attr: &mut A) -> Result<(), Error>
+    where
+        A: Attr<WebDriver>,
+    {
+        if attr.is_prop() {
+            // The property may be reflected by an attribute of the same name,
+            // e.g. `value` rendered on the server, which is kept.
+            self.names.push(attr.name().to_string());
+            return set_prop(self.parent_element, attr);
+        }
+        if attr.listener().is_some() {
+            return add_listener(self.parent_element, attr);
+        }
+        if let Some(value) = attr_to_str(attr) {
+            let live = match attr.namespace() {
+                Some(ns) => {
+                    self.parent_element
+                        .get_attribute_ns(Some(ns.uri), attr.name())
+                }
+                None => self.parent_element.get_attribute(attr.name()),
+            };
+            if live.as_ref().map(String::as_str) != Some(value) {
+                set_attr(self.parent_element, attr, value)?;
+            }
+            self.names.push(match attr.namespace() {
+                Some(ns) => format!("{}:{}", ns.prefix, attr.name()),
+                None => attr.name().to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+fn truncate_children(element: &web::Element, len: usize) -> Result<(), Error> {
+    let node = child_container(element.as_ref());
+    for child in rendered_children(&node).iter().skip(len) {
+        node.remove_child(child)?;
+        forget_exiting(child);
+    }
+    Ok(())
+}
+
+/// Puts the nodes last rendered into `element` back in place if other code
+/// changed its children since, e.g. `Node::normalize` merging adjacent text
+/// nodes or dropping empty ones. Diffing relies on one live node per virtual
+/// node at the same index. `visit` hands the previously rendered children to
+/// the visitor, and is called again to collect them if the number of live
+/// children doesn't match. Children of a lazily hydrated app that weren't
+/// hydrated yet can't be resynced, so they're left as is.
+fn resync_children<F>(element: &web::Element, mut visit: F) -> Result<(), Error>
+where
+    F: FnMut(&mut LiveChildren) -> Result<(), Error>,
+{
+    let container = child_container(element.as_ref());
+    let mut live = LiveChildren {
+        len: 0,
+        nodes: None,
+        unhydrated: false,
+    };
+    visit(&mut live)?;
+    if live.unhydrated || live.len == rendered_len(&container) {
+        return Ok(());
+    }
+
+    #[cfg(debug_assertions)]
+    web::console::warn_1(&"vdom: resyncing children changed outside of vdom".into());
+    live.nodes = Some(Vec::new());
+    visit(&mut live)?;
+    let nodes = live.nodes.unwrap_or_default();
+    for (index, node) in nodes.iter().enumerate() {
+        let at = rendered_child(&container, index);
+        if at.as_ref() != Some(node) {
+            container.insert_before(node, at.as_ref())?;
+        }
+    }
+    truncate_children(element, nodes.len())
+}
+
+/// Counts the live nodes of rendered nodes, and collects them if `nodes` is
+/// set, restoring the text of text nodes that other nodes were merged into.
+struct LiveChildren {
+    len: usize,
+    nodes: Option<Vec<web::Node>>,
+    /// Whether a node that wasn't hydrated was found. Components that weren't
+    /// can't even be counted.
+    unhydrated: bool,
+}
+
+impl NodeVisitor<WebDriver> for LiveChildren {
+    type Err = Error;
+
+    fn on_tag<T>(&mut self, _index: usize, tag: &mut T) -> Result<(), Error>
+    where
+        T: Tag<WebDriver>,
+    {
+        self.len += 1;
+        self.unhydrated |= tag.driver_store().element.is_none();
+        if let Some(nodes) = &mut self.nodes {
+            let elem = tag
+                .driver_store()
+                .element
+                .as_ref()
+                .ok_or("element is None")?;
+            nodes.push(AsRef::<web::Node>::as_ref(elem).clone());
+        }
+        Ok(())
+    }
+
+    fn on_text<T>(&mut self, _index: usize, text: &mut T) -> Result<(), Error>
+    where
+        T: Text<WebDriver>,
+    {
+        self.len += 1;
+        self.unhydrated |= text.driver_store().text.is_none();
+        if let Some(nodes) = &mut self.nodes {
+            let data = text.get().to_string();
+            let text_node = text.driver_store().text.as_ref().ok_or("text is None")?;
+            set_text_data(text_node, &data)?;
+            nodes.push(AsRef::<web::Node>::as_ref(text_node).clone());
+        }
+        Ok(())
+    }
+
+    fn on_comp<C>(
+        &mut self,
+        index: &mut usize,
+        comp: &mut CompNode<WebDriver, C>,
+    ) -> Result<(), Error>
+    where
+        C: Comp<WebDriver>,
+    {
+        if comp.comp_ctx().is_none() {
+            self.unhydrated = true;
+            return Ok(());
+        }
+        comp.visit_rendered(index, self)
+    }
+}
+
+struct AttrAddVisitor<'a> {
+    parent_element: &'a web::Element,
+}
+
+impl<'a> AttrVisitor<WebDriver> for AttrAddVisitor<'a> {
+    type Err = Error;
+
+    fn on_attr<A>(&mut self, attr: &mut A) -> Result<(), Error>
+    where
+        A: Attr<WebDriver>,
+    {
+        if attr.is_prop() {
+            return set_prop(self.parent_element, attr);
+        }
+        if attr.listener().is_some() {
+            return add_listener(self.parent_element, attr);
+        }
+        if let Some(value) = attr_to_str(attr) {
+            set_attr(self.parent_element, attr, value)?;
+        }
+        Ok(())
+    }
+}
+
+struct NodeStdDiffer<'a> {
+    driver_ctx: &'a DriverCtx<WebDriver>,
+    parent_element: &'a web::Element,
+    /// Whether the app was hydrated lazily, in which case previous nodes
+    /// that weren't hydrated yet are hydrated once something within them
+    /// changes. Their live nodes are at the index of the current nodes, as
+    /// the live nodes before them were diffed already.
+    lazy: bool,
+}
+
+impl<'a> NodeDiffer<WebDriver> for NodeStdDiffer<'a> {
+    type Err = Error;
+
+    fn on_node_added<N>(&mut self, index: &mut usize, curr: &mut N) -> Result<(), Error>
+    where
+        N: Node<WebDriver>,
+    {
+        curr.visit(
+            index,
+            &mut NodeAddVisitor {
+                driver_ctx: self.driver_ctx,
+                parent_element: &self.parent_element,
+            },
+        )
+    }
+
+    fn on_node_removed<N>(
+        &mut self,
+        curr_index: usize,
+        ancestor_index: &mut usize,
+        ancestor: &mut N,
+    ) -> Result<(), Error>
+    where
+        N: Node<WebDriver>,
+    {
+        ancestor.visit(
+            ancestor_index,
+            &mut NodeRemoveVisitor {
+                parent_element: self.parent_element,
+                lazy_index: if self.lazy { Some(curr_index) } else { None },
+            },
+        )
+    }
+
+    fn on_node_moved(
+        &mut self,
+        from_index: usize,
+        to_index: usize,
+        _key: &Key,
+    ) -> Result<(), Error> {
+        move_child(
+            &child_container(self.parent_element.as_ref()),
+            from_index,
+            to_index,
+        )
+    }
+
+    fn on_invariant_violation(
+        &mut self,
+        curr_index: usize,
+        expected: &str,
+        found: &str,
+    ) -> Result<(), Error> {
+        Err(Error::InvariantViolation {
+            path: Path::root().child(curr_index),
+            expected: expected.to_string(),
+            found: found.to_string(),
+        })
+    }
+
+    fn on_tag<T>(
+        &mut self,
+        curr_index: usize,
+        _ancestor_index: usize,
         curr: &mut T,
         ancestor: &mut T,
     ) -> Result<(), Error>
     where
         T: Tag<WebDriver>,
     {
+        if self.lazy && ancestor.driver_store().element.is_none() {
+            return diff_unhydrated_tag(
+                self.driver_ctx,
+                PendingParent::Hydrated(self.parent_element),
+                curr_index,
+                curr,
+                ancestor,
+            );
+        }
+        if loads_other_document(curr, ancestor) {
+            NodeRemoveVisitor {
+                parent_element: self.parent_element,
+                lazy_index: None,
+            }
+            .on_tag(curr_index, ancestor)?;
+            return NodeAddVisitor {
+                driver_ctx: self.driver_ctx,
+                parent_element: self.parent_element,
+            }
+            .on_tag(curr_index, curr);
+        }
         let elem = ancestor
             .driver_store()
             .element
             .take()
             .ok_or("element is None")?;
+        // The children of a `<script>` were run when it was mounted, and
+        // changing them wouldn't run them again.
+        let runs_once = element_info::runs_once(curr.tag());
+        let (curr_skips, ancestor_skips) = (
+            runs_once || curr.skips_diff(),
+            runs_once || ancestor.skips_diff(),
+        );
+        if !curr_skips && !ancestor_skips {
+            resync_children(&elem, |live| ancestor.visit_children(live))?;
+        }
         curr.diff_attrs(
             ancestor,
             &mut AttrStdDiffer {
                 parent_element: &elem,
             },
         )?;
-        curr.diff_children(
-            ancestor,
-            &mut NodeStdDiffer {
+        if curr_skips {
+            // The children are left as they are, now for others to change.
+        } else if ancestor_skips {
+            // Whatever others put in the element is replaced by its children.
+            truncate_children(&elem, 0)?;
+            curr.visit_children(&mut NodeAddVisitor {
                 driver_ctx: self.driver_ctx,
                 parent_element: &elem,
-            },
-        )?;
+            })?;
+        } else {
+            curr.diff_children(
+                ancestor,
+                &mut NodeStdDiffer {
+                    driver_ctx: self.driver_ctx,
+                    parent_element: &elem,
+                    lazy: self.lazy,
+                },
+            )
+            .map_err(|err| err.within(curr_index))?;
+        }
         curr.driver_store().element = Some(elem);
         Ok(())
     }
 
     fn on_text<T>(
         &mut self,
-        _curr_index: usize,
+        curr_index: usize,
         _ancestor_index: usize,
         curr: &mut T,
         ancestor: &mut T,
@@ -305,9 +1804,21 @@ impl<'a> NodeDiffer<WebDriver> for NodeStdDiffer<'a> {
     where
         T: Text<WebDriver>,
     {
+        if self.lazy && ancestor.driver_store().text.is_none() {
+            if curr.get() == ancestor.get() {
+                return Ok(());
+            }
+            NodeReconcileVisitor {
+                driver_ctx: self.driver_ctx,
+                parent_element: self.parent_element,
+                parent: None,
+                len: 0,
+            }
+            .on_text(curr_index, ancestor)?;
+        }
         let text = ancestor.driver_store().text.take().ok_or("text is None")?;
         if curr.get() != ancestor.get() {
-            AsRef::<web::CharacterData>::as_ref(&text).set_data(curr.get());
+            set_text_data(&text, curr.get())?;
         }
         curr.driver_store().text = Some(text);
         Ok(())
@@ -323,12 +1834,304 @@ impl<'a> NodeDiffer<WebDriver> for NodeStdDiffer<'a> {
     where
         C: Comp<WebDriver>,
     {
+        if self.lazy {
+            render_unhydrated_comp(self.driver_ctx, ancestor)?;
+        }
+        if curr.comp_ctx().is_none() {
+            let ctx = ancestor.comp_ctx().expect("ancestor.comp_ctx is None");
+            curr.set_comp_ctx(ctx.clone());
+        }
+        curr.diff_rendered(curr_index, ancestor_index, ancestor, self)
+    }
+
+    fn driver_ctx(&self) -> Option<DriverCtx<WebDriver>> {
+        Some(self.driver_ctx.clone())
+    }
+}
+
+/// Whether `curr` embeds another document than `ancestor`, see
+/// `element_info::document_attrs`, so the element is replaced rather than
+/// patched.
+fn loads_other_document<T>(curr: &mut T, ancestor: &mut T) -> bool
+where
+    T: Tag<WebDriver>,
+{
+    element_info::document_attrs(curr.tag())
+        .iter()
+        .any(|name| curr.attr_value(name) != ancestor.attr_value(name))
+}
+
+/// The element of a lazily hydrated app at `index` among the children of
+/// `parent`, which wasn't hydrated yet. It's only looked up once something
+/// within it changes.
+struct PendingElement<'p> {
+    parent: PendingParent<'p>,
+    index: usize,
+    element: RefCell<Option<web::Element>>,
+}
+
+impl<'p> PendingElement<'p> {
+    fn resolve(&self) -> Result<web::Element, Error> {
+        if let Some(elem) = &*self.element.borrow() {
+            return Ok(elem.clone());
+        }
+        let elem = rendered_child(
+            &child_container(self.parent.resolve()?.as_ref()),
+            self.index,
+        )
+        .and_then(|live| live.dyn_into::<web::Element>().ok())
+        .ok_or("element is None")?;
+        *self.element.borrow_mut() = Some(elem.clone());
+        Ok(elem)
+    }
+}
+
+enum PendingParent<'p> {
+    Hydrated(&'p web::Element),
+    Pending(&'p PendingElement<'p>),
+}
+
+impl<'p> PendingParent<'p> {
+    fn resolve(&self) -> Result<web::Element, Error> {
+        match self {
+            PendingParent::Hydrated(elem) => Ok((*elem).clone()),
+            PendingParent::Pending(pending) => pending.resolve(),
+        }
+    }
+}
+
+/// Diffs a tag of a lazily hydrated app that wasn't hydrated yet. It's
+/// hydrated if its attributes changed, or once its element is looked up to
+/// change its children.
+fn diff_unhydrated_tag<T>(
+    driver_ctx: &DriverCtx<WebDriver>,
+    parent: PendingParent<'_>,
+    curr_index: usize,
+    curr: &mut T,
+    ancestor: &mut T,
+) -> Result<(), Error>
+where
+    T: Tag<WebDriver>,
+{
+    let mut changes = AttrChanges { changed: false };
+    curr.diff_attrs(ancestor, &mut changes)?;
+    if changes.changed {
+        let parent_element = parent.resolve()?;
+        NodeReconcileVisitor {
+            driver_ctx,
+            parent_element: &parent_element,
+            parent: None,
+            len: 0,
+        }
+        .enter_tag(curr_index, ancestor)?;
+        return NodeStdDiffer {
+            driver_ctx,
+            parent_element: &parent_element,
+            lazy: true,
+        }
+        .on_tag(curr_index, curr_index, curr, ancestor);
+    }
+    // Unchanged attributes include the marker, so both skip diffing or
+    // neither does.
+    if element_info::runs_once(curr.tag()) || curr.skips_diff() {
+        return Ok(());
+    }
+
+    let pending = PendingElement {
+        parent,
+        index: curr_index,
+        element: RefCell::new(None),
+    };
+    curr.diff_children(
+        ancestor,
+        &mut LazyDiffer {
+            driver_ctx,
+            parent: &pending,
+        },
+    )
+    .map_err(|err| err.within(curr_index))?;
+    if let Some(elem) = pending.element.into_inner() {
+        reconcile_attrs(&elem, curr)?;
+        curr.driver_store().element = Some(elem);
+    }
+    Ok(())
+}
+
+/// Renders a component of a lazily hydrated app that wasn't rendered yet, so
+/// it can be diffed. Its nodes are hydrated as they're diffed.
+fn render_unhydrated_comp<C>(
+    driver_ctx: &DriverCtx<WebDriver>,
+    comp: &mut CompNode<WebDriver, C>,
+) -> Result<(), Error>
+where
+    C: Comp<WebDriver>,
+{
+    if comp.comp_ctx().is_some() {
+        return Ok(());
+    }
+    comp.init_comp_ctx(driver_ctx.clone());
+    comp.visit_rendered(&mut 0, &mut CompRenderer { driver_ctx })
+}
+
+/// Diffs the children of a `PendingElement`, looking it up once they change.
+struct LazyDiffer<'a, 'p> {
+    driver_ctx: &'a DriverCtx<WebDriver>,
+    parent: &'p PendingElement<'p>,
+}
+
+impl<'a, 'p> NodeDiffer<WebDriver> for LazyDiffer<'a, 'p> {
+    type Err = Error;
+
+    fn on_node_added<N>(&mut self, index: &mut usize, curr: &mut N) -> Result<(), Error>
+    where
+        N: Node<WebDriver>,
+    {
+        NodeStdDiffer {
+            driver_ctx: self.driver_ctx,
+            parent_element: &self.parent.resolve()?,
+            lazy: true,
+        }
+        .on_node_added(index, curr)
+    }
+
+    fn on_node_removed<N>(
+        &mut self,
+        curr_index: usize,
+        ancestor_index: &mut usize,
+        ancestor: &mut N,
+    ) -> Result<(), Error>
+    where
+        N: Node<WebDriver>,
+    {
+        NodeStdDiffer {
+            driver_ctx: self.driver_ctx,
+            parent_element: &self.parent.resolve()?,
+            lazy: true,
+        }
+        .on_node_removed(curr_index, ancestor_index, ancestor)
+    }
+
+    fn on_node_moved(
+        &mut self,
+        from_index: usize,
+        to_index: usize,
+        key: &Key,
+    ) -> Result<(), Error> {
+        NodeStdDiffer {
+            driver_ctx: self.driver_ctx,
+            parent_element: &self.parent.resolve()?,
+            lazy: true,
+        }
+        .on_node_moved(from_index, to_index, key)
+    }
+
+    fn on_invariant_violation(
+        &mut self,
+        curr_index: usize,
+        expected: &str,
+        found: &str,
+    ) -> Result<(), Error> {
+        Err(Error::InvariantViolation {
+            path: Path::root().child(curr_index),
+            expected: expected.to_string(),
+            found: found.to_string(),
+        })
+    }
+
+    fn on_tag<T>(
+        &mut self,
+        curr_index: usize,
+        _ancestor_index: usize,
+        curr: &mut T,
+        ancestor: &mut T,
+    ) -> Result<(), Error>
+    where
+        T: Tag<WebDriver>,
+    {
+        diff_unhydrated_tag(
+            self.driver_ctx,
+            PendingParent::Pending(self.parent),
+            curr_index,
+            curr,
+            ancestor,
+        )
+    }
+
+    fn on_text<T>(
+        &mut self,
+        curr_index: usize,
+        ancestor_index: usize,
+        curr: &mut T,
+        ancestor: &mut T,
+    ) -> Result<(), Error>
+    where
+        T: Text<WebDriver>,
+    {
+        if curr.get() == ancestor.get() {
+            return Ok(());
+        }
+        NodeStdDiffer {
+            driver_ctx: self.driver_ctx,
+            parent_element: &self.parent.resolve()?,
+            lazy: true,
+        }
+        .on_text(curr_index, ancestor_index, curr, ancestor)
+    }
+
+    fn on_comp<C>(
+        &mut self,
+        curr_index: &mut usize,
+        ancestor_index: &mut usize,
+        curr: &mut CompNode<WebDriver, C>,
+        ancestor: &mut CompNode<WebDriver, C>,
+    ) -> Result<(), Error>
+    where
+        C: Comp<WebDriver>,
+    {
+        render_unhydrated_comp(self.driver_ctx, ancestor)?;
         if curr.comp_ctx().is_none() {
             let ctx = ancestor.comp_ctx().expect("ancestor.comp_ctx is None");
             curr.set_comp_ctx(ctx.clone());
         }
         curr.diff_rendered(curr_index, ancestor_index, ancestor, self)
     }
+
+    fn driver_ctx(&self) -> Option<DriverCtx<WebDriver>> {
+        Some(self.driver_ctx.clone())
+    }
+}
+
+/// Records whether attributes changed, without touching the DOM.
+struct AttrChanges {
+    changed: bool,
+}
+
+impl AttrDiffer<WebDriver> for AttrChanges {
+    type Err = Error;
+
+    fn on_diff<A>(&mut self, curr: &mut A, ancestor: &mut A) -> Result<(), Error>
+    where
+        A: Attr<WebDriver>,
+    {
+        self.changed |= curr.value() != ancestor.value();
+        Ok(())
+    }
+
+    fn on_added<A>(&mut self, _curr: &mut A) -> Result<(), Error>
+    where
+        A: Attr<WebDriver>,
+    {
+        self.changed = true;
+        Ok(())
+    }
+
+    fn on_removed<A>(&mut self, _ancestor: &mut A) -> Result<(), Error>
+    where
+        A: Attr<WebDriver>,
+    {
+        self.changed = true;
+        Ok(())
+    }
 }
 
 struct AttrStdDiffer<'a> {
@@ -342,22 +2145,111 @@ impl<'a> AttrDiffer<WebDriver> for AttrStdDiffer<'a> {
     where
         A: Attr<WebDriver>,
     {
+        if curr.is_prop() {
+            // The live property may have been changed by the user since the
+            // last render, so it is compared against the DOM, not `ancestor`.
+            return set_prop(self.parent_element, curr);
+        }
+        if let Some(handler) = curr.listener().cloned() {
+            let listener = ancestor
+                .driver_store()
+                .listener
+                .take()
+                .ok_or("listener is None")?;
+            *listener.handler.borrow_mut() = handler;
+            curr.driver_store().listener = Some(listener);
+            return Ok(());
+        }
+        if let (Some(curr_tokens), Some(ancestor_tokens)) = (curr.tokens(), ancestor.tokens()) {
+            let diff = TokenDiff::new(curr_tokens, ancestor_tokens);
+            if diff.is_empty() {
+                return Ok(());
+            }
+            // Other token lists such as `rel` have no `DOMTokenList` on every
+            // element, so they are set as a whole below.
+            if curr.name() == "class" && !curr_tokens.is_empty() && !ancestor_tokens.is_empty() {
+                let class_list = self.parent_element.class_list();
+                for token in diff.removed {
+                    class_list.remove_1(token)?;
+                }
+                for token in diff.added {
+                    class_list.add_1(token)?;
+                }
+                return Ok(());
+            }
+        }
         match (attr_to_str(curr), attr_to_str(ancestor)) {
             (Some(curr_val), Some(ancestor_val)) => {
                 if curr_val != ancestor_val {
-                    self.parent_element.set_attribute(curr.name(), curr_val)?;
+                    set_attr(self.parent_element, curr, curr_val)?;
                 }
             }
             (Some(curr_val), None) => {
-                self.parent_element.set_attribute(curr.name(), curr_val)?;
+                set_attr(self.parent_element, curr, curr_val)?;
             }
             (None, Some(_)) => {
-                self.parent_element.remove_attribute(curr.name())?;
+                remove_attr(self.parent_element, curr)?;
             }
             (None, None) => {}
         }
         Ok(())
     }
+
+    fn on_added<A>(&mut self, curr: &mut A) -> Result<(), Error>
+    where
+        A: Attr<WebDriver>,
+    {
+        AttrAddVisitor {
+            parent_element: self.parent_element,
+        }
+        .on_attr(curr)
+    }
+
+    fn on_removed<A>(&mut self, ancestor: &mut A) -> Result<(), Error>
+    where
+        A: Attr<WebDriver>,
+    {
+        if let Some(listener) = ancestor.driver_store().listener.take() {
+            self.parent_element.remove_event_listener_with_callback(
+                ancestor.name(),
+                listener.closure.as_ref().unchecked_ref(),
+            )?;
+        } else if ancestor.is_prop() {
+            js_sys::Reflect::set(
+                self.parent_element,
+                &JsValue::from_str(ancestor.name()),
+                &JsValue::null(),
+            )?;
+        } else {
+            remove_attr(self.parent_element, ancestor)?;
+        }
+        Ok(())
+    }
+}
+
+fn set_attr<A>(elem: &web::Element, attr: &A, value: &str) -> Result<(), Error>
+where
+    A: Attr<WebDriver>,
+{
+    match attr.namespace() {
+        Some(ns) => {
+            let name = format!("{}:{}", ns.prefix, attr.name());
+            elem.set_attribute_ns(Some(ns.uri), &name, value)?;
+        }
+        None => elem.set_attribute(attr.name(), value)?,
+    }
+    Ok(())
+}
+
+fn remove_attr<A>(elem: &web::Element, attr: &A) -> Result<(), Error>
+where
+    A: Attr<WebDriver>,
+{
+    match attr.namespace() {
+        Some(ns) => elem.remove_attribute_ns(Some(ns.uri), attr.name())?,
+        None => elem.remove_attribute(attr.name())?,
+    }
+    Ok(())
 }
 
 fn attr_to_str<A>(attr: &A) -> Option<&str>
@@ -370,3 +2262,145 @@ where
         AttrRefValue::Str(s) => Some(s),
     }
 }
+
+fn set_prop<A>(element: &web::Element, attr: &A) -> Result<(), Error>
+where
+    A: Attr<WebDriver>,
+{
+    set_live_prop(element, attr.name(), attr.value())
+}
+
+/// Sets the property `name` unless it's `value` already. Writing the value
+/// of a form control moves the caret to the end, and could drop what the
+/// user typed after the value was rendered.
+fn set_live_prop(element: &web::Element, name: &str, value: AttrRefValue<'_>) -> Result<(), Error> {
+    let name = JsValue::from_str(name);
+    let value = match value {
+        AttrRefValue::True => JsValue::from_bool(true),
+        AttrRefValue::Null => JsValue::null(),
+        AttrRefValue::Str(s) => JsValue::from_str(s),
+    };
+    if js_sys::Reflect::get(element, &name)? != value {
+        js_sys::Reflect::set(element, &name, &value)?;
+    }
+    Ok(())
+}
+
+/// Sets the text of `node` by replacing only the part that differs from its
+/// live text. `replaceData` moves a caret within the replaced part to its
+/// start, so within a `contenteditable` the caret and the selection are kept
+/// at the same offset into the inserted text instead, as far as it goes.
+fn set_text_data(node: &web::CharacterData, text: &str) -> Result<(), Error> {
+    let live = node.data();
+    if live == text {
+        return Ok(());
+    }
+    let (start, delete_len, insert) =
+        text_splice(&live, text).unwrap_or((0, live.chars().count(), text));
+    // The DOM counts offsets in UTF-16 code units rather than in chars.
+    let offset = utf16_len(live.chars().take(start));
+    let count = utf16_len(live.chars().skip(start).take(delete_len));
+    let insert_len = utf16_len(insert.chars());
+    let node_ref: &web::Node = node.as_ref();
+    if !is_editable(node_ref) {
+        node.replace_data(offset, count, insert)?;
+        return Ok(());
+    }
+    let selection = web::window()
+        .and_then(|window| window.get_selection().ok().flatten())
+        .filter(|selection| {
+            selection.anchor_node().as_ref() == Some(node_ref)
+                || selection.focus_node().as_ref() == Some(node_ref)
+        })
+        .map(|selection| {
+            let carets = (
+                selection.anchor_node(),
+                selection.anchor_offset(),
+                selection.focus_node(),
+                selection.focus_offset(),
+            );
+            (selection, carets)
+        });
+    node.replace_data(offset, count, insert)?;
+    if let Some((selection, (anchor_node, anchor, focus_node, focus))) = selection {
+        let moved = |caret_node: &Option<web::Node>, caret: u32| {
+            if caret_node.as_ref() != Some(node_ref) || caret <= offset {
+                caret
+            } else if caret >= offset + count {
+                caret - count + insert_len
+            } else {
+                offset + (caret - offset).min(insert_len)
+            }
+        };
+        let (anchor, focus) = (moved(&anchor_node, anchor), moved(&focus_node, focus));
+        // Setting the selection fires `selectionchange`, so it's only done
+        // when `replaceData` moved the carets elsewhere.
+        if selection.anchor_offset() != anchor || selection.focus_offset() != focus {
+            selection.set_base_and_extent(
+                anchor_node.as_ref().unwrap_or(node_ref),
+                anchor,
+                focus_node.as_ref().unwrap_or(node_ref),
+                focus,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Whether `node` is within a `contenteditable` element, and so might hold
+/// the caret of the user.
+fn is_editable(node: &web::Node) -> bool {
+    match node.parent_element() {
+        Some(parent) => {
+            match parent.dyn_ref::<web::HtmlElement>() {
+                Some(parent) => parent.is_content_editable(),
+                None => false,
+            }
+        }
+        None => false,
+    }
+}
+
+fn utf16_len<I>(chars: I) -> u32
+where
+    I: Iterator<Item = char>,
+{
+    chars.map(|c| c.len_utf16() as u32).sum()
+}
+
+/// Whether the `value` attribute of `element` only sets its initial value,
+/// while the current one is its `value` property.
+fn has_value_prop(element: &web::Element) -> bool {
+    element.dyn_ref::<web::HtmlInputElement>().is_some()
+        || element.dyn_ref::<web::HtmlTextAreaElement>().is_some()
+        || element.dyn_ref::<web::HtmlSelectElement>().is_some()
+}
+
+/// Sets the current value of a form control from a patch, which sets its
+/// `value` attribute, unless the user already typed it.
+fn set_value_prop(element: &web::Element, value: AttrRefValue<'_>) -> Result<(), Error> {
+    let value = match value {
+        AttrRefValue::Str(s) => AttrRefValue::Str(s),
+        _ => AttrRefValue::Str(""),
+    };
+    set_live_prop(element, "value", value)
+}
+
+fn add_listener<A>(element: &web::Element, attr: &mut A) -> Result<(), Error>
+where
+    A: Attr<WebDriver>,
+{
+    let handler = Rc::new(RefCell::new(
+        attr.listener().cloned().ok_or("listener is None")?,
+    ));
+    let closure = {
+        let handler = handler.clone();
+        Closure::wrap(Box::new(move |event: web::Event| {
+            let handler = handler.borrow().clone();
+            handler(event)
+        }) as Box<dyn FnMut(web::Event)>)
+    };
+    element.add_event_listener_with_callback(attr.name(), closure.as_ref().unchecked_ref())?;
+    attr.driver_store().listener = Some(Listener { handler, closure });
+    Ok(())
+}
@@ -0,0 +1,179 @@
+use crate::{
+    driver::{App, WebDriver},
+    events::{self, FromEvent},
+    Error,
+};
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    rc::{Rc, Weak},
+};
+use vdom::vdom::{attr::AttrListener, node::Node};
+use web_sys as web;
+
+/// Queues messages for an `ElmApp` and triggers its update/re-render cycle.
+pub struct Dispatcher<M> {
+    queue: Rc<RefCell<VecDeque<M>>>,
+    flush: Rc<dyn Fn()>,
+}
+
+impl<M> Dispatcher<M>
+where
+    M: 'static,
+{
+    pub fn send(&self, msg: M) {
+        self.queue.borrow_mut().push_back(msg);
+        (self.flush)();
+    }
+
+    /// Listens for `event` and sends the message `f` maps it to.
+    pub fn on<E, F>(&self, event: &'static str, f: F) -> AttrListener<WebDriver>
+    where
+        E: FromEvent<WebDriver>,
+        F: Fn(E) -> M + 'static,
+    {
+        let dispatcher = self.clone();
+        events::on(event, move |event: E| dispatcher.send(f(event)))
+    }
+
+    /// Like `events::checked_bind`, sending the message `f` maps the new
+    /// state to.
+    pub fn checked_bind<F>(&self, checked: bool, f: F) -> events::CheckedBind
+    where
+        F: Fn(bool) -> M + 'static,
+    {
+        let dispatcher = self.clone();
+        events::checked_bind(checked, move |checked| dispatcher.send(f(checked)))
+    }
+}
+
+impl<M> Clone for Dispatcher<M> {
+    fn clone(&self) -> Self {
+        Dispatcher {
+            queue: self.queue.clone(),
+            flush: self.flush.clone(),
+        }
+    }
+}
+
+struct ElmInner<S, M, N>
+where
+    N: Node<WebDriver>,
+{
+    state: S,
+    app: App<N>,
+    view: Box<dyn Fn(&S, &Dispatcher<M>) -> N>,
+    update: Box<dyn Fn(&mut S, M)>,
+    dispatcher: Dispatcher<M>,
+}
+
+impl<S, M, N> ElmInner<S, M, N>
+where
+    M: 'static,
+    N: Node<WebDriver>,
+{
+    fn flush(&mut self) -> Result<(), Error> {
+        loop {
+            let mut dirty = false;
+            loop {
+                let msg = self.dispatcher.queue.borrow_mut().pop_front();
+                match msg {
+                    Some(msg) => {
+                        (self.update)(&mut self.state, msg);
+                        dirty = true;
+                    }
+                    None => break,
+                }
+            }
+            if !dirty {
+                return Ok(());
+            }
+            let node = (self.view)(&self.state, &self.dispatcher);
+            self.app.set(node)?;
+        }
+    }
+}
+
+/// An app runtime where handlers map events to messages `M` and a single
+/// `update` function owns all mutation of the state `S`.
+pub struct ElmApp<S, M, N>
+where
+    N: Node<WebDriver>,
+{
+    inner: Rc<RefCell<Option<ElmInner<S, M, N>>>>,
+}
+
+impl<S, M, N> ElmApp<S, M, N>
+where
+    S: 'static,
+    M: 'static,
+    N: Node<WebDriver> + 'static,
+{
+    pub fn new<V, U>(
+        state: S,
+        view: V,
+        update: U,
+        root_element: web::Element,
+    ) -> Result<ElmApp<S, M, N>, Error>
+    where
+        V: Fn(&S, &Dispatcher<M>) -> N + 'static,
+        U: Fn(&mut S, M) + 'static,
+    {
+        let inner = Rc::new(RefCell::new(None));
+        let dispatcher = Dispatcher {
+            queue: Rc::new(RefCell::new(VecDeque::new())),
+            flush: Rc::new({
+                let inner: Weak<RefCell<Option<ElmInner<S, M, N>>>> = Rc::downgrade(&inner);
+                move || flush(&inner)
+            }),
+        };
+        let app = App::new(view(&state, &dispatcher), root_element)?;
+        *inner.borrow_mut() = Some(ElmInner {
+            state,
+            app,
+            view: Box::new(view),
+            update: Box::new(update),
+            dispatcher,
+        });
+        // Messages sent while rendering the first view were queued before
+        // there was anything to update.
+        inner.borrow_mut().as_mut().unwrap().flush()?;
+        Ok(ElmApp { inner })
+    }
+
+    pub fn dispatcher(&self) -> Dispatcher<M> {
+        let inner = self.inner.borrow();
+        inner.as_ref().unwrap().dispatcher.clone()
+    }
+
+    pub fn with_state<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&S) -> R,
+    {
+        let inner = self.inner.borrow();
+        f(&inner.as_ref().unwrap().state)
+    }
+}
+
+fn flush<S, M, N>(inner: &Weak<RefCell<Option<ElmInner<S, M, N>>>>)
+where
+    M: 'static,
+    N: Node<WebDriver>,
+{
+    let inner = match inner.upgrade() {
+        Some(inner) => inner,
+        None => return,
+    };
+    // Already updating further up the stack, e.g. a `blur` fired while the
+    // DOM was being patched. That loop will pick the message up.
+    let mut inner = match inner.try_borrow_mut() {
+        Ok(inner) => inner,
+        Err(_) => return,
+    };
+    if let Some(inner) = inner.as_mut() {
+        if let Err(err) = inner.flush() {
+            let err = format!("Error: {:?}", err);
+            web::console::log_1(&err.into());
+        }
+    }
+}
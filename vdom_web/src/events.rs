@@ -0,0 +1,137 @@
+use crate::driver::WebDriver;
+pub use vdom::vdom::attr::FromEvent;
+use vdom::vdom::attr::{AttrListEntry, AttrListener, AttrProp};
+use wasm_bindgen::JsCast;
+use web_sys as web;
+
+/// Listens for `event`, handing `f` the typed wrapper selected by its
+/// parameter type. Events that don't convert are ignored. `html!` does the
+/// same for handlers whose parameter has a type, e.g.
+/// `oninput=(|e: InputEvent| ..)`.
+pub fn on<E, F>(event: &'static str, f: F) -> AttrListener<WebDriver>
+where
+    E: FromEvent<WebDriver>,
+    F: Fn(E) + 'static,
+{
+    AttrListener::typed(event, f)
+}
+
+/// The attributes of `checked_bind`.
+pub type CheckedBind = (
+    AttrListEntry<AttrProp<WebDriver>>,
+    AttrListEntry<AttrListener<WebDriver>>,
+);
+
+/// A `checked` state driven by the app for checkboxes and radio buttons,
+/// spread into the `input`, e.g. `{..checked_bind(done, on_toggle)}`. The
+/// `checked` property is set to `checked` and `f` is called with the new state
+/// on `change`. Server-side rendering writes `checked` when it is true.
+///
+/// The property is compared against the live element on each render, so a
+/// click the app ignores is undone by the next render. This covers radio
+/// groups too: the browser unchecks the other buttons of the group without a
+/// `change` event for them, and a render from the app's state sets them back
+/// if the app rejects the selection.
+pub fn checked_bind<F>(checked: bool, f: F) -> CheckedBind
+where
+    F: Fn(bool) + 'static,
+{
+    (
+        AttrListEntry(AttrProp::new("checked", checked)),
+        AttrListEntry(on("change", move |event: InputEvent| f(event.checked))),
+    )
+}
+
+impl FromEvent<WebDriver> for web::Event {
+    fn from_event(event: web::Event) -> Option<web::Event> {
+        Some(event)
+    }
+}
+
+pub struct GenericEvent(pub web::Event);
+
+impl FromEvent<WebDriver> for GenericEvent {
+    fn from_event(event: web::Event) -> Option<GenericEvent> {
+        Some(GenericEvent(event))
+    }
+}
+
+pub struct ClickEvent {
+    pub client_x: i32,
+    pub client_y: i32,
+    pub event: web::MouseEvent,
+}
+
+impl FromEvent<WebDriver> for ClickEvent {
+    fn from_event(event: web::Event) -> Option<ClickEvent> {
+        let event = event.dyn_into::<web::MouseEvent>().ok()?;
+        Some(ClickEvent {
+            client_x: event.client_x(),
+            client_y: event.client_y(),
+            event,
+        })
+    }
+}
+
+pub struct InputEvent {
+    pub value: String,
+    pub checked: bool,
+    pub event: web::Event,
+}
+
+impl FromEvent<WebDriver> for InputEvent {
+    fn from_event(event: web::Event) -> Option<InputEvent> {
+        let target = event.target()?;
+        let (value, checked) = if let Some(input) = target.dyn_ref::<web::HtmlInputElement>() {
+            (input.value(), input.checked())
+        } else if let Some(textarea) = target.dyn_ref::<web::HtmlTextAreaElement>() {
+            (textarea.value(), false)
+        } else if let Some(select) = target.dyn_ref::<web::HtmlSelectElement>() {
+            (select.value(), false)
+        } else {
+            return None;
+        };
+        Some(InputEvent {
+            value,
+            checked,
+            event,
+        })
+    }
+}
+
+pub struct KeyEvent {
+    pub key: String,
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+    pub meta: bool,
+    pub event: web::KeyboardEvent,
+}
+
+impl FromEvent<WebDriver> for KeyEvent {
+    fn from_event(event: web::Event) -> Option<KeyEvent> {
+        let event = event.dyn_into::<web::KeyboardEvent>().ok()?;
+        Some(KeyEvent {
+            key: event.key(),
+            ctrl: event.ctrl_key(),
+            shift: event.shift_key(),
+            alt: event.alt_key(),
+            meta: event.meta_key(),
+            event,
+        })
+    }
+}
+
+pub struct SubmitEvent(pub web::Event);
+
+impl SubmitEvent {
+    pub fn prevent_default(&self) {
+        self.0.prevent_default();
+    }
+}
+
+impl FromEvent<WebDriver> for SubmitEvent {
+    fn from_event(event: web::Event) -> Option<SubmitEvent> {
+        Some(SubmitEvent(event))
+    }
+}
@@ -1,13 +1,57 @@
 #![deny(bare_trait_objects, anonymous_parameters, elided_lifetimes_in_paths)]
 
+use crate::driver::WebDriver;
+use vdom::vdom::{
+    patch::Patch,
+    path::{Path, PathError},
+};
 use wasm_bindgen::JsValue;
 
 pub mod driver;
+pub mod elm;
+pub mod events;
+pub mod style;
 
 #[derive(Debug)]
 pub enum Error {
     JsValue(JsValue),
     Str(&'static str),
+    /// A patch that doesn't apply to the DOM, see `driver::apply_patches`.
+    PathNotFound {
+        patch: Patch<WebDriver>,
+        err: PathError,
+    },
+    /// A static tag or text at `path` that isn't the one it replaces, see
+    /// `NodeDiffer::on_invariant_violation`. Only returned with the `strict`
+    /// feature of `vdom`.
+    InvariantViolation {
+        path: Path,
+        expected: String,
+        found: String,
+    },
+}
+
+impl Error {
+    /// Moves an error from diffing the children of the element at `index`
+    /// to the element's parent, so the path leads to where it occurred.
+    pub(crate) fn within(self, index: usize) -> Error {
+        match self {
+            Error::InvariantViolation {
+                path,
+                expected,
+                found,
+            } => {
+                let mut indices = vec![index];
+                indices.extend_from_slice(path.indices());
+                Error::InvariantViolation {
+                    path: Path::from(indices),
+                    expected,
+                    found,
+                }
+            }
+            err => err,
+        }
+    }
 }
 
 impl From<JsValue> for Error {
@@ -0,0 +1,50 @@
+use crate::Error;
+use std::{cell::RefCell, collections::HashSet};
+use vdom::vdom::style::StyleRegistry;
+use web_sys as web;
+
+thread_local! {
+    /// The rules injected so far, which include the class derived from the
+    /// hash of their snippet.
+    static INJECTED: RefCell<HashSet<String>> = RefCell::new(HashSet::new());
+}
+
+const STYLE_ID: &str = "vdom-scoped-styles";
+
+/// Adds the rules registered with `styles` to a `<style>` element in the
+/// head of the document that all scoped styles share, unless they were added
+/// before, e.g. after each render with a new registry. Rules count as added
+/// once they're in the element, so they're added by a later call if this one
+/// fails.
+pub fn inject_styles(styles: &StyleRegistry) -> Result<(), Error> {
+    let rules = INJECTED.with(|injected| {
+        let injected = injected.borrow();
+        let mut rules = styles.rules();
+        rules.retain(|rule| !injected.contains(rule));
+        rules
+    });
+    if rules.is_empty() {
+        return Ok(());
+    }
+
+    let doc = web::window()
+        .ok_or("window is None")?
+        .document()
+        .ok_or("document is None")?;
+    let sheet = match doc.get_element_by_id(STYLE_ID) {
+        Some(sheet) => sheet,
+        None => {
+            let sheet = doc.create_element("style")?;
+            sheet.set_id(STYLE_ID);
+            doc.head()
+                .ok_or("document has no head")?
+                .append_child(&sheet)?;
+            sheet
+        }
+    };
+    for rule in rules {
+        sheet.append_child(&doc.create_text_node(&format!("{}\n", rule)))?;
+        INJECTED.with(|injected| injected.borrow_mut().insert(rule));
+    }
+    Ok(())
+}
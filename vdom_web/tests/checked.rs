@@ -0,0 +1,129 @@
+use std::{cell::Cell, rc::Rc};
+use vdom::vdom::{
+    attr::{AttrListEntry, AttrStr},
+    node::TagStatic,
+};
+use vdom_web::{
+    driver::{App, WebDriver},
+    events::{checked_bind, CheckedBind},
+};
+use wasm_bindgen::JsCast;
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+type Input = TagStatic<
+    WebDriver,
+    (),
+    (
+        AttrListEntry<AttrStr<WebDriver>>,
+        (AttrListEntry<AttrStr<WebDriver>>, CheckedBind),
+    ),
+>;
+
+fn input(kind: &'static str, checked: CheckedBind) -> Input {
+    TagStatic::new(
+        "input",
+        (
+            AttrListEntry(AttrStr::new("type", kind)),
+            (AttrListEntry(AttrStr::new("name", "group")), checked),
+        ),
+        (),
+    )
+}
+
+fn root_element() -> web_sys::Element {
+    let document = web_sys::window().unwrap().document().unwrap();
+    let root_element = document.create_element("div").unwrap();
+    document
+        .body()
+        .unwrap()
+        .append_child(&root_element)
+        .unwrap();
+    root_element
+}
+
+fn inputs(root_element: &web_sys::Element) -> Vec<web_sys::HtmlInputElement> {
+    let nodes = root_element.child_nodes();
+    (0..nodes.length())
+        .map(|i| nodes.get(i).unwrap().dyn_into().unwrap())
+        .collect()
+}
+
+fn checked(root_element: &web_sys::Element) -> Vec<bool> {
+    inputs(root_element)
+        .iter()
+        .map(|input| input.checked())
+        .collect()
+}
+
+#[wasm_bindgen_test]
+fn checkboxes_toggle_the_state() {
+    let root_element = root_element();
+    let done = Rc::new(Cell::new(false));
+    let view = |done: &Rc<Cell<bool>>| {
+        let on_change = done.clone();
+        input(
+            "checkbox",
+            checked_bind(done.get(), move |checked| on_change.set(checked)),
+        )
+    };
+    let mut app = App::new(view(&done), root_element.clone()).unwrap();
+    assert_eq!(checked(&root_element), [false]);
+
+    inputs(&root_element)[0].click();
+    assert!(done.get());
+    app.set(view(&done)).unwrap();
+    assert_eq!(checked(&root_element), [true]);
+
+    inputs(&root_element)[0].click();
+    assert!(!done.get());
+    app.set(view(&done)).unwrap();
+    assert_eq!(checked(&root_element), [false]);
+
+    // A click the app ignores is undone by the next render.
+    let ignored = input("checkbox", checked_bind(false, |_| {}));
+    app.set(ignored).unwrap();
+    inputs(&root_element)[0].click();
+    assert_eq!(checked(&root_element), [true]);
+    app.set(input("checkbox", checked_bind(false, |_| {})))
+        .unwrap();
+    assert_eq!(checked(&root_element), [false]);
+}
+
+#[wasm_bindgen_test]
+fn radio_buttons_of_a_group_exclude_each_other() {
+    let root_element = root_element();
+    let selected = Rc::new(Cell::new(0));
+    let view = |selected: &Rc<Cell<usize>>, accept: bool| {
+        (0..3)
+            .map(|i| {
+                let on_change = selected.clone();
+                input(
+                    "radio",
+                    checked_bind(selected.get() == i, move |checked| {
+                        if checked && accept {
+                            on_change.set(i);
+                        }
+                    }),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+    let mut app = App::new(view(&selected, true), root_element.clone()).unwrap();
+    assert_eq!(checked(&root_element), [true, false, false]);
+
+    inputs(&root_element)[2].click();
+    assert_eq!(selected.get(), 2);
+    app.set(view(&selected, true)).unwrap();
+    assert_eq!(checked(&root_element), [false, false, true]);
+
+    // The browser unchecks the other buttons without a change event for
+    // them, and a rejected selection is set back by the next render.
+    app.set(view(&selected, false)).unwrap();
+    inputs(&root_element)[1].click();
+    assert_eq!(checked(&root_element), [false, true, false]);
+    assert_eq!(selected.get(), 2);
+    app.set(view(&selected, false)).unwrap();
+    assert_eq!(checked(&root_element), [false, false, true]);
+}
@@ -0,0 +1,73 @@
+use vdom::vdom::{
+    attr::{AttrDyn, AttrListEntry, ContentEditable},
+    node::TagStatic,
+};
+use vdom_web::driver::{App, WebDriver};
+use wasm_bindgen::JsCast;
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+type Editor = TagStatic<WebDriver, (), AttrListEntry<AttrDyn<WebDriver>>>;
+
+fn editor(editable: Option<ContentEditable>) -> Editor {
+    TagStatic::new(
+        "div",
+        AttrListEntry(AttrDyn::new("contenteditable", editable)),
+        (),
+    )
+}
+
+/// A root element within an editable parent, for the editor to inherit
+/// from when it has no `contenteditable` of its own.
+fn root_element() -> web_sys::Element {
+    let document = web_sys::window().unwrap().document().unwrap();
+    let root_element = document.create_element("div").unwrap();
+    root_element
+        .set_attribute("contenteditable", "true")
+        .unwrap();
+    document
+        .body()
+        .unwrap()
+        .append_child(&root_element)
+        .unwrap();
+    root_element
+}
+
+/// The `contenteditable` of the editor and whether it's editable.
+fn state(root_element: &web_sys::Element) -> (Option<String>, bool) {
+    let element = root_element
+        .first_element_child()
+        .unwrap()
+        .dyn_into::<web_sys::HtmlElement>()
+        .unwrap();
+    (
+        element.get_attribute("contenteditable"),
+        element.is_content_editable(),
+    )
+}
+
+#[wasm_bindgen_test]
+fn each_state_is_set_and_diffed() {
+    let root_element = root_element();
+    let node = editor(Some(ContentEditable::False));
+    let mut app = App::new(node, root_element.clone()).unwrap();
+    // `false` is written rather than omitted, so it overrides the parent.
+    assert_eq!(state(&root_element), (Some("false".to_string()), false));
+
+    app.set(editor(Some(ContentEditable::True))).unwrap();
+    assert_eq!(state(&root_element), (Some("true".to_string()), true));
+
+    app.set(editor(Some(ContentEditable::PlaintextOnly)))
+        .unwrap();
+    let plaintext_only = Some("plaintext-only".to_string());
+    assert_eq!(state(&root_element).0, plaintext_only);
+
+    // Without the attribute, the editor inherits the parent's state.
+    app.set(editor(None)).unwrap();
+    assert_eq!(state(&root_element), (None, true));
+
+    app.set(editor(Some(ContentEditable::False))).unwrap();
+    assert_eq!(state(&root_element), (Some("false".to_string()), false));
+    root_element.remove();
+}
@@ -0,0 +1,25 @@
+use vdom::vdom::node::TextDyn;
+use vdom_web::{driver::WebDriver, elm::ElmApp};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+fn messages_sent_by_the_first_view_are_handled() {
+    let document = web_sys::window().unwrap().document().unwrap();
+    let root_element = document.create_element("div").unwrap();
+    let app = ElmApp::new(
+        0,
+        |count: &u32, dispatcher| {
+            if *count == 0 {
+                dispatcher.send(1);
+            }
+            TextDyn::<WebDriver>::new(count.to_string())
+        },
+        |count, msg| *count = msg,
+        root_element.clone(),
+    )
+    .unwrap();
+    assert_eq!(app.with_state(|count| *count), 1);
+    assert_eq!(root_element.inner_html(), "1");
+}
@@ -0,0 +1,147 @@
+use std::{cell::RefCell, rc::Rc};
+use vdom::vdom::{
+    attr::{AttrListEntry, AttrListener},
+    node::TagStatic,
+};
+use vdom_web::{
+    driver::{App, WebDriver},
+    events::{on, ClickEvent, InputEvent, KeyEvent, SubmitEvent},
+};
+use wasm_bindgen::JsCast;
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+type Tag = TagStatic<WebDriver, (), AttrListEntry<AttrListener<WebDriver>>>;
+
+fn tag(tag: &'static str, listener: AttrListener<WebDriver>) -> Tag {
+    TagStatic::new(tag, AttrListEntry(listener), ())
+}
+
+fn root_element() -> web_sys::Element {
+    let document = web_sys::window().unwrap().document().unwrap();
+    let root_element = document.create_element("div").unwrap();
+    document
+        .body()
+        .unwrap()
+        .append_child(&root_element)
+        .unwrap();
+    root_element
+}
+
+#[wasm_bindgen_test]
+fn click_events_carry_their_position() {
+    let seen = Rc::new(RefCell::new(None));
+    let root_element = root_element();
+    let listener = {
+        let seen = seen.clone();
+        on("click", move |e: ClickEvent| {
+            *seen.borrow_mut() = Some((e.client_x, e.client_y))
+        })
+    };
+    let _app = App::new(tag("button", listener), root_element.clone()).unwrap();
+
+    let event = web_sys::MouseEvent::new_with_mouse_event_init_dict(
+        "click",
+        web_sys::MouseEventInit::new()
+            .bubbles(true)
+            .client_x(12)
+            .client_y(34),
+    )
+    .unwrap();
+    let button = root_element.first_child().unwrap();
+    button.dispatch_event(&event).unwrap();
+    assert_eq!(*seen.borrow(), Some((12, 34)));
+    root_element.remove();
+}
+
+#[wasm_bindgen_test]
+fn click_listeners_ignore_other_events() {
+    let seen = Rc::new(RefCell::new(None));
+    let root_element = root_element();
+    let listener = {
+        let seen = seen.clone();
+        on("click", move |e: ClickEvent| {
+            *seen.borrow_mut() = Some((e.client_x, e.client_y))
+        })
+    };
+    let _app = App::new(tag("button", listener), root_element.clone()).unwrap();
+
+    // A plain `Event` isn't a `MouseEvent`, so it doesn't convert.
+    let event = web_sys::Event::new("click").unwrap();
+    let button = root_element.first_child().unwrap();
+    button.dispatch_event(&event).unwrap();
+    assert_eq!(*seen.borrow(), None);
+    root_element.remove();
+}
+
+#[wasm_bindgen_test]
+fn input_events_carry_the_value_of_their_target() {
+    let seen = Rc::new(RefCell::new(None));
+    let root_element = root_element();
+    let listener = {
+        let seen = seen.clone();
+        on("input", move |e: InputEvent| {
+            *seen.borrow_mut() = Some((e.value, e.checked))
+        })
+    };
+    let _app = App::new(tag("input", listener), root_element.clone()).unwrap();
+
+    let input = root_element
+        .first_child()
+        .unwrap()
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    input.set_value("typed");
+    input
+        .dispatch_event(&web_sys::Event::new("input").unwrap())
+        .unwrap();
+    assert_eq!(*seen.borrow(), Some(("typed".to_owned(), false)));
+    root_element.remove();
+}
+
+#[wasm_bindgen_test]
+fn key_events_carry_the_key_and_modifiers() {
+    let seen = Rc::new(RefCell::new(None));
+    let root_element = root_element();
+    let listener = {
+        let seen = seen.clone();
+        on("keydown", move |e: KeyEvent| {
+            *seen.borrow_mut() = Some((e.key, e.ctrl, e.shift, e.alt, e.meta))
+        })
+    };
+    let _app = App::new(tag("input", listener), root_element.clone()).unwrap();
+
+    let event = web_sys::KeyboardEvent::new_with_keyboard_event_init_dict(
+        "keydown",
+        web_sys::KeyboardEventInit::new()
+            .bubbles(true)
+            .key("Enter")
+            .ctrl_key(true),
+    )
+    .unwrap();
+    let input = root_element.first_child().unwrap();
+    input.dispatch_event(&event).unwrap();
+    assert_eq!(
+        *seen.borrow(),
+        Some(("Enter".to_owned(), true, false, false, false))
+    );
+    root_element.remove();
+}
+
+#[wasm_bindgen_test]
+fn submit_events_can_be_prevented() {
+    let root_element = root_element();
+    let listener = on("submit", |e: SubmitEvent| e.prevent_default());
+    let _app = App::new(tag("form", listener), root_element.clone()).unwrap();
+
+    let event = web_sys::Event::new_with_event_init_dict(
+        "submit",
+        web_sys::EventInit::new().bubbles(true).cancelable(true),
+    )
+    .unwrap();
+    let form = root_element.first_child().unwrap();
+    assert!(!form.dispatch_event(&event).unwrap());
+    assert!(event.default_prevented());
+    root_element.remove();
+}
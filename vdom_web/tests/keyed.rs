@@ -0,0 +1,51 @@
+use vdom::vdom::{
+    attr::{AttrListEntry, AttrStr},
+    node::{Keyed, TagStatic},
+};
+use vdom_web::driver::{App, WebDriver};
+use wasm_bindgen::JsCast;
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+type Input = TagStatic<WebDriver, (), AttrListEntry<AttrStr<WebDriver>>>;
+
+fn inputs(names: &[&'static str]) -> Keyed<Input> {
+    Keyed::new(names.iter().map(|&name| {
+        let input = TagStatic::new("input", AttrListEntry(AttrStr::new("name", name)), ());
+        (name, input)
+    }))
+}
+
+fn root_element() -> web_sys::Element {
+    let document = web_sys::window().unwrap().document().unwrap();
+    let root_element = document.create_element("div").unwrap();
+    document
+        .body()
+        .unwrap()
+        .append_child(&root_element)
+        .unwrap();
+    root_element
+}
+
+#[wasm_bindgen_test]
+fn reordered_inputs_keep_the_focus() {
+    let document = web_sys::window().unwrap().document().unwrap();
+    let root_element = root_element();
+    let mut app = App::new(inputs(&["a", "b"]), root_element.clone()).unwrap();
+    let b = root_element.last_element_child().unwrap();
+    b.dyn_ref::<web_sys::HtmlElement>()
+        .unwrap()
+        .focus()
+        .unwrap();
+    assert!(document.active_element() == Some(b.clone()));
+
+    app.set(inputs(&["b", "a"])).unwrap();
+    assert_eq!(
+        root_element.inner_html(),
+        "<input name=\"b\"><input name=\"a\">"
+    );
+    assert!(root_element.first_element_child() == Some(b.clone()));
+    assert!(document.active_element() == Some(b));
+    root_element.remove();
+}
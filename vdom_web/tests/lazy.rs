@@ -0,0 +1,95 @@
+use std::{cell::RefCell, rc::Rc};
+use vdom::{
+    html::HYDRATION_KEY,
+    vdom::{
+        attr::{AttrListEntry, AttrListener},
+        node::{TagStatic, TextStatic},
+        path::Path,
+    },
+};
+use vdom_web::driver::{App, WebDriver, LAZY_EVENTS};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+type Button = TagStatic<WebDriver, TextStatic<WebDriver>, AttrListEntry<AttrListener<WebDriver>>>;
+
+fn root_element(html: &str) -> web_sys::Element {
+    let document = web_sys::window().unwrap().document().unwrap();
+    let root_element = document.create_element("div").unwrap();
+    root_element.set_inner_html(html);
+    root_element
+}
+
+/// A button whose listener for `event` records `name`.
+fn button(
+    event: &'static str,
+    name: &'static str,
+    seen: &Rc<RefCell<Vec<&'static str>>>,
+) -> Button {
+    let seen = seen.clone();
+    TagStatic::new(
+        "button",
+        AttrListEntry(AttrListener::new(event, move |_| {
+            seen.borrow_mut().push(name)
+        })),
+        TextStatic::new(name),
+    )
+}
+
+fn dispatch(target: &web_sys::Node, event: &str) {
+    let event = web_sys::Event::new(event).unwrap();
+    target.dispatch_event(&event).unwrap();
+}
+
+#[wasm_bindgen_test]
+fn events_with_listeners_hydrate_their_target() {
+    assert!(!LAZY_EVENTS.contains(&"ping"));
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    let root_element = root_element("<button>a</button>");
+    let _app = App::hydrate_lazily(button("ping", "a", &seen), root_element.clone()).unwrap();
+    dispatch(&root_element.first_child().unwrap(), "ping");
+    assert_eq!(*seen.borrow(), ["a"]);
+}
+
+#[wasm_bindgen_test]
+fn text_targets_hydrate_their_element() {
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    let root_element = root_element("<button>a</button>");
+    let _app = App::hydrate_lazily(button("click", "a", &seen), root_element.clone()).unwrap();
+    let text = root_element.first_child().unwrap().first_child().unwrap();
+    dispatch(&text, "click");
+    assert_eq!(*seen.borrow(), ["a"]);
+}
+
+#[wasm_bindgen_test]
+fn keyed_targets_hydrate_their_virtual_node() {
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    // Rendered in another order than the virtual tree.
+    let root_element = root_element(&format!(
+        "<button {key}=\"{b}\">b</button><button {key}=\"{a}\">a</button>",
+        key = HYDRATION_KEY,
+        a = Path::root().child(0),
+        b = Path::root().child(1),
+    ));
+    let live_a = root_element.last_child().unwrap();
+    let node = (button("click", "a", &seen), button("click", "b", &seen));
+    let _app = App::hydrate_lazily(node, root_element.clone()).unwrap();
+    dispatch(&live_a, "click");
+    assert_eq!(*seen.borrow(), ["a"]);
+    assert!(root_element.first_child().unwrap() == live_a);
+}
+
+#[wasm_bindgen_test]
+fn removed_subtrees_leave_no_hydrated_paths() {
+    let root_element = root_element("<p>a</p><p>b</p>");
+    let p = |text| TagStatic::<WebDriver, _, ()>::new("p", (), TextStatic::new(text));
+    let mut app = App::hydrate_lazily(vec![p("a"), p("b")], root_element.clone()).unwrap();
+    app.hydrate_path(&Path::from(vec![1])).unwrap();
+
+    // The path of the removed paragraph no longer resolves, which hydrating
+    // another path asserts in debug builds if it's still recorded.
+    app.set(vec![p("a")]).unwrap();
+    app.hydrate_path(&Path::from(vec![0])).unwrap();
+    assert_eq!(root_element.inner_html(), "<p>a</p>");
+}
@@ -0,0 +1,49 @@
+use vdom::vdom::{
+    attr::{AttrDyn, AttrListEntry, AttrNs},
+    node::TagStatic,
+};
+use vdom_web::driver::{App, WebDriver};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+type Use = TagStatic<WebDriver, (), AttrListEntry<AttrDyn<WebDriver>>>;
+
+fn icon(href: Option<&'static str>) -> Use {
+    TagStatic::new(
+        "use",
+        AttrListEntry(AttrDyn::new("href", href).ns(AttrNs::XLINK)),
+        (),
+    )
+}
+
+fn root_element() -> web_sys::Element {
+    let document = web_sys::window().unwrap().document().unwrap();
+    document.create_element("div").unwrap()
+}
+
+/// The `xlink:href` of `element`, looked up by namespace and by its
+/// qualified name.
+fn xlink_href(element: &web_sys::Element) -> (Option<String>, Option<String>) {
+    (
+        element.get_attribute_ns(Some(AttrNs::XLINK.uri), "href"),
+        element.get_attribute("xlink:href"),
+    )
+}
+
+#[wasm_bindgen_test]
+fn namespaced_attrs_are_set_in_their_namespace() {
+    let root_element = root_element();
+    let mut app = App::new(icon(Some("#a")), root_element.clone()).unwrap();
+    let element = root_element.first_element_child().unwrap();
+    let set = |href: &str| (Some(href.to_string()), Some(href.to_string()));
+    assert_eq!(xlink_href(&element), set("#a"));
+    // Not in the namespace of attributes without a prefix.
+    assert_eq!(element.get_attribute_ns(None, "href"), None);
+
+    app.set(icon(Some("#b"))).unwrap();
+    assert_eq!(xlink_href(&element), set("#b"));
+
+    app.set(icon(None)).unwrap();
+    assert_eq!(xlink_href(&element), (None, None));
+}
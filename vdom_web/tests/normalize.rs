@@ -0,0 +1,51 @@
+use vdom::vdom::node::{TagStatic, TextDyn};
+use vdom_web::driver::{App, WebDriver};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+type Texts = (TextDyn<WebDriver>, TextDyn<WebDriver>);
+
+fn texts(a: &str, b: &str) -> Texts {
+    (TextDyn::new(a.to_string()), TextDyn::new(b.to_string()))
+}
+
+fn root_element() -> web_sys::Element {
+    let document = web_sys::window().unwrap().document().unwrap();
+    document.create_element("div").unwrap()
+}
+
+#[wasm_bindgen_test]
+fn texts_merged_by_normalize_are_patched() {
+    let root_element = root_element();
+    let p = |a, b| TagStatic::<WebDriver, _, ()>::new("p", (), texts(a, b));
+    let mut app = App::new(p("a", "b"), root_element.clone()).unwrap();
+    let elem = root_element.first_child().unwrap();
+    assert_eq!(elem.child_nodes().length(), 2);
+
+    elem.normalize();
+    assert_eq!(elem.child_nodes().length(), 1);
+    app.set(p("a", "c")).unwrap();
+    assert_eq!(root_element.inner_html(), "<p>ac</p>");
+    assert_eq!(elem.child_nodes().length(), 2);
+
+    // And again, with the second text changing first.
+    elem.normalize();
+    app.set(p("x", "c")).unwrap();
+    assert_eq!(root_element.inner_html(), "<p>xc</p>");
+    assert_eq!(elem.child_nodes().length(), 2);
+}
+
+#[wasm_bindgen_test]
+fn empty_texts_dropped_by_normalize_are_patched() {
+    let root_element = root_element();
+    let mut app = App::new(texts("", "b"), root_element.clone()).unwrap();
+    root_element.normalize();
+    assert_eq!(root_element.child_nodes().length(), 1);
+
+    app.set(texts("a", "b")).unwrap();
+    assert_eq!(root_element.inner_html(), "ab");
+    assert_eq!(root_element.child_nodes().length(), 2);
+    let first = root_element.first_child().unwrap();
+    assert_eq!(first.text_content(), Some("a".to_string()));
+}
@@ -0,0 +1,45 @@
+use vdom::vdom::{
+    attr::{AttrDyn, AttrListEntry},
+    node::{TagStatic, TextDyn},
+};
+use vdom_web::driver::{App, WebDriver};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+type NoScript = TagStatic<
+    WebDriver,
+    TagStatic<WebDriver, TextDyn<WebDriver>, ()>,
+    AttrListEntry<AttrDyn<WebDriver>>,
+>;
+
+fn noscript(title: &'static str, text: &'static str) -> NoScript {
+    TagStatic::new(
+        "noscript",
+        AttrListEntry(AttrDyn::new("title", title)),
+        TagStatic::new("p", (), TextDyn::new(text)),
+    )
+}
+
+fn root_element() -> web_sys::Element {
+    let document = web_sys::window().unwrap().document().unwrap();
+    document.create_element("div").unwrap()
+}
+
+#[wasm_bindgen_test]
+fn noscript_children_are_left_to_the_browser() {
+    let root_element = root_element();
+    let mut app = App::new(noscript("a", "Enable JS"), root_element.clone()).unwrap();
+    let elem = root_element.first_element_child().unwrap();
+    assert_eq!(elem.tag_name(), "NOSCRIPT");
+    // With scripts enabled, the children would show up, so they aren't built.
+    assert_eq!(elem.child_nodes().length(), 0);
+
+    app.set(noscript("b", "Please enable JS")).unwrap();
+    assert_eq!(elem.get_attribute("title"), Some("b".to_string()));
+    assert_eq!(elem.child_nodes().length(), 0);
+    assert_eq!(
+        root_element.inner_html(),
+        r#"<noscript title="b"></noscript>"#
+    );
+}
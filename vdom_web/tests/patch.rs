@@ -0,0 +1,74 @@
+use vdom::vdom::{
+    dom::{DomAttr, DomNode},
+    patch::Patch,
+    path::Path,
+};
+use vdom_web::{
+    driver::{apply_patches, finish_unmount, App, EXITING_ATTR, EXIT_MS_ATTR},
+    Error,
+};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+fn root_element(html: &str) -> web_sys::Element {
+    let document = web_sys::window().unwrap().document().unwrap();
+    let root_element = document.create_element("div").unwrap();
+    root_element.set_inner_html(html);
+    root_element
+}
+
+#[wasm_bindgen_test]
+fn texts_are_patched() {
+    let root_element = root_element("a<p>b</p>");
+    let patches = vec![Patch::SetText {
+        path: Path::from(vec![1, 0]),
+        text: "c".to_string(),
+    }];
+    apply_patches(&root_element, patches).unwrap();
+    assert_eq!(root_element.inner_html(), "a<p>c</p>");
+}
+
+#[wasm_bindgen_test]
+fn comments_are_not_texts() {
+    let root_element = root_element("<!--a-->");
+    let patches = vec![Patch::SetText {
+        path: Path::from(vec![0]),
+        text: "b".to_string(),
+    }];
+    match apply_patches(&root_element, patches) {
+        Err(Error::PathNotFound { .. }) => {}
+        res => panic!("expected the path not to be found, got {:?}", res),
+    }
+    assert_eq!(root_element.inner_html(), "<!--a-->");
+}
+
+#[wasm_bindgen_test]
+fn exiting_elements_are_skipped_by_paths() {
+    let root_element = root_element("");
+    let mut app = App::new(
+        vec![
+            DomNode::tag(
+                "div",
+                vec![DomAttr::new(EXIT_MS_ATTR, "10000")],
+                vec![DomNode::text("a")],
+            ),
+            DomNode::tag("p", vec![], vec![DomNode::text("b")]),
+        ],
+        root_element.clone(),
+    )
+    .unwrap();
+    app.set(vec![DomNode::tag("p", vec![], vec![DomNode::text("b")])])
+        .unwrap();
+    let exiting = root_element.first_element_child().unwrap();
+    assert!(exiting.has_attribute(EXITING_ATTR));
+
+    let patches = vec![Patch::SetText {
+        path: Path::from(vec![0, 0]),
+        text: "c".to_string(),
+    }];
+    apply_patches(&root_element, patches).unwrap();
+    assert_eq!(exiting.text_content().unwrap(), "a");
+    assert_eq!(root_element.last_element_child().unwrap().inner_html(), "c");
+    finish_unmount(&exiting);
+}
@@ -0,0 +1,104 @@
+use vdom::vdom::{
+    dom::{DomAttr, DomNode},
+    patch::Patch,
+    path::Path,
+};
+use vdom_web::driver::{apply_patches, App, WebDriver};
+use wasm_bindgen::{closure::Closure, JsCast};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+fn root_element() -> web_sys::Element {
+    let document = web_sys::window().unwrap().document().unwrap();
+    let root_element = document.create_element("div").unwrap();
+    document
+        .body()
+        .unwrap()
+        .append_child(&root_element)
+        .unwrap();
+    root_element
+}
+
+fn iframe(src: &'static str, title: &'static str) -> DomNode<WebDriver> {
+    DomNode::tag(
+        "iframe",
+        vec![DomAttr::new("src", src), DomAttr::new("title", title)],
+        vec![],
+    )
+}
+
+/// The writes to `root_element` and below while `f` runs, e.g. `attributes
+/// title` for setting the title of an element.
+fn writes<F>(root_element: &web_sys::Element, f: F) -> Vec<String>
+where
+    F: FnOnce(),
+{
+    let callback = Closure::wrap(Box::new(|| {}) as Box<dyn FnMut()>);
+    let observer = web_sys::MutationObserver::new(callback.as_ref().unchecked_ref()).unwrap();
+    let mut init = web_sys::MutationObserverInit::new();
+    init.attributes(true)
+        .character_data(true)
+        .child_list(true)
+        .subtree(true);
+    observer.observe_with_options(root_element, &init).unwrap();
+    f();
+    let records = observer.take_records();
+    observer.disconnect();
+    (0..records.length())
+        .map(|i| {
+            let record: web_sys::MutationRecord = records.get(i).dyn_into().unwrap();
+            match record.attribute_name() {
+                Some(name) => format!("{} {}", record.type_(), name),
+                None => record.type_(),
+            }
+        })
+        .collect()
+}
+
+#[wasm_bindgen_test]
+fn unchanged_iframe_src_is_not_written() {
+    let root_element = root_element();
+    let mut app = App::new(vec![iframe("about:blank", "a")], root_element.clone()).unwrap();
+    let frame = root_element.first_child().unwrap();
+
+    let writes = writes(&root_element, || {
+        app.set(vec![iframe("about:blank", "b")]).unwrap();
+    });
+    assert_eq!(writes, ["attributes title"]);
+    assert!(root_element.first_child().unwrap() == frame);
+
+    // Another document replaces the iframe.
+    app.set(vec![iframe("about:blank#other", "b")]).unwrap();
+    assert!(root_element.first_child().unwrap() != frame);
+    root_element.remove();
+}
+
+#[wasm_bindgen_test]
+fn patches_setting_an_identical_src_are_not_written() {
+    let root_element = root_element();
+    let _app = App::new(vec![iframe("about:blank", "a")], root_element.clone()).unwrap();
+    let patches = vec![Patch::SetAttr {
+        path: Path::from(vec![0]),
+        name: "src".to_string(),
+        value: "about:blank".to_string().into(),
+    }];
+    let writes = writes(&root_element, || {
+        apply_patches(&root_element, patches).unwrap();
+    });
+    assert!(writes.is_empty());
+    root_element.remove();
+}
+
+#[wasm_bindgen_test]
+fn scripts_are_not_patched() {
+    let root_element = root_element();
+    let script = |code| DomNode::tag("script", vec![], vec![DomNode::text(code)]);
+    let mut app = App::new(vec![script("0")], root_element.clone()).unwrap();
+    let writes = writes(&root_element, || {
+        app.set(vec![script("1")]).unwrap();
+    });
+    assert!(writes.is_empty());
+    assert_eq!(root_element.inner_html(), "<script>0</script>");
+    root_element.remove();
+}
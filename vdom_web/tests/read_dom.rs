@@ -0,0 +1,53 @@
+use vdom::vdom::{dom::DomNode, node::Text};
+use vdom_web::driver::{read_dom, App};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+fn root_element(html: &str) -> web_sys::Element {
+    let document = web_sys::window().unwrap().document().unwrap();
+    let root_element = document.create_element("div").unwrap();
+    root_element.set_inner_html(html);
+    root_element
+}
+
+#[wasm_bindgen_test]
+fn read_dom_round_trips_elements_and_text() {
+    let html = "intro<ul class=\"list\" data-x=\"\"><li title=\"a &amp; b\">a</li>\
+                <li><input disabled=\"\"></li></ul>";
+    let nodes = read_dom(&root_element(html));
+    assert_eq!(nodes.len(), 2);
+    match &nodes[1] {
+        DomNode::Tag(tag) => {
+            assert_eq!(tag.tag, "ul");
+            assert_eq!(tag.attrs.len(), 2);
+            assert_eq!(tag.children.len(), 2);
+        }
+        DomNode::Text(_) | DomNode::Comment(_) => panic!("expected an element"),
+    }
+
+    let rendered = root_element("");
+    let _app = App::new(nodes, rendered.clone()).unwrap();
+    assert_eq!(rendered.inner_html(), html);
+}
+
+#[wasm_bindgen_test]
+fn read_dom_keeps_comments() {
+    let html = "a<!-- note --><p>b<!---->c</p>";
+    let nodes = read_dom(&root_element(html));
+    match &nodes[1] {
+        DomNode::Comment(comment) => assert_eq!(comment.get(), " note "),
+        node => panic!("expected a comment, got {:?}", node),
+    }
+    let rendered = root_element("");
+    let mut app = App::new(nodes, rendered.clone()).unwrap();
+    assert_eq!(rendered.inner_html(), html);
+    assert_eq!(rendered.child_nodes().length(), 3);
+
+    // Comments are diffed like text, keeping their node.
+    let comment = rendered.child_nodes().get(1).unwrap();
+    app.set(read_dom(&root_element("a<!-- edited --><p>b<!---->c</p>")))
+        .unwrap();
+    assert_eq!(rendered.inner_html(), "a<!-- edited --><p>b<!---->c</p>");
+    assert_eq!(rendered.child_nodes().get(1), Some(comment));
+}
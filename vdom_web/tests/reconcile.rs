@@ -0,0 +1,71 @@
+use vdom::vdom::{
+    attr::{AttrDyn, AttrListEntry, AttrProp},
+    node::{TagStatic, TextDyn},
+};
+use vdom_web::driver::{App, WebDriver};
+use wasm_bindgen::JsCast;
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+fn root_element(html: &str) -> web_sys::Element {
+    let document = web_sys::window().unwrap().document().unwrap();
+    let root_element = document.create_element("div").unwrap();
+    root_element.set_inner_html(html);
+    root_element
+}
+
+#[wasm_bindgen_test]
+fn changed_attributes_are_reconciled() {
+    let root_element = root_element("<p class=\"a\" title=\"t\">x</p>");
+    let p = root_element.first_child().unwrap();
+    let node = TagStatic::<WebDriver, _, _>::new(
+        "p",
+        AttrListEntry(AttrDyn::new("class", "b")),
+        TextDyn::new("y"),
+    );
+    let _app = App::reconcile(node, root_element.clone()).unwrap();
+    assert_eq!(root_element.inner_html(), "<p class=\"b\">y</p>");
+    assert!(root_element.first_child().unwrap() == p);
+}
+
+#[wasm_bindgen_test]
+fn attributes_reflecting_props_are_kept() {
+    let root_element = root_element("<input value=\"a\" title=\"t\">");
+    let node =
+        TagStatic::<WebDriver, (), _>::new("input", AttrListEntry(AttrProp::new("value", "b")), ());
+    let _app = App::reconcile(node, root_element.clone()).unwrap();
+    let input = root_element
+        .first_child()
+        .unwrap()
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    assert_eq!(input.value(), "b");
+    assert_eq!(input.get_attribute("value"), Some("a".to_string()));
+    assert!(!input.has_attribute("title"));
+}
+
+#[wasm_bindgen_test]
+fn hydration_matches_elements_by_key() {
+    // Rendered with `HtmlRenderer::hydration_keys`, then reordered and
+    // indented.
+    let root_element = root_element(
+        "<ul data-hk=\"/0\">\n  <li data-hk=\"/0/1\">b</li>\n  <li data-hk=\"/0/0\">a</li>\n</ul>",
+    );
+    let ul = root_element.first_child().unwrap();
+    let items = ul.child_nodes();
+    let (b, a) = (items.get(1).unwrap(), items.get(3).unwrap());
+    let node = TagStatic::<WebDriver, _, ()>::new(
+        "ul",
+        (),
+        (
+            TagStatic::<WebDriver, _, ()>::new("li", (), TextDyn::new("a")),
+            TagStatic::<WebDriver, _, ()>::new("li", (), TextDyn::new("b")),
+        ),
+    );
+    let _app = App::hydrate(node, root_element.clone()).unwrap();
+    assert_eq!(root_element.inner_html(), "<ul><li>a</li><li>b</li></ul>");
+    let items = root_element.first_child().unwrap().child_nodes();
+    assert!(items.get(0).unwrap() == a);
+    assert!(items.get(1).unwrap() == b);
+}
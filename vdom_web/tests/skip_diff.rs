@@ -0,0 +1,51 @@
+use vdom::vdom::{
+    dom::{DomAttr, DomNode},
+    node::SKIP_DIFF_ATTR,
+};
+use vdom_web::driver::{App, WebDriver};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+fn root_element() -> web_sys::Element {
+    let document = web_sys::window().unwrap().document().unwrap();
+    document.create_element("div").unwrap()
+}
+
+fn widget(title: &'static str, text: &'static str) -> DomNode<WebDriver> {
+    DomNode::tag(
+        "div",
+        vec![
+            DomAttr::new(SKIP_DIFF_ATTR, ""),
+            DomAttr::new("title", title),
+        ],
+        vec![DomNode::text(text)],
+    )
+}
+
+#[wasm_bindgen_test]
+fn foreign_children_survive_rerenders() {
+    let root_element = root_element();
+    let mut app = App::new(vec![widget("a", "x")], root_element.clone()).unwrap();
+    let elem = root_element.first_element_child().unwrap();
+    assert_eq!(elem.child_nodes().length(), 0);
+
+    // What a map widget or an editor would do.
+    elem.set_inner_html("<canvas></canvas>");
+    app.set(vec![widget("b", "y")]).unwrap();
+    assert_eq!(elem.get_attribute("title"), Some("b".to_string()));
+    assert_eq!(elem.inner_html(), "<canvas></canvas>");
+
+    // Siblings moving around it don't touch it either.
+    app.set(vec![
+        DomNode::tag("p", vec![], vec![DomNode::text("z")]),
+        widget("b", "y"),
+    ])
+    .unwrap();
+    assert_eq!(elem.inner_html(), "<canvas></canvas>");
+
+    // Removing the element itself still works.
+    app.set(vec![DomNode::tag("p", vec![], vec![DomNode::text("z")])])
+        .unwrap();
+    assert_eq!(root_element.inner_html(), "<p>z</p>");
+}
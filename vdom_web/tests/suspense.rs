@@ -0,0 +1,130 @@
+use futures::{
+    channel::oneshot,
+    compat::{Compat, Future01CompatExt},
+    Future, FutureExt,
+};
+use js_sys::Promise;
+use std::{cell::Cell, pin::Pin, rc::Rc};
+use vdom::{
+    driver::DriverCtx,
+    vdom::node::{Resource, Suspense, TagStatic, TextDyn, TextStatic},
+};
+use vdom_web::driver::{App, WebDriver};
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::JsFuture;
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// What async tests return, a futures 0.1 future.
+type TestFuture = Compat<Pin<Box<dyn Future<Output = Result<(), JsValue>>>>>;
+
+type Data = Resource<&'static str, &'static str>;
+
+type Sender = oneshot::Sender<Result<&'static str, &'static str>>;
+
+type Page = TagStatic<
+    WebDriver,
+    Suspense<TextStatic<WebDriver>, TextDyn<WebDriver>, TextDyn<WebDriver>>,
+    (),
+>;
+
+fn root_element() -> web_sys::Element {
+    let document = web_sys::window().unwrap().document().unwrap();
+    document.create_element("div").unwrap()
+}
+
+fn page(resource: &Data) -> Page {
+    TagStatic::new(
+        "p",
+        (),
+        Suspense::new(
+            resource,
+            || TextStatic::new("loading"),
+            |data| TextDyn::new(*data),
+            |err| TextDyn::new(format!("failed: {}", err)),
+        ),
+    )
+}
+
+/// A resource spawned on a driver, settled by sending its result. The
+/// returned flag is set once the resource settled.
+fn resource() -> (Data, Sender, Rc<Cell<bool>>) {
+    let (sender, receiver) = oneshot::channel();
+    let settled = Rc::new(Cell::new(false));
+    let on_settled = {
+        let settled = settled.clone();
+        move || settled.set(true)
+    };
+    let resource = Resource::spawn(
+        &DriverCtx::new(WebDriver {}),
+        receiver.map(|res| res.unwrap_or(Err("canceled"))),
+        on_settled,
+    );
+    (resource, sender, settled)
+}
+
+/// Resolves after `ms` milliseconds.
+fn sleep(ms: i32) -> impl Future<Output = Result<JsValue, JsValue>> {
+    let promise = Promise::new(&mut |resolve, _| {
+        web_sys::window()
+            .unwrap()
+            .set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, ms)
+            .unwrap();
+    });
+    JsFuture::from(promise).compat()
+}
+
+#[wasm_bindgen_test(async)]
+fn settled_resources_replace_the_placeholder() -> TestFuture {
+    let root_element = root_element();
+    let (resource, sender, settled) = resource();
+    let mut app = App::new(page(&resource), root_element.clone()).unwrap();
+    assert_eq!(root_element.inner_html(), "<p>loading</p>");
+
+    sender.send(Ok("data")).unwrap();
+    Compat::new(Box::pin(sleep(0).map(move |res| {
+        res?;
+        assert!(settled.get());
+        app.set(page(&resource)).unwrap();
+        assert_eq!(root_element.inner_html(), "<p>data</p>");
+        Ok(())
+    })))
+}
+
+#[wasm_bindgen_test(async)]
+fn failed_resources_render_their_error() -> TestFuture {
+    let root_element = root_element();
+    let (resource, sender, settled) = resource();
+    let mut app = App::new(page(&resource), root_element.clone()).unwrap();
+
+    sender.send(Err("timeout")).unwrap();
+    Compat::new(Box::pin(sleep(0).map(move |res| {
+        res?;
+        assert!(settled.get());
+        app.set(page(&resource)).unwrap();
+        assert_eq!(root_element.inner_html(), "<p>failed: timeout</p>");
+        Ok(())
+    })))
+}
+
+#[wasm_bindgen_test(async)]
+fn unmounted_resources_are_canceled() -> TestFuture {
+    let root_element = root_element();
+    let (resource, sender, settled) = resource();
+    let mut app = App::new(Some(page(&resource)), root_element.clone()).unwrap();
+    app.set(None).unwrap();
+    drop(resource);
+    assert_eq!(root_element.inner_html(), "");
+
+    // The result arrives after the resource is gone, so it's dropped
+    // without settling anything.
+    sender.send(Ok("data")).unwrap();
+    Compat::new(Box::pin(sleep(0).map(move |res| {
+        res?;
+        assert!(!settled.get());
+        assert_eq!(root_element.inner_html(), "");
+        drop(app);
+        Ok(())
+    })))
+}
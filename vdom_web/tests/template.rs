@@ -0,0 +1,63 @@
+use vdom::vdom::{
+    dom::DomNode,
+    node::{TagStatic, TextDyn},
+};
+use vdom_web::driver::{read_dom, App, WebDriver};
+use wasm_bindgen::JsCast;
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+type Template = TagStatic<WebDriver, TagStatic<WebDriver, TextDyn<WebDriver>, ()>, ()>;
+
+fn template(text: &'static str) -> Template {
+    TagStatic::new("template", (), TagStatic::new("p", (), TextDyn::new(text)))
+}
+
+fn root_element() -> web_sys::Element {
+    let document = web_sys::window().unwrap().document().unwrap();
+    document.create_element("div").unwrap()
+}
+
+/// The content of the template rendered into `root_element`.
+fn content(root_element: &web_sys::Element) -> web_sys::DocumentFragment {
+    let template = root_element
+        .first_child()
+        .unwrap()
+        .dyn_into::<web_sys::HtmlTemplateElement>()
+        .unwrap();
+    // The children aren't rendered, so the template itself has none.
+    assert_eq!(template.child_nodes().length(), 0);
+    template.content()
+}
+
+#[wasm_bindgen_test]
+fn template_children_are_built_into_its_content() {
+    let root_element = root_element();
+    let mut app = App::new(template("a"), root_element.clone()).unwrap();
+    let content = content(&root_element);
+    assert_eq!(content.child_nodes().length(), 1);
+    assert_eq!(content.text_content(), Some("a".to_string()));
+    assert_eq!(root_element.inner_html(), "<template><p>a</p></template>");
+
+    // Patches reach the children within the content.
+    app.set(template("b")).unwrap();
+    let content = self::content(&root_element);
+    assert_eq!(content.text_content(), Some("b".to_string()));
+}
+
+#[wasm_bindgen_test]
+fn template_children_are_read_from_its_content() {
+    let root_element = root_element();
+    let _app = App::new(template("a"), root_element.clone()).unwrap();
+    let nodes = read_dom(&root_element);
+    match &nodes[..] {
+        [DomNode::Tag(tag)] => assert_eq!(tag.children.len(), 1),
+        nodes => panic!("expected a template, got {} nodes", nodes.len()),
+    }
+
+    let rendered = self::root_element();
+    let _app = App::new(nodes, rendered.clone()).unwrap();
+    assert_eq!(rendered.inner_html(), "<template><p>a</p></template>");
+    assert_eq!(content(&rendered).child_nodes().length(), 1);
+}
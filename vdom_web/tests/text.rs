@@ -0,0 +1,68 @@
+use vdom::vdom::node::TextDyn;
+use vdom_web::driver::{App, WebDriver};
+use wasm_bindgen::JsCast;
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// A `contenteditable` element in the document, holding `text` with the
+/// caret after its first `caret` chars, and the app rendering into it.
+fn editor(text: &str, caret: u32) -> (web_sys::Selection, App<TextDyn<WebDriver>>) {
+    let window = web_sys::window().unwrap();
+    let document = window.document().unwrap();
+    let root_element = document.create_element("div").unwrap();
+    root_element
+        .set_attribute("contenteditable", "true")
+        .unwrap();
+    document
+        .body()
+        .unwrap()
+        .append_child(&root_element)
+        .unwrap();
+    let app = App::new(TextDyn::new(text.to_string()), root_element.clone()).unwrap();
+
+    let text_node = root_element.first_child().unwrap();
+    let selection = window.get_selection().unwrap().unwrap();
+    selection
+        .collapse_with_offset(Some(&text_node), caret)
+        .unwrap();
+    (selection, app)
+}
+
+fn caret(selection: &web_sys::Selection) -> u32 {
+    assert!(selection
+        .anchor_node()
+        .unwrap()
+        .dyn_ref::<web_sys::Text>()
+        .is_some());
+    assert_eq!(selection.anchor_offset(), selection.focus_offset());
+    selection.anchor_offset()
+}
+
+#[wasm_bindgen_test]
+fn caret_stays_put_when_text_after_it_changes() {
+    let (selection, mut app) = editor("hello world", 5);
+    app.set(TextDyn::new("hello there, world".to_string()))
+        .unwrap();
+    assert_eq!(caret(&selection), 5);
+}
+
+#[wasm_bindgen_test]
+fn caret_moves_along_with_text_before_it() {
+    let (selection, mut app) = editor("hello world", 6);
+    app.set(TextDyn::new("oh, hello world".to_string()))
+        .unwrap();
+    assert_eq!(caret(&selection), 10);
+
+    // Counted in UTF-16 code units, as the DOM does.
+    app.set(TextDyn::new("😀 oh, hello world".to_string()))
+        .unwrap();
+    assert_eq!(caret(&selection), 13);
+}
+
+#[wasm_bindgen_test]
+fn caret_keeps_its_offset_into_replaced_text() {
+    let (selection, mut app) = editor("abcdef", 4);
+    app.set(TextDyn::new("aXYZWf".to_string())).unwrap();
+    assert_eq!(caret(&selection), 4);
+}
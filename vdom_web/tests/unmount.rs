@@ -0,0 +1,144 @@
+use futures::{
+    compat::{Compat, Future01CompatExt},
+    Future, FutureExt,
+};
+use js_sys::Promise;
+use std::pin::Pin;
+use vdom::vdom::dom::{DomAttr, DomNode};
+use vdom_web::driver::{
+    finish_unmount, App, WebDriver, BEFORE_UNMOUNT, EXITING_ATTR, EXIT_MS_ATTR,
+};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// What async tests return, a futures 0.1 future.
+type TestFuture = Compat<Pin<Box<dyn Future<Output = Result<(), JsValue>>>>>;
+
+fn root_element() -> web_sys::Element {
+    let document = web_sys::window().unwrap().document().unwrap();
+    document.create_element("div").unwrap()
+}
+
+fn tag(name: &'static str, attrs: Vec<DomAttr<WebDriver>>) -> DomNode<WebDriver> {
+    DomNode::tag(name, attrs, vec![DomNode::text(name)])
+}
+
+fn tag_names(root_element: &web_sys::Element) -> Vec<String> {
+    let children = root_element.children();
+    (0..children.length())
+        .filter_map(|i| children.item(i))
+        .map(|child| child.tag_name().to_lowercase())
+        .collect()
+}
+
+/// Keeps `element` in place when it's removed, until `finish_unmount` is
+/// called or the removal times out.
+fn defer_removal(element: &web_sys::Element) {
+    let defer = Closure::wrap(
+        Box::new(|event: web_sys::Event| event.prevent_default()) as Box<dyn FnMut(_)>
+    );
+    element
+        .add_event_listener_with_callback(BEFORE_UNMOUNT, defer.as_ref().unchecked_ref())
+        .unwrap();
+    defer.forget();
+}
+
+/// Resolves after `ms` milliseconds.
+fn sleep(ms: i32) -> impl Future<Output = Result<JsValue, JsValue>> {
+    let promise = Promise::new(&mut |resolve, _| {
+        web_sys::window()
+            .unwrap()
+            .set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, ms)
+            .unwrap();
+    });
+    JsFuture::from(promise).compat()
+}
+
+#[wasm_bindgen_test]
+fn removal_waits_for_the_listener() {
+    let root_element = root_element();
+    let mut app = App::new(
+        vec![tag("div", vec![]), tag("p", vec![])],
+        root_element.clone(),
+    )
+    .unwrap();
+    let exiting = root_element.first_element_child().unwrap();
+    defer_removal(&exiting);
+
+    app.set(vec![tag("p", vec![])]).unwrap();
+    assert_eq!(tag_names(&root_element), ["div", "p"]);
+    assert!(exiting.has_attribute(EXITING_ATTR));
+    assert!(exiting.has_attribute("inert"));
+
+    // Nodes rendered at its index go after it.
+    app.set(vec![tag("span", vec![]), tag("p", vec![])])
+        .unwrap();
+    assert_eq!(tag_names(&root_element), ["div", "span", "p"]);
+
+    finish_unmount(&exiting);
+    assert_eq!(tag_names(&root_element), ["span", "p"]);
+    assert!(!exiting.has_attribute(EXITING_ATTR));
+}
+
+#[wasm_bindgen_test]
+fn exiting_elements_leave_with_their_parent() {
+    let root_element = root_element();
+    let list = |items| DomNode::tag("ul", vec![], items);
+    let mut app = App::new(
+        vec![list(vec![tag("li", vec![]), tag("li", vec![])])],
+        root_element.clone(),
+    )
+    .unwrap();
+    let exiting = root_element
+        .first_element_child()
+        .unwrap()
+        .last_element_child()
+        .unwrap();
+    defer_removal(&exiting);
+    app.set(vec![list(vec![tag("li", vec![])])]).unwrap();
+    assert!(exiting.has_attribute(EXITING_ATTR));
+
+    // It's no longer kept in place once the list is replaced.
+    app.set(vec![tag("p", vec![])]).unwrap();
+    assert_eq!(root_element.inner_html(), "<p>p</p>");
+    assert!(!exiting.has_attribute(EXITING_ATTR));
+}
+
+#[wasm_bindgen_test]
+fn elements_without_a_listener_are_removed_at_once() {
+    let root_element = root_element();
+    let mut app = App::new(
+        vec![tag("div", vec![]), tag("p", vec![])],
+        root_element.clone(),
+    )
+    .unwrap();
+    app.set(vec![tag("p", vec![])]).unwrap();
+    assert_eq!(root_element.inner_html(), "<p>p</p>");
+}
+
+#[wasm_bindgen_test(async)]
+fn elements_are_removed_after_their_exit_ms() -> TestFuture {
+    let root_element = root_element();
+    let mut app = App::new(
+        vec![
+            tag("div", vec![DomAttr::new(EXIT_MS_ATTR, "20")]),
+            tag("p", vec![]),
+        ],
+        root_element.clone(),
+    )
+    .unwrap();
+    // A listener that never finishes the removal doesn't keep it forever.
+    defer_removal(&root_element.first_element_child().unwrap());
+    app.set(vec![tag("p", vec![])]).unwrap();
+    assert_eq!(tag_names(&root_element), ["div", "p"]);
+
+    Compat::new(Box::pin(sleep(100).map(move |res| {
+        res?;
+        assert_eq!(tag_names(&root_element), ["p"]);
+        drop(app);
+        Ok(())
+    })))
+}
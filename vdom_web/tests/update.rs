@@ -0,0 +1,73 @@
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+use vdom::vdom::node::{Comp, CompCtx, CompNode, TagStatic, TextDyn, TextStatic};
+use vdom_web::driver::{App, WebDriver};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+thread_local! {
+    /// The context of the latest `Counter`, to invalidate it.
+    static COUNTER: RefCell<Option<CompCtx<WebDriver, Counter>>> = RefCell::new(None);
+}
+
+/// Shared state compared by identity, as inputs have to be `Eq`.
+#[derive(Clone)]
+struct Count(Rc<Cell<u32>>);
+
+impl PartialEq for Count {
+    fn eq(&self, other: &Count) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for Count {}
+
+#[derive(Clone, Eq, PartialEq)]
+struct Counter;
+
+impl Comp<WebDriver> for Counter {
+    type Input = Count;
+    type Rendered = TextDyn<WebDriver>;
+
+    fn new(_input: &Count, ctx: CompCtx<WebDriver, Counter>) -> Counter {
+        COUNTER.with(|counter| *counter.borrow_mut() = Some(ctx));
+        Counter
+    }
+
+    fn render(&self, input: &Count) -> TextDyn<WebDriver> {
+        TextDyn::new(input.0.get().to_string())
+    }
+}
+
+#[wasm_bindgen_test]
+fn invalidated_components_update_without_their_owner() {
+    let document = web_sys::window().unwrap().document().unwrap();
+    let root_element = document.create_element("div").unwrap();
+    let count = Count(Rc::new(Cell::new(0)));
+    let owner_renders = Cell::new(0);
+    let view = || {
+        owner_renders.set(owner_renders.get() + 1);
+        TagStatic::<WebDriver, _, ()>::new(
+            "p",
+            (),
+            (
+                TextStatic::new("Count: "),
+                CompNode::<WebDriver, Counter>::new(count.clone()),
+            ),
+        )
+    };
+    let mut app = App::new(view(), root_element.clone()).unwrap();
+
+    count.0.set(1);
+    app.update().unwrap();
+    // Neither the component nor its input changed.
+    assert_eq!(root_element.inner_html(), "<p>Count: 0</p>");
+
+    COUNTER.with(|counter| counter.borrow().as_ref().unwrap().invalidate());
+    app.update().unwrap();
+    assert_eq!(root_element.inner_html(), "<p>Count: 1</p>");
+    assert_eq!(owner_renders.get(), 1);
+}
@@ -0,0 +1,85 @@
+use vdom::vdom::{
+    attr::{AttrListEntry, AttrProp},
+    node::TagStatic,
+    patch::Patch,
+    path::Path,
+};
+use vdom_web::driver::{apply_patches, App, WebDriver};
+use wasm_bindgen::JsCast;
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+type Input = TagStatic<WebDriver, (), AttrListEntry<AttrProp<WebDriver>>>;
+
+fn input(value: &'static str) -> Input {
+    TagStatic::new("input", AttrListEntry(AttrProp::new("value", value)), ())
+}
+
+fn root_element(html: &str) -> web_sys::Element {
+    let document = web_sys::window().unwrap().document().unwrap();
+    let root_element = document.create_element("div").unwrap();
+    root_element.set_inner_html(html);
+    document
+        .body()
+        .unwrap()
+        .append_child(&root_element)
+        .unwrap();
+    root_element
+}
+
+fn first_input(root_element: &web_sys::Element) -> web_sys::HtmlInputElement {
+    root_element.first_child().unwrap().dyn_into().unwrap()
+}
+
+/// Types `text` at the end of `input`, then moves the caret to `caret`, as
+/// a user editing in the middle of the value would.
+fn type_text(input: &web_sys::HtmlInputElement, text: &str, caret: u32) {
+    input.set_value(&(input.value() + text));
+    input.set_selection_range(caret, caret).unwrap();
+}
+
+#[wasm_bindgen_test]
+fn rendering_the_typed_value_keeps_the_caret() {
+    let root_element = root_element("");
+    let mut app = App::new(input("ab"), root_element.clone()).unwrap();
+    let elem = first_input(&root_element);
+
+    // The keystroke lands before the state it updates is rendered.
+    type_text(&elem, "c", 1);
+    app.set(input("abc")).unwrap();
+    assert_eq!(elem.value(), "abc");
+    // Writing the value would have moved the caret to the end, where the
+    // next keystroke would go.
+    assert_eq!(elem.selection_start().unwrap(), Some(1));
+
+    app.set(input("")).unwrap();
+    assert_eq!(elem.value(), "");
+    root_element.remove();
+}
+
+#[wasm_bindgen_test]
+fn patching_the_typed_value_keeps_the_caret() {
+    let root_element = root_element("<input value=\"ab\">");
+    let elem = first_input(&root_element);
+    let set_value = |value: &str| {
+        vec![Patch::SetAttr {
+            path: Path::from(vec![0]),
+            name: "value".to_string(),
+            value: value.to_string().into(),
+        }]
+    };
+
+    // The keystroke lands after the patch for it was made on the server, but
+    // before it's applied.
+    let patches = set_value("abc");
+    type_text(&elem, "c", 1);
+    apply_patches(&root_element, patches).unwrap();
+    assert_eq!(elem.value(), "abc");
+    assert_eq!(elem.selection_start().unwrap(), Some(1));
+    assert_eq!(elem.get_attribute("value"), Some("abc".to_string()));
+
+    apply_patches(&root_element, set_value("x")).unwrap();
+    assert_eq!(elem.value(), "x");
+    root_element.remove();
+}